@@ -1,7 +1,7 @@
 module Concordium.Crypto.BlsSignature
   (PublicKey, SecretKey(..), Signature, Proof,
   generateSecretKey, derivePublicKey, sign, verify, aggregate, aggregateMany, verifyAggregate, emptySignature,
-  freeSecretKey, proveKnowledgeOfSK, checkProofOfKnowledgeSK)
+  freeSecretKey, proveKnowledgeOfSK, checkProofOfKnowledgeSK, setThreadCount)
   where
 
 import Concordium.Crypto.FFIHelpers
@@ -68,6 +68,7 @@ foreign import ccall unsafe "bls_aggregate" aggregateBls :: Ptr Signature -> Ptr
 foreign import ccall safe "bls_verify_aggregate" verifyBlsAggregate :: Ptr Word8 -> CSize -> Ptr (Ptr PublicKey) -> CSize -> Ptr Signature -> IO Word8
 foreign import ccall safe "bls_prove" proveBls :: Ptr Word8 -> CSize -> Ptr SecretKey -> IO (Ptr Proof)
 foreign import ccall safe "bls_check_proof" checkProofBls :: Ptr Word8 -> CSize -> Ptr Proof -> Ptr PublicKey -> IO Word8
+foreign import ccall safe "bls_set_thread_count" setThreadCountBls :: CSize -> IO Word8
 
 withSecretKey :: SecretKey -> (Ptr SecretKey -> IO b) -> IO b
 withSecretKey (SecretKey fp) = withForeignPtr fp
@@ -295,6 +296,12 @@ checkProofOfKnowledgeSK context proof pk = unsafePerformIO $ do
     withProof proof $ \proof' ->
       (== 1) <$> checkProofBls (castPtr c) (fromIntegral clen) proof' pk'
 
+-- |Set the number of threads used when verifying aggregate signatures. If the
+-- argument is 0 the default of one thread per core is used. Returns 'False' if
+-- the thread pool could not be created, in which case the setting is unchanged.
+setThreadCount :: Word -> IO Bool
+setThreadCount n = (== 1) <$> setThreadCountBls (fromIntegral n)
+
 instance Semigroup Signature where
     (<>) = aggregate
 
@@ -9,6 +9,7 @@ use id::{
     identity_provider::{
         create_initial_cdi, sign_identity_object, validate_request as ip_validate_request,
     },
+    random_oracle::ChallengeContext,
     types::*,
 };
 use log::{error, info, warn};
@@ -1047,7 +1048,7 @@ fn validate_worker(
         ars_infos:      &server_config.ars.anonymity_revokers,
         global_context: &server_config.global,
     };
-    match ip_validate_request(request, context) {
+    match ip_validate_request(&ChallengeContext::Legacy, request, context) {
         Ok(()) => {
             info!("Request is valid.");
             Ok(input)
@@ -5,6 +5,7 @@ use id::{
     identity_provider::{
         create_initial_cdi, sign_identity_object, validate_request as ip_validate_request,
     },
+    random_oracle::ChallengeContext,
     types::*,
 };
 use pairing::bls12_381::{Bls12, G1};
@@ -43,11 +44,7 @@ fn parse_exact_versioned_global_context(
 ) -> Result<GlobalContext<ExampleCurve>, String> {
     let v: Versioned<GlobalContext<ExampleCurve>> =
         from_str(global_context_str).map_err(show_err)?;
-    if v.version == VERSION_0 {
-        Ok(v.value)
-    } else {
-        Err(show_err("Incorrect Global context version."))
-    }
+    GlobalContext::from_versioned(v).map_err(show_err)
 }
 
 pub fn validate_request(
@@ -104,7 +101,7 @@ pub fn validate_request(
         &request.pub_info_for_ip.reg_id
     )))
     .expect("JSON serialization of accounts cannot fail.");
-    let vf = ip_validate_request(&request, context);
+    let vf = ip_validate_request(&ChallengeContext::Legacy, &request, context);
     if let Ok(()) = vf {
         (true, addr)
     } else {
@@ -2,18 +2,95 @@ package com.concordium.mobile_wallet_lib
 
 external fun create_id_request_and_private_data(input: String) : ReturnValue
 external fun create_credential(input: String) : ReturnValue
+external fun prepare_credential(input: String) : ReturnValue
+external fun finalize_credential(input: String) : ReturnValue
+external fun get_credential_id(input: String) : ReturnValue
 external fun generate_accounts(input: String) : ReturnValue
+external fun derive_keys_from_seed(input: String) : ReturnValue
+external fun generate_mnemonic(input: String) : ReturnValue
+external fun validate_mnemonic(input: String) : ReturnValue
+external fun get_input_schema(input: String) : ReturnValue
+external fun get_output_schema(input: String) : ReturnValue
+external fun estimate_transaction_energy(input: String) : ReturnValue
+external fun prove_attribute_in_range(input: String) : ReturnValue
+external fun verify_attribute_in_range(input: String) : ReturnValue
+external fun prove_statement(input: String) : ReturnValue
+external fun create_account_ownership_proof(input: String) : ReturnValue
+external fun decode_transaction(input: String) : ReturnValue
+external fun parse_transaction(input: String) : ReturnValue
 external fun create_transfer(input: String) : ReturnValue
+external fun create_transfer_with_schedule(input: String) : ReturnValue
+external fun create_update_keys(input: String) : ReturnValue
+external fun create_unsigned_transfer(input: String) : ReturnValue
+external fun create_unsigned_encrypted_transfer(input: String) : ReturnValue
+external fun attach_signatures(input: String) : ReturnValue
+external fun partially_sign_transaction(input: String) : ReturnValue
+external fun merge_signatures(input: String) : ReturnValue
+external fun check_signature_thresholds(input: String) : ReturnValue
+external fun generate_baker_keys(input: String) : ReturnValue
+external fun create_configure_baker(input: String) : ReturnValue
+external fun create_configure_delegation(input: String) : ReturnValue
 external fun create_encrypted_transfer(input: String) : ReturnValue
 external fun create_pub_to_sec_transfer(input: String) : ReturnValue
 external fun create_sec_to_pub_transfer(input: String) : ReturnValue
 external fun combine_encrypted_amounts(input1: String, input2: String) : ReturnValue
+external fun combine_encrypted_amounts_list(input: String) : ReturnValue
+external fun update_encrypted_balance(input: String) : ReturnValue
+external fun create_encrypted_transfer_from_balance(input: String) : ReturnValue
+external fun create_sec_to_pub_transfer_from_balance(input: String) : ReturnValue
 external fun decrypt_encrypted_amount(input: String) : ReturnValue
+external fun set_decryption_table(input: String) : ReturnValue
 external fun check_account_address(input: String) : Boolean
+external fun encode_payment_request(input: String) : ReturnValue
+external fun decode_payment_request(input: String) : ReturnValue
+external fun encode_key_export(input: String) : ReturnValue
+external fun decode_key_export(input: String) : ReturnValue
+external fun call_v2(function: String, input: String) : String?
+external fun call_v2_cancellable(function: String, input: String, handle: Long) : String?
+external fun new_operation_handle() : Long
+external fun cancel_operation(handle: Long)
+external fun free_operation_handle(handle: Long)
+external fun create_credential_cancellable(input: String, handle: Long) : ReturnValue
+external fun create_encrypted_transfer_cancellable(input: String, handle: Long) : ReturnValue
+external fun create_sec_to_pub_transfer_cancellable(input: String, handle: Long) : ReturnValue
+external fun start_create_credential(input: String) : Long
+external fun start_create_id_request_and_private_data(input: String) : Long
+external fun poll_job(jobId: Long) : Int
+external fun cancel_job(jobId: Long)
+external fun get_job_result(jobId: Long) : ReturnValue
+external fun generate_accounts_cursor(input: String) : Long
+external fun response_cursor_next(cursor: Long) : ReturnValue?
+external fun free_response_cursor(cursor: Long)
+external fun memory_current_usage() : Long
+external fun memory_peak_usage() : Long
+external fun reset_memory_peak_usage()
+external fun set_thread_count(n: Int) : Boolean
 external fun link_check(input: String) : String
+external fun get_abi_version() : Int
+external fun get_library_version() : String
+external fun crypto_self_test() : String
+external fun wallet_context_create(input: String) : Long
+external fun wallet_context_free(context: Long)
+external fun create_id_request_and_private_data_with_context(input: String, context: Long) : ReturnValue
+external fun create_credential_with_context(input: String, context: Long) : ReturnValue
+external fun generate_accounts_with_context(input: String, context: Long) : ReturnValue
+external fun get_credential_id_with_context(input: String, context: Long) : ReturnValue
+external fun create_encrypted_transfer_with_context(input: String, context: Long) : ReturnValue
+external fun create_pub_to_sec_transfer_with_context(input: String, context: Long) : ReturnValue
+external fun create_sec_to_pub_transfer_with_context(input: String, context: Long) : ReturnValue
+external fun decrypt_encrypted_amount_with_context(input: String, context: Long) : ReturnValue
+
+/** Version of the C interface these bindings are written against. */
+const val ABI_VERSION = 1
 
 fun loadWalletLib() {
     System.loadLibrary("mobile_wallet")
+    val libraryAbiVersion = get_abi_version()
+    if (libraryAbiVersion != ABI_VERSION) {
+        throw UnsatisfiedLinkError(
+            "mobile_wallet has ABI version $libraryAbiVersion, but version $ABI_VERSION is required."
+        )
+    }
 }
 
 data class ReturnValue (val result : Int, val output : String)
@@ -1,4 +1,5 @@
-use crypto_common::to_bytes;
+use crypto_common::serial_to_writer;
+use std::io::{BufWriter, Write};
 
 fn main() {
     let out_dir = std::env::var_os("OUT_DIR").unwrap();
@@ -9,5 +10,11 @@ fn main() {
     ));
     let m = 1 << 16;
     let table = elgamal::BabyStepGiantStep::new(global.encryption_in_exponent_generator(), m);
-    std::fs::write(&dest_path, &to_bytes(&table)).expect("Could not write table to file.");
+    // Write the table directly to the file instead of collecting it in memory
+    // first.
+    let mut out = BufWriter::new(
+        std::fs::File::create(&dest_path).expect("Could not create the table file."),
+    );
+    serial_to_writer(&table, &mut out).expect("Could not write table to file.");
+    out.flush().expect("Could not write table to file.");
 }
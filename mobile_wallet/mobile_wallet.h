@@ -3,6 +3,59 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Version of the C interface this header describes. It must match the value
+ * returned by `get_abi_version`.
+ */
+#define MOBILE_WALLET_ABI_VERSION 1
+
+/**
+ * The prefix of the names of the functions of this version of the C
+ * interface.
+ */
+#define MOBILE_WALLET_SYMBOL_PREFIX "ccd_wallet_v1_"
+
+/**
+ * A handle that can be used to cancel a long-running operation.
+ */
+typedef struct OperationHandle OperationHandle;
+
+/**
+ * An opaque handle to a response that is produced incrementally.
+ */
+typedef struct ResponseCursor ResponseCursor;
+
+/**
+ * An opaque handle to the parameters of a wallet context. If the input of
+ * `wallet_context_create` is invalid the handle holds the error instead, and
+ * every function called with it fails with that error.
+ */
+typedef struct WalletContext WalletContext;
+
+/**
+ * Type of an externally supplied SHA-256 implementation. The function is
+ * given a pointer to the data and its length, and must write exactly 32 bytes
+ * of output to the `out` pointer.
+ */
+typedef void (*ExternalHashFunction)(const uint8_t *data, uintptr_t len, uint8_t *out);
+
+/**
+ * Type of the callback invoked when an allocation fails. The argument is the
+ * size of the allocation that failed, in bytes.
+ */
+typedef void (*AllocationFailureCallback)(uintptr_t size);
+
+/**
+ * Type of the callback that signs a message with a key held by the platform.
+ * The arguments are the NUL-terminated handle of the key, the message, the
+ * length of the message, and a buffer of 64 bytes for the ed25519 signature.
+ * The callback returns 1 if it wrote the signature and 0 otherwise.
+ */
+typedef uint8_t (*SignCallback)(const char *key_handle,
+                                const uint8_t *message,
+                                uintptr_t message_len,
+                                uint8_t *signature);
+
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return whether this is
  * a correct format for a concordium address.
@@ -28,6 +81,67 @@ uint8_t check_account_address(const char *input_ptr);
  */
 char *combine_encrypted_amounts(const char *input_ptr_1, const char *input_ptr_2, uint8_t *success);
 
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * The input must be a JSON list of encrypted amounts with consecutive indices.
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *combine_encrypted_amounts_list(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *update_encrypted_balance(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_encrypted_transfer_from_balance(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_sec_to_pub_transfer_from_balance(const char *input_ptr, uint8_t *success);
+
 /**
  * # Safety
  * The input pointer must point to a null-terminated buffer, otherwise this
@@ -36,11 +150,19 @@ char *combine_encrypted_amounts(const char *input_ptr_1, const char *input_ptr_2
 char *generate_accounts(const char *input_ptr, uint8_t *success);
 
 /**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
  * # Safety
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_credential(const char *input_ptr, uint8_t *success);
+char *get_credential_id(const char *input_ptr, uint8_t *success);
 
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
@@ -55,14 +177,22 @@ char *create_credential(const char *input_ptr, uint8_t *success);
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_encrypted_transfer(const char *input_ptr, uint8_t *success);
+char *derive_keys_from_seed(const char *input_ptr, uint8_t *success);
 
 /**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
  * # Safety
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_id_request_and_private_data(const char *input_ptr, uint8_t *success);
+char *prove_attribute_in_range(const char *input_ptr, uint8_t *success);
 
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
@@ -77,7 +207,7 @@ char *create_id_request_and_private_data(const char *input_ptr, uint8_t *success
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_pub_to_sec_transfer(const char *input_ptr, uint8_t *success);
+char *verify_attribute_in_range(const char *input_ptr, uint8_t *success);
 
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
@@ -92,7 +222,7 @@ char *create_pub_to_sec_transfer(const char *input_ptr, uint8_t *success);
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_sec_to_pub_transfer(const char *input_ptr, uint8_t *success);
+char *prove_statement(const char *input_ptr, uint8_t *success);
 
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
@@ -107,7 +237,7 @@ char *create_sec_to_pub_transfer(const char *input_ptr, uint8_t *success);
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-char *create_transfer(const char *input_ptr, uint8_t *success);
+char *create_account_ownership_proof(const char *input_ptr, uint8_t *success);
 
 /**
  * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
@@ -122,11 +252,1564 @@ char *create_transfer(const char *input_ptr, uint8_t *success);
  * The input pointer must point to a null-terminated buffer, otherwise this
  * function will fail in unspecified ways.
  */
-uint64_t decrypt_encrypted_amount(const char *input_ptr, uint8_t *success);
+char *decode_transaction(const char *input_ptr, uint8_t *success);
 
 /**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
  * # Safety
- * This function is unsafe in the sense that if the argument pointer was not
- * Constructed via CString::into_raw its behaviour is undefined.
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
  */
-void free_response_string(char *ptr);
+char *generate_baker_keys(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_configure_baker(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_configure_delegation(const char *input_ptr, uint8_t *success);
+
+/**
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_credential`, but the operation can be cancelled by calling
+ * `cancel_operation` with the given handle, in which case the function
+ * fails. The handle is obtained from `new_operation_handle` and is not
+ * freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the handle
+ * must have been obtained from `new_operation_handle` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_credential_cancellable(const char *input_ptr, const OperationHandle *handle, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *prepare_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *finalize_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_encrypted_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer`, but the operation can be cancelled by calling
+ * `cancel_operation` with the given handle, in which case the function
+ * fails. The handle is obtained from `new_operation_handle` and is not
+ * freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the handle
+ * must have been obtained from `new_operation_handle` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_encrypted_transfer_cancellable(const char *input_ptr, const OperationHandle *handle, uint8_t *success);
+
+/**
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_id_request_and_private_data(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_pub_to_sec_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_sec_to_pub_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer`, but the operation can be cancelled by calling
+ * `cancel_operation` with the given handle, in which case the function
+ * fails. The handle is obtained from `new_operation_handle` and is not
+ * freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the handle
+ * must have been obtained from `new_operation_handle` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_sec_to_pub_transfer_cancellable(const char *input_ptr, const OperationHandle *handle, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_transfer_with_schedule(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_update_keys(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_unsigned_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *create_unsigned_encrypted_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *attach_signatures(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *partially_sign_transaction(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *merge_signatures(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *check_signature_thresholds(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *encode_payment_request(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *decode_payment_request(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *encode_key_export(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *decode_key_export(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *generate_mnemonic(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *validate_mnemonic(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string with the name of a library
+ * function and return the JSON schema of its input as a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure, e.g., if
+ * the library is built without the `schema` feature, the function returns
+ * an error message as the response, and sets the 'success' flag to 0.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *get_input_schema(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string with the name of a library
+ * function and return the JSON schema of its output as a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure, e.g., if
+ * the library is built without the `schema` feature, the function returns
+ * an error message as the response, and sets the 'success' flag to 0.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *get_output_schema(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string containing a JSON
+ * description of an account transaction, and return the energy of the
+ * transaction as a NUL-terminated UTF8-encoded string. The returned string
+ * must be freed by the caller by calling the function
+ * 'free_response_string'. In case of failure the function returns an
+ * error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and
+ * output.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *estimate_transaction_energy(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string with a hex encoded account
+ * transaction, as returned by the functions that create transactions, and
+ * return a JSON description of it for displaying to the user as a
+ * NUL-terminated UTF8-encoded string. The returned string must be freed by
+ * the caller by calling the function 'free_response_string'. In case of
+ * failure the function returns an error message as the response, and sets
+ * the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and
+ * output.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *parse_transaction(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_id_request_and_private_data`, but use the parameters of
+ * the wallet context instead of the corresponding fields of the input. The
+ * context is obtained from `wallet_context_create` and is not freed by this
+ * function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_id_request_and_private_data_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `create_credential`, but use the parameters of the wallet context
+ * instead of the corresponding fields of the input. The context is obtained
+ * from `wallet_context_create` and is not freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_credential_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `generate_accounts`, but use the global context of the wallet
+ * context instead of the corresponding fields of the input. The context is
+ * obtained from `wallet_context_create` and is not freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *generate_accounts_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `get_credential_id`, but use the global context of the wallet
+ * context instead of the corresponding fields of the input. The context is
+ * obtained from `wallet_context_create` and is not freed by this function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *get_credential_id_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer`, but use the global context of the
+ * wallet context instead of the corresponding fields of the input. The
+ * context is obtained from `wallet_context_create` and is not freed by this
+ * function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_encrypted_transfer_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `create_pub_to_sec_transfer`, but use the global context of the
+ * wallet context instead of the corresponding fields of the input. The
+ * context is obtained from `wallet_context_create` and is not freed by this
+ * function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_pub_to_sec_transfer_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer`, but use the global context of the
+ * wallet context instead of the corresponding fields of the input. The
+ * context is obtained from `wallet_context_create` and is not freed by this
+ * function.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+char *create_sec_to_pub_transfer_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Same as `decrypt_encrypted_amount`, but use the table of the wallet context,
+ * which avoids parsing the table on every call. If the context is NULL or
+ * invalid the `success` flag is set to `0`.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, and the context
+ * must have been obtained from `wallet_context_create` and not yet freed,
+ * otherwise this function will fail in unspecified ways.
+ */
+uint64_t decrypt_encrypted_amount_with_context(const char *input_ptr, const WalletContext *wallet_context, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+uint64_t decrypt_encrypted_amount(const char *input_ptr, uint8_t *success);
+
+/**
+ * Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+ * UTF8-encoded string. The returned string must be freed by the caller by
+ * calling the function 'free_response_string'. In case of failure the function
+ * returns an error message as the response, and sets the 'success' flag to 0.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+char *set_decryption_table(const char *input_ptr, uint8_t *success);
+
+/**
+ * # Safety
+ * This function is unsafe in the sense that if the argument pointer was not
+ * Constructed via CString::into_raw its behaviour is undefined.
+ */
+void free_response_string(char *ptr);
+
+/**
+ * Same as `generate_accounts`, but return a cursor from which the accounts
+ * can be fetched one by one via `response_cursor_next`. The accounts are
+ * generated as they are fetched. The returned cursor must be freed by the
+ * caller by calling `free_response_cursor`.
+ *
+ * If the input cannot be parsed the first call to `response_cursor_next`
+ * returns the error. A NULL pointer is returned only if the input is a NULL
+ * pointer or not a valid UTF8 string.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+ResponseCursor *generate_accounts_cursor(const char *input_ptr);
+
+/**
+ * Fetch the next item from the cursor. The returned string must be freed by
+ * the caller by calling the function 'free_response_string'. When there are
+ * no more items a NULL pointer is returned and the 'success' flag is set to
+ * 1. In case of failure the function returns an error message as the
+ * response, and sets the 'success' flag to 0.
+ *
+ * # Safety
+ * The cursor must have been obtained from one of the functions returning a
+ * cursor, and must not have been freed.
+ */
+char *response_cursor_next(ResponseCursor *cursor, uint8_t *success);
+
+/**
+ * # Safety
+ * This function is unsafe in the sense that if the argument pointer was not
+ * obtained from one of the functions returning a cursor its behaviour is
+ * undefined.
+ */
+void free_response_cursor(ResponseCursor *cursor);
+
+/**
+ * Register a SHA-256 implementation to be used for hashing transactions
+ * instead of the builtin one. Passing a NULL pointer restores the default.
+ *
+ * # Safety
+ * The supplied function must be safe to call from any thread, and it must
+ * write exactly 32 bytes to its output argument.
+ */
+void set_transaction_hash_function(ExternalHashFunction f);
+
+/**
+ * Register a callback that is invoked when the library fails to allocate
+ * memory. Passing a NULL pointer removes the callback.
+ *
+ * Only the allocator of the `memory-accounting` feature invokes the
+ * callback. The return value is 1 if the callback is registered, and 0 if
+ * the library was built without the feature, in which case nothing is
+ * registered.
+ *
+ * # Safety
+ * The callback must be safe to call from any thread, and it must not
+ * allocate memory using this library.
+ */
+uint8_t set_allocation_failure_callback(AllocationFailureCallback cb);
+
+/**
+ * Register the callback used to sign with key handles. Passing a NULL pointer
+ * removes the callback.
+ *
+ * # Safety
+ * The callback must be safe to call from any thread that calls into the
+ * library, and it must write exactly 64 bytes to the signature buffer when it
+ * returns 1.
+ */
+void set_sign_callback(SignCallback cb);
+
+/**
+ * Return the version of the C interface of the library.
+ */
+uint32_t get_abi_version(void);
+
+/**
+ * Return a NUL-terminated UTF8-encoded JSON object with the version of the
+ * library, the version of the C interface, and the features the library was
+ * built with. The returned string must be freed by the caller by calling the
+ * function 'free_response_string'.
+ */
+char *get_library_version(void);
+
+/**
+ * Run the self-test of the cryptographic primitives, and return a
+ * NUL-terminated UTF8-encoded JSON object with the result. The returned
+ * string must be freed by the caller by calling the function
+ * 'free_response_string'.
+ */
+char *crypto_self_test(void);
+
+/**
+ * Return the amount of memory, in bytes, currently allocated by the library.
+ * This is always 0 if the library was built without the `memory-accounting`
+ * feature.
+ */
+uint64_t memory_current_usage(void);
+
+/**
+ * Return the peak amount of memory, in bytes, allocated by the library since
+ * the last call to `reset_memory_peak_usage`. This is always 0 if the library
+ * was built without the `memory-accounting` feature.
+ */
+uint64_t memory_peak_usage(void);
+
+/**
+ * Reset the peak memory usage to the current usage. This is typically called
+ * before an operation whose memory usage is to be measured.
+ */
+void reset_memory_peak_usage(void);
+
+/**
+ * Set the number of threads used for parallel computations to `n`. If `n` is
+ * 0 the default of one thread per core is restored. Computations that are
+ * already running are not affected.
+ *
+ * Returns 1 if the number of threads was set, and 0 if the threads could not
+ * be created, in which case the previous setting is retained.
+ */
+uint8_t set_thread_count(uint32_t n);
+
+/**
+ * Create a new handle for a cancellable operation. The returned handle must
+ * be freed by the caller by calling `free_operation_handle`.
+ */
+OperationHandle *new_operation_handle(void);
+
+/**
+ * Request cancellation of the operation using the given handle. The operation
+ * will return an error at the next point where it checks for cancellation. It
+ * is safe to call this function from a different thread than the one running
+ * the operation, and to call it multiple times. If the handle is NULL this
+ * function does nothing.
+ *
+ * # Safety
+ * The handle must have been obtained from `new_operation_handle` and not yet
+ * freed.
+ */
+void cancel_operation(const OperationHandle *handle);
+
+/**
+ * # Safety
+ * This function is unsafe in the sense that if the argument pointer was not
+ * obtained from `new_operation_handle` its behaviour is undefined. The handle
+ * must not be freed while an operation using it is running.
+ */
+void free_operation_handle(OperationHandle *handle);
+
+/**
+ * Create a wallet context from a NUL-terminated UTF8-string with a JSON
+ * object with the field `global`, and optionally the fields `ipInfo` and
+ * `arsInfos`. The returned context must be freed by the caller by calling
+ * `wallet_context_free`.
+ *
+ * If the input cannot be parsed every function called with the context
+ * fails with the error. A NULL pointer is returned only if the input is a
+ * NULL pointer or not a valid UTF8 string.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+WalletContext *wallet_context_create(const char *input_ptr);
+
+/**
+ * # Safety
+ * This function is unsafe in the sense that if the argument pointer was not
+ * obtained from `wallet_context_create` its behaviour is undefined. The
+ * context must not be freed while a function using it is running.
+ */
+void wallet_context_free(WalletContext *ctx);
+
+/**
+ * Take the name of a library function and a pointer to its input, both
+ * NUL-terminated UTF8-strings, and return a NUL-terminated UTF8-encoded string
+ * with the response of the function in the v2 format, i.e., a JSON object with
+ * fields 'ok', 'value' and 'warnings'. The returned string must be freed by
+ * the caller by calling the function 'free_response_string'.
+ *
+ * See rust-bins/wallet-notes/README.md for the description of input and output
+ * formats.
+ *
+ * # Safety
+ * The pointers must point to null-terminated buffers, otherwise this function
+ * will fail in unspecified ways.
+ */
+char *call_v2(const char *function_ptr, const char *input_ptr);
+
+/**
+ * Same as `call_v2`, but for the functions that have a cancellable variant.
+ * The handle is obtained from `new_operation_handle`.
+ *
+ * # Safety
+ * The string pointers must point to null-terminated buffers, and the handle
+ * must have been obtained from `new_operation_handle` and not yet freed.
+ */
+char *call_v2_cancellable(const char *function_ptr, const char *input_ptr,
+                          const OperationHandle *handle);
+
+/**
+ * Start creating a credential as `create_credential` in the background, and
+ * return the identifier of the job. If the input is not a NUL-terminated
+ * UTF8-string the function returns 0 and sets the 'success' flag to 0.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+uint64_t start_create_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Start generating an identity request as
+ * `create_id_request_and_private_data` in the background, and return the
+ * identifier of the job. If the input is not a NUL-terminated UTF8-string the
+ * function returns 0 and sets the 'success' flag to 0.
+ *
+ * # Safety
+ * The input pointer must point to a null-terminated buffer, otherwise this
+ * function will fail in unspecified ways.
+ */
+uint64_t start_create_id_request_and_private_data(const char *input_ptr, uint8_t *success);
+
+/**
+ * Return the status of the job: 0 if there is no job with the identifier, 1
+ * if it is queued, 2 if it is running, and 3 if it is finished (see
+ * [JobStatus]).
+ */
+uint8_t poll_job(uint64_t job_id);
+
+/**
+ * Cancel the job, and release it. Its result is discarded, and the
+ * identifier is no longer valid afterwards. If there is no job with the
+ * identifier this function does nothing.
+ */
+void cancel_job(uint64_t job_id);
+
+/**
+ * Return the response of a finished job as a NUL-terminated UTF8-encoded
+ * string, and release the job. The response is the same as that of the
+ * function the job was started for. The returned string must be freed by the
+ * caller by calling the function 'free_response_string'. In case of failure,
+ * including if the job is not finished, the function returns an error
+ * message as the response, and sets the 'success' flag to 0. A job that is
+ * not finished is not released.
+ *
+ * # Safety
+ * The 'success' pointer must be valid.
+ */
+char *get_job_result(uint64_t job_id, uint8_t *success);
+
+/**
+ * Same as `check_account_address`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `check_account_address`.
+ */
+uint8_t ccd_wallet_v1_check_account_address(const char *input_ptr);
+
+/**
+ * Same as `combine_encrypted_amounts`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `combine_encrypted_amounts`.
+ */
+char *ccd_wallet_v1_combine_encrypted_amounts(const char *input_ptr_1,
+                                              const char *input_ptr_2,
+                                              uint8_t *success);
+
+/**
+ * Same as `combine_encrypted_amounts_list`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `combine_encrypted_amounts_list`.
+ */
+char *ccd_wallet_v1_combine_encrypted_amounts_list(const char *input_ptr,
+                                                   uint8_t *success);
+
+/**
+ * Same as `update_encrypted_balance`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `update_encrypted_balance`.
+ */
+char *ccd_wallet_v1_update_encrypted_balance(const char *input_ptr,
+                                             uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer_from_balance`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_encrypted_transfer_from_balance`.
+ */
+char *ccd_wallet_v1_create_encrypted_transfer_from_balance(const char *input_ptr,
+                                                           uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer_from_balance`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_sec_to_pub_transfer_from_balance`.
+ */
+char *ccd_wallet_v1_create_sec_to_pub_transfer_from_balance(const char *input_ptr,
+                                                            uint8_t *success);
+
+/**
+ * Same as `generate_accounts`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `generate_accounts`.
+ */
+char *ccd_wallet_v1_generate_accounts(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `get_credential_id`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_credential_id`.
+ */
+char *ccd_wallet_v1_get_credential_id(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `derive_keys_from_seed`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `derive_keys_from_seed`.
+ */
+char *ccd_wallet_v1_derive_keys_from_seed(const char *input_ptr,
+                                          uint8_t *success);
+
+/**
+ * Same as `prove_attribute_in_range`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `prove_attribute_in_range`.
+ */
+char *ccd_wallet_v1_prove_attribute_in_range(const char *input_ptr,
+                                             uint8_t *success);
+
+/**
+ * Same as `verify_attribute_in_range`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `verify_attribute_in_range`.
+ */
+char *ccd_wallet_v1_verify_attribute_in_range(const char *input_ptr,
+                                              uint8_t *success);
+
+/**
+ * Same as `prove_statement`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `prove_statement`.
+ */
+char *ccd_wallet_v1_prove_statement(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_account_ownership_proof`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_account_ownership_proof`.
+ */
+char *ccd_wallet_v1_create_account_ownership_proof(const char *input_ptr,
+                                                   uint8_t *success);
+
+/**
+ * Same as `decode_transaction`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `decode_transaction`.
+ */
+char *ccd_wallet_v1_decode_transaction(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `generate_baker_keys`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `generate_baker_keys`.
+ */
+char *ccd_wallet_v1_generate_baker_keys(const char *input_ptr,
+                                        uint8_t *success);
+
+/**
+ * Same as `create_configure_baker`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_configure_baker`.
+ */
+char *ccd_wallet_v1_create_configure_baker(const char *input_ptr,
+                                           uint8_t *success);
+
+/**
+ * Same as `create_configure_delegation`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_configure_delegation`.
+ */
+char *ccd_wallet_v1_create_configure_delegation(const char *input_ptr,
+                                                uint8_t *success);
+
+/**
+ * Same as `create_credential`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_credential`.
+ */
+char *ccd_wallet_v1_create_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_credential_cancellable`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_credential_cancellable`.
+ */
+char *ccd_wallet_v1_create_credential_cancellable(const char *input_ptr,
+                                                  const OperationHandle *handle,
+                                                  uint8_t *success);
+
+/**
+ * Same as `prepare_credential`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `prepare_credential`.
+ */
+char *ccd_wallet_v1_prepare_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `finalize_credential`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `finalize_credential`.
+ */
+char *ccd_wallet_v1_finalize_credential(const char *input_ptr,
+                                        uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_encrypted_transfer`.
+ */
+char *ccd_wallet_v1_create_encrypted_transfer(const char *input_ptr,
+                                              uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer_cancellable`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_encrypted_transfer_cancellable`.
+ */
+char *ccd_wallet_v1_create_encrypted_transfer_cancellable(const char *input_ptr,
+                                                          const OperationHandle *handle,
+                                                          uint8_t *success);
+
+/**
+ * Same as `create_id_request_and_private_data`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_id_request_and_private_data`.
+ */
+char *ccd_wallet_v1_create_id_request_and_private_data(const char *input_ptr,
+                                                       uint8_t *success);
+
+/**
+ * Same as `create_pub_to_sec_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_pub_to_sec_transfer`.
+ */
+char *ccd_wallet_v1_create_pub_to_sec_transfer(const char *input_ptr,
+                                               uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_sec_to_pub_transfer`.
+ */
+char *ccd_wallet_v1_create_sec_to_pub_transfer(const char *input_ptr,
+                                               uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer_cancellable`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_sec_to_pub_transfer_cancellable`.
+ */
+char *ccd_wallet_v1_create_sec_to_pub_transfer_cancellable(const char *input_ptr,
+                                                           const OperationHandle *handle,
+                                                           uint8_t *success);
+
+/**
+ * Same as `create_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_transfer`.
+ */
+char *ccd_wallet_v1_create_transfer(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_transfer_with_schedule`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_transfer_with_schedule`.
+ */
+char *ccd_wallet_v1_create_transfer_with_schedule(const char *input_ptr,
+                                                  uint8_t *success);
+
+/**
+ * Same as `create_update_keys`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_update_keys`.
+ */
+char *ccd_wallet_v1_create_update_keys(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_unsigned_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_unsigned_transfer`.
+ */
+char *ccd_wallet_v1_create_unsigned_transfer(const char *input_ptr,
+                                             uint8_t *success);
+
+/**
+ * Same as `create_unsigned_encrypted_transfer`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_unsigned_encrypted_transfer`.
+ */
+char *ccd_wallet_v1_create_unsigned_encrypted_transfer(const char *input_ptr,
+                                                       uint8_t *success);
+
+/**
+ * Same as `attach_signatures`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `attach_signatures`.
+ */
+char *ccd_wallet_v1_attach_signatures(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `partially_sign_transaction`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `partially_sign_transaction`.
+ */
+char *ccd_wallet_v1_partially_sign_transaction(const char *input_ptr,
+                                               uint8_t *success);
+
+/**
+ * Same as `merge_signatures`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `merge_signatures`.
+ */
+char *ccd_wallet_v1_merge_signatures(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `check_signature_thresholds`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `check_signature_thresholds`.
+ */
+char *ccd_wallet_v1_check_signature_thresholds(const char *input_ptr,
+                                               uint8_t *success);
+
+/**
+ * Same as `encode_payment_request`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `encode_payment_request`.
+ */
+char *ccd_wallet_v1_encode_payment_request(const char *input_ptr,
+                                           uint8_t *success);
+
+/**
+ * Same as `decode_payment_request`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `decode_payment_request`.
+ */
+char *ccd_wallet_v1_decode_payment_request(const char *input_ptr,
+                                           uint8_t *success);
+
+/**
+ * Same as `encode_key_export`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `encode_key_export`.
+ */
+char *ccd_wallet_v1_encode_key_export(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `decode_key_export`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `decode_key_export`.
+ */
+char *ccd_wallet_v1_decode_key_export(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `generate_mnemonic`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `generate_mnemonic`.
+ */
+char *ccd_wallet_v1_generate_mnemonic(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `validate_mnemonic`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `validate_mnemonic`.
+ */
+char *ccd_wallet_v1_validate_mnemonic(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `get_input_schema`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_input_schema`.
+ */
+char *ccd_wallet_v1_get_input_schema(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `get_output_schema`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_output_schema`.
+ */
+char *ccd_wallet_v1_get_output_schema(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `estimate_transaction_energy`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `estimate_transaction_energy`.
+ */
+char *ccd_wallet_v1_estimate_transaction_energy(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `parse_transaction`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `parse_transaction`.
+ */
+char *ccd_wallet_v1_parse_transaction(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `create_id_request_and_private_data_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_id_request_and_private_data_with_context`.
+ */
+char *ccd_wallet_v1_create_id_request_and_private_data_with_context(const char *input_ptr,
+                                                                    const WalletContext *wallet_context,
+                                                                    uint8_t *success);
+
+/**
+ * Same as `create_credential_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_credential_with_context`.
+ */
+char *ccd_wallet_v1_create_credential_with_context(const char *input_ptr,
+                                                   const WalletContext *wallet_context,
+                                                   uint8_t *success);
+
+/**
+ * Same as `generate_accounts_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `generate_accounts_with_context`.
+ */
+char *ccd_wallet_v1_generate_accounts_with_context(const char *input_ptr,
+                                                   const WalletContext *wallet_context,
+                                                   uint8_t *success);
+
+/**
+ * Same as `get_credential_id_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_credential_id_with_context`.
+ */
+char *ccd_wallet_v1_get_credential_id_with_context(const char *input_ptr,
+                                                   const WalletContext *wallet_context,
+                                                   uint8_t *success);
+
+/**
+ * Same as `create_encrypted_transfer_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_encrypted_transfer_with_context`.
+ */
+char *ccd_wallet_v1_create_encrypted_transfer_with_context(const char *input_ptr,
+                                                           const WalletContext *wallet_context,
+                                                           uint8_t *success);
+
+/**
+ * Same as `create_pub_to_sec_transfer_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_pub_to_sec_transfer_with_context`.
+ */
+char *ccd_wallet_v1_create_pub_to_sec_transfer_with_context(const char *input_ptr,
+                                                            const WalletContext *wallet_context,
+                                                            uint8_t *success);
+
+/**
+ * Same as `create_sec_to_pub_transfer_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `create_sec_to_pub_transfer_with_context`.
+ */
+char *ccd_wallet_v1_create_sec_to_pub_transfer_with_context(const char *input_ptr,
+                                                            const WalletContext *wallet_context,
+                                                            uint8_t *success);
+
+/**
+ * Same as `decrypt_encrypted_amount_with_context`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `decrypt_encrypted_amount_with_context`.
+ */
+uint64_t ccd_wallet_v1_decrypt_encrypted_amount_with_context(const char *input_ptr,
+                                                             const WalletContext *wallet_context,
+                                                             uint8_t *success);
+
+/**
+ * Same as `decrypt_encrypted_amount`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `decrypt_encrypted_amount`.
+ */
+uint64_t ccd_wallet_v1_decrypt_encrypted_amount(const char *input_ptr,
+                                                uint8_t *success);
+
+/**
+ * Same as `set_decryption_table`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `set_decryption_table`.
+ */
+char *ccd_wallet_v1_set_decryption_table(const char *input_ptr,
+                                         uint8_t *success);
+
+/**
+ * Same as `free_response_string`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `free_response_string`.
+ */
+void ccd_wallet_v1_free_response_string(char *ptr);
+
+/**
+ * Same as `generate_accounts_cursor`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `generate_accounts_cursor`.
+ */
+ResponseCursor *ccd_wallet_v1_generate_accounts_cursor(const char *input_ptr);
+
+/**
+ * Same as `response_cursor_next`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `response_cursor_next`.
+ */
+char *ccd_wallet_v1_response_cursor_next(ResponseCursor *cursor,
+                                         uint8_t *success);
+
+/**
+ * Same as `free_response_cursor`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `free_response_cursor`.
+ */
+void ccd_wallet_v1_free_response_cursor(ResponseCursor *cursor);
+
+/**
+ * Same as `set_transaction_hash_function`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `set_transaction_hash_function`.
+ */
+void ccd_wallet_v1_set_transaction_hash_function(ExternalHashFunction f);
+
+/**
+ * Same as `set_allocation_failure_callback`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `set_allocation_failure_callback`.
+ */
+uint8_t ccd_wallet_v1_set_allocation_failure_callback(AllocationFailureCallback cb);
+
+/**
+ * Same as `set_sign_callback`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `set_sign_callback`.
+ */
+void ccd_wallet_v1_set_sign_callback(SignCallback cb);
+
+/**
+ * Same as `get_abi_version`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_abi_version`.
+ */
+uint32_t ccd_wallet_v1_get_abi_version(void);
+
+/**
+ * Same as `get_library_version`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_library_version`.
+ */
+char *ccd_wallet_v1_get_library_version(void);
+
+/**
+ * Same as `crypto_self_test`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `crypto_self_test`.
+ */
+char *ccd_wallet_v1_crypto_self_test(void);
+
+/**
+ * Same as `memory_current_usage`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `memory_current_usage`.
+ */
+uint64_t ccd_wallet_v1_memory_current_usage(void);
+
+/**
+ * Same as `memory_peak_usage`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `memory_peak_usage`.
+ */
+uint64_t ccd_wallet_v1_memory_peak_usage(void);
+
+/**
+ * Same as `reset_memory_peak_usage`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `reset_memory_peak_usage`.
+ */
+void ccd_wallet_v1_reset_memory_peak_usage(void);
+
+/**
+ * Same as `set_thread_count`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `set_thread_count`.
+ */
+uint8_t ccd_wallet_v1_set_thread_count(uint32_t n);
+
+/**
+ * Same as `new_operation_handle`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `new_operation_handle`.
+ */
+OperationHandle *ccd_wallet_v1_new_operation_handle(void);
+
+/**
+ * Same as `cancel_operation`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `cancel_operation`.
+ */
+void ccd_wallet_v1_cancel_operation(const OperationHandle *handle);
+
+/**
+ * Same as `free_operation_handle`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `free_operation_handle`.
+ */
+void ccd_wallet_v1_free_operation_handle(OperationHandle *handle);
+
+/**
+ * Same as `wallet_context_create`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `wallet_context_create`.
+ */
+WalletContext *ccd_wallet_v1_wallet_context_create(const char *input_ptr);
+
+/**
+ * Same as `wallet_context_free`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `wallet_context_free`.
+ */
+void ccd_wallet_v1_wallet_context_free(WalletContext *ctx);
+
+/**
+ * Same as `call_v2`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `call_v2`.
+ */
+char *ccd_wallet_v1_call_v2(const char *function_ptr, const char *input_ptr);
+
+/**
+ * Same as `call_v2_cancellable`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `call_v2_cancellable`.
+ */
+char *ccd_wallet_v1_call_v2_cancellable(const char *function_ptr,
+                                        const char *input_ptr,
+                                        const OperationHandle *handle);
+
+/**
+ * Same as `start_create_credential`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `start_create_credential`.
+ */
+uint64_t ccd_wallet_v1_start_create_credential(const char *input_ptr, uint8_t *success);
+
+/**
+ * Same as `start_create_id_request_and_private_data`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `start_create_id_request_and_private_data`.
+ */
+uint64_t ccd_wallet_v1_start_create_id_request_and_private_data(const char *input_ptr,
+                                                                uint8_t *success);
+
+/**
+ * Same as `poll_job`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `poll_job`.
+ */
+uint8_t ccd_wallet_v1_poll_job(uint64_t job_id);
+
+/**
+ * Same as `cancel_job`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `cancel_job`.
+ */
+void ccd_wallet_v1_cancel_job(uint64_t job_id);
+
+/**
+ * Same as `get_job_result`, under the name of version 1 of the C interface.
+ *
+ * # Safety
+ * The arguments must satisfy the requirements of `get_job_result`.
+ */
+char *ccd_wallet_v1_get_job_result(uint64_t job_id, uint8_t *success);
@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+# Generate the C header mobile_wallet.h from the sources of the library.
+#
+# This requires cbindgen (`cargo install cbindgen`) and a nightly toolchain,
+# which cbindgen uses to expand the macros that define the exported functions.
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+RUSTUP_TOOLCHAIN="${RUSTUP_TOOLCHAIN:-nightly}" cbindgen \
+    --config cbindgen.toml \
+    --crate mobile_wallet \
+    --output mobile_wallet.h
@@ -2,13 +2,47 @@
 #![allow(unused_assignments)]
 
 use crate::{
-    check_account_address, combine_encrypted_amounts, create_credential, create_encrypted_transfer,
-    create_id_request_and_private_data, create_pub_to_sec_transfer, create_sec_to_pub_transfer,
-    create_transfer, decrypt_encrypted_amount, generate_accounts,
+    attach_signatures, check_account_address, check_signature_thresholds,
+    combine_encrypted_amounts, combine_encrypted_amounts_list,
+    context::{wallet_context_create, wallet_context_free, WalletContext},
+    create_account_ownership_proof, create_configure_baker, create_credential,
+    create_credential_cancellable, create_credential_with_context, create_encrypted_transfer,
+    create_encrypted_transfer_cancellable, create_encrypted_transfer_from_balance,
+    create_encrypted_transfer_with_context, create_id_request_and_private_data,
+    create_id_request_and_private_data_with_context, create_pub_to_sec_transfer,
+    create_pub_to_sec_transfer_with_context, create_sec_to_pub_transfer,
+    create_sec_to_pub_transfer_cancellable, create_sec_to_pub_transfer_from_balance,
+    create_sec_to_pub_transfer_with_context, create_transfer, create_transfer_with_schedule,
+    create_unsigned_encrypted_transfer, create_unsigned_transfer, create_update_keys,
+    cursor::{
+        free_response_cursor, generate_accounts_cursor, response_cursor_next, ResponseCursor,
+    },
+    decode_key_export, decode_payment_request, decode_transaction, decrypt_encrypted_amount,
+    decrypt_encrypted_amount_with_context, derive_keys_from_seed, encode_key_export,
+    encode_payment_request, estimate_transaction_energy, finalize_credential, generate_accounts,
+    generate_accounts_with_context, generate_baker_keys, generate_mnemonic, get_credential_id,
+    get_credential_id_with_context, get_input_schema, get_output_schema,
+    job::{
+        cancel_job, get_job_result, poll_job, start_create_credential,
+        start_create_id_request_and_private_data,
+    },
+    memory::{memory_current_usage, memory_peak_usage, reset_memory_peak_usage},
+    merge_signatures,
+    operation::{cancel_operation, free_operation_handle, new_operation_handle, OperationHandle},
+    parse_transaction, partially_sign_transaction, prepare_credential, prove_attribute_in_range,
+    prove_statement,
+    self_test::self_test,
+    set_decryption_table,
+    threads::set_thread_count,
+    update_encrypted_balance,
+    v2::{call_v2, call_v2_cancellable},
+    validate_mnemonic, verify_attribute_in_range,
+    version::{get_abi_version, library_version},
 };
+use crypto_common::{c_char, Zeroize};
 use jni::{
     objects::{JClass, JString, JValue},
-    sys::{jboolean, jint, jobject},
+    sys::{jboolean, jint, jlong, jobject, jstring},
     JNIEnv,
 };
 use std::ffi::CString;
@@ -46,14 +80,7 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
@@ -88,14 +115,7 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
@@ -130,14 +150,182 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `derive_keys_from_seed` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_derive_1keys_1from_1seed(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = derive_keys_from_seed(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `generate_mnemonic` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate_1mnemonic(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = generate_mnemonic(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `validate_mnemonic` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_validate_1mnemonic(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = validate_mnemonic(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_input_schema` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be the name of a library function
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1input_1schema(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = get_input_schema(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_output_schema` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be the name of a library function
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1output_1schema(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = get_output_schema(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
@@ -172,21 +360,14 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `create_encrypted_transfer` method.
+/// The JNI wrapper for the `create_transfer_with_schedule` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
 /// is non-null. The input must be valid JSON according to specified format
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1encrypted_1transfer(
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1transfer_1with_1schedule(
     env: JNIEnv,
     _: JClass,
     input: JString,
@@ -207,28 +388,21 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
 
     let mut success: u8 = 127;
     let cstr_res = unsafe {
-        let unsafe_res_ptr = create_encrypted_transfer(input_str.as_ptr(), &mut success);
+        let unsafe_res_ptr = create_transfer_with_schedule(input_str.as_ptr(), &mut success);
         if unsafe_res_ptr.is_null() {
             return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
         }
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `create_pub_to_sec_transfer` method.
+/// The JNI wrapper for the `create_update_keys` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
 /// is non-null. The input must be valid JSON according to specified format
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1pub_1to_1sec_1transfer(
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1update_1keys(
     env: JNIEnv,
     _: JClass,
     input: JString,
@@ -249,28 +423,21 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
 
     let mut success: u8 = 127;
     let cstr_res = unsafe {
-        let unsafe_res_ptr = create_pub_to_sec_transfer(input_str.as_ptr(), &mut success);
+        let unsafe_res_ptr = create_update_keys(input_str.as_ptr(), &mut success);
         if unsafe_res_ptr.is_null() {
             return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
         }
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `create_sec_to_pub_transfer` method.
+/// The JNI wrapper for the `generate_baker_keys` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
 /// is non-null. The input must be valid JSON according to specified format
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1sec_1to_1pub_1transfer(
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate_1baker_1keys(
     env: JNIEnv,
     _: JClass,
     input: JString,
@@ -291,34 +458,26 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1
 
     let mut success: u8 = 127;
     let cstr_res = unsafe {
-        let unsafe_res_ptr = create_sec_to_pub_transfer(input_str.as_ptr(), &mut success);
+        let unsafe_res_ptr = generate_baker_keys(input_str.as_ptr(), &mut success);
         if unsafe_res_ptr.is_null() {
             return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
         }
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `combine_encrypted_amounts` method.
+/// The JNI wrapper for the `create_configure_baker` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
 /// is non-null. The input must be valid JSON according to specified format
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_combine_1encrypted_1amounts(
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1configure_1baker(
     env: JNIEnv,
     _: JClass,
-    input1: JString,
-    input2: JString,
+    input: JString,
 ) -> jobject {
-    let input_str_1 = match env.get_string(input1) {
+    let input_str = match env.get_string(input) {
         Ok(res_str) => res_str,
         Err(e) => {
             return wrap_return_tuple(
@@ -332,7 +491,28 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_combine_
         }
     };
 
-    let input_str_2 = match env.get_string(input2) {
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = create_configure_baker(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_configure_delegation` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1configure_1delegation(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
         Ok(res_str) => res_str,
         Err(e) => {
             return wrap_return_tuple(
@@ -348,29 +528,21 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_combine_
 
     let mut success: u8 = 127;
     let cstr_res = unsafe {
-        let unsafe_res_ptr =
-            combine_encrypted_amounts(input_str_1.as_ptr(), input_str_2.as_ptr(), &mut success);
+        let unsafe_res_ptr = create_configure_delegation(input_str.as_ptr(), &mut success);
         if unsafe_res_ptr.is_null() {
             return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
         }
         CString::from_raw(unsafe_res_ptr)
     };
 
-    match cstr_res.to_str() {
-        Ok(str_ref) => wrap_return_tuple(&env, success, str_ref),
-        Err(e) => wrap_return_tuple(
-            &env,
-            127,
-            &format!("Could not read CString from crypto library {:?}", e),
-        ),
-    }
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `decrypt_encrypted_amount` method.
+/// The JNI wrapper for the `set_decryption_table` method.
 /// The `input` parameter must be a properly initalized `java.lang.String` that
-/// is non-null. The input must be valid JSON according to specified format.
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decrypt_1encrypted_1amount(
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_set_1decryption_1table(
     env: JNIEnv,
     _: JClass,
     input: JString,
@@ -390,25 +562,1140 @@ pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decrypt_
     };
 
     let mut success: u8 = 127;
-    let decrypted_amount_res: String =
-        unsafe { decrypt_encrypted_amount(input_str.as_ptr(), &mut success) }.to_string();
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = set_decryption_table(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
 
-    wrap_return_tuple(&env, success, &decrypted_amount_res)
+    wrap_response(&env, success, cstr_res)
 }
 
 #[no_mangle]
-/// The JNI wrapper for the `check_account_address` method.
-/// The `input` parameter must be a `java.lang.String` that is non-null.
-pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_check_1account_1address(
+/// The JNI wrapper for the `create_encrypted_transfer` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1encrypted_1transfer(
     env: JNIEnv,
     _: JClass,
     input: JString,
-) -> jboolean {
+) -> jobject {
     let input_str = match env.get_string(input) {
         Ok(res_str) => res_str,
-        Err(_) => return 0,
-    };
-    unsafe { check_account_address(input_str.as_ptr()) }
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = create_encrypted_transfer(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_pub_to_sec_transfer` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1pub_1to_1sec_1transfer(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = create_pub_to_sec_transfer(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_sec_to_pub_transfer` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1sec_1to_1pub_1transfer(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = create_sec_to_pub_transfer(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `combine_encrypted_amounts` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_combine_1encrypted_1amounts(
+    env: JNIEnv,
+    _: JClass,
+    input1: JString,
+    input2: JString,
+) -> jobject {
+    let input_str_1 = match env.get_string(input1) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let input_str_2 = match env.get_string(input2) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr =
+            combine_encrypted_amounts(input_str_1.as_ptr(), input_str_2.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `combine_encrypted_amounts_list` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_combine_1encrypted_1amounts_1list(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, combine_encrypted_amounts_list)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `update_encrypted_balance` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_update_1encrypted_1balance(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, update_encrypted_balance)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_encrypted_transfer_from_balance` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1encrypted_1transfer_1from_1balance(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, create_encrypted_transfer_from_balance)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_sec_to_pub_transfer_from_balance` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1sec_1to_1pub_1transfer_1from_1balance(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, create_sec_to_pub_transfer_from_balance)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_unsigned_transfer` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1unsigned_1transfer(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, create_unsigned_transfer)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_unsigned_encrypted_transfer` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1unsigned_1encrypted_1transfer(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, create_unsigned_encrypted_transfer)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `attach_signatures` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_attach_1signatures(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, attach_signatures)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `partially_sign_transaction` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_partially_1sign_1transaction(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, partially_sign_transaction)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `merge_signatures` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_merge_1signatures(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, merge_signatures)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `check_signature_thresholds` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_check_1signature_1thresholds(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, check_signature_thresholds)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `prepare_credential` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_prepare_1credential(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, prepare_credential)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `finalize_credential` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_finalize_1credential(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, finalize_credential)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_credential_id` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1credential_1id(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, get_credential_id)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `prove_attribute_in_range` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_prove_1attribute_1in_1range(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, prove_attribute_in_range)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `verify_attribute_in_range` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_verify_1attribute_1in_1range(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, verify_attribute_in_range)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `prove_statement` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_prove_1statement(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, prove_statement)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_account_ownership_proof` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1account_1ownership_1proof(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, create_account_ownership_proof)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `decode_transaction` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decode_1transaction(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, decode_transaction)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `estimate_transaction_energy` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_estimate_1transaction_1energy(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, estimate_transaction_energy)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `parse_transaction` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be a hex encoded transaction.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_parse_1transaction(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, parse_transaction)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `encode_payment_request` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_encode_1payment_1request(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, encode_payment_request)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `decode_payment_request` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decode_1payment_1request(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, decode_payment_request)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `encode_key_export` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_encode_1key_1export(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, encode_key_export)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `decode_key_export` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decode_1key_1export(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    call_with_input(&env, input, decode_key_export)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `call_v2` method. Returns null if the strings
+/// cannot be converted.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_call_1v2(
+    env: JNIEnv,
+    _: JClass,
+    function: JString,
+    input: JString,
+) -> jstring {
+    let (function_str, input_str) = match (env.get_string(function), env.get_string(input)) {
+        (Ok(function_str), Ok(input_str)) => (function_str, input_str),
+        _ => return std::ptr::null_mut(),
+    };
+    let response = unsafe { CString::from_raw(call_v2(function_str.as_ptr(), input_str.as_ptr())) };
+    wrap_v2_response(&env, response)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `call_v2_cancellable` method. Returns null if the
+/// strings cannot be converted.
+/// The `handle` must have been obtained from `new_operation_handle`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_call_1v2_1cancellable(
+    env: JNIEnv,
+    _: JClass,
+    function: JString,
+    input: JString,
+    handle: jlong,
+) -> jstring {
+    let (function_str, input_str) = match (env.get_string(function), env.get_string(input)) {
+        (Ok(function_str), Ok(input_str)) => (function_str, input_str),
+        _ => return std::ptr::null_mut(),
+    };
+    let response = unsafe {
+        CString::from_raw(call_v2_cancellable(
+            function_str.as_ptr(),
+            input_str.as_ptr(),
+            handle as *const OperationHandle,
+        ))
+    };
+    wrap_v2_response(&env, response)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `generate_accounts_cursor` method. The returned
+/// cursor must be freed with `free_response_cursor`. Returns 0 if the input
+/// cannot be read.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate_1accounts_1cursor(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jlong {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(_) => return 0,
+    };
+    unsafe { generate_accounts_cursor(input_str.as_ptr()) as jlong }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `response_cursor_next` method. Returns null when
+/// there are no more items.
+/// The `cursor` must have been obtained from one of the functions returning a
+/// cursor, and must not have been freed.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_response_1cursor_1next(
+    env: JNIEnv,
+    _: JClass,
+    cursor: jlong,
+) -> jobject {
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = response_cursor_next(cursor as *mut ResponseCursor, &mut success);
+        if unsafe_res_ptr.is_null() {
+            if success == 1 {
+                return std::ptr::null_mut();
+            }
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `free_response_cursor` method.
+/// The `cursor` must have been obtained from one of the functions returning a
+/// cursor, and must not be used afterwards.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_free_1response_1cursor(
+    _: JNIEnv,
+    _: JClass,
+    cursor: jlong,
+) {
+    unsafe { free_response_cursor(cursor as *mut ResponseCursor) }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `memory_current_usage` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_memory_1current_1usage(
+    _: JNIEnv,
+    _: JClass,
+) -> jlong {
+    memory_current_usage() as jlong
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `memory_peak_usage` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_memory_1peak_1usage(
+    _: JNIEnv,
+    _: JClass,
+) -> jlong {
+    memory_peak_usage() as jlong
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `reset_memory_peak_usage` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_reset_1memory_1peak_1usage(
+    _: JNIEnv,
+    _: JClass,
+) {
+    reset_memory_peak_usage()
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `set_thread_count` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_set_1thread_1count(
+    _: JNIEnv,
+    _: JClass,
+    n: jint,
+) -> jboolean {
+    set_thread_count(n as u32)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `decrypt_encrypted_amount` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decrypt_1encrypted_1amount(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let decrypted_amount_res: String =
+        unsafe { decrypt_encrypted_amount(input_str.as_ptr(), &mut success) }.to_string();
+
+    wrap_return_tuple(&env, success, &decrypted_amount_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `check_account_address` method.
+/// The `input` parameter must be a `java.lang.String` that is non-null.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_check_1account_1address(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jboolean {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(_) => return 0,
+    };
+    unsafe { check_account_address(input_str.as_ptr()) }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_abi_version` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1abi_1version(
+    _: JNIEnv,
+    _: JClass,
+) -> jint {
+    get_abi_version() as jint
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_library_version` method. Returns null if the
+/// string cannot be created.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1library_1version(
+    env: JNIEnv,
+    _: JClass,
+) -> jstring {
+    match env.new_string(library_version().to_string()) {
+        Ok(s) => s.into_inner(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `crypto_self_test` method. Returns null if the
+/// string cannot be created.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_crypto_1self_1test(
+    env: JNIEnv,
+    _: JClass,
+) -> jstring {
+    let report = match serde_json::to_string(&self_test()) {
+        Ok(report) => report,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    match env.new_string(report) {
+        Ok(s) => s.into_inner(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `new_operation_handle` method. The returned handle
+/// must be freed with `free_operation_handle`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_new_1operation_1handle(
+    _: JNIEnv,
+    _: JClass,
+) -> jlong {
+    new_operation_handle() as jlong
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `cancel_operation` method.
+/// The `handle` must have been obtained from `new_operation_handle` and not yet
+/// freed.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_cancel_1operation(
+    _: JNIEnv,
+    _: JClass,
+    handle: jlong,
+) {
+    unsafe { cancel_operation(handle as *const OperationHandle) }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `free_operation_handle` method.
+/// The `handle` must have been obtained from `new_operation_handle`, and must
+/// not be used afterwards.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_free_1operation_1handle(
+    _: JNIEnv,
+    _: JClass,
+    handle: jlong,
+) {
+    unsafe { free_operation_handle(handle as *mut OperationHandle) }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_credential_cancellable` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `handle` must have been obtained from `new_operation_handle`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1credential_1cancellable(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    handle: jlong,
+) -> jobject {
+    call_cancellable(&env, input, handle, create_credential_cancellable)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_encrypted_transfer_cancellable` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `handle` must have been obtained from `new_operation_handle`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1encrypted_1transfer_1cancellable(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    handle: jlong,
+) -> jobject {
+    call_cancellable(&env, input, handle, create_encrypted_transfer_cancellable)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_sec_to_pub_transfer_cancellable` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `handle` must have been obtained from `new_operation_handle`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1sec_1to_1pub_1transfer_1cancellable(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    handle: jlong,
+) -> jobject {
+    call_cancellable(&env, input, handle, create_sec_to_pub_transfer_cancellable)
+}
+
+/// Call one of the functions taking a single input with the given input, and
+/// wrap the result for returning to Java.
+fn call_with_input(
+    env: &JNIEnv,
+    input: JString,
+    f: unsafe fn(*const c_char, *mut u8) -> *mut c_char,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = f(input_str.as_ptr(), &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(env, success, cstr_res)
+}
+
+/// Call one of the cancellable functions with the given input and operation
+/// handle, and wrap the result for returning to Java.
+fn call_cancellable(
+    env: &JNIEnv,
+    input: JString,
+    handle: jlong,
+    f: unsafe fn(*const c_char, *const OperationHandle, *mut u8) -> *mut c_char,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = f(
+            input_str.as_ptr(),
+            handle as *const OperationHandle,
+            &mut success,
+        );
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `start_create_credential` method. Returns 0 if the
+/// input cannot be read.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_start_1create_1credential(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jlong {
+    start_job(&env, input, start_create_credential)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `start_create_id_request_and_private_data` method.
+/// Returns 0 if the input cannot be read.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_start_1create_1id_1request_1and_1private_1data(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jlong {
+    start_job(&env, input, start_create_id_request_and_private_data)
+}
+
+/// Start a job with the given input, and return its identifier, or 0 if the
+/// input cannot be read.
+fn start_job(env: &JNIEnv, input: JString, f: unsafe fn(*const c_char, *mut u8) -> u64) -> jlong {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(_) => return 0,
+    };
+    let mut success: u8 = 127;
+    unsafe { f(input_str.as_ptr(), &mut success) as jlong }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `poll_job` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_poll_1job(
+    _: JNIEnv,
+    _: JClass,
+    job_id: jlong,
+) -> jint {
+    poll_job(job_id as u64) as jint
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `cancel_job` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_cancel_1job(
+    _: JNIEnv,
+    _: JClass,
+    job_id: jlong,
+) {
+    cancel_job(job_id as u64)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_job_result` method.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1job_1result(
+    env: JNIEnv,
+    _: JClass,
+    job_id: jlong,
+) -> jobject {
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = get_job_result(job_id as u64, &mut success);
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(&env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(&env, success, cstr_res)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `wallet_context_create` method. The returned
+/// context must be freed with `wallet_context_free`. Returns 0 if the input
+/// cannot be read.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_wallet_1context_1create(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+) -> jlong {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(_) => return 0,
+    };
+    unsafe { wallet_context_create(input_str.as_ptr()) as jlong }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `wallet_context_free` method.
+/// The `context` must have been obtained from `wallet_context_create`, and must
+/// not be used afterwards.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_wallet_1context_1free(
+    _: JNIEnv,
+    _: JClass,
+    context: jlong,
+) {
+    unsafe { wallet_context_free(context as *mut WalletContext) }
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_id_request_and_private_data_with_context`
+/// method. The `input` parameter must be a properly initalized
+/// `java.lang.String` that is non-null. The input must be valid JSON according
+/// to specified format. The `context` must have been obtained from
+/// `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1id_1request_1and_1private_1data_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(
+        &env,
+        input,
+        context,
+        create_id_request_and_private_data_with_context,
+    )
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_credential_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1credential_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(&env, input, context, create_credential_with_context)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `generate_accounts_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_generate_1accounts_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(&env, input, context, generate_accounts_with_context)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `get_credential_id_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_get_1credential_1id_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(&env, input, context, get_credential_id_with_context)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_encrypted_transfer_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1encrypted_1transfer_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(&env, input, context, create_encrypted_transfer_with_context)
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_pub_to_sec_transfer_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1pub_1to_1sec_1transfer_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(
+        &env,
+        input,
+        context,
+        create_pub_to_sec_transfer_with_context,
+    )
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `create_sec_to_pub_transfer_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_create_1sec_1to_1pub_1transfer_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    call_with_context(
+        &env,
+        input,
+        context,
+        create_sec_to_pub_transfer_with_context,
+    )
+}
+
+#[no_mangle]
+/// The JNI wrapper for the `decrypt_encrypted_amount_with_context` method.
+/// The `input` parameter must be a properly initalized `java.lang.String` that
+/// is non-null. The input must be valid JSON according to specified format.
+/// The `context` must have been obtained from `wallet_context_create`.
+pub extern "system" fn Java_com_concordium_mobile_1wallet_1lib_WalletKt_decrypt_1encrypted_1amount_1with_1context(
+    env: JNIEnv,
+    _: JClass,
+    input: JString,
+    context: jlong,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                &env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let decrypted_amount_res: String = unsafe {
+        decrypt_encrypted_amount_with_context(
+            input_str.as_ptr(),
+            context as *const WalletContext,
+            &mut success,
+        )
+    }
+    .to_string();
+
+    wrap_return_tuple(&env, success, &decrypted_amount_res)
+}
+
+/// Call one of the functions using a wallet context with the given input and
+/// context, and wrap the result for returning to Java.
+fn call_with_context(
+    env: &JNIEnv,
+    input: JString,
+    context: jlong,
+    f: unsafe fn(*const c_char, *const WalletContext, *mut u8) -> *mut c_char,
+) -> jobject {
+    let input_str = match env.get_string(input) {
+        Ok(res_str) => res_str,
+        Err(e) => {
+            return wrap_return_tuple(
+                env,
+                127,
+                &format!(
+                    "Could not read java.lang.String given as input due to {:?}",
+                    e
+                ),
+            )
+        }
+    };
+
+    let mut success: u8 = 127;
+    let cstr_res = unsafe {
+        let unsafe_res_ptr = f(
+            input_str.as_ptr(),
+            context as *const WalletContext,
+            &mut success,
+        );
+        if unsafe_res_ptr.is_null() {
+            return wrap_return_tuple(env, 127, "Pointer returned from crypto library was NULL");
+        }
+        CString::from_raw(unsafe_res_ptr)
+    };
+
+    wrap_response(env, success, cstr_res)
+}
+
+/// Wrap a response of the library for returning to Java. The response is
+/// overwritten once it has been copied to a Java string, since it can contain
+/// secret keys.
+fn wrap_response(env: &JNIEnv, success: u8, response: CString) -> jobject {
+    let ret = match response.to_str() {
+        Ok(str_ref) => wrap_return_tuple(env, success, str_ref),
+        Err(e) => wrap_return_tuple(
+            env,
+            127,
+            &format!("Could not read CString from crypto library {:?}", e),
+        ),
+    };
+    response.into_bytes().zeroize();
+    ret
+}
+
+/// Convert a v2 response of the library to a Java string, or null if this
+/// fails. The response is overwritten once it has been copied, as in
+/// [wrap_response].
+fn wrap_v2_response(env: &JNIEnv, response: CString) -> jstring {
+    let ret = match response.to_str().map(|str_ref| env.new_string(str_ref)) {
+        Ok(Ok(s)) => s.into_inner(),
+        _ => std::ptr::null_mut(),
+    };
+    response.into_bytes().zeroize();
+    ret
 }
 
 /// Method for wrapping the return value to Java
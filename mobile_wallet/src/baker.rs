@@ -0,0 +1,201 @@
+//! Transactions that register and update bakers.
+//!
+//! A single transaction type, configure baker, both registers an account as a
+//! baker and updates the baker of an account, see [transactions::baker]. When
+//! the keys of the baker are set the payload contains proofs of knowledge of
+//! the secret keys, which are bound to the account sending the transaction.
+use crate::{
+    make_signatures, make_transaction_bytes, parse_transaction_input, try_get, TransferContext,
+};
+use anyhow::ensure;
+use crypto_common::*;
+use ed25519_dalek as ed25519;
+use id::{cost, types::AccountAddress};
+use pairing::bls12_381::Bls12;
+use rand::thread_rng;
+use random_oracle::RandomOracle;
+use serde_json::{from_value, to_string, Value};
+use transactions::{
+    baker::{key_challenge, ConfigureBakerPayload, MAX_URL_LENGTH},
+    Payload, PayloadLike,
+};
+
+/// Generate the keys of a baker together with the proofs of knowledge of the
+/// secret keys for the given account. The result contains both the secret and
+/// the public keys, and the public part parses as
+/// [BakerKeysWithProofs](transactions::baker::BakerKeysWithProofs).
+pub fn generate_baker_keys(sender: &AccountAddress) -> Value {
+    let mut csprng = thread_rng();
+    let election = ecvrf::Keypair::generate(&mut csprng);
+    let signature = ed25519::Keypair::generate(&mut csprng);
+    let aggregation_sign = aggregate_sig::SecretKey::<Bls12>::generate(&mut csprng);
+    let aggregation_verify = aggregate_sig::PublicKey::from_secret(&aggregation_sign);
+
+    let challenge = key_challenge(
+        sender,
+        &election.public,
+        &signature.public,
+        &aggregation_verify,
+    );
+    let proof_election = eddsa_ed25519::prove_dlog_ed25519(
+        &mut RandomOracle::domain(&challenge),
+        &election.public,
+        &election.secret,
+    );
+    let proof_sig = eddsa_ed25519::prove_dlog_ed25519(
+        &mut RandomOracle::domain(&challenge),
+        &signature.public,
+        &signature.secret,
+    );
+    let proof_aggregation =
+        aggregation_sign.prove(&mut csprng, &mut RandomOracle::domain(&challenge));
+
+    json!({
+        "electionVerifyKey": base16_encode_string(&election.public),
+        "electionPrivateKey": base16_encode_string(&election.secret),
+        "signatureVerifyKey": base16_encode_string(&signature.public),
+        "signatureSignKey": base16_encode_string(&signature.secret),
+        "aggregationVerifyKey": base16_encode_string(&aggregation_verify),
+        "aggregationSignKey": base16_encode_string(&aggregation_sign),
+        "proofElection": proof_election,
+        "proofSig": proof_sig,
+        "proofAggregation": proof_aggregation,
+    })
+}
+
+/// Create a configure baker transaction. Besides the fields of the transfer
+/// context the input has the fields of [ConfigureBakerPayload], all optional,
+/// but at least one must be given. Instead of `keysWithProofs` the input can
+/// set `generateKeys` to generate fresh keys, which are then returned with
+/// their secret keys in the field `bakerKeys` of the response.
+pub(crate) fn create_configure_baker_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+
+    let ctx: TransferContext = from_value(v.clone())?;
+    let mut payload: ConfigureBakerPayload = from_value(v.clone())?;
+    let generate_keys: bool = match v.get("generateKeys") {
+        Some(_) => try_get(&v, "generateKeys")?,
+        None => false,
+    };
+
+    let baker_keys = if generate_keys {
+        ensure!(
+            payload.keys_with_proofs.is_none(),
+            "Only one of 'keysWithProofs' and 'generateKeys' can be given."
+        );
+        let keys = generate_baker_keys(&ctx.from);
+        payload.keys_with_proofs = Some(from_value(keys.clone())?);
+        Some(keys)
+    } else {
+        None
+    };
+    if let Some(keys) = &payload.keys_with_proofs {
+        ensure!(
+            keys.verify(&ctx.from),
+            "The proofs of the baker keys are not valid for the sender account."
+        );
+    }
+    if let Some(url) = &payload.metadata_url {
+        ensure!(
+            url.len() <= MAX_URL_LENGTH,
+            "Metadata URL of {} bytes exceeds the maximum of {}.",
+            url.len(),
+            MAX_URL_LENGTH
+        );
+    }
+    ensure!(
+        payload.bitmap() != 0,
+        "At least one field of the baker must be configured."
+    );
+
+    let execution_energy = if payload.keys_with_proofs.is_some() {
+        cost::CONFIGURE_BAKER_WITH_KEYS_ENERGY
+    } else {
+        cost::CONFIGURE_BAKER_WITHOUT_KEYS_ENERGY
+    };
+    let (hash, body) = {
+        let payload_bytes = Payload::ConfigureBaker {
+            data: Box::new(payload),
+        }
+        .encode();
+
+        make_transaction_bytes(
+            &ctx.header(execution_energy, &payload_bytes)?,
+            &payload_bytes,
+        )
+    };
+
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
+
+    let mut response = json!({
+        "signatures": signatures,
+        "transaction": hex::encode(&body),
+    });
+    if let Some(keys) = baker_keys {
+        response["bakerKeys"] = keys;
+    }
+
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::decode_payload;
+    use crypto_common::types::Amount;
+    use curve_arithmetic::Curve;
+    use transactions::baker::{AmountFraction, BakerKeysWithProofs, OpenStatus};
+
+    #[test]
+    fn test_configure_baker_payload() {
+        let sender = AccountAddress::new(&crate::ExampleCurve::one_point());
+        let keys: BakerKeysWithProofs =
+            from_value(generate_baker_keys(&sender)).expect("Generated keys should parse.");
+        assert!(keys.verify(&sender));
+        assert!(!keys.verify(&AccountAddress::new(
+            &crate::ExampleCurve::one_point().double_point()
+        )));
+
+        let payload = ConfigureBakerPayload {
+            capital: Some(Amount::from(1_000_000)),
+            restake_earnings: Some(true),
+            open_for_delegation: Some(OpenStatus::ClosedForNew),
+            keys_with_proofs: Some(keys),
+            metadata_url: Some("https://example.com/baker.json".into()),
+            transaction_fee_commission: AmountFraction::new(5000),
+            baking_reward_commission: AmountFraction::new(100_000),
+            finalization_reward_commission: AmountFraction::new(0),
+        };
+        assert_eq!(payload.bitmap(), 0xff);
+        let bytes = Payload::ConfigureBaker {
+            data: Box::new(payload),
+        }
+        .encode();
+        let decoded = decode_payload(4, bytes.as_ref()).expect("Payload should decode.");
+        assert_eq!(decoded["type"], "configureBaker");
+        assert_eq!(decoded["capital"], "1000000");
+        assert_eq!(decoded["restakeEarnings"], true);
+        assert_eq!(decoded["openForDelegation"], "closedForNew");
+        assert_eq!(decoded["metadataUrl"], "https://example.com/baker.json");
+        assert_eq!(decoded["transactionFeeCommission"], 0.05);
+        assert_eq!(decoded["bakingRewardCommission"], 1.0);
+        let keys: BakerKeysWithProofs =
+            from_value(decoded["keysWithProofs"].clone()).expect("Decoded keys should parse.");
+        assert!(keys.verify(&sender));
+        // Configure baker does not exist before protocol version 4.
+        assert!(decode_payload(3, bytes.as_ref()).is_err());
+
+        // Only the given fields are serialized.
+        let payload = ConfigureBakerPayload {
+            restake_earnings: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(to_bytes(&payload), vec![0, 2, 0]);
+
+        // Fractions must be exact and at most 1.
+        assert!(from_value::<AmountFraction>(json!(0.12345)).is_ok());
+        assert!(from_value::<AmountFraction>(json!(0.123456)).is_err());
+        assert!(from_value::<AmountFraction>(json!(1.5)).is_err());
+        assert!(AmountFraction::new(100_001).is_none());
+    }
+}
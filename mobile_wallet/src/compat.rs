@@ -0,0 +1,110 @@
+//! Compatibility with the input field names of older versions of the wallets.
+//!
+//! Some fields of the inputs of the library functions have been renamed. The
+//! old names are still accepted, but a deprecation warning is added to the
+//! response under the key `deprecationWarnings`, so that the apps can be
+//! updated before the old names are removed.
+use crate::warnings::{warn, WarningKind, Warnings};
+use serde_json::{from_str, Map, Value};
+
+/// Pairs of a deprecated field name and the current name of the field.
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("globalContext", "global"),
+    ("arsInfo", "arsInfos"),
+    ("idObject", "identityObject"),
+    ("accountNum", "accountNumber"),
+];
+
+/// Rename the deprecated fields of an input object to their current names. If
+/// both the deprecated and the current name are present then the deprecated
+/// field is ignored.
+pub(crate) fn normalize_input(v: &mut Value) {
+    let obj = match v.as_object_mut() {
+        Some(obj) => obj,
+        None => return,
+    };
+    for (old, new) in FIELD_ALIASES.iter() {
+        if let Some(value) = obj.remove(*old) {
+            if obj.contains_key(*new) {
+                warn(
+                    WarningKind::Deprecation,
+                    format!(
+                        "Field '{}' is deprecated and ignored since '{}' is present.",
+                        old, new
+                    ),
+                );
+            } else {
+                warn(
+                    WarningKind::Deprecation,
+                    format!("Field '{}' is deprecated, use '{}' instead.", old, new),
+                );
+                obj.insert((*new).to_owned(), value);
+            }
+        }
+    }
+}
+
+/// Parse the JSON input of a library function and rename deprecated fields.
+pub(crate) fn parse_input(input: &str) -> anyhow::Result<Value> {
+    let mut v: Value = from_str(input)?;
+    normalize_input(&mut v);
+    Ok(v)
+}
+
+/// Add the deprecation warnings to a JSON response. Warnings can only be added
+/// to objects, so any other response is wrapped in an object under the key
+/// `result`, and responses that are not JSON are included as a string.
+pub(crate) fn add_warnings(response: String, warnings: &Warnings) -> String {
+    let warnings = warnings.deprecations();
+    if warnings.is_empty() {
+        return response;
+    }
+    let mut obj = match from_str::<Value>(&response) {
+        Ok(Value::Object(obj)) => obj,
+        Ok(value) => {
+            let mut obj = Map::new();
+            obj.insert("result".to_owned(), value);
+            obj
+        }
+        Err(_) => {
+            let mut obj = Map::new();
+            obj.insert("result".to_owned(), Value::String(response));
+            obj
+        }
+    };
+    obj.insert("deprecationWarnings".to_owned(), warnings.into());
+    Value::Object(obj).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::warnings::collect_warnings;
+
+    #[test]
+    fn test_normalize_input() {
+        let input = r#"{"globalContext": 1, "arsInfo": 2, "arsInfos": 3, "amount": 4}"#;
+        let (v, warnings) = collect_warnings(|| parse_input(input).unwrap());
+        assert_eq!(v, json!({"global": 1, "arsInfos": 3, "amount": 4}));
+        assert_eq!(warnings.deprecations().len(), 2);
+
+        let response = add_warnings(r#"{"amount":"4"}"#.to_owned(), &warnings);
+        let response: Value = from_str(&response).unwrap();
+        assert_eq!(response["amount"], "4");
+        assert_eq!(
+            response["deprecationWarnings"],
+            json!(warnings.deprecations())
+        );
+        let response: Value = from_str(&add_warnings("[]".to_owned(), &warnings)).unwrap();
+        assert_eq!(response["result"], json!([]));
+        assert_eq!(
+            response["deprecationWarnings"],
+            json!(warnings.deprecations())
+        );
+        let response: Value = from_str(&add_warnings("0a1b".to_owned(), &warnings)).unwrap();
+        assert_eq!(response["result"], "0a1b");
+
+        let (_, warnings) = collect_warnings(|| parse_input(r#"{"global": 1}"#).unwrap());
+        assert!(warnings.all().is_empty());
+    }
+}
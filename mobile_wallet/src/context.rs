@@ -0,0 +1,167 @@
+//! Handles to parsed parameters that are shared by many calls.
+//!
+//! Most functions take the cryptographic parameters, and possibly the
+//! identity provider and anonymity revokers, as part of their input, and
+//! parse and validate them on every call. Decryption of amounts additionally
+//! parses the table for computing discrete logarithms. On mobile devices this
+//! dominates the time of the cheaper functions. A wallet context holds these
+//! values in parsed form, and the `_with_context` variants of the functions
+//! use the values of the context instead of the corresponding fields of their
+//! input.
+//!
+//! The intended usage is
+//! - create a context with `wallet_context_create` when the parameters are
+//!   known, e.g., at startup,
+//! - call the `_with_context` variants of the functions with it,
+//! - free the context with `wallet_context_free` when it is no longer needed.
+use crate::{parse_input, try_get, ExampleCurve, TABLE_BYTES};
+use anyhow::bail;
+use crypto_common::*;
+use elgamal::BabyStepGiantStep;
+use id::types::*;
+use pairing::bls12_381::Bls12;
+use serde_json::Value;
+use std::{borrow::Cow, collections::BTreeMap, ffi::CStr, io::Cursor};
+
+/// The parsed parameters of a wallet context.
+pub struct ContextData {
+    pub global:    GlobalContext<ExampleCurve>,
+    pub ip_info:   Option<IpInfo<Bls12>>,
+    pub ars_infos: Option<BTreeMap<ArIdentity, ArInfo<ExampleCurve>>>,
+    pub table:     BabyStepGiantStep<ExampleCurve>,
+}
+
+/// An opaque handle to the parameters of a wallet context. If the input of
+/// `wallet_context_create` is invalid the handle holds the error instead, and
+/// every function called with it fails with that error. This way errors are
+/// reported the same way as all other errors, i.e., via the `success` flag and
+/// the response string.
+pub struct WalletContext {
+    data: Result<ContextData, String>,
+}
+
+impl WalletContext {
+    /// The parameters of the context, or the error of creating it.
+    pub fn data(&self) -> anyhow::Result<&ContextData> {
+        match &self.data {
+            Ok(data) => Ok(data),
+            Err(e) => bail!("Invalid wallet context: {}", e),
+        }
+    }
+}
+
+impl ContextData {
+    /// Parse the parameters from the input of `wallet_context_create`. The
+    /// global context is required, and the identity provider and anonymity
+    /// revokers are optional. If the optional field `precompute` is `true`, a
+    /// table for the on-chain commitment key is computed, which is worthwhile
+    /// if the context is used for creating several credentials.
+    pub fn from_input(input: &str) -> anyhow::Result<Self> {
+        let v = parse_input(input)?;
+        // Only the generators for credential deployments and encrypted transfers
+        // are decoded. The others are decoded if an identity request needs them.
+        let mut global = match v.get("global") {
+            Some(global) => {
+                GlobalContext::<ExampleCurve>::deserialize_lazy(global, MIN_BULLETPROOF_GENERATORS)?
+            }
+            None => bail!("Field global not present, but should be."),
+        };
+        let precompute = match v.get("precompute") {
+            Some(_) => try_get(&v, "precompute")?,
+            None => false,
+        };
+        if precompute {
+            global.precompute();
+        }
+        let ip_info = match v.get("ipInfo") {
+            Some(_) => Some(try_get(&v, "ipInfo")?),
+            None => None,
+        };
+        let ars_infos = match v.get("arsInfos") {
+            Some(_) => Some(try_get(&v, "arsInfos")?),
+            None => None,
+        };
+        let table = (&mut Cursor::new(TABLE_BYTES)).get()?;
+        Ok(ContextData {
+            global,
+            ip_info,
+            ars_infos,
+            table,
+        })
+    }
+}
+
+/// The global context of the wallet context if given, and otherwise the
+/// `global` field of the input.
+pub(crate) fn get_global<'a>(
+    v: &Value,
+    ctx: Option<&'a ContextData>,
+) -> anyhow::Result<Cow<'a, GlobalContext<ExampleCurve>>> {
+    match ctx {
+        Some(ctx) => Ok(Cow::Borrowed(&ctx.global)),
+        None => Ok(Cow::Owned(try_get(v, "global")?)),
+    }
+}
+
+/// The given global context with at least the first `n` bulletproof generators
+/// decoded. If the context is borrowed and has fewer decoded generators they
+/// are decoded in a copy.
+pub(crate) fn with_generators<'a>(
+    mut global: Cow<'a, GlobalContext<ExampleCurve>>,
+    n: usize,
+) -> anyhow::Result<Cow<'a, GlobalContext<ExampleCurve>>> {
+    if global.bulletproof_generators().G_H.len() < n {
+        global.to_mut().ensure_bulletproof_generators(n)?;
+    }
+    Ok(global)
+}
+
+/// The value of the wallet context if it has one, and otherwise the value of
+/// the field `name` of the parsed input, which must then be present.
+pub(crate) fn context_or_input<'a, T: Clone>(
+    from_context: Option<&'a T>,
+    from_input: Option<T>,
+    name: &str,
+) -> anyhow::Result<Cow<'a, T>> {
+    match (from_context, from_input) {
+        (Some(value), _) => Ok(Cow::Borrowed(value)),
+        (None, Some(value)) => Ok(Cow::Owned(value)),
+        (None, None) => bail!("Field {} not present, but should be.", name),
+    }
+}
+
+/// Create a wallet context from a NUL-terminated UTF8-string with a JSON
+/// object with the field `global`, and optionally the fields `ipInfo` and
+/// `arsInfos`. The returned context must be freed by the caller by calling
+/// `wallet_context_free`.
+///
+/// If the input cannot be parsed every function called with the context
+/// fails with the error. A NULL pointer is returned only if the input is a
+/// NULL pointer or not a valid UTF8 string.
+///
+/// # Safety
+/// The input pointer must point to a null-terminated buffer, otherwise this
+/// function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn wallet_context_create(input_ptr: *const c_char) -> *mut WalletContext {
+    if input_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_str = match CStr::from_ptr(input_ptr).to_str() {
+        Ok(s) => s,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let data = ContextData::from_input(input_str).map_err(|e| e.to_string());
+    Box::into_raw(Box::new(WalletContext { data }))
+}
+
+/// # Safety
+/// This function is unsafe in the sense that if the argument pointer was not
+/// obtained from `wallet_context_create` its behaviour is undefined. The
+/// context must not be freed while a function using it is running.
+#[no_mangle]
+pub unsafe fn wallet_context_free(ctx: *mut WalletContext) {
+    if !ctx.is_null() {
+        let _ = Box::from_raw(ctx);
+    }
+}
@@ -0,0 +1,130 @@
+//! Cursors for streaming large responses over FFI.
+//!
+//! Some functions, such as `generate_accounts`, can produce responses that are
+//! several megabytes large. Instead of returning a single string, the cursor
+//! variants of these functions return a handle from which the response can be
+//! fetched one item at a time. Each item is a complete JSON value, namely one
+//! element of the array that the non-cursor variant would return.
+//!
+//! The intended usage is
+//! - open a cursor, e.g., with `generate_accounts_cursor`,
+//! - repeatedly call `response_cursor_next` until it returns `NULL`,
+//! - free the cursor with `free_response_cursor`.
+use crate::{encode_response, generate_accounts_iter, signal_error};
+use crypto_common::c_char;
+use serde_json::to_string;
+use std::ffi::CStr;
+
+/// An opaque handle to a response that is produced incrementally.
+pub struct ResponseCursor {
+    items: Box<dyn Iterator<Item = anyhow::Result<String>>>,
+}
+
+impl ResponseCursor {
+    /// Make a cursor that produces the JSON serialization of each item.
+    fn from_json_iter<I: Iterator<Item = serde_json::Value> + 'static>(items: I) -> Self {
+        ResponseCursor {
+            items: Box::new(items.map(|v| Ok(to_string(&v)?))),
+        }
+    }
+
+    /// Make a cursor whose only item is the given error. This is used so that
+    /// errors when opening a cursor are reported the same way as all other
+    /// errors, i.e., via the `success` flag and the response string.
+    fn from_error(e: anyhow::Error) -> Self {
+        ResponseCursor {
+            items: Box::new(std::iter::once(Err(e))),
+        }
+    }
+
+    /// Make a cursor from the result of parsing the input.
+    fn from_result<I: Iterator<Item = serde_json::Value> + 'static>(
+        result: anyhow::Result<I>,
+    ) -> Self {
+        match result {
+            Ok(items) => Self::from_json_iter(items),
+            Err(e) => Self::from_error(e),
+        }
+    }
+}
+
+/// Same as `generate_accounts`, but return a cursor from which the accounts
+/// can be fetched one by one via `response_cursor_next`. The accounts are
+/// generated as they are fetched. The returned cursor must be freed by the
+/// caller by calling `free_response_cursor`.
+///
+/// If the input cannot be parsed the first call to `response_cursor_next`
+/// returns the error. A NULL pointer is returned only if the input is a NULL
+/// pointer or not a valid UTF8 string.
+///
+/// # Safety
+/// The input pointer must point to a null-terminated buffer, otherwise this
+/// function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn generate_accounts_cursor(input_ptr: *const c_char) -> *mut ResponseCursor {
+    if input_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_str = match CStr::from_ptr(input_ptr).to_str() {
+        Ok(s) => s,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let cursor = ResponseCursor::from_result(generate_accounts_iter(input_str));
+    Box::into_raw(Box::new(cursor))
+}
+
+/// Fetch the next item from the cursor. The returned string must be freed by
+/// the caller by calling the function 'free_response_string'. When there are
+/// no more items a NULL pointer is returned and the 'success' flag is set to
+/// 1. In case of failure the function returns an error message as the
+/// response, and sets the 'success' flag to 0.
+///
+/// # Safety
+/// The cursor must have been obtained from one of the functions returning a
+/// cursor, and must not have been freed.
+#[no_mangle]
+pub unsafe fn response_cursor_next(cursor: *mut ResponseCursor, success: *mut u8) -> *mut c_char {
+    if cursor.is_null() {
+        return signal_error(success, "Null pointer input.".to_owned());
+    }
+    match (*cursor).items.next() {
+        Some(item) => encode_response(item, success),
+        None => {
+            *success = 1;
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// This function is unsafe in the sense that if the argument pointer was not
+/// obtained from one of the functions returning a cursor its behaviour is
+/// undefined.
+#[no_mangle]
+pub unsafe fn free_response_cursor(cursor: *mut ResponseCursor) {
+    if !cursor.is_null() {
+        let _ = Box::from_raw(cursor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let mut cursor =
+            ResponseCursor::from_result(Ok(vec![json!(1), json!({"a": 2})].into_iter()));
+        assert_eq!(cursor.items.next().map(|r| r.ok()), Some(Some("1".into())));
+        assert_eq!(
+            cursor.items.next().map(|r| r.ok()),
+            Some(Some(r#"{"a":2}"#.into()))
+        );
+        assert!(cursor.items.next().is_none());
+
+        let mut cursor =
+            ResponseCursor::from_result::<std::vec::IntoIter<_>>(Err(anyhow::anyhow!("Failure.")));
+        assert!(matches!(cursor.items.next(), Some(Err(_))));
+        assert!(cursor.items.next().is_none());
+    }
+}
@@ -0,0 +1,449 @@
+//! Decoding of account transactions and block items.
+//!
+//! The payload of an account transaction starts with a tag that determines its
+//! type, see [transactions::tags]. Payloads are decoded as a [Payload], which
+//! is then described in JSON. Which payload types exist depends on the
+//! protocol version of the chain, e.g., transfers with memos only exist from
+//! protocol version 2, so payloads are decoded for a given protocol version and
+//! types that do not exist in it are rejected.
+use crate::{
+    parse_input, try_get,
+    unsigned_transaction::{parse_transaction_body, ACCOUNT_TRANSACTION_TAG},
+};
+use anyhow::{bail, ensure};
+use crypto_common::{
+    types::{Amount, TransactionSignature},
+    *,
+};
+use id::{constants::AttributeKind, types::*};
+use pairing::bls12_381::{Bls12, G1};
+use serde_json::{to_string, to_value, Value};
+use std::io::Cursor;
+use transactions::{tags, EncodedPayload, Payload, Schedule, TransactionHeader};
+
+/// Version of the protocol of the chain.
+pub type ProtocolVersion = u8;
+
+/// The latest protocol version known to the decoder.
+pub const LATEST_PROTOCOL_VERSION: ProtocolVersion = 4;
+
+/// The first protocol version in which payloads with the given tag exist, or
+/// `None` if the tag is unknown.
+pub fn first_protocol_version(tag: u8) -> Option<ProtocolVersion> {
+    match tag {
+        tags::INIT_CONTRACT
+        | tags::UPDATE
+        | tags::TRANSFER
+        | tags::UPDATE_CREDENTIAL_KEYS
+        | tags::ENCRYPTED_AMOUNT_TRANSFER
+        | tags::TRANSFER_TO_ENCRYPTED
+        | tags::TRANSFER_TO_PUBLIC
+        | tags::TRANSFER_WITH_SCHEDULE => Some(1),
+        tags::TRANSFER_WITH_MEMO
+        | tags::ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO
+        | tags::TRANSFER_WITH_SCHEDULE_AND_MEMO => Some(2),
+        tags::CONFIGURE_BAKER | tags::CONFIGURE_DELEGATION => Some(4),
+        _ => None,
+    }
+}
+
+/// Parse a payload, including its tag. This fails if the payload type does
+/// not exist in the given protocol version, or there are trailing bytes.
+pub fn parse_payload(protocol_version: ProtocolVersion, bytes: &[u8]) -> ParseResult<Payload> {
+    let payload = EncodedPayload::from_bytes(bytes.to_vec());
+    let tag = match payload.tag() {
+        Some(tag) => tag,
+        None => bail!("The payload is empty."),
+    };
+    match first_protocol_version(tag) {
+        Some(since) if since <= protocol_version => payload.decode(),
+        _ => bail!(
+            "Unknown payload type {} in protocol version {}.",
+            tag,
+            protocol_version
+        ),
+    }
+}
+
+/// The releases of a schedule, each with its timestamp and amount.
+fn describe_schedule(schedule: &Schedule) -> Value {
+    schedule
+        .as_ref()
+        .iter()
+        .map(|(timestamp, amount)| json!({ "timestamp": timestamp, "amount": amount }))
+        .collect()
+}
+
+/// The JSON description of a payload: an object with the name of the payload
+/// type in the field `type`, see [tags::name], and the fields of the payload.
+/// Contract parameters are hex encoded.
+pub fn describe_payload(payload: &Payload) -> ParseResult<Value> {
+    let mut value = match payload {
+        Payload::InitContract {
+            amount,
+            mod_ref,
+            init_name,
+            param,
+        } => json!({
+            "amount": amount,
+            "moduleRef": mod_ref,
+            "initName": init_name,
+            "parameter": hex::encode(param)
+        }),
+        Payload::Update {
+            amount,
+            address,
+            receive_name,
+            message,
+        } => json!({
+            "amount": amount,
+            "address": { "index": address.index, "subindex": address.subindex },
+            "receiveName": receive_name,
+            "parameter": hex::encode(message)
+        }),
+        Payload::Transfer { to_address, amount } => json!({ "to": to_address, "amount": amount }),
+        Payload::TransferWithMemo {
+            to_address,
+            memo,
+            amount,
+        } => json!({ "to": to_address, "memo": memo, "amount": amount }),
+        Payload::TransferWithSchedule {
+            to_address,
+            schedule,
+        } => json!({ "to": to_address, "schedule": describe_schedule(schedule) }),
+        Payload::TransferWithScheduleAndMemo {
+            to_address,
+            memo,
+            schedule,
+        } => json!({ "to": to_address, "memo": memo, "schedule": describe_schedule(schedule) }),
+        Payload::UpdateCredentialKeys { cred_id, keys } => {
+            json!({ "credId": base16_encode_string(cred_id), "keys": keys })
+        }
+        Payload::EncryptedAmountTransfer { to_address, data } => {
+            json!({ "to": to_address, "data": data })
+        }
+        Payload::EncryptedAmountTransferWithMemo {
+            to_address,
+            memo,
+            data,
+        } => json!({ "to": to_address, "memo": memo, "data": data }),
+        Payload::TransferToEncrypted { amount } => json!({ "amount": amount }),
+        Payload::TransferToPublic { data } => json!({ "data": data }),
+        Payload::ConfigureBaker { data } => to_value(data)?,
+        Payload::ConfigureDelegation { data } => to_value(data)?,
+    };
+    value["type"] = json!(tags::name(payload.tag()));
+    Ok(value)
+}
+
+/// Decode a payload, including its tag, and describe it in JSON, see
+/// [parse_payload] and [describe_payload].
+pub fn decode_payload(protocol_version: ProtocolVersion, bytes: &[u8]) -> ParseResult<Value> {
+    describe_payload(&parse_payload(protocol_version, bytes)?)
+}
+
+/// The JSON description of an account transaction with the given header and
+/// payload, as returned by [decode_account_transaction].
+fn describe_transaction(header: &TransactionHeader, payload: &Payload) -> ParseResult<Value> {
+    Ok(json!({
+        "sender": header.sender,
+        "nonce": header.nonce,
+        "energy": header.energy,
+        "expiry": header.expiry,
+        "payload": describe_payload(payload)?,
+    }))
+}
+
+/// Decode the header and payload of an account transaction, i.e., the bytes
+/// whose hash is signed.
+pub fn decode_account_transaction(
+    protocol_version: ProtocolVersion,
+    bytes: &[u8],
+) -> ParseResult<Value> {
+    let (header, payload) = parse_transaction_body(bytes)?;
+    describe_transaction(&header, &parse_payload(protocol_version, &payload)?)
+}
+
+/// Decode a block item in the encoding of the node, i.e., a tag followed by
+/// either a signed account transaction or a credential deployment.
+pub fn decode_block_item(protocol_version: ProtocolVersion, bytes: &[u8]) -> ParseResult<Value> {
+    let mut source = Cursor::new(bytes);
+    match source.get::<u8>()? {
+        ACCOUNT_TRANSACTION_TAG => {
+            let signatures: TransactionSignature = source.get()?;
+            let mut transaction =
+                decode_account_transaction(protocol_version, &bytes[source.position() as usize..])?;
+            transaction["type"] = json!("accountTransaction");
+            transaction["signatures"] = to_value(&signatures)?;
+            Ok(transaction)
+        }
+        CREDENTIAL_DEPLOYMENT_TAG => {
+            let message: AccountCredentialMessage<Bls12, G1, AttributeKind> = source.get()?;
+            ensure!(
+                source.position() == bytes.len() as u64,
+                "Trailing bytes after the credential deployment."
+            );
+            let mut value = to_value(&message)?;
+            value["type"] = json!("credentialDeployment");
+            Ok(value)
+        }
+        tag => bail!("Unknown block item tag {}.", tag),
+    }
+}
+
+/// Decode a hex encoded transaction or block item. The input has the field
+/// `transaction` with the header and payload of an account transaction, as
+/// returned by the functions that create transactions, or the field
+/// `blockItem` with a block item. The optional field `protocolVersion`
+/// defaults to the latest version.
+pub fn decode_transaction_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let protocol_version = match v.get("protocolVersion") {
+        Some(_) => try_get(&v, "protocolVersion")?,
+        None => LATEST_PROTOCOL_VERSION,
+    };
+    let response = if v.get("blockItem").is_some() {
+        let bytes = hex::decode(try_get::<String>(&v, "blockItem")?)?;
+        decode_block_item(protocol_version, &bytes)?
+    } else {
+        let bytes = hex::decode(try_get::<String>(&v, "transaction")?)?;
+        decode_account_transaction(protocol_version, &bytes)?
+    };
+    Ok(to_string(&response)?)
+}
+
+/// The fields that are shown to the user before signing a payload: the
+/// receiver, the memo, and the public amount that is sent. The total of a
+/// transfer with schedule is the sum of the releases. Fields that do not apply
+/// to the payload type, such as the amount of an encrypted transfer, are
+/// omitted.
+fn summarize_payload(payload: &Payload) -> anyhow::Result<Value> {
+    let (recipient, memo, amount) = match payload {
+        Payload::InitContract { amount, .. } => (None, None, Some(*amount)),
+        Payload::Update { amount, .. } => (None, None, Some(*amount)),
+        Payload::Transfer { to_address, amount } => (Some(to_address), None, Some(*amount)),
+        Payload::TransferWithMemo {
+            to_address,
+            memo,
+            amount,
+        } => (Some(to_address), Some(memo), Some(*amount)),
+        Payload::TransferWithSchedule {
+            to_address,
+            schedule,
+        } => (Some(to_address), None, Some(schedule_total(schedule)?)),
+        Payload::TransferWithScheduleAndMemo {
+            to_address,
+            memo,
+            schedule,
+        } => (
+            Some(to_address),
+            Some(memo),
+            Some(schedule_total(schedule)?),
+        ),
+        Payload::UpdateCredentialKeys { .. } => (None, None, None),
+        Payload::EncryptedAmountTransfer { to_address, .. } => (Some(to_address), None, None),
+        Payload::EncryptedAmountTransferWithMemo {
+            to_address, memo, ..
+        } => (Some(to_address), Some(memo), None),
+        Payload::TransferToEncrypted { amount } => (None, None, Some(*amount)),
+        Payload::TransferToPublic { data } => (None, None, Some(data.transfer_amount)),
+        Payload::ConfigureBaker { .. } => (None, None, None),
+        Payload::ConfigureDelegation { .. } => (None, None, None),
+    };
+    let mut summary = json!({});
+    if let Some(recipient) = recipient {
+        summary["recipient"] = to_value(recipient)?;
+    }
+    if let Some(memo) = memo {
+        summary["memo"] = to_value(memo)?;
+    }
+    if let Some(amount) = amount {
+        summary["amount"] = to_value(amount)?;
+    }
+    Ok(summary)
+}
+
+/// The total amount of the releases of a schedule.
+fn schedule_total(schedule: &Schedule) -> anyhow::Result<Amount> {
+    let mut total = Amount::from(0);
+    for (_, amount) in schedule.as_ref().iter() {
+        total = match total + *amount {
+            Some(total) => total,
+            None => bail!("The total amount of the schedule overflows."),
+        };
+    }
+    Ok(total)
+}
+
+/// Decode a hex encoded account transaction as returned by the functions that
+/// create transactions, and add the fields that are shown to the user before
+/// signing, see [summarize_payload]. The input is the hex string itself, not
+/// JSON. The transaction is decoded with the latest protocol version.
+pub fn parse_transaction_aux(input: &str) -> anyhow::Result<String> {
+    let bytes = hex::decode(input.trim())?;
+    let (header, payload) = parse_transaction_body(&bytes)?;
+    let payload = parse_payload(LATEST_PROTOCOL_VERSION, &payload)?;
+    let mut transaction = describe_transaction(&header, &payload)?;
+    transaction["transactionType"] = json!(tags::name(payload.tag()));
+    if let Value::Object(summary) = summarize_payload(&payload)? {
+        for (field, value) in summary {
+            transaction[field] = value;
+        }
+    }
+    Ok(to_string(&transaction)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::{
+        CredentialIndex, KeyIndex, KeyPair, Memo, Timestamp, TransactionTime,
+    };
+    use curve_arithmetic::Curve;
+    use std::{collections::BTreeMap, convert::TryFrom};
+    use transactions::{delegation::ConfigureDelegationPayload, transaction_body, PayloadLike};
+
+    fn transfer_body(payload: &[u8]) -> Vec<u8> {
+        let payload = EncodedPayload::from_bytes(payload.to_vec());
+        let header = TransactionHeader::new(
+            AccountAddress::new(&G1::one_point()),
+            1,
+            1000,
+            TransactionTime::from_seconds(1_700_000_000),
+            &payload,
+        );
+        transaction_body(&header, &payload)
+    }
+
+    #[test]
+    fn test_decode_transactions() {
+        let to = AccountAddress::new(&G1::one_point().double_point());
+        let mut payload = Vec::new();
+        payload.put(&tags::TRANSFER);
+        payload.put(&to);
+        payload.put(&Amount::from(1234));
+        let decoded = decode_account_transaction(1, &transfer_body(&payload))
+            .expect("Transfer should decode.");
+        assert_eq!(decoded["nonce"], 1);
+        assert_eq!(decoded["payload"]["type"], "transfer");
+        assert_eq!(decoded["payload"]["to"], json!(to));
+        assert_eq!(decoded["payload"]["amount"], "1234");
+
+        // Transfers with memo only exist from protocol version 2.
+        let mut payload = Vec::new();
+        payload.put(&tags::TRANSFER_WITH_MEMO);
+        payload.put(&to);
+        payload.put(&Memo {
+            memo: vec![1, 2, 3],
+        });
+        payload.put(&Amount::from(1234));
+        let body = transfer_body(&payload);
+        assert!(decode_account_transaction(1, &body).is_err());
+        let decoded = decode_account_transaction(2, &body).expect("Transfer should decode.");
+        assert_eq!(decoded["payload"]["type"], "transferWithMemo");
+
+        // Block items with signatures.
+        let mut item = vec![ACCOUNT_TRANSACTION_TAG];
+        let kp = KeyPair::generate(&mut rand::thread_rng());
+        let mut sigs = BTreeMap::new();
+        sigs.insert(KeyIndex(0), kp.sign(&body));
+        let mut signatures = BTreeMap::new();
+        signatures.insert(CredentialIndex { index: 0 }, sigs);
+        item.put(&TransactionSignature { signatures });
+        item.extend_from_slice(&body);
+        let decoded = decode_block_item(2, &item).expect("Block item should decode.");
+        assert_eq!(decoded["type"], "accountTransaction");
+        assert_eq!(decoded["payload"]["type"], "transferWithMemo");
+
+        // Payload sizes and trailing bytes are checked.
+        let mut body = transfer_body(&payload);
+        body.push(0);
+        assert!(decode_account_transaction(2, &body).is_err());
+        assert!(decode_payload(2, &[tags::TRANSFER]).is_err());
+        assert!(decode_payload(2, &[255u8]).is_err());
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let to = AccountAddress::new(&G1::one_point().double_point());
+        let payload = Payload::TransferWithScheduleAndMemo {
+            to_address: to,
+            memo:       Memo {
+                memo: vec![1, 2, 3],
+            },
+            schedule:   Schedule::try_from(vec![
+                (Timestamp { millis: 1 }, Amount::from(10)),
+                (Timestamp { millis: 2 }, Amount::from(20)),
+            ])
+            .expect("The schedule is short."),
+        }
+        .encode();
+        let body = hex::encode(transfer_body(payload.as_ref()));
+        let parsed: Value =
+            serde_json::from_str(&parse_transaction_aux(&body).expect("Transaction should parse."))
+                .unwrap();
+        assert_eq!(parsed["transactionType"], "transferWithScheduleAndMemo");
+        assert_eq!(parsed["recipient"], json!(to));
+        assert_eq!(parsed["amount"], "30");
+        assert_eq!(
+            parsed["sender"],
+            json!(AccountAddress::new(&G1::one_point()))
+        );
+        assert_eq!(parsed["expiry"], 1_700_000_000u64);
+        assert!(parsed.get("memo").is_some());
+
+        let payload = Payload::TransferToEncrypted {
+            amount: Amount::from(5),
+        }
+        .encode();
+        let parsed: Value = serde_json::from_str(
+            &parse_transaction_aux(&hex::encode(transfer_body(payload.as_ref()))).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(parsed["amount"], "5");
+        assert!(parsed.get("recipient").is_none());
+
+        // Configuring delegation has no receiver or amount to show.
+        let payload = Payload::ConfigureDelegation {
+            data: ConfigureDelegationPayload {
+                restake_earnings: Some(true),
+                ..Default::default()
+            },
+        }
+        .encode();
+        let parsed: Value = serde_json::from_str(
+            &parse_transaction_aux(&hex::encode(transfer_body(payload.as_ref()))).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(parsed["transactionType"], "configureDelegation");
+        assert!(parsed.get("amount").is_none());
+
+        // Payloads of unknown types are rejected.
+        assert!(parse_transaction_aux(&hex::encode(transfer_body(&[255u8]))).is_err());
+        assert!(parse_transaction_aux("not hex").is_err());
+    }
+
+    #[test]
+    fn test_decode_update() {
+        let update = |name: &[u8]| {
+            let mut payload = Vec::new();
+            payload.put(&tags::UPDATE);
+            payload.put(&Amount::from(0));
+            payload.put(&7u64);
+            payload.put(&0u64);
+            payload.put(&(name.len() as u16));
+            payload.extend_from_slice(name);
+            payload.put(&2u16);
+            payload.extend_from_slice(&[0xab, 0xcd]);
+            payload
+        };
+        let decoded =
+            decode_payload(1, &update(b"counter.increment")).expect("Update should decode.");
+        assert_eq!(decoded["type"], "update");
+        assert_eq!(decoded["receiveName"], "counter.increment");
+        assert_eq!(decoded["address"]["index"], 7);
+        assert_eq!(decoded["parameter"], "abcd");
+        // Receive names must contain a '.'.
+        assert!(decode_payload(1, &update(b"increment")).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Transactions that delegate stake to a baker pool.
+//!
+//! The configure delegation transaction both starts delegation from an
+//! account and updates it. As for configure baker, each field of the payload
+//! is optional, and a bitmap at the start of the payload records which fields
+//! are present. Starting delegation requires all the fields.
+use crate::{make_signatures, make_transaction_bytes, parse_transaction_input, TransferContext};
+use anyhow::ensure;
+use crypto_common::*;
+use id::cost;
+use serde_json::{from_value, to_string};
+use transactions::{delegation::ConfigureDelegationPayload, Payload, PayloadLike};
+
+/// Create a configure delegation transaction. Besides the fields of the
+/// transfer context the input has the fields of [ConfigureDelegationPayload],
+/// all optional, but at least one must be given.
+pub(crate) fn create_configure_delegation_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+
+    let ctx: TransferContext = from_value(v.clone())?;
+    let payload: ConfigureDelegationPayload = from_value(v)?;
+    ensure!(
+        payload.bitmap() != 0,
+        "At least one field of the delegation must be configured."
+    );
+
+    let (hash, body) = {
+        let payload_bytes = Payload::ConfigureDelegation { data: payload }.encode();
+
+        make_transaction_bytes(
+            &ctx.header(cost::CONFIGURE_DELEGATION_ENERGY, &payload_bytes)?,
+            &payload_bytes,
+        )
+    };
+
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
+
+    let response = json!({
+        "signatures": signatures,
+        "transaction": hex::encode(&body),
+    });
+
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::decode_payload;
+    use crypto_common::types::Amount;
+    use serde_json::Value;
+    use std::io::Cursor;
+    use transactions::delegation::DelegationTarget;
+
+    #[test]
+    fn test_configure_delegation_payload() {
+        let payload = ConfigureDelegationPayload {
+            capital:           Some(Amount::from(1000)),
+            restake_earnings:  Some(false),
+            delegation_target: Some(DelegationTarget::Baker { baker_id: 42 }),
+        };
+        let bytes = Payload::ConfigureDelegation { data: payload }.encode();
+        assert_eq!(bytes.size(), 1 + 2 + 8 + 1 + 9);
+        let decoded: Value = decode_payload(4, bytes.as_ref()).expect("Payload should decode.");
+        assert_eq!(decoded["type"], "configureDelegation");
+        assert_eq!(decoded["capital"], "1000");
+        assert_eq!(decoded["restakeEarnings"], false);
+        assert_eq!(
+            decoded["delegationTarget"],
+            json!({"delegateType": "Baker", "bakerId": 42})
+        );
+        assert!(decode_payload(3, bytes.as_ref()).is_err());
+
+        let payload: ConfigureDelegationPayload =
+            from_value(json!({"delegationTarget": {"delegateType": "Passive"}}))
+                .expect("Payload should parse.");
+        assert_eq!(to_bytes(&payload), vec![0, 4, 0]);
+        // Unknown fields are rejected.
+        let unknown = from_bytes::<ConfigureDelegationPayload, _>(&mut Cursor::new(&[0u8, 8]));
+        assert!(unknown.is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Tracking the encrypted balance of an account in the wallet.
+//!
+//! To make a transfer from the encrypted balance the wallet needs the
+//! aggregated self amount and incoming amounts it uses as input, their
+//! decrypted value, and the aggregation index. Instead of tracking these, the
+//! wallet can store the balance returned by `update_encrypted_balance`, and
+//! pass it to `create_encrypted_transfer_from_balance` and
+//! `create_sec_to_pub_transfer_from_balance`, which compute the input amount
+//! and return the balance after the transfer. The balance is a JSON object
+//! that the wallet stores as is, see [EncryptedBalance].
+use crate::{
+    context::get_global, create_encrypted_transfer_aux, create_sec_to_pub_transfer_aux,
+    parse_input, table::with_decryption_table, try_get, ExampleCurve,
+};
+use anyhow::{bail, ensure};
+use crypto_common::{types::Amount, *};
+use encrypted_transfers::{
+    decrypt_amount,
+    state::EncryptedBalance,
+    types::{EncryptedAmount, EncryptedAmountIndex},
+};
+use serde_json::{from_str, to_string, Value};
+
+/// An incoming amount in the input of `update_encrypted_balance`.
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncomingAmount {
+    index:            EncryptedAmountIndex,
+    encrypted_amount: EncryptedAmount<ExampleCurve>,
+    /// The decrypted value. If absent the amount is decrypted with the
+    /// `encryptionSecretKey` of the input.
+    amount:           Option<Amount>,
+}
+
+/// Update the encrypted balance of an account. The input has the optional
+/// fields
+/// - `balance`, the balance to update. If absent the update starts from the
+///   balance of a new account, which requires the `global` context.
+/// - `incomingAmounts`, the new incoming amounts in the order of their indices.
+///   Amounts with indices that are already part of the balance are skipped.
+/// - `encryptionSecretKey`, used to decrypt the incoming amounts without an
+///   `amount`.
+/// - `addedToSelfAmount`, the amounts of finalized transfers from the public
+///   balance, which requires the `global` context.
+pub fn update_encrypted_balance_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let mut balance = match v.get("balance") {
+        Some(_) => try_get(&v, "balance")?,
+        None => EncryptedBalance::empty(&get_global(&v, None)?),
+    };
+
+    let incoming: Vec<IncomingAmount> = match v.get("incomingAmounts") {
+        Some(_) => try_get(&v, "incomingAmounts")?,
+        None => Vec::new(),
+    };
+    for incoming in incoming {
+        if incoming.index.index < balance.next_index().index {
+            continue;
+        }
+        let amount = match incoming.amount {
+            Some(amount) => amount,
+            None => {
+                let secret = try_get(&v, "encryptionSecretKey")?;
+                with_decryption_table(|table| {
+                    decrypt_amount(table, &secret, &incoming.encrypted_amount)
+                })?
+            }
+        };
+        balance.add_incoming(incoming.index, incoming.encrypted_amount, amount)?;
+    }
+
+    if v.get("addedToSelfAmount").is_some() {
+        let added: Vec<Amount> = try_get(&v, "addedToSelfAmount")?;
+        let global_context = get_global(&v, None)?;
+        for amount in added {
+            balance.add_to_self_amount(&global_context, amount)?;
+        }
+    }
+
+    let total = match balance.total() {
+        Some(total) => total,
+        None => bail!("The total of the encrypted balance overflows."),
+    };
+    let response = json!({
+        "balance": balance,
+        "total": total,
+    });
+    Ok(to_string(&response)?)
+}
+
+/// Create an encrypted transfer from the given `balance`. The input is as for
+/// `create_encrypted_transfer`, with the `balance` instead of the
+/// `inputEncryptedAmount`, and the output additionally contains the `balance`
+/// after the transfer.
+pub fn create_encrypted_transfer_from_balance_aux(input: &str) -> anyhow::Result<String> {
+    transfer_from_balance(input, create_encrypted_transfer_aux)
+}
+
+/// Same as [create_encrypted_transfer_from_balance_aux], but for
+/// `create_sec_to_pub_transfer`.
+pub fn create_sec_to_pub_transfer_from_balance_aux(input: &str) -> anyhow::Result<String> {
+    transfer_from_balance(input, create_sec_to_pub_transfer_aux)
+}
+
+/// Compute the input amount of a transfer from the balance, create the
+/// transfer with `create`, and add the balance after the transfer to its
+/// response.
+fn transfer_from_balance(
+    input: &str,
+    create: fn(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let mut v = parse_input(input)?;
+    let mut balance: EncryptedBalance<ExampleCurve> = try_get(&v, "balance")?;
+    let amount: Amount = try_get(&v, "amount")?;
+    let input_amount = balance.input_amount(amount)?;
+    match v.as_object_mut() {
+        Some(fields) => {
+            ensure!(
+                !fields.contains_key("inputEncryptedAmount"),
+                "'inputEncryptedAmount' is computed from 'balance' and cannot be given."
+            );
+            fields.insert("inputEncryptedAmount".to_owned(), json!(input_amount));
+        }
+        None => bail!("The input must be a JSON object."),
+    }
+
+    let mut response: Value = from_str(&create(&to_string(&v)?)?)?;
+    let remaining = try_get(&response, "remaining")?;
+    balance.spend(&input_amount, amount, remaining)?;
+    response["balance"] = json!(balance);
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encrypted_transfers::encrypt_amount_with_fixed_randomness;
+    use id::test::read_golden_bytes;
+    use serde_json::from_slice;
+
+    #[test]
+    fn test_transfer_from_balance() {
+        let mut input: Value = from_slice(&read_golden_bytes(
+            "rust-bins/wallet-notes/files/create_sec_to_pub_transfer-input.json",
+        ))
+        .expect("Input is JSON.");
+        let global = input["global"].clone();
+
+        // Amounts from the public balance are encrypted with zero randomness,
+        // so they can be decrypted with any key.
+        let global_context = get_global(&input, None).unwrap();
+        let incoming = encrypt_amount_with_fixed_randomness(&global_context, Amount::from(3000));
+        let updated: Value = from_str(
+            &update_encrypted_balance_aux(
+                &json!({
+                    "global": global,
+                    "incomingAmounts": [
+                        { "index": 0, "encryptedAmount": incoming, "amount": "3000" },
+                    ],
+                    "addedToSelfAmount": ["2000"],
+                })
+                .to_string(),
+            )
+            .expect("Balance should be updated."),
+        )
+        .unwrap();
+        assert_eq!(updated["total"], json!("5000"));
+
+        let fields = input.as_object_mut().unwrap();
+        fields.remove("inputEncryptedAmount");
+        fields.insert("balance".to_owned(), updated["balance"].clone());
+        fields.insert("amount".to_owned(), json!("4000"));
+        let transfer: Value = from_str(
+            &create_sec_to_pub_transfer_from_balance_aux(&input.to_string())
+                .expect("Transfer should be created."),
+        )
+        .unwrap();
+        assert_eq!(transfer["balance"]["startIndex"], json!(1));
+        assert_eq!(transfer["balance"]["selfAmountValue"], json!("1000"));
+        assert_eq!(transfer["balance"]["selfAmount"], transfer["remaining"]);
+
+        input["amount"] = json!("6000");
+        assert!(create_sec_to_pub_transfer_from_balance_aux(&input.to_string()).is_err());
+    }
+}
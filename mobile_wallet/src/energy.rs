@@ -0,0 +1,179 @@
+//! Estimates of the energy of account transactions.
+//!
+//! The functions that create transactions compute the energy of the header
+//! themselves, but wallets also need the cost of a transaction before creating
+//! it, e.g., to show the fee to the user. The energy of a transaction is the
+//! cost of checking its header and signatures, which depends on the size of the
+//! transaction and the number of signatures, plus the cost of executing the
+//! payload, which depends on its type. The costs are those of [id::cost], which
+//! match what the node charges.
+use crate::{parse_input, try_get};
+use anyhow::bail;
+use crypto_common::*;
+use id::cost;
+use serde_json::{to_string, Value};
+use transactions::tags;
+
+/// The parameters that the execution cost of some payload types depends on.
+/// Only the parameters of the given type are used.
+#[derive(SerdeDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CostParameters {
+    /// The number of releases of a transfer with schedule.
+    pub num_releases:     Option<usize>,
+    /// The number of credentials of the account whose credential keys are
+    /// updated.
+    pub num_credentials:  Option<usize>,
+    /// The number of new keys of the credential.
+    pub num_keys:         Option<usize>,
+    /// Whether a configure baker transaction sets the keys of the baker.
+    pub with_keys:        Option<bool>,
+    /// The cost of initializing or updating a smart contract, which is only
+    /// known by running it.
+    pub execution_energy: Option<u64>,
+}
+
+/// The parameter with the given name, which the cost of the payload type
+/// depends on.
+fn required<A>(parameter: Option<A>, name: &str) -> anyhow::Result<A> {
+    match parameter {
+        Some(parameter) => Ok(parameter),
+        None => bail!("The cost of the transaction depends on '{}'.", name),
+    }
+}
+
+/// The cost of executing the payload of a transaction of the type with the
+/// given tag, see [transactions::tags]. Transfers with memos cost the same as
+/// those without. This fails if the type is unknown, or a parameter its cost
+/// depends on is missing.
+pub fn execution_energy(tag: u8, parameters: &CostParameters) -> anyhow::Result<u64> {
+    let energy = match tag {
+        tags::TRANSFER | tags::TRANSFER_WITH_MEMO => cost::SIMPLE_TRANSFER_ENERGY,
+        tags::TRANSFER_WITH_SCHEDULE | tags::TRANSFER_WITH_SCHEDULE_AND_MEMO => {
+            cost::scheduled_transfer_energy(required(parameters.num_releases, "numReleases")?)
+        }
+        tags::ENCRYPTED_AMOUNT_TRANSFER | tags::ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO => {
+            cost::ENCRYPTED_TRANSFER_ENERGY
+        }
+        tags::TRANSFER_TO_ENCRYPTED => cost::TRANSFER_TO_ENCRYPTED_ENERGY,
+        tags::TRANSFER_TO_PUBLIC => cost::TRANSFER_TO_PUBLIC_ENERGY,
+        tags::UPDATE_CREDENTIAL_KEYS => cost::update_credential_keys_energy(
+            required(parameters.num_credentials, "numCredentials")?,
+            required(parameters.num_keys, "numKeys")?,
+        ),
+        tags::CONFIGURE_BAKER => {
+            if required(parameters.with_keys, "withKeys")? {
+                cost::CONFIGURE_BAKER_WITH_KEYS_ENERGY
+            } else {
+                cost::CONFIGURE_BAKER_WITHOUT_KEYS_ENERGY
+            }
+        }
+        tags::CONFIGURE_DELEGATION => cost::CONFIGURE_DELEGATION_ENERGY,
+        tags::INIT_CONTRACT | tags::UPDATE => {
+            required(parameters.execution_energy, "executionEnergy")?
+        }
+        _ => bail!("Unknown transaction type {}.", tag),
+    };
+    Ok(energy)
+}
+
+/// The energy of an account transaction, split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnergyEstimate {
+    /// The cost of checking the header and the signatures.
+    pub base_energy:      u64,
+    /// The cost of executing the payload.
+    pub execution_energy: u64,
+    /// The energy to put in the header, the sum of the two.
+    pub energy:           u64,
+}
+
+/// Estimate the energy of a transaction of the type with the given tag, with
+/// the given number of signatures and size of the payload in bytes, including
+/// the tag. This is the energy that the functions creating transactions put
+/// in the header.
+pub fn estimate_transaction_energy(
+    tag: u8,
+    parameters: &CostParameters,
+    num_signatures: u32,
+    payload_size: u64,
+) -> anyhow::Result<EnergyEstimate> {
+    let base_energy = cost::transaction_base_energy(num_signatures, payload_size);
+    let execution_energy = execution_energy(tag, parameters)?;
+    Ok(EnergyEstimate {
+        base_energy,
+        execution_energy,
+        energy: base_energy + execution_energy,
+    })
+}
+
+/// Estimate the energy of the transaction described by the JSON input, see
+/// [estimate_transaction_energy]. The type of the transaction is given by the
+/// name of its payload type, see [transactions::tags::name].
+pub fn estimate_transaction_energy_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = parse_input(input)?;
+    let name: String = try_get(&v, "transactionType")?;
+    let tag = match tags::from_name(&name) {
+        Some(tag) => tag,
+        None => bail!("Unknown transaction type '{}'.", name),
+    };
+    let parameters: CostParameters = serde_json::from_value(v.clone())?;
+    let num_signatures: u32 = try_get(&v, "numSignatures")?;
+    let payload_size: u64 = try_get(&v, "payloadSize")?;
+    let estimate = estimate_transaction_energy(tag, &parameters, num_signatures, payload_size)?;
+    Ok(to_string(&json!({
+        "energy": estimate.energy,
+        "baseEnergy": estimate.base_energy,
+        "executionEnergy": estimate.execution_energy,
+    }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_transaction_energy() {
+        let response = estimate_transaction_energy_aux(
+            &json!({
+                "transactionType": "transferWithMemo",
+                "numSignatures": 1,
+                "payloadSize": 41,
+            })
+            .to_string(),
+        )
+        .expect("The input is valid.");
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["energy"], 501);
+        assert_eq!(response["executionEnergy"], cost::SIMPLE_TRANSFER_ENERGY);
+
+        let schedule = CostParameters {
+            num_releases: Some(3),
+            ..CostParameters::default()
+        };
+        let estimate = estimate_transaction_energy(tags::TRANSFER_WITH_SCHEDULE, &schedule, 2, 100)
+            .expect("The number of releases is given.");
+        assert_eq!(estimate.base_energy, cost::transaction_base_energy(2, 100));
+        assert_eq!(
+            estimate.energy,
+            cost::transaction_energy(2, 100, cost::scheduled_transfer_energy(3))
+        );
+        assert!(estimate_transaction_energy(
+            tags::TRANSFER_WITH_SCHEDULE,
+            &CostParameters::default(),
+            2,
+            100
+        )
+        .is_err());
+
+        assert!(estimate_transaction_energy_aux(
+            &json!({
+                "transactionType": "initContract",
+                "numSignatures": 1,
+                "payloadSize": 100,
+            })
+            .to_string()
+        )
+        .is_err());
+    }
+}
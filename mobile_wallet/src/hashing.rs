@@ -0,0 +1,124 @@
+//! Hashing of transactions before signing.
+//!
+//! By default transactions are hashed using the pure Rust SHA-256
+//! implementation from the `sha2` crate. Integrators that have access to a
+//! faster implementation (e.g., platform crypto libraries) can register it via
+//! [set_transaction_hash_function], after which it is used for hashing all
+//! transactions constructed by this library.
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An implementation of SHA-256 used to compute the hash of a transaction,
+/// which is what is signed by the account keys.
+///
+/// Implementations must compute exactly SHA-256 of the given data, otherwise
+/// the produced signatures will be rejected by the chain.
+pub trait TransactionHasher {
+    /// The resulting 32-byte digest.
+    type Output: AsRef<[u8]>;
+
+    /// Compute the SHA-256 hash of the given data.
+    fn hash(&self, data: &[u8]) -> Self::Output;
+}
+
+/// The default hasher, using the pure Rust implementation of SHA-256.
+/// If the `sha2-asm` feature is enabled then this uses the assembly
+/// implementation where available.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sha256Hasher;
+
+impl TransactionHasher for Sha256Hasher {
+    type Output = [u8; 32];
+
+    fn hash(&self, data: &[u8]) -> Self::Output { Sha256::digest(data).into() }
+}
+
+/// Type of an externally supplied SHA-256 implementation. The function is
+/// given a pointer to the data and its length, and must write exactly 32 bytes
+/// of output to the `out` pointer.
+pub type ExternalHashFunction = extern "C" fn(data: *const u8, len: usize, out: *mut u8);
+
+/// A hasher that delegates to an externally supplied function.
+#[derive(Clone, Copy)]
+pub struct ExternalHasher(ExternalHashFunction);
+
+impl TransactionHasher for ExternalHasher {
+    type Output = [u8; 32];
+
+    fn hash(&self, data: &[u8]) -> Self::Output {
+        let mut out = [0u8; 32];
+        (self.0)(data.as_ptr(), data.len(), out.as_mut_ptr());
+        out
+    }
+}
+
+/// The currently registered external hash function, or 0 if none is
+/// registered.
+static EXTERNAL_HASH_FUNCTION: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the externally registered hasher, if any.
+pub(crate) fn external_hasher() -> Option<ExternalHasher> {
+    let ptr = EXTERNAL_HASH_FUNCTION.load(Ordering::Acquire);
+    if ptr == 0 {
+        None
+    } else {
+        // This is safe since the only values we store are either 0 or
+        // valid function pointers of type ExternalHashFunction.
+        let f = unsafe { std::mem::transmute::<usize, ExternalHashFunction>(ptr) };
+        Some(ExternalHasher(f))
+    }
+}
+
+/// Hash the body of a transaction, i.e., the header followed by the payload,
+/// with the registered hash function if there is one.
+pub(crate) fn hash_transaction_body(body: &[u8]) -> [u8; 32] {
+    match external_hasher() {
+        Some(hasher) => hasher.hash(body),
+        None => Sha256Hasher.hash(body),
+    }
+}
+
+/// Register a SHA-256 implementation to be used for hashing transactions
+/// instead of the builtin one. Passing a NULL pointer restores the default.
+///
+/// # Safety
+/// The supplied function must be safe to call from any thread, and it must
+/// write exactly 32 bytes to its output argument.
+#[no_mangle]
+pub unsafe extern "C" fn set_transaction_hash_function(f: Option<ExternalHashFunction>) {
+    let ptr = match f {
+        Some(f) => f as usize,
+        None => 0,
+    };
+    EXTERNAL_HASH_FUNCTION.store(ptr, Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn builtin_sha256(data: *const u8, len: usize, out: *mut u8) {
+        let data = unsafe { std::slice::from_raw_parts(data, len) };
+        let out = unsafe { std::slice::from_raw_parts_mut(out, 32) };
+        out.copy_from_slice(&Sha256Hasher.hash(data));
+    }
+
+    #[test]
+    fn test_sha256_hasher() {
+        // Test vector for the string "abc" from FIPS 180-2.
+        let expected =
+            hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+                .expect("Valid hex.");
+        assert_eq!(Sha256Hasher.hash(b"abc").as_ref(), &expected[..]);
+    }
+
+    #[test]
+    fn test_external_hasher() {
+        assert!(external_hasher().is_none());
+        unsafe { set_transaction_hash_function(Some(builtin_sha256)) };
+        let hasher = external_hasher().expect("Hasher was just registered.");
+        assert_eq!(hasher.hash(b"abc"), Sha256Hasher.hash(b"abc"));
+        unsafe { set_transaction_hash_function(None) };
+        assert!(external_hasher().is_none());
+    }
+}
@@ -0,0 +1,297 @@
+//! Jobs for running long-running operations in the background.
+//!
+//! Credential creation and generating identity requests can take several
+//! seconds on slower devices. Instead of blocking the calling thread, the
+//! `start_` variants of these functions queue the operation on the thread pool
+//! used for parallel computations (see [crate::threads]), and return the
+//! identifier of the job immediately.
+//!
+//! The intended usage is
+//! - start a job, e.g., with `start_create_credential`,
+//! - call `poll_job` until the job is finished,
+//! - fetch the response with `get_job_result`, which also releases the job.
+//!
+//! A job that is no longer needed can be cancelled with `cancel_job`, after
+//! which its identifier is no longer valid. Cancellable operations stop at the
+//! next phase of proof generation, the others run to completion, and their
+//! result is discarded.
+use crate::{
+    compat, create_credential_cancellable_aux, create_id_request_and_private_data_aux,
+    encode_response, operation::OperationHandle, signal_error, warnings,
+};
+use crypto_common::{c_char, parallel, Zeroize};
+use lazy_static::lazy_static;
+use std::{
+    collections::BTreeMap,
+    ffi::CStr,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+/// An operation that can be run as a job.
+type JobFn = fn(&str, &OperationHandle) -> anyhow::Result<String>;
+
+/// The status of a job, as returned by `poll_job`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum JobStatus {
+    /// There is no job with the given identifier, either because it was never
+    /// started, or because it was cancelled or its result was fetched.
+    Unknown  = 0,
+    /// The job is waiting for a thread to run on.
+    Queued   = 1,
+    /// The job is running.
+    Running  = 2,
+    /// The job is finished, and its result can be fetched.
+    Finished = 3,
+}
+
+enum JobState {
+    Queued,
+    Running,
+    Finished(anyhow::Result<String>),
+}
+
+struct Job {
+    handle: OperationHandle,
+    state:  JobState,
+}
+
+/// The jobs that are not yet released, indexed by their identifiers.
+struct Jobs {
+    next_id: u64,
+    jobs:    BTreeMap<u64, Job>,
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<Jobs> = Mutex::new(Jobs {
+        next_id: 1,
+        jobs:    BTreeMap::new(),
+    });
+}
+
+fn with_jobs<A>(f: impl FnOnce(&mut Jobs) -> A) -> A {
+    f(&mut JOBS
+        .lock()
+        .expect("Jobs do not panic while holding the lock."))
+}
+
+/// Discard the result of a job, overwriting the response since it can contain
+/// secret keys.
+fn discard(result: anyhow::Result<String>) {
+    if let Ok(mut response) = result {
+        response.zeroize();
+    }
+}
+
+/// Queue the operation with the given input, and return the identifier of the
+/// job.
+fn start_job(f: JobFn, input: String) -> u64 {
+    let handle = OperationHandle::new();
+    let job_id = with_jobs(|jobs| {
+        let job_id = jobs.next_id;
+        jobs.next_id += 1;
+        jobs.jobs.insert(job_id, Job {
+            handle: handle.clone(),
+            state:  JobState::Queued,
+        });
+        job_id
+    });
+    parallel::spawn(move || {
+        let still_wanted = with_jobs(|jobs| match jobs.jobs.get_mut(&job_id) {
+            Some(job) => {
+                job.state = JobState::Running;
+                true
+            }
+            None => false,
+        });
+        if !still_wanted {
+            return;
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let (response, warnings) = warnings::collect_warnings(|| f(&input, &handle));
+            response.map(|r| compat::add_warnings(r, &warnings))
+        }))
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("The job failed unexpectedly.")));
+        let unwanted = with_jobs(|jobs| match jobs.jobs.get_mut(&job_id) {
+            Some(job) => {
+                job.state = JobState::Finished(result);
+                None
+            }
+            None => Some(result),
+        });
+        if let Some(result) = unwanted {
+            discard(result);
+        }
+    });
+    job_id
+}
+
+/// The status of the job.
+fn job_status(job_id: u64) -> JobStatus {
+    with_jobs(|jobs| match jobs.jobs.get(&job_id).map(|job| &job.state) {
+        None => JobStatus::Unknown,
+        Some(JobState::Queued) => JobStatus::Queued,
+        Some(JobState::Running) => JobStatus::Running,
+        Some(JobState::Finished(_)) => JobStatus::Finished,
+    })
+}
+
+/// Cancel the job and release it.
+fn cancel(job_id: u64) {
+    if let Some(job) = with_jobs(|jobs| jobs.jobs.remove(&job_id)) {
+        job.handle.cancel();
+        if let JobState::Finished(result) = job.state {
+            discard(result)
+        }
+    }
+}
+
+/// Take the result of the job if it is finished, and release it. Fails if
+/// there is no such job or it is not finished.
+fn take_result(job_id: u64) -> anyhow::Result<anyhow::Result<String>> {
+    with_jobs(|jobs| {
+        match jobs.jobs.get(&job_id).map(|job| &job.state) {
+            None => anyhow::bail!("There is no job with identifier {}.", job_id),
+            Some(JobState::Finished(_)) => {}
+            Some(_) => anyhow::bail!("Job {} is not finished.", job_id),
+        }
+        match jobs.jobs.remove(&job_id).map(|job| job.state) {
+            Some(JobState::Finished(result)) => Ok(result),
+            _ => unreachable!("The job is finished."),
+        }
+    })
+}
+
+/// Start a job running the function on the given input. Returns 0 and sets
+/// the 'success' flag to 0 if the input is not a valid string.
+unsafe fn start(f: JobFn, input_ptr: *const c_char, success: *mut u8) -> u64 {
+    if input_ptr.is_null() {
+        *success = 0;
+        return 0;
+    }
+    match CStr::from_ptr(input_ptr).to_str() {
+        Ok(input) => {
+            *success = 1;
+            start_job(f, input.to_owned())
+        }
+        Err(_) => {
+            *success = 0;
+            0
+        }
+    }
+}
+
+/// Start creating a credential as `create_credential` in the background, and
+/// return the identifier of the job. If the input is not a NUL-terminated
+/// UTF8-string the function returns 0 and sets the 'success' flag to 0.
+///
+/// # Safety
+/// The input pointer must point to a null-terminated buffer, otherwise this
+/// function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn start_create_credential(input_ptr: *const c_char, success: *mut u8) -> u64 {
+    start(create_credential_cancellable_aux, input_ptr, success)
+}
+
+/// Start generating an identity request as
+/// `create_id_request_and_private_data` in the background, and return the
+/// identifier of the job. If the input is not a NUL-terminated UTF8-string the
+/// function returns 0 and sets the 'success' flag to 0.
+///
+/// # Safety
+/// The input pointer must point to a null-terminated buffer, otherwise this
+/// function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn start_create_id_request_and_private_data(
+    input_ptr: *const c_char,
+    success: *mut u8,
+) -> u64 {
+    start(
+        |input, _| create_id_request_and_private_data_aux(input),
+        input_ptr,
+        success,
+    )
+}
+
+/// Return the status of the job: 0 if there is no job with the identifier, 1
+/// if it is queued, 2 if it is running, and 3 if it is finished (see
+/// [JobStatus]).
+#[no_mangle]
+pub extern "C" fn poll_job(job_id: u64) -> u8 { job_status(job_id) as u8 }
+
+/// Cancel the job, and release it. Its result is discarded, and the
+/// identifier is no longer valid afterwards. If there is no job with the
+/// identifier this function does nothing.
+#[no_mangle]
+pub extern "C" fn cancel_job(job_id: u64) { cancel(job_id) }
+
+/// Return the response of a finished job as a NUL-terminated UTF8-encoded
+/// string, and release the job. The response is the same as that of the
+/// function the job was started for. The returned string must be freed by the
+/// caller by calling the function 'free_response_string'. In case of failure,
+/// including if the job is not finished, the function returns an error
+/// message as the response, and sets the 'success' flag to 0. A job that is
+/// not finished is not released.
+///
+/// # Safety
+/// The 'success' pointer must be valid.
+#[no_mangle]
+pub unsafe fn get_job_result(job_id: u64, success: *mut u8) -> *mut c_char {
+    match take_result(job_id) {
+        Ok(result) => encode_response(result, success),
+        Err(e) => signal_error(success, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    fn wait_for(job_id: u64) {
+        while job_status(job_id) != JobStatus::Finished {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_jobs() {
+        let ok = start_job(|input, _| Ok(input.to_uppercase()), "response".to_owned());
+        let failing = start_job(|_, _| anyhow::bail!("failure"), String::new());
+        wait_for(ok);
+        wait_for(failing);
+        let response = take_result(ok).expect("The job is finished.");
+        assert_eq!(response.expect("The job succeeded."), "RESPONSE");
+        assert_eq!(job_status(ok), JobStatus::Unknown);
+        assert!(take_result(ok).is_err());
+        assert_eq!(
+            take_result(failing)
+                .expect("The job is finished.")
+                .expect_err("The job failed.")
+                .to_string(),
+            "failure"
+        );
+    }
+
+    #[test]
+    fn test_cancel_job() {
+        let job_id = start_job(
+            |_, handle| {
+                while !handle.is_cancelled() {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Ok(String::new())
+            },
+            String::new(),
+        );
+        assert_ne!(job_status(job_id), JobStatus::Finished);
+        assert!(take_result(job_id).is_err());
+        cancel(job_id);
+        assert_eq!(job_status(job_id), JobStatus::Unknown);
+        // The pool is not blocked by the cancelled job.
+        let next = start_job(|_, _| Ok(String::new()), String::new());
+        wait_for(next);
+        assert!(take_result(next).expect("The job is finished.").is_ok());
+    }
+}
@@ -2,154 +2,553 @@
 extern crate serde_json;
 use anyhow::{bail, ensure};
 use crypto_common::{
-    types::{Amount, KeyIndex, Memo, Signature, TransactionSignature},
+    types::{Amount, KeyIndex, Memo, Timestamp, TransactionSignature, TransactionTime},
     *,
 };
 use dodis_yampolskiy_prf as prf;
 use ed25519_dalek as ed25519;
-use ed25519_dalek::Signer;
 use either::Either::{Left, Right};
-use encrypted_transfers::encrypt_amount_with_fixed_randomness;
-use id::{account_holder, constants::AttributeKind, secret_sharing::Threshold, types::*};
+use encrypted_transfers::{
+    encrypt_amount_with_fixed_randomness,
+    types::{EncryptedAmount, EncryptedAmountAggIndex, IndexedEncryptedAmount},
+};
+use ffi_helpers::json::{self, call_with_input, encode_response, signal_error, try_get};
+use id::{account_holder, constants::AttributeKind, cost, secret_sharing::Threshold, types::*};
 use pairing::bls12_381::{Bls12, G1};
-use rand::thread_rng;
+use random_oracle::ChallengeContext;
 use serde_json::{from_str, from_value, to_string, Value};
-use sha2::{Digest, Sha256};
 use std::{
+    borrow::Cow,
     cmp::max,
     collections::BTreeMap,
-    convert::TryInto,
-    ffi::{CStr, CString},
-    io::Cursor,
+    convert::{TryFrom, TryInto},
+    ffi::CStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use transactions::{
+    transaction_body, EncodedPayload, Payload, PayloadLike, Schedule, TransactionHeader,
+    MAX_RELEASES,
 };
 
 use crypto_common::types::KeyPair;
+
+mod compat;
+use compat::parse_input;
+mod warnings;
+use warnings::{warn, WarningKind};
+pub mod hashing;
+pub mod job;
+use hashing::{Sha256Hasher, TransactionHasher};
+pub mod baker;
+pub mod context;
+use context::{context_or_input, get_global, with_generators, ContextData, WalletContext};
+pub mod cursor;
+pub mod decoder;
+pub mod delegation;
+pub mod encrypted_balance;
+pub mod energy;
+pub mod memory;
+pub mod mnemonic;
+pub mod multisig;
+pub mod operation;
+pub mod ownership;
+use operation::OperationHandle;
+pub mod qr;
+pub mod randomness;
+pub mod range_proof;
+pub mod recovery;
+mod schema;
+pub mod self_test;
+pub mod signer;
+pub mod statement_proof;
+pub mod table;
+pub mod threads;
+pub mod unsigned_credential;
+pub mod unsigned_transaction;
+use randomness::WalletRng;
+use signer::{select_signer, KeyHandles, Signer};
+pub mod v2;
+pub mod version;
+pub mod versioned;
+pub mod wallet;
+
 type ExampleCurve = G1;
 
 /// Context for a transaction to send.
 #[derive(SerdeDeserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 struct TransferContext {
-    pub from:   AccountAddress,
-    pub to:     Option<AccountAddress>,
-    pub expiry: u64,
-    pub nonce:  u64,
-    pub keys:   AccountKeys,
-    pub energy: u64,
+    /// The address of the sender.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub from:             AccountAddress,
+    /// The address of the receiver.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub to:               Option<AccountAddress>,
+    /// The expiry of the transaction, in seconds since the unix epoch.
+    pub expiry:           u64,
+    /// The next nonce of the sender.
+    pub nonce:            u64,
+    /// The keys to sign with.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Value>"))]
+    pub keys:             Option<AccountKeys>,
+    /// Handles of keys held by the platform, instead of `keys`.
+    pub key_handles:      Option<KeyHandles>,
+    /// The energy to put in the header. If absent it is computed from the
+    /// execution energy, the number of signatures and the size of the
+    /// transaction.
+    pub energy:           Option<u64>,
+    /// The cost of executing the payload. If absent the standard cost of the
+    /// transaction type is used.
+    pub execution_energy: Option<u64>,
+    /// Use the given `energy` instead of the computed energy without warnings.
+    #[serde(default)]
+    pub override_energy:  bool,
+    /// The number of signatures of a transaction that is signed elsewhere,
+    /// instead of with `keys` or `keyHandles`.
+    pub num_signatures:   Option<u32>,
+}
+
+impl TransferContext {
+    /// The signer of the transaction, which uses either the keys or the key
+    /// handles of the input.
+    fn signer(&self) -> anyhow::Result<&dyn Signer> { select_signer(&self.keys, &self.key_handles) }
+
+    /// The number of signatures the transaction will have. This is the number
+    /// of keys of the signer, or `numSignatures` if the transaction is signed
+    /// elsewhere.
+    fn num_signatures(&self) -> anyhow::Result<u32> {
+        match self.num_signatures {
+            Some(n) => {
+                ensure!(
+                    self.keys.is_none() && self.key_handles.is_none(),
+                    "'numSignatures' cannot be given together with 'keys' or 'keyHandles'."
+                );
+                ensure!(n > 0, "'numSignatures' must be at least 1.");
+                Ok(n)
+            }
+            None => Ok(self.signer()?.key_indices()?.len() as u32),
+        }
+    }
+
+    /// The header of the transaction with the given payload. The energy is
+    /// the base cost of the transaction, given the number of keys it is signed
+    /// with, plus the execution energy, which defaults to the given cost of
+    /// the transaction type. An explicit `energy` in the input is still used
+    /// instead, but is deprecated unless `overrideEnergy` is set.
+    fn header(
+        &self,
+        default_execution_energy: u64,
+        payload: &EncodedPayload,
+    ) -> anyhow::Result<TransactionHeader> {
+        let num_signatures = self.num_signatures()?;
+        let execution_energy = self.execution_energy.unwrap_or(default_execution_energy);
+        let computed =
+            cost::transaction_energy(num_signatures, payload.size().into(), execution_energy);
+        let energy = match self.energy {
+            None => computed,
+            Some(energy) if self.override_energy => energy,
+            Some(energy) => {
+                warn(
+                    WarningKind::Deprecation,
+                    "The field 'energy' is deprecated. Omit it to use the energy computed from \
+                     the transaction, or set 'overrideEnergy' to use it anyway."
+                        .to_owned(),
+                );
+                if energy < computed {
+                    warn(
+                        WarningKind::Other,
+                        format!(
+                            "The energy {} is less than the {} needed by the transaction, so the \
+                             transaction will be rejected.",
+                            energy, computed
+                        ),
+                    );
+                }
+                energy
+            }
+        };
+        Ok(TransactionHeader::new(
+            self.from,
+            self.nonce,
+            energy,
+            TransactionTime {
+                seconds: self.expiry,
+            },
+            payload,
+        ))
+    }
+}
+
+/// Earliest time, in seconds since the unix epoch, that is accepted as the
+/// current time when resolving relative expiry times. This is 2021-01-01.
+const MIN_CLOCK_TIME: u64 = 1_609_459_200;
+
+/// Latest time, in seconds since the unix epoch, that is accepted as the
+/// current time when resolving relative expiry times. This is 2100-01-01.
+const MAX_CLOCK_TIME: u64 = 4_102_444_800;
+
+/// Replace the `expiryRelativeSeconds` field of the input, if present, by an
+/// absolute `expiry`. The expiry is relative to the `currentTime` field of the
+/// input if present, and otherwise to the system clock. This fails if the
+/// current time is not plausible, since that indicates that the clock of the
+/// device is wrong and the transaction would be rejected anyway.
+fn resolve_expiry(v: &mut Value) -> anyhow::Result<()> {
+    let obj = match v.as_object_mut() {
+        Some(obj) => obj,
+        None => return Ok(()),
+    };
+    let relative = match obj.remove("expiryRelativeSeconds") {
+        Some(relative) => relative,
+        None => return Ok(()),
+    };
+    ensure!(
+        !obj.contains_key("expiry"),
+        "Only one of 'expiry' and 'expiryRelativeSeconds' can be given."
+    );
+    let relative: u64 = from_value(relative)?;
+    ensure!(relative > 0, "The relative expiry must be positive.");
+    let now: u64 = match obj.remove("currentTime") {
+        Some(now) => from_value(now)?,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    ensure!(
+        (MIN_CLOCK_TIME..MAX_CLOCK_TIME).contains(&now),
+        "The current time {} is not plausible. Check the clock of the device.",
+        now
+    );
+    let expiry = match now.checked_add(relative) {
+        Some(expiry) => expiry,
+        None => bail!("The relative expiry is too large."),
+    };
+    obj.insert("expiry".to_owned(), expiry.into());
+    Ok(())
+}
+
+/// Warn if the expiry is so close that the transaction is likely to expire
+/// before it is included in a block.
+const EXPIRY_WARNING_SECONDS: u64 = 60;
+
+/// Parse the input of a function that creates a transaction or a credential,
+/// resolving a relative expiry.
+fn parse_transaction_input(input: &str) -> anyhow::Result<Value> {
+    let mut v = parse_input(input)?;
+    resolve_expiry(&mut v)?;
+    if let (Some(expiry), Ok(now)) = (
+        v.get("expiry").and_then(Value::as_u64),
+        SystemTime::now().duration_since(UNIX_EPOCH),
+    ) {
+        let now = now.as_secs();
+        if expiry <= now {
+            warn(
+                WarningKind::Other,
+                "The expiry is in the past, so the transaction will be rejected.".to_owned(),
+            );
+        } else if expiry - now < EXPIRY_WARNING_SECONDS {
+            warn(
+                WarningKind::Other,
+                format!(
+                    "The expiry is only {} seconds from now, so the transaction might expire \
+                     before it is included in a block.",
+                    expiry - now
+                ),
+            );
+        }
+    }
+    Ok(v)
 }
 
-/// Sign the given hash.
-fn make_signatures<H: AsRef<[u8]>>(keys: AccountKeys, hash: &H) -> TransactionSignature {
+/// Sign the given hash with all the keys of the signer. This fails if the keys
+/// are not valid.
+fn make_signatures<S: Signer + ?Sized, H: AsRef<[u8]>>(
+    signer: &S,
+    hash: &H,
+) -> anyhow::Result<TransactionSignature> {
     // we'll just sign with all the keys we are given, disregarding the threshold.
     // It is not our job here to decide and in any case the wallet is meant to
     // support only single key accounts.
-    let mut out = BTreeMap::new();
-    for (cred_index, map) in keys.keys.into_iter() {
-        let mut cred_sigs = BTreeMap::new();
-        for (key_index, kp) in map.keys.into_iter() {
-            let public = kp.public;
-            let secret = kp.secret;
-            let signature = ed25519_dalek::Keypair { secret, public }.sign(hash.as_ref());
-            cred_sigs.insert(key_index, Signature {
-                sig: signature.to_bytes().to_vec(),
-            });
-        }
-        out.insert(cred_index, cred_sigs);
+    let mut out: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+    for (cred_index, key_index) in signer.key_indices()? {
+        let signature = signer.sign(cred_index, key_index, hash.as_ref())?;
+        out.entry(cred_index)
+            .or_default()
+            .insert(key_index, signature);
     }
-    TransactionSignature { signatures: out }
+    Ok(TransactionSignature { signatures: out })
 }
 
 /// Create a JSON encoding of an encrypted transfer transaction.
 fn create_encrypted_transfer_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+    create_encrypted_transfer_cancellable_aux(input, &OperationHandle::new())
+}
+
+/// Same as [create_encrypted_transfer_aux], but fail if the operation is
+/// cancelled via the given handle while the proofs are being generated.
+fn create_encrypted_transfer_cancellable_aux(
+    input: &str,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    create_encrypted_transfer_with(input, None, handle)
+}
+
+/// Same as [create_encrypted_transfer_aux], but use the global context of the
+/// wallet context.
+fn create_encrypted_transfer_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    create_encrypted_transfer_with(input, Some(wallet_context), &OperationHandle::new())
+}
+
+fn create_encrypted_transfer_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
     let ctx: TransferContext = from_value(v.clone())?;
+    let (hash, body, remaining) = make_encrypted_transfer(&v, &ctx, wallet_context, handle)?;
+
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
+
+    let response = json!({
+        "signatures": signatures,
+        "transaction": hex::encode(&body),
+        "remaining": remaining,
+    });
+
+    Ok(to_string(&response)?)
+}
+
+/// Make the body of an encrypted transfer, with or without a memo, together
+/// with its hash and the remaining encrypted balance of the sender.
+fn make_encrypted_transfer(
+    v: &Value,
+    ctx: &TransferContext,
+    wallet_context: Option<&ContextData>,
+    handle: &OperationHandle,
+) -> anyhow::Result<(impl AsRef<[u8]>, Vec<u8>, EncryptedAmount<ExampleCurve>)> {
     let ctx_to = match ctx.to {
         Some(to) => to,
         None => bail!("to account should be present"),
     };
 
     // context with parameters
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let global_context = get_global(v, wallet_context)?;
 
     // plaintext amount to transfer
-    let amount: Amount = try_get(&v, "amount")?;
+    let amount: Amount = try_get(v, "amount")?;
 
     let maybe_memo: Option<Memo> = match v.get("memo") {
         Some(m) => from_value(m.clone())?,
         None => None,
     };
 
-    let sender_sk: elgamal::SecretKey<ExampleCurve> = try_get(&v, "senderSecretKey")?;
+    let sender_sk: elgamal::SecretKey<ExampleCurve> = try_get(v, "senderSecretKey")?;
 
-    let receiver_pk = try_get(&v, "receiverPublicKey")?;
+    let receiver_pk = try_get(v, "receiverPublicKey")?;
 
-    let input_amount = try_get(&v, "inputEncryptedAmount")?;
+    let input_amount = try_get(v, "inputEncryptedAmount")?;
 
     // Should be safe on iOS and Android, by calling SecRandomCopyBytes/getrandom,
-    // respectively.
-    let mut csprng = thread_rng();
+    // respectively, unless a seed is given for testing.
+    let mut csprng = WalletRng::from_input(v)?;
 
-    let payload = encrypted_transfers::make_transfer_data(
+    let payload = encrypted_transfers::make_transfer_data_cancellable(
+        &ChallengeContext::Legacy,
         &global_context,
         &receiver_pk,
         &sender_sk,
         &input_amount,
         amount,
         &mut csprng,
+        handle,
     );
     let payload = match payload {
         Some(payload) => payload,
-        None => bail!("Could not produce payload."),
+        None => {
+            handle.check()?;
+            bail!("Could not produce payload.")
+        }
     };
 
     let (hash, body) = {
-        let mut payload_bytes = Vec::new();
-        if let Some(memo) = maybe_memo {
-            payload_bytes.put(&23u8); // transaction type is encrypted transfer with memo
-            payload_bytes.put(&ctx_to);
-            payload_bytes.put(&memo);
-        } else {
-            payload_bytes.put(&16u8); // transaction type is encrypted transfer
-            payload_bytes.put(&ctx_to);
+        let data = Box::new(payload.clone());
+        let payload_bytes = match maybe_memo {
+            Some(memo) => Payload::EncryptedAmountTransferWithMemo {
+                to_address: ctx_to,
+                memo,
+                data,
+            },
+            None => Payload::EncryptedAmountTransfer {
+                to_address: ctx_to,
+                data,
+            },
         }
-        payload_bytes.extend_from_slice(&to_bytes(&payload));
+        .encode();
 
-        make_transaction_bytes(&ctx, &payload_bytes)
+        make_transaction_bytes(
+            &ctx.header(cost::ENCRYPTED_TRANSFER_ENERGY, &payload_bytes)?,
+            &payload_bytes,
+        )
     };
 
-    let signatures = make_signatures(ctx.keys, &hash);
-
-    let response = json!({
-        "signatures": signatures,
-        "transaction": hex::encode(&body),
-        "remaining": payload.remaining_amount,
-    });
-
-    Ok(to_string(&response)?)
+    Ok((hash, body, payload.remaining_amount))
 }
 
-/// Given payload bytes, make a full transaction body (that is, transaction
+/// Given the payload, make a full transaction body (that is, transaction
 /// minus the signature) together with its hash.
 fn make_transaction_bytes(
-    ctx: &TransferContext,
-    payload_bytes: &[u8],
+    header: &TransactionHeader,
+    payload_bytes: &EncodedPayload,
 ) -> (impl AsRef<[u8]>, Vec<u8>) {
-    let payload_size: u32 = payload_bytes.len() as u32;
-    let mut body = Vec::new();
-    // this needs to match with what is in Transactions.hs
-    body.put(&ctx.from);
-    body.put(&ctx.nonce);
-    body.put(&ctx.energy);
-    body.put(&payload_size);
-    body.put(&ctx.expiry);
-    body.extend_from_slice(payload_bytes);
+    match hashing::external_hasher() {
+        Some(hasher) => make_transaction_bytes_with(&hasher, header, payload_bytes),
+        None => make_transaction_bytes_with(&Sha256Hasher, header, payload_bytes),
+    }
+}
+
+/// Same as [make_transaction_bytes], but use the supplied hasher to compute
+/// the transaction hash.
+fn make_transaction_bytes_with<H: TransactionHasher>(
+    hasher: &H,
+    header: &TransactionHeader,
+    payload_bytes: &EncodedPayload,
+) -> (H::Output, Vec<u8>) {
+    let body = transaction_body(header, payload_bytes);
+    (hasher.hash(&body), body)
+}
 
-    let hasher = Sha256::new().chain(&body);
-    (hasher.finalize(), body)
+/// The input of `create_transfer`.
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(crate) struct TransferInput {
+    #[cfg_attr(feature = "schema", schemars(flatten))]
+    context: TransferContext,
+    /// The amount to send, in microCCD.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    amount:  Amount,
+    /// The hex encoded memo of the transfer.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    memo:    Option<Memo>,
+}
+
+/// A signed transaction, as in the output of `create_transfer`.
+#[derive(SerdeSerialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(crate) struct SignedTransaction {
+    /// The signatures of the transaction, indexed by credential index and key
+    /// index.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    signatures:  TransactionSignature,
+    /// The hex encoded transaction without the signatures.
+    transaction: String,
+}
+
+impl TransferInput {
+    /// Parse the input. The context is parsed on its own instead of being
+    /// flattened into the input, since serde cannot parse the integer keys of
+    /// the maps of keys in a flattened struct.
+    fn from_value(v: Value) -> anyhow::Result<Self> {
+        let amount = try_get(&v, "amount")?;
+        let memo = match v.get("memo") {
+            Some(m) => from_value(m.clone())?,
+            None => None,
+        };
+        Ok(TransferInput {
+            context: from_value(v)?,
+            amount,
+            memo,
+        })
+    }
 }
 
 fn create_transfer_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+    let v = parse_transaction_input(input)?;
+
+    let input = TransferInput::from_value(v)?;
+    let ctx = &input.context;
+    let (hash, body) = make_transfer(ctx, input.amount, input.memo)?;
+
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
+
+    let response = SignedTransaction {
+        signatures,
+        transaction: hex::encode(&body),
+    };
+
+    Ok(to_string(&response)?)
+}
+
+/// Make the body of a transfer, with or without a memo, together with its
+/// hash.
+fn make_transfer(
+    ctx: &TransferContext,
+    amount: Amount,
+    maybe_memo: Option<Memo>,
+) -> anyhow::Result<(impl AsRef<[u8]>, Vec<u8>)> {
+    let ctx_to = match ctx.to {
+        Some(to) => to,
+        None => bail!("to account should be present"),
+    };
+
+    let payload = match maybe_memo {
+        Some(memo) => Payload::TransferWithMemo {
+            to_address: ctx_to,
+            memo,
+            amount,
+        },
+        None => Payload::Transfer {
+            to_address: ctx_to,
+            amount,
+        },
+    }
+    .encode();
+
+    Ok(make_transaction_bytes(
+        &ctx.header(cost::SIMPLE_TRANSFER_ENERGY, &payload)?,
+        &payload,
+    ))
+}
+
+/// A release of a transfer with schedule, i.e., an amount that becomes
+/// available to the receiver at the given time.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, Copy, Debug)]
+struct Release {
+    #[serde(rename = "timestamp")]
+    timestamp: Timestamp,
+    #[serde(rename = "amount")]
+    amount:    Amount,
+}
+
+/// Check that the schedule is accepted by the chain: it is not empty, the
+/// amounts of the releases are positive, the timestamps are strictly
+/// increasing, and the total amount does not overflow. That there are at most
+/// [MAX_RELEASES] releases is checked when the [Schedule] is constructed.
+fn check_schedule(schedule: &[Release]) -> anyhow::Result<()> {
+    ensure!(!schedule.is_empty(), "The schedule must not be empty.");
+    let mut total = Amount::from(0);
+    for (i, release) in schedule.iter().enumerate() {
+        ensure!(
+            release.amount.microgtu > 0,
+            "The amount of release {} is zero.",
+            i
+        );
+        if i > 0 {
+            ensure!(
+                schedule[i - 1].timestamp < release.timestamp,
+                "The timestamps of the schedule must be strictly increasing."
+            );
+        }
+        total = match total + release.amount {
+            Some(total) => total,
+            None => bail!("The total amount of the schedule overflows."),
+        };
+    }
+    Ok(())
+}
+
+fn create_transfer_with_schedule_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
 
     let ctx: TransferContext = from_value(v.clone())?;
     let ctx_to = match ctx.to {
@@ -157,28 +556,88 @@ fn create_transfer_aux(input: &str) -> anyhow::Result<String> {
         None => bail!("to account should be present"),
     };
 
-    let amount: Amount = try_get(&v, "amount")?;
+    let schedule: Vec<Release> = try_get(&v, "schedule")?;
+    check_schedule(&schedule)?;
+    let releases = Schedule::try_from(
+        schedule
+            .iter()
+            .map(|release| (release.timestamp, release.amount))
+            .collect::<Vec<_>>(),
+    )?;
     let maybe_memo: Option<Memo> = match v.get("memo") {
         Some(m) => Some(from_value(m.clone())?),
         None => None,
     };
 
     let (hash, body) = {
-        let mut payload = Vec::new();
-        if let Some(memo) = maybe_memo {
-            payload.put(&22u8); // transaction type is transfer with memo
-            payload.put(&ctx_to);
-            payload.put(&memo);
-        } else {
-            payload.put(&3u8); // transaction type is transfer
-            payload.put(&ctx_to);
+        let payload = match maybe_memo {
+            Some(memo) => Payload::TransferWithScheduleAndMemo {
+                to_address: ctx_to,
+                memo,
+                schedule: releases,
+            },
+            None => Payload::TransferWithSchedule {
+                to_address: ctx_to,
+                schedule:   releases,
+            },
+        }
+        .encode();
+
+        make_transaction_bytes(
+            &ctx.header(cost::scheduled_transfer_energy(schedule.len()), &payload)?,
+            &payload,
+        )
+    };
+
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
+
+    let response = json!({
+        "signatures": signatures,
+        "transaction": hex::encode(&body),
+    });
+
+    Ok(to_string(&response)?)
+}
+
+/// Create a transaction that replaces the keys of a credential of the sender
+/// account. The transaction is signed with the current keys of the account.
+fn create_update_keys_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+
+    let ctx: TransferContext = from_value(v.clone())?;
+
+    let cred_id: String = try_get(&v, "credId")?;
+    let cred_id: ExampleCurve = base16_decode_string(&cred_id)?;
+    let new_keys: BTreeMap<KeyIndex, VerifyKey> = try_get(&v, "newVerifyKeys")?;
+    let new_threshold: SignatureThreshold = try_get(&v, "newThreshold")?;
+    // The cost depends on the number of credentials on the account.
+    let num_credentials: usize = match v.get("numCredentials") {
+        Some(n) => from_value(n.clone())?,
+        None => 1,
+    };
+    ensure!(!new_keys.is_empty(), "At least one new key must be given.");
+    ensure!(
+        new_threshold.0 >= 1 && usize::from(new_threshold.0) <= new_keys.len(),
+        "The threshold must be between 1 and the number of new keys."
+    );
+    let new_keys = CredentialPublicKeys {
+        keys:      new_keys,
+        threshold: new_threshold,
+    };
+
+    let (hash, body) = {
+        let execution_energy =
+            cost::update_credential_keys_energy(num_credentials, new_keys.keys.len());
+        let payload = Payload::UpdateCredentialKeys {
+            cred_id,
+            keys: new_keys,
         }
-        payload.put(&amount);
+        .encode();
 
-        make_transaction_bytes(&ctx, &payload)
+        make_transaction_bytes(&ctx.header(execution_energy, &payload)?, &payload)
     };
 
-    let signatures = make_signatures(ctx.keys, &hash);
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
 
     let response = json!({
         "signatures": signatures,
@@ -189,27 +648,41 @@ fn create_transfer_aux(input: &str) -> anyhow::Result<String> {
 }
 
 fn create_pub_to_sec_transfer_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+    create_pub_to_sec_transfer_with(input, None)
+}
+
+/// Same as [create_pub_to_sec_transfer_aux], but use the global context of the
+/// wallet context.
+fn create_pub_to_sec_transfer_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    create_pub_to_sec_transfer_with(input, Some(wallet_context))
+}
+
+fn create_pub_to_sec_transfer_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
 
     let ctx: TransferContext = from_value(v.clone())?;
 
     let amount: Amount = try_get(&v, "amount")?;
 
     // context with parameters
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let global_context = get_global(&v, wallet_context)?;
 
     let (hash, body) = {
-        let mut payload = Vec::new();
-        payload.put(&17u8); // transaction type is public to secret transfer
-        payload.put(&amount);
+        let payload = Payload::TransferToEncrypted { amount }.encode();
 
-        // let payload_size: u32 = payload.len() as u32;
-        // assert_eq!(payload_size, 41);
-
-        make_transaction_bytes(&ctx, &payload)
+        make_transaction_bytes(
+            &ctx.header(cost::TRANSFER_TO_ENCRYPTED_ENERGY, &payload)?,
+            &payload,
+        )
     };
 
-    let signatures = make_signatures(ctx.keys, &hash);
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
     let encryption = encrypt_amount_with_fixed_randomness(&global_context, amount);
     let response = json!({
         "signatures": signatures,
@@ -222,11 +695,37 @@ fn create_pub_to_sec_transfer_aux(input: &str) -> anyhow::Result<String> {
 
 /// Create a JSON encoding of a secret to public amount transaction.
 fn create_sec_to_pub_transfer_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+    create_sec_to_pub_transfer_cancellable_aux(input, &OperationHandle::new())
+}
+
+/// Same as [create_sec_to_pub_transfer_aux], but fail if the operation is
+/// cancelled via the given handle while the proofs are being generated.
+fn create_sec_to_pub_transfer_cancellable_aux(
+    input: &str,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    create_sec_to_pub_transfer_with(input, None, handle)
+}
+
+/// Same as [create_sec_to_pub_transfer_aux], but use the global context of the
+/// wallet context.
+fn create_sec_to_pub_transfer_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    create_sec_to_pub_transfer_with(input, Some(wallet_context), &OperationHandle::new())
+}
+
+fn create_sec_to_pub_transfer_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
     let ctx: TransferContext = from_value(v.clone())?;
 
     // context with parameters
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let global_context = get_global(&v, wallet_context)?;
 
     // plaintext amount to transfer
     let amount: Amount = try_get(&v, "amount")?;
@@ -236,30 +735,39 @@ fn create_sec_to_pub_transfer_aux(input: &str) -> anyhow::Result<String> {
     let input_amount = try_get(&v, "inputEncryptedAmount")?;
 
     // Should be safe on iOS and Android, by calling SecRandomCopyBytes/getrandom,
-    // respectively.
-    let mut csprng = thread_rng();
+    // respectively, unless a seed is given for testing.
+    let mut csprng = WalletRng::from_input(&v)?;
 
-    let payload = encrypted_transfers::make_sec_to_pub_transfer_data(
+    let payload = encrypted_transfers::make_sec_to_pub_transfer_data_cancellable(
+        &ChallengeContext::Legacy,
         &global_context,
         &sender_sk,
         &input_amount,
         amount,
         &mut csprng,
+        handle,
     );
     let payload = match payload {
         Some(payload) => payload,
-        None => bail!("Could not produce payload."),
+        None => {
+            handle.check()?;
+            bail!("Could not produce payload.")
+        }
     };
 
     let (hash, body) = {
-        let mut payload_bytes = Vec::new();
-        payload_bytes.put(&18u8); // transaction type is secret to public transfer
-        payload_bytes.extend_from_slice(&to_bytes(&payload));
+        let payload_bytes = Payload::TransferToPublic {
+            data: Box::new(payload.clone()),
+        }
+        .encode();
 
-        make_transaction_bytes(&ctx, &payload_bytes)
+        make_transaction_bytes(
+            &ctx.header(cost::TRANSFER_TO_PUBLIC_ENERGY, &payload_bytes)?,
+            &payload_bytes,
+        )
     };
 
-    let signatures = make_signatures(ctx.keys, &hash);
+    let signatures = make_signatures(ctx.signer()?, &hash)?;
 
     let response = json!({
         "signatures": signatures,
@@ -281,31 +789,168 @@ fn combine_encrypted_amounts_aux(left: &str, right: &str) -> anyhow::Result<Stri
     ))?)
 }
 
-/// Try to extract a field with a given name from the JSON value.
-fn try_get<A: serde::de::DeserializeOwned>(v: &Value, fname: &str) -> anyhow::Result<A> {
-    match v.get(fname) {
-        Some(v) => Ok(from_value(v.clone())?),
-        None => bail!(format!("Field {} not present, but should be.", fname)),
+/// Aggregate a list of encrypted amounts with consecutive indices on an
+/// account. Besides the aggregated amount, the result contains the aggregation
+/// index one past the last amount, which is the `aggIndex` of an input
+/// encrypted amount that includes all of them.
+fn combine_encrypted_amounts_list_aux(input: &str) -> anyhow::Result<String> {
+    let mut amounts: Vec<IndexedEncryptedAmount<ExampleCurve>> = from_str(input)?;
+    amounts.sort_by_key(|amount| amount.index.index);
+    let (first, rest) = match amounts.split_first() {
+        Some(split) => split,
+        None => bail!("At least one encrypted amount must be given."),
+    };
+    let mut combined = first.encrypted_chunks.clone();
+    let mut last = first.index.index;
+    for amount in rest {
+        ensure!(
+            amount.index.index == last + 1,
+            "The indices of the encrypted amounts must be consecutive, but {} is followed by {}.",
+            last,
+            amount.index.index
+        );
+        combined = encrypted_transfers::aggregate(&combined, &amount.encrypted_chunks);
+        last = amount.index.index;
     }
+    let agg_index = match last.checked_add(1) {
+        Some(index) => EncryptedAmountAggIndex::from(index),
+        None => bail!("The index of the last encrypted amount is too large."),
+    };
+    let response = json!({
+        "aggEncryptedAmount": combined,
+        "aggIndex": agg_index,
+    });
+    Ok(to_string(&response)?)
+}
+
+/// The input of `create_id_request_and_private_data`. The identity provider,
+/// anonymity revokers and global context are only optional if they are given
+/// in the wallet context.
+#[derive(SerdeDeserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IdRequestInput {
+    /// The public information of the identity provider.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Value>"))]
+    ip_info:         Option<IpInfo<Bls12>>,
+    /// The public information of the anonymity revokers, indexed by their
+    /// identities.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<BTreeMap<u32, Value>>"))]
+    ars_infos:       Option<BTreeMap<ArIdentity, ArInfo<ExampleCurve>>>,
+    /// The cryptographic parameters of the chain.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Value>"))]
+    global:          Option<GlobalContext<ExampleCurve>>,
+    /// The anonymity revokers of the identity, a subset of `arsInfos`.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Vec<u32>>"))]
+    chosen_ars:      Option<Vec<ArIdentity>>,
+    /// The number of anonymity revokers needed to revoke the identity.
+    ar_threshold:    Option<u8>,
+    /// Hex encoded 32 byte seed of the randomness. Only for testing.
+    randomness_seed: Option<String>,
+}
+
+/// The keys and address of a new account, as in the outputs of
+/// `create_id_request_and_private_data` and `create_credential`.
+#[derive(SerdeSerialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NewAccountData {
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    account_keys:          AccountKeys,
+    /// The hex encoded secret key for decrypting amounts sent to the account.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    encryption_secret_key: elgamal::SecretKey<ExampleCurve>,
+    /// The hex encoded public key for encrypting amounts sent to the account.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    encryption_public_key: elgamal::PublicKey<ExampleCurve>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    account_address:       AccountAddress,
+}
+
+/// The output of `create_id_request_and_private_data`.
+#[derive(SerdeSerialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IdRequestOutput {
+    /// The versioned request to send to the identity provider.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    id_object_request:      Versioned<PreIdentityObject<Bls12, ExampleCurve>>,
+    /// The versioned private data to keep for creating credentials.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    private_id_object_data: Versioned<IdObjectUseData<Bls12, ExampleCurve>>,
+    /// The initial account created by the identity provider.
+    initial_account_data:   NewAccountData,
 }
 
 /// This function creates the identity object request
 fn create_id_request_and_private_data_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+    create_id_request_and_private_data_with(input, None)
+}
+
+/// Same as [create_id_request_and_private_data_aux], but use the parameters of
+/// the wallet context.
+fn create_id_request_and_private_data_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    create_id_request_and_private_data_with(input, Some(wallet_context))
+}
 
-    let ip_info: IpInfo<Bls12> = try_get(&v, "ipInfo")?;
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+fn create_id_request_and_private_data_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let input: IdRequestInput = from_value(v)?;
+
+    let ip_info = context_or_input(
+        wallet_context.and_then(|ctx| ctx.ip_info.as_ref()),
+        input.ip_info,
+        "ipInfo",
+    )?;
+    // The proofs of the encrypted shares of the PRF key need all the generators.
+    let global_context = with_generators(
+        context_or_input(
+            wallet_context.map(|ctx| &ctx.global),
+            input.global,
+            "global",
+        )?,
+        NUM_BULLETPROOF_GENERATORS,
+    )?;
 
-    let ars_infos: BTreeMap<ArIdentity, ArInfo<ExampleCurve>> = try_get(&v, "arsInfos")?;
+    let ars_infos = context_or_input(
+        wallet_context.and_then(|ctx| ctx.ars_infos.as_ref()),
+        input.ars_infos,
+        "arsInfos",
+    )?;
+    // The anonymity revokers that can revoke the identity. These are all the
+    // given ones unless the caller chooses a subset in `chosenArs`.
+    let ars_infos = match input.chosen_ars {
+        Some(chosen) => {
+            let mut selected = BTreeMap::new();
+            for ar in chosen {
+                let info = match ars_infos.get(&ar) {
+                    Some(info) => info.clone(),
+                    None => bail!("Chosen anonymity revoker {} is not in arsInfos.", ar),
+                };
+                ensure!(
+                    selected.insert(ar, info).is_none(),
+                    "Anonymity revoker {} is chosen more than once.",
+                    ar
+                );
+            }
+            Cow::Owned(selected)
+        }
+        None => ars_infos,
+    };
 
     let num_of_ars = ars_infos.len();
-    let threshold = match v.get("arThreshold") {
-        Some(v) => {
-            let threshold: u8 = from_value(v.clone())?;
+    let threshold = match input.ar_threshold {
+        Some(threshold) => {
             ensure!(threshold > 0, "arThreshold must be at least 1.");
             ensure!(
                 num_of_ars >= usize::from(threshold),
-                "Number of anonymity revokers in arsInfos should be at least arThreshold."
+                "Number of chosen anonymity revokers should be at least arThreshold."
             );
             Threshold(threshold)
         }
@@ -314,15 +959,15 @@ fn create_id_request_and_private_data_aux(input: &str) -> anyhow::Result<String>
             // case of only a single anonymity revoker.
             ensure!(
                 num_of_ars > 0,
-                "arsInfos should have at least 1 anonymity revoker."
+                "At least 1 anonymity revoker should be chosen."
             );
             Threshold(max((num_of_ars - 1).try_into().unwrap_or(255), 1))
         }
     };
 
     // Should be safe on iOS and Android, by calling SecRandomCopyBytes/getrandom,
-    // respectively.
-    let mut csprng = thread_rng();
+    // respectively, unless a seed is given for testing.
+    let mut csprng = WalletRng::from_seed(input.randomness_seed.as_deref())?;
 
     let prf_key = prf::SecretKey::generate(&mut csprng);
 
@@ -335,12 +980,11 @@ fn create_id_request_and_private_data_aux(input: &str) -> anyhow::Result<String>
         prf_key,
     };
 
-    // Choice of anonymity revokers, all of them in this implementation.
+    // The context has only the chosen anonymity revokers.
     let context = IpContext::new(&ip_info, &ars_infos, &global_context);
 
     // Generating account data for the initial account
     let mut keys = std::collections::BTreeMap::new();
-    let mut csprng = thread_rng();
     keys.insert(
         KeyIndex(0),
         crypto_common::types::KeyPair::from(ed25519::Keypair::generate(&mut csprng)),
@@ -351,7 +995,14 @@ fn create_id_request_and_private_data_aux(input: &str) -> anyhow::Result<String>
         threshold: SignatureThreshold(1),
     };
     let (pio, randomness) = {
-        match account_holder::generate_pio(&context, threshold, &aci, &initial_acc_data) {
+        match account_holder::generate_pio_with_rng(
+            &ChallengeContext::Legacy,
+            &context,
+            threshold,
+            &aci,
+            &initial_acc_data,
+            &mut csprng,
+        ) {
             Some(x) => x,
             None => bail!("Generating the pre-identity object failed."),
         }
@@ -369,77 +1020,143 @@ fn create_id_request_and_private_data_aux(input: &str) -> anyhow::Result<String>
         scalar:    id_use_data.aci.prf_key.prf_exponent(0).unwrap(),
     };
 
-    let response = json!({
-        "idObjectRequest": Versioned::new(VERSION_0, pio),
-        "privateIdObjectData": Versioned::new(VERSION_0, id_use_data),
-        "initialAccountData": json!({
-            "accountKeys": acc_keys,
-            "encryptionSecretKey": secret_key,
-            "encryptionPublicKey": elgamal::PublicKey::from(&secret_key),
-            "accountAddress": address,
-        })
-    });
+    let response = IdRequestOutput {
+        id_object_request:      Versioned::new(VERSION_0, pio),
+        private_id_object_data: Versioned::new(VERSION_0, id_use_data),
+        initial_account_data:   NewAccountData {
+            account_keys:          acc_keys,
+            encryption_public_key: elgamal::PublicKey::from(&secret_key),
+            encryption_secret_key: secret_key,
+            account_address:       address,
+        },
+    };
 
     Ok(to_string(&response)?)
 }
 
+/// The input of `create_credential`. The identity provider, anonymity revokers
+/// and global context are only optional if they are given in the wallet
+/// context.
+#[derive(SerdeDeserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CredentialInput {
+    /// The public information of the identity provider.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Value>"))]
+    ip_info:                Option<IpInfo<Bls12>>,
+    /// The public information of the anonymity revokers, indexed by their
+    /// identities.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<BTreeMap<u32, Value>>"))]
+    ars_infos:              Option<BTreeMap<ArIdentity, ArInfo<ExampleCurve>>>,
+    /// The cryptographic parameters of the chain.
+    #[cfg_attr(feature = "schema", schemars(with = "Option<Value>"))]
+    global:                 Option<GlobalContext<ExampleCurve>>,
+    /// The identity object returned by the identity provider.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    identity_object:        IdentityObject<Bls12, ExampleCurve, AttributeKind>,
+    /// The private data returned by `create_id_request_and_private_data`.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    private_id_object_data: IdObjectUseData<Bls12, ExampleCurve>,
+    /// The names of the attributes to reveal in the credential.
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<String>"))]
+    revealed_attributes:    Vec<AttributeTag>,
+    /// The index of the credential to create with the identity.
+    account_number:         u8,
+    /// The expiry of the credential message, in seconds since the unix epoch.
+    #[cfg_attr(feature = "schema", schemars(with = "u64"))]
+    expiry:                 TransactionTime,
+    /// Hex encoded 32 byte seed of the randomness. Only for testing.
+    randomness_seed:        Option<String>,
+}
+
+/// The output of `create_credential`.
+#[derive(SerdeSerialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CredentialOutput {
+    /// The versioned credential message to send to the chain.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    credential:             Versioned<AccountCredentialMessage<Bls12, ExampleCurve, AttributeKind>>,
+    /// The randomness of the commitments of the credential.
+    #[cfg_attr(feature = "schema", schemars(with = "Value"))]
+    commitments_randomness: CommitmentsRandomness<ExampleCurve>,
+    #[serde(flatten)]
+    account:                NewAccountData,
+}
+
 fn create_credential_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
-    let expiry = try_get(&v, "expiry")?;
-    let ip_info: IpInfo<Bls12> = try_get(&v, "ipInfo")?;
+    create_credential_cancellable_aux(input, &OperationHandle::new())
+}
 
-    let ars_infos: BTreeMap<ArIdentity, ArInfo<ExampleCurve>> = try_get(&v, "arsInfos")?;
+/// Same as [create_credential_aux], but fail if the operation is cancelled via
+/// the given handle while the proofs are being generated.
+fn create_credential_cancellable_aux(
+    input: &str,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    create_credential_with(input, None, handle)
+}
 
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+/// Same as [create_credential_aux], but use the parameters of the wallet
+/// context.
+fn create_credential_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    create_credential_with(input, Some(wallet_context), &OperationHandle::new())
+}
 
-    let id_object: IdentityObject<Bls12, ExampleCurve, AttributeKind> =
-        try_get(&v, "identityObject")?;
+fn create_credential_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+    handle: &OperationHandle,
+) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+    let input: CredentialInput = from_value(v)?;
+    let expiry = input.expiry;
+    let ip_info = context_or_input(
+        wallet_context.and_then(|ctx| ctx.ip_info.as_ref()),
+        input.ip_info,
+        "ipInfo",
+    )?;
 
-    let id_use_data: IdObjectUseData<Bls12, ExampleCurve> = try_get(&v, "privateIdObjectData")?;
+    let ars_infos = context_or_input(
+        wallet_context.and_then(|ctx| ctx.ars_infos.as_ref()),
+        input.ars_infos,
+        "arsInfos",
+    )?;
+
+    let global_context = context_or_input(
+        wallet_context.map(|ctx| &ctx.global),
+        input.global,
+        "global",
+    )?;
 
-    let tags: Vec<AttributeTag> = try_get(&v, "revealedAttributes")?;
+    let id_object = input.identity_object;
 
-    let acc_num: u8 = try_get(&v, "accountNumber")?;
+    let id_use_data = input.private_id_object_data;
+
+    let tags = input.revealed_attributes;
+
+    let acc_num = input.account_number;
 
     // The mobile wallet for now only creates new accounts and does not support
     // adding credentials onto existing ones. Once that is supported the address
     // should be coming from the input data.
     let new_or_existing = Left(expiry);
 
+    let mut csprng = WalletRng::from_seed(input.randomness_seed.as_deref())?;
+
     // The mobile wallet can only create new accounts, which means new credential
     // data will be generated.
-    let cred_data = {
-        let mut keys = std::collections::BTreeMap::new();
-        let mut csprng = thread_rng();
-        keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-
-        CredentialData {
-            keys,
-            threshold: SignatureThreshold(1),
-        }
-    };
-
-    let mut policy_vec = std::collections::BTreeMap::new();
-    for tag in tags {
-        if let Some(att) = id_object.alist.alist.get(&tag) {
-            if policy_vec.insert(tag, att.clone()).is_some() {
-                bail!("Cannot reveal an attribute more than once.")
-            }
-        } else {
-            bail!("Cannot reveal an attribute which is not part of the attribute list.")
-        }
-    }
+    let cred_data = CredentialData::from(KeyPair::generate(&mut csprng));
 
-    let policy = Policy {
-        valid_to: id_object.alist.valid_to,
-        created_at: id_object.alist.created_at,
-        policy_vec,
-        _phantom: Default::default(),
-    };
+    let policy = make_policy(&id_object, tags)?;
 
     let context = IpContext::new(&ip_info, &ars_infos, &global_context);
 
-    let (cdi, randomness) = account_holder::create_credential(
+    let (cdi, randomness) = account_holder::create_credential_cancellable(
+        &ChallengeContext::Legacy,
         context,
         &id_object,
         &id_use_data,
@@ -447,6 +1164,8 @@ fn create_credential_aux(input: &str) -> anyhow::Result<String> {
         policy,
         &cred_data,
         &new_or_existing,
+        handle,
+        &mut csprng,
     )?;
 
     let address = match new_or_existing {
@@ -467,21 +1186,102 @@ fn create_credential_aux(input: &str) -> anyhow::Result<String> {
         credential:     AccountCredential::Normal { cdi },
     };
 
-    let response = json!({
-        "credential": Versioned::new(VERSION_0, credential_message),
-        "commitmentsRandomness": randomness,
-        "accountKeys": AccountKeys::from(cred_data),
-        "encryptionSecretKey": secret_key,
-        "encryptionPublicKey": elgamal::PublicKey::from(&secret_key),
-        "accountAddress": address,
-    });
+    let response = CredentialOutput {
+        credential:             Versioned::new(VERSION_0, credential_message),
+        commitments_randomness: randomness,
+        account:                NewAccountData {
+            account_keys:          AccountKeys::from(cred_data),
+            encryption_public_key: elgamal::PublicKey::from(&secret_key),
+            encryption_secret_key: secret_key,
+            account_address:       address,
+        },
+    };
     Ok(to_string(&response)?)
 }
 
-fn generate_accounts_aux(input: &str) -> anyhow::Result<String> {
-    let v: Value = from_str(input)?;
+/// Make the policy of a credential that reveals the given attributes of the
+/// identity object.
+fn make_policy(
+    id_object: &IdentityObject<Bls12, ExampleCurve, AttributeKind>,
+    tags: Vec<AttributeTag>,
+) -> anyhow::Result<Policy<ExampleCurve, AttributeKind>> {
+    let mut policy_vec = std::collections::BTreeMap::new();
+    for tag in tags {
+        if let Some(att) = id_object.alist.alist.get(&tag) {
+            if policy_vec.insert(tag, att.clone()).is_some() {
+                bail!("Cannot reveal an attribute more than once.")
+            }
+        } else {
+            bail!("Cannot reveal an attribute which is not part of the attribute list.")
+        }
+    }
+
+    Ok(Policy {
+        valid_to: id_object.alist.valid_to,
+        created_at: id_object.alist.created_at,
+        policy_vec,
+        _phantom: Default::default(),
+    })
+}
+
+/// The keys and address of an account created from an identity.
+pub struct AccountData {
+    /// The account number (credential counter) of the credential creating the
+    /// account.
+    pub account_number:        u8,
+    /// The credential registration id.
+    pub reg_id:                ExampleCurve,
+    pub encryption_secret_key: elgamal::SecretKey<ExampleCurve>,
+    pub address:               AccountAddress,
+}
+
+/// Compute the data of the accounts with the account numbers in the given
+/// range. The PRF values of all the accounts are computed as a batch, see
+/// [prf::SecretKey::prf_batch]. Account numbers for which the PRF is not
+/// defined are skipped, since no account can be created with them.
+pub fn generate_account_data(
+    global_context: &GlobalContext<ExampleCurve>,
+    prf_key: &prf::SecretKey<ExampleCurve>,
+    range: std::ops::Range<u8>,
+) -> Vec<AccountData> {
+    let account_numbers: Vec<u8> = range.collect();
+    let generator = global_context.elgamal_generator();
+    let reg_ids = prf_key.prf_batch(generator, &account_numbers);
+    let enc_keys = prf_key.prf_exponent_batch(&account_numbers);
+    account_numbers
+        .into_iter()
+        .zip(reg_ids.into_iter().zip(enc_keys))
+        .filter_map(|(account_number, (reg_id, enc_key))| {
+            let reg_id = reg_id.ok()?;
+            let encryption_secret_key = elgamal::SecretKey {
+                generator: *generator,
+                scalar:    enc_key.ok()?,
+            };
+            Some(AccountData {
+                account_number,
+                reg_id,
+                encryption_secret_key,
+                address: AccountAddress::new(&reg_id),
+            })
+        })
+        .collect()
+}
+
+/// Parse the input of `generate_accounts` and return an iterator over the
+/// generated accounts. The keys and addresses of all the accounts are computed
+/// up front with [generate_account_data], and the iterator converts them to
+/// JSON.
+fn generate_accounts_iter(input: &str) -> anyhow::Result<impl Iterator<Item = Value>> {
+    generate_accounts_iter_with(input, None)
+}
+
+fn generate_accounts_iter_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+) -> anyhow::Result<impl Iterator<Item = Value>> {
+    let v = parse_input(input)?;
 
-    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let global_context = get_global(&v, wallet_context)?;
 
     let id_object: IdentityObject<Bls12, ExampleCurve, AttributeKind> =
         try_get(&v, "identityObject")?;
@@ -490,79 +1290,222 @@ fn generate_accounts_aux(input: &str) -> anyhow::Result<String> {
 
     let start: u8 = try_get(&v, "start").unwrap_or(0);
 
-    let mut response = Vec::with_capacity(256);
+    let accounts = generate_account_data(
+        &global_context,
+        &id_use_data.aci.prf_key,
+        start..id_object.alist.max_accounts,
+    );
+    Ok(accounts.into_iter().map(|account| {
+        json!({
+            "encryptionSecretKey": account.encryption_secret_key,
+            "encryptionPublicKey": elgamal::PublicKey::from(&account.encryption_secret_key),
+            "accountAddress": account.address,
+        })
+    }))
+}
 
-    for acc_num in start..id_object.alist.max_accounts {
-        if let Ok(reg_id) = id_use_data
-            .aci
-            .prf_key
-            .prf(global_context.elgamal_generator(), acc_num)
-        {
-            let enc_key = id_use_data.aci.prf_key.prf_exponent(acc_num).unwrap();
-            let secret_key = elgamal::SecretKey {
-                generator: *global_context.elgamal_generator(),
-                scalar:    enc_key,
-            };
-            let address = AccountAddress::new(&reg_id);
-            response.push(json!({
-                "encryptionSecretKey": secret_key,
-                "encryptionPublicKey": elgamal::PublicKey::from(&secret_key),
-                "accountAddress": address,
-            }));
-        }
-    }
-    Ok(to_string(&response)?)
+/// Compute the credential registration id of the credential with the given
+/// account number, and the address of the account it creates. This only
+/// requires the PRF key, so it is much cheaper than creating the credential.
+fn get_credential_id_aux(input: &str) -> anyhow::Result<String> {
+    get_credential_id_with(input, None)
 }
 
-/// Embed the precomputed table for decryption.
-/// It is unfortunate that this is pure bytes, but not enough of data is marked
-/// as const, and in any case a HashMap relies on an allocator, so will never be
-/// const.
-static TABLE_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/table_bytes.bin"));
+/// Same as [get_credential_id_aux], but use the global context of the wallet
+/// context.
+fn get_credential_id_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    get_credential_id_with(input, Some(wallet_context))
+}
 
-fn decrypt_encrypted_amount_aux(input: &str) -> anyhow::Result<Amount> {
-    let v: Value = from_str(input)?;
-    let encrypted_amount = try_get(&v, "encryptedAmount")?;
-    let secret = try_get(&v, "encryptionSecretKey")?;
+fn get_credential_id_with(
+    input: &str,
+    wallet_context: Option<&ContextData>,
+) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
 
-    let table = (&mut Cursor::new(TABLE_BYTES)).get()?;
-    Ok(
-        encrypted_transfers::decrypt_amount::<id::constants::ArCurve>(
-            &table,
-            &secret,
-            &encrypted_amount,
-        ),
-    )
+    let global_context = get_global(&v, wallet_context)?;
+
+    let prf_key: prf::SecretKey<ExampleCurve> = try_get(&v, "prfKey")?;
+
+    let acc_num: u8 = try_get(&v, "accountNumber")?;
+
+    let cred_id = match prf_key.prf(global_context.elgamal_generator(), acc_num) {
+        Ok(cred_id) => cred_id,
+        Err(_) => bail!(
+            "Cannot compute the credential id for account number {}.",
+            acc_num
+        ),
+    };
+    let response = json!({
+        "credId": base16_encode_string(&cred_id),
+        "accountAddress": AccountAddress::new(&cred_id),
+    });
+    Ok(to_string(&response)?)
 }
 
-/// Set the flag to 0, and return a newly allocated string containing
-/// the error message. The returned string is NUL terminated.
-///
-/// # Safety
-/// This function does not check that the flag pointer is not null.
-unsafe fn signal_error(flag: *mut u8, err_msg: String) -> *mut c_char {
-    *flag = 0;
-    CString::new(err_msg)
-        .expect("Error message string should be non-zero and utf8.")
-        .into_raw()
-}
-
-unsafe fn encode_response(response: anyhow::Result<String>, success: *mut u8) -> *mut c_char {
-    match response {
-        Ok(s) => {
-            let cstr: CString = {
-                match CString::new(s) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        return signal_error(success, format!("Could not encode response: {}", e))
-                    }
-                }
+/// Derive the keys of an identity and of one of its accounts from a seed, as
+/// specified by [key_derivation::ConcordiumHdWallet]. The seed is given either
+/// as a BIP-39 phrase with an optional passphrase, or as hex. If the global
+/// context is given the encryption keys and the address of the account are
+/// also returned.
+fn derive_keys_from_seed_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+
+    let net: key_derivation::Net = try_get::<String>(&v, "net")?.parse()?;
+    let wallet = match v.get("seedPhrase") {
+        Some(_) => {
+            let phrase: String = try_get(&v, "seedPhrase")?;
+            let passphrase: String = try_get(&v, "passphrase").unwrap_or_default();
+            key_derivation::ConcordiumHdWallet::from_mnemonic(&phrase, &passphrase, net)
+        }
+        None => {
+            let seed: String = try_get(&v, "seed")?;
+            let mut seed = hex::decode(&seed)?;
+            if seed.len() != 64 {
+                seed.zeroize();
+                bail!("The seed must be 64 bytes.");
+            }
+            let mut wallet = key_derivation::ConcordiumHdWallet {
+                seed: [0u8; 64],
+                net,
             };
-            *success = 1;
-            cstr.into_raw()
+            wallet.seed.copy_from_slice(&seed);
+            seed.zeroize();
+            wallet
+        }
+    };
+    let ip_index: u32 = try_get(&v, "identityProviderIndex")?;
+    let identity_index: u32 = try_get(&v, "identityIndex")?;
+    let credential_counter: u8 = try_get(&v, "credentialCounter")?;
+
+    let to_scalar = |mut ikm: [u8; 32]| {
+        let scalar = keygen_bls::keygen_bls(&ikm, &[]);
+        ikm.zeroize();
+        match scalar {
+            Ok(scalar) => Ok(scalar),
+            Err(_) => bail!("Cannot generate a scalar from the derived key."),
         }
-        Err(e) => signal_error(success, format!("Could not produce response: {}", e)),
+    };
+    let id_cred_sec = to_scalar(wallet.get_id_cred_sec_seed(ip_index, identity_index)?)?;
+    let prf_key = prf::SecretKey::new(to_scalar(
+        wallet.get_prf_key_seed(ip_index, identity_index)?,
+    )?);
+    let randomness = to_scalar(wallet.get_blinding_randomness_seed(ip_index, identity_index)?)?;
+    let id_use_data = IdObjectUseData::<Bls12, ExampleCurve> {
+        aci:        AccCredentialInfo {
+            cred_holder_info: CredentialHolderInfo {
+                id_cred: IdCredentials {
+                    id_cred_sec: pedersen_scheme::Value::new(id_cred_sec),
+                },
+            },
+            prf_key,
+        },
+        randomness: ps_sig::SigRetrievalRandomness::new(randomness),
+    };
+
+    let mut signing_key =
+        wallet.get_account_signing_key(ip_index, identity_index, credential_counter.into())?;
+    let secret = ed25519::SecretKey::from_bytes(&signing_key);
+    signing_key.zeroize();
+    let secret = secret?;
+    let account_keys = KeyPair {
+        public: ed25519::PublicKey::from(&secret),
+        secret,
+    };
+
+    let mut response = json!({
+        "privateIdObjectData": id_use_data,
+        "accountKeys": account_keys,
+    });
+    if v.get("global").is_some() {
+        let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+        let account = match generate_account_data(
+            &global_context,
+            &id_use_data.aci.prf_key,
+            credential_counter..credential_counter.saturating_add(1),
+        )
+        .pop()
+        {
+            Some(account) => account,
+            None => bail!(
+                "Cannot create an account with credential counter {}.",
+                credential_counter
+            ),
+        };
+        response["encryptionSecretKey"] = json!(account.encryption_secret_key);
+        response["encryptionPublicKey"] =
+            json!(elgamal::PublicKey::from(&account.encryption_secret_key));
+        response["credId"] = json!(base16_encode_string(&account.reg_id));
+        response["accountAddress"] = json!(account.address);
     }
+    Ok(to_string(&response)?)
+}
+
+/// Generate the keys of a baker together with the proofs of knowledge of the
+/// secret keys that the chain requires when the keys are registered by the
+/// given account.
+fn generate_baker_keys_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+
+    let sender: AccountAddress = try_get(&v, "sender")?;
+
+    Ok(to_string(&baker::generate_baker_keys(&sender))?)
+}
+
+fn generate_accounts_aux(input: &str) -> anyhow::Result<String> {
+    let response = generate_accounts_iter(input)?.collect::<Vec<_>>();
+    Ok(to_string(&response)?)
+}
+
+/// Same as [generate_accounts_aux], but use the global context of the wallet
+/// context.
+fn generate_accounts_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<String> {
+    let response = generate_accounts_iter_with(input, Some(wallet_context))?.collect::<Vec<_>>();
+    Ok(to_string(&response)?)
+}
+
+/// Embed the precomputed table for decryption.
+/// It is unfortunate that this is pure bytes, but not enough of data is marked
+/// as const, and in any case a HashMap relies on an allocator, so will never be
+/// const.
+static TABLE_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/table_bytes.bin"));
+
+/// Decrypt with the table set with `set_decryption_table`, or the embedded
+/// table if none is set.
+fn decrypt_encrypted_amount_aux(input: &str) -> anyhow::Result<Amount> {
+    table::with_decryption_table(|table| decrypt_encrypted_amount_with(input, table))?
+}
+
+/// Same as [decrypt_encrypted_amount_aux], but use the table of the wallet
+/// context instead of parsing it.
+fn decrypt_encrypted_amount_with_context_aux(
+    input: &str,
+    wallet_context: &ContextData,
+) -> anyhow::Result<Amount> {
+    decrypt_encrypted_amount_with(input, &wallet_context.table)
+}
+
+fn decrypt_encrypted_amount_with(
+    input: &str,
+    table: &elgamal::BabyStepGiantStep<ExampleCurve>,
+) -> anyhow::Result<Amount> {
+    let v = parse_input(input)?;
+    let encrypted_amount = try_get(&v, "encryptedAmount")?;
+    let secret = try_get(&v, "encryptionSecretKey")?;
+
+    Ok(
+        encrypted_transfers::decrypt_amount::<id::constants::ArCurve>(
+            table,
+            &secret,
+            &encrypted_amount,
+        ),
+    )
 }
 
 /// Try to get a normal string from a `*const c_char`.
@@ -591,14 +1534,23 @@ macro_rules! get_string {
 /// ```
 ///    f(input_ptr_1: *const c_char, input_ptr_2: *const c_char, success: *mut u8) -> *mut c_char
 /// ```
+/// or
+/// ```
+///    f(input_ptr: *const c_char, handle: *const OperationHandle, success: *mut u8) -> *mut c_char
+/// ```
+/// or
+/// ```
+///    f(input_ptr: *const c_char, wallet_context: *const WalletContext, success: *mut u8) -> *mut c_char
+/// ```
 macro_rules! make_wrapper {
     ($(#[$attr:meta])* => $f:ident -> $call:expr) => {
         $(#[$attr])*
         #[no_mangle]
         pub unsafe fn $f(input_ptr: *const c_char, success: *mut u8) -> *mut c_char {
-            let input_str = get_string!(input_ptr, success);
-            let response = $call(input_str);
-            encode_response(response, success)
+            call_with_input(input_ptr, success, |input_str| {
+                let (response, warnings) = warnings::collect_warnings(|| $call(input_str));
+                response.map(|r| compat::add_warnings(r, &warnings))
+            })
         }
     };
     ($(#[$attr:meta])* => $f:ident --> $call:expr) => {
@@ -607,8 +1559,35 @@ macro_rules! make_wrapper {
         pub unsafe fn $f(input_ptr_1: *const c_char, input_ptr_2: *const c_char, success: *mut u8) -> *mut c_char {
             let input_str_1 = get_string!(input_ptr_1, success);
             let input_str_2 = get_string!(input_ptr_2, success);
-            let response = $call(input_str_1, input_str_2);
-            encode_response(response, success)
+            let (response, warnings) = warnings::collect_warnings(|| $call(input_str_1, input_str_2));
+            encode_response(response.map(|r| compat::add_warnings(r, &warnings)), success)
+        }
+    };
+    ($(#[$attr:meta])* => $f:ident ~> $call:expr) => {
+        $(#[$attr])*
+        #[no_mangle]
+        pub unsafe fn $f(input_ptr: *const c_char, handle: *const OperationHandle, success: *mut u8) -> *mut c_char {
+            let input_str = get_string!(input_ptr, success);
+            let handle = match handle.as_ref() {
+                Some(handle) => handle,
+                None => return signal_error(success, "Null operation handle.".to_owned()),
+            };
+            let (response, warnings) = warnings::collect_warnings(|| $call(input_str, handle));
+            encode_response(response.map(|r| compat::add_warnings(r, &warnings)), success)
+        }
+    };
+    ($(#[$attr:meta])* => $f:ident @> $call:expr) => {
+        $(#[$attr])*
+        #[no_mangle]
+        pub unsafe fn $f(input_ptr: *const c_char, wallet_context: *const WalletContext, success: *mut u8) -> *mut c_char {
+            let input_str = get_string!(input_ptr, success);
+            let wallet_context = match wallet_context.as_ref().map(WalletContext::data) {
+                Some(Ok(data)) => data,
+                Some(Err(e)) => return signal_error(success, e.to_string()),
+                None => return signal_error(success, "Null wallet context.".to_owned()),
+            };
+            let (response, warnings) = warnings::collect_warnings(|| $call(input_str, wallet_context));
+            encode_response(response.map(|r| compat::add_warnings(r, &warnings)), success)
         }
     };
 }
@@ -627,6 +1606,110 @@ make_wrapper!(
     /// The input pointer must point to a null-terminated buffer, otherwise this
     /// function will fail in unspecified ways.
     => create_transfer -> create_transfer_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_transfer_with_schedule -> create_transfer_with_schedule_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_update_keys -> create_update_keys_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_unsigned_transfer -> unsigned_transaction::create_unsigned_transfer_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_unsigned_encrypted_transfer -> unsigned_transaction::create_unsigned_encrypted_transfer_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => attach_signatures -> unsigned_transaction::attach_signatures_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => partially_sign_transaction -> multisig::partially_sign_transaction_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => merge_signatures -> multisig::merge_signatures_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => check_signature_thresholds -> multisig::check_signature_thresholds_aux);
 make_wrapper!(
     /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
     /// UTF8-encoded string. The input string should contain the JSON payload of an
@@ -661,6 +1744,46 @@ make_wrapper!(
     /// function will fail in unspecified ways.
     => create_credential -> create_credential_aux);
 
+make_wrapper!(
+    /// Same as `create_credential`, but the operation can be cancelled by calling
+    /// `cancel_operation` with the given handle, in which case the function
+    /// fails. The handle is obtained from `new_operation_handle` and is not
+    /// freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the handle
+    /// must have been obtained from `new_operation_handle` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_credential_cancellable ~> create_credential_cancellable_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => prepare_credential -> unsigned_credential::prepare_credential_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => finalize_credential -> unsigned_credential::finalize_credential_aux);
+
 make_wrapper!(
     /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
     /// UTF8-encoded string. The returned string must be freed by the caller by
@@ -675,6 +1798,18 @@ make_wrapper!(
     /// function will fail in unspecified ways.
     => create_encrypted_transfer -> create_encrypted_transfer_aux);
 
+make_wrapper!(
+    /// Same as `create_encrypted_transfer`, but the operation can be cancelled by calling
+    /// `cancel_operation` with the given handle, in which case the function
+    /// fails. The handle is obtained from `new_operation_handle` and is not
+    /// freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the handle
+    /// must have been obtained from `new_operation_handle` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_encrypted_transfer_cancellable ~> create_encrypted_transfer_cancellable_aux);
+
 make_wrapper!(
     /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
     /// UTF8-encoded string. The returned string must be freed by the caller by
@@ -703,6 +1838,18 @@ make_wrapper!(
     /// function will fail in unspecified ways.
     => create_sec_to_pub_transfer -> create_sec_to_pub_transfer_aux);
 
+make_wrapper!(
+    /// Same as `create_sec_to_pub_transfer`, but the operation can be cancelled by calling
+    /// `cancel_operation` with the given handle, in which case the function
+    /// fails. The handle is obtained from `new_operation_handle` and is not
+    /// freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the handle
+    /// must have been obtained from `new_operation_handle` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_sec_to_pub_transfer_cancellable ~> create_sec_to_pub_transfer_cancellable_aux);
+
 make_wrapper!(
     /// Take pointers to NUL-terminated UTF8-strings and return a NUL-terminated
     /// UTF8-encoded string. The returned string must be freed by the caller by
@@ -718,21 +1865,454 @@ make_wrapper!(
     /// The input pointers must point to a null-terminated buffer, otherwise this
     /// function will fail in unspecified ways.
     => combine_encrypted_amounts --> combine_encrypted_amounts_aux);
-
 make_wrapper!(
-    /// Take pointers to NUL-terminated UTF8-strings and return a NUL-terminated
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
     /// UTF8-encoded string. The returned string must be freed by the caller by
     /// calling the function 'free_response_string'. In case of failure the function
     /// returns an error message as the response, and sets the 'success' flag to 0.
     ///
-    /// The input strings must contain a valid JSON object with fields `identityObject`, `privateIdObjectData`, and `global`.
-    /// If there is failure decoding input arguments the return value is a string
-    /// describing the error.
+    /// The input must be a JSON list of encrypted amounts with consecutive indices.
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
     ///
     /// # Safety
     /// The input pointer must point to a null-terminated buffer, otherwise this
     /// function will fail in unspecified ways.
-    => generate_accounts -> generate_accounts_aux);
+    => combine_encrypted_amounts_list -> combine_encrypted_amounts_list_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => update_encrypted_balance -> encrypted_balance::update_encrypted_balance_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_encrypted_transfer_from_balance -> encrypted_balance::create_encrypted_transfer_from_balance_aux);
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_sec_to_pub_transfer_from_balance -> encrypted_balance::create_sec_to_pub_transfer_from_balance_aux);
+
+make_wrapper!(
+    /// Take pointers to NUL-terminated UTF8-strings and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// The input strings must contain a valid JSON object with fields `identityObject`, `privateIdObjectData`, and `global`.
+    /// If there is failure decoding input arguments the return value is a string
+    /// describing the error.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => generate_accounts -> generate_accounts_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => get_credential_id -> get_credential_id_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => derive_keys_from_seed -> derive_keys_from_seed_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => prove_attribute_in_range -> range_proof::prove_attribute_in_range_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => verify_attribute_in_range -> range_proof::verify_attribute_in_range_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => prove_statement -> statement_proof::prove_statement_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_account_ownership_proof -> ownership::create_account_ownership_proof_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => decode_transaction -> decoder::decode_transaction_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => generate_baker_keys -> generate_baker_keys_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_configure_baker -> baker::create_configure_baker_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => create_configure_delegation -> delegation::create_configure_delegation_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => set_decryption_table -> table::set_decryption_table_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => encode_payment_request -> qr::encode_payment_request_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => decode_payment_request -> qr::decode_payment_request_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => encode_key_export -> qr::encode_key_export_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => decode_key_export -> qr::decode_key_export_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => generate_mnemonic -> mnemonic::generate_mnemonic_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string and return a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure the function
+    /// returns an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and output
+    /// formats.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => validate_mnemonic -> mnemonic::validate_mnemonic_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string with the name of a library
+    /// function and return the JSON schema of its input as a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure, e.g., if
+    /// the library is built without the `schema` feature, the function returns
+    /// an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => get_input_schema -> schema::get_input_schema_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string with the name of a library
+    /// function and return the JSON schema of its output as a NUL-terminated
+    /// UTF8-encoded string. The returned string must be freed by the caller by
+    /// calling the function 'free_response_string'. In case of failure, e.g., if
+    /// the library is built without the `schema` feature, the function returns
+    /// an error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => get_output_schema -> schema::get_output_schema_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string containing a JSON
+    /// description of an account transaction, and return the energy of the
+    /// transaction as a NUL-terminated UTF8-encoded string. The returned string
+    /// must be freed by the caller by calling the function
+    /// 'free_response_string'. In case of failure the function returns an
+    /// error message as the response, and sets the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and
+    /// output.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => estimate_transaction_energy -> energy::estimate_transaction_energy_aux);
+
+make_wrapper!(
+    /// Take a pointer to a NUL-terminated UTF8-string with a hex encoded account
+    /// transaction, as returned by the functions that create transactions, and
+    /// return a JSON description of it for displaying to the user as a
+    /// NUL-terminated UTF8-encoded string. The returned string must be freed by
+    /// the caller by calling the function 'free_response_string'. In case of
+    /// failure the function returns an error message as the response, and sets
+    /// the 'success' flag to 0.
+    ///
+    /// See rust-bins/wallet-notes/README.md for the description of input and
+    /// output.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, otherwise this
+    /// function will fail in unspecified ways.
+    => parse_transaction -> decoder::parse_transaction_aux);
+
+// Variants of the functions that use the parameters of a wallet context, see
+// the [context] module.
+make_wrapper!(
+    /// Same as `create_id_request_and_private_data`, but use the parameters of
+    /// the wallet context instead of the corresponding fields of the input. The
+    /// context is obtained from `wallet_context_create` and is not freed by this
+    /// function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_id_request_and_private_data_with_context @> create_id_request_and_private_data_with_context_aux);
+
+make_wrapper!(
+    /// Same as `create_credential`, but use the parameters of the wallet context
+    /// instead of the corresponding fields of the input. The context is obtained
+    /// from `wallet_context_create` and is not freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_credential_with_context @> create_credential_with_context_aux);
+
+make_wrapper!(
+    /// Same as `generate_accounts`, but use the global context of the wallet
+    /// context instead of the corresponding fields of the input. The context is
+    /// obtained from `wallet_context_create` and is not freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => generate_accounts_with_context @> generate_accounts_with_context_aux);
+
+make_wrapper!(
+    /// Same as `get_credential_id`, but use the global context of the wallet
+    /// context instead of the corresponding fields of the input. The context is
+    /// obtained from `wallet_context_create` and is not freed by this function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => get_credential_id_with_context @> get_credential_id_with_context_aux);
+
+make_wrapper!(
+    /// Same as `create_encrypted_transfer`, but use the global context of the
+    /// wallet context instead of the corresponding fields of the input. The
+    /// context is obtained from `wallet_context_create` and is not freed by this
+    /// function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_encrypted_transfer_with_context @> create_encrypted_transfer_with_context_aux);
+
+make_wrapper!(
+    /// Same as `create_pub_to_sec_transfer`, but use the global context of the
+    /// wallet context instead of the corresponding fields of the input. The
+    /// context is obtained from `wallet_context_create` and is not freed by this
+    /// function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_pub_to_sec_transfer_with_context @> create_pub_to_sec_transfer_with_context_aux);
+
+make_wrapper!(
+    /// Same as `create_sec_to_pub_transfer`, but use the global context of the
+    /// wallet context instead of the corresponding fields of the input. The
+    /// context is obtained from `wallet_context_create` and is not freed by this
+    /// function.
+    ///
+    /// # Safety
+    /// The input pointer must point to a null-terminated buffer, and the context
+    /// must have been obtained from `wallet_context_create` and not yet freed,
+    /// otherwise this function will fail in unspecified ways.
+    => create_sec_to_pub_transfer_with_context @> create_sec_to_pub_transfer_with_context_aux);
 
 /// Take pointers to a NUL-terminated UTF8-string and return a u64.
 ///
@@ -770,6 +2350,48 @@ pub unsafe fn decrypt_encrypted_amount(input_ptr: *const c_char, success: *mut u
     }
 }
 
+/// Same as `decrypt_encrypted_amount`, but use the table of the wallet context,
+/// which avoids parsing the table on every call. If the context is NULL or
+/// invalid the `success` flag is set to `0`.
+///
+/// # Safety
+/// The input pointer must point to a null-terminated buffer, and the context
+/// must have been obtained from `wallet_context_create` and not yet freed,
+/// otherwise this function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn decrypt_encrypted_amount_with_context(
+    input_ptr: *const c_char,
+    wallet_context: *const WalletContext,
+    success: *mut u8,
+) -> u64 {
+    let input_str = if input_ptr.is_null() {
+        *success = 0;
+        return 0;
+    } else {
+        match CStr::from_ptr(input_ptr).to_str() {
+            Ok(s) => s,
+            Err(_) => {
+                *success = 0;
+                return 0;
+            }
+        }
+    };
+    let wallet_context = match wallet_context.as_ref().map(WalletContext::data) {
+        Some(Ok(data)) => data,
+        _ => {
+            *success = 0;
+            return 0;
+        }
+    };
+    if let Ok(v) = decrypt_encrypted_amount_with_context_aux(input_str, wallet_context) {
+        *success = 1;
+        u64::from(v)
+    } else {
+        *success = 0;
+        0
+    }
+}
+
 #[no_mangle]
 /// # Safety
 /// The input must be NUL-terminated.
@@ -791,11 +2413,658 @@ pub unsafe fn check_account_address(input_ptr: *const c_char) -> u8 {
 /// # Safety
 /// This function is unsafe in the sense that if the argument pointer was not
 /// Constructed via CString::into_raw its behaviour is undefined.
-pub unsafe fn free_response_string(ptr: *mut c_char) {
-    if !ptr.is_null() {
-        let _ = CString::from_raw(ptr);
-    }
-}
+/// The string is overwritten before it is freed, since responses can contain
+/// secret keys.
+pub unsafe fn free_response_string(ptr: *mut c_char) { json::free_response_string(ptr) }
 
 #[cfg(target_os = "android")]
 mod android;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context::{wallet_context_create, wallet_context_free};
+    use curve_arithmetic::Curve;
+    use encrypted_transfers::types::{EncryptedAmountTransferData, SecToPubAmountTransferData};
+    use id::test::{read_golden_bytes, read_golden_json};
+    use rand::thread_rng;
+    use random_oracle::RandomOracle;
+    use std::io::Cursor;
+
+    /// Directory with the recorded inputs and outputs of the library functions,
+    /// relative to the root of the repository.
+    const WALLET_NOTES_FILES: &str = "rust-bins/wallet-notes/files";
+
+    /// Size of the transaction header, i.e., sender, nonce, energy, payload
+    /// size, and expiry.
+    const HEADER_SIZE: usize = 32 + 8 + 8 + 4 + 8;
+
+    type Aux = fn(&str) -> anyhow::Result<String>;
+
+    fn read_input(name: &str) -> String {
+        let bytes = read_golden_bytes(format!("{}/{}-input.json", WALLET_NOTES_FILES, name));
+        String::from_utf8(bytes).expect("Input files are UTF8.")
+    }
+
+    fn read_output(name: &str) -> Value {
+        read_golden_json(format!("{}/{}-output.json", WALLET_NOTES_FILES, name))
+    }
+
+    fn transaction_bytes(output: &Value) -> Vec<u8> {
+        let tx = output["transaction"]
+            .as_str()
+            .expect("Transaction should be a string.");
+        hex::decode(tx).expect("Transaction should be hex encoded.")
+    }
+
+    /// Parse the transaction payload and check that serializing it again gives
+    /// exactly the same bytes.
+    fn check_payload_round_trip(payload: &[u8]) {
+        let mut cursor = Cursor::new(payload);
+        let tag: u8 = cursor.get().expect("Payload should have a tag.");
+        let mut out = Vec::new();
+        out.put(&tag);
+        match tag {
+            16 | 23 => {
+                let to: AccountAddress = cursor.get().expect("Receiver should parse.");
+                out.put(&to);
+                if tag == 23 {
+                    let memo: Memo = cursor.get().expect("Memo should parse.");
+                    out.put(&memo);
+                }
+                let data: EncryptedAmountTransferData<ExampleCurve> =
+                    cursor.get().expect("Transfer data should parse.");
+                out.put(&data);
+            }
+            18 => {
+                let data: SecToPubAmountTransferData<ExampleCurve> =
+                    cursor.get().expect("Transfer data should parse.");
+                out.put(&data);
+            }
+            _ => panic!("Unexpected transaction type {}.", tag),
+        }
+        assert_eq!(
+            cursor.position() as usize,
+            payload.len(),
+            "Payload should have no trailing bytes."
+        );
+        assert_eq!(out, payload, "Payload should round trip.");
+    }
+
+    #[test]
+    fn test_resolve_expiry() {
+        let resolve = |input: Value| -> anyhow::Result<Value> {
+            let mut v = input;
+            resolve_expiry(&mut v)?;
+            Ok(v)
+        };
+        let now = 1_700_000_000u64;
+        assert_eq!(
+            resolve(json!({"expiryRelativeSeconds": 600, "currentTime": now, "nonce": 1})).unwrap(),
+            json!({"expiry": now + 600, "nonce": 1})
+        );
+        assert_eq!(
+            resolve(json!({"expiry": now})).unwrap(),
+            json!({"expiry": now})
+        );
+        let from_clock = resolve(json!({"expiryRelativeSeconds": 600})).unwrap();
+        assert!(from_clock["expiry"].as_u64().unwrap() > MIN_CLOCK_TIME + 600);
+        assert!(resolve(json!({"expiryRelativeSeconds": 600, "expiry": now})).is_err());
+        assert!(resolve(json!({"expiryRelativeSeconds": 0, "currentTime": now})).is_err());
+        assert!(resolve(json!({"expiryRelativeSeconds": 600, "currentTime": 1000})).is_err());
+        assert!(resolve(json!({"expiryRelativeSeconds": u64::MAX, "currentTime": now})).is_err());
+    }
+
+    #[test]
+    fn test_generate_baker_keys() {
+        let sender: AccountAddress = "2ysA8FcCY3n5drZ3B5q5atuvAQcqqCLDM97Jh17cc6XYBoaZZL"
+            .parse()
+            .expect("Address should parse.");
+        let input = json!({ "sender": sender }).to_string();
+        let keys: Value =
+            from_str(&generate_baker_keys_aux(&input).expect("Keys should be generated."))
+                .expect("Response is JSON.");
+        let get = |name: &str| keys[name].as_str().expect("Keys are strings.").to_owned();
+        let election: ecvrf::PublicKey =
+            base16_decode_string(&get("electionVerifyKey")).expect("Election key should parse.");
+        let signature: ed25519::PublicKey =
+            base16_decode_string(&get("signatureVerifyKey")).expect("Signature key should parse.");
+        let aggregation: aggregate_sig::PublicKey<Bls12> =
+            base16_decode_string(&get("aggregationVerifyKey"))
+                .expect("Aggregation key should parse.");
+        let proof_election: eddsa_ed25519::Ed25519DlogProof =
+            from_value(keys["proofElection"].clone()).expect("Election proof should parse.");
+        let proof_sig: eddsa_ed25519::Ed25519DlogProof =
+            from_value(keys["proofSig"].clone()).expect("Signature proof should parse.");
+        let proof_aggregation: aggregate_sig::Proof<Bls12> =
+            from_value(keys["proofAggregation"].clone()).expect("Aggregation proof should parse.");
+
+        let mut challenge = b"configureBaker".to_vec();
+        challenge.put(&sender);
+        challenge.put(&election);
+        challenge.put(&signature);
+        challenge.put(&aggregation);
+        let election_as_ed25519 = ed25519::PublicKey::from_bytes(election.as_bytes())
+            .expect("VRF keys are ed25519 points.");
+        assert!(eddsa_ed25519::verify_dlog_ed25519(
+            &mut RandomOracle::domain(&challenge),
+            &election_as_ed25519,
+            &proof_election
+        ));
+        assert!(eddsa_ed25519::verify_dlog_ed25519(
+            &mut RandomOracle::domain(&challenge),
+            &signature,
+            &proof_sig
+        ));
+        assert!(aggregation.check_proof(&mut RandomOracle::domain(&challenge), &proof_aggregation));
+        // The proofs are bound to the sender.
+        let mut other = b"configureBaker".to_vec();
+        other.put(&AccountAddress::new(&ExampleCurve::one_point()));
+        other.extend_from_slice(&challenge[14 + 32..]);
+        assert!(!eddsa_ed25519::verify_dlog_ed25519(
+            &mut RandomOracle::domain(&other),
+            &signature,
+            &proof_sig
+        ));
+    }
+
+    #[test]
+    fn test_get_credential_id() {
+        let input: Value = from_str(&read_input("generate-accounts")).expect("Input is JSON.");
+        let accounts = read_output("generate-accounts");
+        let request = json!({
+            "global": input["global"],
+            "prfKey": input["privateIdObjectData"]["aci"]["prfKey"],
+            "accountNumber": 0,
+        });
+        let response: Value = from_str(
+            &get_credential_id_aux(&request.to_string()).expect("Credential id should succeed."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(response["accountAddress"], accounts[0]["accountAddress"]);
+        let cred_id: ExampleCurve = base16_decode_string(
+            response["credId"]
+                .as_str()
+                .expect("Credential id should be a string."),
+        )
+        .expect("Credential id should be a group element.");
+        assert_eq!(
+            Value::String(AccountAddress::new(&cred_id).to_string()),
+            response["accountAddress"]
+        );
+    }
+
+    #[test]
+    fn test_derive_keys_from_seed() {
+        let input: Value = from_str(&read_input("generate-accounts")).expect("Input is JSON.");
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let derive = |request: Value| -> Value {
+            from_str(
+                &derive_keys_from_seed_aux(&request.to_string())
+                    .expect("Key derivation should succeed."),
+            )
+            .expect("Response is JSON.")
+        };
+        let response = derive(json!({
+            "seedPhrase": phrase,
+            "passphrase": "TREZOR",
+            "net": "Testnet",
+            "identityProviderIndex": 0,
+            "identityIndex": 1,
+            "credentialCounter": 2,
+            "global": input["global"],
+        }));
+        // The seed of the phrase, see the test vectors of BIP-39.
+        let from_seed = derive(json!({
+            "seed": "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            "net": "Testnet",
+            "identityProviderIndex": 0,
+            "identityIndex": 1,
+            "credentialCounter": 2,
+        }));
+        assert_eq!(
+            response["privateIdObjectData"],
+            from_seed["privateIdObjectData"]
+        );
+        assert_eq!(response["accountKeys"], from_seed["accountKeys"]);
+        assert!(from_seed.get("encryptionSecretKey").is_none());
+
+        // The encryption keys are those of the account with the derived PRF key.
+        let id_use_data: IdObjectUseData<Bls12, ExampleCurve> =
+            from_value(response["privateIdObjectData"].clone())
+                .expect("Private data should parse.");
+        let global: GlobalContext<ExampleCurve> =
+            from_value(input["global"].clone()).expect("Global context should parse.");
+        let account = &generate_account_data(&global, &id_use_data.aci.prf_key, 2..3)[0];
+        assert_eq!(response["accountAddress"], json!(account.address));
+        assert_eq!(
+            response["encryptionSecretKey"],
+            json!(account.encryption_secret_key)
+        );
+
+        // Other accounts of the identity have other signing keys, and other
+        // networks derive other keys.
+        let other_account = derive(json!({
+            "seedPhrase": phrase,
+            "passphrase": "TREZOR",
+            "net": "Testnet",
+            "identityProviderIndex": 0,
+            "identityIndex": 1,
+            "credentialCounter": 3,
+        }));
+        assert_eq!(
+            other_account["privateIdObjectData"],
+            response["privateIdObjectData"]
+        );
+        assert_ne!(other_account["accountKeys"], response["accountKeys"]);
+        let mainnet = derive(json!({
+            "seedPhrase": phrase,
+            "passphrase": "TREZOR",
+            "net": "Mainnet",
+            "identityProviderIndex": 0,
+            "identityIndex": 1,
+            "credentialCounter": 2,
+        }));
+        assert_ne!(
+            mainnet["privateIdObjectData"],
+            response["privateIdObjectData"]
+        );
+    }
+
+    #[test]
+    fn test_golden_generate_accounts() {
+        let response = generate_accounts_aux(&read_input("generate-accounts"))
+            .expect("Accounts should be generated.");
+        let output: Value = from_str(&response).expect("Response should be JSON.");
+        assert_eq!(
+            output,
+            read_output("generate-accounts"),
+            "Generated accounts have changed."
+        );
+    }
+
+    #[test]
+    fn test_wallet_context() {
+        let mut input: Value = from_str(&read_input("generate-accounts")).expect("Input is JSON.");
+        let global = input
+            .as_object_mut()
+            .and_then(|o| o.remove("global"))
+            .expect("Input has a global context.");
+        let context_input =
+            CString::new(json!({ "global": global, "precompute": true }).to_string())
+                .expect("No NUL bytes.");
+        let wallet_context = unsafe { wallet_context_create(context_input.as_ptr()) };
+        let data = unsafe { wallet_context.as_ref() }
+            .expect("Context is not NULL.")
+            .data()
+            .expect("Context should be valid.");
+
+        // The global context is taken from the wallet context.
+        let response = generate_accounts_with_context_aux(&input.to_string(), data)
+            .expect("Accounts should be generated.");
+        let output: Value = from_str(&response).expect("Response should be JSON.");
+        assert_eq!(output, read_output("generate-accounts"));
+
+        let mut transfer: Value =
+            from_str(&read_input("create_pub_to_sec_transfer")).expect("Input is JSON.");
+        transfer
+            .as_object_mut()
+            .and_then(|o| o.remove("global"))
+            .expect("Input has a global context.");
+        let response = create_pub_to_sec_transfer_with_context_aux(&transfer.to_string(), data)
+            .expect("Transaction should be created.");
+        let output: Value = from_str(&response).expect("Response should be JSON.");
+        assert_eq!(
+            output["transaction"],
+            read_output("create_pub_to_sec_transfer")["transaction"]
+        );
+
+        let decrypt_input = read_input("decrypt_encrypted_amount");
+        assert_eq!(
+            decrypt_encrypted_amount_with_context_aux(&decrypt_input, data)
+                .expect("Decryption should succeed."),
+            decrypt_encrypted_amount_aux(&decrypt_input).expect("Decryption should succeed.")
+        );
+        unsafe { wallet_context_free(wallet_context) };
+
+        // An invalid context is reported by the functions using it.
+        let invalid_input = CString::new("{}").expect("No NUL bytes.");
+        let request = CString::new(input.to_string()).expect("No NUL bytes.");
+        unsafe {
+            let invalid = wallet_context_create(invalid_input.as_ptr());
+            let mut success = 1;
+            let response = generate_accounts_with_context(request.as_ptr(), invalid, &mut success);
+            assert_eq!(success, 0);
+            let message = CString::from_raw(response);
+            assert!(message
+                .to_str()
+                .expect("Error is UTF8.")
+                .starts_with("Invalid wallet context"));
+            wallet_context_free(invalid);
+        }
+    }
+
+    #[test]
+    fn test_golden_deterministic_transactions() {
+        let cases: [(&str, Aux); 3] = [
+            ("create_transfer", create_transfer_aux),
+            ("create_transfer_with_memo", create_transfer_aux),
+            ("create_pub_to_sec_transfer", create_pub_to_sec_transfer_aux),
+        ];
+        for (name, f) in cases.iter() {
+            let response = f(&read_input(name)).expect("Transaction should be created.");
+            let output: Value = from_str(&response).expect("Response should be JSON.");
+            let expected = read_output(name);
+            assert_eq!(
+                output["transaction"], expected["transaction"],
+                "Transaction encoding for {} has changed.",
+                name
+            );
+            assert_eq!(
+                output["signatures"], expected["signatures"],
+                "Signatures for {} have changed.",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_transfer_with_schedule() {
+        let mut input: Value = from_str(&read_input("create_transfer")).expect("Input is JSON.");
+        input["schedule"] = json!([
+            { "timestamp": 1_700_000_000_000u64, "amount": "1" },
+            { "timestamp": 1_700_000_001_000u64, "amount": "2500000" },
+        ]);
+        let response = create_transfer_with_schedule_aux(&input.to_string())
+            .expect("Transfer should be created.");
+        let body = transaction_bytes(&from_str(&response).expect("Response should be JSON."));
+        let decoded = decoder::decode_payload(1, &body[HEADER_SIZE..]).expect("Payload decodes.");
+        assert_eq!(decoded["type"], "transferWithSchedule");
+        assert_eq!(decoded["to"], input["to"]);
+        assert_eq!(decoded["schedule"], input["schedule"]);
+
+        for schedule in &[
+            json!([]),
+            json!([{ "timestamp": 1, "amount": "0" }]),
+            json!([{ "timestamp": 2, "amount": "1" }, { "timestamp": 2, "amount": "1" }]),
+        ] {
+            input["schedule"] = schedule.clone();
+            assert!(create_transfer_with_schedule_aux(&input.to_string()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_update_keys() {
+        let mut input: Value = from_str(&read_input("create_transfer")).expect("Input is JSON.");
+        let cred_id = ExampleCurve::one_point();
+        let new_key = KeyPair::generate(&mut thread_rng());
+        input["credId"] = json!(base16_encode_string(&cred_id));
+        input["newVerifyKeys"] = json!({ "0": VerifyKey::from(&new_key) });
+        input["newThreshold"] = json!(1);
+        let response =
+            create_update_keys_aux(&input.to_string()).expect("Update should be created.");
+        let body = transaction_bytes(&from_str(&response).expect("Response should be JSON."));
+        let decoded = decoder::decode_payload(1, &body[HEADER_SIZE..]).expect("Payload decodes.");
+        assert_eq!(decoded["type"], "updateCredentialKeys");
+        assert_eq!(decoded["credId"], input["credId"]);
+        assert_eq!(decoded["keys"]["keys"], input["newVerifyKeys"]);
+
+        input["newThreshold"] = json!(2);
+        assert!(create_update_keys_aux(&input.to_string()).is_err());
+        input["newVerifyKeys"] = json!({});
+        input["newThreshold"] = json!(1);
+        assert!(create_update_keys_aux(&input.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_id_request_chosen_ars() {
+        let credential_input: Value =
+            from_str(&read_input("create_credential")).expect("Input is JSON.");
+        let mut input = json!({
+            "ipInfo": credential_input["ipInfo"],
+            "arsInfos": credential_input["arsInfos"],
+            "global": credential_input["global"],
+            "chosenArs": [1, 3, 5],
+            "arThreshold": 2,
+        });
+        let response: Value = from_str(
+            &create_id_request_and_private_data_aux(&input.to_string())
+                .expect("Request should be created."),
+        )
+        .expect("Response should be JSON.");
+        let pio = &response["idObjectRequest"]["value"];
+        assert_eq!(pio["choiceArData"]["arIdentities"], json!([1, 3, 5]));
+        assert_eq!(pio["choiceArData"]["threshold"], 2);
+        let shared_with = pio["ipArData"]
+            .as_object()
+            .expect("Shares are a map.")
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(shared_with, vec!["1", "3", "5"]);
+
+        // The threshold is at most the number of chosen anonymity revokers.
+        input["arThreshold"] = json!(4);
+        assert!(create_id_request_and_private_data_aux(&input.to_string()).is_err());
+        input.as_object_mut().unwrap().remove("arThreshold");
+        input["chosenArs"] = json!([1, 1]);
+        assert!(create_id_request_and_private_data_aux(&input.to_string()).is_err());
+        input["chosenArs"] = json!([6]);
+        assert!(create_id_request_and_private_data_aux(&input.to_string()).is_err());
+        input["chosenArs"] = json!([]);
+        assert!(create_id_request_and_private_data_aux(&input.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_create_configure_baker() {
+        let mut input: Value = from_str(&read_input("create_transfer")).expect("Input is JSON.");
+        let fields = input.as_object_mut().unwrap();
+        fields.remove("to");
+        fields.remove("amount");
+        fields.remove("energy");
+        // Nothing to configure.
+        assert!(baker::create_configure_baker_aux(&input.to_string()).is_err());
+
+        input["generateKeys"] = json!(true);
+        input["capital"] = json!("15000000000");
+        input["restakeEarnings"] = json!(true);
+        input["openForDelegation"] = json!("openForAll");
+        input["metadataUrl"] = json!("");
+        input["transactionFeeCommission"] = json!(0.1);
+        input["bakingRewardCommission"] = json!(0.1);
+        input["finalizationRewardCommission"] = json!(1);
+        let response: Value = from_str(
+            &baker::create_configure_baker_aux(&input.to_string())
+                .expect("Transaction should be created."),
+        )
+        .expect("Response should be JSON.");
+        let body = transaction_bytes(&response);
+        let decoded = decoder::decode_payload(4, &body[HEADER_SIZE..]).expect("Payload decodes.");
+        assert_eq!(decoded["type"], "configureBaker");
+        assert_eq!(decoded["capital"], "15000000000");
+        assert_eq!(decoded["openForDelegation"], "openForAll");
+        assert_eq!(decoded["finalizationRewardCommission"], 1.0);
+        assert_eq!(
+            decoded["keysWithProofs"]["signatureVerifyKey"],
+            response["bakerKeys"]["signatureVerifyKey"]
+        );
+        let energy: u64 = from_bytes(&mut Cursor::new(&body[40..48])).unwrap();
+        assert!(energy > cost::CONFIGURE_BAKER_WITH_KEYS_ENERGY);
+
+        // Keys given with proofs for another account are rejected.
+        let other = json!({ "sender": AccountAddress::new(&ExampleCurve::one_point()) });
+        let keys: Value = from_str(&generate_baker_keys_aux(&other.to_string()).unwrap()).unwrap();
+        input.as_object_mut().unwrap().remove("generateKeys");
+        input["keysWithProofs"] = keys;
+        assert!(baker::create_configure_baker_aux(&input.to_string()).is_err());
+        input["generateKeys"] = json!(true);
+        assert!(baker::create_configure_baker_aux(&input.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_computed_energy() {
+        let mut input: Value = from_str(&read_input("create_transfer")).expect("Input is JSON.");
+        input.as_object_mut().unwrap().remove("energy");
+        let response =
+            create_transfer_aux(&input.to_string()).expect("Transfer should be created.");
+        let body = transaction_bytes(&from_str(&response).expect("Response should be JSON."));
+        let energy: u64 = from_bytes(&mut Cursor::new(&body[40..48])).unwrap();
+        let num_signatures = input["keys"]["keys"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|cred| cred["keys"].as_object().unwrap().len() as u32)
+            .sum();
+        let payload_size = (body.len() - HEADER_SIZE) as u64;
+        assert_eq!(
+            energy,
+            cost::transaction_energy(num_signatures, payload_size, cost::SIMPLE_TRANSFER_ENERGY)
+        );
+
+        // The execution energy can be given explicitly.
+        input["executionEnergy"] = json!(1000);
+        let response =
+            create_transfer_aux(&input.to_string()).expect("Transfer should be created.");
+        let body = transaction_bytes(&from_str(&response).expect("Response should be JSON."));
+        let with_execution: u64 = from_bytes(&mut Cursor::new(&body[40..48])).unwrap();
+        assert_eq!(with_execution, energy + 1000 - cost::SIMPLE_TRANSFER_ENERGY);
+    }
+
+    #[test]
+    fn test_golden_randomized_transactions() {
+        let cases: [(&str, Aux); 3] = [
+            ("create_encrypted_transfer", create_encrypted_transfer_aux),
+            (
+                "create_encrypted_transfer_with_memo",
+                create_encrypted_transfer_aux,
+            ),
+            ("create_sec_to_pub_transfer", create_sec_to_pub_transfer_aux),
+        ];
+        for (name, f) in cases.iter() {
+            let expected = transaction_bytes(&read_output(name));
+            check_payload_round_trip(&expected[HEADER_SIZE..]);
+            // The proofs are randomized, so we can only compare the parts of the
+            // transaction that are deterministic.
+            let response = f(&read_input(name)).expect("Transaction should be created.");
+            let actual = transaction_bytes(&from_str(&response).expect("Response should be JSON."));
+            assert_eq!(
+                actual.len(),
+                expected.len(),
+                "Transaction size for {} has changed.",
+                name
+            );
+            assert_eq!(
+                actual[..HEADER_SIZE],
+                expected[..HEADER_SIZE],
+                "Transaction header for {} has changed.",
+                name
+            );
+            check_payload_round_trip(&actual[HEADER_SIZE..]);
+        }
+    }
+
+    #[test]
+    fn test_seeded_randomness() {
+        let seed = hex::encode([42u8; randomness::SEED_LENGTH]);
+        let cases: [(&str, Aux); 2] = [
+            ("create_encrypted_transfer", create_encrypted_transfer_aux),
+            ("create_sec_to_pub_transfer", create_sec_to_pub_transfer_aux),
+        ];
+        for (name, f) in cases.iter() {
+            let mut input: Value = from_str(&read_input(name)).expect("Input is JSON.");
+            input["randomnessSeed"] = json!(seed);
+            let first = f(&input.to_string()).expect("Transaction should be created.");
+            let second = f(&input.to_string()).expect("Transaction should be created.");
+            assert_eq!(first, second, "Seeded {} should be deterministic.", name);
+        }
+
+        let credential_input: Value =
+            from_str(&read_input("create_credential")).expect("Input is JSON.");
+        let input = json!({
+            "ipInfo": credential_input["ipInfo"],
+            "arsInfos": credential_input["arsInfos"],
+            "global": credential_input["global"],
+            "randomnessSeed": seed,
+        })
+        .to_string();
+        assert_eq!(
+            create_id_request_and_private_data_aux(&input).expect("Request should be created."),
+            create_id_request_and_private_data_aux(&input).expect("Request should be created.")
+        );
+    }
+
+    #[test]
+    fn test_seeded_create_credential() {
+        let create = |seed: [u8; randomness::SEED_LENGTH]| {
+            let mut input: Value =
+                from_str(&read_input("create_credential")).expect("Input is JSON.");
+            input["randomnessSeed"] = json!(hex::encode(seed));
+            create_credential_aux(&input.to_string())
+                .expect("Credential should be created.")
+                .into_bytes()
+        };
+        // The keys, the commitments and the proofs are all derived from the seed,
+        // so the output is the same byte for byte.
+        assert_eq!(
+            create([42u8; randomness::SEED_LENGTH]),
+            create([42u8; randomness::SEED_LENGTH])
+        );
+        assert_ne!(
+            create([42u8; randomness::SEED_LENGTH]),
+            create([43u8; randomness::SEED_LENGTH])
+        );
+    }
+
+    #[test]
+    fn test_combine_encrypted_amounts_list() {
+        let global = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        // Encryptions with fixed randomness add up to the encryption of the sum.
+        let encrypt =
+            |amount: u64| encrypt_amount_with_fixed_randomness(&global, Amount::from(amount));
+        let amounts = json!([
+            { "encryptedChunks": encrypt(300), "index": 7 },
+            { "encryptedChunks": encrypt(100), "index": 5 },
+            { "encryptedChunks": encrypt(200), "index": 6 },
+        ]);
+        let combined: Value = from_str(
+            &combine_encrypted_amounts_list_aux(&amounts.to_string())
+                .expect("Amounts should be combined."),
+        )
+        .unwrap();
+        assert_eq!(combined["aggEncryptedAmount"], json!(encrypt(600)));
+        assert_eq!(combined["aggIndex"], json!(8));
+
+        let gap = json!([
+            { "encryptedChunks": encrypt(100), "index": 5 },
+            { "encryptedChunks": encrypt(300), "index": 7 },
+        ]);
+        assert!(combine_encrypted_amounts_list_aux(&gap.to_string()).is_err());
+        assert!(combine_encrypted_amounts_list_aux("[]").is_err());
+    }
+
+    #[test]
+    fn test_cancelled_transactions() {
+        type CancellableAux = fn(&str, &OperationHandle) -> anyhow::Result<String>;
+        let cases: [(&str, CancellableAux); 2] = [
+            (
+                "create_encrypted_transfer",
+                create_encrypted_transfer_cancellable_aux,
+            ),
+            (
+                "create_sec_to_pub_transfer",
+                create_sec_to_pub_transfer_cancellable_aux,
+            ),
+        ];
+        let handle = OperationHandle::new();
+        handle.cancel();
+        for (name, f) in cases.iter() {
+            let err = f(&read_input(name), &handle).expect_err("Cancelled transfer should fail.");
+            assert!(
+                err.downcast_ref::<Cancelled>().is_some(),
+                "Transfer {} should fail due to cancellation.",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Memory accounting for the library.
+//!
+//! When the `memory-accounting` feature is enabled (it is not by default) the
+//! library installs a global allocator that keeps track of the amount of
+//! memory currently allocated, and the peak amount since the last reset. This
+//! makes it possible to find out how much memory, e.g., credential creation
+//! needs on a given device.
+//!
+//! In addition a callback can be registered that is invoked when an
+//! allocation fails. Rust aborts the process when allocation fails, so the
+//! callback is the last chance for the host application to record what
+//! happened.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Type of the callback invoked when an allocation fails. The argument is the
+/// size of the allocation that failed, in bytes.
+pub type AllocationFailureCallback = extern "C" fn(size: usize);
+
+/// Amount of memory currently allocated, in bytes.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+/// Peak amount of allocated memory since the last reset, in bytes.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// The registered allocation failure callback, or 0 if none is registered.
+static FAILURE_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+/// An allocator that delegates to the system allocator and records the amount
+/// of allocated memory.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    #[inline(always)]
+    fn record_alloc(&self, ptr: *mut u8, size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            let cb = FAILURE_CALLBACK.load(Ordering::Acquire);
+            if cb != 0 {
+                // This is safe since the only values we store are either 0 or
+                // valid function pointers of type AllocationFailureCallback.
+                let cb = unsafe { std::mem::transmute::<usize, AllocationFailureCallback>(cb) };
+                cb(size);
+            }
+        } else {
+            let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.record_alloc(System.alloc(layout), layout.size())
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.record_alloc(System.alloc_zeroed(layout), layout.size())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        self.record_alloc(new_ptr, new_size)
+    }
+}
+
+#[cfg(feature = "memory-accounting")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Register a callback that is invoked when the library fails to allocate
+/// memory. Passing a NULL pointer removes the callback.
+///
+/// Only the allocator of the `memory-accounting` feature invokes the
+/// callback. The return value is 1 if the callback is registered, and 0 if
+/// the library was built without the feature, in which case nothing is
+/// registered.
+///
+/// # Safety
+/// The callback must be safe to call from any thread, and it must not
+/// allocate memory using this library.
+#[no_mangle]
+pub unsafe extern "C" fn set_allocation_failure_callback(
+    cb: Option<AllocationFailureCallback>,
+) -> u8 {
+    if !cfg!(feature = "memory-accounting") {
+        return 0;
+    }
+    let ptr = match cb {
+        Some(cb) => cb as usize,
+        None => 0,
+    };
+    FAILURE_CALLBACK.store(ptr, Ordering::Release);
+    1
+}
+
+/// Return the amount of memory, in bytes, currently allocated by the library.
+/// This is always 0 if the library was built without the `memory-accounting`
+/// feature.
+#[no_mangle]
+pub extern "C" fn memory_current_usage() -> u64 { CURRENT.load(Ordering::Relaxed) as u64 }
+
+/// Return the peak amount of memory, in bytes, allocated by the library since
+/// the last call to `reset_memory_peak_usage`. This is always 0 if the library
+/// was built without the `memory-accounting` feature.
+#[no_mangle]
+pub extern "C" fn memory_peak_usage() -> u64 { PEAK.load(Ordering::Relaxed) as u64 }
+
+/// Reset the peak memory usage to the current usage. This is typically called
+/// before an operation whose memory usage is to be measured.
+#[no_mangle]
+pub extern "C" fn reset_memory_peak_usage() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn ignore_failure(_size: usize) {}
+
+    #[test]
+    fn test_failure_callback_registration() {
+        let expected = if cfg!(feature = "memory-accounting") {
+            1
+        } else {
+            0
+        };
+        assert_eq!(
+            unsafe { set_allocation_failure_callback(Some(ignore_failure)) },
+            expected
+        );
+        assert_eq!(unsafe { set_allocation_failure_callback(None) }, expected);
+        assert_eq!(FAILURE_CALLBACK.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "memory-accounting")]
+    fn test_peak_usage() {
+        reset_memory_peak_usage();
+        let before = memory_peak_usage();
+        let v = vec![0u8; 1 << 20];
+        assert!(memory_peak_usage() >= before + (1 << 20));
+        drop(v);
+    }
+}
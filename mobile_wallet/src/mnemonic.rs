@@ -0,0 +1,159 @@
+//! Generation and validation of BIP-39 seed phrases.
+//!
+//! The phrases are encoded with the English word list unless the caller
+//! supplies another list of 2048 words, e.g., one of the other lists of
+//! BIP-39. The responses contain the entropy encoded by the phrase and the
+//! seed computed from it, from which keys are derived with
+//! `derive_keys_from_seed`.
+use anyhow::bail;
+use crypto_common::Zeroize;
+use key_derivation::{
+    bip39::{entropy_to_mnemonic, mnemonic_to_entropy, WordList},
+    seed_from_mnemonic,
+};
+use rand::{thread_rng, Rng};
+use serde_json::{to_string, Value};
+
+/// The number of words of generated phrases if not specified.
+pub const DEFAULT_WORD_COUNT: usize = 24;
+
+/// The word list of the input, given as a list of words in the field
+/// `wordList`, or the English list if the field is absent.
+fn get_word_list(v: &Value) -> anyhow::Result<WordList> {
+    match v.get("wordList") {
+        Some(_) => Ok(WordList::new(crate::try_get(v, "wordList")?)?),
+        None => Ok(WordList::english()),
+    }
+}
+
+/// The response for a valid phrase.
+fn phrase_response(v: &Value, words: &[String], entropy: &[u8]) -> anyhow::Result<String> {
+    let passphrase: String = crate::try_get(v, "passphrase").unwrap_or_default();
+    let mnemonic = words.join(" ");
+    let mut seed = seed_from_mnemonic(&mnemonic, &passphrase);
+    let response = to_string(&json!({
+        "mnemonic": mnemonic,
+        "words": words,
+        "entropy": hex::encode(entropy),
+        "seed": hex::encode(&seed[..]),
+    }));
+    seed.zeroize();
+    Ok(response?)
+}
+
+pub(crate) fn generate_mnemonic_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let word_count: usize = crate::try_get(&v, "wordCount").unwrap_or(DEFAULT_WORD_COUNT);
+    let entropy_len = match word_count {
+        12 | 15 | 18 | 21 | 24 => word_count / 3 * 4,
+        _ => bail!(
+            "A phrase must have 12, 15, 18, 21 or 24 words, not {}.",
+            word_count
+        ),
+    };
+    let word_list = get_word_list(&v)?;
+    let mut entropy = vec![0u8; entropy_len];
+    thread_rng().fill(&mut entropy[..]);
+    let words = entropy_to_mnemonic(&entropy, &word_list);
+    let response = words
+        .map_err(anyhow::Error::from)
+        .and_then(|words| phrase_response(&v, &words, &entropy));
+    entropy.zeroize();
+    response
+}
+
+pub(crate) fn validate_mnemonic_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let mnemonic: String = crate::try_get(&v, "mnemonic")?;
+    let word_list = get_word_list(&v)?;
+    let mut entropy = mnemonic_to_entropy(&mnemonic, &word_list)?;
+    // Return the phrase in normalized form, with single spaces.
+    let words = mnemonic
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let response = phrase_response(&v, &words, &entropy);
+    entropy.zeroize();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::from_str;
+
+    #[test]
+    fn test_generate_and_validate() {
+        let generated: Value = from_str(
+            &generate_mnemonic_aux(r#"{"wordCount": 12, "passphrase": "TREZOR"}"#)
+                .expect("Generation should succeed."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(generated["words"].as_array().map(Vec::len), Some(12));
+        let validated: Value = from_str(
+            &validate_mnemonic_aux(
+                &json!({
+                    "mnemonic": generated["mnemonic"],
+                    "passphrase": "TREZOR",
+                })
+                .to_string(),
+            )
+            .expect("Generated phrase should be valid."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(validated, generated);
+
+        // Test vector from the reference implementation of BIP-39.
+        let validated: Value = from_str(
+            &validate_mnemonic_aux(
+                r#"{"mnemonic": "legal winner thank year wave sausage worth useful legal  winner thank yellow", "passphrase": "TREZOR"}"#,
+            )
+            .expect("Phrase should be valid."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(validated["entropy"], "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f");
+        assert_eq!(
+            validated["seed"],
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"
+        );
+        assert_eq!(
+            validated["mnemonic"],
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+        );
+
+        assert!(validate_mnemonic_aux(
+            r#"{"mnemonic": "legal winner thank year wave sausage worth useful legal winner thank year"}"#
+        )
+        .is_err());
+        assert!(generate_mnemonic_aux(r#"{"wordCount": 13}"#).is_err());
+    }
+
+    #[test]
+    fn test_custom_word_list() {
+        let words = (0..2048).map(|i| format!("w{}", i)).collect::<Vec<_>>();
+        let generated: Value = from_str(
+            &generate_mnemonic_aux(&json!({ "wordList": words }).to_string())
+                .expect("Generation should succeed."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(generated["words"].as_array().map(Vec::len), Some(24));
+        let validated: Value = from_str(
+            &validate_mnemonic_aux(
+                &json!({
+                    "mnemonic": generated["mnemonic"],
+                    "wordList": words,
+                })
+                .to_string(),
+            )
+            .expect("Generated phrase should be valid."),
+        )
+        .expect("Response is JSON.");
+        assert_eq!(validated["entropy"], generated["entropy"]);
+        // The phrase is not valid with the English list.
+        assert!(
+            validate_mnemonic_aux(&json!({ "mnemonic": generated["mnemonic"] }).to_string())
+                .is_err()
+        );
+        assert!(generate_mnemonic_aux(r#"{"wordList": ["abandon"]}"#).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! Signing a transaction with keys held on several devices.
+//!
+//! A transaction from an account is valid once at least `threshold` of the
+//! credentials of the account have signed it, where a credential has signed
+//! once at least its own `threshold` of keys have signed. When these keys are
+//! spread over several devices, the transaction is created without signatures,
+//! e.g., with `create_unsigned_transfer`, and passed between the devices as a
+//! partially signed transaction: a JSON object with the hex encoded
+//! `transaction`, the `numSignatures` it was created for, and the `signatures`
+//! collected so far. Each device adds the
+//! signatures of its keys with `partially_sign_transaction`, and copies that
+//! were signed in parallel are combined with `merge_signatures`.
+//! `check_signature_thresholds` tells whether enough valid signatures have been
+//! collected, after which `attach_signatures` produces the block item.
+use crate::{
+    hashing::hash_transaction_body,
+    make_signatures, parse_input,
+    signer::{select_signer, KeyHandles},
+    try_get,
+    unsigned_transaction::parse_transaction_body,
+};
+use anyhow::{bail, ensure};
+use crypto_common::{
+    types::{CredentialIndex, KeyIndex, Signature, TransactionSignature},
+    *,
+};
+use id::types::{AccountKeys, CredentialPublicKeys, SignatureThreshold};
+use serde_json::{from_value, to_string, Value};
+use std::collections::BTreeMap;
+
+/// A transaction together with the signatures collected so far.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartiallySigned {
+    /// The hex encoded transaction, i.e., the header and the payload.
+    transaction:    String,
+    /// The number of signatures the energy of the transaction pays for.
+    num_signatures: u32,
+    /// The signatures on the hash of the transaction.
+    #[serde(default = "no_signatures")]
+    signatures:     TransactionSignature,
+}
+
+fn no_signatures() -> TransactionSignature {
+    TransactionSignature {
+        signatures: BTreeMap::new(),
+    }
+}
+
+impl PartiallySigned {
+    /// The hash of the transaction that is signed. This fails if the
+    /// transaction is malformed.
+    fn hash(&self) -> anyhow::Result<[u8; 32]> {
+        let body = hex::decode(&self.transaction)?;
+        parse_transaction_body(&body)?;
+        Ok(hash_transaction_body(&body))
+    }
+
+    /// Add the given signatures to the ones collected so far. This fails if
+    /// a key has already signed with a different signature, since the
+    /// signatures of the same key on the same transaction are identical, or if
+    /// there are more signatures than the transaction was created for.
+    fn add_signatures(&mut self, new: TransactionSignature) -> anyhow::Result<()> {
+        for (cred_index, sigs) in new.signatures {
+            let existing = self.signatures.signatures.entry(cred_index).or_default();
+            for (key_index, sig) in sigs {
+                match existing.get(&key_index) {
+                    Some(old) if *old != sig => bail!(
+                        "Key {} of credential {} has two different signatures.",
+                        key_index,
+                        cred_index
+                    ),
+                    _ => {
+                        existing.insert(key_index, sig);
+                    }
+                }
+            }
+        }
+        ensure!(
+            self.signatures.num_signatures() <= self.num_signatures,
+            "The transaction was created for {} signatures, but it has {}.",
+            self.num_signatures,
+            self.signatures.num_signatures()
+        );
+        Ok(())
+    }
+}
+
+/// The keys of a device taking part in signing.
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceKeys {
+    keys:        Option<AccountKeys>,
+    key_handles: Option<KeyHandles>,
+}
+
+/// Sign a transaction with some of the keys of the account. The input has the
+/// `transaction` and `numSignatures` as returned by the functions creating
+/// unsigned transactions, optionally the `signatures` collected so far, and
+/// either `keys` or `keyHandles`, which need only contain the keys held by this
+/// device. The thresholds in `keys` must be valid for the keys that are given,
+/// e.g., 1. The output is the transaction with the new signatures added.
+pub fn partially_sign_transaction_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let mut partial: PartiallySigned = from_value(v.clone())?;
+    let device: DeviceKeys = from_value(v)?;
+    let hash = partial.hash()?;
+    let signatures = make_signatures(select_signer(&device.keys, &device.key_handles)?, &hash)?;
+    partial.add_signatures(signatures)?;
+    Ok(to_string(&partial)?)
+}
+
+/// Merge the signatures of copies of the same transaction that were signed on
+/// different devices. The input has the field `partiallySigned`, a non-empty
+/// list of partially signed transactions. This fails if they are not for the
+/// same transaction.
+pub fn merge_signatures_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let copies: Vec<PartiallySigned> = try_get(&v, "partiallySigned")?;
+    let mut copies = copies.into_iter();
+    let mut merged = match copies.next() {
+        Some(first) => first,
+        None => bail!("At least one partially signed transaction must be given."),
+    };
+    for copy in copies {
+        ensure!(
+            copy.transaction == merged.transaction && copy.num_signatures == merged.num_signatures,
+            "The partially signed transactions are for different transactions."
+        );
+        merged.add_signatures(copy.signatures)?;
+    }
+    Ok(to_string(&merged)?)
+}
+
+/// The public keys of an account, as they are on the chain.
+#[derive(SerdeDeserialize)]
+struct AccountPublicKeys {
+    keys:      BTreeMap<CredentialIndex, CredentialPublicKeys>,
+    threshold: SignatureThreshold,
+}
+
+/// Check whether the signatures of a partially signed transaction satisfy the
+/// thresholds of the account. The input is a partially signed transaction
+/// together with `accountKeys`, the public keys of the credentials of the
+/// account with their thresholds and the threshold of the account. Every
+/// signature is verified, and those that are invalid or by keys that are not
+/// on the account are listed in `invalidSignatures` of the output.
+pub fn check_signature_thresholds_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let partial: PartiallySigned = from_value(v.clone())?;
+    let account: AccountPublicKeys = try_get(&v, "accountKeys")?;
+    let hash = partial.hash()?;
+
+    let mut invalid = Vec::new();
+    let mut credentials = BTreeMap::new();
+    let mut signed_credentials: usize = 0;
+    for (cred_index, sigs) in &partial.signatures.signatures {
+        let cred_keys = account.keys.get(cred_index);
+        let mut valid: usize = 0;
+        for (key_index, sig) in sigs {
+            if is_valid(cred_keys, *key_index, &hash, sig) {
+                valid += 1;
+            } else {
+                invalid.push(json!({
+                    "credentialIndex": cred_index,
+                    "keyIndex": key_index,
+                }));
+            }
+        }
+        if let Some(cred_keys) = cred_keys {
+            let satisfied = valid >= usize::from(cred_keys.threshold.0);
+            if satisfied {
+                signed_credentials += 1;
+            }
+            credentials.insert(
+                *cred_index,
+                json!({
+                    "validSignatures": valid,
+                    "threshold": cred_keys.threshold,
+                    "satisfied": satisfied,
+                }),
+            );
+        }
+    }
+
+    let response = json!({
+        "satisfied": signed_credentials >= usize::from(account.threshold.0),
+        "signedCredentials": signed_credentials,
+        "threshold": account.threshold,
+        "credentials": credentials,
+        "invalidSignatures": invalid,
+    });
+    Ok(to_string(&response)?)
+}
+
+/// Whether the signature is valid for the key with the given index.
+fn is_valid(
+    cred_keys: Option<&CredentialPublicKeys>,
+    key_index: KeyIndex,
+    hash: &[u8; 32],
+    sig: &Signature,
+) -> bool {
+    cred_keys
+        .and_then(|cred| cred.keys.get(&key_index))
+        .map_or(false, |key| key.verify(hash, sig))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unsigned_transaction::create_unsigned_transfer_aux;
+    use crypto_common::types::KeyPair;
+    use id::{
+        test::read_golden_bytes,
+        types::{CredentialData, PublicCredentialData},
+    };
+    use rand::thread_rng;
+    use serde_json::{from_slice, from_str};
+
+    #[test]
+    fn test_multisig() {
+        let mut input: Value = from_slice(&read_golden_bytes(
+            "rust-bins/wallet-notes/files/create_transfer-input.json",
+        ))
+        .expect("Input is JSON.");
+        input.as_object_mut().unwrap().remove("keys");
+        input["numSignatures"] = json!(2);
+        let unsigned: Value = from_str(
+            &create_unsigned_transfer_aux(&input.to_string())
+                .expect("Unsigned transfer should be created."),
+        )
+        .unwrap();
+
+        // A credential with two keys, both of which must sign.
+        let key_pairs = vec![
+            KeyPair::generate(&mut thread_rng()),
+            KeyPair::generate(&mut thread_rng()),
+        ];
+        let device_keys: Vec<Value> = key_pairs
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| {
+                json!({
+                    "keys": { "0": { "keys": { i.to_string(): kp }, "threshold": 1 } },
+                    "threshold": 1,
+                })
+            })
+            .collect();
+        let cred = CredentialData {
+            keys:      key_pairs
+                .into_iter()
+                .enumerate()
+                .map(|(i, kp)| (KeyIndex(i as u8), kp))
+                .collect(),
+            threshold: SignatureThreshold(2),
+        };
+        let account_keys = json!({
+            "keys": { "0": cred.get_cred_key_info() },
+            "threshold": 1,
+        });
+        let sign = |i: usize| -> Value {
+            let input = json!({
+                "transaction": unsigned["transaction"],
+                "numSignatures": unsigned["numSignatures"],
+                "keys": device_keys[i],
+            });
+            from_str(&partially_sign_transaction_aux(&input.to_string()).unwrap()).unwrap()
+        };
+        let check = |partial: &Value| -> Value {
+            let mut input = partial.clone();
+            input["accountKeys"] = account_keys.clone();
+            from_str(&check_signature_thresholds_aux(&input.to_string()).unwrap()).unwrap()
+        };
+
+        let first = sign(0);
+        let second = sign(1);
+        assert_eq!(check(&first)["satisfied"], json!(false));
+        assert_eq!(
+            check(&first)["credentials"]["0"]["validSignatures"],
+            json!(1)
+        );
+
+        let merged: Value = from_str(
+            &merge_signatures_aux(&json!({ "partiallySigned": [&first, &second] }).to_string())
+                .expect("Signatures should be merged."),
+        )
+        .unwrap();
+        let checked = check(&merged);
+        assert_eq!(checked["satisfied"], json!(true));
+        assert_eq!(checked["invalidSignatures"], json!([]));
+
+        // Signing in sequence gives the same result as merging.
+        let sequential: Value = from_str(
+            &partially_sign_transaction_aux(
+                &json!({
+                    "transaction": first["transaction"],
+                    "numSignatures": first["numSignatures"],
+                    "signatures": first["signatures"],
+                    "keys": device_keys[1],
+                })
+                .to_string(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(sequential, merged);
+        // Signatures beyond the number the transaction pays for are rejected.
+        let too_many = json!({
+            "transaction": first["transaction"],
+            "numSignatures": 1,
+            "signatures": first["signatures"],
+            "keys": device_keys[1],
+        });
+        assert!(partially_sign_transaction_aux(&too_many.to_string()).is_err());
+
+        // A signature by the wrong key is reported as invalid.
+        let mut wrong = second.clone();
+        wrong["signatures"]["0"]["0"] = second["signatures"]["0"]["1"].clone();
+        assert_eq!(
+            check(&wrong)["invalidSignatures"].as_array().unwrap().len(),
+            1
+        );
+        assert!(
+            merge_signatures_aux(&json!({ "partiallySigned": [first, wrong] }).to_string())
+                .is_err()
+        );
+        assert!(merge_signatures_aux(&json!({ "partiallySigned": [] }).to_string()).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+//! Handles for cancelling long-running operations.
+//!
+//! Credential creation and the encrypted transfers involve generating
+//! zero-knowledge proofs, which can take several seconds on slower devices.
+//! The `_cancellable` variants of these functions take an operation handle in
+//! addition to their input. Calling `cancel_operation` on the handle, from any
+//! thread, makes the operation stop at the next phase of proof generation and
+//! return an error.
+//!
+//! The intended usage is
+//! - create a handle with `new_operation_handle`,
+//! - start the operation, e.g., with `create_credential_cancellable`,
+//! - possibly call `cancel_operation` from a different thread,
+//! - free the handle with `free_operation_handle` once the operation has
+//!   returned.
+pub use crypto_common::CancellationToken as OperationHandle;
+
+/// Create a new handle for a cancellable operation. The returned handle must
+/// be freed by the caller by calling `free_operation_handle`.
+#[no_mangle]
+pub fn new_operation_handle() -> *mut OperationHandle {
+    Box::into_raw(Box::new(OperationHandle::new()))
+}
+
+/// Request cancellation of the operation using the given handle. The operation
+/// will return an error at the next point where it checks for cancellation. It
+/// is safe to call this function from a different thread than the one running
+/// the operation, and to call it multiple times. If the handle is NULL this
+/// function does nothing.
+///
+/// # Safety
+/// The handle must have been obtained from `new_operation_handle` and not yet
+/// freed.
+#[no_mangle]
+pub unsafe fn cancel_operation(handle: *const OperationHandle) {
+    if let Some(handle) = handle.as_ref() {
+        handle.cancel()
+    }
+}
+
+/// # Safety
+/// This function is unsafe in the sense that if the argument pointer was not
+/// obtained from `new_operation_handle` its behaviour is undefined. The handle
+/// must not be freed while an operation using it is running.
+#[no_mangle]
+pub unsafe fn free_operation_handle(handle: *mut OperationHandle) {
+    if !handle.is_null() {
+        let _ = Box::from_raw(handle);
+    }
+}
@@ -0,0 +1,92 @@
+//! Proofs of ownership of an account for third parties.
+//!
+//! An exchange, for example, asks the user to prove that a withdrawal address
+//! is theirs. It gives the wallet its name and a fresh nonce, and the wallet
+//! signs them with the keys of the account. The exchange checks the proof
+//! against the credentials of the account on the chain with
+//! [verify_account_ownership_challenge](id::id_verifier::verify_account_ownership_challenge).
+use crate::{parse_input, try_get};
+use anyhow::{ensure, Context};
+use id::{
+    account_ownership::OwnershipChallenge,
+    id_prover::prove_account_ownership_challenge,
+    types::{AccountAddress, AccountKeys},
+};
+use serde_json::to_string;
+
+/// Sign the challenge of a verifier with the keys of an account. The input has
+/// the `address` and the `keys` of the account, the name of the `verifier`,
+/// and the hex encoded `nonce` of the verifier. The response has the `proof`,
+/// the signatures of each credential.
+pub fn create_account_ownership_proof_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let account: AccountAddress = try_get(&v, "address")?;
+    let keys: AccountKeys = try_get(&v, "keys")?;
+    let verifier: String = try_get(&v, "verifier")?;
+    let nonce =
+        hex::decode(try_get::<String>(&v, "nonce")?).context("The nonce is not hex encoded.")?;
+    ensure!(!nonce.is_empty(), "The nonce must not be empty.");
+    let challenge = OwnershipChallenge { verifier, nonce };
+    let proof = prove_account_ownership_challenge(&keys, account, &challenge);
+    let response = json!({ "proof": proof });
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::{CredentialIndex, KeyIndex, KeyPair};
+    use id::{
+        account_ownership::AccountOwnershipProofs,
+        id_verifier::verify_account_ownership,
+        types::{CredentialData, SignatureThreshold},
+    };
+    use serde_json::{from_str, from_value, Value};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_create_account_ownership_proof() {
+        let mut csprng = rand::thread_rng();
+        let mut keys = AccountKeys::new();
+        for i in 0..2 {
+            let mut cred_keys = BTreeMap::new();
+            cred_keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+            keys.add_credential(CredentialIndex { index: i }, CredentialData {
+                keys:      cred_keys,
+                threshold: SignatureThreshold(1),
+            })
+            .unwrap();
+        }
+        let address: AccountAddress = "2ysA8FcCY3n5drZ3B5q5atuvAQcqqCLDM97Jh17cc6XYBoaZZL"
+            .parse()
+            .unwrap();
+        let mut input = json!({
+            "address": address,
+            "keys": keys,
+            "verifier": "exchange.example",
+            "nonce": "0123456789abcdef",
+        });
+        let response: Value = from_str(
+            &create_account_ownership_proof_aux(&input.to_string())
+                .expect("Proof should be created."),
+        )
+        .unwrap();
+        let proof: AccountOwnershipProofs = from_value(response["proof"].clone()).unwrap();
+        let challenge = OwnershipChallenge {
+            verifier: String::from("exchange.example"),
+            nonce:    hex::decode("0123456789abcdef").unwrap(),
+        };
+        assert_eq!(proof.len(), 2);
+        for (index, cred_proof) in proof.iter() {
+            assert!(verify_account_ownership(
+                &keys.keys[index].get_cred_key_info(),
+                address,
+                &challenge.to_signed_bytes(),
+                cred_proof
+            ));
+        }
+
+        input["nonce"] = json!("");
+        assert!(create_account_ownership_proof_aux(&input.to_string()).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Encoding of payloads that are exchanged between wallets via QR codes.
+//!
+//! Payment requests are encoded as URIs of the form
+//! `concordium:<address>?amount=<amount>&memo=<memo>` where the amount is in
+//! GTU, e.g., `12.5`, and the memo is hex encoded. Both the amount and the memo
+//! are optional.
+//!
+//! Payloads that are too big to fit into a single QR code, such as exported
+//! keys, are split into chunks of the form `CCDQR/<index>/<total>/<data>`,
+//! where the index is 1-based. Chunks can be scanned in any order.
+use anyhow::{anyhow, bail, ensure};
+use crypto_common::{
+    encryption::{decrypt, encrypt, EncryptedData, Password},
+    types::{Amount, Memo},
+    SerdeDeserialize, SerdeSerialize,
+};
+use id::types::AccountAddress;
+use rand::thread_rng;
+use serde_json::{from_str, from_value, to_string, Value};
+use std::collections::BTreeMap;
+
+/// Scheme used for payment request URIs.
+pub const PAYMENT_REQUEST_SCHEME: &str = "concordium:";
+
+/// Prefix of each chunk of a multi-part payload.
+pub const CHUNK_PREFIX: &str = "CCDQR/";
+
+/// Default maximum size of a single chunk in bytes. QR codes can hold more
+/// data than this, but dense codes are hard to scan with phone cameras.
+pub const DEFAULT_MAX_CHUNK_SIZE: usize = 1000;
+
+/// A request for payment to a given account.
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequest {
+    pub address: AccountAddress,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount:  Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo:    Option<Memo>,
+}
+
+impl PaymentRequest {
+    /// Encode the request as a URI.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!("{}{}", PAYMENT_REQUEST_SCHEME, self.address);
+        let mut separator = '?';
+        if let Some(amount) = self.amount {
+            uri.push(separator);
+            uri.push_str(&format!("amount={}", amount));
+            separator = '&';
+        }
+        if let Some(memo) = &self.memo {
+            uri.push(separator);
+            uri.push_str(&format!("memo={}", hex::encode(&memo.memo)));
+        }
+        uri
+    }
+
+    /// Parse a payment request from a URI. Unknown query parameters are
+    /// ignored so that the format can be extended in the future.
+    pub fn from_uri(uri: &str) -> anyhow::Result<Self> {
+        let rest = match uri.strip_prefix(PAYMENT_REQUEST_SCHEME) {
+            Some(rest) => rest,
+            None => bail!(
+                "Payment request must start with '{}'.",
+                PAYMENT_REQUEST_SCHEME
+            ),
+        };
+        let (address, query) = match rest.find('?') {
+            Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+            None => (rest, None),
+        };
+        let address = address
+            .parse::<AccountAddress>()
+            .map_err(|_| anyhow!("Invalid account address."))?;
+        let mut amount = None;
+        let mut memo = None;
+        for param in query.into_iter().flat_map(|q| q.split('&')) {
+            let (key, value) = match param.find('=') {
+                Some(i) => (&param[..i], &param[i + 1..]),
+                None => bail!("Malformed query parameter '{}'.", param),
+            };
+            match key {
+                "amount" => {
+                    ensure!(amount.is_none(), "Duplicate amount.");
+                    amount = Some(value.parse::<Amount>()?);
+                }
+                "memo" => {
+                    ensure!(memo.is_none(), "Duplicate memo.");
+                    memo = Some(from_value::<Memo>(Value::String(value.into()))?);
+                }
+                _ => {}
+            }
+        }
+        Ok(PaymentRequest {
+            address,
+            amount,
+            memo,
+        })
+    }
+}
+
+/// Number of decimal digits needed to print the number.
+fn num_digits(n: usize) -> usize { n.to_string().len() }
+
+/// Split the payload into chunks so that each chunk is at most
+/// `max_chunk_size` bytes. If the payload fits into a single chunk it is
+/// returned unchanged, otherwise each chunk is prefixed with its index and
+/// the total number of chunks.
+pub fn split_into_chunks(payload: &str, max_chunk_size: usize) -> anyhow::Result<Vec<String>> {
+    if payload.len() <= max_chunk_size {
+        return Ok(vec![payload.into()]);
+    }
+    // The size of the header depends on the number of chunks, so we
+    // iterate until the number of chunks is stable. Since the header grows
+    // logarithmically this terminates quickly.
+    let mut total = 2;
+    loop {
+        let header_size = CHUNK_PREFIX.len() + 2 * num_digits(total) + 2;
+        // A single character is at most 4 bytes in UTF8.
+        ensure!(
+            max_chunk_size >= header_size + 4,
+            "Maximum chunk size {} is too small.",
+            max_chunk_size
+        );
+        let capacity = max_chunk_size - header_size;
+        let mut pieces = Vec::new();
+        let mut rest = payload;
+        while !rest.is_empty() {
+            let mut end = std::cmp::min(capacity, rest.len());
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            pieces.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        if num_digits(pieces.len()) <= num_digits(total) {
+            let total = pieces.len();
+            return Ok(pieces
+                .into_iter()
+                .enumerate()
+                .map(|(i, piece)| format!("{}{}/{}/{}", CHUNK_PREFIX, i + 1, total, piece))
+                .collect());
+        }
+        total = pieces.len();
+    }
+}
+
+/// Reassemble a payload from its chunks, which may be given in any order.
+/// This is the inverse of [split_into_chunks].
+pub fn join_chunks<S: AsRef<str>>(chunks: &[S]) -> anyhow::Result<String> {
+    if let [single] = chunks {
+        if !single.as_ref().starts_with(CHUNK_PREFIX) {
+            return Ok(single.as_ref().into());
+        }
+    }
+    ensure!(!chunks.is_empty(), "At least one chunk is needed.");
+    let mut total = None;
+    let mut pieces = BTreeMap::new();
+    for chunk in chunks {
+        let chunk = chunk.as_ref();
+        let rest = match chunk.strip_prefix(CHUNK_PREFIX) {
+            Some(rest) => rest,
+            None => bail!("Chunk does not start with '{}'.", CHUNK_PREFIX),
+        };
+        let mut parts = rest.splitn(3, '/');
+        let (index, chunk_total, data) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(index), Some(chunk_total), Some(data)) => {
+                (index.parse::<usize>()?, chunk_total.parse::<usize>()?, data)
+            }
+            _ => bail!("Malformed chunk."),
+        };
+        ensure!(
+            *total.get_or_insert(chunk_total) == chunk_total,
+            "Chunks disagree on the total number of chunks."
+        );
+        ensure!(
+            index >= 1 && index <= chunk_total,
+            "Chunk index {} out of range.",
+            index
+        );
+        ensure!(
+            pieces.insert(index, data).is_none(),
+            "Duplicate chunk {}.",
+            index
+        );
+    }
+    ensure!(
+        Some(pieces.len()) == total,
+        "Missing chunks, got {} out of {:?}.",
+        pieces.len(),
+        total
+    );
+    Ok(pieces.values().copied().collect())
+}
+
+/// Get the optional maximum chunk size from the input.
+fn get_max_chunk_size(v: &Value) -> anyhow::Result<usize> {
+    match v.get("maxChunkSize") {
+        Some(size) => Ok(from_value(size.clone())?),
+        None => Ok(DEFAULT_MAX_CHUNK_SIZE),
+    }
+}
+
+pub(crate) fn encode_payment_request_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let request: PaymentRequest = from_value(v.clone())?;
+    let chunks = split_into_chunks(&request.to_uri(), get_max_chunk_size(&v)?)?;
+    Ok(to_string(&json!({ "chunks": chunks }))?)
+}
+
+pub(crate) fn decode_payment_request_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let chunks: Vec<String> = crate::try_get(&v, "chunks")?;
+    let request = PaymentRequest::from_uri(&join_chunks(&chunks)?)?;
+    Ok(to_string(&request)?)
+}
+
+pub(crate) fn encode_key_export_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let password: String = crate::try_get(&v, "password")?;
+    let keys: Value = crate::try_get(&v, "keys")?;
+    let encrypted = encrypt(
+        &Password::from(password),
+        &to_string(&keys)?,
+        &mut thread_rng(),
+    );
+    let chunks = split_into_chunks(&to_string(&encrypted)?, get_max_chunk_size(&v)?)?;
+    Ok(to_string(&json!({ "chunks": chunks }))?)
+}
+
+pub(crate) fn decode_key_export_aux(input: &str) -> anyhow::Result<String> {
+    let v = crate::parse_input(input)?;
+    let password: String = crate::try_get(&v, "password")?;
+    let chunks: Vec<String> = crate::try_get(&v, "chunks")?;
+    let encrypted: EncryptedData = from_str(&join_chunks(&chunks)?)?;
+    let plaintext = decrypt(&Password::from(password), &encrypted)?;
+    let keys: Value = serde_json::from_slice(&plaintext)?;
+    Ok(to_string(&json!({ "keys": keys }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "3ZFGxLtnUUSJGW2WqjMh1DDjxyq5rnytCwkSqxFTpsWSFdQnNn";
+
+    #[test]
+    fn test_payment_request_uri() {
+        let request = PaymentRequest {
+            address: ADDRESS.parse().expect("Valid address."),
+            amount:  Some(Amount::from(12_500_000)),
+            memo:    Some(Memo {
+                memo: vec![1, 2, 3],
+            }),
+        };
+        let uri = request.to_uri();
+        assert_eq!(
+            uri,
+            format!("concordium:{}?amount=12.5&memo=010203", ADDRESS)
+        );
+        let parsed = PaymentRequest::from_uri(&uri).expect("Valid URI.");
+        assert_eq!(parsed.address, request.address);
+        assert_eq!(parsed.amount, request.amount);
+        assert_eq!(parsed.memo.map(|m| m.memo), Some(vec![1, 2, 3]));
+
+        let parsed = PaymentRequest::from_uri(&format!("concordium:{}", ADDRESS))
+            .expect("Amount and memo are optional.");
+        assert!(parsed.amount.is_none() && parsed.memo.is_none());
+        assert!(PaymentRequest::from_uri(ADDRESS).is_err());
+    }
+
+    #[test]
+    fn test_chunks_round_trip() {
+        let payload = "abcdefghijklmnopqrstuvwxyz".repeat(20);
+        for max_chunk_size in &[20, 37, 100, 520, 1000] {
+            let mut chunks =
+                split_into_chunks(&payload, *max_chunk_size).expect("Chunk size is large enough.");
+            assert!(chunks.iter().all(|c| c.len() <= *max_chunk_size));
+            chunks.reverse();
+            assert_eq!(
+                join_chunks(&chunks).expect("All chunks are present."),
+                payload
+            );
+            if chunks.len() > 1 {
+                chunks.pop();
+                assert!(join_chunks(&chunks).is_err(), "Missing chunk is detected.");
+            }
+        }
+        assert!(split_into_chunks(&payload, 10).is_err());
+    }
+
+    #[test]
+    fn test_key_export_round_trip() {
+        let input = json!({
+            "password": "secret",
+            "keys": { "signKey": "00ff" },
+            "maxChunkSize": 100
+        });
+        let output: Value = from_str(
+            &encode_key_export_aux(&input.to_string()).expect("Encryption should succeed."),
+        )
+        .expect("Output is JSON.");
+        let decode_input = json!({
+            "password": "secret",
+            "chunks": output["chunks"]
+        });
+        let decoded: Value = from_str(
+            &decode_key_export_aux(&decode_input.to_string()).expect("Decryption should succeed."),
+        )
+        .expect("Output is JSON.");
+        assert_eq!(decoded["keys"], input["keys"]);
+    }
+}
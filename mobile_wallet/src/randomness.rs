@@ -0,0 +1,125 @@
+//! The source of randomness of the functions that generate keys and proofs.
+//!
+//! By default the randomness is taken from the thread-local generator of the
+//! `rand` crate, which is seeded from the operating system, i.e., via
+//! SecRandomCopyBytes on iOS and getrandom on Android. For reproducible tests,
+//! and for checking on a second device that an air-gapped signer produced the
+//! expected output, the input of `create_id_request_and_private_data`,
+//! `create_credential`, `create_encrypted_transfer` and
+//! `create_sec_to_pub_transfer` can instead contain the field
+//! `randomnessSeed`. The output is then a deterministic function of the input.
+//! The seeded generator is ChaCha20, whose output is specified, so the same
+//! seed gives the same output on all platforms and versions of the library.
+//!
+//! A seeded generator must only be used for testing. Anybody who knows the seed
+//! can recompute the generated keys and the randomness of the commitments, and
+//! thereby learn the private data that the proofs hide.
+use crate::{
+    try_get,
+    warnings::{warn, WarningKind},
+};
+use anyhow::ensure;
+use rand::{rngs::ThreadRng, thread_rng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde_json::Value;
+
+/// Length of the seed in bytes.
+pub const SEED_LENGTH: usize = 32;
+
+/// The random number generator of a single call.
+pub enum WalletRng {
+    /// The thread-local generator seeded by the operating system.
+    Thread(ThreadRng),
+    /// A generator seeded from the input of the call.
+    Seeded(ChaCha20Rng),
+}
+
+impl WalletRng {
+    /// The generator for the call with the given input. This is seeded with
+    /// the hex encoded `randomnessSeed` of the input if present, in which case
+    /// a warning is reported, since the output is then predictable.
+    pub fn from_input(v: &Value) -> anyhow::Result<Self> {
+        match v.get("randomnessSeed") {
+            Some(_) => Self::from_seed(Some(&try_get::<String>(v, "randomnessSeed")?)),
+            None => Self::from_seed(None),
+        }
+    }
+
+    /// The generator seeded with the given hex encoded seed, or the
+    /// thread-local generator if there is no seed. As for
+    /// [WalletRng::from_input] a warning is reported if a seed is given.
+    pub fn from_seed(seed: Option<&str>) -> anyhow::Result<Self> {
+        let seed = match seed {
+            Some(seed) => seed,
+            None => return Ok(WalletRng::Thread(thread_rng())),
+        };
+        let bytes = hex::decode(seed)?;
+        ensure!(
+            bytes.len() == SEED_LENGTH,
+            "The randomness seed must be {} bytes, but it is {}.",
+            SEED_LENGTH,
+            bytes.len()
+        );
+        let mut seed = [0u8; SEED_LENGTH];
+        seed.copy_from_slice(&bytes);
+        warn(
+            WarningKind::Other,
+            "The randomness is derived from 'randomnessSeed', so the output is deterministic. \
+             This must only be used for testing."
+                .to_owned(),
+        );
+        Ok(WalletRng::Seeded(ChaCha20Rng::from_seed(seed)))
+    }
+}
+
+impl RngCore for WalletRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            WalletRng::Thread(rng) => rng.next_u32(),
+            WalletRng::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            WalletRng::Thread(rng) => rng.next_u64(),
+            WalletRng::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            WalletRng::Thread(rng) => rng.fill_bytes(dest),
+            WalletRng::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            WalletRng::Thread(rng) => rng.try_fill_bytes(dest),
+            WalletRng::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for WalletRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_seeded_rng() {
+        let input = json!({ "randomnessSeed": hex::encode([7u8; SEED_LENGTH]) });
+        let mut first = WalletRng::from_input(&input).expect("Seed is valid.");
+        let mut second = WalletRng::from_input(&input).expect("Seed is valid.");
+        assert_eq!(first.gen::<[u64; 4]>(), second.gen::<[u64; 4]>());
+        assert!(matches!(
+            WalletRng::from_input(&json!({})),
+            Ok(WalletRng::Thread(_))
+        ));
+        assert!(WalletRng::from_input(&json!({ "randomnessSeed": "0102" })).is_err());
+        assert!(WalletRng::from_input(&json!({ "randomnessSeed": 12 })).is_err());
+    }
+}
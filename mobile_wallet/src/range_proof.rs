@@ -0,0 +1,147 @@
+//! Proofs that a committed attribute lies in a range.
+//!
+//! These allow an app to prove, e.g., that the date of birth committed to in a
+//! credential on the chain is before a given date, without revealing it. The
+//! statement is that `lower <= attribute < upper`, where the attributes are
+//! compared as the numbers they are encoded as, see
+//! [Attribute::to_field_element](id::types::Attribute::to_field_element).
+use crate::{parse_input, try_get, ExampleCurve};
+use anyhow::{bail, ensure};
+use bulletproofs::range_proof::RangeProof;
+use ff::PrimeField;
+use id::{
+    constants::AttributeKind,
+    id_prover::prove_attribute_in_range,
+    id_verifier::{verify_attribute, verify_attribute_range},
+    types::{Attribute, GlobalContext},
+};
+use pedersen_scheme::{Commitment, Randomness as PedersenRandomness};
+use random_oracle::ChallengeContext;
+use serde_json::to_string;
+
+/// Prove that the attribute in a commitment lies in the given range. The
+/// commitment is optional. If it is given, the attribute and the randomness
+/// are checked to be an opening of it, so that the error is reported here
+/// instead of by the verifier.
+pub fn prove_attribute_in_range_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let global: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let attribute: AttributeKind = try_get(&v, "attribute")?;
+    let randomness: PedersenRandomness<ExampleCurve> = try_get(&v, "randomness")?;
+    let lower: AttributeKind = try_get(&v, "lower")?;
+    let upper: AttributeKind = try_get(&v, "upper")?;
+    if v.get("commitment").is_some() {
+        let commitment: Commitment<ExampleCurve> = try_get(&v, "commitment")?;
+        ensure!(
+            verify_attribute(
+                &global.on_chain_commitment_key,
+                &attribute,
+                &randomness,
+                &commitment
+            ),
+            "The attribute and randomness are not an opening of the commitment."
+        );
+    }
+    let value = attribute.to_field_element().into_repr();
+    ensure!(
+        lower.to_field_element().into_repr() <= value
+            && value < upper.to_field_element().into_repr(),
+        "The attribute is not in the range."
+    );
+    let proof = match prove_attribute_in_range(
+        &ChallengeContext::Legacy,
+        global.bulletproof_generators(),
+        &global.on_chain_commitment_key,
+        &attribute,
+        &lower,
+        &upper,
+        &randomness,
+    ) {
+        Some(proof) => proof,
+        None => bail!("Could not produce the proof."),
+    };
+    let response = json!({ "proof": proof });
+    Ok(to_string(&response)?)
+}
+
+/// Verify a proof that the attribute in a commitment lies in the given range.
+/// The response is `{"valid": true}` or `{"valid": false}`. An error is only
+/// returned if the input cannot be parsed.
+pub fn verify_attribute_in_range_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let global: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let commitment: Commitment<ExampleCurve> = try_get(&v, "commitment")?;
+    let lower: AttributeKind = try_get(&v, "lower")?;
+    let upper: AttributeKind = try_get(&v, "upper")?;
+    let proof: RangeProof<ExampleCurve> = try_get(&v, "proof")?;
+    let valid = verify_attribute_range(
+        &ChallengeContext::Legacy,
+        &global.on_chain_commitment_key,
+        global.bulletproof_generators(),
+        &lower,
+        &upper,
+        &commitment,
+        &proof,
+    )
+    .is_ok();
+    let response = json!({ "valid": valid });
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pedersen_scheme::Value as PedersenValue;
+    use serde_json::{from_str, Value};
+
+    #[test]
+    fn test_attribute_range_proof() {
+        let mut csprng = rand::thread_rng();
+        let global = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        let attribute = AttributeKind("19900101".to_string());
+        let value = PedersenValue::<ExampleCurve>::new(attribute.to_field_element());
+        let (commitment, randomness) = global.on_chain_commitment_key.commit(&value, &mut csprng);
+
+        let input = json!({
+            "global": global,
+            "attribute": attribute,
+            "randomness": randomness,
+            "commitment": commitment,
+            "lower": "19000101",
+            "upper": "20030101",
+        });
+        let response: Value = from_str(
+            &prove_attribute_in_range_aux(&input.to_string()).expect("Proof should succeed."),
+        )
+        .unwrap();
+
+        let verify = |lower: &str, upper: &str| -> bool {
+            let input = json!({
+                "global": global,
+                "commitment": commitment,
+                "lower": lower,
+                "upper": upper,
+                "proof": response["proof"],
+            });
+            let response: Value = from_str(
+                &verify_attribute_in_range_aux(&input.to_string())
+                    .expect("Verification should not fail."),
+            )
+            .unwrap();
+            response["valid"].as_bool().unwrap()
+        };
+        assert!(verify("19000101", "20030101"));
+        assert!(!verify("19000101", "19900101"));
+
+        // The range is checked before proving, since an attribute outside the
+        // range would give a proof that does not verify.
+        let mut input = input;
+        input["upper"] = json!("19900101");
+        assert!(prove_attribute_in_range_aux(&input.to_string()).is_err());
+
+        // The opening is checked against the commitment.
+        input["upper"] = json!("20030101");
+        input["attribute"] = json!("19900102");
+        assert!(prove_attribute_in_range_aux(&input.to_string()).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Recovery of the accounts of a wallet from its PRF keys.
+//!
+//! The registration ids of the credentials of an identity, and thus the
+//! addresses of the accounts created from it, are determined by the PRF key of
+//! the identity and the account number. A wallet that still has the PRF keys
+//! of its identities, e.g., because it derives them from a seed, can therefore
+//! find its accounts by computing the registration ids and asking a node
+//! whether they are on chain.
+//!
+//! Since it is not known in advance how many identities and accounts were
+//! used, the scan stops after a number of consecutive unused indices, in the
+//! same way as the gap limit of BIP-44 wallets. Account numbers of an identity
+//! are scanned until [account_gap_limit](RecoveryConfig::account_gap_limit)
+//! consecutive account numbers have no credential on chain, and identities are
+//! scanned until [identity_gap_limit](RecoveryConfig::identity_gap_limit)
+//! consecutive identities have no accounts.
+use dodis_yampolskiy_prf as prf;
+use id::types::*;
+use pairing::bls12_381::G1;
+
+/// Queries to the chain that are needed for recovery.
+pub trait ChainQuery {
+    type Error;
+
+    /// Whether a credential with the given registration id is deployed on
+    /// chain.
+    fn is_credential_on_chain(&mut self, cred_id: &G1) -> Result<bool, Self::Error>;
+}
+
+/// The PRF keys of the identities of a wallet, indexed by the identity index.
+pub trait PrfKeySource {
+    /// The PRF key of the identity with the given index, or `None` if the
+    /// source has no identity with that index.
+    fn prf_key(&self, identity: u32) -> Option<prf::SecretKey<G1>>;
+}
+
+/// A list of PRF keys, where the identity index is the position in the list.
+impl PrfKeySource for [prf::SecretKey<G1>] {
+    fn prf_key(&self, identity: u32) -> Option<prf::SecretKey<G1>> {
+        self.get(identity as usize).cloned()
+    }
+}
+
+/// Limits of the recovery scan. See the [module level documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryConfig {
+    pub identity_gap_limit: u32,
+    pub account_gap_limit:  u8,
+}
+
+/// The gap limit of 20 recommended by BIP-44 for both identities and accounts.
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        RecoveryConfig {
+            identity_gap_limit: 20,
+            account_gap_limit:  20,
+        }
+    }
+}
+
+/// An account that was found on chain.
+#[derive(Debug, Clone)]
+pub struct RecoveredAccount {
+    /// Index of the identity the credential of the account is created from.
+    pub identity:              u32,
+    /// The credential counter of the credential of the account.
+    pub account_number:        u8,
+    pub cred_id:               G1,
+    pub address:               AccountAddress,
+    pub encryption_secret_key: elgamal::SecretKey<G1>,
+}
+
+/// Find the accounts of the identities in the key source that are on chain.
+/// The accounts are returned ordered by identity index and account number.
+pub fn recover_accounts<K: PrfKeySource + ?Sized, Q: ChainQuery>(
+    global: &GlobalContext<G1>,
+    keys: &K,
+    chain: &mut Q,
+    config: &RecoveryConfig,
+) -> Result<Vec<RecoveredAccount>, Q::Error> {
+    let mut accounts = Vec::new();
+    let mut identity_gap = 0;
+    let mut identity = 0u32;
+    while identity_gap < config.identity_gap_limit {
+        let prf_key = match keys.prf_key(identity) {
+            Some(prf_key) => prf_key,
+            None => break,
+        };
+        let found = recover_identity_accounts(global, identity, &prf_key, chain, config)?;
+        if found.is_empty() {
+            identity_gap += 1;
+        } else {
+            identity_gap = 0;
+            accounts.extend(found);
+        }
+        identity = match identity.checked_add(1) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    Ok(accounts)
+}
+
+/// Find the accounts of a single identity that are on chain.
+fn recover_identity_accounts<Q: ChainQuery>(
+    global: &GlobalContext<G1>,
+    identity: u32,
+    prf_key: &prf::SecretKey<G1>,
+    chain: &mut Q,
+    config: &RecoveryConfig,
+) -> Result<Vec<RecoveredAccount>, Q::Error> {
+    let mut accounts = Vec::new();
+    let mut gap = 0;
+    for acc_num in 0..=u8::MAX {
+        if gap >= config.account_gap_limit {
+            break;
+        }
+        // If the registration id cannot be computed then no credential with
+        // this account number can exist, so it does not count towards the gap.
+        let cred_id = match prf_key.prf(global.elgamal_generator(), acc_num) {
+            Ok(cred_id) => cred_id,
+            Err(_) => continue,
+        };
+        if !chain.is_credential_on_chain(&cred_id)? {
+            gap += 1;
+            continue;
+        }
+        gap = 0;
+        // unwrap is safe here since we've computed the registration id already,
+        // and that does the same computation.
+        let enc_key = prf_key.prf_exponent(acc_num).unwrap();
+        accounts.push(RecoveredAccount {
+            identity,
+            account_number: acc_num,
+            cred_id,
+            address: AccountAddress::new(&cred_id),
+            encryption_secret_key: elgamal::SecretKey {
+                generator: *global.elgamal_generator(),
+                scalar:    enc_key,
+            },
+        });
+    }
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+    use std::collections::BTreeSet;
+
+    /// A chain with the accounts with the given addresses, which counts the
+    /// queries.
+    struct TestChain {
+        addresses: BTreeSet<AccountAddress>,
+        queries:   usize,
+    }
+
+    impl ChainQuery for TestChain {
+        type Error = std::convert::Infallible;
+
+        fn is_credential_on_chain(&mut self, cred_id: &G1) -> Result<bool, Self::Error> {
+            self.queries += 1;
+            Ok(self.addresses.contains(&AccountAddress::new(cred_id)))
+        }
+    }
+
+    #[test]
+    fn test_recover_accounts() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let keys: Vec<prf::SecretKey<G1>> = (0..6)
+            .map(|_| prf::SecretKey::generate(&mut csprng))
+            .collect();
+        let config = RecoveryConfig {
+            identity_gap_limit: 2,
+            account_gap_limit:  3,
+        };
+        let address = |identity: usize, acc_num: u8| {
+            AccountAddress::new(
+                &keys[identity]
+                    .prf(global.elgamal_generator(), acc_num)
+                    .unwrap(),
+            )
+        };
+        // Identity 1 is unused, so identity 2 is within the gap limit, but
+        // identities 3 and 4 are unused, so identity 5 is not found. Account 4
+        // of identity 0 is within the gap limit, but account 8 is not.
+        let expected = vec![(0, 0), (0, 1), (0, 4), (2, 2)];
+        let mut on_chain = expected.clone();
+        on_chain.push((0, 8));
+        on_chain.push((5, 0));
+        let mut chain = TestChain {
+            addresses: on_chain
+                .iter()
+                .map(|&(identity, acc_num)| address(identity, acc_num))
+                .collect(),
+            queries:   0,
+        };
+
+        let accounts = recover_accounts(&global, &keys[..], &mut chain, &config).unwrap();
+        let found = accounts
+            .iter()
+            .map(|acc| (acc.identity as usize, acc.account_number))
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+        for acc in accounts.iter() {
+            assert_eq!(
+                acc.address,
+                address(acc.identity as usize, acc.account_number)
+            );
+            assert_eq!(acc.address, AccountAddress::new(&acc.cred_id));
+        }
+        // Identity 0 is scanned up to account 7, identity 2 up to account 5, and
+        // identities 1, 3 and 4 up to account 2.
+        assert_eq!(chain.queries, 8 + 6 + 3 * 3);
+    }
+}
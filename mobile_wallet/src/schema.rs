@@ -0,0 +1,131 @@
+//! JSON schemas of the inputs and outputs of the library functions, so that
+//! clients can validate their payloads before calling the library.
+//!
+//! The schemas are derived from the types the library parses the inputs into
+//! and serializes the outputs from. Values that are parsed by other crates,
+//! such as keys and commitments, are described as hex encoded strings, and
+//! objects that are produced by the chain or the identity provider, such as
+//! the global context, are described as arbitrary JSON values. The schemas are
+//! only available if the library is built with the `schema` feature.
+#[cfg(feature = "schema")]
+use crate::{
+    CredentialInput, CredentialOutput, IdRequestInput, IdRequestOutput, SignedTransaction,
+    TransferInput,
+};
+#[cfg(feature = "schema")]
+use schemars::schema_for;
+
+/// The JSON schema of the input of the library function with the given name.
+#[cfg(feature = "schema")]
+pub(crate) fn get_input_schema_aux(function_name: &str) -> anyhow::Result<String> {
+    let schema = match function_name.trim() {
+        "create_transfer" => schema_for!(TransferInput),
+        "create_id_request_and_private_data" => schema_for!(IdRequestInput),
+        "create_credential" => schema_for!(CredentialInput),
+        name => anyhow::bail!("There is no schema for the input of '{}'.", name),
+    };
+    Ok(serde_json::to_string(&schema)?)
+}
+
+/// The JSON schema of the output of the library function with the given name.
+#[cfg(feature = "schema")]
+pub(crate) fn get_output_schema_aux(function_name: &str) -> anyhow::Result<String> {
+    let schema = match function_name.trim() {
+        "create_transfer" => schema_for!(SignedTransaction),
+        "create_id_request_and_private_data" => schema_for!(IdRequestOutput),
+        "create_credential" => schema_for!(CredentialOutput),
+        name => anyhow::bail!("There is no schema for the output of '{}'.", name),
+    };
+    Ok(serde_json::to_string(&schema)?)
+}
+
+/// Without the `schema` feature there are no schemas.
+#[cfg(not(feature = "schema"))]
+pub(crate) fn get_input_schema_aux(_function_name: &str) -> anyhow::Result<String> {
+    anyhow::bail!("The library is built without schema support.")
+}
+
+/// Without the `schema` feature there are no schemas.
+#[cfg(not(feature = "schema"))]
+pub(crate) fn get_output_schema_aux(_function_name: &str) -> anyhow::Result<String> {
+    anyhow::bail!("The library is built without schema support.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "schema")]
+    use serde_json::Value;
+
+    /// The names of the properties and the required properties of a schema.
+    #[cfg(feature = "schema")]
+    fn schema_fields(schema: &str) -> (Vec<String>, Vec<Value>) {
+        let schema: Value = serde_json::from_str(schema).expect("Schema is JSON.");
+        let properties = schema["properties"]
+            .as_object()
+            .expect("Properties are listed.")
+            .keys()
+            .cloned()
+            .collect();
+        // The list of required properties is omitted if it would be empty.
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        (properties, required)
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_input_schemas() {
+        let (properties, required) =
+            schema_fields(&get_input_schema_aux("create_transfer").expect("Schema should exist."));
+        assert!(required.contains(&Value::from("amount")));
+        assert!(required.contains(&Value::from("from")));
+        assert!(!required.contains(&Value::from("memo")));
+        assert!(properties.contains(&"keyHandles".to_owned()));
+        assert!(properties.contains(&"numSignatures".to_owned()));
+
+        let (properties, required) = schema_fields(
+            &get_input_schema_aux("create_id_request_and_private_data")
+                .expect("Schema should exist."),
+        );
+        assert!(properties.contains(&"chosenArs".to_owned()));
+        assert!(properties.contains(&"randomnessSeed".to_owned()));
+        assert!(!required.contains(&Value::from("ipInfo")));
+
+        let (_, required) = schema_fields(
+            &get_input_schema_aux("create_credential").expect("Schema should exist."),
+        );
+        assert!(required.contains(&Value::from("identityObject")));
+        assert!(required.contains(&Value::from("expiry")));
+        assert!(get_input_schema_aux("no_such_function").is_err());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_output_schemas() {
+        let (_, required) =
+            schema_fields(&get_output_schema_aux("create_transfer").expect("Schema should exist."));
+        assert!(required.contains(&Value::from("signatures")));
+        assert!(required.contains(&Value::from("transaction")));
+
+        let (_, required) = schema_fields(
+            &get_output_schema_aux("create_id_request_and_private_data")
+                .expect("Schema should exist."),
+        );
+        assert!(required.contains(&Value::from("initialAccountData")));
+
+        // The keys of the account are flattened into the output.
+        let (_, required) = schema_fields(
+            &get_output_schema_aux("create_credential").expect("Schema should exist."),
+        );
+        assert!(required.contains(&Value::from("credential")));
+        assert!(required.contains(&Value::from("accountAddress")));
+        assert!(get_output_schema_aux("no_such_function").is_err());
+    }
+
+    #[cfg(not(feature = "schema"))]
+    #[test]
+    fn test_no_schemas() {
+        assert!(get_input_schema_aux("create_transfer").is_err());
+        assert!(get_output_schema_aux("create_transfer").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Power-on self-test of the cryptographic primitives used by the library.
+//!
+//! The test checks that the random number generator is not stuck, runs
+//! known-answer tests of SHA-256, ed25519 and ElGamal, and checks that the
+//! embedded table for decrypting amounts decrypts correctly. It is meant to
+//! be run once when the library is loaded, and the library should not be used
+//! if any of the checks fail.
+use crate::TABLE_BYTES;
+use crypto_common::{c_char, types::Amount, *};
+use curve_arithmetic::Curve;
+use ed25519_dalek as ed25519;
+use elgamal::{BabyStepGiantStep, Message, PublicKey, SecretKey};
+use id::{constants::ArCurve, types::GlobalContext};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::{ffi::CString, io::Cursor};
+
+/// SHA-256 of "abc", from FIPS 180-2.
+const SHA256_ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+/// Test 1 of RFC 8032, the signature of the empty message.
+const ED25519_SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const ED25519_PUBLIC: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const ED25519_SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+/// ElGamal encryption on BLS12-381 G1 with the standard generator `g`, secret
+/// key `sk`, randomness `r` and message `g^m`. The expected public key
+/// `g^sk` and ciphertext `(g^r, g^(sk * r + m))` are in the compressed
+/// serialization of zcash, and were computed independently of this library
+/// with a plain Python implementation of the curve.
+const ELGAMAL_SECRET: u64 = 0x5a3c_1f0e_9d7b_2468;
+const ELGAMAL_RANDOMNESS: u64 = 0x0f1e_2d3c_4b5a_6978;
+const ELGAMAL_MESSAGE: u64 = 0x1234_5678_90ab_cdef;
+const ELGAMAL_PUBLIC: &str = "95a612756f128716aaf53cd86517bd2426717e7778930dfd0532c1da1025621092b98091f09c58e0f49b25e0048a22f6";
+const ELGAMAL_CIPHER: &str = "b346d38bdf451b55726e7b9baa8546c73d10528803d7aadc6782afb2c58e70887fb90e0641e4c786a3170f8c3b9a77c6815c592101ee4a04441f22741097e513c7cf6bf65c520870cacb97d288ea017c9a78ddc3908e87d5cc84e206fad9545b";
+
+/// A single check of the self-test.
+#[derive(SerdeSerialize)]
+pub struct Check {
+    #[serde(rename = "check")]
+    pub check:   &'static str,
+    #[serde(rename = "passed")]
+    pub passed:  bool,
+    #[serde(rename = "details", skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// The result of the self-test. It passed if all the checks passed.
+#[derive(SerdeSerialize)]
+pub struct SelfTestReport {
+    #[serde(rename = "passed")]
+    pub passed: bool,
+    #[serde(rename = "checks")]
+    pub checks: Vec<Check>,
+}
+
+fn check(check: &'static str, result: Result<(), String>) -> Check {
+    match result {
+        Ok(()) => Check {
+            check,
+            passed: true,
+            details: None,
+        },
+        Err(details) => Check {
+            check,
+            passed: false,
+            details: Some(details),
+        },
+    }
+}
+
+/// Check the output of the random number generator used by the library. This
+/// only detects a generator that is stuck. A working generator fails the
+/// check with probability 2^-256, so it never fails by chance.
+fn check_rng() -> Result<(), String> {
+    let mut csprng = rand::thread_rng();
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    csprng.fill_bytes(&mut first);
+    csprng.fill_bytes(&mut second);
+    if first == second {
+        return Err("Two consecutive outputs are equal.".into());
+    }
+    Ok(())
+}
+
+fn check_sha256() -> Result<(), String> {
+    let hash = hex::encode(Sha256::digest(b"abc"));
+    if hash != SHA256_ABC {
+        return Err(format!("Unexpected hash {}.", hash));
+    }
+    Ok(())
+}
+
+fn check_ed25519() -> Result<(), String> {
+    let decode = |s: &str| hex::decode(s).map_err(|e| e.to_string());
+    let secret =
+        ed25519::SecretKey::from_bytes(&decode(ED25519_SECRET)?).map_err(|e| e.to_string())?;
+    let public = ed25519::PublicKey::from(&secret);
+    if public.as_bytes()[..] != decode(ED25519_PUBLIC)?[..] {
+        return Err("Unexpected public key.".into());
+    }
+    let signature = ed25519::ExpandedSecretKey::from(&secret).sign(&[], &public);
+    if signature.to_bytes()[..] != decode(ED25519_SIGNATURE)?[..] {
+        return Err("Unexpected signature.".into());
+    }
+    public
+        .verify_strict(&[], &signature)
+        .map_err(|e| format!("The signature does not verify: {}", e))
+}
+
+/// Encrypt with fixed secret key, randomness and message, and compare the
+/// result with the expected values.
+fn check_elgamal() -> Result<(), String> {
+    let g = ArCurve::one_point();
+    let sk = SecretKey {
+        generator: g,
+        scalar:    ArCurve::scalar_from_u64(ELGAMAL_SECRET),
+    };
+    let pk = PublicKey::from(&sk);
+    if hex::encode(to_bytes(&pk.key)) != ELGAMAL_PUBLIC {
+        return Err("Unexpected public key.".into());
+    }
+    let message = Message {
+        value: g.mul_by_scalar(&ArCurve::scalar_from_u64(ELGAMAL_MESSAGE)),
+    };
+    let cipher = pk.hide(&ArCurve::scalar_from_u64(ELGAMAL_RANDOMNESS), &message);
+    if hex::encode(to_bytes(&cipher)) != ELGAMAL_CIPHER {
+        return Err("Unexpected ciphertext.".into());
+    }
+    if sk.decrypt(&cipher) != message {
+        return Err("Decryption does not return the message.".into());
+    }
+    Ok(())
+}
+
+/// Check that the embedded table parses and decrypts amounts whose chunks are
+/// at the boundaries of the table.
+fn check_table() -> Result<(), String> {
+    let table: BabyStepGiantStep<ArCurve> = (&mut Cursor::new(TABLE_BYTES))
+        .get()
+        .map_err(|e| format!("The table cannot be parsed: {}", e))?;
+    let global = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+    let sk = SecretKey {
+        generator: *global.elgamal_generator(),
+        scalar:    ArCurve::scalar_from_u64(1),
+    };
+    for &value in &[0, 1, 65535, 65536, u64::from(u32::MAX), u64::MAX] {
+        let encrypted =
+            encrypted_transfers::encrypt_amount_with_fixed_randomness(&global, Amount::from(value));
+        let decrypted = encrypted_transfers::decrypt_amount(&table, &sk, &encrypted);
+        if decrypted != Amount::from(value) {
+            return Err(format!("{} decrypts to {}.", value, decrypted));
+        }
+    }
+    Ok(())
+}
+
+/// Run all the checks of the self-test.
+pub fn self_test() -> SelfTestReport {
+    let checks = vec![
+        check("rng", check_rng()),
+        check("sha256", check_sha256()),
+        check("ed25519", check_ed25519()),
+        check("elgamal", check_elgamal()),
+        check("table", check_table()),
+    ];
+    SelfTestReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+    }
+}
+
+/// Run the self-test of the cryptographic primitives, and return a
+/// NUL-terminated UTF8-encoded JSON object with the result. The returned
+/// string must be freed by the caller by calling the function
+/// 'free_response_string'.
+#[no_mangle]
+pub extern "C" fn crypto_self_test() -> *mut c_char {
+    let report = serde_json::to_string(&self_test()).expect("The report can be serialized.");
+    CString::new(report)
+        .expect("JSON does not contain NUL bytes.")
+        .into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test() {
+        let report = self_test();
+        for c in report.checks.iter() {
+            assert!(c.passed, "Check {} failed: {:?}", c.check, c.details);
+        }
+        assert!(report.passed);
+    }
+}
@@ -0,0 +1,280 @@
+//! Signing of transactions with keys held by the platform.
+//!
+//! By default the functions that create transactions take the secret keys of
+//! the sender account in the `keys` field of the input. Wallets that keep the
+//! keys in secure hardware, e.g., the iOS secure enclave or the Android
+//! keystore, can instead give `keyHandles`, which maps credential indices and
+//! key indices to platform specific handles of the keys. The library then asks
+//! the callback registered with `set_sign_callback` to sign the transaction
+//! hash with each of the keys, so that the secret keys never leave the secure
+//! hardware.
+use crate::warnings::{warn, WarningKind};
+use anyhow::{bail, ensure};
+use crypto_common::{
+    c_char, size_t,
+    types::{CredentialIndex, KeyIndex, Signature},
+    SerdeDeserialize,
+};
+use ed25519_dalek as ed25519;
+use id::types::AccountKeys;
+use std::{
+    collections::BTreeMap,
+    ffi::CString,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Length of an ed25519 signature.
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Something that can sign messages with the keys of an account.
+pub trait Signer {
+    /// The credential and key indices of the keys that are used for signing.
+    /// This fails if the keys cannot be used.
+    fn key_indices(&self) -> anyhow::Result<Vec<(CredentialIndex, KeyIndex)>>;
+
+    /// Sign the message with the key with the given indices.
+    fn sign(
+        &self,
+        cred_index: CredentialIndex,
+        key_index: KeyIndex,
+        message: &[u8],
+    ) -> anyhow::Result<Signature>;
+}
+
+/// Sign with the secret keys of the account.
+impl Signer for AccountKeys {
+    fn key_indices(&self) -> anyhow::Result<Vec<(CredentialIndex, KeyIndex)>> {
+        self.validate()?;
+        let threshold_ignored = self.keys.len() > usize::from(self.threshold.0)
+            || self
+                .keys
+                .values()
+                .any(|cred| cred.keys.len() > usize::from(cred.threshold.0));
+        if threshold_ignored {
+            warn(
+                WarningKind::Other,
+                "The signature thresholds are ignored and all the given keys are used for signing."
+                    .to_owned(),
+            );
+        }
+        Ok(self
+            .keys
+            .iter()
+            .flat_map(|(cred_index, cred)| {
+                cred.keys
+                    .keys()
+                    .map(move |key_index| (*cred_index, *key_index))
+            })
+            .collect())
+    }
+
+    fn sign(
+        &self,
+        cred_index: CredentialIndex,
+        key_index: KeyIndex,
+        message: &[u8],
+    ) -> anyhow::Result<Signature> {
+        let kp = match self
+            .keys
+            .get(&cred_index)
+            .and_then(|cred| cred.keys.get(&key_index))
+        {
+            Some(kp) => kp,
+            None => bail!(
+                "No key with index {} for credential {}.",
+                key_index,
+                cred_index
+            ),
+        };
+        let signature = ed25519::ExpandedSecretKey::from(&kp.secret).sign(message, &kp.public);
+        Ok(Signature {
+            sig: signature.to_bytes().to_vec(),
+        })
+    }
+}
+
+/// Type of the callback that signs a message with a key held by the platform.
+/// The arguments are the NUL-terminated handle of the key, the message, the
+/// length of the message, and a buffer of 64 bytes for the ed25519 signature.
+/// The callback returns 1 if it wrote the signature and 0 otherwise.
+pub type SignCallback = unsafe extern "C" fn(
+    key_handle: *const c_char,
+    message: *const u8,
+    message_len: size_t,
+    signature: *mut u8,
+) -> u8;
+
+/// The registered sign callback, or 0 if none is registered.
+static SIGN_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
+/// Register the callback used to sign with key handles. Passing a NULL pointer
+/// removes the callback.
+///
+/// # Safety
+/// The callback must be safe to call from any thread that calls into the
+/// library, and it must write exactly 64 bytes to the signature buffer when it
+/// returns 1.
+#[no_mangle]
+pub unsafe extern "C" fn set_sign_callback(cb: Option<SignCallback>) {
+    let ptr = match cb {
+        Some(cb) => cb as usize,
+        None => 0,
+    };
+    SIGN_CALLBACK.store(ptr, Ordering::Release);
+}
+
+/// Handles of keys held by the platform, indexed by credential index and key
+/// index. Signing with them uses the callback registered with
+/// [set_sign_callback].
+#[derive(SerdeDeserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct KeyHandles {
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "BTreeMap<u8, BTreeMap<u8, String>>")
+    )]
+    handles: BTreeMap<CredentialIndex, BTreeMap<u8, String>>,
+}
+
+impl Signer for KeyHandles {
+    fn key_indices(&self) -> anyhow::Result<Vec<(CredentialIndex, KeyIndex)>> {
+        ensure!(
+            self.handles.values().any(|keys| !keys.is_empty()),
+            "At least one key handle must be given."
+        );
+        ensure!(
+            SIGN_CALLBACK.load(Ordering::Acquire) != 0,
+            "Key handles are given, but no sign callback is registered."
+        );
+        Ok(self
+            .handles
+            .iter()
+            .flat_map(|(cred_index, keys)| {
+                keys.keys()
+                    .map(move |key_index| (*cred_index, KeyIndex(*key_index)))
+            })
+            .collect())
+    }
+
+    fn sign(
+        &self,
+        cred_index: CredentialIndex,
+        key_index: KeyIndex,
+        message: &[u8],
+    ) -> anyhow::Result<Signature> {
+        let handle = match self
+            .handles
+            .get(&cred_index)
+            .and_then(|keys| keys.get(&key_index.0))
+        {
+            Some(handle) => CString::new(handle.as_str())?,
+            None => bail!(
+                "No key handle with index {} for credential {}.",
+                key_index,
+                cred_index
+            ),
+        };
+        let cb = SIGN_CALLBACK.load(Ordering::Acquire);
+        ensure!(cb != 0, "No sign callback is registered.");
+        // This is safe since the only values we store are either 0 or valid
+        // function pointers of type SignCallback.
+        let cb = unsafe { std::mem::transmute::<usize, SignCallback>(cb) };
+        let mut sig = vec![0u8; SIGNATURE_LENGTH];
+        let success = unsafe {
+            cb(
+                handle.as_ptr(),
+                message.as_ptr(),
+                message.len(),
+                sig.as_mut_ptr(),
+            )
+        };
+        ensure!(
+            success == 1,
+            "The sign callback failed to sign with key handle {:?}.",
+            handle
+        );
+        Ok(Signature { sig })
+    }
+}
+
+/// The signer given by the `keys` or the `keyHandles` of an input. Exactly one
+/// of them must be present.
+pub(crate) fn select_signer<'a>(
+    keys: &'a Option<AccountKeys>,
+    key_handles: &'a Option<KeyHandles>,
+) -> anyhow::Result<&'a dyn Signer> {
+    match (keys, key_handles) {
+        (Some(keys), None) => Ok(keys),
+        (None, Some(handles)) => Ok(handles),
+        (Some(_), Some(_)) => bail!("Only one of 'keys' and 'keyHandles' can be given."),
+        (None, None) => bail!("One of 'keys' and 'keyHandles' must be given."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::warnings::collect_warnings;
+    use crypto_common::types::KeyPair;
+    use id::types::CredentialData;
+    use std::{convert::TryFrom, ffi::CStr};
+
+    thread_local! {
+        static TEST_KEY: ed25519::Keypair = ed25519::Keypair::generate(&mut rand::thread_rng());
+    }
+
+    /// A sign callback that signs with [TEST_KEY] for the handle "test-key",
+    /// and fails for other handles.
+    unsafe extern "C" fn test_callback(
+        key_handle: *const c_char,
+        message: *const u8,
+        message_len: size_t,
+        signature: *mut u8,
+    ) -> u8 {
+        if CStr::from_ptr(key_handle).to_str() != Ok("test-key") {
+            return 0;
+        }
+        let message = std::slice::from_raw_parts(message, message_len);
+        let sig = TEST_KEY.with(|kp| ed25519_dalek::Signer::sign(kp, message));
+        std::ptr::copy_nonoverlapping(sig.to_bytes().as_ptr(), signature, SIGNATURE_LENGTH);
+        1
+    }
+
+    #[test]
+    fn test_signers() {
+        let message = b"transaction hash";
+
+        let kp = KeyPair::generate(&mut rand::thread_rng());
+        let public = kp.public;
+        let keys = AccountKeys::from(CredentialData::from(kp));
+        let (indices, warnings) = collect_warnings(|| keys.key_indices().unwrap());
+        assert_eq!(indices, vec![(CredentialIndex { index: 0 }, KeyIndex(0))]);
+        assert!(warnings.all().is_empty());
+        let sig = keys
+            .sign(CredentialIndex { index: 0 }, KeyIndex(0), message)
+            .unwrap();
+        let sig = ed25519::Signature::try_from(&sig.sig[..]).unwrap();
+        assert!(ed25519_dalek::Verifier::verify(&public, message, &sig).is_ok());
+
+        let handles: KeyHandles =
+            serde_json::from_str(r#"{"0": {"0": "test-key", "1": "unknown-key"}}"#).unwrap();
+        unsafe { set_sign_callback(None) };
+        assert!(handles.key_indices().is_err());
+        unsafe { set_sign_callback(Some(test_callback)) };
+        assert_eq!(handles.key_indices().unwrap().len(), 2);
+        let sig = handles
+            .sign(CredentialIndex { index: 0 }, KeyIndex(0), message)
+            .unwrap();
+        let sig = ed25519::Signature::try_from(&sig.sig[..]).unwrap();
+        let public = TEST_KEY.with(|kp| kp.public);
+        assert!(ed25519_dalek::Verifier::verify(&public, message, &sig).is_ok());
+        assert!(handles
+            .sign(CredentialIndex { index: 0 }, KeyIndex(1), message)
+            .is_err());
+        assert!(handles
+            .sign(CredentialIndex { index: 1 }, KeyIndex(0), message)
+            .is_err());
+        unsafe { set_sign_callback(None) };
+    }
+}
@@ -0,0 +1,131 @@
+//! Proofs of statements about the attributes of a credential.
+//!
+//! A dApp asks the wallet to prove a statement about one of its credentials,
+//! e.g., that the date of birth is in a range, or that the country of
+//! residence is one of a list of countries, without revealing the attributes.
+//! The proof is made against the commitments to the attributes of the
+//! credential on the chain, using the randomness of the commitments that
+//! `create_credential` returns as `commitmentsRandomness`. It is verified with
+//! [verify_statement](id::id_verifier::verify_statement), with the
+//! [ChallengeContext::Legacy] context.
+use crate::{parse_input, try_get, ExampleCurve};
+use anyhow::Context;
+use id::{
+    constants::AttributeKind,
+    id_prover::prove_statement,
+    statement::{AtomicStatement, StatementBuilder},
+    types::{CommitmentsRandomness, GlobalContext, IdentityObject},
+};
+use pairing::bls12_381::Bls12;
+use random_oracle::ChallengeContext;
+use serde_json::to_string;
+
+/// Prove a statement about the attributes of a credential. The input has the
+/// `statement`, a list of statements about individual attributes, the hex
+/// encoded `challenge` of the verifier, the `identityObject` the credential is
+/// derived from, and the `commitmentsRandomness` and `global` context the
+/// credential was created with. The response has the hex encoded `proof`.
+pub fn prove_statement_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let global: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let statements: Vec<AtomicStatement<AttributeKind>> = try_get(&v, "statement")?;
+    let challenge = hex::decode(try_get::<String>(&v, "challenge")?)
+        .context("The challenge is not hex encoded.")?;
+    let id_object: IdentityObject<Bls12, ExampleCurve, AttributeKind> =
+        try_get(&v, "identityObject")?;
+    let randomness: CommitmentsRandomness<ExampleCurve> = try_get(&v, "commitmentsRandomness")?;
+    let statement = statements
+        .into_iter()
+        .fold(StatementBuilder::new(), StatementBuilder::add)
+        .build()?;
+    let proof = prove_statement(
+        &ChallengeContext::Legacy,
+        &challenge,
+        &global,
+        &statement,
+        &id_object.alist.alist,
+        &randomness,
+        &mut rand::thread_rng(),
+    )?;
+    let response = json!({ "proof": proof });
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_credential_aux;
+    use crypto_common::Versioned;
+    use id::{
+        id_verifier::verify_statement,
+        test::read_golden_bytes,
+        types::{
+            AccountCredential, AccountCredentialMessage, AccountCredentialWithoutProofs,
+            AttributeTag,
+        },
+    };
+    use serde_json::{from_slice, from_str, from_value, Value};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_prove_statement() {
+        let input: Value = from_slice(&read_golden_bytes(
+            "rust-bins/wallet-notes/files/create_credential-input.json",
+        ))
+        .expect("Input is JSON.");
+        let created: Value = from_str(
+            &create_credential_aux(&input.to_string()).expect("Credential should be created."),
+        )
+        .unwrap();
+        let credential: Versioned<AccountCredentialMessage<Bls12, ExampleCurve, AttributeKind>> =
+            from_value(created["credential"].clone()).unwrap();
+        let credential = match credential.value.credential {
+            AccountCredential::Normal { cdi } => AccountCredentialWithoutProofs::Normal {
+                cdv:         cdi.values,
+                commitments: cdi.proofs.id_proofs.commitments,
+            },
+            AccountCredential::Initial { .. } => panic!("The credential should be normal."),
+        };
+
+        let mut proof_input = json!({
+            "global": input["global"],
+            "identityObject": input["identityObject"],
+            "commitmentsRandomness": created["commitmentsRandomness"],
+            "statement": [{
+                "type": "AttributeInSet",
+                "attributeTag": "nationality",
+                "set": ["DE", "DK"],
+            }],
+            "challenge": "00ff",
+        });
+        let response: Value = from_str(
+            &prove_statement_aux(&proof_input.to_string()).expect("Statement should be proved."),
+        )
+        .unwrap();
+        let proof = from_value(response["proof"].clone()).expect("Proof should parse.");
+        let global: GlobalContext<ExampleCurve> = from_value(input["global"].clone()).unwrap();
+        let statement = StatementBuilder::new()
+            .in_set(AttributeTag::from_str("nationality").unwrap(), vec![
+                AttributeKind("DE".to_owned()),
+                AttributeKind("DK".to_owned()),
+            ])
+            .build()
+            .unwrap();
+        assert!(verify_statement(
+            &ChallengeContext::Legacy,
+            &[0, 255],
+            &global,
+            &statement,
+            &credential,
+            &proof
+        ));
+
+        // Statements the attributes do not satisfy are rejected.
+        proof_input["statement"] = json!([{
+            "type": "AttributeNotInSet",
+            "attributeTag": "nationality",
+            "set": ["DK"],
+        }]);
+        assert!(prove_statement_aux(&proof_input.to_string()).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! The table used for decrypting amounts.
+//!
+//! Decryption computes discrete logarithms with the baby step giant step
+//! algorithm, which uses a precomputed table of `m` points. Decrypting a 32-bit
+//! chunk of an amount takes up to `2^32 / m` steps, so a larger table makes
+//! decryption faster, at the cost of memory. The library embeds a table with
+//! 2^16 entries, which is parsed on every call of `decrypt_encrypted_amount`.
+//! Integrators can instead supply a table of a different size with
+//! `set_decryption_table`, either as bytes, e.g., read from a file shipped with
+//! the application, or by letting the library compute it on the device.
+use crate::{parse_input, try_get, ExampleCurve, TABLE_BYTES};
+use anyhow::{bail, ensure};
+use crypto_common::*;
+use elgamal::BabyStepGiantStep;
+use id::types::GlobalContext;
+use lazy_static::lazy_static;
+use serde_json::{to_string, Value};
+use std::{
+    io::Cursor,
+    sync::{Arc, RwLock},
+};
+
+/// Maximum size of a table computed by the library. A table of this size
+/// needs about 100MB of memory.
+pub const MAX_TABLE_SIZE: u64 = 1 << 20;
+
+lazy_static! {
+    /// The table set with `set_decryption_table`, if any.
+    static ref DECRYPTION_TABLE: RwLock<Option<Arc<BabyStepGiantStep<ExampleCurve>>>> =
+        RwLock::new(None);
+}
+
+/// Apply the function to the table set with `set_decryption_table`, or to the
+/// embedded table if none is set.
+pub(crate) fn with_decryption_table<A>(
+    f: impl FnOnce(&BabyStepGiantStep<ExampleCurve>) -> A,
+) -> anyhow::Result<A> {
+    // Clone the table so that the lock is not held during decryption. A table
+    // that is replaced meanwhile is freed when the last decryption using it
+    // finishes.
+    let table = DECRYPTION_TABLE
+        .read()
+        .expect("Decryption table lock is not poisoned.")
+        .clone();
+    match table {
+        Some(table) => Ok(f(&table)),
+        None => {
+            let table = (&mut Cursor::new(TABLE_BYTES)).get()?;
+            Ok(f(&table))
+        }
+    }
+}
+
+/// Parse or compute the table described by the input of
+/// `set_decryption_table`.
+fn decryption_table_from_input(v: &Value) -> anyhow::Result<BabyStepGiantStep<ExampleCurve>> {
+    let global: GlobalContext<ExampleCurve> = match v.get("global") {
+        Some(_) => try_get(v, "global")?,
+        None => GlobalContext::generate(String::from("genesis_string")),
+    };
+    let base = global.encryption_in_exponent_generator();
+    let table = match (v.get("tableBytes"), v.get("tableSize")) {
+        (Some(_), None) => {
+            let bytes = hex::decode(try_get::<String>(v, "tableBytes")?)?;
+            let parsed: BabyStepGiantStep<ExampleCurve> = (&mut Cursor::new(&bytes)).get()?;
+            // Reconstructing the table from its base checks that it is a table for
+            // the base, since decryption with a table for another base does not
+            // terminate.
+            BabyStepGiantStep::from_table(base, parsed.into_table())?
+        }
+        (None, Some(_)) => {
+            let m: u64 = try_get(v, "tableSize")?;
+            ensure!(
+                m > 0 && m <= MAX_TABLE_SIZE,
+                "tableSize must be between 1 and {}.",
+                MAX_TABLE_SIZE
+            );
+            BabyStepGiantStep::new(base, m)
+        }
+        _ => bail!("Exactly one of 'tableBytes' and 'tableSize' must be given."),
+    };
+    Ok(table)
+}
+
+/// Set the table used for decryption. The input has either the field
+/// `tableBytes` with a hex encoded table, in the serialization of
+/// `BabyStepGiantStep`, or the field `tableSize` with the size of the table
+/// to compute. The optional field `global` gives the global context the table
+/// is for. It defaults to the context the embedded table is computed for.
+pub(crate) fn set_decryption_table_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let table = decryption_table_from_input(&v)?;
+    let m = table.m();
+    // The lock is only poisoned if a thread panicked while holding it, and we
+    // never panic while holding it.
+    *DECRYPTION_TABLE
+        .write()
+        .expect("Decryption table lock is not poisoned.") = Some(Arc::new(table));
+    Ok(to_string(&json!({ "tableSize": m }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve_arithmetic::Curve;
+
+    #[test]
+    fn test_decryption_table_from_input() {
+        let embedded: BabyStepGiantStep<ExampleCurve> = (&mut Cursor::new(TABLE_BYTES))
+            .get()
+            .expect("Table parses.");
+        let table = |input: Value| decryption_table_from_input(&input);
+        assert!(table(json!({ "tableSize": 0 })).is_err());
+        assert!(table(json!({ "tableSize": 16, "tableBytes": "00" })).is_err());
+        // A table for another base is rejected.
+        let other = BabyStepGiantStep::new(&ExampleCurve::one_point(), 16);
+        assert!(table(json!({ "tableBytes": hex::encode(to_bytes(&other)) })).is_err());
+
+        let parsed =
+            table(json!({ "tableBytes": hex::encode(TABLE_BYTES) })).expect("Table should parse.");
+        assert!(parsed == embedded);
+        let computed = table(json!({ "tableSize": 16 })).expect("Table should be computed.");
+        assert_eq!(computed.m(), 16);
+    }
+}
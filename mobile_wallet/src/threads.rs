@@ -0,0 +1,33 @@
+//! Control over the threads used by the library.
+//!
+//! Some computations, e.g., the PRF evaluations when creating credentials,
+//! run in parallel via [crypto_common::parallel]. By default they use one
+//! thread per core, which an application may want to limit, e.g., to keep the
+//! user interface responsive on devices with few cores.
+
+/// Set the number of threads used for parallel computations to `n`. If `n` is
+/// 0 the default of one thread per core is restored. Computations that are
+/// already running are not affected.
+///
+/// Returns 1 if the number of threads was set, and 0 if the threads could not
+/// be created, in which case the previous setting is retained.
+#[no_mangle]
+pub extern "C" fn set_thread_count(n: u32) -> u8 {
+    u8::from(crypto_common::parallel::set_thread_count(n as usize).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::parallel::thread_count;
+
+    #[test]
+    fn test_set_thread_count() {
+        assert_eq!(set_thread_count(2), 1);
+        assert_eq!(thread_count(), 2);
+        assert_eq!(set_thread_count(3), 1, "The setting can be changed.");
+        assert_eq!(thread_count(), 3);
+        assert_eq!(set_thread_count(0), 1);
+        assert!(thread_count() >= 1);
+    }
+}
@@ -0,0 +1,167 @@
+//! Creating a credential in two phases, with the account keys held elsewhere.
+//!
+//! `create_credential` generates the account keys and signs the credential in
+//! one go. With `prepare_credential` the wallet instead gives the public keys
+//! of the account, and gets back the credential without the signatures of the
+//! account keys together with the hash that must be signed. The credential can
+//! then be reviewed, and the hash signed, e.g., on a hardware wallet, before
+//! `finalize_credential` attaches the signatures and produces the credential
+//! that is sent to the chain.
+use crate::{make_policy, parse_input, parse_transaction_input, try_get, ExampleCurve};
+use anyhow::ensure;
+use crypto_common::{
+    types::{KeyIndex, TransactionTime},
+    *,
+};
+use either::Either::Left;
+use id::{
+    account_holder,
+    constants::AttributeKind,
+    types::*,
+    utils::{credential_hash_to_sign, verify_account_ownership_proof},
+};
+use pairing::bls12_381::Bls12;
+use random_oracle::ChallengeContext;
+use serde_json::to_string;
+use std::collections::BTreeMap;
+
+type UnsignedCredential = UnsignedCredentialDeploymentInfo<Bls12, ExampleCurve, AttributeKind>;
+
+/// Create a credential for a new account without signing it. The input is as
+/// for `create_credential`, with the additional field `credentialPublicKeys`
+/// with the public keys and threshold of the account.
+pub fn prepare_credential_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+    let expiry: TransactionTime = try_get(&v, "expiry")?;
+    let ip_info: IpInfo<Bls12> = try_get(&v, "ipInfo")?;
+    let ars_infos: BTreeMap<ArIdentity, ArInfo<ExampleCurve>> = try_get(&v, "arsInfos")?;
+    let global_context: GlobalContext<ExampleCurve> = try_get(&v, "global")?;
+    let id_object: IdentityObject<Bls12, ExampleCurve, AttributeKind> =
+        try_get(&v, "identityObject")?;
+    let id_use_data: IdObjectUseData<Bls12, ExampleCurve> = try_get(&v, "privateIdObjectData")?;
+    let tags: Vec<AttributeTag> = try_get(&v, "revealedAttributes")?;
+    let acc_num: u8 = try_get(&v, "accountNumber")?;
+    let cred_key_info: CredentialPublicKeys = try_get(&v, "credentialPublicKeys")?;
+
+    let policy = make_policy(&id_object, tags)?;
+    let context = IpContext::new(&ip_info, &ars_infos, &global_context);
+    let (unsigned_cdi, randomness) = account_holder::create_unsigned_credential(
+        &ChallengeContext::Legacy,
+        context,
+        &id_object,
+        &id_use_data,
+        acc_num,
+        policy,
+        cred_key_info,
+        None,
+    )?;
+    let hash = credential_hash_to_sign(&unsigned_cdi.values, &unsigned_cdi.proofs, &Left(expiry));
+
+    // unwrap is safe here since we've generated the credential already, and that
+    // does the same computation.
+    let enc_key = id_use_data.aci.prf_key.prf_exponent(acc_num).unwrap();
+    let secret_key = elgamal::SecretKey {
+        generator: *global_context.elgamal_generator(),
+        scalar:    enc_key,
+    };
+
+    let response = json!({
+        "unsignedCredential": unsigned_cdi,
+        "expiry": expiry,
+        "hashToSign": hex::encode(&hash),
+        "commitmentsRandomness": randomness,
+        "encryptionSecretKey": secret_key,
+        "encryptionPublicKey": elgamal::PublicKey::from(&secret_key),
+        "accountAddress": AccountAddress::new(&unsigned_cdi.values.cred_id),
+    });
+    Ok(to_string(&response)?)
+}
+
+/// Attach the signatures of the account keys to a credential produced by
+/// `prepare_credential`. The input has the fields `unsignedCredential` and
+/// `expiry` as returned by `prepare_credential`, and `signatures`, a map from
+/// key indices to signatures on the hash. There must be a valid signature for
+/// each of the keys of the credential.
+pub fn finalize_credential_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let unsigned_cdi: UnsignedCredential = try_get(&v, "unsignedCredential")?;
+    let expiry: TransactionTime = try_get(&v, "expiry")?;
+    let sigs: BTreeMap<KeyIndex, AccountOwnershipSignature> = try_get(&v, "signatures")?;
+
+    let hash = credential_hash_to_sign(&unsigned_cdi.values, &unsigned_cdi.proofs, &Left(expiry));
+    let proof_acc_sk = AccountOwnershipProof { sigs };
+    let keys = &unsigned_cdi.values.cred_key_info;
+    ensure!(
+        verify_account_ownership_proof(&keys.keys, keys.threshold, &proof_acc_sk, &hash),
+        "The signatures are not valid signatures of the keys of the credential on the hash."
+    );
+
+    let cdi = CredentialDeploymentInfo {
+        values: unsigned_cdi.values,
+        proofs: CredDeploymentProofs {
+            id_proofs: unsigned_cdi.proofs,
+            proof_acc_sk,
+        },
+    };
+    let credential_message = AccountCredentialMessage {
+        message_expiry: expiry,
+        credential:     AccountCredential::Normal { cdi },
+    };
+    let response = json!({
+        "credential": Versioned::new(VERSION_0, credential_message),
+    });
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::KeyPair;
+    use ed25519_dalek as ed25519;
+    use id::test::read_golden_bytes;
+    use serde_json::{from_slice, from_str, Value};
+
+    #[test]
+    fn test_prepare_and_finalize_credential() {
+        let mut input: Value = from_slice(&read_golden_bytes(
+            "rust-bins/wallet-notes/files/create_credential-input.json",
+        ))
+        .expect("Input is JSON.");
+        let cred_data = CredentialData::from(KeyPair::generate(&mut rand::thread_rng()));
+        input["credentialPublicKeys"] = json!(cred_data.get_cred_key_info());
+        let prepared: Value = from_str(
+            &prepare_credential_aux(&input.to_string()).expect("Credential should be prepared."),
+        )
+        .unwrap();
+
+        let sign = |msg: &[u8]| {
+            let kp = &cred_data.keys[&KeyIndex(0)];
+            let sig = ed25519::ExpandedSecretKey::from(&kp.secret).sign(msg, &kp.public);
+            let mut sigs = BTreeMap::new();
+            sigs.insert(KeyIndex(0), AccountOwnershipSignature::from(sig));
+            json!({
+                "unsignedCredential": prepared["unsignedCredential"],
+                "expiry": prepared["expiry"],
+                "signatures": sigs,
+            })
+        };
+        let hash = hex::decode(prepared["hashToSign"].as_str().unwrap()).unwrap();
+        let finalized: Value = from_str(
+            &finalize_credential_aux(&sign(&hash).to_string())
+                .expect("Credential should be finalized."),
+        )
+        .unwrap();
+        let credential: Versioned<AccountCredentialMessage<Bls12, ExampleCurve, AttributeKind>> =
+            serde_json::from_value(finalized["credential"].clone()).unwrap();
+        match credential.value.credential {
+            AccountCredential::Normal { cdi } => assert_eq!(
+                json!(AccountAddress::new(&cdi.values.cred_id)),
+                prepared["accountAddress"]
+            ),
+            AccountCredential::Initial { .. } => panic!("The credential should be normal."),
+        }
+
+        // Signatures on anything but the hash are rejected.
+        assert!(finalize_credential_aux(&sign(b"something else").to_string()).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Creating transactions in two phases, with the account keys held elsewhere.
+//!
+//! `create_transfer` and `create_encrypted_transfer` build the transaction and
+//! sign it in one go. Hardware wallets, such as the Ledger app, instead hold
+//! the keys and sign the transaction after showing it to the user. With
+//! `create_unsigned_transfer` and `create_unsigned_encrypted_transfer` the
+//! wallet gets back the transaction without signatures together with the hash
+//! that must be signed, and the number of signatures the energy of the
+//! transaction accounts for. Once the hash has been signed, `attach_signatures`
+//! combines the signatures and the transaction into the block item that is sent
+//! to the chain.
+use crate::{
+    make_encrypted_transfer, make_transfer, operation::OperationHandle, parse_input,
+    parse_transaction_input, try_get, TransferContext, TransferInput,
+};
+use anyhow::ensure;
+use crypto_common::{types::TransactionSignature, *};
+use serde_json::{from_value, to_string, Value};
+use std::{convert::TryFrom, io::Cursor};
+use transactions::{AccountTransaction, EncodedPayload, TransactionHeader};
+
+/// The tag of account transactions in block items.
+pub(crate) const ACCOUNT_TRANSACTION_TAG: u8 = 0;
+
+/// The context of a transaction that is signed elsewhere. The input must not
+/// contain keys, and the number of signatures the transaction will have
+/// defaults to 1.
+fn unsigned_context(v: &Value) -> anyhow::Result<TransferContext> {
+    let mut ctx: TransferContext = from_value(v.clone())?;
+    ensure!(
+        ctx.keys.is_none() && ctx.key_handles.is_none(),
+        "Unsigned transactions take 'numSignatures' instead of 'keys' or 'keyHandles'."
+    );
+    ctx.num_signatures.get_or_insert(1);
+    Ok(ctx)
+}
+
+/// Create a transfer without signing it. The input is as for
+/// `create_transfer`, except that the keys are replaced by the optional
+/// `numSignatures`, which is used to compute the energy.
+pub fn create_unsigned_transfer_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+    let ctx = unsigned_context(&v)?;
+    let input = TransferInput::from_value(v)?;
+    let (hash, body) = make_transfer(&ctx, input.amount, input.memo)?;
+    let response = json!({
+        "transaction": hex::encode(&body),
+        "hashToSign": hex::encode(&hash),
+        "numSignatures": ctx.num_signatures()?,
+    });
+    Ok(to_string(&response)?)
+}
+
+/// Create an encrypted transfer without signing it. The input is as for
+/// `create_encrypted_transfer`, except that the keys are replaced by the
+/// optional `numSignatures`. Only the signatures are produced elsewhere, the
+/// secret encryption key of the sender is still needed for the proofs.
+pub fn create_unsigned_encrypted_transfer_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_transaction_input(input)?;
+    let ctx = unsigned_context(&v)?;
+    let (hash, body, remaining) = make_encrypted_transfer(&v, &ctx, None, &OperationHandle::new())?;
+    let response = json!({
+        "transaction": hex::encode(&body),
+        "hashToSign": hex::encode(&hash),
+        "numSignatures": ctx.num_signatures()?,
+        "remaining": remaining,
+    });
+    Ok(to_string(&response)?)
+}
+
+/// Split the body of a transaction into the header and the payload, checking
+/// that the payload has the size given in the header.
+pub(crate) fn parse_transaction_body(body: &[u8]) -> anyhow::Result<(TransactionHeader, Vec<u8>)> {
+    let mut source = Cursor::new(body);
+    let header: TransactionHeader = source.get()?;
+    let payload = body[source.position() as usize..].to_vec();
+    ensure!(
+        payload.len() == usize::try_from(header.payload_size)?,
+        "The payload size in the header is {}, but the payload has {} bytes.",
+        header.payload_size,
+        payload.len()
+    );
+    Ok((header, payload))
+}
+
+/// Attach signatures to a transaction produced by one of the functions
+/// creating unsigned transactions. The input has the fields `transaction` and
+/// `numSignatures` as returned by them, and `signatures`, the signatures on the
+/// hash in the same format as in the responses of the functions creating
+/// signed transactions. The signatures are not checked, since the keys of the
+/// sender are not known, but there can be at most `numSignatures` of them,
+/// since the energy of the transaction only pays for that many.
+pub fn attach_signatures_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let body = hex::decode(try_get::<String>(&v, "transaction")?)?;
+    let signature: TransactionSignature = try_get(&v, "signatures")?;
+    let num_signatures: u32 = try_get(&v, "numSignatures")?;
+    ensure!(
+        signature.num_signatures() > 0,
+        "At least one signature must be given."
+    );
+    ensure!(
+        signature.num_signatures() <= num_signatures,
+        "The transaction was created for {} signatures, but {} are given.",
+        num_signatures,
+        signature.num_signatures()
+    );
+
+    let (header, payload) = parse_transaction_body(&body)?;
+
+    let transaction = AccountTransaction {
+        signature,
+        header,
+        payload: EncodedPayload::from_bytes(payload),
+    };
+    let mut block_item = vec![ACCOUNT_TRANSACTION_TAG];
+    block_item.put(&transaction);
+
+    let response = json!({
+        "signatures": transaction.signature,
+        "transaction": hex::encode(&body),
+        "blockItem": hex::encode(&block_item),
+    });
+    Ok(to_string(&response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_transfer_aux, decoder};
+    use id::test::read_golden_bytes;
+    use serde_json::{from_slice, from_str};
+
+    #[test]
+    fn test_unsigned_transfer() {
+        let mut input: Value = from_slice(&read_golden_bytes(
+            "rust-bins/wallet-notes/files/create_transfer-input.json",
+        ))
+        .expect("Input is JSON.");
+        let signed: Value = from_str(
+            &create_transfer_aux(&input.to_string()).expect("Transfer should be created."),
+        )
+        .unwrap();
+
+        // The input gives the energy, so without the keys the transaction is
+        // the same as the signed one.
+        assert!(create_unsigned_transfer_aux(&input.to_string()).is_err());
+        input.as_object_mut().unwrap().remove("keys");
+        let unsigned: Value = from_str(
+            &create_unsigned_transfer_aux(&input.to_string())
+                .expect("Unsigned transfer should be created."),
+        )
+        .unwrap();
+        assert_eq!(unsigned["transaction"], signed["transaction"]);
+        assert_eq!(unsigned["numSignatures"], 1);
+
+        let attached: Value = from_str(
+            &attach_signatures_aux(
+                &json!({
+                    "transaction": unsigned["transaction"],
+                    "numSignatures": unsigned["numSignatures"],
+                    "signatures": signed["signatures"],
+                })
+                .to_string(),
+            )
+            .expect("Signatures should be attached."),
+        )
+        .unwrap();
+        assert_eq!(attached["signatures"], signed["signatures"]);
+        assert_eq!(attached["transaction"], signed["transaction"]);
+        let block_item = hex::decode(attached["blockItem"].as_str().unwrap()).unwrap();
+        let decoded =
+            decoder::decode_block_item(decoder::LATEST_PROTOCOL_VERSION, &block_item).unwrap();
+        assert_eq!(decoded["signatures"], signed["signatures"]);
+
+        // More signatures than the energy pays for are rejected.
+        let mut signatures = signed["signatures"].clone();
+        signatures["1"] = signatures["0"].clone();
+        assert!(attach_signatures_aux(
+            &json!({
+                "transaction": unsigned["transaction"],
+                "numSignatures": unsigned["numSignatures"],
+                "signatures": signatures,
+            })
+            .to_string()
+        )
+        .is_err());
+
+        // A truncated transaction is rejected.
+        let body = unsigned["transaction"].as_str().unwrap();
+        assert!(attach_signatures_aux(
+            &json!({
+                "transaction": body[..body.len() - 2],
+                "numSignatures": unsigned["numSignatures"],
+                "signatures": signed["signatures"],
+            })
+            .to_string()
+        )
+        .is_err());
+    }
+}
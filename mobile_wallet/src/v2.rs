@@ -0,0 +1,257 @@
+//! Version 2 of the entry points of the library.
+//!
+//! The entry points of the first version return either the response or an
+//! error message, and signal which one it is via a flag. The v2 entry points
+//! instead always return a JSON object of the form
+//!
+//! ```json
+//! { "ok": true, "value": ..., "warnings": [...] }
+//! ```
+//!
+//! where `value` is the response if `ok` is `true`, and the error message
+//! otherwise. The `warnings` are non-fatal issues found while handling the
+//! call, e.g., an expiry that is very close. Warnings are reported both when
+//! the call succeeds and when it fails.
+//!
+//! The library function is selected by its name, and takes the same input as
+//! the first version.
+use crate::{
+    baker, combine_encrypted_amounts_aux, combine_encrypted_amounts_list_aux,
+    create_credential_aux, create_credential_cancellable_aux, create_encrypted_transfer_aux,
+    create_encrypted_transfer_cancellable_aux, create_id_request_and_private_data_aux,
+    create_pub_to_sec_transfer_aux, create_sec_to_pub_transfer_aux,
+    create_sec_to_pub_transfer_cancellable_aux, create_transfer_aux,
+    create_transfer_with_schedule_aux, create_update_keys_aux, decoder, delegation,
+    derive_keys_from_seed_aux, encrypted_balance, energy, generate_accounts_aux,
+    generate_baker_keys_aux, get_credential_id_aux, mnemonic, multisig,
+    operation::OperationHandle,
+    ownership, parse_input, qr, range_proof, schema, statement_proof, try_get, unsigned_credential,
+    unsigned_transaction,
+    warnings::{collect_warnings, Warnings},
+};
+use anyhow::anyhow;
+use crypto_common::c_char;
+use serde_json::{from_str, to_string, Value};
+use std::ffi::{CStr, CString};
+
+type Aux = fn(&str) -> anyhow::Result<String>;
+
+type CancellableAux = fn(&str, &OperationHandle) -> anyhow::Result<String>;
+
+/// The library function with the given name.
+fn lookup(name: &str) -> Option<Aux> {
+    let f: Aux = match name {
+        "create_id_request_and_private_data" => create_id_request_and_private_data_aux,
+        "create_credential" => create_credential_aux,
+        "prepare_credential" => unsigned_credential::prepare_credential_aux,
+        "finalize_credential" => unsigned_credential::finalize_credential_aux,
+        "get_credential_id" => get_credential_id_aux,
+        "derive_keys_from_seed" => derive_keys_from_seed_aux,
+        "generate_accounts" => generate_accounts_aux,
+        "generate_baker_keys" => generate_baker_keys_aux,
+        "create_configure_baker" => baker::create_configure_baker_aux,
+        "create_configure_delegation" => delegation::create_configure_delegation_aux,
+        "create_transfer" => create_transfer_aux,
+        "create_transfer_with_schedule" => create_transfer_with_schedule_aux,
+        "create_update_keys" => create_update_keys_aux,
+        "create_unsigned_transfer" => unsigned_transaction::create_unsigned_transfer_aux,
+        "create_unsigned_encrypted_transfer" => {
+            unsigned_transaction::create_unsigned_encrypted_transfer_aux
+        }
+        "attach_signatures" => unsigned_transaction::attach_signatures_aux,
+        "partially_sign_transaction" => multisig::partially_sign_transaction_aux,
+        "merge_signatures" => multisig::merge_signatures_aux,
+        "check_signature_thresholds" => multisig::check_signature_thresholds_aux,
+        "create_encrypted_transfer" => create_encrypted_transfer_aux,
+        "create_pub_to_sec_transfer" => create_pub_to_sec_transfer_aux,
+        "create_sec_to_pub_transfer" => create_sec_to_pub_transfer_aux,
+        "combine_encrypted_amounts" => combine_encrypted_amounts_v2_aux,
+        "combine_encrypted_amounts_list" => combine_encrypted_amounts_list_aux,
+        "update_encrypted_balance" => encrypted_balance::update_encrypted_balance_aux,
+        "create_encrypted_transfer_from_balance" => {
+            encrypted_balance::create_encrypted_transfer_from_balance_aux
+        }
+        "create_sec_to_pub_transfer_from_balance" => {
+            encrypted_balance::create_sec_to_pub_transfer_from_balance_aux
+        }
+        "encode_payment_request" => qr::encode_payment_request_aux,
+        "decode_payment_request" => qr::decode_payment_request_aux,
+        "encode_key_export" => qr::encode_key_export_aux,
+        "decode_key_export" => qr::decode_key_export_aux,
+        "generate_mnemonic" => mnemonic::generate_mnemonic_aux,
+        "validate_mnemonic" => mnemonic::validate_mnemonic_aux,
+        "prove_attribute_in_range" => range_proof::prove_attribute_in_range_aux,
+        "verify_attribute_in_range" => range_proof::verify_attribute_in_range_aux,
+        "prove_statement" => statement_proof::prove_statement_aux,
+        "create_account_ownership_proof" => ownership::create_account_ownership_proof_aux,
+        "decode_transaction" => decoder::decode_transaction_aux,
+        "parse_transaction" => decoder::parse_transaction_aux,
+        "get_input_schema" => schema::get_input_schema_aux,
+        "get_output_schema" => schema::get_output_schema_aux,
+        "estimate_transaction_energy" => energy::estimate_transaction_energy_aux,
+        _ => return None,
+    };
+    Some(f)
+}
+
+/// The cancellable library function with the given name.
+fn lookup_cancellable(name: &str) -> Option<CancellableAux> {
+    let f: CancellableAux = match name {
+        "create_credential" => create_credential_cancellable_aux,
+        "create_encrypted_transfer" => create_encrypted_transfer_cancellable_aux,
+        "create_sec_to_pub_transfer" => create_sec_to_pub_transfer_cancellable_aux,
+        _ => return None,
+    };
+    Some(f)
+}
+
+/// The v2 version of `combine_encrypted_amounts`, which takes the two amounts
+/// as the fields `left` and `right` of a single input object.
+fn combine_encrypted_amounts_v2_aux(input: &str) -> anyhow::Result<String> {
+    let v = parse_input(input)?;
+    let left: Value = try_get(&v, "left")?;
+    let right: Value = try_get(&v, "right")?;
+    combine_encrypted_amounts_aux(&to_string(&left)?, &to_string(&right)?)
+}
+
+/// Wrap the response of a library function in the v2 envelope.
+fn envelope(response: anyhow::Result<String>, warnings: &Warnings) -> String {
+    let (ok, value) = match response.and_then(|r| Ok(from_str::<Value>(&r)?)) {
+        Ok(value) => (true, value),
+        Err(e) => (
+            false,
+            Value::String(format!("Could not produce response: {}", e)),
+        ),
+    };
+    json!({
+        "ok": ok,
+        "value": value,
+        "warnings": warnings.all(),
+    })
+    .to_string()
+}
+
+/// Call the library function with the given name on the given input, and
+/// return the response in the v2 envelope.
+fn call(name: &str, input: &str) -> String {
+    let (response, warnings) = collect_warnings(|| match lookup(name) {
+        Some(f) => f(input),
+        None => Err(anyhow!("Unknown function {}.", name)),
+    });
+    envelope(response, &warnings)
+}
+
+/// Same as [call], but for the cancellable library functions.
+fn call_cancellable(name: &str, input: &str, handle: &OperationHandle) -> String {
+    let (response, warnings) = collect_warnings(|| match lookup_cancellable(name) {
+        Some(f) => f(input, handle),
+        None => Err(anyhow!("Unknown cancellable function {}.", name)),
+    });
+    envelope(response, &warnings)
+}
+
+/// Try to get a string from a `*const c_char`, returning the error as a v2
+/// response on failure.
+unsafe fn get_str<'a>(ptr: *const c_char) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(envelope(
+            Err(anyhow!("Null pointer input.")),
+            &Warnings::default(),
+        ));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|e| {
+        envelope(
+            Err(anyhow!("Could not decode input string: {}", e)),
+            &Warnings::default(),
+        )
+    })
+}
+
+fn into_c_string(response: String) -> *mut c_char {
+    CString::new(response)
+        .expect("JSON responses do not contain NUL characters.")
+        .into_raw()
+}
+
+/// Take the name of a library function and a pointer to its input, both
+/// NUL-terminated UTF8-strings, and return a NUL-terminated UTF8-encoded
+/// string with the v2 response of the function. The returned string must be
+/// freed by the caller by calling the function 'free_response_string'.
+///
+/// See rust-bins/wallet-notes/README.md for the description of input and output
+/// formats.
+///
+/// # Safety
+/// The pointers must point to null-terminated buffers, otherwise this function
+/// will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn call_v2(function_ptr: *const c_char, input_ptr: *const c_char) -> *mut c_char {
+    let response = get_str(function_ptr)
+        .and_then(|name| Ok(call(name, get_str(input_ptr)?)))
+        .unwrap_or_else(|e| e);
+    into_c_string(response)
+}
+
+/// Same as [call_v2], but for the cancellable functions, which take an
+/// operation handle as an additional argument.
+///
+/// # Safety
+/// The string pointers must point to null-terminated buffers, and the handle
+/// must be a valid pointer to a handle created with `new_operation_handle`,
+/// otherwise this function will fail in unspecified ways.
+#[no_mangle]
+pub unsafe fn call_v2_cancellable(
+    function_ptr: *const c_char,
+    input_ptr: *const c_char,
+    handle: *const OperationHandle,
+) -> *mut c_char {
+    let response = get_str(function_ptr)
+        .and_then(|name| {
+            let input = get_str(input_ptr)?;
+            match handle.as_ref() {
+                Some(handle) => Ok(call_cancellable(name, input, handle)),
+                None => Err(envelope(
+                    Err(anyhow!("Null operation handle.")),
+                    &Warnings::default(),
+                )),
+            }
+        })
+        .unwrap_or_else(|e| e);
+    into_c_string(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id::test::read_golden_bytes;
+
+    fn read_input(name: &str) -> String {
+        let bytes = read_golden_bytes(format!("rust-bins/wallet-notes/files/{}-input.json", name));
+        String::from_utf8(bytes).expect("Input files are UTF8.")
+    }
+
+    #[test]
+    fn test_envelope() {
+        let input = read_input("create_transfer");
+        let response: Value = from_str(&call("create_transfer", &input)).unwrap();
+        assert_eq!(response["ok"], true);
+        assert_eq!(
+            response["value"],
+            from_str::<Value>(&create_transfer_aux(&input).unwrap()).unwrap()
+        );
+        // The expiry of the example transfer is in the past.
+        assert!(response["warnings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|w| w.as_str().unwrap().contains("expiry")));
+
+        let response: Value = from_str(&call("create_transfer", "{}")).unwrap();
+        assert_eq!(response["ok"], false);
+        assert!(response["value"].is_string());
+
+        let response: Value = from_str(&call("no_such_function", &input)).unwrap();
+        assert_eq!(response["ok"], false);
+        assert_eq!(response["warnings"], json!([]));
+    }
+}
@@ -0,0 +1,99 @@
+//! Version of the library and of its C interface.
+//!
+//! The platform shims are compiled against a specific version of the C
+//! interface, recorded in the constant `MOBILE_WALLET_ABI_VERSION` of the
+//! header and `ABI_VERSION` of the Kotlin bindings. They should check that it
+//! matches the value returned by [get_abi_version] when the native library is
+//! loaded, so that a mismatched library is detected at startup instead of
+//! failing later on a missing or changed function.
+use crypto_common::c_char;
+use std::ffi::CString;
+
+/// Version of the C interface. It is increased whenever an exported function
+/// is removed or its signature changes in an incompatible way. Adding
+/// functions does not change it. This is the only definition of the version;
+/// the constants in the header and in the Kotlin bindings are checked against
+/// it by the tests below.
+pub const ABI_VERSION: u32 = 1;
+
+/// The features the library was built with. The baby-step giant-step table
+/// used for decrypting amounts is always embedded in the library, and the
+/// `parallel` feature of `crypto_common` is always enabled (see
+/// [set_thread_count](crate::threads::set_thread_count)).
+fn features() -> Vec<&'static str> {
+    let mut features = vec!["table-embedded", "parallel"];
+    if cfg!(feature = "memory-accounting") {
+        features.push("memory-accounting");
+    }
+    if cfg!(feature = "sha2-asm") {
+        features.push("sha2-asm");
+    }
+    features
+}
+
+/// A JSON object with the version of the library, the version of the C
+/// interface, and the features the library was built with.
+pub fn library_version() -> serde_json::Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "abiVersion": ABI_VERSION,
+        "features": features(),
+    })
+}
+
+/// Return the version of the C interface of the library.
+#[no_mangle]
+pub extern "C" fn get_abi_version() -> u32 { ABI_VERSION }
+
+/// Return a NUL-terminated UTF8-encoded JSON object with the version of the
+/// library, the version of the C interface, and the features the library was
+/// built with. The returned string must be freed by the caller by calling the
+/// function 'free_response_string'.
+#[no_mangle]
+pub extern "C" fn get_library_version() -> *mut c_char {
+    CString::new(library_version().to_string())
+        .expect("JSON does not contain NUL bytes.")
+        .into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_version() {
+        let version = library_version();
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(version["abiVersion"], get_abi_version());
+        let features = version["features"]
+            .as_array()
+            .expect("Features should be a list.");
+        assert!(features.contains(&json!("table-embedded")));
+        assert!(features.contains(&json!("parallel")));
+        assert_eq!(
+            features.contains(&json!("memory-accounting")),
+            cfg!(feature = "memory-accounting")
+        );
+    }
+
+    /// The header and the Kotlin bindings have their own copies of the version
+    /// of the C interface. Check that they agree with [ABI_VERSION].
+    #[test]
+    fn test_abi_version_copies() {
+        let header = include_str!("../mobile_wallet.h");
+        assert!(
+            header.contains(&format!(
+                "#define MOBILE_WALLET_ABI_VERSION {}\n",
+                ABI_VERSION
+            )),
+            "The header is out of date, regenerate it with scripts/generate-header.sh."
+        );
+        let kotlin = include_str!(
+            "../android/mobile_wallet_lib/src/main/java/com/concordium/mobile_wallet_lib/wallet.kt"
+        );
+        assert!(
+            kotlin.contains(&format!("const val ABI_VERSION = {}\n", ABI_VERSION)),
+            "ABI_VERSION in wallet.kt does not match the library."
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Names of the exported functions that are specific to a version of the C
+//! interface.
+//!
+//! Every function of the C interface is also exported under the name
+//! `ccd_wallet_v<N>_<name>`, where `N` is the version of the C interface (see
+//! [crate::version::ABI_VERSION]). The prefixed names of a version never
+//! change, so an application that links against them keeps working, or fails
+//! to link, instead of calling a function whose signature changed. It also
+//! allows two versions of the library with different C interfaces to be
+//! loaded into the same process, as long as the application only uses the
+//! prefixed names. The version of a library can be detected at runtime with
+//! [crate::version::get_abi_version], which is also exported under its plain
+//! name.
+//!
+//! When the version of the C interface is increased the prefix of the names
+//! below must be changed accordingly.
+use crate::{
+    context::{wallet_context_create, wallet_context_free, WalletContext},
+    cursor::{
+        free_response_cursor, generate_accounts_cursor, response_cursor_next, ResponseCursor,
+    },
+    hashing::{set_transaction_hash_function, ExternalHashFunction},
+    job::{
+        cancel_job, get_job_result, poll_job, start_create_credential,
+        start_create_id_request_and_private_data,
+    },
+    memory::{
+        memory_current_usage, memory_peak_usage, reset_memory_peak_usage,
+        set_allocation_failure_callback, AllocationFailureCallback,
+    },
+    operation::{cancel_operation, free_operation_handle, new_operation_handle, OperationHandle},
+    self_test::crypto_self_test,
+    signer::{set_sign_callback, SignCallback},
+    threads::set_thread_count,
+    v2::{call_v2, call_v2_cancellable},
+    version::{get_abi_version, get_library_version},
+    *,
+};
+use crypto_common::c_char;
+
+/// The prefix of the names of the functions of this version of the C
+/// interface.
+pub const SYMBOL_PREFIX: &str = "ccd_wallet_v1_";
+
+/// Export the given functions under the prefixed names.
+macro_rules! versioned_symbols {
+    ($($f:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        paste::paste! {
+            $(
+                #[doc = "Same as `" $f "`, under the name of version 1 of the C interface."]
+                ///
+                /// # Safety
+                #[doc = "The arguments must satisfy the requirements of `" $f "`."]
+                #[no_mangle]
+                pub unsafe extern "C" fn [<ccd_wallet_v1_ $f>]($($arg: $ty),*) $(-> $ret)? {
+                    $f($($arg),*)
+                }
+            )*
+        }
+    };
+}
+
+versioned_symbols! {
+    check_account_address(input_ptr: *const c_char) -> u8;
+    combine_encrypted_amounts(
+        input_ptr_1: *const c_char,
+        input_ptr_2: *const c_char,
+        success: *mut u8
+    ) -> *mut c_char;
+    combine_encrypted_amounts_list(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    update_encrypted_balance(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_encrypted_transfer_from_balance(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_sec_to_pub_transfer_from_balance(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    generate_accounts(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    get_credential_id(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    derive_keys_from_seed(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    prove_attribute_in_range(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    verify_attribute_in_range(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    prove_statement(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_account_ownership_proof(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    decode_transaction(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    generate_baker_keys(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_configure_baker(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_configure_delegation(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_credential(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_credential_cancellable(
+        input_ptr: *const c_char,
+        handle: *const OperationHandle,
+        success: *mut u8
+    ) -> *mut c_char;
+    prepare_credential(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    finalize_credential(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_encrypted_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_encrypted_transfer_cancellable(
+        input_ptr: *const c_char,
+        handle: *const OperationHandle,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_id_request_and_private_data(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_pub_to_sec_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_sec_to_pub_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_sec_to_pub_transfer_cancellable(
+        input_ptr: *const c_char,
+        handle: *const OperationHandle,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_transfer_with_schedule(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_update_keys(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_unsigned_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_unsigned_encrypted_transfer(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    attach_signatures(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    partially_sign_transaction(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    merge_signatures(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    check_signature_thresholds(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    encode_payment_request(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    decode_payment_request(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    encode_key_export(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    decode_key_export(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    generate_mnemonic(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    validate_mnemonic(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    get_input_schema(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    get_output_schema(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    estimate_transaction_energy(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    parse_transaction(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    create_id_request_and_private_data_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_credential_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    generate_accounts_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    get_credential_id_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_encrypted_transfer_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_pub_to_sec_transfer_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    create_sec_to_pub_transfer_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> *mut c_char;
+    decrypt_encrypted_amount_with_context(
+        input_ptr: *const c_char,
+        wallet_context: *const WalletContext,
+        success: *mut u8
+    ) -> u64;
+    decrypt_encrypted_amount(input_ptr: *const c_char, success: *mut u8) -> u64;
+    set_decryption_table(input_ptr: *const c_char, success: *mut u8) -> *mut c_char;
+    free_response_string(ptr: *mut c_char);
+    generate_accounts_cursor(input_ptr: *const c_char) -> *mut ResponseCursor;
+    response_cursor_next(cursor: *mut ResponseCursor, success: *mut u8) -> *mut c_char;
+    free_response_cursor(cursor: *mut ResponseCursor);
+    set_transaction_hash_function(f: Option<ExternalHashFunction>);
+    set_allocation_failure_callback(cb: Option<AllocationFailureCallback>) -> u8;
+    set_sign_callback(cb: Option<SignCallback>);
+    get_abi_version() -> u32;
+    get_library_version() -> *mut c_char;
+    crypto_self_test() -> *mut c_char;
+    memory_current_usage() -> u64;
+    memory_peak_usage() -> u64;
+    reset_memory_peak_usage();
+    set_thread_count(n: u32) -> u8;
+    new_operation_handle() -> *mut OperationHandle;
+    cancel_operation(handle: *const OperationHandle);
+    free_operation_handle(handle: *mut OperationHandle);
+    wallet_context_create(input_ptr: *const c_char) -> *mut WalletContext;
+    wallet_context_free(ctx: *mut WalletContext);
+    call_v2(function_ptr: *const c_char, input_ptr: *const c_char) -> *mut c_char;
+    call_v2_cancellable(
+        function_ptr: *const c_char,
+        input_ptr: *const c_char,
+        handle: *const OperationHandle
+    ) -> *mut c_char;
+    start_create_credential(input_ptr: *const c_char, success: *mut u8) -> u64;
+    start_create_id_request_and_private_data(input_ptr: *const c_char, success: *mut u8) -> u64;
+    poll_job(job_id: u64) -> u8;
+    cancel_job(job_id: u64);
+    get_job_result(job_id: u64, success: *mut u8) -> *mut c_char;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::ABI_VERSION;
+
+    #[test]
+    fn test_symbol_prefix() {
+        assert_eq!(SYMBOL_PREFIX, format!("ccd_wallet_v{}_", ABI_VERSION));
+        assert_eq!(unsafe { ccd_wallet_v1_get_abi_version() }, ABI_VERSION);
+        let mut success = 127;
+        let input = std::ffi::CString::new("not an address").unwrap();
+        assert_eq!(
+            unsafe { ccd_wallet_v1_check_account_address(input.as_ptr()) },
+            0
+        );
+        let response = unsafe { ccd_wallet_v1_validate_mnemonic(input.as_ptr(), &mut success) };
+        assert_eq!(success, 0);
+        unsafe { ccd_wallet_v1_free_response_string(response) };
+    }
+}
@@ -0,0 +1,421 @@
+//! A wallet that keeps the state of identities and accounts.
+//!
+//! The functions exported over FFI are stateless, and it is up to the caller to
+//! keep track of keys, nonces, and shielded balances between calls. The
+//! [Wallet] keeps this state for Rust frontends and combines it with the
+//! functions that create credentials and transactions. The state is
+//! serializable to JSON so that it can be persisted by the frontend.
+//!
+//! The wallet does not communicate with the chain. Nonces and shielded
+//! balances are updated optimistically when transactions are created, and the
+//! frontend should overwrite them with [Wallet::set_nonce] and
+//! [Wallet::set_shielded_balance] when it learns the state of an account from
+//! a node.
+use crate::{make_policy, make_signatures, make_transaction_bytes};
+use anyhow::{bail, Context};
+use crypto_common::{
+    types::{Amount, KeyPair, TransactionSignature, TransactionTime},
+    *,
+};
+use either::Either::Left;
+use encrypted_transfers::types::AggregatedDecryptedAmount;
+use id::{account_holder, constants::AttributeKind, types::*};
+use pairing::bls12_381::{Bls12, G1};
+use rand::thread_rng;
+use random_oracle::ChallengeContext;
+use std::collections::BTreeMap;
+use transactions::{EncodedPayload, Payload, PayloadLike, TransactionHeader};
+
+/// An identity of the wallet, with everything needed to create credentials
+/// from it.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletIdentity {
+    /// The identity provider that issued the identity object.
+    pub ip_info:                IpInfo<Bls12>,
+    /// The anonymity revokers that credentials are created with.
+    pub ars_infos:              BTreeMap<ArIdentity, ArInfo<G1>>,
+    pub identity_object:        IdentityObject<Bls12, G1, AttributeKind>,
+    pub private_id_object_data: IdObjectUseData<Bls12, G1>,
+}
+
+/// An account of the wallet.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletAccount {
+    /// Index of the identity the credential of the account is created from.
+    pub identity:              usize,
+    /// The credential counter of the credential of the account.
+    pub account_number:        u8,
+    pub keys:                  AccountKeys,
+    pub encryption_secret_key: elgamal::SecretKey<G1>,
+    /// Nonce of the next transaction sent from the account.
+    pub nonce:                 u64,
+    /// The shielded balance of the account as known to the wallet.
+    pub shielded_balance:      AggregatedDecryptedAmount<G1>,
+}
+
+/// A transaction together with its signatures, ready to be sent to a node.
+pub struct SignedTransaction {
+    pub signatures: TransactionSignature,
+    /// The serialized transaction, i.e., the header followed by the payload.
+    pub body:       Vec<u8>,
+}
+
+/// Identities and accounts of a wallet. See the [module level
+/// documentation](self) for how the state is kept.
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Wallet {
+    global:     GlobalContext<G1>,
+    identities: Vec<WalletIdentity>,
+    accounts:   BTreeMap<AccountAddress, WalletAccount>,
+}
+
+/// The shielded balance of a new account. This is the encryption of 0 that the
+/// chain starts every account with.
+fn empty_shielded_balance(global: &GlobalContext<G1>) -> AggregatedDecryptedAmount<G1> {
+    let zero = Amount::from(0);
+    AggregatedDecryptedAmount {
+        agg_encrypted_amount: encrypted_transfers::encrypt_amount_with_fixed_randomness(
+            global, zero,
+        ),
+        agg_amount:           zero,
+        agg_index:            0.into(),
+    }
+}
+
+impl Wallet {
+    /// An empty wallet for the chain with the given global context.
+    pub fn new(global: GlobalContext<G1>) -> Self {
+        Wallet {
+            global,
+            identities: Vec::new(),
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    pub fn global(&self) -> &GlobalContext<G1> { &self.global }
+
+    pub fn identities(&self) -> &[WalletIdentity] { &self.identities }
+
+    pub fn accounts(&self) -> &BTreeMap<AccountAddress, WalletAccount> { &self.accounts }
+
+    pub fn account(&self, address: &AccountAddress) -> Option<&WalletAccount> {
+        self.accounts.get(address)
+    }
+
+    /// Add an identity to the wallet and return its index.
+    pub fn add_identity(&mut self, identity: WalletIdentity) -> usize {
+        self.identities.push(identity);
+        self.identities.len() - 1
+    }
+
+    /// Overwrite the nonce of the next transaction of the account, e.g., after
+    /// querying it from a node.
+    pub fn set_nonce(&mut self, address: &AccountAddress, nonce: u64) -> anyhow::Result<()> {
+        self.account_mut(address)?.nonce = nonce;
+        Ok(())
+    }
+
+    /// Overwrite the shielded balance of the account, e.g., after decrypting
+    /// the encrypted amounts of the account as reported by a node.
+    pub fn set_shielded_balance(
+        &mut self,
+        address: &AccountAddress,
+        balance: AggregatedDecryptedAmount<G1>,
+    ) -> anyhow::Result<()> {
+        self.account_mut(address)?.shielded_balance = balance;
+        Ok(())
+    }
+
+    /// Create a new account from the given identity. The credential of the
+    /// account uses the smallest account number of the identity that is not
+    /// already used by an account of the wallet, and reveals the given
+    /// attributes. The returned message must be sent to the chain to create
+    /// the account.
+    pub fn create_account(
+        &mut self,
+        identity: usize,
+        revealed_attributes: Vec<AttributeTag>,
+        expiry: TransactionTime,
+    ) -> anyhow::Result<(
+        AccountAddress,
+        AccountCredentialMessage<Bls12, G1, AttributeKind>,
+    )> {
+        let id = self.identities.get(identity).context("Unknown identity.")?;
+        let max_accounts = id.identity_object.alist.max_accounts;
+        let acc_num = (0..max_accounts)
+            .find(|n| {
+                !self
+                    .accounts
+                    .values()
+                    .any(|acc| acc.identity == identity && acc.account_number == *n)
+            })
+            .context("All accounts of the identity are used.")?;
+
+        let policy = make_policy(&id.identity_object, revealed_attributes)?;
+        let cred_data = CredentialData::from(KeyPair::generate(&mut thread_rng()));
+        let context = IpContext::new(&id.ip_info, &id.ars_infos, &self.global);
+        let (cdi, _) = account_holder::create_credential(
+            &ChallengeContext::Legacy,
+            context,
+            &id.identity_object,
+            &id.private_id_object_data,
+            acc_num,
+            policy,
+            &cred_data,
+            &Left(expiry),
+        )?;
+
+        // unwrap is safe here since we've generated the credential already, and that
+        // does the same computation.
+        let enc_key = id
+            .private_id_object_data
+            .aci
+            .prf_key
+            .prf_exponent(acc_num)
+            .unwrap();
+        let address = AccountAddress::new(&cdi.values.cred_id);
+        let account = WalletAccount {
+            identity,
+            account_number: acc_num,
+            keys: AccountKeys::from(cred_data),
+            encryption_secret_key: elgamal::SecretKey {
+                generator: *self.global.elgamal_generator(),
+                scalar:    enc_key,
+            },
+            nonce: 1,
+            shielded_balance: empty_shielded_balance(&self.global),
+        };
+        self.accounts.insert(address, account);
+        let message = AccountCredentialMessage {
+            message_expiry: expiry,
+            credential:     AccountCredential::Normal { cdi },
+        };
+        Ok((address, message))
+    }
+
+    /// Create a transfer of a public amount.
+    pub fn send_transfer(
+        &mut self,
+        from: &AccountAddress,
+        to: &AccountAddress,
+        amount: Amount,
+        energy: u64,
+        expiry: TransactionTime,
+    ) -> anyhow::Result<SignedTransaction> {
+        let payload = Payload::Transfer {
+            to_address: *to,
+            amount,
+        }
+        .encode();
+        self.sign_transaction(from, energy, expiry, &payload)
+    }
+
+    /// Create a transfer of a shielded amount to the account with the given
+    /// encryption public key.
+    pub fn send_shielded_transfer(
+        &mut self,
+        from: &AccountAddress,
+        to: &AccountAddress,
+        receiver_pk: &elgamal::PublicKey<G1>,
+        amount: Amount,
+        energy: u64,
+        expiry: TransactionTime,
+    ) -> anyhow::Result<SignedTransaction> {
+        let account = self.account(from).context("Unknown account.")?;
+        let data = match encrypted_transfers::make_transfer_data(
+            &ChallengeContext::Legacy,
+            &self.global,
+            receiver_pk,
+            &account.encryption_secret_key,
+            &account.shielded_balance,
+            amount,
+            &mut thread_rng(),
+        ) {
+            Some(data) => data,
+            None => bail!("Could not produce payload."),
+        };
+        let payload = Payload::EncryptedAmountTransfer {
+            to_address: *to,
+            data:       Box::new(data.clone()),
+        }
+        .encode();
+        let tx = self.sign_transaction(from, energy, expiry, &payload)?;
+        let account = self.account_mut(from)?;
+        account.shielded_balance = AggregatedDecryptedAmount {
+            agg_encrypted_amount: data.remaining_amount,
+            agg_amount:           decrease(account.shielded_balance.agg_amount, amount)?,
+            agg_index:            data.index,
+        };
+        Ok(tx)
+    }
+
+    /// Create a transfer of a public amount to the shielded balance of the
+    /// account.
+    pub fn shield(
+        &mut self,
+        from: &AccountAddress,
+        amount: Amount,
+        energy: u64,
+        expiry: TransactionTime,
+    ) -> anyhow::Result<SignedTransaction> {
+        let payload = Payload::TransferToEncrypted { amount }.encode();
+        let tx = self.sign_transaction(from, energy, expiry, &payload)?;
+        let encryption =
+            encrypted_transfers::encrypt_amount_with_fixed_randomness(&self.global, amount);
+        let account = self.account_mut(from)?;
+        let balance = &mut account.shielded_balance;
+        balance.agg_encrypted_amount =
+            encrypted_transfers::aggregate(&balance.agg_encrypted_amount, &encryption);
+        balance.agg_amount = (balance.agg_amount + amount).context("Amount overflow.")?;
+        Ok(tx)
+    }
+
+    /// Create a transfer of a shielded amount of the account to its public
+    /// balance.
+    pub fn unshield(
+        &mut self,
+        from: &AccountAddress,
+        amount: Amount,
+        energy: u64,
+        expiry: TransactionTime,
+    ) -> anyhow::Result<SignedTransaction> {
+        let account = self.account(from).context("Unknown account.")?;
+        let data = match encrypted_transfers::make_sec_to_pub_transfer_data(
+            &ChallengeContext::Legacy,
+            &self.global,
+            &account.encryption_secret_key,
+            &account.shielded_balance,
+            amount,
+            &mut thread_rng(),
+        ) {
+            Some(data) => data,
+            None => bail!("Could not produce payload."),
+        };
+        let payload = Payload::TransferToPublic {
+            data: Box::new(data.clone()),
+        }
+        .encode();
+        let tx = self.sign_transaction(from, energy, expiry, &payload)?;
+        let account = self.account_mut(from)?;
+        account.shielded_balance = AggregatedDecryptedAmount {
+            agg_encrypted_amount: data.remaining_amount,
+            agg_amount:           decrease(account.shielded_balance.agg_amount, amount)?,
+            agg_index:            data.index,
+        };
+        Ok(tx)
+    }
+
+    /// Sign a transaction with the given payload with the keys of the sender,
+    /// and increase the nonce of the sender.
+    fn sign_transaction(
+        &mut self,
+        from: &AccountAddress,
+        energy: u64,
+        expiry: TransactionTime,
+        payload: &EncodedPayload,
+    ) -> anyhow::Result<SignedTransaction> {
+        let account = self.account_mut(from)?;
+        let header = TransactionHeader::new(*from, account.nonce, energy, expiry, payload);
+        let (hash, body) = make_transaction_bytes(&header, payload);
+        let signatures = make_signatures(&account.keys, &hash)?;
+        account.nonce += 1;
+        Ok(SignedTransaction { signatures, body })
+    }
+
+    fn account_mut(&mut self, address: &AccountAddress) -> anyhow::Result<&mut WalletAccount> {
+        self.accounts.get_mut(address).context("Unknown account.")
+    }
+}
+
+fn decrease(balance: Amount, amount: Amount) -> anyhow::Result<Amount> {
+    let remaining = u64::from(balance)
+        .checked_sub(u64::from(amount))
+        .context("Insufficient shielded balance.")?;
+    Ok(Amount::from(remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id::test::read_golden_json;
+    use serde_json::Value;
+
+    const EXPIRY: TransactionTime = TransactionTime {
+        seconds: 123123123123,
+    };
+
+    fn test_wallet() -> Wallet {
+        let input: Value =
+            read_golden_json("rust-bins/wallet-notes/files/create_credential-input.json");
+        let get = |name: &str| input[name].clone();
+        let mut wallet = Wallet::new(serde_json::from_value(get("global")).unwrap());
+        wallet.add_identity(WalletIdentity {
+            ip_info:                serde_json::from_value(get("ipInfo")).unwrap(),
+            ars_infos:              serde_json::from_value(get("arsInfos")).unwrap(),
+            identity_object:        serde_json::from_value(get("identityObject")).unwrap(),
+            private_id_object_data: serde_json::from_value(get("privateIdObjectData")).unwrap(),
+        });
+        wallet
+    }
+
+    /// The nonce in the header of a serialized transaction.
+    fn nonce(tx: &SignedTransaction) -> u64 {
+        let mut cursor = std::io::Cursor::new(&tx.body[32..]);
+        cursor.get().expect("Nonce should parse.")
+    }
+
+    #[test]
+    fn test_wallet_bookkeeping() {
+        let mut wallet = test_wallet();
+        let (from, message) = wallet
+            .create_account(0, Vec::new(), EXPIRY)
+            .expect("Account should be created.");
+        let (to, _) = wallet
+            .create_account(0, Vec::new(), EXPIRY)
+            .expect("Account should be created.");
+        assert_ne!(from, to);
+        match message.credential {
+            AccountCredential::Normal { cdi } => {
+                assert_eq!(AccountAddress::new(&cdi.values.cred_id), from)
+            }
+            _ => panic!("Account should have a normal credential."),
+        }
+        assert_eq!(wallet.account(&to).unwrap().account_number, 1);
+
+        let tx = wallet
+            .send_transfer(&from, &to, Amount::from(10), 1000, EXPIRY)
+            .expect("Transfer should be created.");
+        assert_eq!(nonce(&tx), 1);
+        let tx = wallet
+            .shield(&from, Amount::from(100), 1000, EXPIRY)
+            .expect("Shielding should be created.");
+        assert_eq!(nonce(&tx), 2);
+        assert_eq!(
+            wallet.account(&from).unwrap().shielded_balance.agg_amount,
+            Amount::from(100)
+        );
+        assert!(wallet
+            .unshield(&from, Amount::from(101), 1000, EXPIRY)
+            .is_err());
+        let tx = wallet
+            .unshield(&from, Amount::from(40), 1000, EXPIRY)
+            .expect("Unshielding should be created.");
+        assert_eq!(nonce(&tx), 3);
+        assert_eq!(
+            wallet.account(&from).unwrap().shielded_balance.agg_amount,
+            Amount::from(60)
+        );
+
+        let json = serde_json::to_string(&wallet).expect("Wallet should serialize.");
+        let mut restored: Wallet = serde_json::from_str(&json).expect("Wallet should deserialize.");
+        assert_eq!(restored.accounts().len(), 2);
+        assert_eq!(restored.account(&from).unwrap().nonce, 4);
+        let tx = restored
+            .send_transfer(&to, &from, Amount::from(1), 1000, EXPIRY)
+            .expect("Transfer should be created.");
+        assert_eq!(nonce(&tx), 1);
+    }
+}
@@ -0,0 +1,51 @@
+//! Non-fatal issues found while handling a call of a library function.
+//!
+//! Functions report issues with [warn] while they run, and the FFI wrappers
+//! collect the warnings of the call with [collect_warnings] and add them to
+//! the response. Warnings are kept per thread, since each call runs on the
+//! thread of its caller.
+use std::cell::RefCell;
+
+/// The kind of a warning. Deprecation warnings are reported by both versions
+/// of the entry points, the other warnings only by the v2 entry points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WarningKind {
+    Deprecation,
+    Other,
+}
+
+thread_local! {
+    /// Warnings produced while handling the current call.
+    static WARNINGS: RefCell<Vec<(WarningKind, String)>> = RefCell::new(Vec::new());
+}
+
+/// Record a warning for the current call.
+pub(crate) fn warn(kind: WarningKind, warning: String) {
+    WARNINGS.with(|ws| ws.borrow_mut().push((kind, warning)))
+}
+
+/// The warnings produced by a call.
+#[derive(Debug, Default)]
+pub(crate) struct Warnings(Vec<(WarningKind, String)>);
+
+impl Warnings {
+    /// All the warnings, in the order they were produced.
+    pub(crate) fn all(&self) -> Vec<&str> { self.0.iter().map(|(_, w)| w.as_str()).collect() }
+
+    /// The deprecation warnings, in the order they were produced.
+    pub(crate) fn deprecations(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(kind, _)| *kind == WarningKind::Deprecation)
+            .map(|(_, w)| w.as_str())
+            .collect()
+    }
+}
+
+/// Run the given function and return the warnings it produced.
+pub(crate) fn collect_warnings<A>(f: impl FnOnce() -> A) -> (A, Warnings) {
+    WARNINGS.with(|ws| ws.borrow_mut().clear());
+    let result = f();
+    let warnings = WARNINGS.with(|ws| std::mem::take(&mut *ws.borrow_mut()));
+    (result, Warnings(warnings))
+}
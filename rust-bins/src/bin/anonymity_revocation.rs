@@ -2,15 +2,12 @@ use clap::AppSettings;
 use client_server_helpers::*;
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{Curve, Value};
+use curve_arithmetic::Curve;
 use dodis_yampolskiy_prf as prf;
-use elgamal::{decrypt_from_chunks_given_generator, Message};
+use elgamal::BabyStepGiantStep;
 use id::{anonymity_revoker::*, constants::ArCurve, types::*};
 use serde_json::json;
-use std::{
-    convert::TryFrom,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -75,6 +72,12 @@ struct Combine {
         help = "Files with the JSON encoded decrypted shares."
     )]
     shares:     Vec<PathBuf>,
+    #[structopt(
+        long = "ar-info",
+        help = "File with the public information of the anonymity revokers. If given, the proofs \
+                that the shares were decrypted correctly are checked."
+    )]
+    ar_info:    Option<PathBuf>,
     #[structopt(long = "out", help = "File to output the decryption to.")]
     out:        PathBuf,
 }
@@ -217,32 +220,24 @@ fn handle_compute_regids(rid: ComputeRegIds) -> Result<(), String> {
     if ar_record.version != VERSION_0 {
         return Err("The version of the ArRecord should be 0.".to_owned());
     }
-    let max_account: u8 = ar_record.value.max_accounts;
-    let g = global_context.on_chain_commitment_key.g;
     let prf_key: prf::SecretKey<_> = prf_wrapper.prf_key;
 
-    let mut regids = Vec::with_capacity(usize::from(max_account));
-    for x in 0..=max_account {
-        if let Ok(secret) = prf_key.prf_exponent(x) {
-            let regid = g.mul_by_scalar(&secret);
-            let regid_hex = hex::encode(&to_bytes(&regid));
+    let regids: Vec<_> = trace_accounts(&prf_key, &global_context, ar_record.value.max_accounts)
+        .into_iter()
+        .map(|account| {
+            let mut value = json!(account);
             if !rid.no_secret {
-                regids.push(json!({
-                    "regId": regid_hex,
-                    "accountAddress": AccountAddress::new(&regid),
-                    "encryptionSecretKey": elgamal::SecretKey{
-                        generator: *global_context.elgamal_generator(),
-                        scalar: secret
-                    }
-                }));
-            } else {
-                regids.push(json!({
-                    "regId": regid_hex,
-                    "accountAddress": AccountAddress::new(&regid),
-                }));
+                let secret = prf_key
+                    .prf_exponent(account.cred_counter)
+                    .expect("The PRF is defined for traced accounts.");
+                value["encryptionSecretKey"] = json!(elgamal::SecretKey {
+                    generator: *global_context.elgamal_generator(),
+                    scalar:    secret,
+                });
             }
-        }
-    }
+            value
+        })
+        .collect();
 
     match write_json_to_file(&rid.out, &regids) {
         Ok(_) => eprintln!("Wrote regIds to {}.", rid.out.display()),
@@ -286,20 +281,12 @@ fn handle_decrypt_id(dcr: Decrypt) -> Result<(), String> {
         AccountCredentialValues::Normal { cdi } => cdi,
     };
 
-    let ar_data = credential.ar_data;
     let ar: ArData<ExampleCurve> = succeed_or_die!(decrypt_ar_data(&dcr.ar_private), e => "Could not read anonymity revoker secret keys due to {}");
 
-    let single_ar_data = succeed_or_die!(
-        ar_data.get(&ar.public_ar_info.ar_identity),
+    let share = succeed_or_die!(
+        decrypt_id_cred_pub_share(&ar, &credential.ar_data, &mut rand::thread_rng()),
         "Supplied AR is not part of the credential."
     );
-    let m = ar
-        .ar_secret_key
-        .decrypt(&single_ar_data.enc_id_cred_pub_share);
-    let share = ChainArDecryptedData {
-        ar_identity:       ar.public_ar_info.ar_identity,
-        id_cred_pub_share: m,
-    };
     match write_json_to_file(&dcr.out, &share) {
         Ok(_) => println!("Wrote decryption to {}", dcr.out.display()),
         Err(e) => {
@@ -324,24 +311,13 @@ fn handle_decrypt_prf(dcr: DecryptPrf) -> Result<(), String> {
     }
     let global_context = global_context.value;
 
-    let ar_data = ar_record.ar_data;
     let ar: ArData<ExampleCurve> = succeed_or_die!(decrypt_ar_data(&dcr.ar_private), e => "Could not read AR secret keys due to {}");
 
-    let single_ar_data = succeed_or_die!(
-        ar_data.get(&ar.public_ar_info.ar_identity),
+    let table = BabyStepGiantStep::new(global_context.encryption_in_exponent_generator(), 1 << 16);
+    let share = succeed_or_die!(
+        decrypt_prf_key_share(&ar, &ar_record.ar_data, &table),
         "Given AR is not part of the credential."
     );
-    let m = decrypt_from_chunks_given_generator(
-        &ar.ar_secret_key,
-        &single_ar_data.enc_prf_key_share,
-        &global_context.encryption_in_exponent_generator(),
-        1 << 16,
-        CHUNK_SIZE,
-    );
-    let share = IpArDecryptedData {
-        ar_identity:   ar.public_ar_info.ar_identity,
-        prf_key_share: m,
-    };
     match write_json_to_file(&dcr.out, &share) {
         Ok(_) => println!("Wrote decryption to {}.", dcr.out.display()),
         Err(e) => {
@@ -363,26 +339,8 @@ fn handle_combine_id(cmb: Combine) -> Result<(), String> {
         }
         AccountCredentialValues::Normal { cdi } => cdi,
     };
-    let revocation_threshold = credential.threshold;
-
-    let shares_values: Vec<_> = cmb.shares;
-
-    let number_of_ars = shares_values.len();
-    let number_of_ars =
-        u8::try_from(number_of_ars).expect("Number of anonymity revokers should not exceed 2^8-1");
-    if number_of_ars < revocation_threshold.into() {
-        return Err(format!(
-            "Insufficient number of anonymity revokers ({}). Threshold is {}.",
-            number_of_ars, revocation_threshold
-        ));
-    }
-
-    let mut ar_decrypted_data_vec: Vec<ChainArDecryptedData<ExampleCurve>> =
-        Vec::with_capacity(shares_values.len());
-    let mut shares: Vec<(ArIdentity, Message<ExampleCurve>)> =
-        Vec::with_capacity(shares_values.len());
-
-    for share_value in shares_values.iter() {
+    let mut shares: Vec<ChainArDecryptedData<ExampleCurve>> = Vec::with_capacity(cmb.shares.len());
+    for share_value in cmb.shares.iter() {
         let decrypted = read_json_from_file(&share_value).map_err(|e| {
             format!(
                 "Could not read from ar file {}, error: {}",
@@ -390,26 +348,21 @@ fn handle_combine_id(cmb: Combine) -> Result<(), String> {
                 e
             )
         })?;
-        ar_decrypted_data_vec.push(decrypted);
-    }
-
-    let mut ar_identities = Vec::with_capacity(ar_decrypted_data_vec.len());
-
-    for ar_decrypted_data in ar_decrypted_data_vec {
-        let ar_id = ar_decrypted_data.ar_identity;
-        ar_identities.push(ar_id);
-        shares.push((ar_id, ar_decrypted_data.id_cred_pub_share));
-    }
-    ar_identities.sort();
-    ar_identities.dedup();
-    if ar_identities.len() < shares.len() {
-        return Err(
-            "No duplicates among the anonymity revokers identities nor share numbers are allowed."
-                .to_owned(),
-        );
+        shares.push(decrypted);
     }
 
-    let id_cred_pub = reveal_id_cred_pub(&shares);
+    let ars = match cmb.ar_info {
+        Some(ar_info) => Some(
+            succeed_or_die!(read_anonymity_revokers(ar_info), e => "Could not read anonymity revokers due to {}"),
+        ),
+        None => None,
+    };
+    let id_cred_pub = succeed_or_die!(combine_id_cred_pub_shares(
+        credential.threshold,
+        &credential.ar_data,
+        ars.as_ref().map(|ars| &ars.anonymity_revokers),
+        &shares,
+    ), e => "Could not combine the shares: {}");
     let id_cred_pub_string = base16_encode_string(&id_cred_pub);
 
     let json = json!({ "idCredPub": id_cred_pub_string });
@@ -429,26 +382,10 @@ fn handle_combine_prf(cmb: CombinePrf) -> Result<(), String> {
         return Err("The version of the ArRecord should be 0.".to_owned());
     }
 
-    let revocation_threshold = ar_record.value.threshold;
-
-    let shares_values: Vec<_> = cmb.shares;
-
-    let number_of_ars = shares_values.len();
-    let number_of_ars =
-        u8::try_from(number_of_ars).expect("Number of anonymity revokers should not exceed 2^8-1");
-    if number_of_ars < revocation_threshold.into() {
-        return Err(format!(
-            "Insufficient number of anonymity revokers ({}). Threshold is {}.",
-            number_of_ars, revocation_threshold
-        ));
-    }
-
-    let mut ar_decrypted_data_vec: Vec<IpArDecryptedData<ExampleCurve>> =
-        Vec::with_capacity(shares_values.len());
-    let mut shares: Vec<(ArIdentity, Value<ExampleCurve>)> =
-        Vec::with_capacity(shares_values.len());
+    let ar_record = ar_record.value;
 
-    for share_value in shares_values.iter() {
+    let mut shares: Vec<IpArDecryptedData<ExampleCurve>> = Vec::with_capacity(cmb.shares.len());
+    for share_value in cmb.shares.iter() {
         match read_json_from_file(&share_value) {
             Err(y) => {
                 return Err(format!(
@@ -457,27 +394,11 @@ fn handle_combine_prf(cmb: CombinePrf) -> Result<(), String> {
                     y
                 ));
             }
-            Ok(val) => ar_decrypted_data_vec.push(val),
+            Ok(val) => shares.push(val),
         }
     }
 
-    let mut ar_identities = Vec::with_capacity(ar_decrypted_data_vec.len());
-
-    for ar_decrypted_data in ar_decrypted_data_vec {
-        let ar_id = ar_decrypted_data.ar_identity;
-        ar_identities.push(ar_id);
-        shares.push((ar_id, ar_decrypted_data.prf_key_share));
-    }
-    ar_identities.sort();
-    ar_identities.dedup();
-    if ar_identities.len() < shares.len() {
-        return Err(
-            "No duplicates among the anonymity revokers identities nor share numbers are allowed."
-                .to_owned(),
-        );
-    }
-
-    let prf_key = reveal_prf_key(&shares);
+    let prf_key = succeed_or_die!(combine_prf_key_shares(&ar_record, &shares), e => "Could not combine the shares: {}");
     let prf_key_string = base16_encode_string(&prf_key);
     let json = json!({ "prfKey": prf_key_string });
     match write_json_to_file(&cmb.out, &json) {
@@ -13,6 +13,7 @@ use id::{
     account_holder::*,
     constants::{ArCurve, IpPairing},
     identity_provider::*,
+    random_oracle::ChallengeContext,
     secret_sharing::*,
     types::*,
 };
@@ -602,6 +603,7 @@ fn handle_verify_credential(vcred: VerifyCredential) {
     };
 
     if let Err(e) = id::chain::verify_cdi(
+        &ChallengeContext::Legacy,
         &global_ctx,
         &ip_info,
         &all_ars_infos.anonymity_revokers,
@@ -836,6 +838,7 @@ fn handle_create_credential(cc: CreateCredential) {
     };
 
     let cdi = create_credential(
+        &ChallengeContext::Legacy,
         context,
         &id_object,
         &id_use_data,
@@ -1286,8 +1289,14 @@ fn handle_start_ip(sip: StartIp) {
         },
         threshold: SignatureThreshold(2),
     };
-    let (pio, randomness) = generate_pio(&context, threshold, &aci, &initial_acc_data)
-        .expect("Generating the pre-identity object should succeed.");
+    let (pio, randomness) = generate_pio(
+        &ChallengeContext::Legacy,
+        &context,
+        threshold,
+        &aci,
+        &initial_acc_data,
+    )
+    .expect("Generating the pre-identity object should succeed.");
 
     // the only thing left is to output all the information
 
@@ -9,8 +9,10 @@ use dodis_yampolskiy_prf as prf;
 use either::{Left, Right};
 use id::{
     account_holder::*,
+    conformance::{CredentialFixture, Fixture, FIXTURE_EXPIRY},
     constants::{ArCurve, IpPairing, *},
     identity_provider::*,
+    random_oracle::ChallengeContext,
     secret_sharing::Threshold,
     types::*,
 };
@@ -49,7 +51,16 @@ struct GenerateTestData {
     anonymity_revokers: PathBuf,
 }
 
-const EXPIRY: TransactionTime = TransactionTime { seconds: u64::MAX };
+const EXPIRY: TransactionTime = FIXTURE_EXPIRY;
+
+/// Write a binary output file, and exit with a non-zero status if that fails.
+fn write_binary(path: &str, bytes: &[u8]) {
+    if let Err(err) = File::create(path).and_then(|mut file| file.write_all(bytes)) {
+        eprintln!("Could not output binary file {}, because {}.", path, err);
+        std::process::exit(1);
+    }
+    println!("Output binary file {}.", path);
+}
 
 fn main() {
     let args = {
@@ -68,7 +79,7 @@ fn main() {
             ars
         } else {
             eprintln!("Cannot read anonymity revokers from the database. Terminating.");
-            return;
+            std::process::exit(1);
         }
     };
 
@@ -77,7 +88,7 @@ fn main() {
             gc
         } else {
             eprintln!("Cannot read global context information database. Terminating.");
-            return;
+            std::process::exit(1);
         }
     };
 
@@ -95,7 +106,7 @@ fn main() {
             }) => (public_ip_info, ip_secret_key, ip_cdi_secret_key),
             Err(x) => {
                 eprintln!("Could not read identity issuer information because {}", x);
-                return;
+                std::process::exit(1);
             }
         };
 
@@ -138,6 +149,7 @@ fn main() {
     };
     // Threshold is all anonymity revokers.
     let (pio, randomness) = generate_pio(
+        &ChallengeContext::Legacy,
         &context,
         Threshold(ars_infos.anonymity_revokers.len() as u8),
         &aci,
@@ -191,6 +203,7 @@ fn main() {
         };
 
         let (cdi_1, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_object_use_data,
@@ -215,6 +228,7 @@ fn main() {
         let addr = AccountAddress::new(&cdi_1.values.cred_id);
 
         let (cdi_2, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_object_use_data,
@@ -225,36 +239,8 @@ fn main() {
         )
         .expect("We should have generated valid data.");
 
-        let mut out = Vec::new();
-        let gc_bytes = to_bytes(&global_ctx);
-        out.put(&(gc_bytes.len() as u32));
-        out.write_all(&gc_bytes).unwrap();
-        let ip_info_bytes = to_bytes(&ip_info);
-        out.put(&(ip_info_bytes.len() as u32));
-        out.write_all(&ip_info_bytes).unwrap();
-        let ars_len = ars_infos.anonymity_revokers.len();
-        out.put(&(ars_len as u64)); // length of the list, expected big-endian in Haskell.
-        for ar in ars_infos.anonymity_revokers.values() {
-            let ar_bytes = to_bytes(ar);
-            out.put(&(ar_bytes.len() as u32));
-            out.write_all(&ar_bytes).unwrap();
-        }
-
-        // output the first credential
-        let cdi1_bytes = to_bytes(&cdi_1);
-        out.put(&(cdi1_bytes.len() as u32));
-        out.write_all(&cdi1_bytes).unwrap();
-        // and account address and then the second credential
-        out.put(&addr);
-        let cdi2_bytes = to_bytes(&cdi_2);
-        out.put(&(cdi2_bytes.len() as u32));
-        out.write_all(&cdi2_bytes).unwrap();
-
         // output another random address
-        {
-            let other_addr = AccountAddress::new(&G1::generate(&mut csprng));
-            out.put(&other_addr)
-        }
+        let other_address = AccountAddress::new(&G1::generate(&mut csprng));
 
         // Create an initial cdi and output it
         let icdi = create_initial_cdi(
@@ -264,65 +250,59 @@ fn main() {
             EXPIRY,
             &ip_cdi_secret_key,
         );
-        let icdi_bytes = to_bytes(&icdi);
-        out.put(&(icdi_bytes.len() as u32));
-        out.write_all(&icdi_bytes).unwrap();
 
-        let file = File::create("testdata.bin");
-        if let Err(err) = file.unwrap().write_all(&out) {
-            eprintln!(
-                "Could not output binary file testdata.bin, because {}.",
-                err
-            );
-        } else {
-            println!("Output binary file testdata.bin.");
+        let fixture = CredentialFixture {
+            global_context: global_ctx.clone(),
+            ip_info: ip_info.clone(),
+            ars_infos: ars_infos.anonymity_revokers.clone(),
+            new_account: cdi_1,
+            address: addr,
+            existing: cdi_2,
+            other_address,
+            initial: icdi,
+        };
+        if let Err(err) = fixture.verify() {
+            eprintln!("The generated credentials are not valid: {}", err);
+            std::process::exit(1);
         }
+        let out = to_bytes(&fixture);
+        let CredentialFixture {
+            new_account: cdi_1,
+            initial: icdi,
+            ..
+        } = fixture;
+
+        write_binary("testdata.bin", &out);
 
         // We also output a versioned CDI in JSON and binary, to test compatiblity with
         // the haskell serialization
 
-        let cdi_coms_file = File::create("cdi-coms.bin");
-        if let Err(err) = cdi_coms_file
-            .unwrap()
-            .write_all(&to_bytes(&cdi_1.proofs.id_proofs.commitments))
-        {
-            eprintln!(
-                "Could not output binary file cdi-coms.bin, because {}.",
-                err
-            );
-        } else {
-            println!("Output binary file cdi-coms.bin.");
-        }
+        write_binary(
+            "cdi-coms.bin",
+            &to_bytes(&cdi_1.proofs.id_proofs.commitments),
+        );
 
         let ver_cdi_1 = Versioned::new(VERSION_0, cdi_1);
         if let Err(err) = write_json_to_file("cdi.json", &ver_cdi_1) {
             eprintln!("Could not output JSON file cdi.json, because {}.", err);
+            std::process::exit(1);
         } else {
             println!("Output cdi.json.");
         }
 
-        let cdi_file = File::create("cdi.bin");
-        if let Err(err) = cdi_file.unwrap().write_all(&to_bytes(&ver_cdi_1)) {
-            eprintln!("Could not output binary file cdi.bin, because {}.", err);
-        } else {
-            println!("Output binary file cdi.bin.");
-        }
+        write_binary("cdi.bin", &to_bytes(&ver_cdi_1));
 
         // As for CDI we output an ICDI json and binary to test compatibility between
         // haskell and rust serialization
         let ver_icdi = Versioned::new(VERSION_0, icdi);
         if let Err(err) = write_json_to_file("icdi.json", &ver_icdi) {
             eprintln!("Could not output JSON file icdi.json, because {}.", err);
+            std::process::exit(1);
         } else {
             println!("Output icdi.json.");
         }
 
-        let icdi_file = File::create("icdi.bin");
-        if let Err(err) = icdi_file.unwrap().write_all(&to_bytes(&ver_icdi)) {
-            eprintln!("Could not output binary file icdi.bin, because {}.", err);
-        } else {
-            println!("Output binary file icdi.bin.");
-        }
+        write_binary("icdi.bin", &to_bytes(&ver_icdi));
     }
 
     // generate account credentials, parametrized
@@ -340,6 +320,7 @@ fn main() {
         };
 
         let (cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_object_use_data,
@@ -369,6 +350,7 @@ fn main() {
 
         if let Err(err) = write_json_to_file(&format!("credential-{}.json", idx), &js) {
             eprintln!("Could not output credential = {}, because {}.", idx, err);
+            std::process::exit(1);
         } else {
             println!("Output credential {}.", idx);
         }
@@ -377,6 +359,7 @@ fn main() {
             write_json_to_file(&format!("credential-private-keys-{}.json", idx), &acc_data)
         {
             eprintln!("Could not output private keys = {}, because {}.", idx, err);
+            std::process::exit(1);
         } else {
             println!("Output private keys {}.", idx);
         }
@@ -425,6 +408,7 @@ fn main() {
             prf_key:          prf,
         };
         let (pio, _) = generate_pio(
+            &ChallengeContext::Legacy,
             &context,
             Threshold(ars_infos.anonymity_revokers.len() as u8),
             &aci,
@@ -131,12 +131,14 @@ fn main() -> std::io::Result<()> {
     // Load identity provider and anonymity revokers.
     let ip_info = read_json_from_file::<_, Versioned<IpInfo<IpPairing>>>(&common.ip_info)?.value;
 
-    let global_ctx = read_global_context(&common.global).ok_or_else(|| {
+    let mut global_ctx = read_global_context(&common.global).ok_or_else(|| {
         Error::new(
             ErrorKind::Other,
             "Cannot read global context information database. Terminating.",
         )
     })?;
+    // The context is used for the commitments of every generated account.
+    global_ctx.precompute();
 
     let ar_info = read_json_from_file::<_, Versioned<ArInfo<ArCurve>>>(&common.ar_info)?.value;
 
@@ -241,10 +243,11 @@ fn main() -> std::io::Result<()> {
             &chosen_ars,
             threshold,
             &global_ctx.on_chain_commitment_key,
+            csprng,
         );
 
-        let (commitments, _) = compute_commitments(
-            &global_ctx.on_chain_commitment_key,
+        let (commitments, _) = compute_commitments_precomputed(
+            &global_ctx.precomputed_commitment_key(),
             &attributes,
             &aci.prf_key,
             cred_counter,
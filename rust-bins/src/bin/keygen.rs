@@ -24,7 +24,7 @@ use std::{
 };
 use structopt::StructOpt;
 
-const BIP39_ENGLISH: &str = include_str!("data/BIP39English.txt");
+const BIP39_ENGLISH: &str = key_derivation::bip39::ENGLISH;
 
 /// List of BIP39 words. There is a test that checks that this list has correct
 /// length, so there is no need to check when using this in the tool.
@@ -0,0 +1,431 @@
+//! A reference verifier for the artifacts produced by the libraries.
+//!
+//! The tool takes an artifact, e.g., a credential or a signed transaction,
+//! together with the context needed to check it, and prints a JSON report of
+//! the checks that were made and whether each of them passed. It exits with
+//! status 1 if the artifact is invalid, and 2 if the inputs could not be read.
+//! It is meant as the reference against which other implementations of the
+//! protocols can be tested.
+use anyhow::{bail, Context};
+use bulletproofs::range_proof::RangeProof;
+use clap::AppSettings;
+use client_server_helpers::*;
+use crypto_common::{
+    types::{TransactionSignature, TransactionTime},
+    *,
+};
+use either::Either;
+use id::{
+    account_info::parse_account_credentials, chain::verify_cdi,
+    id_verifier::verify_attribute_range, identity_provider::validate_request,
+    random_oracle::ChallengeContext, types::*, utils::*,
+};
+use pairing::bls12_381::Bls12;
+use pedersen_scheme::Commitment;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use transactions::TransactionHeader;
+
+#[derive(StructOpt)]
+struct VerifyCredential {
+    #[structopt(long = "credential", help = "File with the versioned credential.")]
+    credential: PathBuf,
+    #[structopt(long = "global", help = "File with the versioned global context.")]
+    global:     PathBuf,
+    #[structopt(
+        long = "ip-info",
+        help = "File with the versioned public information of the identity provider."
+    )]
+    ip_info:    PathBuf,
+    #[structopt(
+        long = "ars",
+        help = "File with the versioned public information of the anonymity revokers."
+    )]
+    ars:        PathBuf,
+    #[structopt(
+        long = "expiry",
+        help = "Expiry of the credential, in seconds since the unix epoch, if it creates a new \
+                account.",
+        required_unless = "address",
+        conflicts_with = "address"
+    )]
+    expiry:     Option<u64>,
+    #[structopt(
+        long = "address",
+        help = "Address of the account the credential is deployed to, if it is added to an \
+                existing account."
+    )]
+    address:    Option<AccountAddress>,
+}
+
+#[derive(StructOpt)]
+struct VerifyIdRequest {
+    #[structopt(
+        long = "request",
+        help = "File with the versioned identity object request."
+    )]
+    request: PathBuf,
+    #[structopt(long = "global", help = "File with the versioned global context.")]
+    global:  PathBuf,
+    #[structopt(
+        long = "ip-info",
+        help = "File with the versioned public information of the identity provider."
+    )]
+    ip_info: PathBuf,
+    #[structopt(
+        long = "ars",
+        help = "File with the versioned public information of the anonymity revokers."
+    )]
+    ars:     PathBuf,
+}
+
+#[derive(StructOpt)]
+struct VerifyTransaction {
+    #[structopt(
+        long = "transaction",
+        help = "File with the signed transaction, as returned by the wallet library, i.e., an \
+                object with fields `transaction` and `signatures`."
+    )]
+    transaction:  PathBuf,
+    #[structopt(
+        long = "account-info",
+        help = "File with the account info of the sender as returned by the node."
+    )]
+    account_info: PathBuf,
+    #[structopt(
+        long = "now",
+        help = "Current time in seconds since the unix epoch, used to check the expiry. If not \
+                given the expiry is not checked."
+    )]
+    now:          Option<u64>,
+}
+
+#[derive(StructOpt)]
+struct VerifyAttributeProof {
+    #[structopt(
+        long = "proof",
+        help = "File with an object with fields `commitment`, `lower`, `upper` and `proof`."
+    )]
+    proof:  PathBuf,
+    #[structopt(long = "global", help = "File with the versioned global context.")]
+    global: PathBuf,
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    about = "Reference verifier for credentials, identity object requests, transactions and \
+             attribute proofs.",
+    author = "Concordium",
+    version = "0.1"
+)]
+enum VerifyAnything {
+    #[structopt(name = "credential", about = "Verify a credential deployment.")]
+    Credential(VerifyCredential),
+    #[structopt(name = "id-request", about = "Verify an identity object request.")]
+    IdRequest(VerifyIdRequest),
+    #[structopt(
+        name = "transaction",
+        about = "Verify the signatures of an account transaction."
+    )]
+    Transaction(VerifyTransaction),
+    #[structopt(
+        name = "attribute-proof",
+        about = "Verify a proof that an attribute lies in a range."
+    )]
+    AttributeProof(VerifyAttributeProof),
+}
+
+/// A single check made on an artifact.
+#[derive(SerdeSerialize)]
+struct Check {
+    check:   String,
+    passed:  bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+}
+
+/// The result of verifying an artifact.
+#[derive(SerdeSerialize)]
+struct Report {
+    artifact: &'static str,
+    valid:    bool,
+    checks:   Vec<Check>,
+}
+
+impl Report {
+    fn new(artifact: &'static str) -> Self {
+        Report {
+            artifact,
+            valid: true,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Record a check. The artifact is invalid if any check fails.
+    fn check(&mut self, check: &str, passed: bool, details: Option<String>) -> bool {
+        self.valid &= passed;
+        self.checks.push(Check {
+            check: check.to_owned(),
+            passed,
+            details,
+        });
+        passed
+    }
+}
+
+fn main() {
+    let app = VerifyAnything::clap()
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .global_setting(AppSettings::ColoredHelp);
+    let matches = app.get_matches();
+    let va = VerifyAnything::from_clap(&matches);
+    use VerifyAnything::*;
+    let report = match va {
+        Credential(vc) => handle_verify_credential(vc),
+        IdRequest(vr) => handle_verify_id_request(vr),
+        Transaction(vt) => handle_verify_transaction(vt),
+        AttributeProof(vp) => handle_verify_attribute_proof(vp),
+    };
+    match report {
+        Ok(report) => {
+            output_json(&report);
+            if !report.valid {
+                std::process::exit(1)
+            }
+        }
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(2)
+        }
+    }
+}
+
+fn read_global(path: &Path) -> anyhow::Result<GlobalContext<ExampleCurve>> {
+    match read_global_context(path) {
+        Some(global) => Ok(global),
+        None => bail!("Could not read the global context from {}.", path.display()),
+    }
+}
+
+fn handle_verify_credential(vc: VerifyCredential) -> anyhow::Result<Report> {
+    let cdi = read_credential(&vc.credential).context("Could not read the credential")?;
+    let global = read_global(&vc.global)?;
+    let ip_info = read_ip_info(&vc.ip_info).context("Could not read the identity provider")?;
+    let ars = read_anonymity_revokers(&vc.ars).context("Could not read the anonymity revokers")?;
+    let new_or_existing = match (vc.expiry, vc.address) {
+        (_, Some(address)) => Either::Right(address),
+        (Some(seconds), None) => Either::Left(TransactionTime { seconds }),
+        (None, None) => bail!("Either the expiry or the address must be given."),
+    };
+
+    let mut report = Report::new("credential");
+    report.check(
+        "The credential is issued by the given identity provider.",
+        cdi.values.ip_identity == ip_info.ip_identity,
+        Some(format!(
+            "The credential names identity provider {}.",
+            cdi.values.ip_identity
+        )),
+    );
+    let unknown_ars = cdi
+        .values
+        .ar_data
+        .keys()
+        .filter(|ar| !ars.anonymity_revokers.contains_key(ar))
+        .map(|ar| ar.to_string())
+        .collect::<Vec<_>>();
+    report.check(
+        "All anonymity revokers of the credential are known.",
+        unknown_ars.is_empty(),
+        if unknown_ars.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "Unknown anonymity revokers: {}.",
+                unknown_ars.join(", ")
+            ))
+        },
+    );
+    report.check(
+        "The anonymity revocation threshold is at most the number of anonymity revokers.",
+        usize::from(cdi.values.threshold) <= cdi.values.ar_data.len(),
+        None,
+    );
+    let result = verify_cdi::<Bls12, _, _, _>(
+        &ChallengeContext::Legacy,
+        &global,
+        &ip_info,
+        &ars.anonymity_revokers,
+        &cdi,
+        &new_or_existing,
+    );
+    report.check(
+        "The proofs and signatures of the credential are valid.",
+        result.is_ok(),
+        result.err().map(|e| e.to_string()),
+    );
+    Ok(report)
+}
+
+fn handle_verify_id_request(vr: VerifyIdRequest) -> anyhow::Result<Report> {
+    let request: Versioned<PreIdentityObject<Bls12, ExampleCurve>> =
+        read_json_from_file(&vr.request).context("Could not read the request")?;
+    let global = read_global(&vr.global)?;
+    let ip_info = read_ip_info(&vr.ip_info).context("Could not read the identity provider")?;
+    let ars = read_anonymity_revokers(&vr.ars).context("Could not read the anonymity revokers")?;
+
+    let mut report = Report::new("identity object request");
+    if !report.check(
+        "The request has a supported version.",
+        request.version == VERSION_0,
+        Some(format!("The version is {}.", request.version)),
+    ) {
+        return Ok(report);
+    }
+    let request = request.value;
+    let unknown_ars = request
+        .choice_ar_parameters
+        .ar_identities
+        .iter()
+        .filter(|ar| !ars.anonymity_revokers.contains_key(ar))
+        .map(|ar| ar.to_string())
+        .collect::<Vec<_>>();
+    report.check(
+        "All chosen anonymity revokers are known.",
+        unknown_ars.is_empty(),
+        if unknown_ars.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "Unknown anonymity revokers: {}.",
+                unknown_ars.join(", ")
+            ))
+        },
+    );
+    let context = IpContext::new(&ip_info, &ars.anonymity_revokers, &global);
+    let result = validate_request(&ChallengeContext::Legacy, &request, context);
+    report.check(
+        "The proofs of the request are valid.",
+        result.is_ok(),
+        result.err().map(|e| e.to_string()),
+    );
+    Ok(report)
+}
+
+fn handle_verify_transaction(vt: VerifyTransaction) -> anyhow::Result<Report> {
+    let input: serde_json::Value =
+        read_json_from_file(&vt.transaction).context("Could not read the transaction")?;
+    let body = match input.get("transaction").and_then(|t| t.as_str()) {
+        Some(body) => hex::decode(body).context("The transaction is not a hex string")?,
+        None => bail!("The input has no field `transaction`."),
+    };
+    let signature: TransactionSignature = match input.get("signatures") {
+        Some(sigs) => serde_json::from_value(sigs.clone()).context("Invalid signatures")?,
+        None => bail!("The input has no field `signatures`."),
+    };
+    let account_info: serde_json::Value =
+        read_json_from_file(&vt.account_info).context("Could not read the account info")?;
+    let credentials = parse_account_credentials::<ExampleCurve, ExampleAttribute>(&account_info)?;
+    let threshold: SignatureThreshold = match account_info.get("accountThreshold") {
+        Some(t) => serde_json::from_value(t.clone()).context("Invalid account threshold")?,
+        None => bail!("The account info has no field `accountThreshold`."),
+    };
+    let keys: BTreeMap<_, _> = credentials
+        .iter()
+        .map(|(idx, cred)| (*idx, cred.public_keys().clone()))
+        .collect();
+
+    let mut report = Report::new("account transaction");
+    let mut source = std::io::Cursor::new(&body);
+    let header: TransactionHeader = match source.get() {
+        Ok(header) => header,
+        Err(e) => {
+            report.check(
+                "The transaction has a complete header.",
+                false,
+                Some(format!("The header could not be parsed: {}.", e)),
+            );
+            return Ok(report);
+        }
+    };
+    let payload_len = body.len() - source.position() as usize;
+    report.check(
+        "The payload size in the header matches the payload.",
+        header.payload_size as usize == payload_len,
+        Some(format!(
+            "The header gives {} bytes, and the payload is {} bytes.",
+            header.payload_size, payload_len
+        )),
+    );
+    report.check(
+        "The nonce is positive.",
+        header.nonce > 0,
+        Some(format!("The nonce is {}.", header.nonce)),
+    );
+    if let Some(now) = vt.now {
+        report.check(
+            "The transaction has not expired.",
+            header.expiry.seconds >= now,
+            Some(format!(
+                "The transaction expires at {}.",
+                header.expiry.seconds
+            )),
+        );
+    }
+    let hash = Sha256::digest(&body);
+    report.check(
+        "The signatures are valid and satisfy the thresholds of the account.",
+        verify_transaction_signatures_batch(&[TransactionToVerify {
+            keys: &keys,
+            threshold,
+            hash: &hash,
+            signature: &signature,
+        }])[0],
+        Some(format!(
+            "The transaction hash is {}, and it has {} signatures.",
+            hex::encode(&hash),
+            signature.num_signatures()
+        )),
+    );
+    Ok(report)
+}
+
+/// A proof that an attribute in a commitment lies in `[lower, upper)`.
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributeRangeProof {
+    commitment: Commitment<ExampleCurve>,
+    lower:      ExampleAttribute,
+    upper:      ExampleAttribute,
+    proof:      RangeProof<ExampleCurve>,
+}
+
+fn handle_verify_attribute_proof(vp: VerifyAttributeProof) -> anyhow::Result<Report> {
+    let proof: AttributeRangeProof =
+        read_json_from_file(&vp.proof).context("Could not read the proof")?;
+    let global = read_global(&vp.global)?;
+
+    let mut report = Report::new("attribute range proof");
+    let result = verify_attribute_range(
+        &ChallengeContext::Legacy,
+        &global.on_chain_commitment_key,
+        global.bulletproof_generators(),
+        &proof.lower,
+        &proof.upper,
+        &proof.commitment,
+        &proof.proof,
+    );
+    report.check(
+        "The range proof is valid.",
+        result.is_ok(),
+        Some(match result {
+            Ok(()) => format!("The range is [{}, {}).", proof.lower, proof.upper),
+            Err(e) => format!("Verification failed with {:?}.", e),
+        }),
+    );
+    Ok(report)
+}
@@ -6,6 +6,7 @@ use generic_array::GenericArray;
 use id::sigma_protocols::{common::*, dlog::*};
 use rand::Rng;
 use random_oracle::RandomOracle;
+#[cfg(feature = "parallel")]
 use rayon::iter::*;
 use sha2::{Digest, Sha512};
 
@@ -107,7 +108,9 @@ impl<P: Pairing> PartialEq for PublicKey<P> {
     fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
 }
 
-#[derive(Debug, Eq, Serialize)]
+/// A signature is a point on the first curve of the pairing. Signatures can be
+/// aggregated, and an aggregate signature is again a signature.
+#[derive(Debug, Eq, Serialize, SerdeBase16Serialize)]
 pub struct Signature<P: Pairing>(P::G1);
 
 impl<P: Pairing> Signature<P> {
@@ -131,6 +134,15 @@ impl<P: Pairing> PartialEq for Signature<P> {
     fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
 }
 
+/// Aggregates a list of signatures into a single signature. Aggregating the
+/// empty list gives the empty signature, which does not verify against any
+/// set of signers.
+pub fn aggregate_signatures<P: Pairing>(signatures: &[Signature<P>]) -> Signature<P> {
+    signatures
+        .iter()
+        .fold(Signature::empty(), |agg, sig| agg.aggregate(*sig))
+}
+
 /// A proof of knowledge of a secretkey
 pub type Proof<P> = SigmaProof<Witness<<P as Pairing>::G2>>;
 
@@ -154,20 +166,29 @@ pub fn verify_aggregate_sig<P: Pairing>(
         return false;
     }
 
+    let pair_and_multiply = |prod: P::TargetField, (m, pk): &(&[u8], PublicKey<P>)| {
+        let g1_hash = P::G1::hash_to_group(m);
+        let paired = P::pair(&g1_hash, &pk.0);
+        let mut p = prod;
+        p.mul_assign(&paired);
+        p
+    };
+
+    #[cfg(feature = "parallel")]
+    let product = crypto_common::parallel::install(|| {
+        m_pk_pairs
+            .par_iter()
+            .fold(<P::TargetField as Field>::one, pair_and_multiply)
+            .reduce(<P::TargetField as Field>::one, |prod, x| {
+                let mut p = prod;
+                p.mul_assign(&x);
+                p
+            })
+    });
+    #[cfg(not(feature = "parallel"))]
     let product = m_pk_pairs
-        .par_iter()
-        .fold(<P::TargetField as Field>::one, |prod, (m, pk)| {
-            let g1_hash = P::G1::hash_to_group(m);
-            let paired = P::pair(&g1_hash, &pk.0);
-            let mut p = prod;
-            p.mul_assign(&paired);
-            p
-        })
-        .reduce(<P::TargetField as Field>::one, |prod, x| {
-            let mut p = prod;
-            p.mul_assign(&x);
-            p
-        });
+        .iter()
+        .fold(<P::TargetField as Field>::one(), pair_and_multiply);
 
     P::pair(&signature.0, &P::G2::one_point()) == product
 }
@@ -188,14 +209,21 @@ pub fn verify_aggregate_sig_trusted_keys<P: Pairing>(
         return false;
     }
 
+    #[cfg(feature = "parallel")]
     let sum = if pks.len() < 150 {
         pks.iter()
             .fold(P::G2::zero_point(), |s, x| s.plus_point(&x.0))
     } else {
-        pks.par_iter()
-            .fold(P::G2::zero_point, |s, x| s.plus_point(&x.0))
-            .reduce(P::G2::zero_point, |s, x| s.plus_point(&x))
+        crypto_common::parallel::install(|| {
+            pks.par_iter()
+                .fold(P::G2::zero_point, |s, x| s.plus_point(&x.0))
+                .reduce(P::G2::zero_point, |s, x| s.plus_point(&x))
+        })
     };
+    #[cfg(not(feature = "parallel"))]
+    let sum = pks
+        .iter()
+        .fold(P::G2::zero_point(), |s, x| s.plus_point(&x.0));
 
     // compute pairings in parallel
     P::check_pairing_eq(
@@ -352,6 +380,27 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_aggregate_signatures() {
+        let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
+        let (sks, pks) = get_sks_pks::<Bls12>(10, &mut rng);
+        let m: [u8; 32] = rng.gen::<[u8; 32]>();
+        let sigs: Vec<Signature<Bls12>> = sks.iter().map(|sk| sk.sign(&m)).collect();
+        let agg_sig = aggregate_signatures(&sigs);
+        assert_eq!(agg_sig, aggregate_sigs!(vec![m; sks.len()], sks));
+        assert!(verify_aggregate_sig_trusted_keys(&m, &pks, agg_sig));
+        assert_eq!(aggregate_signatures(&sigs[..1]), sigs[0]);
+        assert_eq!(aggregate_signatures::<Bls12>(&[]), Signature::empty());
+
+        // signatures are serialized to JSON as hex strings of their binary
+        // serialization
+        let json = serde_json::to_value(&agg_sig).expect("Serialization to JSON failed.");
+        assert_eq!(json, serde_json::json!(base16_encode_string(&agg_sig)));
+        let agg_sig_json: Signature<Bls12> =
+            serde_json::from_value(json).expect("Deserialization from JSON failed.");
+        assert_eq!(agg_sig_json, agg_sig);
+    }
+
     #[test]
     fn test_verification_empty_signers() {
         let mut rng: StdRng = SeedableRng::from_rng(thread_rng()).unwrap();
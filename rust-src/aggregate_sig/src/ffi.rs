@@ -191,6 +191,16 @@ pub extern "C" fn bls_check_proof(
     u8::from(check)
 }
 
+/// Set the number of threads used when verifying aggregate signatures. If `n`
+/// is 0 the default of one thread per core is restored. Returns 1 on success,
+/// and 0 if the thread pool could not be created, in which case the previous
+/// setting is retained.
+#[cfg(feature = "parallel")]
+#[no_mangle]
+pub extern "C" fn bls_set_thread_count(n: size_t) -> u8 {
+    u8::from(crypto_common::parallel::set_thread_count(n as usize).is_ok())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -6,12 +6,46 @@ use random_oracle::RandomOracle;
 
 #[derive(Clone, Serialize, Debug)]
 pub struct InnerProductProof<C: Curve> {
+    /// One pair per round, i.e., the logarithm of the number of generators.
     #[size_length = 4]
+    #[max_size = 64]
     pub lr_vec: Vec<(C, C)>,
     pub a:      C::Scalar,
     pub b:      C::Scalar,
 }
 
+/// The structured JSON representation of [InnerProductProof], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "InnerProductProof",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct InnerProductProofJson<C: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_vec_pair",
+        deserialize_with = "base16_decode_vec_pair"
+    )]
+    lr_vec: Vec<(C, C)>,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    a:      C::Scalar,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    b:      C::Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for InnerProductProof<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        InnerProductProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        InnerProductProofJson::deserialize(des)
+    }
+}
+
 /// This function computes an inner product proof,
 /// which is a proof of knowledge that the prover knows vectors a and b such
 /// that P'=<a,G>+<b,H>+<a,b>Q.
@@ -1,4 +1,5 @@
 use crate::inner_product_proof::*;
+use anyhow::bail;
 use crypto_common::*;
 use crypto_common_derive::*;
 use curve_arithmetic::{multiexp, multiexp_table, multiexp_worker_given_table, Curve, Value};
@@ -21,6 +22,48 @@ pub struct RangeProof<C: Curve> {
     ip_proof: InnerProductProof<C>,
 }
 
+/// The structured JSON representation of [RangeProof], see [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "RangeProof",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+#[allow(non_snake_case)]
+struct RangeProofJson<C: Curve> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    A:        C,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    S:        C,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    T_1:      C,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    T_2:      C,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    tx:       C::Scalar,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    tx_tilde: C::Scalar,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    e_tilde:  C::Scalar,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    ip_proof: InnerProductProof<C>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for RangeProof<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        RangeProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        RangeProofJson::deserialize(des)
+    }
+}
+
 /// Determine whether the i-th bit (counting from least significant) is set in
 /// the given u64 value.
 fn ith_bit_bool(v: u64, i: u8) -> bool { v & (1 << i) != 0 }
@@ -104,6 +147,115 @@ impl<C: Curve> Generators<C> {
     }
 }
 
+/// Bulletproof generators of which only a prefix has been decoded. The
+/// remaining generators are kept in serialized form and are only decoded when
+/// they are needed, see [LazyGenerators::ensure]. Decoding group elements is
+/// expensive and decoded elements take more space than serialized ones, so this
+/// saves both time and memory when only short range proofs are needed.
+///
+/// The serialization is the same as that of [Generators], regardless of how
+/// many of the generators have been decoded.
+#[derive(Clone, SerdeBase16Serialize)]
+pub struct LazyGenerators<C: Curve> {
+    decoded:   Generators<C>,
+    /// The serialization of the generators following the decoded ones.
+    remaining: Vec<u8>,
+}
+
+impl<C: Curve> LazyGenerators<C> {
+    /// The size in bytes of a serialized pair of generators.
+    const PAIR_LENGTH: usize = 2 * C::GROUP_ELEMENT_LENGTH;
+
+    /// Deserialize generators in the format of [Generators], but only decode
+    /// the first `n` of them.
+    pub fn deserial_prefix<R: ReadBytesExt>(source: &mut R, n: usize) -> ParseResult<Self> {
+        let len: u32 = source.get()?;
+        let len = len as usize;
+        let n = std::cmp::min(n, len);
+        let mut gh = safe_with_capacity(n);
+        for _ in 0..n {
+            gh.push(source.get()?);
+        }
+        let remaining_length = match (len - n).checked_mul(Self::PAIR_LENGTH) {
+            Some(l) => l,
+            None => bail!("Too many generators: {}.", len),
+        };
+        let remaining = deserial_bytes(source, remaining_length)?;
+        Ok(Self {
+            decoded: Generators { G_H: gh },
+            remaining,
+        })
+    }
+
+    /// The total number of generators, decoded or not.
+    pub fn len(&self) -> usize { self.decoded.G_H.len() + self.remaining.len() / Self::PAIR_LENGTH }
+
+    /// Whether there are no generators at all.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// The generators that have been decoded so far.
+    pub fn decoded(&self) -> &Generators<C> { &self.decoded }
+
+    /// The serialized pairs of generators that have not been decoded yet,
+    /// following the decoded ones.
+    pub fn undecoded(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.remaining
+            .chunks(Self::PAIR_LENGTH)
+            .map(|pair| pair.split_at(C::GROUP_ELEMENT_LENGTH))
+    }
+
+    /// Make sure that at least the first `n` generators are decoded. This fails
+    /// if there are fewer than `n` generators, or if they cannot be decoded.
+    pub fn ensure(&mut self, n: usize) -> ParseResult<()> {
+        let have = self.decoded.G_H.len();
+        if n <= have {
+            return Ok(());
+        }
+        if n > self.len() {
+            bail!(
+                "Only {} generators are available, but {} are needed.",
+                self.len(),
+                n
+            );
+        }
+        let bytes = (n - have) * Self::PAIR_LENGTH;
+        let mut source = std::io::Cursor::new(&self.remaining[..bytes]);
+        let mut new = Vec::with_capacity(n - have);
+        for _ in have..n {
+            new.push(source.get()?);
+        }
+        self.decoded.G_H.extend(new);
+        self.remaining.drain(..bytes);
+        Ok(())
+    }
+}
+
+impl<C: Curve> From<Generators<C>> for LazyGenerators<C> {
+    fn from(decoded: Generators<C>) -> Self {
+        Self {
+            decoded,
+            remaining: Vec::new(),
+        }
+    }
+}
+
+impl<C: Curve> Serial for LazyGenerators<C> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&(self.len() as u32));
+        serial_vector_no_length(&self.decoded.G_H, out);
+        out.write_all(&self.remaining)
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+/// Deserialization decodes all the generators. Use
+/// [LazyGenerators::deserial_prefix] to decode only some of them.
+impl<C: Curve> Deserial for LazyGenerators<C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        Self::deserial_prefix(source, usize::MAX)
+    }
+}
+
 /// This function produces a range proof given scalars in a prime field
 /// instead of integers. It invokes prove(), documented below.
 ///
@@ -427,38 +579,43 @@ pub enum VerificationError {
     Second,
     /// The length of G_H was less than nm, which is too small
     NotEnoughGenerators,
+    /// The combined check of a batch of proofs failed, see [BatchVerifier].
+    Batch,
 }
 
-/// This function verifies a range proof, i.e. a proof of knowledge
-/// of value v_1, v_2, ..., v_m that are all in [0, 2^n) that are consistent
-/// with commitments V_i to v_i. The arguments are
-/// - n - the number n such that each v_i is claimed to be in [0, 2^n) by the
-///   prover
-/// - commitments - commitments V_i to each v_i
-/// - proof - the range proof
-/// - gens - generators containing vectors G and H both of length nm
-/// - v_keys - commitment keys B and B_tilde
-///
-/// This function is more efficient than the naive_verify since it
-/// unfolds what the inner product proof verifier does using the verification
-/// scalars.
+/// The two checks of the verifier, each of which is that a linear combination
+/// of points is zero. The terms with the generators G and H of the second
+/// check are kept apart, so that [BatchVerifier] can combine them across
+/// proofs.
+#[allow(non_snake_case)]
+struct VerificationEquations<C: Curve> {
+    /// The points of the first check and their scalars.
+    first:     (Vec<C>, Vec<C::Scalar>),
+    /// The points of the second check, except for G and H, and their scalars.
+    second:    (Vec<C>, Vec<C::Scalar>),
+    /// The scalars of the generators G in the second check.
+    G_scalars: Vec<C::Scalar>,
+    /// The scalars of the generators H in the second check.
+    H_scalars: Vec<C::Scalar>,
+}
+
+/// Add the commitments and the proof to the transcript, and compute the checks
+/// the verifier does. The arguments are as for [verify_efficient].
 #[allow(non_snake_case)]
-#[allow(clippy::too_many_arguments)]
 #[allow(clippy::many_single_char_names)]
-pub fn verify_efficient<C: Curve>(
+fn verification_equations<C: Curve>(
     transcript: &mut RandomOracle,
     n: u8,
     commitments: &[Commitment<C>],
     proof: &RangeProof<C>,
     gens: &Generators<C>,
     v_keys: &CommitmentKey<C>,
-) -> Result<(), VerificationError> {
+) -> Result<VerificationEquations<C>, VerificationError> {
     let m = commitments.len();
     let nm = usize::from(n) * m;
     if gens.G_H.len() < nm {
         return Err(VerificationError::NotEnoughGenerators);
     }
-    let (G, H): (Vec<_>, Vec<_>) = gens.G_H.iter().take(nm).cloned().unzip();
     let B = v_keys.g;
     let B_tilde = v_keys.h;
     for V in commitments {
@@ -484,7 +641,6 @@ pub fn verify_efficient<C: Curve>(
     let x: C::Scalar = transcript.challenge_scalar::<C, _>(b"x");
     let mut x2 = x;
     x2.mul_assign(&x);
-    // println!("verifier's x = {:?}", x);
     transcript.append_message(b"tx", &tx);
     transcript.append_message(b"tx_tilde", &tx_tilde);
     transcript.append_message(b"e_tilde", &e_tilde);
@@ -492,7 +648,7 @@ pub fn verify_efficient<C: Curve>(
     // Calculate delta(x,y):
     let mut ip_1_y_nm = C::Scalar::zero();
     let mut yi = C::Scalar::one();
-    for _ in 0..G.len() {
+    for _ in 0..nm {
         ip_1_y_nm.add_assign(&yi);
         yi.mul_assign(&y);
     }
@@ -514,37 +670,38 @@ pub fn verify_efficient<C: Curve>(
     delta_yz.mul_assign(&ip_1_y_nm);
     delta_yz.sub_assign(&sum);
 
-    // LHS of check equation 1:
-    let LHS = B
-        .mul_by_scalar(&tx)
-        .plus_point(&B_tilde.mul_by_scalar(&tx_tilde));
-    let mut RHS = {
-        let mut zj2 = z2;
-        let mut powers = Vec::with_capacity(m);
-        for _ in 0..m {
-            powers.push(zj2);
-            zj2.mul_assign(&z);
-        }
-        multiexp::<C, Commitment<C>>(commitments, &powers)
-    };
-
-    RHS = RHS.plus_point(&multiexp(&[B, T_1, T_2], &[delta_yz, x, x2]));
-
-    let first = LHS.minus_point(&RHS).is_zero_point();
-    if !first {
-        // Terminate early to avoid wasted effort.
-        return Err(VerificationError::First);
+    // The first check is that
+    // tx * B + tx_tilde * B_tilde
+    //   = sum_j z^{j+2} * V_j + delta(y,z) * B + x * T_1 + x^2 * T_2.
+    let mut B_scalar = tx;
+    B_scalar.sub_assign(&delta_yz);
+    let mut minus_x = x;
+    minus_x.negate();
+    let mut minus_x2 = x2;
+    minus_x2.negate();
+    let mut first_points = Vec::with_capacity(4 + m);
+    let mut first_scalars = Vec::with_capacity(4 + m);
+    first_points.extend_from_slice(&[B, B_tilde, T_1, T_2]);
+    first_scalars.extend_from_slice(&[B_scalar, tx_tilde, minus_x, minus_x2]);
+    let mut zj2 = z2;
+    for V in commitments {
+        let mut minus_zj2 = zj2;
+        minus_zj2.negate();
+        first_points.push(V.0);
+        first_scalars.push(minus_zj2);
+        zj2.mul_assign(&z);
     }
 
+    // The second check unfolds what the verifier of the inner product proof
+    // does, using the verification scalars.
     let ip_proof = &proof.ip_proof;
-    let mut H_scalars: Vec<C::Scalar> = Vec::with_capacity(G.len());
+    let mut H_scalars: Vec<C::Scalar> = Vec::with_capacity(nm);
     let mut y_i = C::Scalar::one();
     let z_2_m = z_vec(z, 2, m);
-    let verification_scalars = verify_scalars(transcript, G.len(), &ip_proof);
-    if verification_scalars.is_none() {
-        return Err(VerificationError::DivisionError);
-    }
-    let verification_scalars = verification_scalars.unwrap();
+    let verification_scalars = match verify_scalars(transcript, nm, &ip_proof) {
+        Some(scalars) => scalars,
+        None => return Err(VerificationError::DivisionError),
+    };
     let (u_sq, u_inv_sq, s) = (
         verification_scalars.u_sq,
         verification_scalars.u_inv_sq,
@@ -560,7 +717,7 @@ pub fn verify_efficient<C: Curve>(
         None => return Err(VerificationError::DivisionError),
     };
     let two_n: Vec<C::Scalar> = two_n_vec(n);
-    for i in 0..G.len() {
+    for i in 0..nm {
         let j = i / usize::from(n);
         let mut H_scalar = two_n[i % usize::from(n)];
         H_scalar.mul_assign(&z_2_m[j]);
@@ -574,20 +731,14 @@ pub fn verify_efficient<C: Curve>(
     }
     s_inv.reverse();
     let s = s_inv;
-    let H_term = multiexp(&H, &H_scalars); // Expensive!
-    let A_term = A;
-    let S_term = S.mul_by_scalar(&x);
     let mut B_scalar = tx;
     let mut ab = a;
     ab.mul_assign(&b);
     B_scalar.sub_assign(&ab);
     B_scalar.mul_assign(&w);
-    let B_term = B.mul_by_scalar(&B_scalar);
     let mut minus_e_tilde = e_tilde;
     minus_e_tilde.negate();
-    let B_tilde_scalar = minus_e_tilde;
-    let B_tilde_term = B_tilde.mul_by_scalar(&B_tilde_scalar);
-    let mut G_scalars = Vec::with_capacity(G.len());
+    let mut G_scalars = Vec::with_capacity(nm);
     for si in s {
         let mut G_scalar = z;
         G_scalar.negate();
@@ -596,29 +747,193 @@ pub fn verify_efficient<C: Curve>(
         G_scalar.sub_assign(&sa);
         G_scalars.push(G_scalar);
     }
-    let G_term = multiexp(&G, &G_scalars); // Expensive!
-    let L_term = multiexp(&L, &u_sq); // Expensive!
-    let R_term = multiexp(&R, &u_inv_sq); // Expensive!
-
-    let sum = A_term
-        .plus_point(&S_term)
-        .plus_point(&B_term)
-        .plus_point(&B_tilde_term)
-        .plus_point(&G_term)
-        .plus_point(&H_term)
-        .plus_point(&L_term)
-        .plus_point(&R_term);
-
-    let second = sum.is_zero_point();
-    if first && second {
+    let mut second_points = Vec::with_capacity(4 + L.len() + R.len());
+    let mut second_scalars = Vec::with_capacity(4 + u_sq.len() + u_inv_sq.len());
+    second_points.extend_from_slice(&[A, S, B, B_tilde]);
+    second_scalars.extend_from_slice(&[C::Scalar::one(), x, B_scalar, minus_e_tilde]);
+    second_points.extend(L);
+    second_scalars.extend(u_sq);
+    second_points.extend(R);
+    second_scalars.extend(u_inv_sq);
+
+    Ok(VerificationEquations {
+        first: (first_points, first_scalars),
+        second: (second_points, second_scalars),
+        G_scalars,
+        H_scalars,
+    })
+}
+
+/// This function verifies a range proof, i.e. a proof of knowledge
+/// of value v_1, v_2, ..., v_m that are all in [0, 2^n) that are consistent
+/// with commitments V_i to v_i. The arguments are
+/// - n - the number n such that each v_i is claimed to be in [0, 2^n) by the
+///   prover
+/// - commitments - commitments V_i to each v_i
+/// - proof - the range proof
+/// - gens - generators containing vectors G and H both of length nm
+/// - v_keys - commitment keys B and B_tilde
+///
+/// This function is more efficient than the naive_verify since it
+/// unfolds what the inner product proof verifier does using the verification
+/// scalars.
+#[allow(non_snake_case)]
+pub fn verify_efficient<C: Curve>(
+    transcript: &mut RandomOracle,
+    n: u8,
+    commitments: &[Commitment<C>],
+    proof: &RangeProof<C>,
+    gens: &Generators<C>,
+    v_keys: &CommitmentKey<C>,
+) -> Result<(), VerificationError> {
+    let equations = verification_equations(transcript, n, commitments, proof, gens, v_keys)?;
+    let (first_points, first_scalars) = &equations.first;
+    if !multiexp(first_points, first_scalars).is_zero_point() {
+        return Err(VerificationError::First);
+    }
+    let (G, H): (Vec<_>, Vec<_>) = gens
+        .G_H
+        .iter()
+        .take(equations.G_scalars.len())
+        .cloned()
+        .unzip();
+    let (second_points, second_scalars) = &equations.second;
+    let sum = multiexp(second_points, second_scalars)
+        .plus_point(&multiexp(&G, &equations.G_scalars)) // Expensive!
+        .plus_point(&multiexp(&H, &equations.H_scalars)); // Expensive!
+    if sum.is_zero_point() {
         Ok(())
     } else {
-        // We know now the second check failed since we would have terminated
-        // early if the first one had failed.
         Err(VerificationError::Second)
     }
 }
 
+/// Verifier of several range proofs at once. The checks of all the proofs are
+/// combined with random weights into a single multi-exponentiation, in which
+/// the generators G and H are shared by all the proofs, so this is faster than
+/// verifying the proofs one by one with [verify_efficient]. If any of the
+/// proofs is invalid the batch fails, except with negligible probability. The
+/// batch does not tell which proof is invalid, for that the proofs must be
+/// verified individually.
+#[allow(non_snake_case)]
+pub struct BatchVerifier<'a, C: Curve> {
+    gens:      &'a Generators<C>,
+    /// The points of all the checks, except for G and H.
+    points:    Vec<C>,
+    /// The weighted scalars of `points`.
+    scalars:   Vec<C::Scalar>,
+    /// The weighted scalars of the generators G, summed over all proofs.
+    G_scalars: Vec<C::Scalar>,
+    /// The weighted scalars of the generators H, summed over all proofs.
+    H_scalars: Vec<C::Scalar>,
+}
+
+impl<'a, C: Curve> BatchVerifier<'a, C> {
+    /// An empty batch of proofs using the given generators.
+    pub fn new(gens: &'a Generators<C>) -> Self {
+        BatchVerifier {
+            gens,
+            points: Vec::new(),
+            scalars: Vec::new(),
+            G_scalars: Vec::new(),
+            H_scalars: Vec::new(),
+        }
+    }
+
+    /// Add a proof to the batch. The arguments are as for [verify_efficient],
+    /// and the transcript is updated in the same way, so proofs that are
+    /// verified in sequence with the same transcript can be added in the same
+    /// order. This fails if the proof cannot be checked at all, e.g., if there
+    /// are not enough generators.
+    #[allow(non_snake_case)]
+    pub fn add<R: Rng>(
+        &mut self,
+        transcript: &mut RandomOracle,
+        n: u8,
+        commitments: &[Commitment<C>],
+        proof: &RangeProof<C>,
+        v_keys: &CommitmentKey<C>,
+        csprng: &mut R,
+    ) -> Result<(), VerificationError> {
+        let equations =
+            verification_equations(transcript, n, commitments, proof, self.gens, v_keys)?;
+        let first_weight = C::generate_non_zero_scalar(csprng);
+        let second_weight = C::generate_non_zero_scalar(csprng);
+        let (first_points, first_scalars) = equations.first;
+        self.add_terms(first_points, first_scalars, &first_weight);
+        let (second_points, second_scalars) = equations.second;
+        self.add_terms(second_points, second_scalars, &second_weight);
+        let nm = equations.G_scalars.len();
+        if self.G_scalars.len() < nm {
+            self.G_scalars.resize(nm, C::Scalar::zero());
+            self.H_scalars.resize(nm, C::Scalar::zero());
+        }
+        for (acc, G_scalar) in self.G_scalars.iter_mut().zip(equations.G_scalars) {
+            let mut weighted = G_scalar;
+            weighted.mul_assign(&second_weight);
+            acc.add_assign(&weighted);
+        }
+        for (acc, H_scalar) in self.H_scalars.iter_mut().zip(equations.H_scalars) {
+            let mut weighted = H_scalar;
+            weighted.mul_assign(&second_weight);
+            acc.add_assign(&weighted);
+        }
+        Ok(())
+    }
+
+    /// Add a proof that a <= b to the batch. The arguments are as for
+    /// [verify_less_than_or_equal].
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_less_than_or_equal<R: Rng>(
+        &mut self,
+        transcript: &mut RandomOracle,
+        n: u8,
+        commitment_a: &Commitment<C>,
+        commitment_b: &Commitment<C>,
+        proof: &RangeProof<C>,
+        key: &CommitmentKey<C>,
+        csprng: &mut R,
+    ) -> Result<(), VerificationError> {
+        let commitment = Commitment(commitment_b.0.minus_point(&commitment_a.0));
+        self.add(
+            transcript,
+            n,
+            &[commitment, *commitment_a],
+            proof,
+            key,
+            csprng,
+        )
+    }
+
+    fn add_terms(&mut self, points: Vec<C>, scalars: Vec<C::Scalar>, weight: &C::Scalar) {
+        self.points.extend(points);
+        self.scalars.extend(scalars.into_iter().map(|mut scalar| {
+            scalar.mul_assign(weight);
+            scalar
+        }));
+    }
+
+    /// Check all the proofs that were added to the batch.
+    #[allow(non_snake_case)]
+    pub fn verify(self) -> Result<(), VerificationError> {
+        let (G, H): (Vec<_>, Vec<_>) = self
+            .gens
+            .G_H
+            .iter()
+            .take(self.G_scalars.len())
+            .cloned()
+            .unzip();
+        let sum = multiexp(&self.points, &self.scalars)
+            .plus_point(&multiexp(&G, &self.G_scalars))
+            .plus_point(&multiexp(&H, &self.H_scalars));
+        if sum.is_zero_point() {
+            Ok(())
+        } else {
+            Err(VerificationError::Batch)
+        }
+    }
+}
+
 /// For proving that a <= b for integers a,b
 /// It is assumed that a,b \in [0, 2^n)
 #[allow(clippy::too_many_arguments)]
@@ -670,6 +985,7 @@ pub fn verify_less_than_or_equal<C: Curve>(
 mod tests {
     use super::*;
     use pairing::bls12_381::G1;
+    use random_oracle::domains;
 
     /// This function produces a proof that will satisfy the verifier's first
     /// check, even if the values are not in the interval.
@@ -1338,6 +1654,67 @@ mod tests {
         ));
     }
 
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_batch_verification() {
+        let rng = &mut thread_rng();
+        let n = 32;
+        let m = 2;
+        let nm = usize::from(n) * usize::from(m);
+        let G_H = (0..nm)
+            .map(|_| (SomeCurve::generate(rng), SomeCurve::generate(rng)))
+            .collect();
+        let gens = Generators { G_H };
+        // The proofs use different commitment keys, as in encrypted transfers.
+        let keys: Vec<_> = (0..3)
+            .map(|_| CommitmentKey {
+                g: SomeCurve::generate(rng),
+                h: SomeCurve::generate(rng),
+            })
+            .collect();
+        let mut transcript = RandomOracle::domain(domains::ENCRYPTED_TRANSFER);
+        let mut proofs = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            let v_vec: Vec<u64> = vec![rng.gen::<u32>().into(), rng.gen::<u32>().into()];
+            let randomness: Vec<_> = v_vec.iter().map(|_| Randomness::generate(rng)).collect();
+            let commitments: Vec<_> = v_vec
+                .iter()
+                .zip(randomness.iter())
+                .map(|(v, r)| key.hide_worker(&SomeCurve::scalar_from_u64(*v), r))
+                .collect();
+            let proof = prove(&mut transcript, rng, n, m, &v_vec, &gens, key, &randomness)
+                .expect("Proving should succeed.");
+            proofs.push((commitments, proof));
+        }
+
+        // The proofs were produced in sequence with the same transcript, so they
+        // are added to the batch in the same order.
+        let verify_batch = |proofs: &[(Vec<Commitment<SomeCurve>>, RangeProof<SomeCurve>)],
+                            rng: &mut rngs::ThreadRng| {
+            let mut transcript = RandomOracle::domain(domains::ENCRYPTED_TRANSFER);
+            let mut batch = BatchVerifier::new(&gens);
+            for ((commitments, proof), key) in proofs.iter().zip(keys.iter()) {
+                batch.add(&mut transcript, n, commitments, proof, key, rng)?;
+            }
+            batch.verify()
+        };
+        assert_eq!(verify_batch(&proofs, rng), Ok(()));
+
+        // The proofs also verify one by one.
+        let mut transcript = RandomOracle::domain(domains::ENCRYPTED_TRANSFER);
+        for ((commitments, proof), key) in proofs.iter().zip(keys.iter()) {
+            assert_eq!(
+                verify_efficient(&mut transcript, n, commitments, proof, &gens, key),
+                Ok(())
+            );
+        }
+
+        // A single proof that does not match its commitments fails the batch.
+        let mut wrong = proofs.clone();
+        wrong[1].0.swap(0, 1);
+        assert_eq!(verify_batch(&wrong, rng), Err(VerificationError::Batch));
+    }
+
     #[allow(non_snake_case)]
     #[test]
     fn test_cheating_prover() {
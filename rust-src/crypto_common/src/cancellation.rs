@@ -0,0 +1,62 @@
+//! Cooperative cancellation of long-running operations, such as proof
+//! generation.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use thiserror::Error;
+
+/// A token that can be used to request cancellation of an operation. Clones
+/// of the token share the same state, so the token can be cancelled from a
+/// different thread than the one running the operation.
+///
+/// Cancellation is cooperative. Operations that accept a token check it
+/// between their phases and stop with [Cancelled] if it has been cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+/// The error returned by operations that were cancelled.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("The operation was cancelled.")]
+pub struct Cancelled;
+
+impl CancellationToken {
+    /// Construct a new token that is not cancelled.
+    pub fn new() -> Self { Self::default() }
+
+    /// Request cancellation of all operations using this token.
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::Release) }
+
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Acquire) }
+
+    /// Return `Err(Cancelled)` if cancellation has been requested. This is
+    /// intended to be used with the `?` operator between phases of an
+    /// operation.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_shared() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+        std::thread::spawn(move || token.cancel())
+            .join()
+            .expect("Cancelling does not panic.");
+        assert!(clone.is_cancelled());
+        assert_eq!(clone.check(), Err(Cancelled));
+    }
+}
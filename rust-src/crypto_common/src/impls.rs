@@ -248,16 +248,26 @@ impl<T: Deserial> Deserial for Rc<T> {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> { Ok(Rc::new(source.get()?)) }
 }
 
+/// Read the tag of an optional value, and return whether the value is present.
+/// This is strict, and only accepts `0` or `1` tags.
+pub fn deserial_option_tag<X: ReadBytesExt>(source: &mut X) -> ParseResult<bool> {
+    let l: u8 = source.get()?;
+    if l == 0 {
+        Ok(false)
+    } else if l == 1 {
+        Ok(true)
+    } else {
+        bail!("Unknown variant {}", l)
+    }
+}
+
 /// Deserialization is strict. It only accepts `0` or `1` tags.
 impl<T: Deserial> Deserial for Option<T> {
     fn deserial<X: ReadBytesExt>(source: &mut X) -> ParseResult<Self> {
-        let l: u8 = source.get()?;
-        if l == 0 {
-            Ok(None)
-        } else if l == 1 {
+        if deserial_option_tag(source)? {
             Ok(Some(source.get()?))
         } else {
-            bail!("Unknown variant {}", l)
+            Ok(None)
         }
     }
 }
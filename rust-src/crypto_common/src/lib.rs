@@ -1,13 +1,18 @@
 //! Common types and operations used throughout the Concordium chain
 //! development.
+mod cancellation;
 mod helpers;
 mod impls;
 mod serde_impls;
 mod serialize;
+mod streaming;
 pub mod types;
 mod version;
+mod zeroize;
 
-pub use crate::{helpers::*, impls::*, serialize::*, version::*};
+pub use crate::{
+    cancellation::*, helpers::*, impls::*, serialize::*, streaming::*, version::*, zeroize::*,
+};
 
 // Reexport for ease of use.
 pub use byteorder::{ReadBytesExt, WriteBytesExt};
@@ -41,5 +46,10 @@ pub use std::os::raw::c_char;
 /// formats used by Concordium.
 pub mod encryption;
 
+#[cfg(feature = "parallel")]
+/// Module that allows controlling the number of threads used by parallel
+/// computations.
+pub mod parallel;
+
 /// Reexport for ease of use.
 pub use crypto_common_derive as derive;
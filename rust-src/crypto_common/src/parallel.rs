@@ -0,0 +1,97 @@
+//! Control over the thread pool used by the parallel code paths.
+//!
+//! By default parallel computations run on rayon's global thread pool, which
+//! uses one thread per core. After a call to [set_thread_count] they instead
+//! run on a dedicated pool with the given number of threads. All code that
+//! uses rayon should run its parallel computations via [install] so that the
+//! setting is respected.
+use lazy_static::lazy_static;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{Arc, RwLock};
+
+lazy_static! {
+    /// The pool configured by [set_thread_count], if any.
+    static ref THREAD_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+}
+
+/// Set the number of threads used by parallel computations. If `n` is 0 the
+/// default, one thread per core, is restored. Computations that are already
+/// running are not affected.
+pub fn set_thread_count(n: usize) -> Result<(), rayon::ThreadPoolBuildError> {
+    let pool = if n == 0 {
+        None
+    } else {
+        Some(Arc::new(ThreadPoolBuilder::new().num_threads(n).build()?))
+    };
+    // The lock is only poisoned if a thread panicked while holding it, and we
+    // never panic while holding it.
+    *THREAD_POOL
+        .write()
+        .expect("Thread pool lock is not poisoned.") = pool;
+    Ok(())
+}
+
+/// Return the number of threads that parallel computations will use.
+pub fn thread_count() -> usize {
+    match THREAD_POOL
+        .read()
+        .expect("Thread pool lock is not poisoned.")
+        .as_ref()
+    {
+        Some(pool) => pool.current_num_threads(),
+        None => rayon::current_num_threads(),
+    }
+}
+
+/// Run the given closure on the configured thread pool. Any parallel
+/// iterators used by the closure will use the threads of that pool.
+pub fn install<R: Send, F: FnOnce() -> R + Send>(f: F) -> R {
+    // Clone the pool so that the lock is not held while running the closure.
+    let pool = THREAD_POOL
+        .read()
+        .expect("Thread pool lock is not poisoned.")
+        .clone();
+    match pool {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
+}
+
+/// Run the given closure in the background on the configured thread pool.
+/// The closure is queued until a thread of the pool is available.
+pub fn spawn<F: FnOnce() + Send + 'static>(f: F) {
+    let pool = THREAD_POOL
+        .read()
+        .expect("Thread pool lock is not poisoned.")
+        .clone();
+    match pool {
+        Some(pool) => pool.spawn(f),
+        None => rayon::spawn(f),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_thread_count() {
+        set_thread_count(2).expect("Can create a pool with 2 threads.");
+        assert_eq!(thread_count(), 2);
+        let sum: u64 = install(|| {
+            assert_eq!(rayon::current_num_threads(), 2);
+            (0..1000u64).into_par_iter().sum()
+        });
+        assert_eq!(sum, 499500);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        spawn(move || {
+            sender
+                .send(rayon::current_num_threads())
+                .expect("The receiver is alive.")
+        });
+        assert_eq!(receiver.recv(), Ok(2), "Spawned tasks run on the pool.");
+        set_thread_count(0).expect("Resetting the pool succeeds.");
+        assert_eq!(thread_count(), rayon::current_num_threads());
+    }
+}
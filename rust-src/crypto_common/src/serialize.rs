@@ -109,13 +109,10 @@ impl<T: Deserial, S: Deserial, U: Deserial> Deserial for (T, S, U) {
 }
 
 /// Read a string of given size.
-/// NB: Be aware that this allocates a buffer of the given length, and so this
-/// must only be used when the size is bounded, otherwise it will lead to a
-/// memory allocation failure, and panic.
+/// The buffer grows as the bytes are read, so a large claimed length does not
+/// by itself lead to a large allocation, see [deserial_bytes].
 pub fn deserial_string<R: ReadBytesExt>(reader: &mut R, l: usize) -> ParseResult<String> {
-    let mut svec = vec![0; l];
-    reader.read_exact(&mut svec)?;
-    Ok(String::from_utf8(svec)?)
+    Ok(String::from_utf8(deserial_bytes(reader, l)?)?)
 }
 
 /// Write a string directly to the provided sink (without encoding its length).
@@ -138,13 +135,45 @@ pub fn deserial_vector_no_length<R: ReadBytesExt, T: Deserial>(
     Ok(vec)
 }
 
+/// Fail if the length of a collection read from the input exceeds the given
+/// bound. This is used by the derived [Deserial] instances for fields with a
+/// `#[max_size = n]` attribute.
+pub fn check_max_size(len: usize, max_size: usize) -> ParseResult<()> {
+    if len > max_size {
+        bail!(
+            "Length {} exceeds the maximum allowed length {}.",
+            len,
+            max_size
+        )
+    }
+    Ok(())
+}
+
+/// As [deserial_vector_no_length], but fail before reading any elements if
+/// the length exceeds `max_size`.
+pub fn deserial_vector_bounded<R: ReadBytesExt, T: Deserial>(
+    reader: &mut R,
+    len: usize,
+    max_size: usize,
+) -> ParseResult<Vec<T>> {
+    check_max_size(len, max_size)?;
+    deserial_vector_no_length(reader, len)
+}
+
 /// Read a vector of the given size.
-/// NB: Be aware that this allocates a buffer of the given length, and so this
-/// must only be used when the size is bounded, otherwise it will lead to a
-/// memory allocation failure, and panic.
+/// At most a safe size is pre-allocated, and the buffer grows as the bytes are
+/// read, so that a large claimed length does not lead to a large allocation
+/// unless the input actually contains that many bytes.
 pub fn deserial_bytes<R: ReadBytesExt>(reader: &mut R, l: usize) -> ParseResult<Vec<u8>> {
-    let mut svec = vec![0; l];
-    reader.read_exact(&mut svec)?;
+    let mut svec = Vec::with_capacity(cmp::min(l, MAX_PREALLOCATED_CAPACITY));
+    reader.by_ref().take(l as u64).read_to_end(&mut svec)?;
+    if svec.len() != l {
+        bail!(
+            "Expected {} bytes, but the input ended after {}.",
+            l,
+            svec.len()
+        )
+    }
     Ok(svec)
 }
 
@@ -302,25 +331,38 @@ pub fn deserial_map_no_length<R: ReadBytesExt, K: Deserial + Ord + Copy, V: Dese
     len: usize,
 ) -> ParseResult<BTreeMap<K, V>> {
     let mut out = BTreeMap::new();
+    deserial_map_no_length_with(source, len, |k, v| {
+        out.insert(k, v);
+        Ok(())
+    })?;
+    Ok(out)
+}
+
+/// Analogous to [deserial_map_no_length], but instead of collecting the
+/// entries in a map the callback is invoked on each entry as soon as it is
+/// parsed. This is intended for very large maps, e.g., the accounts of a
+/// genesis block, where only a summary of the entries is needed, so that the
+/// memory use does not grow with the size of the map. Parsing stops with the
+/// first error returned by the callback.
+/// NB: This ensures that all keys are in strictly increasing order.
+pub fn deserial_map_no_length_with<R: ReadBytesExt, K: Deserial + Ord + Copy, V: Deserial>(
+    source: &mut R,
+    len: usize,
+    mut f: impl FnMut(K, V) -> ParseResult<()>,
+) -> ParseResult<()> {
     let mut x = None;
     for _ in 0..len {
         let k = source.get()?;
         let v = source.get()?;
-        match x {
-            None => {
-                out.insert(k, v);
-            }
-            Some(kk) => {
-                if k > kk {
-                    out.insert(k, v);
-                } else {
-                    bail!("Keys not in order.")
-                }
+        if let Some(kk) = x {
+            if k <= kk {
+                bail!("Keys not in order.")
             }
         }
         x = Some(k);
+        f(k, v)?;
     }
-    Ok(out)
+    Ok(())
 }
 
 /// Analogous to [serial_map_no_length], but for sets.
@@ -688,3 +730,351 @@ pub fn base16_ignore_length_decode<'de, D: Deserializer<'de>, T: Deserial>(
     }
     des.deserialize_str(Base16IgnoreLengthVisitor(Default::default()))
 }
+
+/// Encode each element of the slice as in [base16_encode], producing a JSON
+/// list of hex strings. This is intended for collections of group elements
+/// and scalars, which do not have a structured JSON representation.
+pub fn base16_encode_vec<S: Serializer, T: Serial>(v: &[T], ser: S) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(v.iter().map(base16_encode_string))
+}
+
+/// Dual to [base16_encode_vec].
+pub fn base16_decode_vec<'de, D: Deserializer<'de>, T: Deserial>(
+    des: D,
+) -> Result<Vec<T>, D::Error> {
+    let strings: Vec<String> = serde::Deserialize::deserialize(des)?;
+    strings
+        .iter()
+        .map(|s| base16_decode_string(s).map_err(de::Error::custom))
+        .collect()
+}
+
+/// A value that is encoded in JSON as in [base16_encode]. This is used to
+/// encode the components of tuples and nested collections individually.
+struct Base16<T>(T);
+
+impl<T: Serial> serde::Serialize for Base16<T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        base16_encode(&self.0, ser)
+    }
+}
+
+impl<'de, T: Deserial> serde::Deserialize<'de> for Base16<T> {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        base16_decode(des).map(Base16)
+    }
+}
+
+/// Encode each component of the pair as in [base16_encode], producing a JSON
+/// list of two hex strings.
+pub fn base16_encode_pair<S: Serializer, A: Serial, B: Serial>(
+    v: &(A, B),
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&(Base16(&v.0), Base16(&v.1)), ser)
+}
+
+/// Dual to [base16_encode_pair].
+pub fn base16_decode_pair<'de, D: Deserializer<'de>, A: Deserial, B: Deserial>(
+    des: D,
+) -> Result<(A, B), D::Error> {
+    let (Base16(a), Base16(b)) = serde::Deserialize::deserialize(des)?;
+    Ok((a, b))
+}
+
+/// Encode each component of the triple as in [base16_encode], producing a
+/// JSON list of three hex strings.
+pub fn base16_encode_triple<S: Serializer, A: Serial, B: Serial, C: Serial>(
+    v: &(A, B, C),
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&(Base16(&v.0), Base16(&v.1), Base16(&v.2)), ser)
+}
+
+/// Dual to [base16_encode_triple].
+pub fn base16_decode_triple<'de, D: Deserializer<'de>, A: Deserial, B: Deserial, C: Deserial>(
+    des: D,
+) -> Result<(A, B, C), D::Error> {
+    let (Base16(a), Base16(b), Base16(c)) = serde::Deserialize::deserialize(des)?;
+    Ok((a, b, c))
+}
+
+/// Encode each element of the array as in [base16_encode], producing a JSON
+/// list of `N` hex strings.
+pub fn base16_encode_array<S: Serializer, T: Serial, const N: usize>(
+    v: &[T; N],
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    base16_encode_vec(v, ser)
+}
+
+/// Dual to [base16_encode_array]. Fails if the list does not have exactly `N`
+/// elements.
+pub fn base16_decode_array<'de, D: Deserializer<'de>, T: Deserial, const N: usize>(
+    des: D,
+) -> Result<[T; N], D::Error> {
+    let v: Vec<T> = base16_decode_vec(des)?;
+    let len = v.len();
+    <[T; N]>::try_from(v)
+        .map_err(|_| de::Error::custom(format!("Expected {} elements, got {}.", N, len)))
+}
+
+/// Encode each pair in the slice as in [base16_encode_pair], producing a JSON
+/// list of lists of two hex strings.
+pub fn base16_encode_vec_pair<S: Serializer, A: Serial, B: Serial>(
+    v: &[(A, B)],
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(v.iter().map(|(a, b)| (Base16(a), Base16(b))))
+}
+
+/// Dual to [base16_encode_vec_pair].
+pub fn base16_decode_vec_pair<'de, D: Deserializer<'de>, A: Deserial, B: Deserial>(
+    des: D,
+) -> Result<Vec<(A, B)>, D::Error> {
+    let pairs: Vec<(Base16<A>, Base16<B>)> = serde::Deserialize::deserialize(des)?;
+    Ok(pairs.into_iter().map(|(a, b)| (a.0, b.0)).collect())
+}
+
+/// Encode each inner vector as in [base16_encode_vec], producing a JSON list
+/// of lists of hex strings.
+pub fn base16_encode_vec_vec<S: Serializer, T: Serial>(
+    v: &[Vec<T>],
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(
+        v.iter()
+            .map(|inner| inner.iter().map(Base16).collect::<Vec<_>>()),
+    )
+}
+
+/// Dual to [base16_encode_vec_vec].
+pub fn base16_decode_vec_vec<'de, D: Deserializer<'de>, T: Deserial>(
+    des: D,
+) -> Result<Vec<Vec<T>>, D::Error> {
+    let vs: Vec<Vec<Base16<T>>> = serde::Deserialize::deserialize(des)?;
+    Ok(vs
+        .into_iter()
+        .map(|inner| inner.into_iter().map(|x| x.0).collect())
+        .collect())
+}
+
+/// An alternative serde representation of proofs and their witnesses, in
+/// which they are encoded field by field, with group elements and scalars as
+/// hex strings. This is separate from the [serde::Serialize] and
+/// [serde::Deserialize] instances of the types, which remain the format in
+/// which proofs are exchanged. Use [Structured] to (de)serialize a value in
+/// this representation.
+pub trait StructuredSerde: Sized {
+    fn serialize_structured<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error>;
+
+    fn deserialize_structured<'de, D: Deserializer<'de>>(des: D) -> Result<Self, D::Error>;
+}
+
+/// A wrapper whose serde instances are the [StructuredSerde] representation of
+/// the wrapped value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Structured<T>(pub T);
+
+impl<T: StructuredSerde> serde::Serialize for Structured<T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_structured(ser)
+    }
+}
+
+impl<'de, T: StructuredSerde> serde::Deserialize<'de> for Structured<T> {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        T::deserialize_structured(des).map(Structured)
+    }
+}
+
+/// Encode the value in its [StructuredSerde] representation. This is intended
+/// to be used with `serialize_with` for fields that are themselves proofs.
+pub fn structured_encode<S: Serializer, T: StructuredSerde>(
+    v: &T,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    v.serialize_structured(ser)
+}
+
+/// Dual to [structured_encode].
+pub fn structured_decode<'de, D: Deserializer<'de>, T: StructuredSerde>(
+    des: D,
+) -> Result<T, D::Error> {
+    T::deserialize_structured(des)
+}
+
+/// A borrowed value that is encoded in its [StructuredSerde] representation.
+struct StructuredRef<'a, T>(&'a T);
+
+impl<'a, T: StructuredSerde> serde::Serialize for StructuredRef<'a, T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_structured(ser)
+    }
+}
+
+/// Encode each element of the slice in its [StructuredSerde] representation,
+/// producing a JSON list.
+pub fn structured_encode_vec<S: Serializer, T: StructuredSerde>(
+    v: &[T],
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    ser.collect_seq(v.iter().map(StructuredRef))
+}
+
+/// Dual to [structured_encode_vec].
+pub fn structured_decode_vec<'de, D: Deserializer<'de>, T: StructuredSerde>(
+    des: D,
+) -> Result<Vec<T>, D::Error> {
+    let v: Vec<Structured<T>> = serde::Deserialize::deserialize(des)?;
+    Ok(v.into_iter().map(|x| x.0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserial_map_streaming() {
+        let mut map = BTreeMap::new();
+        for i in 0u32..100 {
+            map.insert(i, u64::from(i) * 3);
+        }
+        let mut bytes = Vec::new();
+        serial_map_no_length(&map, &mut bytes);
+
+        let mut sum = 0u64;
+        let mut count = 0;
+        deserial_map_no_length_with(&mut Cursor::new(&bytes), map.len(), |_k: u32, v: u64| {
+            sum += v;
+            count += 1;
+            Ok(())
+        })
+        .expect("Deserialization should succeed.");
+        assert_eq!(count, map.len());
+        assert_eq!(sum, map.values().sum::<u64>());
+        let parsed: BTreeMap<u32, u64> =
+            deserial_map_no_length(&mut Cursor::new(&bytes), map.len()).unwrap();
+        assert_eq!(parsed, map);
+
+        // Errors from the callback stop parsing.
+        let mut seen = 0;
+        let res =
+            deserial_map_no_length_with(&mut Cursor::new(&bytes), map.len(), |k: u32, _v: u64| {
+                seen += 1;
+                anyhow::ensure!(k < 10, "Stop.");
+                Ok(())
+            });
+        assert!(res.is_err());
+        assert_eq!(seen, 11);
+
+        // Keys out of order are rejected.
+        let mut bytes = Vec::new();
+        bytes.put(&2u32);
+        bytes.put(&0u64);
+        bytes.put(&1u32);
+        bytes.put(&0u64);
+        assert!(deserial_map_no_length_with(
+            &mut Cursor::new(&bytes),
+            2,
+            |_k: u32, _v: u64| Ok(())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_deserial_bounded() {
+        let v: Vec<u64> = (0..10).collect();
+        let mut bytes = Vec::new();
+        serial_vector_no_length(&v, &mut bytes);
+        let parsed: Vec<u64> = deserial_vector_bounded(&mut Cursor::new(&bytes), v.len(), 10)
+            .expect("The length is within the bound.");
+        assert_eq!(parsed, v);
+        assert!(deserial_vector_bounded::<_, u64>(&mut Cursor::new(&bytes), v.len(), 9).is_err());
+
+        // A length far beyond the input fails without allocating it.
+        assert_eq!(
+            deserial_bytes(&mut Cursor::new(&bytes), bytes.len()).expect("Enough bytes."),
+            bytes
+        );
+        assert!(deserial_bytes(&mut Cursor::new(&bytes), usize::MAX >> 1).is_err());
+        assert!(deserial_string(&mut Cursor::new(&bytes), 1 << 40).is_err());
+    }
+
+    mod derived {
+        // The derived instances refer to the helpers via `crypto_common::`.
+        use crate as crypto_common;
+        use crate::{Buffer, Deserial, ParseResult, ReadBytesExt, Serial};
+
+        #[derive(Debug, PartialEq, crypto_common_derive::Serialize)]
+        pub(super) struct Bounded {
+            #[size_length = 4]
+            #[max_size = 3]
+            pub(super) values: Vec<u8>,
+            #[string_size_length = 2]
+            #[max_size = 5]
+            pub(super) name:   String,
+        }
+    }
+
+    #[test]
+    fn test_max_size_derive() {
+        let bounded = derived::Bounded {
+            values: vec![1, 2, 3],
+            name:   "12345".to_owned(),
+        };
+        let bytes = to_bytes(&bounded);
+        let parsed: derived::Bounded =
+            from_bytes(&mut Cursor::new(&bytes)).expect("Within bounds.");
+        assert_eq!(parsed, bounded);
+
+        // A vector and a string one longer than their bound are rejected.
+        let too_long = derived::Bounded {
+            values: vec![1, 2, 3, 4],
+            name:   "12345".to_owned(),
+        };
+        assert!(from_bytes::<derived::Bounded, _>(&mut Cursor::new(to_bytes(&too_long))).is_err());
+        let too_long = derived::Bounded {
+            values: vec![1, 2, 3],
+            name:   "123456".to_owned(),
+        };
+        assert!(from_bytes::<derived::Bounded, _>(&mut Cursor::new(to_bytes(&too_long))).is_err());
+
+        // A huge claimed length is rejected before anything is read.
+        let mut bytes = Vec::new();
+        bytes.put(&u32::MAX);
+        assert!(from_bytes::<derived::Bounded, _>(&mut Cursor::new(&bytes)).is_err());
+    }
+
+    #[test]
+    fn test_base16_components() {
+        let pair = (1u8, 2u16);
+        let json = base16_encode_pair(&pair, serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!(["01", "0002"]));
+        assert_eq!(base16_decode_pair::<_, u8, u16>(json).unwrap(), pair);
+
+        let triple = (1u8, 2u8, 3u8);
+        let json = base16_encode_triple(&triple, serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!(["01", "02", "03"]));
+        assert_eq!(base16_decode_triple::<_, u8, u8, u8>(json).unwrap(), triple);
+
+        let array = [1u8, 2u8];
+        let json = base16_encode_array(&array, serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!(["01", "02"]));
+        assert_eq!(
+            base16_decode_array::<_, u8, 2>(json.clone()).unwrap(),
+            array
+        );
+        assert!(base16_decode_array::<_, u8, 3>(json).is_err());
+
+        let pairs = vec![(1u8, 2u8), (3u8, 4u8)];
+        let json = base16_encode_vec_pair(&pairs, serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!([["01", "02"], ["03", "04"]]));
+        assert_eq!(base16_decode_vec_pair::<_, u8, u8>(json).unwrap(), pairs);
+
+        let nested = vec![vec![1u8], vec![], vec![2u8, 3u8]];
+        let json = base16_encode_vec_vec(&nested, serde_json::value::Serializer).unwrap();
+        assert_eq!(json, serde_json::json!([["01"], [], ["02", "03"]]));
+        assert_eq!(base16_decode_vec_vec::<_, u8>(json).unwrap(), nested);
+    }
+}
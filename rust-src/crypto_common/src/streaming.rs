@@ -0,0 +1,259 @@
+//! Serialization directly into fixed buffers and writers.
+//!
+//! [to_bytes](crate::to_bytes) collects the serialization of a value in a
+//! vector. For large values, such as the tables for computing discrete
+//! logarithms, this needs as much memory again as the value itself, which
+//! matters on mobile devices. [SerialSized] gives the length of the
+//! serialization up front, so that a buffer of the right size, e.g., a memory
+//! mapped file, can be allocated, and [serial_into_slice] and
+//! [serial_to_writer] write the serialization without an intermediate vector.
+use crate::{Buffer, Serial};
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// A buffer that discards the bytes written to it and only counts them.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ByteCounter {
+    count: usize,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Buffer for ByteCounter {
+    type Result = usize;
+
+    fn start() -> Self { Self::default() }
+
+    fn result(self) -> Self::Result { self.count }
+}
+
+/// The number of bytes of the serialization of the value, computed by
+/// serializing it into a [ByteCounter].
+pub fn serial_length_of<A: Serial + ?Sized>(x: &A) -> usize {
+    let mut counter = ByteCounter::start();
+    x.serial(&mut counter);
+    counter.result()
+}
+
+/// Types whose serialized length can be determined without serializing them
+/// into memory.
+pub trait SerialSized: Serial {
+    /// The number of bytes written by [Serial::serial]. The default
+    /// implementation serializes the value into a [ByteCounter], which does
+    /// not allocate, but types whose length is cheap to compute should
+    /// override it.
+    fn serial_length(&self) -> usize { serial_length_of(self) }
+}
+
+impl SerialSized for u8 {
+    fn serial_length(&self) -> usize { 1 }
+}
+
+impl SerialSized for u16 {
+    fn serial_length(&self) -> usize { 2 }
+}
+
+impl SerialSized for u32 {
+    fn serial_length(&self) -> usize { 4 }
+}
+
+impl SerialSized for u64 {
+    fn serial_length(&self) -> usize { 8 }
+}
+
+impl SerialSized for i64 {
+    fn serial_length(&self) -> usize { 8 }
+}
+
+impl SerialSized for bool {
+    fn serial_length(&self) -> usize { 1 }
+}
+
+/// Vectors are serialized with their length in 8 bytes.
+impl<T: SerialSized> SerialSized for Vec<T> {
+    fn serial_length(&self) -> usize { 8 + self.iter().map(T::serial_length).sum::<usize>() }
+}
+
+impl<'a, T: SerialSized> SerialSized for &'a T {
+    fn serial_length(&self) -> usize { T::serial_length(self) }
+}
+
+/// A buffer writing into a fixed slice of memory. Writing more bytes than
+/// fit in the slice fails, and [Serial::serial] panics in that case, so the
+/// length should be checked first, as [serial_into_slice] does.
+#[derive(Debug)]
+pub struct SliceBuffer<'a> {
+    buf:     &'a mut [u8],
+    written: usize,
+}
+
+impl<'a> SliceBuffer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self { SliceBuffer { buf, written: 0 } }
+}
+
+impl<'a> Write for SliceBuffer<'a> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let end = self.written + data.len();
+        if end > self.buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "Data does not fit in the buffer.",
+            ));
+        }
+        self.buf[self.written..end].copy_from_slice(data);
+        self.written = end;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl<'a> Buffer for SliceBuffer<'a> {
+    /// The number of bytes written.
+    type Result = usize;
+
+    /// A buffer of length 0.
+    fn start() -> Self { SliceBuffer::new(&mut []) }
+
+    fn result(self) -> Self::Result { self.written }
+}
+
+/// The error returned when a value does not fit in the given buffer.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("The serialization needs {required} bytes, but the buffer only has {available}.")]
+pub struct BufferTooSmall {
+    pub required:  usize,
+    pub available: usize,
+}
+
+/// Serialize the value into the start of the buffer and return the number of
+/// bytes written, or fail without writing anything if the buffer is too
+/// small.
+pub fn serial_into_slice<A: SerialSized + ?Sized>(
+    x: &A,
+    buf: &mut [u8],
+) -> Result<usize, BufferTooSmall> {
+    let required = x.serial_length();
+    if required > buf.len() {
+        return Err(BufferTooSmall {
+            required,
+            available: buf.len(),
+        });
+    }
+    let mut out = SliceBuffer::new(&mut buf[..required]);
+    x.serial(&mut out);
+    Ok(out.result())
+}
+
+/// A buffer that passes the bytes on to a writer, e.g., a file. [Serial]
+/// cannot report errors of the buffer, so the first error of the writer is
+/// recorded, and the bytes after it are discarded. The error is returned by
+/// [Buffer::result].
+pub struct WriterBuffer<'a, W> {
+    writer:  Option<&'a mut W>,
+    error:   Option<io::Error>,
+    written: usize,
+}
+
+impl<'a, W: Write> WriterBuffer<'a, W> {
+    pub fn new(writer: &'a mut W) -> Self {
+        WriterBuffer {
+            writer:  Some(writer),
+            error:   None,
+            written: 0,
+        }
+    }
+}
+
+impl<'a, W: Write> Write for WriterBuffer<'a, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.error.is_none() {
+            if let Some(writer) = self.writer.as_mut() {
+                if let Err(e) = writer.write_all(data) {
+                    self.error = Some(e);
+                    return Ok(data.len());
+                }
+            }
+            self.written += data.len();
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, W: Write> Buffer for WriterBuffer<'a, W> {
+    /// The number of bytes written, or the first error of the writer.
+    type Result = io::Result<usize>;
+
+    /// A buffer without a writer, which discards all bytes.
+    fn start() -> Self {
+        WriterBuffer {
+            writer:  None,
+            error:   None,
+            written: 0,
+        }
+    }
+
+    fn result(self) -> Self::Result {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.written),
+        }
+    }
+}
+
+/// Serialize the value to the writer and return the number of bytes written.
+/// The writer is not flushed, and it should be buffered, e.g., by
+/// [BufWriter](std::io::BufWriter), since the value is written in many small
+/// pieces.
+pub fn serial_to_writer<A: Serial + ?Sized, W: Write>(x: &A, writer: &mut W) -> io::Result<usize> {
+    let mut out = WriterBuffer::new(writer);
+    x.serial(&mut out);
+    out.result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_bytes;
+
+    #[test]
+    fn test_serial_into_buffers() {
+        let value = (vec![1u64, 2, 3], vec![true, false]);
+        let bytes = to_bytes(&value);
+        assert_eq!(serial_length_of(&value), bytes.len());
+        assert_eq!(value.0.serial_length(), 8 + 3 * 8);
+
+        let mut buf = [0xffu8; 64];
+        let written = serial_into_slice(&value.0, &mut buf).expect("Value should fit.");
+        assert_eq!(&buf[..written], &to_bytes(&value.0)[..]);
+        assert_eq!(buf[written], 0xff);
+        assert_eq!(
+            serial_into_slice(&value.0, &mut buf[..31]),
+            Err(BufferTooSmall {
+                required:  32,
+                available: 31,
+            })
+        );
+
+        let mut out = Vec::new();
+        assert_eq!(serial_to_writer(&value, &mut out).ok(), Some(bytes.len()));
+        assert_eq!(out, bytes);
+        // Errors of the writer are returned.
+        let mut small = [0u8; 4];
+        assert!(serial_to_writer(&value, &mut &mut small[..]).is_err());
+    }
+}
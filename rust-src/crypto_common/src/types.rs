@@ -1,6 +1,8 @@
 //! Common types needed in concordium.
 
-use crate::{Buffer, Deserial, Get, ParseResult, SerdeDeserialize, SerdeSerialize, Serial};
+use crate::{
+    Buffer, Deserial, Get, ParseResult, SerdeDeserialize, SerdeSerialize, Serial, Zeroize,
+};
 use byteorder::ReadBytesExt;
 use crypto_common_derive::Serialize;
 use derive_more::{Display, From, FromStr, Into};
@@ -439,6 +441,11 @@ pub struct KeyPair {
     pub public: ed25519_dalek::PublicKey,
 }
 
+/// Only the secret key is overwritten, the public key is not secret.
+impl Zeroize for KeyPair {
+    fn zeroize(&mut self) { self.secret.zeroize() }
+}
+
 impl KeyPair {
     pub fn generate<R: rand::CryptoRng + rand::Rng>(rng: &mut R) -> Self {
         Self::from(ed25519_dalek::Keypair::generate(rng))
@@ -474,6 +481,211 @@ impl From<KeyPair> for ed25519_dalek::Keypair {
     }
 }
 
+/// Maximum length of the name of a contract function, i.e., an init or receive
+/// name. This must match the limit of the node.
+pub const MAX_FUNC_NAME_SIZE: usize = 100;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error)]
+/// Reasons why a string is not a valid init or receive name.
+pub enum ContractNameError {
+    #[error(
+        "The name is {0} bytes long, but at most {} are allowed.",
+        MAX_FUNC_NAME_SIZE
+    )]
+    TooLong(usize),
+    #[error("The name can only contain ASCII alphanumeric and punctuation characters.")]
+    InvalidCharacters,
+    #[error("An init name must start with 'init_'.")]
+    MissingInitPrefix,
+    #[error("An init name cannot contain '.'.")]
+    ContainsDot,
+    #[error("A receive name must be of the form 'contract.function'.")]
+    MissingDot,
+}
+
+/// Check the rules that are common to init and receive names.
+fn check_function_name(name: &str) -> Result<(), ContractNameError> {
+    if name.len() > MAX_FUNC_NAME_SIZE {
+        return Err(ContractNameError::TooLong(name.len()));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c.is_ascii_punctuation())
+    {
+        return Err(ContractNameError::InvalidCharacters);
+    }
+    Ok(())
+}
+
+/// Serialize a name as its length in 2 bytes followed by the bytes.
+fn serial_function_name<B: Buffer>(name: &str, out: &mut B) {
+    (name.len() as u16).serial(out);
+    out.write_all(name.as_bytes())
+        .expect("Writing to buffer should succeed.");
+}
+
+/// Deserialize a name serialized by [serial_function_name]. The length is
+/// checked before allocating.
+fn deserial_function_name<R: ReadBytesExt>(source: &mut R) -> ParseResult<String> {
+    let len: u16 = source.get()?;
+    anyhow::ensure!(
+        usize::from(len) <= MAX_FUNC_NAME_SIZE,
+        "Function name of {} bytes is too long.",
+        len
+    );
+    crate::deserial_string(source, usize::from(len))
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
+/// The name of the init function of a contract, e.g., `init_counter`. It is
+/// at most [MAX_FUNC_NAME_SIZE] ASCII alphanumeric or punctuation characters,
+/// starts with `init_`, and does not contain `.`.
+pub struct InitName {
+    name: String,
+}
+
+impl InitName {
+    /// Check that the string is a valid init name.
+    pub fn new(name: String) -> Result<Self, ContractNameError> {
+        check_function_name(&name)?;
+        if !name.starts_with("init_") {
+            return Err(ContractNameError::MissingInitPrefix);
+        }
+        if name.contains('.') {
+            return Err(ContractNameError::ContainsDot);
+        }
+        Ok(InitName { name })
+    }
+
+    pub fn as_str(&self) -> &str { &self.name }
+
+    /// The name of the contract, i.e., the name without the `init_` prefix.
+    pub fn contract_name(&self) -> &str { &self.name[5..] }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
+/// The name of a receive function of a contract, of the form
+/// `contract.function`, e.g., `counter.increment`. It is at most
+/// [MAX_FUNC_NAME_SIZE] ASCII alphanumeric or punctuation characters.
+pub struct ReceiveName {
+    name: String,
+}
+
+impl ReceiveName {
+    /// Check that the string is a valid receive name.
+    pub fn new(name: String) -> Result<Self, ContractNameError> {
+        check_function_name(&name)?;
+        if !name.contains('.') {
+            return Err(ContractNameError::MissingDot);
+        }
+        Ok(ReceiveName { name })
+    }
+
+    pub fn as_str(&self) -> &str { &self.name }
+
+    /// The name of the contract, i.e., the part before the first `.`.
+    pub fn contract_name(&self) -> &str { self.name.split('.').next().unwrap_or_default() }
+
+    /// The name of the function, i.e., the part after the first `.`.
+    pub fn entrypoint_name(&self) -> &str {
+        match self.name.find('.') {
+            Some(i) => &self.name[i + 1..],
+            None => "",
+        }
+    }
+}
+
+/// Implement string conversions, serialization and JSON serialization as a
+/// string for a contract function name type.
+macro_rules! function_name_impls {
+    ($name:ident) => {
+        impl std::convert::TryFrom<String> for $name {
+            type Error = ContractNameError;
+
+            fn try_from(name: String) -> Result<Self, Self::Error> { $name::new(name) }
+        }
+
+        impl FromStr for $name {
+            type Err = ContractNameError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> { $name::new(s.to_owned()) }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { self.name.fmt(f) }
+        }
+
+        impl Serial for $name {
+            fn serial<B: Buffer>(&self, out: &mut B) { serial_function_name(&self.name, out) }
+        }
+
+        impl Deserial for $name {
+            fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+                let name = deserial_function_name(source)?;
+                Ok($name::new(name)?)
+            }
+        }
+
+        impl SerdeSerialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.name)
+            }
+        }
+
+        impl<'de> SerdeDeserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                $name::new(s).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+function_name_impls!(InitName);
+function_name_impls!(ReceiveName);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash, Serialize)]
+/// Reference to a smart contract module deployed on the chain, i.e., the
+/// SHA256 hash of the module. In JSON it is hex encoded.
+pub struct ModuleReference {
+    pub bytes: [u8; 32],
+}
+
+impl FromStr for ModuleReference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)?;
+        anyhow::ensure!(
+            bytes.len() == 32,
+            "A module reference must be 32 bytes, but {} were given.",
+            bytes.len()
+        );
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&bytes);
+        Ok(ModuleReference { bytes: out })
+    }
+}
+
+impl std::fmt::Display for ModuleReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        hex::encode(&self.bytes).fmt(f)
+    }
+}
+
+impl SerdeSerialize for ModuleReference {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> SerdeDeserialize<'de> for ModuleReference {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,4 +828,48 @@ mod tests {
             "Parsed overflowing amount, but should not."
         );
     }
+
+    #[test]
+    fn contract_names() {
+        assert!(InitName::new("init_counter".into()).is_ok());
+        assert_eq!(
+            InitName::new("counter".into()),
+            Err(ContractNameError::MissingInitPrefix)
+        );
+        assert_eq!(
+            InitName::new("init_counter.increment".into()),
+            Err(ContractNameError::ContainsDot)
+        );
+        assert_eq!(
+            InitName::new("init_counter ".into()),
+            Err(ContractNameError::InvalidCharacters)
+        );
+        let receive = ReceiveName::new("counter.increment.by_one".into()).unwrap();
+        assert_eq!(receive.contract_name(), "counter");
+        assert_eq!(receive.entrypoint_name(), "increment.by_one");
+        assert_eq!(
+            ReceiveName::new("counter".into()),
+            Err(ContractNameError::MissingDot)
+        );
+        assert_eq!(
+            ReceiveName::new(format!("counter.{}", "a".repeat(MAX_FUNC_NAME_SIZE))),
+            Err(ContractNameError::TooLong(MAX_FUNC_NAME_SIZE + 8))
+        );
+
+        // Invalid names are rejected when parsing, both binary and JSON.
+        let bytes = crate::to_bytes(&receive);
+        let parsed: ReceiveName = crate::from_bytes(&mut std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(parsed, receive);
+        assert!(crate::from_bytes::<InitName, _>(&mut std::io::Cursor::new(&bytes)).is_err());
+        assert!(serde_json::from_str::<InitName>("\"init_counter\"").is_ok());
+        assert!(serde_json::from_str::<InitName>("\"counter\"").is_err());
+
+        let module_ref: ModuleReference = "ab".repeat(32).parse().unwrap();
+        let json = serde_json::to_string(&module_ref).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ModuleReference>(&json).unwrap(),
+            module_ref
+        );
+        assert!("ab".repeat(31).parse::<ModuleReference>().is_err());
+    }
 }
@@ -101,11 +101,107 @@ impl<T: Deserial> Deserial for Versioned<T> {
     }
 }
 
+/// Types whose binary serialization depends on the version it is serialized
+/// with. This is typically derived with `#[derive(SerialVersioned)]`. Fields
+/// marked with `#[since(version = n)]` are then only present in versions `n`
+/// and later, and are set to their default value when deserializing an
+/// earlier version. The latest version is the largest of these `n`, or 0.
+pub trait SerialVersioned: Sized {
+    /// The most recent version of the serialization.
+    const LATEST_VERSION: Version;
+
+    /// Serialize the value with the layout of the given version. Fields that
+    /// are not present in that version are omitted.
+    fn serial_version<B: Buffer>(&self, version: Version, out: &mut B);
+
+    /// Deserialize a value with the layout of the given version. The version
+    /// should not be newer than [LATEST_VERSION](Self::LATEST_VERSION).
+    fn deserial_version<R: ReadBytesExt>(version: Version, source: &mut R) -> ParseResult<Self>;
+}
+
+impl<T: SerialVersioned> Versioned<T> {
+    /// Wrap the value with the latest version of its serialization.
+    pub fn latest(value: T) -> Versioned<T> { Versioned::new(T::LATEST_VERSION, value) }
+
+    /// Serialize the version followed by the value with the layout of that
+    /// version.
+    pub fn serial_versioned<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.version);
+        self.value.serial_version(self.version, out);
+    }
+
+    /// Deserialize a version followed by a value with the layout of that
+    /// version. This fails if the version is newer than the latest version
+    /// of `T`.
+    pub fn deserial_versioned<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let version: Version = source.get()?;
+        if version.value > T::LATEST_VERSION.value {
+            anyhow::bail!(
+                "Unsupported version {}, the latest supported version is {}.",
+                version,
+                T::LATEST_VERSION
+            );
+        }
+        let value = T::deserial_version(version, source)?;
+        Ok(Versioned { version, value })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rand::{thread_rng, RngCore};
 
+    #[derive(Debug, Clone, PartialEq, Eq, crypto_common_derive::SerialVersioned)]
+    struct VersionedExample {
+        first:  u64,
+        #[since(version = 1)]
+        second: u32,
+        #[since(version = 2)]
+        third:  u8,
+    }
+
+    #[test]
+    fn test_serial_versioned() {
+        assert_eq!(VersionedExample::LATEST_VERSION, Version::from(2));
+        let example = VersionedExample {
+            first:  1,
+            second: 2,
+            third:  3,
+        };
+        // Each version only contains the fields since that version.
+        for (version, len) in [(0u32, 1 + 8), (1, 1 + 8 + 4), (2, 1 + 8 + 4 + 1)].iter() {
+            let mut out = Vec::new();
+            Versioned::new(Version::from(*version), example.clone()).serial_versioned(&mut out);
+            assert_eq!(
+                out.len(),
+                *len,
+                "Unexpected length for version {}.",
+                version
+            );
+        }
+
+        let mut out = Vec::new();
+        Versioned::new(Version::from(1), example).serial_versioned(&mut out);
+        let parsed =
+            Versioned::<VersionedExample>::deserial_versioned(&mut std::io::Cursor::new(&out))
+                .expect("Deserialization should succeed.");
+        assert_eq!(parsed.version, Version::from(1));
+        assert_eq!(parsed.value, VersionedExample {
+            first:  1,
+            second: 2,
+            third:  0,
+        });
+
+        // Versions newer than the latest one are rejected.
+        let mut out = Vec::new();
+        Version::from(3).serial(&mut out);
+        assert!(
+            Versioned::<VersionedExample>::deserial_versioned(&mut std::io::Cursor::new(&out))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_version_serialization_testvector() {
         let test = Version::from(1_700_794_014);
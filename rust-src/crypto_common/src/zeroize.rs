@@ -0,0 +1,99 @@
+//! Overwriting of secret values in memory.
+//!
+//! The compiler is free to remove writes to memory that is not read
+//! afterwards, which includes clearing a value just before it is dropped. The
+//! implementations here use volatile writes followed by a compiler fence,
+//! which is what the `zeroize` crate does, so that the writes are kept.
+use std::{ptr, sync::atomic};
+
+/// Types holding secret values that can be overwritten when they are no
+/// longer needed. Types implementing this trait typically also implement
+/// [Drop] by calling [Zeroize::zeroize].
+pub trait Zeroize {
+    /// Overwrite the value with zeros, or with some other value that reveals
+    /// nothing about the original value.
+    fn zeroize(&mut self);
+}
+
+/// Overwrite `dst` with `value` in a way that the compiler does not optimize
+/// away, even if `dst` is not read afterwards.
+#[inline]
+pub fn volatile_overwrite<T: Copy>(dst: &mut T, value: T) {
+    unsafe { ptr::write_volatile(dst, value) }
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+impl Zeroize for [u8] {
+    fn zeroize(&mut self) {
+        for b in self.iter_mut() {
+            unsafe { ptr::write_volatile(b, 0) }
+        }
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+    }
+}
+
+impl<const N: usize> Zeroize for [u8; N] {
+    fn zeroize(&mut self) { self[..].zeroize() }
+}
+
+/// Overwrite the whole allocated buffer, including the spare capacity, and
+/// make the vector empty.
+impl Zeroize for Vec<u8> {
+    fn zeroize(&mut self) {
+        // Resizing to the capacity does not reallocate.
+        self.resize(self.capacity(), 0);
+        self[..].zeroize();
+        self.clear();
+    }
+}
+
+/// Overwrite the whole allocated buffer and make the string empty.
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        // Zero bytes are valid UTF8, so the string stays valid.
+        unsafe { self.as_mut_vec().zeroize() }
+    }
+}
+
+impl<T: Zeroize> Zeroize for Option<T> {
+    fn zeroize(&mut self) {
+        if let Some(x) = self {
+            x.zeroize()
+        }
+    }
+}
+
+/// The key is replaced by the all zero key. The `SecretKey` of `ed25519_dalek`
+/// overwrites its memory when dropped, so the replaced key is overwritten.
+impl Zeroize for ed25519_dalek::SecretKey {
+    fn zeroize(&mut self) {
+        *self = ed25519_dalek::SecretKey::from_bytes(&[0u8; ed25519_dalek::SECRET_KEY_LENGTH])
+            .expect("Any 32 bytes are a valid secret key.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroize_buffers() {
+        let mut bytes = [0xffu8; 32];
+        bytes.zeroize();
+        assert_eq!(bytes, [0u8; 32]);
+
+        let mut v = Vec::with_capacity(64);
+        v.extend_from_slice(&[1u8, 2, 3]);
+        v.zeroize();
+        assert!(v.is_empty());
+        assert_eq!(v.capacity(), 64);
+
+        let mut s = String::from("secret");
+        s.zeroize();
+        assert!(s.is_empty());
+
+        let mut key = Some(ed25519_dalek::SecretKey::from_bytes(&[1u8; 32]).unwrap());
+        key.zeroize();
+        assert_eq!(key.map(|k| k.to_bytes()), Some([0u8; 32]));
+    }
+}
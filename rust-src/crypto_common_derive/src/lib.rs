@@ -6,6 +6,9 @@ use syn::spanned::Spanned;
 
 use proc_macro::TokenStream;
 
+/// Derive [serde::Serialize] and [serde::Deserialize] instances that encode the
+/// value as the base16 (hex) string of its [Serial] serialization, and parse it
+/// using [Deserial]. The type must implement both [Serial] and [Deserial].
 #[proc_macro_derive(SerdeBase16Serialize)]
 pub fn serde_base16_serialize_derive(input: TokenStream) -> TokenStream {
     let mut ast: syn::DeriveInput = syn::parse(input).expect("Cannot parse input.");
@@ -24,13 +27,13 @@ pub fn serde_base16_serialize_derive(input: TokenStream) -> TokenStream {
     let ident_serializer = format_ident!("serializer", span = span);
     let ident_deserializer = format_ident!("deserializer", span = span);
     let gen = quote! {
-        impl #impl_generics SerdeSerialize for #name #ty_generics #where_clauses {
+        impl #impl_generics crypto_common::SerdeSerialize for #name #ty_generics #where_clauses {
             fn serialize<#ident: serde::Serializer>(&self, #ident_serializer: #ident) -> Result<#ident::Ok, #ident::Error> {
                 crypto_common::base16_encode(self, #ident_serializer)
             }
         }
 
-        impl #impl_generics SerdeDeserialize<#lifetime> for #name #ty_generics #where_clauses {
+        impl #impl_generics crypto_common::SerdeDeserialize<#lifetime> for #name #ty_generics #where_clauses {
             fn deserialize<#ident: serde::Deserializer<#lifetime>>(#ident_deserializer: #ident) -> Result<Self, #ident::Error> {
                 crypto_common::base16_decode::<#lifetime, #ident, #name #ty_generics>(#ident_deserializer)
             }
@@ -39,6 +42,9 @@ pub fn serde_base16_serialize_derive(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// Like [SerdeBase16Serialize](derive@SerdeBase16Serialize), except that the
+/// first 4 bytes of the serialization, the length, are omitted from the
+/// string.
 #[proc_macro_derive(SerdeBase16IgnoreLengthSerialize)]
 pub fn serde_base16_ignore_length_serialize_derive(input: TokenStream) -> TokenStream {
     let mut ast: syn::DeriveInput = syn::parse(input).expect("Cannot parse input.");
@@ -57,15 +63,15 @@ pub fn serde_base16_ignore_length_serialize_derive(input: TokenStream) -> TokenS
     let ident_serializer = format_ident!("serializer", span = span);
     let ident_deserializer = format_ident!("deserializer", span = span);
     let gen = quote! {
-        impl #impl_generics SerdeSerialize for #name #ty_generics #where_clauses {
+        impl #impl_generics crypto_common::SerdeSerialize for #name #ty_generics #where_clauses {
             fn serialize<#ident: serde::Serializer>(&self, #ident_serializer: #ident) -> Result<#ident::Ok, #ident::Error> {
-                base16_ignore_length_encode(self, #ident_serializer)
+                crypto_common::base16_ignore_length_encode(self, #ident_serializer)
             }
         }
 
-        impl #impl_generics SerdeDeserialize<#lifetime> for #name #ty_generics #where_clauses {
+        impl #impl_generics crypto_common::SerdeDeserialize<#lifetime> for #name #ty_generics #where_clauses {
             fn deserialize<#ident: serde::Deserializer<#lifetime>>(#ident_deserializer: #ident) -> Result<Self, #ident::Error> {
-                base16_ignore_length_decode::<#lifetime, #ident, #name #ty_generics>(#ident_deserializer)
+                crypto_common::base16_ignore_length_decode::<#lifetime, #ident, #name #ty_generics>(#ident_deserializer)
             }
         }
     };
@@ -74,7 +80,15 @@ pub fn serde_base16_ignore_length_serialize_derive(input: TokenStream) -> TokenS
 
 #[proc_macro_derive(
     Deserial,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        max_size,
+        serial_skip,
+        deserial_default
+    )
 )]
 pub fn deserial_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
@@ -105,6 +119,180 @@ fn find_length_attribute(l: &[syn::Attribute], attr: &str) -> Option<u32> {
     None
 }
 
+/// The bound given by a `#[max_size = n]` attribute, if present.
+fn find_max_size_attribute(l: &[syn::Attribute]) -> Option<usize> {
+    let max_size = format_ident!("max_size");
+    for attr in l.iter() {
+        if let Ok(syn::Meta::NameValue(mn)) = attr.parse_meta() {
+            if mn.path.is_ident(&max_size) {
+                if let syn::Lit::Int(int) = mn.lit {
+                    if let Ok(v) = int.base10_parse() {
+                        return Some(v);
+                    } else {
+                        panic!("Unknown attribute value {}.", int);
+                    }
+                } else {
+                    panic!("Unknown attribute value {:?}.", mn.lit);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether the field has a `#[serial_skip]` attribute.
+fn has_serial_skip_attribute(l: &[syn::Attribute]) -> bool {
+    let serial_skip = format_ident!("serial_skip");
+    l.iter().any(|attr| attr.path.is_ident(&serial_skip))
+}
+
+/// The value of a `#[deserial_default]` attribute. This is `Some(None)` if the
+/// attribute has no value, and `Some(Some(f))` for `#[deserial_default = "f"]`.
+fn find_deserial_default_attribute(l: &[syn::Attribute]) -> Option<Option<syn::Path>> {
+    let deserial_default = format_ident!("deserial_default");
+    for attr in l.iter() {
+        if !attr.path.is_ident(&deserial_default) {
+            continue;
+        }
+        match attr.parse_meta() {
+            Ok(syn::Meta::Path(_)) => return Some(None),
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) => match s.parse() {
+                Ok(path) => return Some(Some(path)),
+                Err(_) => panic!("Unknown function {} in deserial_default.", s.value()),
+            },
+            _ => panic!(
+                "The deserial_default attribute must be of the form #[deserial_default] or \
+                 #[deserial_default = \"function\"]."
+            ),
+        }
+    }
+    None
+}
+
+/// Whether the type is `Option<T>` for some `T`.
+fn is_option_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// The expression that deserializes the given field from `source`, taking
+/// the attributes of the field into account.
+///
+/// - Fields with `#[serial_skip]` are not read, and are set to
+///   `Default::default()`, or the result of `f()` if the field also has
+///   `#[deserial_default = "f"]`.
+/// - If the field has a `#[max_size = n]` attribute, deserialization fails when
+///   the length exceeds `n`, before anything is allocated.
+/// - Length attributes on a field of type `Option<T>` apply to `T`, which is
+///   preceded by a tag byte.
+fn deserial_field(f: &syn::Field, source: &syn::Ident) -> proc_macro2::TokenStream {
+    let default = find_deserial_default_attribute(&f.attrs);
+    if has_serial_skip_attribute(&f.attrs) {
+        return match default {
+            Some(Some(path)) => quote!(#path()),
+            _ => quote!(Default::default()),
+        };
+    } else if default.is_some() {
+        panic!("The deserial_default attribute requires the serial_skip attribute.")
+    }
+    let max_size = find_max_size_attribute(&f.attrs);
+    let (l, deserial) = if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
+        (l, quote!(crypto_common::deserial_vector_no_length))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
+        (l, quote!(crypto_common::deserial_map_no_length))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
+        (l, quote!(crypto_common::deserial_set_no_length))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
+        (l, quote!(crypto_common::deserial_string))
+    } else {
+        if max_size.is_some() {
+            panic!("The max_size attribute requires one of the length attributes.")
+        }
+        let ty = &f.ty;
+        return quote!(<#ty as Deserial>::deserial(#source)?);
+    };
+    let id = format_ident!("u{}", 8 * l);
+    let check = max_size.map(|max_size| quote!(crypto_common::check_max_size(len, #max_size)?;));
+    let value = quote! {
+        {
+            let len: #id = #id::deserial(#source)?;
+            let len = usize::try_from(len)?;
+            #check
+            #deserial(#source, len)?
+        }
+    };
+    if is_option_type(&f.ty) {
+        quote! {
+            if crypto_common::deserial_option_tag(#source)? {
+                Some(#value)
+            } else {
+                None
+            }
+        }
+    } else {
+        value
+    }
+}
+
+/// The statements that serialize the given field to `out`, taking the
+/// attributes of the field into account, see [deserial_field]. The `value` is
+/// a reference to the value of the field.
+fn serial_field(
+    f: &syn::Field,
+    value: proc_macro2::TokenStream,
+    out: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    if has_serial_skip_attribute(&f.attrs) {
+        return proc_macro2::TokenStream::new();
+    }
+    let (l, serial) = if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
+        (l, quote!(crypto_common::serial_vector_no_length(v, #out)))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
+        (l, quote!(crypto_common::serial_map_no_length(v, #out)))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
+        (l, quote!(crypto_common::serial_set_no_length(v, #out)))
+    } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
+        (l, quote!(crypto_common::serial_string(v.as_str(), #out)))
+    } else {
+        return quote! {
+            #value.serial(#out);
+        };
+    };
+    let id = format_ident!("u{}", 8 * l);
+    let body = quote! {
+        let len: #id = v.len() as #id;
+        len.serial(#out);
+        #serial;
+    };
+    if is_option_type(&f.ty) {
+        quote! {
+            match #value {
+                None => 0u8.serial(#out),
+                Some(v) => {
+                    1u8.serial(#out);
+                    #body
+                }
+            }
+        }
+    } else {
+        quote! {
+            {
+                let v = #value;
+                #body
+            }
+        }
+    }
+}
+
 fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
 
@@ -119,44 +307,10 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
         let mut names = proc_macro2::TokenStream::new();
         let source = format_ident!("source");
         let mut pusher = |f: &syn::Field, ident| {
-            if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
-                let id = format_ident!("u{}", 8 * l);
-                tokens.extend(quote! {
-                    let #ident = {
-                        let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_vector_no_length(#source, usize::try_from(len)?)?
-                    };
-                });
-            } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
-                let id = format_ident!("u{}", 8 * l);
-                tokens.extend(quote! {
-                    let #ident = {
-                        let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_map_no_length(#source, usize::try_from(len)?)?
-                    };
-                });
-            } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
-                let id = format_ident!("u{}", 8 * l);
-                tokens.extend(quote! {
-                    let #ident = {
-                        let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_set_no_length(#source, usize::try_from(len)?)?
-                    };
-                });
-            } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
-                let id = format_ident!("u{}", 8 * l);
-                tokens.extend(quote! {
-                    let #ident = {
-                        let len: #id = #id::deserial(#source)?;
-                        crypto_common::deserial_string(#source, usize::try_from(len)?)?
-                    };
-                });
-            } else {
-                let ty = &f.ty;
-                tokens.extend(quote! {
-                    let #ident = <#ty as Deserial>::deserial(#source)?;
-                });
-            }
+            let value = deserial_field(f, &source);
+            tokens.extend(quote! {
+                let #ident = #value;
+            });
             names.extend(quote!(#ident,))
         };
         let gen = match data.fields {
@@ -201,7 +355,15 @@ fn impl_deserial(ast: &syn::DeriveInput) -> TokenStream {
 
 #[proc_macro_derive(
     Serial,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        max_size,
+        serial_skip,
+        deserial_default
+    )
 )]
 pub fn serial_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
@@ -224,39 +386,7 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
                 let mut body = proc_macro2::TokenStream::new();
                 for f in data.fields.iter() {
                     let ident = f.ident.clone().unwrap(); // safe since named fields.
-                    if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
-                            crypto_common::serial_vector_no_length(&self.#ident, #out);
-                        });
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
-                            crypto_common::serial_map_no_length(&self.#ident, #out);
-                        })
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
-                            crypto_common::serial_set_no_length(&self.#ident, #out);
-                        })
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        body.extend(quote! {
-                            let len: #id = self.#ident.len() as #id;
-                            len.serial(#out);
-                            crypto_common::serial_string(self.#ident.as_str(), #out);
-                        })
-                    } else {
-                        body.extend(quote! {
-                            self.#ident.serial(#out);
-                        });
-                    }
+                    body.extend(serial_field(f, quote!(&self.#ident), &out));
                 }
                 quote! {
                     impl #impl_generics Serial for #name #ty_generics #where_clauses {
@@ -273,43 +403,12 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
                 let mut names = proc_macro2::TokenStream::new();
                 let mut body = proc_macro2::TokenStream::new();
                 for (i, f) in data.fields.iter().enumerate() {
-                    let ident = format_ident!("x_{}", i);
-
-                    if let Some(l) = find_length_attribute(&f.attrs, "size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
-                        body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_vector_no_length(#ident, #out);
-                        });
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "map_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
-                        body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_map_no_length(&self.#ident, #out);
-                        })
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "set_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
-                        body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_set_no_length(&self.#ident, #out);
-                        })
-                    } else if let Some(l) = find_length_attribute(&f.attrs, "string_size_length") {
-                        let id = format_ident!("u{}", 8 * l);
-                        let len_ident = format_ident!("len_{}", i);
-                        body.extend(quote! {
-                            let #len_ident: #id = #ident.len() as #id;
-                            #len_ident.serial(#out);
-                            serial_string(self.#ident.as_str(), #out);
-                        })
-                    } else {
-                        body.extend(quote!(#ident.serial(#out);));
+                    if has_serial_skip_attribute(&f.attrs) {
+                        names.extend(quote!(_,));
+                        continue;
                     }
+                    let ident = format_ident!("x_{}", i);
+                    body.extend(serial_field(f, quote!(#ident), &out));
                     names.extend(quote!(ref #ident,))
                 }
                 quote! {
@@ -331,7 +430,15 @@ fn impl_serial(ast: &syn::DeriveInput) -> TokenStream {
 
 #[proc_macro_derive(
     Serialize,
-    attributes(size_length, map_size_length, set_size_length, string_size_length)
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        max_size,
+        serial_skip,
+        deserial_default
+    )
 )]
 pub fn serialize_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).expect("Cannot parse input.");
@@ -339,3 +446,122 @@ pub fn serialize_derive(input: TokenStream) -> TokenStream {
     tokens.extend(impl_serial(&ast));
     tokens
 }
+
+#[proc_macro_derive(
+    SerialVersioned,
+    attributes(
+        size_length,
+        map_size_length,
+        set_size_length,
+        string_size_length,
+        max_size,
+        serial_skip,
+        deserial_default,
+        since
+    )
+)]
+pub fn serial_versioned_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).expect("Cannot parse input.");
+    impl_serial_versioned(&ast)
+}
+
+/// The version given by a `#[since(version = n)]` attribute, if present.
+fn find_since_attribute(l: &[syn::Attribute]) -> Option<u32> {
+    let since = format_ident!("since");
+    let version = format_ident!("version");
+    for attr in l.iter() {
+        if !attr.path.is_ident(&since) {
+            continue;
+        }
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            if let Some(syn::NestedMeta::Meta(syn::Meta::NameValue(mn))) = list.nested.first() {
+                if mn.path.is_ident(&version) && list.nested.len() == 1 {
+                    if let syn::Lit::Int(int) = &mn.lit {
+                        if let Ok(v) = int.base10_parse() {
+                            return Some(v);
+                        }
+                    }
+                }
+            }
+        }
+        panic!("The since attribute must be of the form #[since(version = n)].")
+    }
+    None
+}
+
+fn impl_serial_versioned(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+
+    let span = ast.span();
+
+    let buffer_ident = format_ident!("GenericBufferType", span = span);
+    let reader_ident = format_ident!("GenericReaderType", span = span);
+
+    let (impl_generics, ty_generics, where_clauses) = ast.generics.split_for_impl();
+
+    let out = format_ident!("out");
+    let source = format_ident!("source");
+    // Named differently from the parameter so that it cannot be shadowed by the
+    // fields when deserializing.
+    let version_value = format_ident!("version_value");
+    let data = match ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(ref fields),
+            ..
+        }) => fields,
+        _ => panic!("#[derive(SerialVersioned)] only implemented for structs with named fields."),
+    };
+    let mut latest = 0u32;
+    let mut serial_body = proc_macro2::TokenStream::new();
+    let mut deserial_body = proc_macro2::TokenStream::new();
+    let mut names = proc_macro2::TokenStream::new();
+    for f in data.named.iter() {
+        let ident = f.ident.clone().unwrap(); // safe since named fields.
+        let serial = serial_field(f, quote!(&self.#ident), &out);
+        let deserial = deserial_field(f, &source);
+        match find_since_attribute(&f.attrs) {
+            Some(since) if since > 0 => {
+                latest = std::cmp::max(latest, since);
+                serial_body.extend(quote! {
+                    if #version_value >= #since {
+                        #serial
+                    }
+                });
+                deserial_body.extend(quote! {
+                    let #ident = if #version_value >= #since {
+                        #deserial
+                    } else {
+                        Default::default()
+                    };
+                });
+            }
+            _ => {
+                serial_body.extend(serial);
+                deserial_body.extend(quote! {
+                    let #ident = #deserial;
+                });
+            }
+        }
+        names.extend(quote!(#ident,));
+    }
+    let gen = quote! {
+        impl #impl_generics SerialVersioned for #name #ty_generics #where_clauses {
+            const LATEST_VERSION: Version = Version { value: #latest };
+
+            #[allow(unused_variables)]
+            fn serial_version<#buffer_ident: Buffer>(&self, version: Version, #out: &mut #buffer_ident) {
+                let #version_value: u32 = version.value;
+                #serial_body
+            }
+
+            #[allow(non_snake_case, unused_variables)]
+            fn deserial_version<#reader_ident: ReadBytesExt>(version: Version, #source: &mut #reader_ident) -> ParseResult<Self> {
+                use std::convert::TryFrom;
+                let #version_value: u32 = version.value;
+                #deserial_body
+                Ok(#name{#names})
+            }
+        }
+    };
+    gen.into()
+}
@@ -298,6 +298,43 @@ pub fn multiexp_table<C: Curve, X: Borrow<C>>(gs: &[X], window_size: usize) -> V
     table
 }
 
+/// Window size of [MultiExpTable]. The table is computed once and used for many
+/// multi-exponentiations, so a larger window than in [multiexp] pays off.
+pub const FIXED_BASE_WINDOW_SIZE: usize = 6;
+
+/// Precomputed table for multi-exponentiations with fixed bases, such as the
+/// keys of a commitment scheme. The table is computed once in
+/// [MultiExpTable::new], which saves that work in every
+/// [multiexp](MultiExpTable::multiexp) with the same bases.
+#[derive(Debug, Clone)]
+pub struct MultiExpTable<C: Curve> {
+    table:       Vec<Vec<C>>,
+    window_size: usize,
+}
+
+impl<C: Curve> MultiExpTable<C> {
+    /// Precompute the table for the given bases.
+    pub fn new<X: Borrow<C>>(gs: &[X]) -> Self {
+        MultiExpTable {
+            table:       multiexp_table(gs, FIXED_BASE_WINDOW_SIZE),
+            window_size: FIXED_BASE_WINDOW_SIZE,
+        }
+    }
+
+    /// The number of bases.
+    pub fn len(&self) -> usize { self.table.len() }
+
+    /// Whether there are no bases.
+    pub fn is_empty(&self) -> bool { self.table.is_empty() }
+
+    /// Compute the product of the bases raised to the given exponents, in
+    /// order. The number of exponents must be the number of bases.
+    pub fn multiexp(&self, exps: &[C::Scalar]) -> C {
+        assert_eq!(exps.len(), self.table.len());
+        multiexp_worker_given_table(exps, &self.table, self.window_size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,4 +362,21 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    pub fn test_multiexp_table() {
+        let mut csprng = thread_rng();
+        for l in 1..20 {
+            let gs: Vec<G1> = (0..l).map(|_| G1::generate(&mut csprng)).collect();
+            let table = MultiExpTable::new(&gs);
+            for _ in 0..5 {
+                let es: Vec<_> = (0..l).map(|_| G1::generate_scalar(&mut csprng)).collect();
+                assert_eq!(
+                    table.multiexp(&es),
+                    multiexp(&gs, &es),
+                    "Multiexponentiation with a precomputed table gives a different answer."
+                );
+            }
+        }
+    }
 }
@@ -1,10 +1,16 @@
 //! Basic definitions of the curve and pairing abstractions, and implementations
-//! of these abstractions for the curves used on Concordium.
+//! of these abstractions for the curves used on Concordium. The `ristretto`
+//! feature adds an implementation for the Ristretto group, see
+//! [RistrettoPoint].
 mod bls12_381_g1hash;
 mod bls12_381_g2hash;
 mod bls12_381_instance;
 mod curve_arithmetic;
+#[cfg(feature = "ristretto")]
+mod ristretto_instance;
 pub use crate::curve_arithmetic::*;
+#[cfg(feature = "ristretto")]
+pub use ristretto_instance::{RistrettoPoint, RistrettoScalar, RistrettoScalarRepr};
 
 pub mod secret_value;
 pub use secret_value::{Secret, Value};
@@ -0,0 +1,487 @@
+//! An implementation of the [Curve] abstraction for the Ristretto group over
+//! Curve25519, based on `curve25519-dalek`.
+//!
+//! The group has no pairing, so it can only be used for constructions that do
+//! not need one, e.g., ElGamal encryption, Pedersen commitments, and the sigma
+//! protocols built on them. Its group operations are considerably faster than
+//! those of BLS12-381, which makes it attractive for off-chain use.
+//!
+//! `curve25519-dalek` does not implement the `ff` traits, so the types here
+//! wrap the point and scalar types of the library and implement the traits on
+//! top of them.
+
+use crate::curve_arithmetic::*;
+use byteorder::ReadBytesExt;
+use crypto_common::*;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint as DalekPoint},
+    scalar::Scalar,
+    traits::Identity,
+};
+use ff::{Field, PrimeField, PrimeFieldDecodingError, PrimeFieldRepr};
+use rand::*;
+use sha2::{Digest, Sha512};
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+const HASH_TO_GROUP_DST: &[u8] = b"CONCORDIUM-hashtoRistretto255-with-SHA-512";
+
+/// The order of the group, in little-endian 64-bit limbs. This is
+/// 2^252 + 27742317777372353535851937790883648493.
+const MODULUS: [u64; 4] = [
+    0x5812_631a_5cf5_d3ed,
+    0x14de_f9de_a2f7_9cd6,
+    0,
+    0x1000_0000_0000_0000,
+];
+
+/// The odd part of `MODULUS - 1`, i.e., `(MODULUS - 1) / 2^S`.
+const MODULUS_MINUS_ONE_ODD: [u64; 4] = [
+    0x9604_98c6_973d_74fb,
+    0x0537_be77_a8bd_e735,
+    0,
+    0x0400_0000_0000_0000,
+];
+
+/// The representation of a [RistrettoScalar] as an integer, in four
+/// little-endian 64-bit limbs.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct RistrettoScalarRepr(pub [u64; 4]);
+
+impl AsRef<[u64]> for RistrettoScalarRepr {
+    fn as_ref(&self) -> &[u64] { &self.0 }
+}
+
+impl AsMut<[u64]> for RistrettoScalarRepr {
+    fn as_mut(&mut self) -> &mut [u64] { &mut self.0 }
+}
+
+impl From<u64> for RistrettoScalarRepr {
+    fn from(n: u64) -> Self { RistrettoScalarRepr([n, 0, 0, 0]) }
+}
+
+impl From<RistrettoScalar> for RistrettoScalarRepr {
+    fn from(s: RistrettoScalar) -> Self { s.into_repr() }
+}
+
+impl Ord for RistrettoScalarRepr {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Compare from the most significant limb.
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for RistrettoScalarRepr {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+impl fmt::Display for RistrettoScalarRepr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.0.iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+impl RistrettoScalarRepr {
+    fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            let mut v = [0u8; 8];
+            v.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(v);
+        }
+        RistrettoScalarRepr(limbs)
+    }
+}
+
+impl PrimeFieldRepr for RistrettoScalarRepr {
+    fn sub_noborrow(&mut self, other: &Self) {
+        let mut borrow = false;
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            let (d, b1) = a.overflowing_sub(*b);
+            let (d, b2) = d.overflowing_sub(u64::from(borrow));
+            *a = d;
+            borrow = b1 || b2;
+        }
+    }
+
+    fn add_nocarry(&mut self, other: &Self) {
+        let mut carry = false;
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            let (s, c1) = a.overflowing_add(*b);
+            let (s, c2) = s.overflowing_add(u64::from(carry));
+            *a = s;
+            carry = c1 || c2;
+        }
+    }
+
+    fn num_bits(&self) -> u32 {
+        let mut bits = 64 * 4;
+        for limb in self.0.iter().rev() {
+            let leading = limb.leading_zeros();
+            bits -= leading;
+            if leading != 64 {
+                break;
+            }
+        }
+        bits
+    }
+
+    fn is_zero(&self) -> bool { self.0.iter().all(|&limb| limb == 0) }
+
+    fn is_odd(&self) -> bool { self.0[0] & 1 == 1 }
+
+    fn is_even(&self) -> bool { !self.is_odd() }
+
+    fn div2(&mut self) { self.shr(1) }
+
+    fn shr(&mut self, amt: u32) {
+        if amt >= 64 * 4 {
+            *self = Self::default();
+            return;
+        }
+        let limbs = (amt / 64) as usize;
+        let bits = amt % 64;
+        for i in 0..4 {
+            let mut limb = self.0.get(i + limbs).map_or(0, |&l| l >> bits);
+            if bits > 0 {
+                limb |= self.0.get(i + limbs + 1).map_or(0, |&l| l << (64 - bits));
+            }
+            self.0[i] = limb;
+        }
+    }
+
+    fn mul2(&mut self) { self.shl(1) }
+
+    fn shl(&mut self, amt: u32) {
+        if amt >= 64 * 4 {
+            *self = Self::default();
+            return;
+        }
+        let limbs = (amt / 64) as usize;
+        let bits = amt % 64;
+        for i in (0..4).rev() {
+            let mut limb = if i >= limbs {
+                self.0[i - limbs] << bits
+            } else {
+                0
+            };
+            if bits > 0 && i > limbs {
+                limb |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+            self.0[i] = limb;
+        }
+    }
+
+    fn write_be<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for limb in self.0.iter().rev() {
+            writer.write_all(&limb.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_be<R: Read>(&mut self, mut reader: R) -> std::io::Result<()> {
+        for limb in self.0.iter_mut().rev() {
+            let mut v = [0u8; 8];
+            reader.read_exact(&mut v)?;
+            *limb = u64::from_be_bytes(v);
+        }
+        Ok(())
+    }
+}
+
+/// An element of the scalar field of the Ristretto group.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RistrettoScalar(pub Scalar);
+
+impl fmt::Display for RistrettoScalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RistrettoScalar({})", self.into_repr())
+    }
+}
+
+impl Field for RistrettoScalar {
+    fn random<R: RngCore + ?std::marker::Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        RistrettoScalar(Scalar::from_bytes_mod_order_wide(&bytes))
+    }
+
+    fn zero() -> Self { RistrettoScalar(Scalar::zero()) }
+
+    fn one() -> Self { RistrettoScalar(Scalar::one()) }
+
+    fn is_zero(&self) -> bool { self.0 == Scalar::zero() }
+
+    fn square(&mut self) { self.0 = self.0 * self.0 }
+
+    fn double(&mut self) { self.0 = self.0 + self.0 }
+
+    fn negate(&mut self) { self.0 = -self.0 }
+
+    fn add_assign(&mut self, other: &Self) { self.0 += other.0 }
+
+    fn sub_assign(&mut self, other: &Self) { self.0 -= other.0 }
+
+    fn mul_assign(&mut self, other: &Self) { self.0 *= other.0 }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(RistrettoScalar(self.0.invert()))
+        }
+    }
+
+    // The field is a prime field, so the Frobenius map is the identity.
+    fn frobenius_map(&mut self, _power: usize) {}
+}
+
+impl PrimeField for RistrettoScalar {
+    type Repr = RistrettoScalarRepr;
+
+    const CAPACITY: u32 = 252;
+    const NUM_BITS: u32 = 253;
+    const S: u32 = 2;
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, PrimeFieldDecodingError> {
+        match Scalar::from_canonical_bytes(repr.to_le_bytes()) {
+            Some(s) => Ok(RistrettoScalar(s)),
+            None => Err(PrimeFieldDecodingError::NotInField(repr.to_string())),
+        }
+    }
+
+    fn into_repr(&self) -> Self::Repr { RistrettoScalarRepr::from_le_bytes(self.0.as_bytes()) }
+
+    fn char() -> Self::Repr { RistrettoScalarRepr(MODULUS) }
+
+    fn multiplicative_generator() -> Self { RistrettoScalar(Scalar::from(2u64)) }
+
+    fn root_of_unity() -> Self { Self::multiplicative_generator().pow(MODULUS_MINUS_ONE_ODD) }
+}
+
+impl Serial for RistrettoScalar {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        // Big endian, as the scalars of the other curves.
+        let mut bytes = self.0.to_bytes();
+        bytes.reverse();
+        if let Err(e) = out.write_all(&bytes) {
+            panic!(
+                "Precondition violated. Buffer should be safe to write {}.",
+                e
+            );
+        }
+    }
+}
+
+impl Deserial for RistrettoScalar {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = [0u8; 32];
+        source.read_exact(&mut bytes)?;
+        bytes.reverse();
+        match Scalar::from_canonical_bytes(bytes) {
+            Some(s) => Ok(RistrettoScalar(s)),
+            None => anyhow::bail!("Not a canonical scalar."),
+        }
+    }
+}
+
+/// An element of the Ristretto group.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RistrettoPoint(pub DalekPoint);
+
+impl fmt::Display for RistrettoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RistrettoPoint(0x")?;
+        for b in self.0.compress().as_bytes() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Serial for RistrettoPoint {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        if let Err(e) = out.write_all(self.0.compress().as_bytes()) {
+            panic!(
+                "Precondition violated. Buffer should be safe to write {}.",
+                e
+            );
+        }
+    }
+}
+
+impl Deserial for RistrettoPoint {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        Ok(RistrettoPoint::bytes_to_curve_unchecked(source)?)
+    }
+}
+
+impl Curve for RistrettoPoint {
+    /// The base field of Curve25519 is not exposed by `curve25519-dalek`. It is
+    /// only needed for pairings, which the group does not have, so the scalar
+    /// field stands in for it.
+    type Base = RistrettoScalar;
+    type Compressed = CompressedRistretto;
+    type Scalar = RistrettoScalar;
+
+    const GROUP_ELEMENT_LENGTH: usize = 32;
+    const SCALAR_LENGTH: usize = 32;
+
+    fn zero_point() -> Self { RistrettoPoint(DalekPoint::identity()) }
+
+    fn one_point() -> Self { RistrettoPoint(RISTRETTO_BASEPOINT_POINT) }
+
+    fn is_zero_point(&self) -> bool { self.0 == DalekPoint::identity() }
+
+    fn inverse_point(&self) -> Self { RistrettoPoint(-self.0) }
+
+    fn double_point(&self) -> Self { RistrettoPoint(self.0 + self.0) }
+
+    fn plus_point(&self, other: &Self) -> Self { RistrettoPoint(self.0 + other.0) }
+
+    fn minus_point(&self, other: &Self) -> Self { RistrettoPoint(self.0 - other.0) }
+
+    fn mul_by_scalar(&self, scalar: &Self::Scalar) -> Self { RistrettoPoint(self.0 * scalar.0) }
+
+    fn compress(&self) -> Self::Compressed { self.0.compress() }
+
+    fn decompress(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+        c.decompress()
+            .map(RistrettoPoint)
+            .ok_or(CurveDecodingError::NotOnCurve)
+    }
+
+    /// Every encoding of a Ristretto point is checked when it is decompressed,
+    /// so this is the same as [Curve::decompress].
+    fn decompress_unchecked(c: &Self::Compressed) -> Result<Self, CurveDecodingError> {
+        Self::decompress(c)
+    }
+
+    fn bytes_to_curve_unchecked<R: ReadBytesExt>(bytes: &mut R) -> anyhow::Result<Self> {
+        let mut c = [0u8; 32];
+        bytes.read_exact(&mut c)?;
+        Ok(Self::decompress(&CompressedRistretto(c))?)
+    }
+
+    fn generate<T: Rng>(csprng: &mut T) -> Self {
+        let mut bytes = [0u8; 64];
+        csprng.fill_bytes(&mut bytes);
+        RistrettoPoint(DalekPoint::from_uniform_bytes(&bytes))
+    }
+
+    fn generate_scalar<T: Rng>(csprng: &mut T) -> Self::Scalar { RistrettoScalar::random(csprng) }
+
+    fn scalar_from_u64(n: u64) -> Self::Scalar { RistrettoScalar(Scalar::from(n)) }
+
+    fn scalar_from_bytes<A: AsRef<[u8]>>(bytes: A) -> Self::Scalar {
+        // Take at most 32 bytes, and erase the top four bits so that the value
+        // is less than 2^252, and hence less than the group order.
+        let mut s = [0u8; 32];
+        let bytes = bytes.as_ref();
+        let len = std::cmp::min(bytes.len(), 32);
+        s[..len].copy_from_slice(&bytes[..len]);
+        s[31] &= 0x0f;
+        RistrettoScalar(
+            Scalar::from_canonical_bytes(s)
+                .expect("The scalar with top four bits erased should be valid."),
+        )
+    }
+
+    fn hash_to_group(m: &[u8]) -> Self {
+        RistrettoPoint(DalekPoint::from_hash(
+            Sha512::new().chain(HASH_TO_GROUP_DST).chain(m),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_repr() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let s = RistrettoPoint::generate_scalar(&mut csprng);
+            assert_eq!(RistrettoScalar::from_repr(s.into_repr()).ok(), Some(s));
+        }
+        assert!(RistrettoScalar::from_repr(RistrettoScalar::char()).is_err());
+        let mut max = RistrettoScalar::char();
+        max.sub_noborrow(&RistrettoScalarRepr::from(1));
+        let mut minus_one = RistrettoScalar::one();
+        minus_one.negate();
+        assert_eq!(RistrettoScalar::from_repr(max).ok(), Some(minus_one));
+        assert_eq!(
+            RistrettoScalar::char().num_bits(),
+            RistrettoScalar::NUM_BITS
+        );
+
+        let mut x = RistrettoScalarRepr([1, 2, 3, 4]);
+        x.shl(70);
+        assert_eq!(x, RistrettoScalarRepr([0, 1 << 6, 2 << 6, 3 << 6]));
+        x.shr(70);
+        assert_eq!(x, RistrettoScalarRepr([1, 2, 3, 0]));
+    }
+
+    #[test]
+    fn test_root_of_unity() {
+        let mut w = RistrettoScalar::root_of_unity();
+        w.square();
+        let mut minus_one = RistrettoScalar::one();
+        minus_one.negate();
+        assert_eq!(w, minus_one);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut csprng = thread_rng();
+        for _ in 0..100 {
+            let p = RistrettoPoint::generate(&mut csprng);
+            let s = RistrettoPoint::generate_scalar(&mut csprng);
+            assert_eq!(to_bytes(&p).len(), RistrettoPoint::GROUP_ELEMENT_LENGTH);
+            assert_eq!(to_bytes(&s).len(), RistrettoPoint::SCALAR_LENGTH);
+            assert_eq!(serialize_deserialize(&p).ok(), Some(p));
+            assert_eq!(serialize_deserialize(&s).ok(), Some(s));
+        }
+    }
+
+    #[test]
+    fn test_group_laws() {
+        let mut csprng = thread_rng();
+        let g = RistrettoPoint::one_point();
+        let a = RistrettoPoint::generate_scalar(&mut csprng);
+        let b = RistrettoPoint::generate_scalar(&mut csprng);
+        let mut ab = a;
+        ab.add_assign(&b);
+        assert_eq!(
+            g.mul_by_scalar(&ab),
+            g.mul_by_scalar(&a).plus_point(&g.mul_by_scalar(&b))
+        );
+        assert!(g.minus_point(&g).is_zero_point());
+        assert_eq!(g.double_point(), g.plus_point(&g));
+        assert_eq!(
+            multiexp(&[g, g.double_point()], &[a, b]),
+            g.mul_by_scalar(&a)
+                .plus_point(&g.double_point().mul_by_scalar(&b))
+        );
+        assert_ne!(
+            RistrettoPoint::hash_to_group(b"a"),
+            RistrettoPoint::hash_to_group(b"b")
+        );
+    }
+}
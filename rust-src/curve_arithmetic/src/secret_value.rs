@@ -8,9 +8,7 @@ use ff::Field;
 use rand::*;
 use std::{
     ops::{Deref, Drop},
-    ptr,
     rc::Rc,
-    sync::atomic,
 };
 
 /// A generic wrapper for a secret that implements a zeroize on drop.
@@ -41,13 +39,12 @@ impl<F: Field + Serialize> Deref for Secret<F> {
 // This works for our current fields since they are arrays
 // But in the future we need to revisit, especially if our
 // upstream dependencies decide to implement drop themselves.
+impl<F: Field + Serialize> Zeroize for Secret<F> {
+    fn zeroize(&mut self) { volatile_overwrite(&mut self.secret, F::zero()) }
+}
+
 impl<F: Field + Serialize> Drop for Secret<F> {
-    fn drop(&mut self) {
-        // This implementation is what the Zeroize trait implementations do.
-        // It protects against most reorderings by the compiler.
-        unsafe { ptr::write_volatile(&mut self.secret, F::zero()) }
-        atomic::compiler_fence(atomic::Ordering::SeqCst);
-    }
+    fn drop(&mut self) { self.zeroize() }
 }
 
 /// A secret value. The idea of this datatype is to mark
@@ -70,6 +67,13 @@ impl<C: Curve> AsRef<C::Scalar> for Value<C> {
     fn as_ref(&self) -> &C::Scalar { &self.value }
 }
 
+/// The value is replaced by a fresh zero value. Since the secret is shared
+/// with other views of the same value, it is only overwritten in memory once
+/// the last of them is dropped or zeroized.
+impl<C: Curve> Zeroize for Value<C> {
+    fn zeroize(&mut self) { self.value = Rc::new(Secret::new(C::Scalar::zero())) }
+}
+
 /// Any 64-bit value can be converted (by-value) to a scalar.
 impl<C: Curve> From<u64> for Value<C> {
     fn from(secret: u64) -> Self { Self::new(C::scalar_from_u64(secret)) }
@@ -0,0 +1,155 @@
+//! The leader election of the consensus protocol.
+//!
+//! In each slot, a baker is leader if the hash of its VRF proof on the leader
+//! election message of the slot, interpreted as a number in `[0, 1)`, is less
+//! than the election probability of the baker. The election probability is
+//! determined by the lottery power of the baker, i.e., its fraction of the
+//! total stake, and the election difficulty. The functions here allow tools to
+//! check the leadership proofs and block nonces of blocks in the same way as
+//! the node.
+use crate::{proof::*, public::*, secret::*};
+
+/// Leadership election nonce. It is a SHA256 hash.
+pub type LeadershipElectionNonce = [u8; 32];
+
+/// The message a baker makes a VRF proof on to show that it won the lottery
+/// in a slot. It is the bytes `"LE"`, followed by the nonce and the slot
+/// number in big-endian.
+pub fn leader_election_message(nonce: &LeadershipElectionNonce, slot: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(2 + 32 + 8);
+    message.extend_from_slice(b"LE");
+    message.extend_from_slice(nonce);
+    message.extend_from_slice(&slot.to_be_bytes());
+    message
+}
+
+/// The message a baker makes a VRF proof on for the block nonce of a block in
+/// the given slot. It is the bytes `"NONCE"`, followed by the nonce and the
+/// slot number in big-endian.
+pub fn block_nonce_message(nonce: &LeadershipElectionNonce, slot: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(5 + 32 + 8);
+    message.extend_from_slice(b"NONCE");
+    message.extend_from_slice(nonce);
+    message.extend_from_slice(&slot.to_be_bytes());
+    message
+}
+
+/// Interpret the first 8 bytes of a VRF hash as a big-endian integer, and
+/// scale it by `2^-64` to get a number in `[0, 1]`. This is `hashToDouble` of
+/// the node.
+pub fn hash_to_double(hash: &[u8; 64]) -> f64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(bytes) as f64 / 18_446_744_073_709_551_616.0
+}
+
+/// The probability that a baker with the given lottery power is leader in a
+/// slot, i.e., `1 - (1 - difficulty)^lottery_power`. The lottery power and the
+/// difficulty are assumed to be in `[0, 1]` and `[0, 1)` respectively.
+pub fn election_probability(lottery_power: f64, difficulty: f64) -> f64 {
+    1.0 - (1.0 - difficulty).powf(lottery_power)
+}
+
+/// Make a leadership proof for the given slot. The baker won the slot if the
+/// proof is accepted by [verify_leadership_proof].
+pub fn prove_leadership(
+    secret: &SecretKey,
+    public: &PublicKey,
+    nonce: &LeadershipElectionNonce,
+    slot: u64,
+) -> Proof {
+    secret.prove(public, &leader_election_message(nonce, slot))
+}
+
+/// Check that the proof is a valid VRF proof on the leader election message
+/// of the slot, and that its hash is small enough for the baker to be leader.
+pub fn verify_leadership_proof(
+    public: &PublicKey,
+    proof: &Proof,
+    nonce: &LeadershipElectionNonce,
+    slot: u64,
+    lottery_power: f64,
+    difficulty: f64,
+) -> bool {
+    public.verify(proof, &leader_election_message(nonce, slot))
+        && hash_to_double(&proof.to_hash()) < election_probability(lottery_power, difficulty)
+}
+
+/// Check that the proof is a valid block nonce for the slot.
+pub fn verify_block_nonce(
+    public: &PublicKey,
+    proof: &Proof,
+    nonce: &LeadershipElectionNonce,
+    slot: u64,
+) -> bool {
+    public.verify(proof, &block_nonce_message(nonce, slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keypair;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_hash_to_double() {
+        let mut hash = [0u8; 64];
+        assert_eq!(hash_to_double(&hash), 0.0);
+        hash[0] = 0x80;
+        assert_eq!(hash_to_double(&hash), 0.5);
+        hash[0] = 0x40;
+        // only the first 8 bytes are used
+        hash[8] = 0xff;
+        assert_eq!(hash_to_double(&hash), 0.25);
+    }
+
+    #[test]
+    fn test_leadership() {
+        let mut csprng = thread_rng();
+        let keys = Keypair::generate(&mut csprng);
+        let other = Keypair::generate(&mut csprng);
+        let nonce = csprng.gen::<LeadershipElectionNonce>();
+        for slot in 0..20 {
+            let proof = prove_leadership(&keys.secret, &keys.public, &nonce, slot);
+            // with difficulty close to 1 and all the stake, every slot is won
+            assert!(verify_leadership_proof(
+                &keys.public,
+                &proof,
+                &nonce,
+                slot,
+                1.0,
+                0.999_999_999
+            ));
+            // without stake no slot is won
+            assert!(!verify_leadership_proof(
+                &keys.public,
+                &proof,
+                &nonce,
+                slot,
+                0.0,
+                0.5
+            ));
+            // the proof is only valid for the given key, slot and nonce
+            assert!(!verify_leadership_proof(
+                &other.public,
+                &proof,
+                &nonce,
+                slot,
+                1.0,
+                0.999_999_999
+            ));
+            assert!(!verify_leadership_proof(
+                &keys.public,
+                &proof,
+                &nonce,
+                slot + 1,
+                1.0,
+                0.999_999_999
+            ));
+            // a leadership proof is not a block nonce
+            assert!(!verify_block_nonce(&keys.public, &proof, &nonce, slot));
+            let block_nonce = keys.prove(&block_nonce_message(&nonce, slot));
+            assert!(verify_block_nonce(&keys.public, &block_nonce, &nonce, slot));
+        }
+    }
+}
@@ -3,6 +3,7 @@
 mod constants;
 mod ecvrf;
 mod errors;
+pub mod leader_election;
 mod proof;
 mod public;
 mod secret;
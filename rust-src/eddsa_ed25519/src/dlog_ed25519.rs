@@ -18,6 +18,43 @@ pub struct Ed25519DlogProof {
     witness:   Scalar,
 }
 
+/// The structured JSON representation of [Ed25519DlogProof], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(remote = "Ed25519DlogProof")]
+struct Ed25519DlogProofJson {
+    #[serde(serialize_with = "scalar_encode", deserialize_with = "scalar_decode")]
+    challenge: Scalar,
+    #[serde(serialize_with = "scalar_encode", deserialize_with = "scalar_decode")]
+    witness:   Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl StructuredSerde for Ed25519DlogProof {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        Ed25519DlogProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        Ed25519DlogProofJson::deserialize(des)
+    }
+}
+
+/// Encode a scalar as a hex string of its canonical little-endian bytes.
+#[cfg(feature = "json-proofs")]
+fn scalar_encode<S: serde::Serializer>(scalar: &Scalar, ser: S) -> Result<S::Ok, S::Error> {
+    base16_encode(&scalar.to_bytes(), ser)
+}
+
+/// Dual to [scalar_encode]. Fails on non-canonical encodings.
+#[cfg(feature = "json-proofs")]
+fn scalar_decode<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Scalar, D::Error> {
+    let bytes: [u8; 32] = base16_decode(des)?;
+    Scalar::from_canonical_bytes(bytes)
+        .ok_or_else(|| serde::de::Error::custom("Not a valid scalar."))
+}
+
 impl Serial for Ed25519DlogProof {
     fn serial<B: Buffer>(&self, out: &mut B) {
         out.write_all(self.challenge.as_bytes())
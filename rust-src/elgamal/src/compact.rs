@@ -0,0 +1,171 @@
+//! Compact encoding of lists of ciphers, intended for storage.
+
+use crate::cipher::Cipher;
+use anyhow::bail;
+use crypto_common::*;
+use curve_arithmetic::Curve;
+use std::{io::Cursor, iter::once};
+
+/// A list of ciphers with a compact binary encoding, intended for wallets and
+/// indexers that store histories of encrypted amounts.
+///
+/// Group elements are serialized in compressed form, as always. In addition,
+/// components of the ciphers that are the identity element are omitted. These
+/// are common in practice, since amounts encrypted with zero randomness, e.g.,
+/// in transfers from the public balance, have the identity as the first
+/// component. Which components are omitted is recorded in a bitmap in a header
+/// that is shared by all the ciphers in the list.
+///
+/// The encoding is
+/// - the number of ciphers `n` as a big-endian `u32`,
+/// - a bitmap of `ceil(2n / 8)` bytes, in which bit `i` (counting from the
+///   least significant bit of the first byte) is set if the `i`-th point is the
+///   identity, where the points are the components of the ciphers in order,
+/// - the points that are not the identity, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CipherBatch<C: Curve> {
+    pub ciphers: Vec<Cipher<C>>,
+}
+
+impl<C: Curve> CipherBatch<C> {
+    pub fn new(ciphers: Vec<Cipher<C>>) -> Self { CipherBatch { ciphers } }
+
+    /// The compact encoding of the ciphers.
+    pub fn to_compact_bytes(&self) -> Vec<u8> { to_bytes(self) }
+
+    /// Parse the output of [CipherBatch::to_compact_bytes]. This fails if
+    /// there are any bytes left after the encoding.
+    pub fn from_compact_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        Self::parse_exact(bytes, |source| source.get())
+    }
+
+    /// Same as [CipherBatch::from_compact_bytes], but does not check that the
+    /// points are in the prime order subgroup. This is significantly faster,
+    /// and must only be used on data that was produced by
+    /// [CipherBatch::to_compact_bytes] from valid ciphers, e.g., a wallet's own
+    /// storage.
+    pub fn from_compact_bytes_unchecked(bytes: &[u8]) -> ParseResult<Self> {
+        Self::parse_exact(bytes, |source| C::bytes_to_curve_unchecked(source))
+    }
+
+    fn parse_exact(
+        bytes: &[u8],
+        read_point: impl Fn(&mut Cursor<&[u8]>) -> ParseResult<C>,
+    ) -> ParseResult<Self> {
+        let mut source = Cursor::new(bytes);
+        let batch = Self::deserial_with(&mut source, read_point)?;
+        if source.position() != bytes.len() as u64 {
+            bail!("Trailing bytes after the ciphers.");
+        }
+        Ok(batch)
+    }
+
+    fn deserial_with<R: ReadBytesExt>(
+        source: &mut R,
+        read_point: impl Fn(&mut R) -> ParseResult<C>,
+    ) -> ParseResult<Self> {
+        let len: u32 = source.get()?;
+        let num_points = 2 * len as usize;
+        let bitmap = deserial_bytes(source, bitmap_length(num_points))?;
+        if num_points % 8 != 0 && bitmap[bitmap.len() - 1] >> (num_points % 8) != 0 {
+            bail!("Padding bits of the bitmap must be zero.");
+        }
+        let mut read = |i: usize| -> ParseResult<C> {
+            if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                Ok(C::zero_point())
+            } else {
+                let point = read_point(source)?;
+                if point.is_zero_point() {
+                    bail!("The identity must not be encoded explicitly.");
+                }
+                Ok(point)
+            }
+        };
+        let mut ciphers = safe_with_capacity(len as usize);
+        for i in 0..len as usize {
+            let c0 = read(2 * i)?;
+            let c1 = read(2 * i + 1)?;
+            ciphers.push(Cipher(c0, c1));
+        }
+        Ok(CipherBatch { ciphers })
+    }
+}
+
+/// The number of bytes needed for a bitmap with one bit per point.
+fn bitmap_length(num_points: usize) -> usize { (num_points + 7) / 8 }
+
+impl<C: Curve> Serial for CipherBatch<C> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&(self.ciphers.len() as u32));
+        let points = || {
+            self.ciphers
+                .iter()
+                .flat_map(|c| once(&c.0).chain(once(&c.1)))
+        };
+        let mut bitmap = vec![0u8; bitmap_length(2 * self.ciphers.len())];
+        for (i, point) in points().enumerate() {
+            if point.is_zero_point() {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+        out.write_all(&bitmap)
+            .expect("Writing to buffer should succeed.");
+        for point in points().filter(|p| !p.is_zero_point()) {
+            out.put(point);
+        }
+    }
+}
+
+impl<C: Curve> Deserial for CipherBatch<C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        Self::deserial_with(source, |source| source.get())
+    }
+}
+
+impl<C: Curve> From<Vec<Cipher<C>>> for CipherBatch<C> {
+    fn from(ciphers: Vec<Cipher<C>>) -> Self { CipherBatch { ciphers } }
+}
+
+impl<C: Curve> From<CipherBatch<C>> for Vec<Cipher<C>> {
+    fn from(batch: CipherBatch<C>) -> Self { batch.ciphers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_cipher_batch_roundtrip() {
+        let mut csprng = thread_rng();
+        let mut ciphers = Vec::new();
+        for i in 0..21 {
+            let c = Cipher::<G1>::generate(&mut csprng);
+            match i % 3 {
+                0 => ciphers.push(c),
+                1 => ciphers.push(Cipher(G1::zero_point(), c.1)),
+                _ => ciphers.push(Cipher(G1::zero_point(), G1::zero_point())),
+            }
+        }
+        let batch = CipherBatch::new(ciphers.clone());
+        let bytes = batch.to_compact_bytes();
+        // 7 ciphers with two points, 7 with one, and 7 with none.
+        let expected_len = 4 + bitmap_length(42) + 21 * G1::GROUP_ELEMENT_LENGTH;
+        assert_eq!(bytes.len(), expected_len);
+        assert!(bytes.len() < to_bytes(&ciphers).len());
+
+        let parsed = CipherBatch::<G1>::from_compact_bytes(&bytes).expect("Should parse.");
+        assert_eq!(Vec::<Cipher<G1>>::from(parsed), ciphers);
+        let parsed =
+            CipherBatch::<G1>::from_compact_bytes_unchecked(&bytes).expect("Should parse.");
+        assert_eq!(parsed, batch);
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(CipherBatch::<G1>::from_compact_bytes(&trailing).is_err());
+        let mut padding = bytes;
+        padding[4 + bitmap_length(42) - 1] |= 0x80;
+        assert!(CipherBatch::<G1>::from_compact_bytes(&padding).is_err());
+    }
+}
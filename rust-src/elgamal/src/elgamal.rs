@@ -143,7 +143,7 @@ pub fn encrypt_in_chunks_given_generator<C: Curve, R: Rng>(
     csprng: &mut R,
 ) -> Vec<(Cipher<C>, Randomness<C>)> {
     let chunks = value_to_chunks::<C>(val, chunk_size);
-    pk.encrypt_exponent_vec_given_generator(&chunks, generator, csprng)
+    PrecomputedPublicKey::new(pk, generator).encrypt_exponent_vec(&chunks, csprng)
 }
 
 /// Encrypt a single `u64` value in chunks in the exponent of the given
@@ -202,6 +202,7 @@ pub fn decrypt_from_chunks_given_table<C: Curve>(
 mod tests {
     use super::*;
     use crate::message::*;
+    use curve_arithmetic::RistrettoPoint;
     use ff::Field;
     use pairing::bls12_381::{G1, G2};
     use rand::{rngs::ThreadRng, Rng};
@@ -231,6 +232,11 @@ mod tests {
     #[test]
     fn encrypt_decrypt_success_g2() { test_encrypt_decrypt_success_generic::<G2>() }
 
+    #[test]
+    fn encrypt_decrypt_success_ristretto() {
+        test_encrypt_decrypt_success_generic::<RistrettoPoint>()
+    }
+
     // This is a generic helper function that tests encryption/decryption in chunks.
     // It is parameterized by a curve, and the intention is that concrete tests are
     // going to use explicit curve instances.
@@ -262,6 +268,11 @@ mod tests {
         test_encrypt_decrypt_exponent_success_generic::<G2>()
     }
 
+    #[test]
+    fn encrypt_decrypt_exponent_success_ristretto() {
+        test_encrypt_decrypt_exponent_success_generic::<RistrettoPoint>()
+    }
+
     // This is a generic helper function that tests encryption/decryption in chunks.
     // It is parameterized by a curve, and the intention is that concrete tests are
     // going to use explicit curve instances.
@@ -285,6 +296,9 @@ mod tests {
     #[test]
     fn chunking_test_g1() { test_chunking_generic::<G1>() }
 
+    #[test]
+    fn chunking_test_ristretto() { test_chunking_generic::<RistrettoPoint>() }
+
     // This is a generic helper function that tests encryption/decryption in chunks.
     // It is parameterized by a curve, and the intention is that concrete tests are
     // going to use explicit curve instances.
@@ -313,4 +327,9 @@ mod tests {
 
     #[test]
     fn chunked_encrypt_decrypt_test_g1() { test_chunked_encrypt_decrypt_generic::<G1>() }
+
+    #[test]
+    fn chunked_encrypt_decrypt_test_ristretto() {
+        test_chunked_encrypt_decrypt_generic::<RistrettoPoint>()
+    }
 }
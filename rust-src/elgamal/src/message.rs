@@ -6,7 +6,7 @@ use crypto_common::*;
 use crypto_common_derive::*;
 use curve_arithmetic::Curve;
 
-#[derive(Debug, PartialEq, Eq, Serialize, SerdeBase16Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, SerdeBase16Serialize)]
 #[repr(transparent)]
 /// Message to be encrypted. This is a simple wrapper around a group element,
 /// but we use it for added type safety.
@@ -7,7 +7,7 @@ use crate::{cipher::*, message::*, secret::*};
 
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{multiexp, Curve, Value};
+use curve_arithmetic::{multiexp, Curve, MultiExpTable, Value};
 
 /// Elgamal public key .
 #[derive(Copy, Clone, Eq, PartialEq, Serialize, SerdeBase16Serialize)]
@@ -145,6 +145,64 @@ impl<C: Curve> PublicKey<C> {
     }
 }
 
+/// A public key with precomputed tables for encrypting in the exponent of a
+/// fixed generator `h`, i.e., for computing `g^r` and `key^r * h^e`. The tables
+/// are computed once in [PrecomputedPublicKey::new], which saves that work in
+/// every encryption with the key, e.g., of the chunks of a value.
+#[derive(Debug, Clone)]
+pub struct PrecomputedPublicKey<C: Curve> {
+    key:             PublicKey<C>,
+    /// The table for the generator of the key.
+    generator_table: MultiExpTable<C>,
+    /// The table for the key and `h`.
+    key_table:       MultiExpTable<C>,
+}
+
+impl<C: Curve> PrecomputedPublicKey<C> {
+    /// Precompute the tables for encrypting in the exponent of `h` with the
+    /// given key.
+    pub fn new(key: &PublicKey<C>, h: &C) -> Self {
+        PrecomputedPublicKey {
+            key:             *key,
+            generator_table: MultiExpTable::new(&[key.generator]),
+            key_table:       MultiExpTable::new(&[key.key, *h]),
+        }
+    }
+
+    /// The public key the tables are for.
+    pub fn key(&self) -> &PublicKey<C> { &self.key }
+
+    /// Same as [PublicKey::encrypt_exponent_rand_given_generator] with the
+    /// generator of the tables, using the precomputed tables.
+    pub fn encrypt_exponent_rand<T>(
+        &self,
+        e: &Value<C>,
+        csprng: &mut T,
+    ) -> (Cipher<C>, Randomness<C>)
+    where
+        T: Rng, {
+        let randomness = C::generate_scalar(csprng);
+        let g = self.generator_table.multiexp(&[randomness]);
+        let s = self.key_table.multiexp(&[randomness, *e.as_ref()]);
+        (Cipher(g, s), Randomness::new(randomness))
+    }
+
+    /// Same as [PublicKey::encrypt_exponent_vec_given_generator] with the
+    /// generator of the tables, using the precomputed tables.
+    pub fn encrypt_exponent_vec<'a, T, I>(
+        &self,
+        es: I,
+        csprng: &mut T,
+    ) -> Vec<(Cipher<C>, Randomness<C>)>
+    where
+        T: Rng,
+        I: IntoIterator<Item = &'a Value<C>>, {
+        es.into_iter()
+            .map(|e| self.encrypt_exponent_rand(e, csprng))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,4 +227,27 @@ mod tests {
 
     macro_test_key_to_byte_conversion!(key_to_byte_conversion_g1, G1);
     macro_test_key_to_byte_conversion!(key_to_byte_conversion_g2, G2);
+
+    #[test]
+    pub fn test_precomputed_public_key() {
+        let mut csprng = thread_rng();
+        let sk: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let h = G1::generate(&mut csprng);
+        let precomputed = PrecomputedPublicKey::new(&pk, &h);
+        let values: Vec<Value<G1>> = (0..8).map(|_| Value::generate(&mut csprng)).collect();
+        for (value, (cipher, randomness)) in values
+            .iter()
+            .zip(precomputed.encrypt_exponent_vec(&values, &mut csprng))
+        {
+            assert_eq!(
+                cipher,
+                pk.hide(randomness.as_ref(), &Message {
+                    value: h.mul_by_scalar(value.as_ref()),
+                }),
+                "Encryption with the precomputed key gives a different cipher."
+            );
+            assert_eq!(sk.decrypt(&cipher).value, h.mul_by_scalar(value.as_ref()));
+        }
+    }
 }
@@ -18,15 +18,15 @@ pub struct SecretKey<C: Curve> {
     pub scalar:    C::Scalar,
 }
 
-// THIS IS COMMENTED FOR NOW FOR COMPATIBILITY WITH BLS CURVE IMPLEMENTATION
-// ONCE WE HAVE TAKEN OVER THE SOURCE OF THE CURVE THIS SHOULD BE IMPLEMENTED
-// Overwrite secret key material with null bytes when it goes out of scope.
-//
-// impl Drop for SecretKey {
-// fn drop(&mut self) {
-// (self.0).into_repr().0.clear();
-// }
-// }
+/// Only the scalar is overwritten, the generator is not secret.
+impl<C: Curve> Zeroize for SecretKey<C> {
+    fn zeroize(&mut self) { volatile_overwrite(&mut self.scalar, C::Scalar::zero()) }
+}
+
+/// Overwrite secret key material with zeros when it goes out of scope.
+impl<C: Curve> Drop for SecretKey<C> {
+    fn drop(&mut self) { self.zeroize() }
+}
 
 pub type BabyStepGiantStepTable = HashMap<Vec<u8>, u64>;
 
@@ -52,6 +52,19 @@ impl<C: Curve> Serial for BabyStepGiantStep<C> {
     }
 }
 
+/// The table is serialized as its size, the inverse point, and the entries
+/// of the table, each a point followed by its exponent.
+///
+/// The table keeps the points of its entries in serialized form, since
+/// lookups are by serialization. Deserializing a table therefore only decodes
+/// the inverse point, and there is no point decompression to skip for tables
+/// from a trusted source.
+impl<C: Curve> SerialSized for BabyStepGiantStep<C> {
+    fn serial_length(&self) -> usize {
+        8 + C::GROUP_ELEMENT_LENGTH + self.table.len() * (C::GROUP_ELEMENT_LENGTH + 8)
+    }
+}
+
 impl<C: Curve> Deserial for BabyStepGiantStep<C> {
     fn deserial<R: ReadBytesExt>(source: &mut R) -> Result<Self> {
         let m: u64 = source.get()?;
@@ -60,7 +73,10 @@ impl<C: Curve> Deserial for BabyStepGiantStep<C> {
         for _ in 0..m {
             let mut k = vec![0; C::GROUP_ELEMENT_LENGTH];
             source.read_exact(&mut k)?;
-            let v = source.get()?;
+            let v: u64 = source.get()?;
+            if v >= m {
+                bail!("Exponent {} in a table of size {}.", v, m)
+            }
             if table.insert(k, v).is_some() {
                 bail!("Duplicate element found during deserialization.")
             }
@@ -89,6 +105,41 @@ impl<C: Curve> BabyStepGiantStep<C> {
         }
     }
 
+    /// Construct an instance from a precomputed table, e.g., one that was
+    /// stored separately from the other data of the instance. The table must
+    /// map the serializations of the multiples `j * base` to `j`, for `j` from
+    /// 0 to the size of the table minus 1. Only the first and the last entries
+    /// are checked, since checking all of them is as expensive as computing the
+    /// table.
+    pub fn from_table(base: &C, table: BabyStepGiantStepTable) -> Result<Self> {
+        let m = table.len() as u64;
+        if m == 0 {
+            bail!("The table must not be empty.")
+        }
+        if table.get(&to_bytes(&C::zero_point())) != Some(&0) {
+            bail!("The table does not map the zero point to 0.")
+        }
+        let last = base.mul_by_scalar(&C::scalar_from_u64(m - 1));
+        if table.get(&to_bytes(&last)) != Some(&(m - 1)) {
+            bail!("The table does not consist of the multiples of the base.")
+        }
+        Ok(Self {
+            table,
+            m,
+            inverse_point: last.plus_point(base).inverse_point(),
+        })
+    }
+
+    /// Size of the table.
+    pub fn m(&self) -> u64 { self.m }
+
+    /// The precomputed table, mapping the serialization of `j * base` to `j`.
+    pub fn table(&self) -> &BabyStepGiantStepTable { &self.table }
+
+    /// Take the precomputed table, e.g., to store it or to construct another
+    /// instance with [BabyStepGiantStep::from_table].
+    pub fn into_table(self) -> BabyStepGiantStepTable { self.table }
+
     /// Compute the discrete log using the instance. This function's performance
     /// is linear in `l / m` where `l` is the value stored in the exponent of
     /// `v`, and `m` is the size of the table.
@@ -114,6 +165,151 @@ impl<C: Curve> BabyStepGiantStep<C> {
     }
 }
 
+/// Pollard's kangaroo (lambda) method for computing discrete logarithms in the
+/// interval `[0, bound)`. In contrast to [BabyStepGiantStep] it only stores a
+/// few points, but computing a logarithm takes up to about `4 * sqrt(bound)`
+/// group operations. The method is probabilistic. An attempt fails with small
+/// probability, in which case it is repeated with a different walk.
+///
+/// A tame kangaroo starts at `bound * base` and jumps a fixed number of times,
+/// where the size of each jump is determined by the point it is at. A wild
+/// kangaroo starts at the point whose logarithm is computed and jumps the same
+/// way. If it lands on a point the tame kangaroo visited it follows the same
+/// path and ends up where the tame kangaroo stopped, which determines the
+/// logarithm from the distances the two have travelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kangaroo<C: Curve> {
+    /// Upper bound of the logarithms that are computed.
+    bound:      u64,
+    /// The possible jumps, the multiples `2^i * base` for `i < jumps.len()`.
+    jumps:      Vec<C>,
+    /// Number of jumps of the tame kangaroo.
+    tame_jumps: u64,
+    /// The point `bound * base` where the tame kangaroo starts.
+    start:      C,
+    /// The distance travelled by the tame kangaroo of the first attempt, and
+    /// the point where it stopped.
+    trap:       (u64, C),
+}
+
+impl<C: Curve> Kangaroo<C> {
+    /// Precompute the jumps for computing logarithms in `[0, bound)`, and the
+    /// path of the tame kangaroo of the first attempt.
+    pub fn new(base: &C, bound: u64) -> Self {
+        let bound = std::cmp::max(bound, 1);
+        // The mean jump size should be about half the square root of the bound.
+        let mean_target = std::cmp::max((bound as f64).sqrt() as u64 / 2, 1);
+        let mut num_jumps = 1;
+        while ((1u64 << num_jumps) - 1) / num_jumps < mean_target {
+            num_jumps += 1;
+        }
+        let mean_jump = ((1u64 << num_jumps) - 1) / num_jumps;
+        let mut jumps = Vec::with_capacity(num_jumps as usize);
+        let mut jump = *base;
+        for _ in 0..num_jumps {
+            jumps.push(jump);
+            jump = jump.plus_point(&jump);
+        }
+        let start = base.mul_by_scalar(&C::scalar_from_u64(bound));
+        let mut kangaroo = Kangaroo {
+            bound,
+            jumps,
+            // With four times as many jumps as the mean jump size the wild
+            // kangaroo misses the path of the tame one with probability about
+            // e^-4.
+            tame_jumps: 4 * mean_jump,
+            start,
+            trap: (0, start),
+        };
+        kangaroo.trap = kangaroo.tame(0);
+        kangaroo
+    }
+
+    /// Upper bound of the logarithms that are computed.
+    pub fn bound(&self) -> u64 { self.bound }
+
+    /// The index of the jump from the given point, in the walk of the given
+    /// attempt.
+    fn jump_index(&self, point: &C, attempt: u64) -> usize {
+        let bytes = to_bytes(point);
+        let mut last = [0u8; 8];
+        last.copy_from_slice(&bytes[bytes.len() - 8..]);
+        let h = u64::from_le_bytes(last)
+            .wrapping_add(attempt)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        ((h >> 32) % self.jumps.len() as u64) as usize
+    }
+
+    /// The distance travelled by the tame kangaroo of the given attempt, and
+    /// the point where it stops.
+    fn tame(&self, attempt: u64) -> (u64, C) {
+        let mut point = self.start;
+        let mut distance = 0u64;
+        for _ in 0..self.tame_jumps {
+            let i = self.jump_index(&point, attempt);
+            point = point.plus_point(&self.jumps[i]);
+            distance += 1 << i;
+        }
+        (distance, point)
+    }
+
+    /// Let the wild kangaroo jump from `v` until it reaches the trap, or is
+    /// past it.
+    fn wild(&self, v: &C, attempt: u64, trap: &(u64, C)) -> Option<u64> {
+        let (tame_distance, trap_point) = trap;
+        // The logarithm is `bound + tame_distance - distance`, which is
+        // non-negative.
+        let limit = self.bound.saturating_add(*tame_distance);
+        let mut point = *v;
+        let mut distance = 0u64;
+        while distance <= limit {
+            if point == *trap_point {
+                return Some(limit - distance);
+            }
+            let i = self.jump_index(&point, attempt);
+            point = point.plus_point(&self.jumps[i]);
+            distance += 1 << i;
+        }
+        None
+    }
+
+    /// Compute the discrete logarithm of `v`, which must be less than the
+    /// bound. Otherwise the function will appear not to terminate.
+    pub fn discrete_log(&self, v: &C) -> u64 {
+        if let Some(x) = self.wild(v, 0, &self.trap) {
+            return x;
+        }
+        for attempt in 1..=u64::MAX {
+            let trap = self.tame(attempt);
+            if let Some(x) = self.wild(v, attempt, &trap) {
+                return x;
+            }
+        }
+        unreachable!("It should not be feasible to do 2^64 attempts.")
+    }
+}
+
+/// The method for computing discrete logarithms when decrypting values in the
+/// exponent, which trades memory for computation.
+#[derive(Debug, Clone, Copy)]
+pub enum DlogMethod<'a, C: Curve> {
+    /// Baby step giant step with a precomputed table. This is the faster
+    /// method, but the table needs memory proportional to its size.
+    BabyStepGiantStep(&'a BabyStepGiantStep<C>),
+    /// Pollard's kangaroo method, which needs almost no memory.
+    Kangaroo(&'a Kangaroo<C>),
+}
+
+impl<'a, C: Curve> DlogMethod<'a, C> {
+    /// Compute the discrete logarithm of `v` with the method.
+    pub fn discrete_log(&self, v: &C) -> u64 {
+        match self {
+            DlogMethod::BabyStepGiantStep(bsgs) => bsgs.discrete_log(v),
+            DlogMethod::Kangaroo(kangaroo) => kangaroo.discrete_log(v),
+        }
+    }
+}
+
 impl<C: Curve> SecretKey<C> {
     pub fn decrypt(&self, c: &Cipher<C>) -> Message<C> {
         let x = c.0; // k * g
@@ -146,6 +342,13 @@ impl<C: Curve> SecretKey<C> {
         bsgs.discrete_log(&dec)
     }
 
+    /// Same as [SecretKey::decrypt_exponent], but compute the discrete
+    /// logarithm with the given method.
+    pub fn decrypt_exponent_with(&self, c: &Cipher<C>, method: DlogMethod<C>) -> u64 {
+        let dec = self.decrypt(c).value;
+        method.discrete_log(&dec)
+    }
+
     /// Generate a `SecretKey` from a `csprng`.
     pub fn generate<T: Rng>(generator: &C, csprng: &mut T) -> Self {
         SecretKey {
@@ -207,4 +410,48 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_bsgs_from_table() {
+        let mut csprng = thread_rng();
+        let base = G1::generate(&mut csprng);
+        let bsgs = BabyStepGiantStep::<G1>::new(&base, 1 << 8);
+        let from_table = BabyStepGiantStep::from_table(&base, bsgs.table().clone())
+            .expect("The table should be accepted.");
+        assert_eq!(from_table, bsgs);
+        let value = 123_456u64;
+        let v = base.mul_by_scalar(&G1::scalar_from_u64(value));
+        assert_eq!(from_table.discrete_log(&v), value);
+        // The table is specific to the base.
+        let other = G1::generate(&mut csprng);
+        assert!(BabyStepGiantStep::from_table(&other, bsgs.into_table()).is_err());
+        assert!(BabyStepGiantStep::<G1>::from_table(&base, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_kangaroo() {
+        let mut csprng = thread_rng();
+        let base = G1::generate(&mut csprng);
+        let bound = 1 << 16;
+        let kangaroo = Kangaroo::new(&base, bound);
+        let bsgs = BabyStepGiantStep::new(&base, 1 << 8);
+        let mut values = vec![0, 1, 2, 12345, bound - 1];
+        values.extend((0..20).map(|_| csprng.gen_range(0, bound)));
+        for x in values {
+            let v = base.mul_by_scalar(&G1::scalar_from_u64(x));
+            assert_eq!(kangaroo.discrete_log(&v), x);
+            assert_eq!(DlogMethod::Kangaroo(&kangaroo).discrete_log(&v), x);
+            assert_eq!(DlogMethod::BabyStepGiantStep(&bsgs).discrete_log(&v), x);
+        }
+    }
+
+    #[test]
+    fn test_bsgs_serial_length() {
+        let bsgs = BabyStepGiantStep::<G1>::new(&G1::generate(&mut thread_rng()), 1 << 10);
+        let bytes = to_bytes(&bsgs);
+        assert_eq!(bsgs.serial_length(), bytes.len());
+        let mut buf = vec![0u8; bytes.len()];
+        assert_eq!(serial_into_slice(&bsgs, &mut buf), Ok(bytes.len()));
+        assert_eq!(buf, bytes);
+    }
 }
@@ -118,6 +118,7 @@ unsafe extern "C" fn make_encrypted_transfer_data(
     let mut csprng = thread_rng();
 
     let data = match make_transfer_data(
+        &ChallengeContext::Legacy,
         &ctx,
         &receiver_pk,
         &sender_sk,
@@ -202,7 +203,14 @@ unsafe extern "C" fn verify_encrypted_transfer(
         proof,
     };
 
-    if verify_transfer_data(ctx, &receiver_pk, &sender_pk, &initial, &transfer_data) {
+    if verify_transfer_data(
+        &ChallengeContext::Legacy,
+        ctx,
+        &receiver_pk,
+        &sender_pk,
+        &initial,
+        &transfer_data,
+    ) {
         1
     } else {
         0
@@ -232,6 +240,7 @@ unsafe extern "C" fn make_sec_to_pub_data(
     let mut csprng = thread_rng();
 
     let data = match make_sec_to_pub_transfer_data(
+        &ChallengeContext::Legacy,
         &ctx,
         &sender_sk,
         &input_amount,
@@ -305,7 +314,13 @@ unsafe extern "C" fn verify_sec_to_pub_transfer(
         proof,
     };
 
-    if verify_sec_to_pub_transfer_data(ctx, &sender_pk, &initial, &transfer_data) {
+    if verify_sec_to_pub_transfer_data(
+        &ChallengeContext::Legacy,
+        ctx,
+        &sender_pk,
+        &initial,
+        &transfer_data,
+    ) {
         1
     } else {
         0
@@ -7,10 +7,12 @@ extern crate itertools;
 
 mod ffi;
 pub mod proofs;
+pub mod report;
+pub mod state;
 pub mod types;
 
 use crate::types::{CHUNK_SIZE as CHUNK_SIZE_ENC_TRANS, *};
-use crypto_common::types::Amount;
+use crypto_common::{types::Amount, CancellationToken};
 use curve_arithmetic::*;
 use elgamal::*;
 use id::types::*;
@@ -111,8 +113,19 @@ pub fn decrypt_amount<C: Curve>(
     sk: &SecretKey<C>,
     amount: &EncryptedAmount<C>,
 ) -> Amount {
-    let low_chunk = sk.decrypt_exponent(&amount.encryptions[0], table);
-    let hi_chunk = sk.decrypt_exponent(&amount.encryptions[1], table);
+    decrypt_amount_with(DlogMethod::BabyStepGiantStep(table), sk, amount)
+}
+
+/// Same as [decrypt_amount], but compute the discrete logarithms of the chunks
+/// with the given method, e.g., with [Kangaroo] when there is not enough
+/// memory for a large table. The same assumptions apply.
+pub fn decrypt_amount_with<C: Curve>(
+    method: DlogMethod<C>,
+    sk: &SecretKey<C>,
+    amount: &EncryptedAmount<C>,
+) -> Amount {
+    let low_chunk = sk.decrypt_exponent_with(&amount.encryptions[0], method);
+    let hi_chunk = sk.decrypt_exponent_with(&amount.encryptions[1], method);
     Amount::from(CHUNK_SIZE_ENC_TRANS.chunks_to_u64([low_chunk, hi_chunk].iter().copied()))
 }
 
@@ -134,6 +147,7 @@ impl<C: Curve> EncryptedAmount<C> {
 ///
 /// The arguments are
 ///
+/// - the context of the proofs, which the verifier must use as well
 /// - global context with parameters for generating proofs, and generators for
 ///   encrypting amounts.
 /// - public key of the receiver of the transfer
@@ -145,20 +159,48 @@ impl<C: Curve> EncryptedAmount<C> {
 /// This could be because the `to_transfer` is too large, or because of some
 /// other data inconsistency that means a proof could not be produced.
 pub fn make_transfer_data<C: Curve, R: Rng>(
+    context: &ChallengeContext,
     ctx: &GlobalContext<C>,
     receiver_pk: &PublicKey<C>,
     sender_sk: &SecretKey<C>,
     input_amount: &AggregatedDecryptedAmount<C>,
     to_transfer: Amount,
     csprng: &mut R,
+) -> Option<EncryptedAmountTransferData<C>> {
+    make_transfer_data_cancellable(
+        context,
+        ctx,
+        receiver_pk,
+        sender_sk,
+        input_amount,
+        to_transfer,
+        csprng,
+        &CancellationToken::new(),
+    )
+}
+
+/// Like [make_transfer_data], but proof generation can be cancelled via the
+/// given token. The return value is also `None` if the token was cancelled,
+/// which the caller can check with
+/// [is_cancelled](CancellationToken::is_cancelled).
+#[allow(clippy::too_many_arguments)]
+pub fn make_transfer_data_cancellable<C: Curve, R: Rng>(
+    context: &ChallengeContext,
+    ctx: &GlobalContext<C>,
+    receiver_pk: &PublicKey<C>,
+    sender_sk: &SecretKey<C>,
+    input_amount: &AggregatedDecryptedAmount<C>,
+    to_transfer: Amount,
+    csprng: &mut R,
+    cancel: &CancellationToken,
 ) -> Option<EncryptedAmountTransferData<C>> {
     let sender_pk = &PublicKey::from(sender_sk);
-    let mut ro = RandomOracle::domain("EncryptedTransfer");
+    let mut ro = RandomOracle::domain_in_context(context, domains::ENCRYPTED_TRANSFER);
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"receiver_pk", &receiver_pk);
     ro.append_message(b"sender_pk", &sender_pk);
 
-    proofs::gen_enc_trans(
+    proofs::gen_enc_trans_cancellable(
         ctx,
         &mut ro,
         sender_pk,
@@ -169,6 +211,7 @@ pub fn make_transfer_data<C: Curve, R: Rng>(
         input_amount.agg_amount,
         to_transfer,
         csprng,
+        cancel,
     )
 }
 
@@ -176,6 +219,7 @@ pub fn make_transfer_data<C: Curve, R: Rng>(
 ///
 /// The arguments are
 ///
+/// - the context the proofs were produced in
 /// - global context with parameters for generating proofs, and generators for
 ///   encrypting amounts.
 /// - public key of the receiver of the transfer
@@ -186,14 +230,14 @@ pub fn make_transfer_data<C: Curve, R: Rng>(
 /// The return value is going to be `true` if verification succeeds and `false`
 /// if not.
 pub fn verify_transfer_data<C: Curve>(
+    context: &ChallengeContext,
     ctx: &GlobalContext<C>,
     receiver_pk: &PublicKey<C>,
     sender_pk: &PublicKey<C>,
     before_amount: &EncryptedAmount<C>,
     transfer_data: &EncryptedAmountTransferData<C>,
 ) -> bool {
-    // Fixme: Put context into the random oracle.
-    let mut ro = RandomOracle::domain("EncryptedTransfer");
+    let mut ro = RandomOracle::domain_in_context(context, domains::ENCRYPTED_TRANSFER);
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"receiver_pk", &receiver_pk);
     ro.append_message(b"sender_pk", &sender_pk);
@@ -215,6 +259,7 @@ pub fn verify_transfer_data<C: Curve>(
 ///
 /// The arguments are
 ///
+/// - the context of the proofs, which the verifier must use as well
 /// - global context with parameters for generating proofs, and generators for
 ///   encrypting amounts.
 /// - secret key of the sender (who is also the receiver)
@@ -225,20 +270,44 @@ pub fn verify_transfer_data<C: Curve>(
 /// This could be because the `to_transfer` is too large, or because of some
 /// other data inconsistency that means a proof could not be produced.
 pub fn make_sec_to_pub_transfer_data<C: Curve, R: Rng>(
+    context: &ChallengeContext,
     ctx: &GlobalContext<C>,
     sk: &SecretKey<C>,
     input_amount: &AggregatedDecryptedAmount<C>,
     to_transfer: Amount,
     csprng: &mut R,
+) -> Option<SecToPubAmountTransferData<C>> {
+    make_sec_to_pub_transfer_data_cancellable(
+        context,
+        ctx,
+        sk,
+        input_amount,
+        to_transfer,
+        csprng,
+        &CancellationToken::new(),
+    )
+}
+
+/// Like [make_sec_to_pub_transfer_data], but proof generation can be cancelled
+/// via the given token. The return value is also `None` if the token was
+/// cancelled, which the caller can check with
+/// [is_cancelled](CancellationToken::is_cancelled).
+pub fn make_sec_to_pub_transfer_data_cancellable<C: Curve, R: Rng>(
+    context: &ChallengeContext,
+    ctx: &GlobalContext<C>,
+    sk: &SecretKey<C>,
+    input_amount: &AggregatedDecryptedAmount<C>,
+    to_transfer: Amount,
+    csprng: &mut R,
+    cancel: &CancellationToken,
 ) -> Option<SecToPubAmountTransferData<C>> {
     let pk = &PublicKey::from(sk);
-    // FIXME: Put context into random oracle
-    let mut ro = RandomOracle::domain("SecToPubTransfer");
+    let mut ro = RandomOracle::domain_in_context(context, domains::SEC_TO_PUB_TRANSFER);
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"pk", &pk);
 
     // FIXME: Make arguments more in line between gen_sec_to_pub_trans and this.
-    proofs::gen_sec_to_pub_trans(
+    proofs::gen_sec_to_pub_trans_cancellable(
         ctx,
         &mut ro,
         pk,
@@ -248,6 +317,7 @@ pub fn make_sec_to_pub_transfer_data<C: Curve, R: Rng>(
         input_amount.agg_amount,
         to_transfer,
         csprng,
+        cancel,
     )
 }
 
@@ -257,6 +327,7 @@ pub fn make_sec_to_pub_transfer_data<C: Curve, R: Rng>(
 ///
 /// The arguments are
 ///
+/// - the context the proofs were produced in
 /// - global context with parameters for generating proofs, and generators for
 ///   encrypting amounts.
 /// - public key of the sender (who is also the receiver) of the transfer
@@ -267,13 +338,13 @@ pub fn make_sec_to_pub_transfer_data<C: Curve, R: Rng>(
 /// if not.
 
 pub fn verify_sec_to_pub_transfer_data<C: Curve>(
+    context: &ChallengeContext,
     ctx: &GlobalContext<C>,
     pk: &PublicKey<C>,
     before_amount: &EncryptedAmount<C>,
     transfer_data: &SecToPubAmountTransferData<C>,
 ) -> bool {
-    // Fixme: Put context into the random oracle.
-    let mut ro = RandomOracle::domain("SecToPubTransfer");
+    let mut ro = RandomOracle::domain_in_context(context, domains::SEC_TO_PUB_TRANSFER);
     ro.append_message(b"ctx", &ctx);
     ro.append_message(b"pk", &pk);
 
@@ -378,26 +449,34 @@ mod tests {
             agg_encrypted_amount: S_in_chunks.0.clone(),
             agg_index:            index,
         };
-        let transfer_data = make_transfer_data(
-            &context,
-            &pk_receiver,
-            &sk_sender,
-            &input_amount,
-            Amount::from(a),
-            &mut csprng,
-        )
-        .unwrap();
-
-        assert_eq!(
-            verify_transfer_data(
+        // A transfer only verifies in the context it was produced in.
+        let (in_context, other_network) = test_challenge_contexts("EncryptedTransfer");
+        let contexts = [ChallengeContext::Legacy, in_context, other_network];
+        for (i, challenge_context) in contexts.iter().enumerate() {
+            let transfer_data = make_transfer_data(
+                challenge_context,
                 &context,
                 &pk_receiver,
-                &pk_sender,
-                &S_in_chunks.0,
-                &transfer_data
-            ),
-            true
-        );
+                &sk_sender,
+                &input_amount,
+                Amount::from(a),
+                &mut csprng,
+            )
+            .expect("Transfer should be produced.");
+            for (j, verifier_context) in contexts.iter().enumerate() {
+                assert_eq!(
+                    verify_transfer_data(
+                        verifier_context,
+                        &context,
+                        &pk_receiver,
+                        &pk_sender,
+                        &S_in_chunks.0,
+                        &transfer_data
+                    ),
+                    i == j
+                );
+            }
+        }
     }
 
     #[test]
@@ -424,18 +503,49 @@ mod tests {
             agg_index:            index,
         };
 
-        let transfer_data = make_sec_to_pub_transfer_data(
-            &context,
-            &sk_sender,
-            &input_amount,
-            Amount::from(a),
-            &mut csprng,
-        )
-        .unwrap();
+        // A transfer only verifies in the context it was produced in.
+        let (in_context, other_network) = test_challenge_contexts("SecToPubTransfer");
+        let contexts = [ChallengeContext::Legacy, in_context, other_network];
+        for (i, challenge_context) in contexts.iter().enumerate() {
+            let transfer_data = make_sec_to_pub_transfer_data(
+                challenge_context,
+                &context,
+                &sk_sender,
+                &input_amount,
+                Amount::from(a),
+                &mut csprng,
+            )
+            .expect("Transfer should be produced.");
+            for (j, verifier_context) in contexts.iter().enumerate() {
+                assert_eq!(
+                    verify_sec_to_pub_transfer_data(
+                        verifier_context,
+                        &context,
+                        &pk_sender,
+                        &S_in_chunks.0,
+                        &transfer_data
+                    ),
+                    i == j
+                );
+            }
+        }
+    }
 
-        assert_eq!(
-            verify_sec_to_pub_transfer_data(&context, &pk_sender, &S_in_chunks.0, &transfer_data),
-            true
-        );
+    /// A context for the tests of proofs in context, and the same context on
+    /// another network.
+    fn test_challenge_contexts(statement_type: &str) -> (ChallengeContext, ChallengeContext) {
+        let context = ProofContext {
+            network_id:       b"testnet".to_vec(),
+            protocol_version: 1,
+            statement_type:   statement_type.to_owned(),
+        };
+        let other_network = ProofContext {
+            network_id: b"mainnet".to_vec(),
+            ..context.clone()
+        };
+        (
+            ChallengeContext::Versioned(context),
+            ChallengeContext::Versioned(other_network),
+        )
     }
 }
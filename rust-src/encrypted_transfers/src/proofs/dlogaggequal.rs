@@ -21,11 +21,43 @@ pub struct DlogAndAggregateDlogsEqual<C: Curve> {
 
 #[derive(Debug, Serialize)]
 pub struct Witness<C: Curve> {
+    /// One per aggregate dlog, i.e., per chunk of an amount.
     #[size_length = 4]
+    #[max_size = 64]
     witnesses:      Vec<Vec<C::Scalar>>,
     witness_common: C::Scalar, // For equality
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_vec_vec",
+        deserialize_with = "base16_decode_vec_vec"
+    )]
+    witnesses:      Vec<Vec<C::Scalar>>,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    witness_common: C::Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for Witness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 #[allow(clippy::type_complexity)]
 impl<C: Curve> SigmaProtocol for DlogAndAggregateDlogsEqual<C> {
     type CommitMessage = (C, Vec<C>);
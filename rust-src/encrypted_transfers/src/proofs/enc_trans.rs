@@ -102,14 +102,52 @@ pub struct EncTrans<C: Curve> {
 pub struct EncTransWitness<C: Curve> {
     /// The common witness for both dlog and elc-dec
     witness_common:  C::Scalar,
-    /// For EncExp/ComEq's involving a_i
+    /// For EncExp/ComEq's involving a_i. There is one per chunk of the
+    /// amount, so at most 64.
     #[size_length = 4]
+    #[max_size = 64]
     witness_encexp1: Vec<ComEqWitness<C>>,
     /// For EncExp/ComEq's involving s_i'
     #[size_length = 4]
+    #[max_size = 64]
     witness_encexp2: Vec<ComEqWitness<C>>,
 }
 
+/// The structured JSON representation of [EncTransWitness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "EncTransWitness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct EncTransWitnessJson<C: Curve> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    witness_common:  C::Scalar,
+    #[serde(
+        serialize_with = "structured_encode_vec",
+        deserialize_with = "structured_decode_vec"
+    )]
+    witness_encexp1: Vec<ComEqWitness<C>>,
+    #[serde(
+        serialize_with = "structured_encode_vec",
+        deserialize_with = "structured_decode_vec"
+    )]
+    witness_encexp2: Vec<ComEqWitness<C>>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for EncTransWitness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        EncTransWitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        EncTransWitnessJson::deserialize(des)
+    }
+}
+
 /// Secret values which the [EncTrans] proof talks about. For constructing
 /// proofs these must match the public values that are part of the [EncTrans]
 /// structure.
@@ -3,10 +3,10 @@
 #![allow(non_snake_case)]
 use crate::{proofs::enc_trans::*, types::*};
 use bulletproofs::range_proof::{
-    prove_given_scalars as bulletprove, verify_efficient,
+    prove_given_scalars as bulletprove, verify_efficient, BatchVerifier,
     VerificationError as BulletproofVerificationError,
 };
-use crypto_common::types::Amount;
+use crypto_common::{types::Amount, CancellationToken};
 use curve_arithmetic::{Curve, Value};
 use elgamal::{Cipher, PublicKey, Randomness, SecretKey};
 use id::{
@@ -189,6 +189,38 @@ pub fn gen_enc_trans<C: Curve, R: Rng>(
     s: Amount,
     a: Amount,
     csprng: &mut R,
+) -> Option<EncryptedAmountTransferData<C>> {
+    gen_enc_trans_cancellable(
+        context,
+        ro,
+        pk_sender,
+        sk_sender,
+        pk_receiver,
+        index,
+        S,
+        s,
+        a,
+        csprng,
+        &CancellationToken::new(),
+    )
+}
+
+/// Like [gen_enc_trans], but the computation can be cancelled via the given
+/// token. The token is checked between the sigma protocol and the
+/// bulletproofs, and the function returns None if it has been cancelled.
+#[allow(clippy::too_many_arguments)]
+pub fn gen_enc_trans_cancellable<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk_sender: &PublicKey<C>,
+    sk_sender: &SecretKey<C>,
+    pk_receiver: &PublicKey<C>,
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+    cancel: &CancellationToken,
 ) -> Option<EncryptedAmountTransferData<C>> {
     if s < a {
         return None;
@@ -240,7 +272,9 @@ pub fn gen_enc_trans<C: Curve, R: Rng>(
         encexp1_secrets: a_secrets,
         encexp2_secrets: s_prime_secrets,
     };
+    cancel.check().ok()?;
     let sigma_proof = prove(ro, &protocol, secret, csprng)?;
+    cancel.check().ok()?;
     let cmm_key_bulletproof_a = CommitmentKey {
         g: *generator,
         h: pk_receiver.key,
@@ -273,6 +307,7 @@ pub fn gen_enc_trans<C: Curve, R: Rng>(
         &cmm_key_bulletproof_a,
         &A_rand_as_pedrand,
     )?;
+    cancel.check().ok()?;
 
     let bulletproof_s_prime = bulletprove(
         ro,
@@ -371,6 +406,37 @@ pub fn gen_sec_to_pub_trans<C: Curve, R: Rng>(
     s: Amount, // input amount
     a: Amount, // amount to send
     csprng: &mut R,
+) -> Option<SecToPubAmountTransferData<C>> {
+    gen_sec_to_pub_trans_cancellable(
+        context,
+        ro,
+        pk,
+        sk,
+        index,
+        S,
+        s,
+        a,
+        csprng,
+        &CancellationToken::new(),
+    )
+}
+
+/// Like [gen_sec_to_pub_trans], but the computation can be cancelled via the
+/// given token. The token is checked between the sigma protocol and the
+/// bulletproof, and the function returns None if it has been cancelled.
+#[allow(clippy::too_many_arguments)]
+#[allow(non_snake_case)]
+pub fn gen_sec_to_pub_trans_cancellable<C: Curve, R: Rng>(
+    context: &GlobalContext<C>,
+    ro: &mut RandomOracle,
+    pk: &PublicKey<C>,
+    sk: &SecretKey<C>,
+    index: EncryptedAmountAggIndex,
+    S: &Cipher<C>,
+    s: Amount,
+    a: Amount,
+    csprng: &mut R,
+    cancel: &CancellationToken,
 ) -> Option<SecToPubAmountTransferData<C>> {
     if s < a {
         return None;
@@ -407,7 +473,9 @@ pub fn gen_sec_to_pub_trans<C: Curve, R: Rng>(
         }],
         encexp2_secrets: s_prime_secrets,
     };
+    cancel.check().ok()?;
     let sigma_proof = prove(ro, &protocol, secret, csprng)?;
+    cancel.check().ok()?;
     let cmm_key_bulletproof_s_prime = CommitmentKey {
         g: *generator,
         h: pk.key,
@@ -537,6 +605,35 @@ pub fn verify_enc_trans<C: Curve>(
         h: pk_sender.key,
     };
 
+    // Both range proofs are checked in one batch. Only if the batch fails are
+    // they verified one by one, to tell which of them is invalid.
+    let mut batch_ro = ro.split();
+    let mut batch = BatchVerifier::new(&gens);
+    let batched = batch
+        .add(
+            &mut batch_ro,
+            u8::from(CHUNK_SIZE),
+            &commitments_a,
+            &transaction.proof.transfer_amount_correct_encryption,
+            &cmm_key_bulletproof_a,
+            &mut thread_rng(),
+        )
+        .and_then(|_| {
+            batch.add(
+                &mut batch_ro,
+                u8::from(CHUNK_SIZE),
+                &commitments_s_prime,
+                &transaction.proof.remaining_amount_correct_encryption,
+                &cmm_key_bulletproof_s_prime,
+                &mut thread_rng(),
+            )
+        })
+        .and_then(|_| batch.verify());
+    if batched.is_ok() {
+        *ro = batch_ro;
+        return Ok(());
+    }
+
     let first_bulletproof = verify_efficient(
         ro,
         u8::from(CHUNK_SIZE),
@@ -716,6 +813,42 @@ mod test {
         )
     }
 
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_enc_trans_cancelled() {
+        let mut csprng = thread_rng();
+        let sk_sender: SecretKey<G1> = SecretKey::generate_all(&mut csprng);
+        let pk_sender = PublicKey::from(&sk_sender);
+        let sk_receiver: SecretKey<G1> = SecretKey::generate(&pk_sender.generator, &mut csprng);
+        let pk_receiver = PublicKey::from(&sk_receiver);
+        let s = csprng.gen::<u64>();
+        let a = csprng.gen_range(0, s);
+
+        let context = GlobalContext::<SomeCurve>::generate_size(String::from("genesis_string"), 64);
+        let generator = context.encryption_in_exponent_generator();
+        let S = pk_sender.encrypt_exponent_given_generator(&Value::from(s), generator, &mut csprng);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let transaction = gen_enc_trans_cancellable(
+            &context,
+            &mut RandomOracle::empty(),
+            &pk_sender,
+            &sk_sender,
+            &pk_receiver,
+            csprng.gen::<u64>().into(),
+            &S,
+            Amount::from(s),
+            Amount::from(a),
+            &mut csprng,
+            &cancel,
+        );
+        assert!(
+            transaction.is_none(),
+            "A cancelled transfer should not be produced."
+        );
+    }
+
     #[allow(non_snake_case)]
     #[test]
     fn test_sec_to_pub() {
@@ -1,9 +1,9 @@
 //! Construction and verification of proofs for encrypted transfer transactions.
 
-// the following two modules are only there for reference if we ever need them,
-// they are not used.
+// the following module is only there for reference if we ever need it, it is
+// not used. Equality of discrete logarithms is proved with
+// `id::sigma_protocols::dlog_eq`.
 mod dlogaggequal;
-mod dlogeq;
 mod enc_trans;
 mod generate_proofs;
 
@@ -0,0 +1,320 @@
+//! Size and timing reports for encrypted transfers.
+//!
+//! Generating the proofs of an encrypted transfer takes long enough on mobile
+//! devices that wallets need to decide how to present it, e.g., whether to
+//! show a spinner or run it in the background. The functions in this module
+//! give the exact size of the transaction payloads, and an estimate of the
+//! worst-case time of preparing a transfer on a device. The time estimate is
+//! based on a [DeviceProfile], which is measured once on a reference device
+//! with [DeviceProfile::measure] and can then be stored and reused.
+use crate::{types::*, *};
+use crypto_common::{to_bytes, SerdeDeserialize, SerdeSerialize};
+use std::{
+    sync::atomic::{AtomicU8, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Number of chunks an amount is encrypted in.
+fn num_chunks() -> usize { 64 / usize::from(u8::from(CHUNK_SIZE_ENC_TRANS)) }
+
+/// Size of an account address.
+const ACCOUNT_ADDRESS_SIZE: usize = 32;
+
+/// Size of the challenge of a sigma protocol.
+const CHALLENGE_SIZE: usize = 32;
+
+/// Size of the payload of an encrypted transfer, including the transaction
+/// type and the address of the receiver. Encrypted transfers without a memo
+/// have transaction type 16.
+pub fn encrypted_transfer_payload_size<C: Curve>() -> usize {
+    1 + ACCOUNT_ADDRESS_SIZE
+        + 2 * encrypted_amount_size::<C>() // remaining and transfer amounts
+        + 8 // index
+        + accounting_proof_size::<C>(num_chunks(), num_chunks())
+        + 2 * range_proof_size::<C>()
+}
+
+/// Size of the payload of a transfer from the shielded to the public balance,
+/// including the transaction type.
+pub fn sec_to_pub_transfer_payload_size<C: Curve>() -> usize {
+    1 + encrypted_amount_size::<C>() // remaining amount
+        + 8 // transfer amount
+        + 8 // index
+        // the transferred amount is a single chunk, encrypted with zero randomness
+        + accounting_proof_size::<C>(1, num_chunks())
+        + range_proof_size::<C>()
+}
+
+fn encrypted_amount_size<C: Curve>() -> usize { 2 * num_chunks() * C::GROUP_ELEMENT_LENGTH }
+
+/// Size of the [EncTrans](proofs::EncTrans) proof with the given number of
+/// chunks of the transferred and the remaining amount.
+fn accounting_proof_size<C: Curve>(transfer_chunks: usize, remaining_chunks: usize) -> usize {
+    CHALLENGE_SIZE
+        + C::SCALAR_LENGTH
+        + 4
+        + transfer_chunks * 2 * C::SCALAR_LENGTH
+        + 4
+        + remaining_chunks * 2 * C::SCALAR_LENGTH
+}
+
+/// Size of a range proof of all the chunks of an amount.
+fn range_proof_size<C: Curve>() -> usize {
+    // The inner product proof halves the 64 generators in every round.
+    let rounds = 64u32.trailing_zeros() as usize;
+    4 * C::GROUP_ELEMENT_LENGTH
+        + 3 * C::SCALAR_LENGTH
+        + 4
+        + rounds * 2 * C::GROUP_ELEMENT_LENGTH
+        + 2 * C::SCALAR_LENGTH
+}
+
+/// Destination of the results of computations that are only timed, so that
+/// they are not optimized away. A store to an atomic cannot be removed by the
+/// compiler, and `std::hint::black_box` is not available on stable Rust.
+static MEASUREMENT_SINK: AtomicU8 = AtomicU8::new(0);
+
+/// Timings of the operations involved in preparing an encrypted transfer on a
+/// device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    /// Time of adding two points.
+    pub point_addition:            Duration,
+    /// Time of multiplying a point by a scalar.
+    pub scalar_multiplication:     Duration,
+    /// Time of generating the data of an encrypted transfer, including the
+    /// proofs.
+    pub encrypted_transfer_proof:  Duration,
+    /// Time of generating the data of a transfer from the shielded to the
+    /// public balance, including the proofs.
+    pub sec_to_pub_transfer_proof: Duration,
+}
+
+impl DeviceProfile {
+    /// Measure the timings on the current device. This generates the proofs
+    /// of an encrypted transfer and a transfer to the public balance, so it
+    /// takes a few seconds on slow devices.
+    pub fn measure<C: Curve, R: Rng>(context: &GlobalContext<C>, csprng: &mut R) -> Self {
+        const POINT_ADDITIONS: u32 = 1000;
+        const SCALAR_MULTIPLICATIONS: u32 = 100;
+
+        let point = C::generate(csprng);
+        let start = Instant::now();
+        let mut sum = C::zero_point();
+        for _ in 0..POINT_ADDITIONS {
+            sum = sum.plus_point(&point);
+        }
+        let point_addition = start.elapsed() / POINT_ADDITIONS;
+
+        let scalar = C::generate_scalar(csprng);
+        let start = Instant::now();
+        for _ in 0..SCALAR_MULTIPLICATIONS {
+            sum = sum.mul_by_scalar(&scalar);
+        }
+        let scalar_multiplication = start.elapsed() / SCALAR_MULTIPLICATIONS;
+        // Use the result so that the computations are not optimized away.
+        MEASUREMENT_SINK.store(to_bytes(&sum)[0], Ordering::Relaxed);
+
+        let sk = SecretKey::generate(context.elgamal_generator(), csprng);
+        let pk = PublicKey::from(&sk);
+        let receiver_sk = SecretKey::generate(context.elgamal_generator(), csprng);
+        let receiver_pk = PublicKey::from(&receiver_sk);
+        let agg_amount = Amount::from(u64::MAX);
+        let (agg_encrypted_amount, _) = encrypt_amount(context, &pk, agg_amount, csprng);
+        let input_amount = AggregatedDecryptedAmount {
+            agg_encrypted_amount,
+            agg_amount,
+            agg_index: EncryptedAmountAggIndex::from(0),
+        };
+        let to_transfer = Amount::from(u64::MAX / 2);
+
+        let start = Instant::now();
+        let _ = make_transfer_data(
+            &ChallengeContext::Legacy,
+            context,
+            &receiver_pk,
+            &sk,
+            &input_amount,
+            to_transfer,
+            csprng,
+        );
+        let encrypted_transfer_proof = start.elapsed();
+
+        let start = Instant::now();
+        let _ = make_sec_to_pub_transfer_data(
+            &ChallengeContext::Legacy,
+            context,
+            &sk,
+            &input_amount,
+            to_transfer,
+            csprng,
+        );
+        let sec_to_pub_transfer_proof = start.elapsed();
+
+        DeviceProfile {
+            point_addition,
+            scalar_multiplication,
+            encrypted_transfer_proof,
+            sec_to_pub_transfer_proof,
+        }
+    }
+}
+
+/// Size and worst-case timing of preparing a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReport {
+    /// Exact size of the transaction payload.
+    pub payload_size: usize,
+    /// Time of aggregating the incoming amounts with the self amount.
+    pub aggregation:  Duration,
+    /// Worst-case time of decrypting the incoming amounts.
+    pub decryption:   Duration,
+    /// Time of generating the transfer data with the proofs.
+    pub proving:      Duration,
+}
+
+impl TransferReport {
+    /// The worst-case total time of preparing the transfer.
+    pub fn total(&self) -> Duration { self.aggregation + self.decryption + self.proving }
+}
+
+/// Report for an encrypted transfer that uses the given number of incoming
+/// amounts which have not been decrypted yet, where decryption uses a
+/// [BabyStepGiantStep] table of the given size.
+pub fn encrypted_transfer_report<C: Curve>(
+    profile: &DeviceProfile,
+    num_incoming: u64,
+    table_size: u64,
+) -> TransferReport {
+    TransferReport {
+        payload_size: encrypted_transfer_payload_size::<C>(),
+        proving: profile.encrypted_transfer_proof,
+        ..input_report(profile, num_incoming, table_size)
+    }
+}
+
+/// Report for a transfer from the shielded to the public balance, see
+/// [encrypted_transfer_report].
+pub fn sec_to_pub_transfer_report<C: Curve>(
+    profile: &DeviceProfile,
+    num_incoming: u64,
+    table_size: u64,
+) -> TransferReport {
+    TransferReport {
+        payload_size: sec_to_pub_transfer_payload_size::<C>(),
+        proving: profile.sec_to_pub_transfer_proof,
+        ..input_report(profile, num_incoming, table_size)
+    }
+}
+
+/// The part of the report for preparing the input amount of a transfer.
+fn input_report(profile: &DeviceProfile, num_incoming: u64, table_size: u64) -> TransferReport {
+    let chunks = num_incoming.saturating_mul(num_chunks() as u64);
+    // Aggregating two encrypted amounts adds the two points of each chunk.
+    let aggregation = saturating_mul(profile.point_addition, chunks.saturating_mul(2));
+    // Decrypting a chunk multiplies by the secret key, and then takes one
+    // giant step for every `table_size` possible values of the chunk.
+    let chunk_values = 1u64 << u8::from(CHUNK_SIZE_ENC_TRANS);
+    let giant_steps = (chunk_values + table_size.max(1) - 1) / table_size.max(1);
+    let decryption = saturating_mul(profile.scalar_multiplication, chunks)
+        + saturating_mul(profile.point_addition, chunks.saturating_mul(giant_steps));
+    TransferReport {
+        payload_size: 0,
+        aggregation,
+        decryption,
+        proving: Duration::from_secs(0),
+    }
+}
+
+fn saturating_mul(d: Duration, n: u64) -> Duration {
+    let nanos = d.as_nanos().saturating_mul(u128::from(n));
+    Duration::from_nanos(std::cmp::min(nanos, u128::from(u64::MAX)) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+
+    #[test]
+    fn test_payload_sizes() {
+        let mut csprng = thread_rng();
+        let context = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let sk = SecretKey::generate(context.elgamal_generator(), &mut csprng);
+        let pk = PublicKey::from(&sk);
+        let agg_amount = Amount::from(csprng.gen::<u64>());
+        let (agg_encrypted_amount, _) = encrypt_amount(&context, &pk, agg_amount, &mut csprng);
+        let input_amount = AggregatedDecryptedAmount {
+            agg_encrypted_amount,
+            agg_amount,
+            agg_index: EncryptedAmountAggIndex::from(3),
+        };
+        let to_transfer = Amount::from(u64::from(agg_amount) / 2);
+
+        let data = make_transfer_data(
+            &ChallengeContext::Legacy,
+            &context,
+            &pk,
+            &sk,
+            &input_amount,
+            to_transfer,
+            &mut csprng,
+        )
+        .expect("Could not produce transfer data.");
+        assert_eq!(
+            encrypted_transfer_payload_size::<G1>(),
+            1 + ACCOUNT_ADDRESS_SIZE + to_bytes(&data).len()
+        );
+
+        let data = make_sec_to_pub_transfer_data(
+            &ChallengeContext::Legacy,
+            &context,
+            &sk,
+            &input_amount,
+            to_transfer,
+            &mut csprng,
+        )
+        .expect("Could not produce transfer data.");
+        assert_eq!(
+            sec_to_pub_transfer_payload_size::<G1>(),
+            1 + to_bytes(&data).len()
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let profile = DeviceProfile {
+            point_addition:            Duration::from_micros(1),
+            scalar_multiplication:     Duration::from_micros(100),
+            encrypted_transfer_proof:  Duration::from_secs(2),
+            sec_to_pub_transfer_proof: Duration::from_secs(1),
+        };
+        let report = encrypted_transfer_report::<G1>(&profile, 0, 1 << 16);
+        assert_eq!(report.aggregation, Duration::from_secs(0));
+        assert_eq!(report.decryption, Duration::from_secs(0));
+        assert_eq!(report.total(), Duration::from_secs(2));
+
+        // Three amounts have six chunks, each of which takes 2^16 giant steps
+        // with a table of size 2^16.
+        let report = sec_to_pub_transfer_report::<G1>(&profile, 3, 1 << 16);
+        assert_eq!(
+            report.payload_size,
+            sec_to_pub_transfer_payload_size::<G1>()
+        );
+        assert_eq!(report.aggregation, Duration::from_micros(12));
+        assert_eq!(
+            report.decryption,
+            Duration::from_micros(600) + Duration::from_micros(6 << 16)
+        );
+        assert_eq!(
+            report.total(),
+            Duration::from_secs(1) + report.aggregation + report.decryption
+        );
+
+        // A larger table makes decryption faster.
+        let faster = sec_to_pub_transfer_report::<G1>(&profile, 3, 1 << 20);
+        assert!(faster.decryption < report.decryption);
+    }
+}
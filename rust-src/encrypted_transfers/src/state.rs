@@ -0,0 +1,327 @@
+//! Bookkeeping of the encrypted balance of an account.
+//!
+//! The encrypted balance of an account on the chain consists of the self
+//! amount, which is what remained after the last transfer from the balance
+//! plus what was transferred to it from the public balance since, and the list
+//! of incoming encrypted amounts, which have consecutive indices starting from
+//! the start index. A transfer from the encrypted balance uses the self amount
+//! and a prefix of the incoming amounts as its input, identified by the
+//! aggregation index one past the last amount used. Once the transfer is
+//! finalized its remaining amount becomes the new self amount, and the used
+//! incoming amounts are removed from the list.
+//!
+//! [EncryptedBalance] tracks this state together with the decrypted values of
+//! the amounts, so that the input of a transfer can be computed without
+//! decrypting anything.
+use crate::{types::*, *};
+use crypto_common::{SerdeDeserialize, SerdeSerialize};
+
+/// Reasons why the encrypted balance cannot be updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum BalanceError {
+    #[error("Expected the incoming amount with index {expected}, but got index {actual}.")]
+    UnexpectedIndex { expected: u64, actual: u64 },
+    #[error("Aggregation index {0} does not belong to the known amounts of the balance.")]
+    UnknownAggregationIndex(u64),
+    #[error("The available amount {available} is less than the amount {required}.")]
+    InsufficientFunds {
+        available: Amount,
+        required:  Amount,
+    },
+    #[error("The amounts of the balance overflow.")]
+    Overflow,
+    #[error("Could not produce the transfer data.")]
+    TransferFailed,
+}
+
+/// An incoming encrypted amount together with its decrypted value.
+#[derive(Clone, Debug, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptedIncomingAmount<C: Curve> {
+    pub encrypted_amount: EncryptedAmount<C>,
+    pub amount:           Amount,
+}
+
+/// The encrypted balance of an account as known to the owner of the account.
+#[derive(Clone, Debug, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedBalance<C: Curve> {
+    /// The self amount of the account.
+    self_amount:       EncryptedAmount<C>,
+    /// The decrypted value of the self amount.
+    self_amount_value: Amount,
+    /// The index of the first incoming amount that is not included in the
+    /// self amount.
+    start_index:       EncryptedAmountIndex,
+    /// The incoming amounts with indices starting from `start_index`.
+    incoming_amounts:  Vec<DecryptedIncomingAmount<C>>,
+}
+
+impl<C: Curve> EncryptedBalance<C> {
+    /// The balance of a new account, with a self amount of zero and no
+    /// incoming amounts.
+    pub fn empty(context: &GlobalContext<C>) -> Self {
+        let zero = Amount::from(0);
+        Self::new(
+            encrypt_amount_with_fixed_randomness(context, zero),
+            zero,
+            EncryptedAmountIndex::from(0),
+        )
+    }
+
+    /// The balance with the given self amount and its value, and no incoming
+    /// amounts from the start index on.
+    pub fn new(
+        self_amount: EncryptedAmount<C>,
+        self_amount_value: Amount,
+        start_index: EncryptedAmountIndex,
+    ) -> Self {
+        EncryptedBalance {
+            self_amount,
+            self_amount_value,
+            start_index,
+            incoming_amounts: Vec::new(),
+        }
+    }
+
+    /// The self amount of the account.
+    pub fn self_amount(&self) -> &EncryptedAmount<C> { &self.self_amount }
+
+    /// The decrypted value of the self amount.
+    pub fn self_amount_value(&self) -> Amount { self.self_amount_value }
+
+    /// The index of the first incoming amount that is not included in the self
+    /// amount.
+    pub fn start_index(&self) -> EncryptedAmountIndex { self.start_index }
+
+    /// The known incoming amounts, in the order of their indices.
+    pub fn incoming_amounts(&self) -> &[DecryptedIncomingAmount<C>] { &self.incoming_amounts }
+
+    /// The index the next incoming amount will have.
+    pub fn next_index(&self) -> EncryptedAmountIndex {
+        EncryptedAmountIndex::from(self.start_index.index + self.incoming_amounts.len() as u64)
+    }
+
+    /// The total value of the balance, or `None` if it overflows.
+    pub fn total(&self) -> Option<Amount> {
+        self.incoming_amounts
+            .iter()
+            .try_fold(self.self_amount_value, |total, incoming| {
+                total + incoming.amount
+            })
+    }
+
+    /// Add an incoming amount with its decrypted value. The index of the amount
+    /// must be [next_index](Self::next_index).
+    pub fn add_incoming(
+        &mut self,
+        index: EncryptedAmountIndex,
+        encrypted_amount: EncryptedAmount<C>,
+        amount: Amount,
+    ) -> Result<(), BalanceError> {
+        let expected = self.next_index().index;
+        if index.index != expected {
+            return Err(BalanceError::UnexpectedIndex {
+                expected,
+                actual: index.index,
+            });
+        }
+        self.incoming_amounts.push(DecryptedIncomingAmount {
+            encrypted_amount,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Add an amount transferred from the public balance to the self amount.
+    /// Such amounts are encrypted with zero randomness on the chain.
+    pub fn add_to_self_amount(
+        &mut self,
+        context: &GlobalContext<C>,
+        amount: Amount,
+    ) -> Result<(), BalanceError> {
+        let value = (self.self_amount_value + amount).ok_or(BalanceError::Overflow)?;
+        let added = encrypt_amount_with_fixed_randomness(context, amount);
+        self.self_amount = aggregate(&self.self_amount, &added);
+        self.self_amount_value = value;
+        Ok(())
+    }
+
+    /// The input amount of a transfer of the given amount. This is the self
+    /// amount together with the shortest prefix of the incoming amounts that
+    /// covers the amount.
+    pub fn input_amount(
+        &self,
+        amount: Amount,
+    ) -> Result<AggregatedDecryptedAmount<C>, BalanceError> {
+        let mut input = AggregatedDecryptedAmount {
+            agg_encrypted_amount: self.self_amount.clone(),
+            agg_amount:           self.self_amount_value,
+            agg_index:            EncryptedAmountAggIndex::from(self.start_index.index),
+        };
+        let mut incoming_amounts = self.incoming_amounts.iter();
+        while input.agg_amount < amount {
+            let incoming = match incoming_amounts.next() {
+                Some(incoming) => incoming,
+                None => {
+                    return Err(BalanceError::InsufficientFunds {
+                        available: input.agg_amount,
+                        required:  amount,
+                    })
+                }
+            };
+            input.agg_encrypted_amount =
+                aggregate(&input.agg_encrypted_amount, &incoming.encrypted_amount);
+            input.agg_amount =
+                (input.agg_amount + incoming.amount).ok_or(BalanceError::Overflow)?;
+            input.agg_index.index += 1;
+        }
+        Ok(input)
+    }
+
+    /// Record a transfer of `amount` with the given input amount, which leaves
+    /// `remaining_amount` as the new self amount. This should be done once the
+    /// transfer is finalized on the chain.
+    pub fn spend(
+        &mut self,
+        input: &AggregatedDecryptedAmount<C>,
+        amount: Amount,
+        remaining_amount: EncryptedAmount<C>,
+    ) -> Result<(), BalanceError> {
+        let agg_index = input.agg_index.index;
+        if agg_index < self.start_index.index || agg_index > self.next_index().index {
+            return Err(BalanceError::UnknownAggregationIndex(agg_index));
+        }
+        if input.agg_amount < amount {
+            return Err(BalanceError::InsufficientFunds {
+                available: input.agg_amount,
+                required:  amount,
+            });
+        }
+        let used = (agg_index - self.start_index.index) as usize;
+        self.incoming_amounts.drain(..used);
+        self.self_amount = remaining_amount;
+        self.self_amount_value = Amount::from(input.agg_amount.microgtu - amount.microgtu);
+        self.start_index = EncryptedAmountIndex::from(agg_index);
+        Ok(())
+    }
+
+    /// Produce the payload of an encrypted transfer of the given amount from
+    /// the balance, see [make_transfer_data], and record the transfer.
+    pub fn make_transfer<R: Rng>(
+        &mut self,
+        context: &GlobalContext<C>,
+        receiver_pk: &PublicKey<C>,
+        sender_sk: &SecretKey<C>,
+        amount: Amount,
+        csprng: &mut R,
+    ) -> Result<EncryptedAmountTransferData<C>, BalanceError> {
+        let input = self.input_amount(amount)?;
+        let data = make_transfer_data(
+            &ChallengeContext::Legacy,
+            context,
+            receiver_pk,
+            sender_sk,
+            &input,
+            amount,
+            csprng,
+        )
+        .ok_or(BalanceError::TransferFailed)?;
+        self.spend(&input, amount, data.remaining_amount.clone())?;
+        Ok(data)
+    }
+
+    /// Produce the payload of a transfer of the given amount from the balance
+    /// to the public balance, see [make_sec_to_pub_transfer_data], and record
+    /// the transfer.
+    pub fn make_sec_to_pub_transfer<R: Rng>(
+        &mut self,
+        context: &GlobalContext<C>,
+        sk: &SecretKey<C>,
+        amount: Amount,
+        csprng: &mut R,
+    ) -> Result<SecToPubAmountTransferData<C>, BalanceError> {
+        let input = self.input_amount(amount)?;
+        let data = make_sec_to_pub_transfer_data(
+            &ChallengeContext::Legacy,
+            context,
+            sk,
+            &input,
+            amount,
+            csprng,
+        )
+        .ok_or(BalanceError::TransferFailed)?;
+        self.spend(&input, amount, data.remaining_amount.clone())?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+
+    #[test]
+    fn test_encrypted_balance() {
+        let mut csprng = thread_rng();
+        let context = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let sk = SecretKey::generate(context.elgamal_generator(), &mut csprng);
+        let pk = PublicKey::from(&sk);
+        let table = BabyStepGiantStep::new(context.encryption_in_exponent_generator(), 1 << 16);
+
+        let mut balance = EncryptedBalance::empty(&context);
+        balance
+            .add_to_self_amount(&context, Amount::from(100))
+            .expect("Amount fits.");
+        for (index, value) in [50u64, 70, 30].iter().enumerate() {
+            let value = Amount::from(*value);
+            let (encrypted, _) = encrypt_amount(&context, &pk, value, &mut csprng);
+            balance
+                .add_incoming(EncryptedAmountIndex::from(index as u64), encrypted, value)
+                .expect("Index is the next one.");
+        }
+        assert_eq!(balance.total(), Some(Amount::from(250)));
+        let (encrypted, _) = encrypt_amount(&context, &pk, Amount::from(1), &mut csprng);
+        assert_eq!(
+            balance.add_incoming(EncryptedAmountIndex::from(5), encrypted, Amount::from(1)),
+            Err(BalanceError::UnexpectedIndex {
+                expected: 3,
+                actual:   5,
+            })
+        );
+
+        // The input of a transfer of 160 is the self amount and the first two
+        // incoming amounts.
+        let input = balance
+            .input_amount(Amount::from(160))
+            .expect("Funds suffice.");
+        assert_eq!(input.agg_index.index, 2);
+        assert_eq!(input.agg_amount, Amount::from(220));
+        assert_eq!(
+            decrypt_amount(&table, &sk, &input.agg_encrypted_amount),
+            Amount::from(220)
+        );
+        assert!(balance.input_amount(Amount::from(251)).is_err());
+
+        let data = balance
+            .make_sec_to_pub_transfer(&context, &sk, Amount::from(160), &mut csprng)
+            .expect("Transfer should be produced.");
+        assert!(verify_sec_to_pub_transfer_data(
+            &ChallengeContext::Legacy,
+            &context,
+            &pk,
+            &input.agg_encrypted_amount,
+            &data
+        ));
+        assert_eq!(balance.start_index().index, 2);
+        assert_eq!(balance.self_amount_value(), Amount::from(60));
+        assert_eq!(balance.incoming_amounts().len(), 1);
+        assert_eq!(balance.total(), Some(Amount::from(90)));
+        assert_eq!(
+            decrypt_amount(&table, &sk, balance.self_amount()),
+            Amount::from(60)
+        );
+    }
+}
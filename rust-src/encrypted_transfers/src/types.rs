@@ -138,6 +138,44 @@ pub struct EncryptedAmountTransferProof<C: Curve> {
     pub remaining_amount_correct_encryption: RangeProof<C>,
 }
 
+/// The structured JSON representation of [EncryptedAmountTransferProof], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "EncryptedAmountTransferProof",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct EncryptedAmountTransferProofJson<C: Curve> {
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    accounting: SigmaProof<proofs::EncTransWitness<C>>,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    transfer_amount_correct_encryption: RangeProof<C>,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    remaining_amount_correct_encryption: RangeProof<C>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for EncryptedAmountTransferProof<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        EncryptedAmountTransferProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        EncryptedAmountTransferProofJson::deserialize(des)
+    }
+}
+
 /// Proof that an encrypted transfer data is well-formed
 #[derive(Serialize, SerdeBase16Serialize, Clone, Debug)]
 pub struct SecToPubAmountTransferProof<C: Curve> {
@@ -148,3 +186,36 @@ pub struct SecToPubAmountTransferProof<C: Curve> {
     /// small enough.
     pub remaining_amount_correct_encryption: RangeProof<C>,
 }
+
+/// The structured JSON representation of [SecToPubAmountTransferProof], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "SecToPubAmountTransferProof",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct SecToPubAmountTransferProofJson<C: Curve> {
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    accounting: SigmaProof<proofs::EncTransWitness<C>>,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    remaining_amount_correct_encryption: RangeProof<C>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for SecToPubAmountTransferProof<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        SecToPubAmountTransferProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        SecToPubAmountTransferProofJson::deserialize(des)
+    }
+}
@@ -0,0 +1,139 @@
+//! Helpers for exporting functions that take a JSON string as input and return
+//! a JSON string, as the mobile wallet and anonymity revocation libraries do.
+//!
+//! The exported functions take a NUL-terminated UTF8 string and a pointer to a
+//! `success` flag, and return a NUL-terminated UTF8 string that must be freed
+//! with [free_response_string]. If the function succeeds the flag is set to 1
+//! and the response is its output. Otherwise the flag is set to 0 and the
+//! response is an error message.
+use anyhow::{bail, Context};
+use crypto_common::Zeroize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+};
+
+/// Get the field of the input object with the given name.
+pub fn try_get<A: DeserializeOwned>(v: &Value, field: &str) -> anyhow::Result<A> {
+    match v.get(field) {
+        Some(x) => serde_json::from_value(x.clone())
+            .with_context(|| format!("Could not parse the field '{}'.", field)),
+        None => bail!("Field '{}' not present, but should be.", field),
+    }
+}
+
+/// Set the flag to 0, and return a newly allocated string containing
+/// the error message. The returned string is NUL terminated.
+///
+/// # Safety
+/// The `success` pointer must be valid.
+pub unsafe fn signal_error(success: *mut u8, err_msg: String) -> *mut c_char {
+    *success = 0;
+    match CString::new(err_msg) {
+        Ok(s) => s.into_raw(),
+        Err(_) => CString::new("The error message contains a NUL byte.")
+            .expect("The message has no NUL bytes.")
+            .into_raw(),
+    }
+}
+
+/// Set the `success` flag and convert the response to a C string.
+///
+/// # Safety
+/// The `success` pointer must be valid.
+pub unsafe fn encode_response(response: anyhow::Result<String>, success: *mut u8) -> *mut c_char {
+    match response {
+        Ok(mut s) => {
+            // Copy the response instead of converting it, since adding the NUL
+            // byte may reallocate the string and leave a copy of it in memory.
+            // The response can contain secret keys, so it is overwritten.
+            let cstr = CString::new(s.as_bytes());
+            s.zeroize();
+            match cstr {
+                Ok(cstr) => {
+                    *success = 1;
+                    cstr.into_raw()
+                }
+                Err(e) => signal_error(success, format!("Could not encode response: {}", e)),
+            }
+        }
+        Err(e) => signal_error(success, format!("Could not produce response: {:#}", e)),
+    }
+}
+
+/// Run the function on the input string and encode its response.
+///
+/// # Safety
+/// The `input` pointer must be NULL or point to a NUL-terminated string, and
+/// the `success` pointer must be valid.
+pub unsafe fn call_with_input(
+    input: *const c_char,
+    success: *mut u8,
+    f: impl FnOnce(&str) -> anyhow::Result<String>,
+) -> *mut c_char {
+    if input.is_null() {
+        return signal_error(success, "Null pointer input.".to_owned());
+    }
+    match CStr::from_ptr(input).to_str() {
+        Ok(s) => encode_response(f(s), success),
+        Err(e) => signal_error(success, format!("Could not decode input string: {}", e)),
+    }
+}
+
+/// Free a string returned by [encode_response] or [signal_error]. The string is
+/// overwritten first, since it can contain secret keys.
+///
+/// # Safety
+/// The pointer must be NULL or have been returned by one of the functions of
+/// this module, and must not be used afterwards.
+pub unsafe fn free_response_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        CString::from_raw(ptr).into_bytes().zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(input: &str, f: impl FnOnce(&str) -> anyhow::Result<String>) -> (u8, String) {
+        let input = CString::new(input).unwrap();
+        let mut success = 2;
+        unsafe {
+            let response = call_with_input(input.as_ptr(), &mut success, f);
+            let s = CStr::from_ptr(response).to_str().unwrap().to_owned();
+            free_response_string(response);
+            (success, s)
+        }
+    }
+
+    #[test]
+    fn test_call_with_input() {
+        let (success, response) = call(r#"{"x": 3}"#, |input| {
+            let v: Value = serde_json::from_str(input)?;
+            let x: u64 = try_get(&v, "x")?;
+            Ok((x + 1).to_string())
+        });
+        assert_eq!((success, response.as_str()), (1, "4"));
+
+        let (success, response) = call(r#"{"x": "three"}"#, |input| {
+            let v: Value = serde_json::from_str(input)?;
+            try_get::<u64>(&v, "x").map(|x| x.to_string())
+        });
+        assert_eq!(success, 0);
+        assert!(response.starts_with("Could not produce response: Could not parse the field 'x'."));
+
+        let (success, response) = call("{}", |_| Ok("a\0b".to_owned()));
+        assert_eq!(success, 0);
+        assert!(response.starts_with("Could not encode response"));
+
+        let mut success = 2;
+        unsafe {
+            let response = call_with_input(std::ptr::null(), &mut success, |_| Ok(String::new()));
+            assert_eq!(success, 0);
+            free_response_string(response);
+        }
+    }
+}
@@ -2,3 +2,4 @@
 pub mod ffi_macros;
 
 mod common;
+pub mod json;
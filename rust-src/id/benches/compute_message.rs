@@ -11,6 +11,7 @@ use id::{
 use pairing::bls12_381::{G1, *};
 use pedersen_scheme::Commitment;
 use rand::*;
+use random_oracle::ChallengeContext;
 use serde_json::from_str;
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -104,7 +105,7 @@ fn bench_validate_request(c: &mut Criterion) {
 
     // Act
     c.bench_function("Validate request", move |b| {
-        b.iter(|| validate_request(&pio, context))
+        b.iter(|| validate_request(&ChallengeContext::Legacy, &pio, context))
     });
 }
 
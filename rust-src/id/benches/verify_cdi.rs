@@ -18,6 +18,7 @@ use id::{
 };
 use pairing::bls12_381::{Bls12, G1};
 use rand::*;
+use random_oracle::ChallengeContext;
 use std::{collections::BTreeMap, convert::TryFrom, io::Cursor};
 
 type ExampleAttribute = AttributeKind;
@@ -130,8 +131,14 @@ fn bench_parts(c: &mut Criterion) {
         threshold: SignatureThreshold(2),
     };
 
-    let (pio, randomness) = generate_pio(&context, Threshold(2), &aci, &initial_acc_data)
-        .expect("Generating the pre-identity object succeed.");
+    let (pio, randomness) = generate_pio(
+        &ChallengeContext::Legacy,
+        &context,
+        Threshold(2),
+        &aci,
+        &initial_acc_data,
+    )
+    .expect("Generating the pre-identity object succeed.");
     let pio_ser = to_bytes(&pio);
     let ip_info_ser = to_bytes(&ip_info);
     let pio_des = from_bytes(&mut Cursor::new(&pio_ser)).unwrap();
@@ -178,6 +185,7 @@ fn bench_parts(c: &mut Criterion) {
     };
 
     let (cdi, _) = create_credential(
+        &ChallengeContext::Legacy,
         context,
         &id_object,
         &id_use_data,
@@ -203,8 +211,9 @@ fn bench_parts(c: &mut Criterion) {
         ar4_secret_key.decrypt(&fourth_ar.enc_id_cred_pub_share),
     );
 
-    let bench_pio =
-        move |b: &mut Bencher, x: &(_, _, _)| b.iter(|| generate_pio(x.0, Threshold(2), x.1, x.2));
+    let bench_pio = move |b: &mut Bencher, x: &(_, _, _)| {
+        b.iter(|| generate_pio(&ChallengeContext::Legacy, x.0, Threshold(2), x.1, x.2))
+    };
     c.bench_with_input(
         BenchmarkId::new("Generate ID request", ""),
         &(&context, &id_use_data.aci, &initial_acc_data),
@@ -222,7 +231,17 @@ fn bench_parts(c: &mut Criterion) {
     let bench_create_credential =
         move |b: &mut Bencher, x: &(_, _, _, _, Policy<ArCurve, AttributeKind>, _)| {
             b.iter(|| {
-                create_credential(x.0, x.1, x.2, x.3, x.4.clone(), x.5, &Left(EXPIRY)).unwrap()
+                create_credential(
+                    &ChallengeContext::Legacy,
+                    x.0,
+                    x.1,
+                    x.2,
+                    x.3,
+                    x.4.clone(),
+                    x.5,
+                    &Left(EXPIRY),
+                )
+                .unwrap()
             })
         };
     c.bench_with_input(
@@ -232,7 +251,7 @@ fn bench_parts(c: &mut Criterion) {
     );
 
     let bench_verify_cdi = move |b: &mut Bencher, x: &(_, _, _, _)| {
-        b.iter(|| verify_cdi(x.0, x.1, x.2, x.3, &Left(EXPIRY)).unwrap())
+        b.iter(|| verify_cdi(&ChallengeContext::Legacy, x.0, x.1, x.2, x.3, &Left(EXPIRY)).unwrap())
     };
     c.bench_with_input(
         BenchmarkId::new("Verify CDI", ""),
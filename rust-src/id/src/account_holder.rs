@@ -13,16 +13,17 @@ use bulletproofs::{
     inner_product_proof::inner_product,
     range_proof::{prove_given_scalars as bulletprove, prove_less_than_or_equal},
 };
-use crypto_common::types::TransactionTime;
+use crypto_common::{types::TransactionTime, CancellationToken};
 use curve_arithmetic::{Curve, Pairing};
 use dodis_yampolskiy_prf as prf;
 use elgamal::{multicombine, Cipher};
 use ff::Field;
 use pedersen_scheme::{
-    Commitment, CommitmentKey as PedersenKey, Randomness as PedersenRandomness, Value,
+    Commitment, CommitmentKey as PedersenKey, PrecomputedCommitmentKey,
+    Randomness as PedersenRandomness, Value,
 };
 use rand::*;
-use random_oracle::RandomOracle;
+use random_oracle::{domains, ChallengeContext, RandomOracle};
 use std::collections::{btree_map::BTreeMap, hash_map::HashMap, BTreeSet};
 
 /// Build the PublicInformationForIP used to generate an PreIdentityObject, out
@@ -70,15 +71,44 @@ pub fn build_pub_info_for_ip<C: Curve>(
 /// the chosen anonymity revoker information, and the necessary contextual
 /// information (group generators, shared commitment keys, etc).
 /// NB: In this method we assume that all the anonymity revokers in context
-/// are to be used.
+/// are to be used. The function returns `None` if the threshold is 0 or
+/// exceeds the number of anonymity revokers, since the identity could then not
+/// be revoked. The challenges of the proofs include the given challenge
+/// context, and the request is only valid with
+/// [validate_request](crate::identity_provider::validate_request) in the same
+/// context.
 pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
     // TODO: consider renaming this function
+    challenge_context: &ChallengeContext,
     context: &IpContext<P, C>,
     threshold: Threshold,
     aci: &AccCredentialInfo<C>,
     initial_account: &impl InitialAccountDataWithSigning,
 ) -> Option<(PreIdentityObject<P, C>, ps_sig::SigRetrievalRandomness<P>)> {
-    let mut csprng = thread_rng();
+    generate_pio_with_rng(
+        challenge_context,
+        context,
+        threshold,
+        aci,
+        initial_account,
+        &mut thread_rng(),
+    )
+}
+
+/// Like [generate_pio], but take all the randomness from the given generator.
+/// With a seeded generator the pre-identity object is deterministic, which is
+/// only meant for testing.
+pub fn generate_pio_with_rng<P: Pairing, C: Curve<Scalar = P::ScalarField>, R: Rng>(
+    challenge_context: &ChallengeContext,
+    context: &IpContext<P, C>,
+    threshold: Threshold,
+    aci: &AccCredentialInfo<C>,
+    initial_account: &impl InitialAccountDataWithSigning,
+    csprng: &mut R,
+) -> Option<(PreIdentityObject<P, C>, ps_sig::SigRetrievalRandomness<P>)> {
+    if threshold.0 == 0 || usize::from(threshold.0) > context.ars_infos.len() {
+        return None;
+    }
 
     // PRF related computation
     let prf_key = &aci.prf_key;
@@ -109,6 +139,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         threshold,
         ar_commitment_key,
         &context.global_context,
+        csprng,
     );
     let number_of_ars = context.ars_infos.len();
     let mut ip_ar_data = Vec::with_capacity(number_of_ars);
@@ -120,7 +151,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         h: context.ip_info.ip_verify_key.g,
     };
 
-    let (cmm_sc, cmm_sc_rand) = sc_ck.commit(&id_cred_sec, &mut csprng);
+    let (cmm_sc, cmm_sc_rand) = sc_ck.commit(&id_cred_sec, csprng);
     let cmm_sc_rand = cmm_sc_rand;
     // We now construct all the zero-knowledge proofs.
     // Since all proofs must be bound together, we
@@ -158,7 +189,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         g: context.ip_info.ip_verify_key.ys[1],
         h: context.ip_info.ip_verify_key.g,
     };
-    let (cmm_prf, rand_cmm_prf) = commitment_key_prf.commit(prf_key, &mut csprng);
+    let (cmm_prf, rand_cmm_prf) = commitment_key_prf.commit(prf_key, csprng);
     let rand_cmm_prf = rand_cmm_prf;
     let snd_cmm_prf = cmm_prf_sharing_coeff.first()?;
     let rand_snd_cmm_prf = cmm_coeff_randomness.first()?.clone();
@@ -193,7 +224,8 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         ar_identities,
         threshold,
     };
-    let mut transcript = RandomOracle::domain("PreIdentityProof");
+    let mut transcript =
+        RandomOracle::domain_in_context(challenge_context, domains::PRE_IDENTITY_PROOF);
     transcript.append_message(b"ctx", &context.global_context);
     transcript.append_message(b"choice_ar_parameters", &choice_ar_parameters);
     transcript.append_message(b"cmm_sc", &cmm_sc);
@@ -250,7 +282,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
             .collect::<Vec<_>>();
         let bulletproof = bulletprove(
             &mut transcript,
-            &mut csprng,
+            csprng,
             u8::from(CHUNK_SIZE),
             item.share_in_chunks.len() as u8,
             &item.share_in_chunks,
@@ -283,7 +315,7 @@ pub fn generate_pio<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
     let prover = prover.add_prover(prover_prf_regid);
     let secret = (secret, secret_prf_regid);
     transcript.append_message(b"bulletproofs", &bulletproofs);
-    let proof = prove(&mut transcript, &prover, secret, &mut csprng)?;
+    let proof = prove(&mut transcript, &prover, secret, csprng)?;
 
     let ip_ar_data = ip_ar_data
         .iter()
@@ -344,20 +376,20 @@ type SharingData<'a, C> = (
 );
 
 /// A function to compute sharing data for a single value.
-pub fn compute_sharing_data<'a, C: Curve>(
+pub fn compute_sharing_data<'a, C: Curve, R: Rng>(
     shared_scalar: &Value<C>,                           // Value to be shared.
     ar_parameters: &'a BTreeMap<ArIdentity, ArInfo<C>>, // Chosen anonimity revokers.
     threshold: Threshold,                               // Anonymity revocation threshold.
     commitment_key: &PedersenKey<C>,                    // commitment key
+    csprng: &mut R,
 ) -> SharingData<'a, C> {
     let n = ar_parameters.len() as u32;
-    let mut csprng = thread_rng();
     // first commit to the scalar
-    let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, &mut csprng);
+    let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, csprng);
     // We evaluate the polynomial at ar_identities.
     let share_points = ar_parameters.keys().copied();
     // share the scalar on ar_identity points.
-    let sharing_data = share::<C, _, _, _>(&shared_scalar, share_points, threshold, &mut csprng);
+    let sharing_data = share::<C, _, _, _>(&shared_scalar, share_points, threshold, csprng);
     // commitments to the sharing coefficients
     let mut cmm_sharing_coefficients: Vec<Commitment<C>> = Vec::with_capacity(threshold.into());
     // first coefficient is the shared scalar
@@ -368,7 +400,7 @@ pub fn compute_sharing_data<'a, C: Curve>(
     cmm_coeff_randomness.push(cmm_scalar_rand);
     // fill the rest
     for coeff in sharing_data.coefficients.iter() {
-        let (cmm, rnd) = commitment_key.commit(coeff, &mut csprng);
+        let (cmm, rnd) = commitment_key.commit(coeff, csprng);
         cmm_sharing_coefficients.push(cmm);
         cmm_coeff_randomness.push(rnd);
     }
@@ -380,7 +412,7 @@ pub fn compute_sharing_data<'a, C: Curve>(
         let si = ar.ar_identity;
         let pk = ar.ar_public_key;
         // encrypt the share
-        let (cipher, rnd2) = pk.encrypt_exponent_rand(&mut csprng, &share);
+        let (cipher, rnd2) = pk.encrypt_exponent_rand(csprng, &share);
         // compute the commitment to this share from the commitment to the coeff
         let (cmm, rnd) =
             commitment_to_share_and_rand(si, &cmm_sharing_coefficients, &cmm_coeff_randomness);
@@ -428,21 +460,21 @@ type SharingDataPrf<'a, C> = (
 );
 
 /// A function to compute sharing data for a single value.
-pub fn compute_sharing_data_prf<'a, C: Curve>(
+pub fn compute_sharing_data_prf<'a, C: Curve, R: Rng>(
     shared_scalar: &Value<C>,                           // Value to be shared.
     ar_parameters: &'a BTreeMap<ArIdentity, ArInfo<C>>, // Chosen anonimity revokers.
     threshold: Threshold,                               // Anonymity revocation threshold.
     commitment_key: &PedersenKey<C>,
     global_context: &GlobalContext<C>, // commitment key
+    csprng: &mut R,
 ) -> SharingDataPrf<'a, C> {
     let n = ar_parameters.len() as u32;
-    let mut csprng = thread_rng();
     // first commit to the scalar
-    let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, &mut csprng);
+    let (cmm_scalar, cmm_scalar_rand) = commitment_key.commit(&shared_scalar, csprng);
     // We evaluate the polynomial at ar_identities.
     let share_points = ar_parameters.keys().copied();
     // share the scalar on ar_identity points.
-    let sharing_data = share::<C, _, _, _>(&shared_scalar, share_points, threshold, &mut csprng);
+    let sharing_data = share::<C, _, _, _>(&shared_scalar, share_points, threshold, csprng);
     // commitments to the sharing coefficients
     let mut cmm_sharing_coefficients: Vec<Commitment<C>> = Vec::with_capacity(threshold.into());
     // first coefficient is the shared scalar
@@ -453,7 +485,7 @@ pub fn compute_sharing_data_prf<'a, C: Curve>(
     cmm_coeff_randomness.push(cmm_scalar_rand);
     // fill the rest
     for coeff in sharing_data.coefficients.iter() {
-        let (cmm, rnd) = commitment_key.commit(coeff, &mut csprng);
+        let (cmm, rnd) = commitment_key.commit(coeff, csprng);
         cmm_sharing_coefficients.push(cmm);
         cmm_coeff_randomness.push(rnd);
     }
@@ -467,7 +499,7 @@ pub fn compute_sharing_data_prf<'a, C: Curve>(
         // encrypt the share
         // let (cipher, rnd2) = pk.encrypt_exponent_rand(&mut csprng, &share);
         let (ciphers, rnd2, share_in_chunks) =
-            utils::encrypt_prf_share(global_context, &pk, &share, &mut csprng);
+            utils::encrypt_prf_share(global_context, &pk, &share, csprng);
         // compute the commitment to this share from the commitment to the coeff
         let (cmm, rnd) =
             commitment_to_share_and_rand(si, &cmm_sharing_coefficients, &cmm_coeff_randomness);
@@ -509,12 +541,17 @@ pub fn commitment_to_share_and_rand<C: Curve>(
 /// commitments later on. The information is meant to be valid in the context of
 /// a given identity provider, and global parameter.
 /// The 'cred_counter' is used to generate a new credential ID.
+/// The challenges of the proofs include the given challenge context, and the
+/// credential only verifies with [verify_cdi](crate::chain::verify_cdi) in the
+/// same context.
+#[allow(clippy::too_many_arguments)]
 pub fn create_credential<
     'a,
     P: Pairing,
     C: Curve<Scalar = P::ScalarField>,
     AttributeType: Attribute<C::Scalar>,
 >(
+    challenge_context: &ChallengeContext,
     context: IpContext<'a, P, C>,
     id_object: &IdentityObject<P, C, AttributeType>,
     id_object_use_data: &IdObjectUseData<P, C>,
@@ -528,15 +565,63 @@ pub fn create_credential<
 )>
 where
     AttributeType: Clone, {
-    let (unsigned_credential_info, commitments_randomness) = create_unsigned_credential(
+    create_credential_cancellable(
+        challenge_context,
         context,
         id_object,
         id_object_use_data,
         cred_counter,
         policy,
-        cred_data.get_cred_key_info(),
-        new_or_existing.as_ref().right(),
-    )?;
+        cred_data,
+        new_or_existing,
+        &CancellationToken::new(),
+        &mut thread_rng(),
+    )
+}
+
+/// Like [create_credential], but the computation can be cancelled via the
+/// given token, and all the randomness is taken from the given generator. The
+/// token is checked between the phases of proof generation, and if it has been
+/// cancelled the function returns an error wrapping
+/// [Cancelled](crypto_common::Cancelled). With a seeded generator the
+/// credential is deterministic, which is only meant for testing.
+#[allow(clippy::too_many_arguments)]
+pub fn create_credential_cancellable<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    R: Rng,
+>(
+    challenge_context: &ChallengeContext,
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_data: &impl CredentialDataWithSigning,
+    new_or_existing: &either::Either<TransactionTime, AccountAddress>,
+    cancel: &CancellationToken,
+    csprng: &mut R,
+) -> anyhow::Result<(
+    CredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
+where
+    AttributeType: Clone, {
+    let (unsigned_credential_info, commitments_randomness) =
+        create_unsigned_credential_cancellable(
+            challenge_context,
+            context,
+            id_object,
+            id_object_use_data,
+            cred_counter,
+            policy,
+            cred_data.get_cred_key_info(),
+            new_or_existing.as_ref().right(),
+            cancel,
+            csprng,
+        )?;
 
     let proof_acc_sk = AccountOwnershipProof {
         sigs: cred_data.sign(&new_or_existing, &unsigned_credential_info),
@@ -561,12 +646,14 @@ where
 /// The 'cred_counter' is used to generate a new credential ID.
 /// It should be the case that using the output, one can construct an actual
 /// credential deployment info, by signing the unsigned challenge.
+#[allow(clippy::too_many_arguments)]
 pub fn create_unsigned_credential<
     'a,
     P: Pairing,
     C: Curve<Scalar = P::ScalarField>,
     AttributeType: Attribute<C::Scalar>,
 >(
+    challenge_context: &ChallengeContext,
     context: IpContext<'a, P, C>,
     id_object: &IdentityObject<P, C, AttributeType>,
     id_object_use_data: &IdObjectUseData<P, C>,
@@ -580,8 +667,49 @@ pub fn create_unsigned_credential<
 )>
 where
     AttributeType: Clone, {
-    let mut csprng = thread_rng();
+    create_unsigned_credential_cancellable(
+        challenge_context,
+        context,
+        id_object,
+        id_object_use_data,
+        cred_counter,
+        policy,
+        cred_key_info,
+        addr,
+        &CancellationToken::new(),
+        &mut thread_rng(),
+    )
+}
 
+/// Like [create_unsigned_credential], but the computation can be cancelled
+/// via the given token, and all the randomness is taken from the given
+/// generator. The token is checked between the phases of proof generation, and
+/// if it has been cancelled the function returns an error wrapping
+/// [Cancelled](crypto_common::Cancelled).
+#[allow(clippy::too_many_arguments)]
+pub fn create_unsigned_credential_cancellable<
+    'a,
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    R: Rng,
+>(
+    challenge_context: &ChallengeContext,
+    context: IpContext<'a, P, C>,
+    id_object: &IdentityObject<P, C, AttributeType>,
+    id_object_use_data: &IdObjectUseData<P, C>,
+    cred_counter: u8,
+    policy: Policy<C, AttributeType>,
+    cred_key_info: CredentialPublicKeys,
+    addr: Option<&AccountAddress>,
+    cancel: &CancellationToken,
+    csprng: &mut R,
+) -> anyhow::Result<(
+    UnsignedCredentialDeploymentInfo<P, C, AttributeType>,
+    CommitmentsRandomness<C>,
+)>
+where
+    AttributeType: Clone, {
     let ip_sig = &id_object.signature;
     let sig_retrieval_rand = &id_object_use_data.randomness;
     let aci = &id_object_use_data.aci;
@@ -639,8 +767,11 @@ where
         &chosen_ars,
         prio.choice_ar_parameters.threshold,
         &context.global_context.on_chain_commitment_key,
+        csprng,
     );
 
+    cancel.check()?;
+
     let number_of_ars = prio.choice_ar_parameters.ar_identities.len();
     // filling ar data
     let ar_data = id_cred_data
@@ -659,19 +790,20 @@ where
 
     // and then we blind the signature to disassociate it from the message.
     // only the second part is used (as per the protocol)
-    let (blinded_sig, blind_rand) = retrieved_sig.blind(&mut csprng);
+    let (blinded_sig, blind_rand) = retrieved_sig.blind(csprng);
     // We now compute commitments to all the items in the attribute list.
     // We use the on-chain pedersen commitment key.
-    let (commitments, commitment_rands) = compute_commitments(
-        &context.global_context.on_chain_commitment_key,
+    let (commitments, commitment_rands) = compute_commitments_precomputed(
+        &context.global_context.precomputed_commitment_key(),
         &alist,
         prf_key,
         cred_counter,
         &cmm_id_cred_sec_sharing_coeff,
         cmm_coeff_randomness,
         &policy,
-        &mut csprng,
+        csprng,
     )?;
+    cancel.check()?;
 
     // We have all the values now.
     let cred_values = CredentialDeploymentValues {
@@ -687,7 +819,7 @@ where
     // Compute the challenge prefix by hashing the values.
     // FIXME: We should do something different here.
     // Eventually we'll have to include the genesis hash.
-    let mut ro = RandomOracle::domain("credential");
+    let mut ro = RandomOracle::domain_in_context(challenge_context, domains::CREDENTIAL);
     ro.append_message(b"cred_values", &cred_values);
     ro.append_message(b"address", &addr);
     ro.append_message(b"global_context", &context.global_context);
@@ -761,15 +893,18 @@ where
         protocols: id_cred_pub_provers,
     });
 
+    cancel.check()?;
+
     let secret = ((secret_reg_id, secret_sig), id_cred_pub_secrets);
-    let proof = match prove(&mut ro, &prover, secret, &mut csprng) {
+    let proof = match prove(&mut ro, &prover, secret, csprng) {
         Some(x) => x,
         None => bail!("Cannot produce zero knowledge proof."),
     };
+    cancel.check()?;
 
     let cred_counter_less_than_max_accounts = match prove_less_than_or_equal(
         &mut ro,
-        &mut csprng,
+        csprng,
         8,
         u64::from(cred_counter),
         u64::from(alist.max_accounts),
@@ -965,6 +1100,32 @@ pub fn compute_commitments<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng
     cmm_coeff_randomness: Vec<PedersenRandomness<C>>,
     policy: &Policy<C, AttributeType>,
     csprng: &mut R,
+) -> anyhow::Result<(CredentialDeploymentCommitments<C>, CommitmentsRandomness<C>)> {
+    compute_commitments_precomputed(
+        &PrecomputedCommitmentKey::from(commitment_key),
+        alist,
+        prf_key,
+        cred_counter,
+        cmm_id_cred_sec_sharing_coeff,
+        cmm_coeff_randomness,
+        policy,
+        csprng,
+    )
+}
+
+/// Same as [compute_commitments], but with a commitment key with a
+/// precomputed table, e.g., the one of
+/// [GlobalContext::precomputed_commitment_key].
+#[allow(clippy::too_many_arguments)]
+pub fn compute_commitments_precomputed<C: Curve, AttributeType: Attribute<C::Scalar>, R: Rng>(
+    commitment_key: &PrecomputedCommitmentKey<C>,
+    alist: &AttributeList<C::Scalar, AttributeType>,
+    prf_key: &prf::SecretKey<C>,
+    cred_counter: u8,
+    cmm_id_cred_sec_sharing_coeff: &[Commitment<C>],
+    cmm_coeff_randomness: Vec<PedersenRandomness<C>>,
+    policy: &Policy<C, AttributeType>,
+    csprng: &mut R,
 ) -> anyhow::Result<(CredentialDeploymentCommitments<C>, CommitmentsRandomness<C>)> {
     let id_cred_sec_rand = if let Some(v) = cmm_coeff_randomness.first() {
         v.clone()
@@ -1179,7 +1340,7 @@ mod tests {
 
         // Act
         let (ar_datas, _comms, _rands) =
-            compute_sharing_data(&value, &ars_infos, Threshold(threshold), &ck);
+            compute_sharing_data(&value, &ars_infos, Threshold(threshold), &ck, &mut csprng);
 
         // Assert ArData's are good
         for data in ar_datas.iter() {
@@ -1200,6 +1361,41 @@ mod tests {
         // encodes value
     }
 
+    /// The shares of the PRF key are encrypted with independent randomness for
+    /// each anonymity revoker.
+    #[test]
+    pub fn test_compute_sharing_data_prf_fresh_randomness() {
+        let mut csprng = thread_rng();
+        let global_context =
+            GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_context.on_chain_commitment_key.g, 3, &mut csprng);
+        let value = Value::<ExampleCurve>::generate(&mut csprng);
+        let (ar_datas, _, _) = compute_sharing_data_prf(
+            &value,
+            &ars_infos,
+            Threshold(2),
+            &global_context.on_chain_commitment_key,
+            &global_context,
+            &mut csprng,
+        );
+        assert_eq!(ar_datas.len(), 3);
+        for (i, data) in ar_datas.iter().enumerate() {
+            for other in ar_datas[..i].iter() {
+                for (cipher, other_cipher) in data
+                    .encrypted_share
+                    .iter()
+                    .zip(other.encrypted_share.iter())
+                {
+                    assert_ne!(
+                        cipher.0, other_cipher.0,
+                        "Encryptions to different anonymity revokers share randomness."
+                    );
+                }
+            }
+        }
+    }
+
     /// This test generates a CDI and check values were set correct.
     /// It does not yet test the proofs for correct-/soundness.
     #[test]
@@ -1215,13 +1411,7 @@ mod tests {
         } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
         let aci = test_create_aci(&mut csprng);
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
         let global_ctx = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
@@ -1272,6 +1462,7 @@ mod tests {
 
         let cred_ctr = 42;
         let (cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_use_data,
@@ -1322,5 +1513,26 @@ mod tests {
 
         // Check policy
         assert_eq!(cdi.values.policy, policy, "CDI policy is invalid");
+
+        // Check that a cancelled computation fails.
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = create_credential_cancellable(
+            &ChallengeContext::Legacy,
+            context,
+            &id_object,
+            &id_use_data,
+            cred_ctr,
+            policy,
+            &acc_data,
+            &Left(EXPIRY),
+            &cancel,
+        )
+        .expect_err("Cancelled credential creation should fail.");
+        assert_eq!(
+            err.downcast_ref::<crypto_common::Cancelled>(),
+            Some(&crypto_common::Cancelled),
+            "Credential creation should fail due to cancellation."
+        );
     }
 }
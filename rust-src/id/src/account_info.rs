@@ -0,0 +1,139 @@
+//! Parsing of the credentials of an account as reported by the node.
+//!
+//! The account info returned by the node has the credentials of the account in
+//! the field `accountCredentials`, which maps credential indices to versioned
+//! credentials without proofs. The functions in this module parse them, so
+//! that a verifier of proofs about the attributes of an account can get the
+//! commitments to the attributes, the revealed attributes, and the keys of the
+//! account directly from the response of the node.
+use crate::types::*;
+use crypto_common::{
+    types::CredentialIndex,
+    version::{Versioned, VERSION_0},
+};
+use curve_arithmetic::Curve;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+/// Reasons why the credentials of an account could not be parsed.
+pub enum AccountInfoError {
+    #[error("Invalid credentials: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Credential {index} has unsupported version {version}.")]
+    UnsupportedVersion { index: u8, version: u32 },
+}
+
+/// The credentials of an account, indexed by credential index.
+pub type AccountCredentials<C, AttributeType> =
+    BTreeMap<CredentialIndex, AccountCredentialWithoutProofs<C, AttributeType>>;
+
+/// Parse the credentials of an account. The input is either the account info
+/// returned by the node, i.e., an object with the field `accountCredentials`,
+/// or the value of that field.
+pub fn parse_account_credentials<
+    C: Curve,
+    AttributeType: Attribute<C::Scalar> + DeserializeOwned,
+>(
+    value: &serde_json::Value,
+) -> Result<AccountCredentials<C, AttributeType>, AccountInfoError> {
+    let credentials = value.get("accountCredentials").unwrap_or(value);
+    let versioned: BTreeMap<
+        CredentialIndex,
+        Versioned<AccountCredentialWithoutProofs<C, AttributeType>>,
+    > = serde_json::from_value(credentials.clone())?;
+    versioned
+        .into_iter()
+        .map(|(index, cred)| {
+            if cred.version != VERSION_0 {
+                return Err(AccountInfoError::UnsupportedVersion {
+                    index:   index.index,
+                    version: cred.version.value,
+                });
+            }
+            Ok((index, cred.value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::*, test::*};
+    use crypto_common::version::Version;
+    use rand::*;
+    use random_oracle::ChallengeContext;
+
+    #[test]
+    fn test_parse_account_credentials() {
+        let mut csprng = thread_rng();
+
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
+        };
+        let identity = test_create_identity(&mut csprng, 5, 10, &acc_data);
+        let policy = Policy {
+            valid_to:   identity.id_object.alist.valid_to,
+            created_at: identity.id_object.alist.created_at,
+            policy_vec: BTreeMap::new(),
+            _phantom:   Default::default(),
+        };
+        let cred_data = CredentialData {
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
+        };
+        let cdi = identity.create_credential(&ChallengeContext::Legacy, 0, policy, &cred_data);
+        let icdi = identity.icdi;
+
+        let initial =
+            AccountCredentialWithoutProofs::<ArCurve, AttributeKind>::Initial { icdv: icdi.values };
+        let normal = AccountCredentialWithoutProofs::<ArCurve, AttributeKind>::Normal {
+            cdv:         cdi.values,
+            commitments: cdi.proofs.id_proofs.commitments,
+        };
+        let account_info = serde_json::json!({
+            "accountNonce": 1,
+            "accountCredentials": {
+                "0": Versioned::new(VERSION_0, &initial),
+                "1": Versioned::new(VERSION_0, &normal),
+            },
+        });
+
+        let parsed: AccountCredentials<ArCurve, AttributeKind> =
+            parse_account_credentials(&account_info).expect("Credentials should parse.");
+        assert_eq!(
+            parse_account_credentials::<ArCurve, AttributeKind>(
+                &account_info["accountCredentials"]
+            )
+            .expect("Credentials should parse.")
+            .len(),
+            2
+        );
+        let parsed_initial = &parsed[&CredentialIndex { index: 0 }];
+        let parsed_normal = &parsed[&CredentialIndex { index: 1 }];
+        assert_eq!(parsed_initial.cred_id(), initial.cred_id());
+        assert!(parsed_initial.commitments().is_none());
+        assert_eq!(parsed_normal.cred_id(), normal.cred_id());
+        assert_eq!(parsed_normal.public_keys(), normal.public_keys());
+        assert_eq!(parsed_normal.ip_identity(), ip_info.ip_identity);
+        assert_eq!(parsed_normal.commitments(), normal.commitments());
+        let tag = AttributeTag::from(0u8);
+        assert!(parsed_normal.attribute_commitment(tag).is_some());
+        assert_eq!(
+            parsed_normal.attribute_commitment(tag),
+            normal.attribute_commitment(tag)
+        );
+
+        let unsupported =
+            serde_json::json!({ "0": Versioned::new(Version { value: 1 }, &initial) });
+        assert!(matches!(
+            parse_account_credentials::<ArCurve, AttributeKind>(&unsupported),
+            Err(AccountInfoError::UnsupportedVersion {
+                index:   0,
+                version: 1,
+            })
+        ));
+    }
+}
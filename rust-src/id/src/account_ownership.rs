@@ -0,0 +1,49 @@
+//! Proofs of ownership of an account for third parties, e.g., an exchange that
+//! asks a user to prove that a withdrawal address is theirs.
+//!
+//! The verifier chooses an [OwnershipChallenge] with a fresh nonce, the wallet
+//! signs it with the keys of the account with
+//! [prove_account_ownership_challenge](crate::id_prover::prove_account_ownership_challenge),
+//! and the verifier checks the signatures against the credentials of the
+//! account on the chain with
+//! [verify_account_ownership_challenge](crate::id_verifier::verify_account_ownership_challenge).
+use crate::types::*;
+use crypto_common::{types::CredentialIndex, *};
+use crypto_common_derive::*;
+use std::collections::BTreeMap;
+
+/// Prefix of the signed encoding of an [OwnershipChallenge], so that the
+/// signatures are not valid for any other message that is signed with the
+/// account keys.
+const OWNERSHIP_CHALLENGE_DOMAIN: &[u8] = b"ConcordiumAccountOwnershipChallenge";
+
+/// The challenge of a third party that asks for a proof of ownership of an
+/// account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnershipChallenge {
+    /// Who asks for the proof, e.g., the domain of an exchange. The wallet
+    /// should show it to the user before signing.
+    #[string_size_length = 4]
+    #[max_size = 1024]
+    pub verifier: String,
+    /// A nonce chosen by the verifier. It must be fresh for every challenge,
+    /// so that proofs cannot be replayed.
+    #[size_length = 4]
+    #[max_size = 1024]
+    pub nonce:    Vec<u8>,
+}
+
+impl OwnershipChallenge {
+    /// The bytes that the keys of the account sign, i.e., the domain followed
+    /// by the serialization of the challenge.
+    pub fn to_signed_bytes(&self) -> Vec<u8> {
+        let mut out = OWNERSHIP_CHALLENGE_DOMAIN.to_vec();
+        out.put(self);
+        out
+    }
+}
+
+/// The proofs of ownership of an account, one for each credential that
+/// signed. The account threshold of credentials must have signed for the proof
+/// to be valid.
+pub type AccountOwnershipProofs = BTreeMap<CredentialIndex, AccountOwnershipProof>;
@@ -1,7 +1,23 @@
 //! Functionality needed by the anonymity revoker.
-use crate::{secret_sharing::*, types::*};
+use crate::{
+    secret_sharing::*,
+    sigma_protocols::{
+        common::*,
+        dlog::{Dlog, DlogSecret},
+        dlog_eq::{DlogEqual, Proof},
+    },
+    types::*,
+};
+use anyhow::{bail, ensure};
+use crypto_common::*;
 use curve_arithmetic::*;
-use elgamal::Message;
+use dodis_yampolskiy_prf as prf;
+use elgamal::{
+    decrypt_from_chunks_given_table, BabyStepGiantStep, Cipher, Message, PublicKey, SecretKey,
+};
+use rand::Rng;
+use random_oracle::{domains, ChallengeContext, RandomOracle};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Reveal the `idCredPub` based on the given shares.
 /// It is important to remember that this always succeeds in computing
@@ -23,3 +39,425 @@ pub fn reveal_id_cred_pub<C: Curve>(shares: &[(ArIdentity, Message<C>)]) -> C {
 /// value is meaningful must be ensured by the caller, e.g., by making sure that
 /// the threshold is compatible with the number of shares.
 pub fn reveal_prf_key<C: Curve>(shares: &[(ArIdentity, Value<C>)]) -> C::Scalar { reveal(&shares) }
+
+/// Proof that a message is the decryption of a ciphertext with the secret key
+/// of a given public key.
+pub type DecryptionProof<C> = Proof<C>;
+
+/// The statement that `message` is the decryption of `cipher`, i.e., that
+/// `cipher.1 - message` is `cipher.0` to the power of the secret key.
+fn decryption_statement<C: Curve>(
+    public_key: &PublicKey<C>,
+    cipher: &Cipher<C>,
+    message: &Message<C>,
+) -> DlogEqual<C> {
+    DlogEqual {
+        dlog1: Dlog {
+            public: public_key.key,
+            coeff:  public_key.generator,
+        },
+        dlog2: Dlog {
+            public: cipher.1.minus_point(&message.value),
+            coeff:  cipher.0,
+        },
+    }
+}
+
+/// Decryption together with a proof that it is correct, which anonymity
+/// revokers attach to the shares they decrypt so that they can be checked
+/// before they are combined.
+pub trait VerifiableDecryption<C: Curve> {
+    /// Decrypt the ciphertext and prove that the message is its decryption,
+    /// without revealing the secret key. The challenge of the proof includes
+    /// the given context, and the proof only verifies with
+    /// [verify_decryption] in the same context.
+    fn decrypt_with_proof<R: Rng>(
+        &self,
+        context: &ChallengeContext,
+        cipher: &Cipher<C>,
+        csprng: &mut R,
+    ) -> (Message<C>, DecryptionProof<C>);
+}
+
+impl<C: Curve> VerifiableDecryption<C> for SecretKey<C> {
+    fn decrypt_with_proof<R: Rng>(
+        &self,
+        context: &ChallengeContext,
+        cipher: &Cipher<C>,
+        csprng: &mut R,
+    ) -> (Message<C>, DecryptionProof<C>) {
+        let message = self.decrypt(cipher);
+        let statement = decryption_statement(&PublicKey::from(self), cipher, &message);
+        let secret = DlogSecret {
+            secret: Value::new(self.scalar),
+        };
+        let mut ro = RandomOracle::domain_in_context(context, domains::ELGAMAL_DECRYPTION_PROOF);
+        // Proving the equality of discrete logarithms does not fail.
+        let proof = prove(&mut ro, &statement, secret, csprng)
+            .expect("Proving correct decryption should succeed.");
+        (message, proof)
+    }
+}
+
+/// Verify a proof produced by [VerifiableDecryption::decrypt_with_proof] that
+/// the message is the decryption of the ciphertext with the secret key of the
+/// public key. The proof only verifies if it was produced in the same context.
+pub fn verify_decryption<C: Curve>(
+    context: &ChallengeContext,
+    public_key: &PublicKey<C>,
+    cipher: &Cipher<C>,
+    message: &Message<C>,
+    proof: &DecryptionProof<C>,
+) -> bool {
+    let statement = decryption_statement(public_key, cipher, message);
+    let mut ro = RandomOracle::domain_in_context(context, domains::ELGAMAL_DECRYPTION_PROOF);
+    verify(&mut ro, &statement, proof)
+}
+
+/// Decrypt the share of `idCredPub` of the anonymity revoker from the
+/// anonymity revocation data of a credential, with a proof that the decryption
+/// is correct. Returns `None` if the anonymity revoker is not among those of
+/// the credential.
+pub fn decrypt_id_cred_pub_share<C: Curve, R: Rng>(
+    ar: &ArData<C>,
+    ar_data: &BTreeMap<ArIdentity, ChainArData<C>>,
+    csprng: &mut R,
+) -> Option<ChainArDecryptedData<C>> {
+    let ar_identity = ar.public_ar_info.ar_identity;
+    let single_ar_data = ar_data.get(&ar_identity)?;
+    let (id_cred_pub_share, proof) = ar.ar_secret_key.decrypt_with_proof(
+        &ChallengeContext::Legacy,
+        &single_ar_data.enc_id_cred_pub_share,
+        csprng,
+    );
+    Some(ChainArDecryptedData {
+        ar_identity,
+        id_cred_pub_share,
+        decryption_proof: Some(proof),
+    })
+}
+
+/// Check that there are enough shares for the threshold, that there is at
+/// most one share of each anonymity revoker, and that all of them are among
+/// the given anonymity revokers.
+fn check_shares(
+    threshold: Threshold,
+    ar_identities: impl IntoIterator<Item = ArIdentity>,
+    known: &BTreeSet<ArIdentity>,
+) -> anyhow::Result<()> {
+    let mut seen = BTreeSet::new();
+    for ar_identity in ar_identities {
+        ensure!(
+            known.contains(&ar_identity),
+            "Anonymity revoker {} is not among the anonymity revokers of the data.",
+            ar_identity
+        );
+        ensure!(
+            seen.insert(ar_identity),
+            "Duplicate share of anonymity revoker {}.",
+            ar_identity
+        );
+    }
+    ensure!(
+        seen.len() >= usize::from(threshold),
+        "Insufficient number of anonymity revokers ({}). Threshold is {}.",
+        seen.len(),
+        threshold
+    );
+    Ok(())
+}
+
+/// Combine the decrypted shares of `idCredPub` of a credential with the given
+/// revocation threshold and anonymity revocation data. This fails if there are
+/// too few shares, or if some are duplicate or not of anonymity revokers of
+/// the credential. If the public keys of the anonymity revokers are given, the
+/// decryption proofs of the shares are checked as well, and shares without
+/// proofs are rejected.
+pub fn combine_id_cred_pub_shares<C: Curve, A: HasArPublicKey<C>>(
+    threshold: Threshold,
+    ar_data: &BTreeMap<ArIdentity, ChainArData<C>>,
+    ar_keys: Option<&BTreeMap<ArIdentity, A>>,
+    shares: &[ChainArDecryptedData<C>],
+) -> anyhow::Result<C> {
+    let known = ar_data.keys().copied().collect();
+    check_shares(threshold, shares.iter().map(|s| s.ar_identity), &known)?;
+    if let Some(ar_keys) = ar_keys {
+        for share in shares {
+            let ar_identity = share.ar_identity;
+            let key = match ar_keys.get(&ar_identity) {
+                Some(ar) => ar.get_public_key(),
+                None => bail!("No public key of anonymity revoker {}.", ar_identity),
+            };
+            let proof = match &share.decryption_proof {
+                Some(proof) => proof,
+                None => bail!(
+                    "The share of anonymity revoker {} has no decryption proof.",
+                    ar_identity
+                ),
+            };
+            // The share is known to be in the data from the check above.
+            let cipher = &ar_data[&ar_identity].enc_id_cred_pub_share;
+            ensure!(
+                verify_decryption(
+                    &ChallengeContext::Legacy,
+                    key,
+                    cipher,
+                    &share.id_cred_pub_share,
+                    proof
+                ),
+                "The share of anonymity revoker {} was not decrypted correctly.",
+                ar_identity
+            );
+        }
+    }
+    let shares = shares
+        .iter()
+        .map(|s| (s.ar_identity, s.id_cred_pub_share))
+        .collect::<Vec<_>>();
+    Ok(reveal_id_cred_pub(&shares))
+}
+
+/// Decrypt the share of the PRF key of the anonymity revoker from the data of
+/// an anonymity revocation record. The table must be for the generator
+/// [GlobalContext::encryption_in_exponent_generator] the shares are encrypted
+/// with. Returns `None` if the anonymity revoker is not among those of the
+/// record.
+pub fn decrypt_prf_key_share<C: Curve>(
+    ar: &ArData<C>,
+    ar_data: &BTreeMap<ArIdentity, IpArData<C>>,
+    table: &BabyStepGiantStep<C>,
+) -> Option<IpArDecryptedData<C>> {
+    let ar_identity = ar.public_ar_info.ar_identity;
+    let single_ar_data = ar_data.get(&ar_identity)?;
+    let prf_key_share = decrypt_from_chunks_given_table(
+        &ar.ar_secret_key,
+        &single_ar_data.enc_prf_key_share,
+        table,
+        CHUNK_SIZE,
+    );
+    Some(IpArDecryptedData {
+        ar_identity,
+        prf_key_share,
+    })
+}
+
+/// Combine the decrypted shares of the PRF key of the anonymity revocation
+/// record. This fails if there are too few shares, or if some are duplicate or
+/// not of anonymity revokers of the record.
+pub fn combine_prf_key_shares<C: Curve>(
+    record: &AnonymityRevocationRecord<C>,
+    shares: &[IpArDecryptedData<C>],
+) -> anyhow::Result<prf::SecretKey<C>> {
+    let known = record.ar_data.keys().copied().collect();
+    check_shares(
+        record.threshold,
+        shares.iter().map(|s| s.ar_identity),
+        &known,
+    )?;
+    let shares = shares
+        .iter()
+        .map(|s| (s.ar_identity, s.prf_key_share.clone()))
+        .collect::<Vec<_>>();
+    Ok(prf::SecretKey::new(reveal_prf_key(&shares)))
+}
+
+/// An account that can have been created from an identity, i.e., the account
+/// whose first credential has the given credential counter.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct TracedAccount<C: Curve> {
+    pub cred_counter:    u8,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub reg_id:          C,
+    pub account_address: AccountAddress,
+}
+
+/// Compute the registration ids and addresses of all the accounts that can
+/// have been created from the identity with the given PRF key, i.e., those
+/// with credential counters up to and including `max_accounts`.
+pub fn trace_accounts<C: Curve>(
+    prf_key: &prf::SecretKey<C>,
+    global_context: &GlobalContext<C>,
+    max_accounts: u8,
+) -> Vec<TracedAccount<C>> {
+    let g = global_context.on_chain_commitment_key.g;
+    (0..=max_accounts)
+        .filter_map(|cred_counter| {
+            // The PRF is undefined for the few counters where the exponent
+            // is not invertible, and no account can be created with them.
+            let exponent = prf_key.prf_exponent(cred_counter).ok()?;
+            let reg_id = g.mul_by_scalar(&exponent);
+            Some(TracedAccount {
+                cred_counter,
+                reg_id,
+                account_address: AccountAddress::new(&reg_id),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_decrypt_with_proof() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let message = Message::<G1>::generate(&mut csprng);
+        let cipher = pk.encrypt(&mut csprng, &message);
+        let (decrypted, proof) =
+            sk.decrypt_with_proof(&ChallengeContext::Legacy, &cipher, &mut csprng);
+        assert_eq!(decrypted, message);
+        assert!(verify_decryption(
+            &ChallengeContext::Legacy,
+            &pk,
+            &cipher,
+            &decrypted,
+            &proof
+        ));
+
+        // A wrong message, or a different key, is rejected.
+        let other = Message::<G1>::generate(&mut csprng);
+        assert!(!verify_decryption(
+            &ChallengeContext::Legacy,
+            &pk,
+            &cipher,
+            &other,
+            &proof
+        ));
+        let other_pk = PublicKey::from(&SecretKey::generate(&pk.generator, &mut csprng));
+        assert!(!verify_decryption(
+            &ChallengeContext::Legacy,
+            &other_pk,
+            &cipher,
+            &decrypted,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_decrypt_with_proof_in_context() {
+        let mut csprng = thread_rng();
+        let sk = SecretKey::<G1>::generate_all(&mut csprng);
+        let pk = PublicKey::from(&sk);
+        let message = Message::<G1>::generate(&mut csprng);
+        let cipher = pk.encrypt(&mut csprng, &message);
+        let context = random_oracle::ProofContext {
+            network_id:       b"testnet".to_vec(),
+            protocol_version: 1,
+            statement_type:   "ElGamalDecryption".to_owned(),
+        };
+        let in_context = ChallengeContext::Versioned(context.clone());
+        let (decrypted, proof) = sk.decrypt_with_proof(&in_context, &cipher, &mut csprng);
+        assert!(verify_decryption(
+            &in_context,
+            &pk,
+            &cipher,
+            &decrypted,
+            &proof
+        ));
+        assert!(
+            !verify_decryption(&ChallengeContext::Legacy, &pk, &cipher, &decrypted, &proof),
+            "Proof should not verify without the context."
+        );
+        let other_network = ChallengeContext::Versioned(random_oracle::ProofContext {
+            network_id: b"mainnet".to_vec(),
+            ..context
+        });
+        assert!(
+            !verify_decryption(&other_network, &pk, &cipher, &decrypted, &proof),
+            "Proof should not verify on another network."
+        );
+    }
+
+    #[test]
+    fn test_revoke_id_cred_pub() {
+        let mut csprng = thread_rng();
+        let generator = G1::generate(&mut csprng);
+        let id_cred_sec = G1::generate_scalar(&mut csprng);
+        let ar_identities = (1..=3).map(ArIdentity::new).collect::<Vec<_>>();
+        let sharing_data = share::<G1, _, _, _>(
+            &id_cred_sec,
+            ar_identities.iter().copied(),
+            Threshold(2),
+            &mut csprng,
+        );
+        let mut ars = BTreeMap::new();
+        let mut ar_data = BTreeMap::new();
+        for (&ar_identity, share) in ar_identities.iter().zip(sharing_data.shares.iter()) {
+            let ar_secret_key = SecretKey::generate(&generator, &mut csprng);
+            let ar_public_key = PublicKey::from(&ar_secret_key);
+            let message = Message {
+                value: generator.mul_by_scalar(share),
+            };
+            ar_data.insert(ar_identity, ChainArData {
+                enc_id_cred_pub_share: ar_public_key.encrypt(&mut csprng, &message),
+            });
+            ars.insert(ar_identity, ArData {
+                public_ar_info: ArInfo {
+                    ar_identity,
+                    ar_description: mk_dummy_description(format!("AR {}", ar_identity)),
+                    ar_public_key,
+                },
+                ar_secret_key,
+            });
+        }
+        let ar_infos = ars
+            .iter()
+            .map(|(id, ar)| (*id, ar.public_ar_info.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let shares = ars
+            .values()
+            .map(|ar| decrypt_id_cred_pub_share(ar, &ar_data, &mut csprng))
+            .collect::<Option<Vec<_>>>()
+            .expect("All anonymity revokers are in the data.");
+
+        let id_cred_pub = generator.mul_by_scalar(&id_cred_sec);
+        let combined =
+            combine_id_cred_pub_shares(Threshold(2), &ar_data, Some(&ar_infos), &shares[1..])
+                .expect("Shares should combine.");
+        assert_eq!(combined, id_cred_pub);
+
+        // Too few or duplicate shares are rejected.
+        assert!(
+            combine_id_cred_pub_shares(Threshold(2), &ar_data, Some(&ar_infos), &shares[..1])
+                .is_err()
+        );
+        let duplicate = [shares[0].clone(), shares[0].clone()];
+        assert!(
+            combine_id_cred_pub_shares(Threshold(2), &ar_data, Some(&ar_infos), &duplicate)
+                .is_err()
+        );
+        // A wrong share is rejected if the keys are given, and only then.
+        let mut wrong = shares.clone();
+        wrong[0].id_cred_pub_share = Message::generate(&mut csprng);
+        assert!(
+            combine_id_cred_pub_shares(Threshold(3), &ar_data, Some(&ar_infos), &wrong).is_err()
+        );
+        let combined =
+            combine_id_cred_pub_shares::<_, ArInfo<G1>>(Threshold(3), &ar_data, None, &wrong)
+                .expect("Proofs are not checked.");
+        assert_ne!(combined, id_cred_pub);
+    }
+
+    #[test]
+    fn test_trace_accounts() {
+        let mut csprng = thread_rng();
+        let global_context = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let prf_key = prf::SecretKey::generate(&mut csprng);
+        let accounts = trace_accounts(&prf_key, &global_context, 3);
+        assert_eq!(accounts.len(), 4);
+        for (i, account) in accounts.iter().enumerate() {
+            assert_eq!(usize::from(account.cred_counter), i);
+            assert_eq!(
+                account.account_address,
+                AccountAddress::new(&account.reg_id)
+            );
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Exports of the anonymity revocation functionality for use from other
+//! languages, so that anonymity revoker backends can reuse this library
+//! instead of the `anonymity_revocation` tool.
+//!
+//! Every function takes a NUL-terminated UTF8 string with a JSON object as
+//! input, and returns a NUL-terminated UTF8 string that must be freed with
+//! [free_ar_response_string]. If the function succeeds the `success` flag is
+//! set to 1 and the response is JSON. Otherwise the flag is set to 0 and the
+//! response is an error message.
+//!
+//! The JSON formats are those of the files used by the `anonymity_revocation`
+//! tool, without the version wrapper:
+//! - `arData` is the private data of the anonymity revoker, i.e., its public
+//!   information together with its secret key,
+//! - `credential` is the values of a normal credential as deployed on the
+//!   chain,
+//! - `arRecord` is the anonymity revocation record of an identity, as stored by
+//!   the identity provider,
+//! - `global` is the global context.
+use crate::{anonymity_revoker::*, constants::ArCurve, types::*};
+use anyhow::bail;
+use crypto_common::*;
+use dodis_yampolskiy_prf as prf;
+use elgamal::BabyStepGiantStep;
+use ffi_helpers::json::{call_with_input, free_response_string, try_get};
+use lazy_static::lazy_static;
+use rand::thread_rng;
+use serde_json::{from_str, json, to_string, Value};
+use std::{
+    collections::BTreeMap,
+    os::raw::c_char,
+    sync::{Arc, Mutex},
+};
+
+/// The size of the table for decrypting the shares of PRF keys. The shares are
+/// encrypted in chunks of [CHUNK_SIZE](crate::constants) bits.
+const PRF_KEY_TABLE_SIZE: u64 = 1 << 16;
+
+lazy_static! {
+    /// The table for decrypting the shares of PRF keys, together with the
+    /// generator it was computed for. All shares of a chain are encrypted with
+    /// the same generator, so the table is only computed again if the
+    /// generator changes.
+    static ref PRF_KEY_TABLE: Mutex<Option<(ArCurve, Arc<BabyStepGiantStep<ArCurve>>)>> =
+        Mutex::new(None);
+}
+
+/// The table for decrypting the shares of PRF keys encrypted in the exponent
+/// of the generator.
+fn prf_key_table(generator: &ArCurve) -> Arc<BabyStepGiantStep<ArCurve>> {
+    let mut cached = PRF_KEY_TABLE
+        .lock()
+        .expect("PRF key table lock is not poisoned.");
+    match &*cached {
+        Some((cached_generator, table)) if cached_generator == generator => table.clone(),
+        _ => {
+            let table = Arc::new(BabyStepGiantStep::new(generator, PRF_KEY_TABLE_SIZE));
+            *cached = Some((*generator, table.clone()));
+            table
+        }
+    }
+}
+
+/// The values of the normal credential in the `credential` field.
+fn get_credential(v: &Value) -> anyhow::Result<CredentialDeploymentValues<ArCurve, AttributeKind>> {
+    match try_get(v, "credential")? {
+        AccountCredentialValues::<ArCurve, AttributeKind>::Normal { cdi } => Ok(cdi),
+        AccountCredentialValues::Initial { .. } => {
+            bail!("Initial credentials have no anonymity revocation data.")
+        }
+    }
+}
+
+/// Decrypt the share of `idCredPub` of the anonymity revoker `arData` from the
+/// `credential`. The output is the share with a proof that it was decrypted
+/// correctly, to be passed to [ar_combine_id_cred_pub_shares].
+fn decrypt_id_cred_pub_share_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let ar: ArData<ArCurve> = try_get(&v, "arData")?;
+    let credential = get_credential(&v)?;
+    match decrypt_id_cred_pub_share(&ar, &credential.ar_data, &mut thread_rng()) {
+        Some(share) => Ok(to_string(&share)?),
+        None => bail!("The anonymity revoker is not among those of the credential."),
+    }
+}
+
+/// Combine the decrypted `shares` of `idCredPub` of the `credential`. If the
+/// optional field `arsInfos` with the public information of the anonymity
+/// revokers is given, the decryption proofs of the shares are checked. The
+/// output is an object with the field `idCredPub`.
+fn combine_id_cred_pub_shares_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let credential = get_credential(&v)?;
+    let shares: Vec<ChainArDecryptedData<ArCurve>> = try_get(&v, "shares")?;
+    let ars_infos: Option<BTreeMap<ArIdentity, ArInfo<ArCurve>>> = match v.get("arsInfos") {
+        Some(_) => Some(try_get(&v, "arsInfos")?),
+        None => None,
+    };
+    let id_cred_pub = combine_id_cred_pub_shares(
+        credential.threshold,
+        &credential.ar_data,
+        ars_infos.as_ref(),
+        &shares,
+    )?;
+    Ok(to_string(&json!({
+        "idCredPub": base16_encode_string(&id_cred_pub)
+    }))?)
+}
+
+/// Decrypt the share of the PRF key of the anonymity revoker `arData` from the
+/// `arRecord`, using the `global` context. The output is to be passed to
+/// [ar_combine_prf_key_shares].
+fn decrypt_prf_key_share_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let ar: ArData<ArCurve> = try_get(&v, "arData")?;
+    let ar_record: AnonymityRevocationRecord<ArCurve> = try_get(&v, "arRecord")?;
+    let global_context: GlobalContext<ArCurve> = try_get(&v, "global")?;
+    let table = prf_key_table(global_context.encryption_in_exponent_generator());
+    match decrypt_prf_key_share(&ar, &ar_record.ar_data, &table) {
+        Some(share) => Ok(to_string(&share)?),
+        None => bail!("The anonymity revoker is not among those of the record."),
+    }
+}
+
+/// Combine the decrypted `shares` of the PRF key of the `arRecord`. The output
+/// is an object with the field `prfKey`.
+fn combine_prf_key_shares_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let ar_record: AnonymityRevocationRecord<ArCurve> = try_get(&v, "arRecord")?;
+    let shares: Vec<IpArDecryptedData<ArCurve>> = try_get(&v, "shares")?;
+    let prf_key = combine_prf_key_shares(&ar_record, &shares)?;
+    Ok(to_string(&json!({
+        "prfKey": base16_encode_string(&prf_key)
+    }))?)
+}
+
+/// List the accounts that can have been created from the identity with the
+/// recovered `prfKey`, using the `global` context. The number of accounts is
+/// given by the field `maxAccounts`, which is the field of the same name of
+/// the anonymity revocation record. The output is a list of objects with the
+/// fields `credCounter`, `regId` and `accountAddress`.
+fn trace_accounts_aux(input: &str) -> anyhow::Result<String> {
+    let v: Value = from_str(input)?;
+    let prf_key: prf::SecretKey<ArCurve> = try_get(&v, "prfKey")?;
+    let global_context: GlobalContext<ArCurve> = try_get(&v, "global")?;
+    let max_accounts: u8 = try_get(&v, "maxAccounts")?;
+    Ok(to_string(&trace_accounts(
+        &prf_key,
+        &global_context,
+        max_accounts,
+    ))?)
+}
+
+/// Make an exported wrapper of a function from a JSON input string to a JSON
+/// output string.
+macro_rules! make_ar_wrapper {
+    ($(#[$attr:meta])* $name:ident => $f:ident) => {
+        $(#[$attr])*
+        ///
+        /// # Safety
+        /// The `input` pointer must be NULL or point to a NUL-terminated
+        /// string, and the `success` pointer must be valid. The returned string
+        /// must be freed with [free_ar_response_string].
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(input: *const c_char, success: *mut u8) -> *mut c_char {
+            call_with_input(input, success, $f)
+        }
+    };
+}
+
+make_ar_wrapper!(
+    /// Decrypt a share of `idCredPub`. The input has the fields `arData` and
+    /// `credential`. The output is the decrypted share with a proof of correct
+    /// decryption.
+    ar_decrypt_id_cred_pub_share => decrypt_id_cred_pub_share_aux
+);
+
+make_ar_wrapper!(
+    /// Combine the decrypted shares of `idCredPub`. The input has the fields
+    /// `credential` and `shares`, and optionally `arsInfos`, in which case the
+    /// decryption proofs of the shares are checked. The output has the field
+    /// `idCredPub`.
+    ar_combine_id_cred_pub_shares => combine_id_cred_pub_shares_aux
+);
+
+make_ar_wrapper!(
+    /// Decrypt a share of the PRF key. The input has the fields `arData`,
+    /// `arRecord` and `global`. The output is the decrypted share.
+    ar_decrypt_prf_key_share => decrypt_prf_key_share_aux
+);
+
+make_ar_wrapper!(
+    /// Combine the decrypted shares of the PRF key. The input has the fields
+    /// `arRecord` and `shares`. The output has the field `prfKey`.
+    ar_combine_prf_key_shares => combine_prf_key_shares_aux
+);
+
+make_ar_wrapper!(
+    /// List the accounts of an identity with a recovered PRF key. The input has
+    /// the fields `prfKey`, `global` and `maxAccounts`. The output is a list of
+    /// the credential counters, registration ids and addresses of the accounts.
+    ar_trace_accounts => trace_accounts_aux
+);
+
+/// Free a string returned by one of the functions of this module. The string
+/// is overwritten first, since decrypted shares and PRF keys are secret.
+///
+/// # Safety
+/// The pointer must be NULL or have been returned by one of the functions of
+/// this module, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn free_ar_response_string(ptr: *mut c_char) { free_response_string(ptr) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve_arithmetic::Curve;
+    use std::ffi::{CStr, CString};
+
+    /// Call an exported function on the input, and return the success flag and
+    /// the response.
+    fn call(
+        f: unsafe extern "C" fn(*const c_char, *mut u8) -> *mut c_char,
+        input: &str,
+    ) -> (u8, String) {
+        let input = CString::new(input).unwrap();
+        let mut success = 2;
+        unsafe {
+            let response = f(input.as_ptr(), &mut success);
+            let s = CStr::from_ptr(response).to_str().unwrap().to_owned();
+            free_ar_response_string(response);
+            (success, s)
+        }
+    }
+
+    #[test]
+    fn test_ar_trace_accounts() {
+        let global = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let prf_key = prf::SecretKey::<ArCurve>::generate(&mut thread_rng());
+        let input = json!({
+            "prfKey": prf_key,
+            "global": global,
+            "maxAccounts": 2,
+        });
+        let (success, response) = call(ar_trace_accounts, &input.to_string());
+        assert_eq!(success, 1, "Tracing should succeed: {}", response);
+        let accounts: Vec<TracedAccount<ArCurve>> = from_str(&response).unwrap();
+        assert_eq!(accounts, trace_accounts(&prf_key, &global, 2));
+
+        let (success, response) = call(ar_trace_accounts, "{}");
+        assert_eq!(success, 0);
+        assert!(response.contains("prfKey"));
+        let (success, _) = call(ar_combine_prf_key_shares, "not JSON");
+        assert_eq!(success, 0);
+    }
+
+    #[test]
+    fn test_prf_key_table_cache() {
+        let global = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let generator = global.encryption_in_exponent_generator();
+        let table = prf_key_table(generator);
+        assert!(Arc::ptr_eq(&table, &prf_key_table(generator)));
+        let other = generator.double_point();
+        assert!(!Arc::ptr_eq(&table, &prf_key_table(&other)));
+    }
+}
@@ -0,0 +1,273 @@
+//! Canonical JSON encoding of values exchanged with identity providers.
+//!
+//! Identity objects, credentials, and related values are exchanged as JSON
+//! between wallets and identity providers, and some of them are signed or
+//! hashed. The JSON produced by different libraries differs in whitespace, the
+//! order of object keys, and the formatting of numbers, so the canonical
+//! encoding defined here is used whenever a JSON value is signed or hashed.
+//!
+//! The canonical encoding of a value is
+//! - without any whitespace outside strings,
+//! - with object keys sorted by their UTF-8 bytes,
+//! - with numbers written as decimal integers without a fraction, an exponent,
+//!   or leading zeros. Values with numbers that are not integers have no
+//!   canonical encoding,
+//! - with strings escaped minimally, i.e., only `"`, `\` and control characters
+//!   are escaped, the latter with the short forms `\b`, `\t`, `\n`, `\f`, `\r`
+//!   where they exist and `\u00xx` otherwise.
+//!
+//! JSON text with duplicate object keys is rejected by [canonicalize], since
+//! different parsers disagree on which of the duplicates is used.
+use serde::{
+    de::{self, Deserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize as SerdeDeserialize, Serialize as SerdeSerialize,
+};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+/// Reasons why a value has no canonical JSON encoding.
+pub enum CanonicalJsonError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The number {0} is not an integer.")]
+    NotInteger(serde_json::Number),
+}
+
+/// A JSON value in canonical form. Numbers are integers, and objects have no
+/// duplicate keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CanonicalValue {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<CanonicalValue>),
+    Object(BTreeMap<String, CanonicalValue>),
+}
+
+fn canonical_number(n: serde_json::Number) -> Result<CanonicalValue, CanonicalJsonError> {
+    if n.is_u64() || n.is_i64() {
+        Ok(CanonicalValue::Number(n))
+    } else {
+        Err(CanonicalJsonError::NotInteger(n))
+    }
+}
+
+impl CanonicalValue {
+    fn from_value(value: serde_json::Value) -> Result<Self, CanonicalJsonError> {
+        use serde_json::Value;
+        match value {
+            Value::Null => Ok(CanonicalValue::Null),
+            Value::Bool(b) => Ok(CanonicalValue::Bool(b)),
+            Value::Number(n) => canonical_number(n),
+            Value::String(s) => Ok(CanonicalValue::String(s)),
+            Value::Array(xs) => Ok(CanonicalValue::Array(
+                xs.into_iter()
+                    .map(Self::from_value)
+                    .collect::<Result<_, _>>()?,
+            )),
+            Value::Object(map) => Ok(CanonicalValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| Ok((k, Self::from_value(v)?)))
+                    .collect::<Result<_, CanonicalJsonError>>()?,
+            )),
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        match self {
+            CanonicalValue::Null => out.push_str("null"),
+            CanonicalValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            CanonicalValue::Number(n) => out.push_str(&n.to_string()),
+            CanonicalValue::String(s) => write_string(s, out),
+            CanonicalValue::Array(xs) => {
+                out.push('[');
+                for (i, x) in xs.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    x.write(out);
+                }
+                out.push(']');
+            }
+            CanonicalValue::Object(map) => {
+                out.push('{');
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_string(k, out);
+                    out.push(':');
+                    v.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push_str(&serde_json::to_string(s).expect("Serializing a string cannot fail."))
+}
+
+/// Parsing of JSON text, which fails on duplicate keys and on numbers that are
+/// not integers.
+impl<'de> SerdeDeserialize<'de> for CanonicalValue {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        struct CanonicalVisitor;
+
+        impl<'de> Visitor<'de> for CanonicalVisitor {
+            type Value = CanonicalValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "A JSON value.")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> { Ok(CanonicalValue::Null) }
+
+            fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+                Ok(CanonicalValue::Bool(b))
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+                Ok(CanonicalValue::Number(n.into()))
+            }
+
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+                Ok(CanonicalValue::Number(n.into()))
+            }
+
+            fn visit_f64<E: de::Error>(self, n: f64) -> Result<Self::Value, E> {
+                Err(E::custom(format!("The number {} is not an integer.", n)))
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(CanonicalValue::String(s.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, s: String) -> Result<Self::Value, E> {
+                Ok(CanonicalValue::String(s))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut xs = Vec::new();
+                while let Some(x) = seq.next_element()? {
+                    xs.push(x);
+                }
+                Ok(CanonicalValue::Array(xs))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut res = BTreeMap::new();
+                while let Some((k, v)) = map.next_entry::<String, _>()? {
+                    if res.contains_key(&k) {
+                        return Err(de::Error::custom(format!("Duplicate key {}.", k)));
+                    }
+                    res.insert(k, v);
+                }
+                Ok(CanonicalValue::Object(res))
+            }
+        }
+
+        des.deserialize_any(CanonicalVisitor)
+    }
+}
+
+/// The canonical JSON encoding of a value.
+pub fn to_canonical_json<T: SerdeSerialize + ?Sized>(
+    value: &T,
+) -> Result<String, CanonicalJsonError> {
+    let value = CanonicalValue::from_value(serde_json::to_value(value)?)?;
+    let mut out = String::new();
+    value.write(&mut out);
+    Ok(out)
+}
+
+/// Convert JSON text to its canonical encoding. This fails if the text is not
+/// valid JSON, if it has duplicate keys, or if it has numbers that are not
+/// integers.
+pub fn canonicalize(json: &str) -> Result<String, CanonicalJsonError> {
+    let value: CanonicalValue = serde_json::from_str(json)?;
+    let mut out = String::new();
+    value.write(&mut out);
+    Ok(out)
+}
+
+/// The SHA256 hash of the canonical JSON encoding of a value.
+pub fn canonical_json_hash<T: SerdeSerialize + ?Sized>(
+    value: &T,
+) -> Result<[u8; 32], CanonicalJsonError> {
+    Ok(hash_str(&to_canonical_json(value)?))
+}
+
+/// The SHA256 hash of the canonical encoding of JSON text. This is the same as
+/// the [canonical_json_hash] of the value the text encodes.
+pub fn canonical_json_str_hash(json: &str) -> Result<[u8; 32], CanonicalJsonError> {
+    Ok(hash_str(&canonicalize(json)?))
+}
+
+fn hash_str(s: &str) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(s.as_bytes()));
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::*, test::*, types::*};
+    use rand::*;
+
+    #[test]
+    fn test_canonicalize() {
+        let json = "{ \"b\": [1, -2, {\"y\": null, \"x\": true}],\n \"a\": \"\\u0041\\n\\/\" }";
+        assert_eq!(
+            canonicalize(json).expect("Valid JSON."),
+            "{\"a\":\"A\\n/\",\"b\":[1,-2,{\"x\":true,\"y\":null}]}"
+        );
+        assert!(canonicalize("{\"a\": 1, \"a\": 2}").is_err());
+        assert!(canonicalize("[1.5]").is_err());
+        assert!(canonicalize("[1e3]").is_err());
+        assert!(to_canonical_json(&1.5f64).is_err());
+    }
+
+    #[test]
+    fn test_canonical_json_identity_object() {
+        let mut csprng = thread_rng();
+
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
+        };
+        let TestIdentity {
+            id_object, icdi, ..
+        } = test_create_identity(&mut csprng, 5, 10, &acc_data);
+        let credential = AccountCredential::<IpPairing, ArCurve, _>::Initial { icdi };
+
+        for (canonical, pretty) in [
+            (
+                to_canonical_json(&id_object).unwrap(),
+                serde_json::to_string_pretty(&id_object).unwrap(),
+            ),
+            (
+                to_canonical_json(&credential).unwrap(),
+                serde_json::to_string_pretty(&credential).unwrap(),
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(&canonicalize(pretty).unwrap(), canonical);
+            assert_eq!(&canonicalize(canonical).unwrap(), canonical);
+            assert_eq!(
+                canonical_json_str_hash(pretty).unwrap(),
+                hash_str(canonical)
+            );
+        }
+        assert_eq!(
+            canonical_json_hash(&id_object).unwrap(),
+            canonical_json_str_hash(&serde_json::to_string(&id_object).unwrap()).unwrap()
+        );
+    }
+}
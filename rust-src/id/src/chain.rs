@@ -5,14 +5,14 @@ use crate::{
     types::*,
     utils,
 };
-use bulletproofs::range_proof::verify_less_than_or_equal;
+use bulletproofs::range_proof::{verify_less_than_or_equal, BatchVerifier};
 use core::fmt::{self, Display};
 use crypto_common::{to_bytes, types::TransactionTime};
 use curve_arithmetic::{Curve, Pairing};
 use ed25519_dalek::Verifier;
 use either::Either;
-use pedersen_scheme::{Commitment, CommitmentKey, Randomness, Value};
-use random_oracle::RandomOracle;
+use pedersen_scheme::{Commitment, CommitmentKey, PrecomputedCommitmentKey, Randomness, Value};
+use random_oracle::{domains, ChallengeContext, RandomOracle};
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -27,6 +27,8 @@ pub enum CdiVerificationError {
     Policy,
     Ar,
     Proof,
+    Expired,
+    IpIdentity,
 }
 
 impl Display for CdiVerificationError {
@@ -40,17 +42,22 @@ impl Display for CdiVerificationError {
             CdiVerificationError::Policy => write!(f, "PolicyVerificationError"),
             CdiVerificationError::Ar => write!(f, "AnonymityRevokerVerificationError"),
             CdiVerificationError::Proof => write!(f, "ProofVerificationError"),
+            CdiVerificationError::Expired => write!(f, "MessageExpiredError"),
+            CdiVerificationError::IpIdentity => write!(f, "IdentityProviderMismatchError"),
         }
     }
 }
 /// Verify credential deployment info. This checks that the data is consistent,
-/// and that the credential is signed by the specified identity provider.
+/// and that the credential is signed by the specified identity provider, which
+/// must be the one named in the credential. The proofs only verify if they were
+/// produced in the given context.
 pub fn verify_cdi<
     P: Pairing,
     C: Curve<Scalar = P::ScalarField>,
     AttributeType: Attribute<C::Scalar>,
     A: HasArPublicKey<C>,
 >(
+    context: &ChallengeContext,
     global_context: &GlobalContext<C>,
     ip_info: &IpInfo<P>,
     // NB: The following map only needs to be a superset of the ars
@@ -59,9 +66,73 @@ pub fn verify_cdi<
     cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
     new_or_existing: &Either<TransactionTime, AccountAddress>,
 ) -> Result<(), CdiVerificationError> {
+    let mut ro = verify_cdi_sigma_proof(
+        context,
+        global_context,
+        ip_info,
+        known_ars,
+        cdi,
+        new_or_existing,
+    )?;
+    let on_chain_commitment_key = global_context.on_chain_commitment_key;
+    let commitments = &cdi.proofs.id_proofs.commitments;
+    if !verify_less_than_or_equal(
+        &mut ro,
+        8,
+        &commitments.cmm_cred_counter,
+        &commitments.cmm_max_accounts,
+        &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
+        global_context.bulletproof_generators(),
+        &on_chain_commitment_key,
+    ) {
+        return Err(CdiVerificationError::Proof);
+    }
+    let cdv = &cdi.values;
+    let proofs = &cdi.proofs;
+    let signed = utils::credential_hash_to_sign(&cdv, &proofs.id_proofs, new_or_existing);
+    // Notice that here we provide all the verification keys, and the
+    // function `verify_accunt_ownership_proof` assumes that
+    // we have as many signatures as verification keys.
+    if !utils::verify_account_ownership_proof(
+        &cdv.cred_key_info.keys,
+        cdv.cred_key_info.threshold,
+        &proofs.proof_acc_sk,
+        signed.as_ref(),
+    ) {
+        return Err(CdiVerificationError::AccountOwnership);
+    }
+
+    let check_policy = verify_policy(&on_chain_commitment_key, &commitments, &cdi.values.policy);
+
+    if !check_policy {
+        return Err(CdiVerificationError::Policy);
+    }
+
+    Ok(())
+}
+
+/// The checks of [verify_cdi] up to and including the sigma protocol proof.
+/// The returned transcript is the one from which the range proof on the
+/// credential counter is to be verified.
+fn verify_cdi_sigma_proof<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    context: &ChallengeContext,
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    cdi: &CredentialDeploymentInfo<P, C, AttributeType>,
+    new_or_existing: &Either<TransactionTime, AccountAddress>,
+) -> Result<RandomOracle, CdiVerificationError> {
     // We need to check that the threshold is actually equal to
     // the number of coefficients in the sharing polynomial
     // (corresponding to the degree+1)
+    if cdi.values.ip_identity != ip_info.ip_identity {
+        return Err(CdiVerificationError::IpIdentity);
+    }
     let addr = new_or_existing.as_ref().right();
     let rt_usize: usize = cdi.values.threshold.into();
     if rt_usize
@@ -75,10 +146,9 @@ pub fn verify_cdi<
         return Err(CdiVerificationError::Ar);
     }
     let on_chain_commitment_key = global_context.on_chain_commitment_key;
-    let gens = global_context.bulletproof_generators();
     let ip_verify_key = &ip_info.ip_verify_key;
     // Compute the challenge prefix by hashing the values.
-    let mut ro = RandomOracle::domain("credential");
+    let mut ro = RandomOracle::domain_in_context(context, domains::CREDENTIAL);
     ro.append_message(b"cred_values", &cdi.values);
     ro.append_message(b"address", &addr);
     ro.append_message(b"global_context", &global_context);
@@ -99,11 +169,8 @@ pub fn verify_cdi<
     // FIXME: Figure out a pattern to get rid of these clone's.
     let witness_reg_id = cdi.proofs.id_proofs.proof_reg_id.clone();
 
-    let cdv = &cdi.values;
-    let proofs = &cdi.proofs;
-
     let verifier_sig = pok_sig_verifier(
-        &on_chain_commitment_key,
+        &global_context.precomputed_commitment_key(),
         cdi.values.threshold,
         &cdi.values
             .ar_data
@@ -151,42 +218,11 @@ pub fn verify_cdi<
     if !verify(&mut ro, &verifier, &proof) {
         return Err(CdiVerificationError::Proof);
     }
-
-    if !verify_less_than_or_equal(
-        &mut ro,
-        8,
-        &cdi.proofs.id_proofs.commitments.cmm_cred_counter,
-        &cdi.proofs.id_proofs.commitments.cmm_max_accounts,
-        &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
-        &gens,
-        &on_chain_commitment_key,
-    ) {
-        return Err(CdiVerificationError::Proof);
-    }
-    let signed = utils::credential_hash_to_sign(&cdv, &proofs.id_proofs, new_or_existing);
-    // Notice that here we provide all the verification keys, and the
-    // function `verify_accunt_ownership_proof` assumes that
-    // we have as many signatures as verification keys.
-    if !utils::verify_account_ownership_proof(
-        &cdv.cred_key_info.keys,
-        cdv.cred_key_info.threshold,
-        &proofs.proof_acc_sk,
-        signed.as_ref(),
-    ) {
-        return Err(CdiVerificationError::AccountOwnership);
-    }
-
-    let check_policy = verify_policy(&on_chain_commitment_key, &commitments, &cdi.values.policy);
-
-    if !check_policy {
-        return Err(CdiVerificationError::Policy);
-    }
-
-    Ok(())
+    Ok(ro)
 }
 
 /// Verify initial account creation. This is essentially checking that the
-/// signature by the identity provider is correct.
+/// signature by the identity provider named in the credential is correct.
 pub fn verify_initial_cdi<
     P: Pairing,
     C: Curve<Scalar = P::ScalarField>,
@@ -196,6 +232,9 @@ pub fn verify_initial_cdi<
     cdi: &InitialCredentialDeploymentInfo<C, AttributeType>,
     expiry: TransactionTime,
 ) -> Result<(), CdiVerificationError> {
+    if cdi.values.ip_identity != ip_info.ip_identity {
+        return Err(CdiVerificationError::IpIdentity);
+    }
     let mut hasher = Sha256::new();
     hasher.update(&to_bytes(&expiry));
     hasher.update(&to_bytes(&cdi.values));
@@ -206,6 +245,188 @@ pub fn verify_initial_cdi<
     }
 }
 
+/// Verify an account credential message before submitting it to the chain.
+/// This checks that the message has not expired at time `now`, and then
+/// verifies the credential in the same way as the node does. This is intended
+/// for third parties that relay credential deployments, who should also submit
+/// exactly [AccountCredentialMessage::submission_bytes] of the message that was
+/// checked.
+pub fn verify_account_credential_message<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    message: &AccountCredentialMessage<P, C, AttributeType>,
+    now: TransactionTime,
+) -> Result<(), CdiVerificationError> {
+    if message.message_expiry < now {
+        return Err(CdiVerificationError::Expired);
+    }
+    match &message.credential {
+        AccountCredential::Initial { icdi } => {
+            verify_initial_cdi(ip_info, icdi, message.message_expiry)
+        }
+        AccountCredential::Normal { cdi } => verify_cdi(
+            &ChallengeContext::Legacy,
+            global_context,
+            ip_info,
+            known_ars,
+            cdi,
+            &Either::Left(message.message_expiry),
+        ),
+    }
+}
+
+/// Verify a batch of account credential messages that are all issued by the
+/// same identity provider, and use the same anonymity revokers and global
+/// context. This is intended for identity providers that need to check many
+/// sign-ups at once.
+///
+/// All ed25519 signatures, i.e., the signatures of the identity provider on
+/// initial credentials and the account ownership proofs of normal credentials,
+/// are checked together using ed25519 batch verification. The range proofs of
+/// normal credentials are checked together with a single multi-exponentiation.
+/// The remaining checks of normal credentials are done as in [verify_cdi],
+/// using the message expiry as the transaction time.
+///
+/// If verification fails the index of the first failing message in the input
+/// is returned together with the reason, which is the same as the result of
+/// verifying the messages one by one.
+pub fn verify_account_credential_messages_batch<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    messages: &[AccountCredentialMessage<P, C, AttributeType>],
+) -> Result<(), (usize, CdiVerificationError)> {
+    if !verify_account_credential_messages_batch_worker(
+        global_context,
+        ip_info,
+        known_ars,
+        messages,
+    ) {
+        // Batch verification only tells us that some check failed, so fall
+        // back to individual checks to find the first culprit.
+        for (i, msg) in messages.iter().enumerate() {
+            verify_account_credential_message(
+                global_context,
+                ip_info,
+                known_ars,
+                msg,
+                msg.message_expiry,
+            )
+            .map_err(|e| (i, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Do all the checks of [verify_account_credential_messages_batch], and
+/// return whether they all succeed.
+fn verify_account_credential_messages_batch_worker<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+    A: HasArPublicKey<C>,
+>(
+    global_context: &GlobalContext<C>,
+    ip_info: &IpInfo<P>,
+    known_ars: &BTreeMap<ArIdentity, A>,
+    messages: &[AccountCredentialMessage<P, C, AttributeType>],
+) -> bool {
+    let csprng = &mut rand::thread_rng();
+    let on_chain_commitment_key = global_context.on_chain_commitment_key;
+    let mut range_proofs = BatchVerifier::new(global_context.bulletproof_generators());
+    // The signed messages are hashes of the credentials, which are kept
+    // separately from the signatures since the signatures borrow from the
+    // input.
+    let mut hashes = Vec::with_capacity(messages.len());
+    let mut sigs = Vec::new();
+    for msg in messages.iter() {
+        match &msg.credential {
+            AccountCredential::Initial { icdi } => {
+                if icdi.values.ip_identity != ip_info.ip_identity {
+                    return false;
+                }
+                let mut hasher = Sha256::new();
+                hasher.update(&to_bytes(&msg.message_expiry));
+                hasher.update(&to_bytes(&icdi.values));
+                hashes.push(hasher.finalize().to_vec());
+                sigs.push(vec![(&ip_info.ip_cdi_verify_key, &*icdi.sig)]);
+            }
+            AccountCredential::Normal { cdi } => {
+                let new_or_existing = Either::Left(msg.message_expiry);
+                // Credentials on the chain are verified without a context.
+                let mut ro = match verify_cdi_sigma_proof(
+                    &ChallengeContext::Legacy,
+                    global_context,
+                    ip_info,
+                    known_ars,
+                    cdi,
+                    &new_or_existing,
+                ) {
+                    Ok(ro) => ro,
+                    Err(_) => return false,
+                };
+                let commitments = &cdi.proofs.id_proofs.commitments;
+                if range_proofs
+                    .add_less_than_or_equal(
+                        &mut ro,
+                        8,
+                        &commitments.cmm_cred_counter,
+                        &commitments.cmm_max_accounts,
+                        &cdi.proofs.id_proofs.cred_counter_less_than_max_accounts,
+                        &on_chain_commitment_key,
+                        csprng,
+                    )
+                    .is_err()
+                {
+                    return false;
+                }
+                let cdv = &cdi.values;
+                match utils::account_ownership_signatures(
+                    &cdv.cred_key_info.keys,
+                    cdv.cred_key_info.threshold,
+                    &cdi.proofs.proof_acc_sk,
+                ) {
+                    Some(cred_sigs) => sigs.push(cred_sigs),
+                    None => return false,
+                }
+                hashes.push(utils::credential_hash_to_sign(
+                    cdv,
+                    &cdi.proofs.id_proofs,
+                    &new_or_existing,
+                ));
+                if !verify_policy(&on_chain_commitment_key, commitments, &cdv.policy) {
+                    return false;
+                }
+            }
+        }
+    }
+    if range_proofs.verify().is_err() {
+        return false;
+    }
+    let mut msgs = Vec::new();
+    let mut keys = Vec::new();
+    let mut signatures = Vec::new();
+    for (hash, cred_sigs) in hashes.iter().zip(sigs) {
+        for (key, sig) in cred_sigs {
+            msgs.push(hash.as_slice());
+            keys.push(*key);
+            signatures.push(*sig);
+        }
+    }
+    msgs.is_empty() || ed25519_dalek::verify_batch(&msgs, &signatures, &keys).is_ok()
+}
+
 /// verify id_cred data
 fn id_cred_pub_verifier<C: Curve, A: HasArPublicKey<C>>(
     commitment_key: &CommitmentKey<C>,
@@ -271,7 +492,7 @@ fn pok_sig_verifier<
     C: Curve<Scalar = P::ScalarField>,
     AttributeType: Attribute<C::Scalar>,
 >(
-    commitment_key: &'a CommitmentKey<C>,
+    commitment_key: &PrecomputedCommitmentKey<C>,
     threshold: Threshold,
     choice_ar_parameters: &BTreeSet<ArIdentity>,
     policy: &'a Policy<C, AttributeType>,
@@ -343,7 +564,7 @@ fn pok_sig_verifier<
         commitments: comm_vec,
         // FIXME: Figure out how to restructure to get rid of this clone.
         ps_pub_key:  ip_pub_key.clone(),
-        comm_key:    *commitment_key,
+        comm_key:    *commitment_key.key(),
     })
 }
 
@@ -352,7 +573,6 @@ mod tests {
     use super::*;
 
     use crate::{account_holder::*, constants::*, identity_provider::*, test::*};
-    use crypto_common::types::{KeyIndex, KeyPair};
     use pairing::bls12_381::G1;
     use rand::*;
     use std::collections::btree_map::BTreeMap;
@@ -362,118 +582,81 @@ mod tests {
         seconds: 111111111111111111,
     };
 
+    fn test_policy() -> Policy<ArCurve, AttributeKind> {
+        Policy {
+            valid_to:   YearMonth::new(2022, 5).unwrap(), // May 2022
+            created_at: YearMonth::new(2020, 5).unwrap(), // May 2020
+            policy_vec: {
+                let mut tree = BTreeMap::new();
+                tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+                tree
+            },
+            _phantom:   Default::default(),
+        }
+    }
+
     #[test]
     fn test_verify_cdi() {
         let mut csprng = thread_rng();
-
-        // Generate PIO
-        let max_attrs = 10;
-        let num_ars = 5;
-        let IpData {
-            public_ip_info: ip_info,
-            ip_secret_key,
-            ip_cdi_secret_key,
-        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
-        let global_ctx = GlobalContext::<G1>::generate(String::from("genesis_string"));
-        let (ars_infos, _) =
-            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
-        let aci = test_create_aci(&mut csprng);
-        let initial_acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
-        let (context, pio, randomness) = test_create_pio(
-            &aci,
-            &ip_info,
-            &ars_infos,
-            &global_ctx,
-            num_ars,
-            &initial_acc_data,
-        );
-        let alist = test_create_attributes();
-        let ver_ok = verify_credentials(
-            &pio,
-            context,
-            &alist,
-            EXPIRY,
-            &ip_secret_key,
-            &ip_cdi_secret_key,
-        );
-        assert!(ver_ok.is_ok());
+        let TestIdentity {
+            ip_info,
+            ars_infos,
+            global_ctx,
+            id_object,
+            id_use_data,
+            ..
+        } = test_create_identity(&mut csprng, 5, 10, &acc_data);
 
         // Generate CDI
-        let (ip_sig, _) = ver_ok.unwrap();
-        let id_object = IdentityObject {
-            pre_identity_object: pio,
-            alist,
-            signature: ip_sig,
-        };
-        let id_use_data = IdObjectUseData { aci, randomness };
-        let valid_to = YearMonth::new(2022, 5).unwrap(); // May 2022
-        let created_at = YearMonth::new(2020, 5).unwrap(); // May 2020
-        let policy = Policy {
-            valid_to,
-            created_at,
-            policy_vec: {
-                let mut tree = BTreeMap::new();
-                tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
-                tree
-            },
-            _phantom: Default::default(),
-        };
+        let context = IpContext::new(&ip_info, &ars_infos, &global_ctx);
         let cred_data = CredentialData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
-        let context = IpContext::new(&ip_info, &ars_infos, &global_ctx);
         let (cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_use_data,
             0,
-            policy.clone(),
+            test_policy(),
             &cred_data,
             &Left(EXPIRY),
         )
         .expect("Should generate the credential successfully.");
-        let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &cdi, &Left(EXPIRY));
+        let cdi_check = verify_cdi(
+            &ChallengeContext::Legacy,
+            &global_ctx,
+            &ip_info,
+            &ars_infos,
+            &cdi,
+            &Left(EXPIRY),
+        );
         assert_eq!(cdi_check, Ok(()));
 
         // Testing with an existing RegId (i.e. an existing account)
         let existing_reg_id = AccountAddress::new(&cdi.values.cred_id);
         let cred_data = CredentialData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
         let (cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_use_data,
             1,
-            policy,
+            test_policy(),
             &cred_data,
             &Right(existing_reg_id),
         )
         .expect("Should generate the credential successfully.");
         let cdi_check = verify_cdi(
+            &ChallengeContext::Legacy,
             &global_ctx,
             &ip_info,
             &ars_infos,
@@ -483,11 +666,71 @@ mod tests {
         assert_eq!(cdi_check, Ok(()));
     }
 
+    #[test]
+    fn test_verify_cdi_in_context() {
+        let mut csprng = thread_rng();
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 3),
+            threshold: SignatureThreshold(2),
+        };
+        let identity = test_create_identity(&mut csprng, 3, 10, &acc_data);
+        let proof_context = random_oracle::ProofContext {
+            network_id:       b"testnet".to_vec(),
+            protocol_version: 1,
+            statement_type:   "CredentialDeployment".to_owned(),
+        };
+        let in_context = ChallengeContext::Versioned(proof_context.clone());
+        let cred_data = CredentialData {
+            keys:      test_create_keys(&mut csprng, 3),
+            threshold: SignatureThreshold(2),
+        };
+        let cdi = identity.create_credential(&in_context, 0, test_policy(), &cred_data);
+        let verify = |ctx: &ChallengeContext| {
+            verify_cdi(
+                ctx,
+                &identity.global_ctx,
+                &identity.ip_info,
+                &identity.ars_infos,
+                &cdi,
+                &Left(EXPIRY),
+            )
+        };
+        assert_eq!(verify(&in_context), Ok(()));
+        assert_eq!(
+            verify(&ChallengeContext::Legacy),
+            Err(CdiVerificationError::Proof),
+            "Credential should not verify without the context."
+        );
+        let other_network = ChallengeContext::Versioned(random_oracle::ProofContext {
+            network_id: b"mainnet".to_vec(),
+            ..proof_context
+        });
+        assert_eq!(
+            verify(&other_network),
+            Err(CdiVerificationError::Proof),
+            "Credential should not verify on another network."
+        );
+    }
+
     #[test]
     fn test_verify_initial_cdi() {
         let mut csprng = thread_rng();
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 3),
+            threshold: SignatureThreshold(2),
+        };
+        let TestIdentity { ip_info, icdi, .. } =
+            test_create_identity(&mut csprng, 5, 10, &acc_data);
+
+        // Verify initial CDI
+        let cdi_check = verify_initial_cdi(&ip_info, &icdi, EXPIRY);
+        assert_eq!(cdi_check, Ok(()));
+    }
+
+    #[test]
+    fn test_verify_account_credential_messages_batch() {
+        let mut csprng = thread_rng();
 
-        // Generate PIO
         let max_attrs = 10;
         let num_ars = 5;
         let IpData {
@@ -498,33 +741,176 @@ mod tests {
         let global_ctx = GlobalContext::<G1>::generate(String::from("genesis_string"));
         let (ars_infos, _) =
             test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
-        let aci = test_create_aci(&mut csprng);
+        let mut messages = Vec::new();
+        for _ in 0..3 {
+            let aci = test_create_aci(&mut csprng);
+            let acc_data = InitialAccountData {
+                keys:      test_create_keys(&mut csprng, 1),
+                threshold: SignatureThreshold(1),
+            };
+            let (context, pio, randomness) =
+                test_create_pio(&aci, &ip_info, &ars_infos, &global_ctx, num_ars, &acc_data);
+            let alist = test_create_attributes();
+            let (ip_sig, icdi) = verify_credentials(
+                &pio,
+                context,
+                &alist,
+                EXPIRY,
+                &ip_secret_key,
+                &ip_cdi_secret_key,
+            )
+            .expect("Credentials should be valid.");
+            messages.push(AccountCredentialMessage::<IpPairing, ArCurve, _> {
+                message_expiry: EXPIRY,
+                credential:     AccountCredential::Initial { icdi },
+            });
+
+            // A normal credential from the same identity object.
+            let id_object = IdentityObject {
+                pre_identity_object: pio,
+                alist,
+                signature: ip_sig,
+            };
+            let id_use_data = IdObjectUseData { aci, randomness };
+            let policy = Policy {
+                valid_to:   YearMonth::new(2022, 5).unwrap(),
+                created_at: YearMonth::new(2020, 5).unwrap(),
+                policy_vec: BTreeMap::new(),
+                _phantom:   Default::default(),
+            };
+            let cred_data = CredentialData {
+                keys:      test_create_keys(&mut csprng, 2),
+                threshold: SignatureThreshold(2),
+            };
+            let context = IpContext::new(&ip_info, &ars_infos, &global_ctx);
+            let (cdi, _) = create_credential(
+                &ChallengeContext::Legacy,
+                context,
+                &id_object,
+                &id_use_data,
+                1,
+                policy,
+                &cred_data,
+                &Left(EXPIRY),
+            )
+            .expect("Should generate the credential successfully.");
+            messages.push(AccountCredentialMessage {
+                message_expiry: EXPIRY,
+                credential:     AccountCredential::Normal { cdi },
+            });
+        }
+        let check =
+            verify_account_credential_messages_batch(&global_ctx, &ip_info, &ars_infos, &messages);
+        assert_eq!(check, Ok(()));
+
+        // The single and batch verifiers agree on a wrong identity provider.
+        let mut other_ip = ip_info.clone();
+        other_ip.ip_identity = IpIdentity(ip_info.ip_identity.0 + 1);
+        for msg in messages.iter() {
+            let check =
+                verify_account_credential_message(&global_ctx, &other_ip, &ars_infos, msg, EXPIRY);
+            assert_eq!(check, Err(CdiVerificationError::IpIdentity));
+        }
+        let check =
+            verify_account_credential_messages_batch(&global_ctx, &other_ip, &ars_infos, &messages);
+        assert_eq!(check, Err((0, CdiVerificationError::IpIdentity)));
+        let check = verify_account_credential_messages_batch(
+            &global_ctx,
+            &other_ip,
+            &ars_infos,
+            &messages[1..],
+        );
+        assert_eq!(check, Err((0, CdiVerificationError::IpIdentity)));
+
+        // Changing the expiry of a normal credential invalidates the account
+        // ownership proof.
+        messages[3].message_expiry = TransactionTime {
+            seconds: EXPIRY.seconds + 1,
+        };
+        let check =
+            verify_account_credential_messages_batch(&global_ctx, &ip_info, &ars_infos, &messages);
+        assert_eq!(check, Err((3, CdiVerificationError::AccountOwnership)));
+
+        // Changing the expiry of an initial credential invalidates its
+        // signature, and it is reported since it comes first.
+        messages[2].message_expiry = TransactionTime {
+            seconds: EXPIRY.seconds + 1,
+        };
+        let check =
+            verify_account_credential_messages_batch(&global_ctx, &ip_info, &ars_infos, &messages);
+        assert_eq!(check, Err((2, CdiVerificationError::Signature)));
+    }
+
+    #[test]
+    fn test_account_credential_message_submission() {
+        let mut csprng = thread_rng();
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
-            threshold: SignatureThreshold(2),
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
         };
-        let (context, pio, _) =
-            test_create_pio(&aci, &ip_info, &ars_infos, &global_ctx, num_ars, &acc_data);
-        let alist = test_create_attributes();
-        let ver_ok = verify_credentials(
-            &pio,
-            context,
-            &alist,
-            EXPIRY,
-            &ip_secret_key,
-            &ip_cdi_secret_key,
+        let TestIdentity {
+            ip_info,
+            ars_infos,
+            global_ctx,
+            icdi,
+            ..
+        } = test_create_identity(&mut csprng, 5, 10, &acc_data);
+        let message = AccountCredentialMessage::<IpPairing, ArCurve, _> {
+            message_expiry: EXPIRY,
+            credential:     AccountCredential::Initial { icdi },
+        };
+
+        let bytes = message.submission_bytes();
+        assert_eq!(bytes[0], 0, "Submissions are version 0.");
+        assert_eq!(bytes[1], CREDENTIAL_DEPLOYMENT_TAG);
+        let parsed =
+            AccountCredentialMessage::<IpPairing, ArCurve, AttributeKind>::from_submission_bytes(
+                &bytes,
+            )
+            .expect("Submission bytes should parse.");
+        assert_eq!(parsed.hash(), message.hash());
+        assert!(
+            AccountCredentialMessage::<IpPairing, ArCurve, AttributeKind>::from_submission_bytes(
+                &[&bytes[..], &[0u8]].concat()
+            )
+            .is_err(),
+            "Trailing bytes should be rejected."
         );
-        assert!(ver_ok.is_ok());
 
-        // Verify initial CDI
-        let (_, initial_cdi) = ver_ok.unwrap();
-        let cdi_check = verify_initial_cdi(&ip_info, &initial_cdi, EXPIRY);
-        assert_eq!(cdi_check, Ok(()));
+        let before_expiry = TransactionTime {
+            seconds: EXPIRY.seconds - 1,
+        };
+        let check = verify_account_credential_message(
+            &global_ctx,
+            &ip_info,
+            &ars_infos,
+            &parsed,
+            before_expiry,
+        );
+        assert_eq!(check, Ok(()));
+        let after_expiry = TransactionTime {
+            seconds: EXPIRY.seconds + 1,
+        };
+        let check = verify_account_credential_message(
+            &global_ctx,
+            &ip_info,
+            &ars_infos,
+            &parsed,
+            after_expiry,
+        );
+        assert_eq!(check, Err(CdiVerificationError::Expired));
+
+        // The credential is rejected for an identity provider other than the
+        // one it names.
+        let mut other_ip = ip_info.clone();
+        other_ip.ip_identity = IpIdentity(ip_info.ip_identity.0 + 1);
+        let check = verify_account_credential_message(
+            &global_ctx,
+            &other_ip,
+            &ars_infos,
+            &parsed,
+            before_expiry,
+        );
+        assert_eq!(check, Err(CdiVerificationError::IpIdentity));
     }
 }
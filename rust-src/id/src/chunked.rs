@@ -0,0 +1,326 @@
+//! A chunked container format for downloading identity objects.
+//!
+//! Identity objects with many attributes and anonymity revokers are large, and
+//! fetching them as one blob is unreliable on mobile networks. The identity
+//! provider can instead split the serialized object into chunks with
+//! [split_into_chunks], and serve a [ChunkManifest] with the hash of every
+//! chunk and of the whole object. The client collects the chunks in a
+//! [Reassembler], which checks every chunk against the manifest as it
+//! arrives. The reassembler can be serialized to JSON and stored, so that an
+//! interrupted download is resumed by fetching only the chunks listed by
+//! [Reassembler::missing_chunks]. The data returned by
+//! [Reassembler::finish] has been checked against the hash of the whole
+//! object, and can then be parsed.
+use crypto_common::{derive::SerdeBase16Serialize, *};
+use serde::{de, Deserializer, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// SHA256 hash of a chunk or of the whole data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerdeBase16Serialize)]
+pub struct ChunkHash([u8; 32]);
+
+impl ChunkHash {
+    fn of(data: &[u8]) -> Self {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&Sha256::digest(data));
+        ChunkHash(hash)
+    }
+}
+
+impl Serial for ChunkHash {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.write_all(&self.0)
+            .expect("Writing to buffer should succeed.")
+    }
+}
+
+impl Deserial for ChunkHash {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let mut hash = [0u8; 32];
+        source.read_exact(&mut hash)?;
+        Ok(ChunkHash(hash))
+    }
+}
+
+/// Serialize bytes as a hex string. Used with `serialize_with` for byte
+/// vectors that are stored as JSON.
+pub(crate) fn hex_encode<S: Serializer>(data: &[u8], ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_str(&hex::encode(data))
+}
+
+/// Dual to [hex_encode].
+pub(crate) fn hex_decode<'de, D: Deserializer<'de>>(des: D) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(des)?;
+    hex::decode(&s).map_err(de::Error::custom)
+}
+
+/// Description of data that is split into chunks. All chunks except the last
+/// one have size `chunk_size`.
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkManifest {
+    /// Size of the whole data in bytes.
+    pub total_size:   u64,
+    /// Size of each chunk in bytes.
+    pub chunk_size:   u32,
+    /// Hashes of the chunks, in order.
+    pub chunk_hashes: Vec<ChunkHash>,
+    /// Hash of the whole data.
+    pub hash:         ChunkHash,
+}
+
+impl ChunkManifest {
+    /// Check that the number of chunks matches the size of the data.
+    fn validate(&self) -> Result<(), ChunkError> {
+        if self.chunk_size == 0 {
+            return Err(ChunkError::InvalidManifest);
+        }
+        let expected =
+            (self.total_size + u64::from(self.chunk_size) - 1) / u64::from(self.chunk_size);
+        if self.chunk_hashes.len() as u64 != expected {
+            return Err(ChunkError::InvalidManifest);
+        }
+        Ok(())
+    }
+
+    /// Expected size of the chunk with the given index.
+    fn chunk_len(&self, index: u32) -> u64 {
+        let start = u64::from(index) * u64::from(self.chunk_size);
+        std::cmp::min(
+            u64::from(self.chunk_size),
+            self.total_size.saturating_sub(start),
+        )
+    }
+}
+
+/// A chunk of the data, together with its position.
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub index: u32,
+    #[serde(serialize_with = "hex_encode", deserialize_with = "hex_decode")]
+    pub data:  Vec<u8>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+/// Reasons why chunks could not be reassembled.
+pub enum ChunkError {
+    #[error("The number of chunks in the manifest does not match the size of the data.")]
+    InvalidManifest,
+    #[error("Chunk {0} is not part of the data.")]
+    UnknownChunk(u32),
+    #[error("Chunk {0} does not match its hash in the manifest.")]
+    InvalidChunk(u32),
+    #[error("The data is incomplete, {0} chunks are missing.")]
+    Incomplete(usize),
+    #[error("The reassembled data does not match its hash in the manifest.")]
+    InvalidData,
+}
+
+/// Split the data into chunks of the given size, which must be positive.
+pub fn split_into_chunks(data: &[u8], chunk_size: u32) -> (ChunkManifest, Vec<Chunk>) {
+    assert!(chunk_size > 0, "The chunk size must be positive.");
+    let chunks: Vec<Chunk> = data
+        .chunks(chunk_size as usize)
+        .enumerate()
+        .map(|(index, data)| Chunk {
+            index: index as u32,
+            data:  data.to_vec(),
+        })
+        .collect();
+    let manifest = ChunkManifest {
+        total_size: data.len() as u64,
+        chunk_size,
+        chunk_hashes: chunks
+            .iter()
+            .map(|chunk| ChunkHash::of(&chunk.data))
+            .collect(),
+        hash: ChunkHash::of(data),
+    };
+    (manifest, chunks)
+}
+
+/// The state of a download. Chunks are added in any order as they arrive, and
+/// each chunk is checked against the manifest when it is added. The state can
+/// be stored as JSON to resume the download later. When it is parsed, the
+/// manifest and the stored chunks are checked in the same way as by
+/// [Reassembler::new] and [Reassembler::add_chunk].
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", try_from = "UncheckedReassembler")]
+pub struct Reassembler {
+    manifest: ChunkManifest,
+    #[serde(serialize_with = "serialize_received_chunks")]
+    received: BTreeMap<u32, Vec<u8>>,
+}
+
+/// The JSON representation of a [Reassembler], before the chunks are checked
+/// against the manifest.
+#[derive(SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+struct UncheckedReassembler {
+    manifest: ChunkManifest,
+    received: Vec<Chunk>,
+}
+
+impl std::convert::TryFrom<UncheckedReassembler> for Reassembler {
+    type Error = ChunkError;
+
+    fn try_from(value: UncheckedReassembler) -> Result<Self, Self::Error> {
+        let mut reassembler = Reassembler::new(value.manifest)?;
+        for chunk in value.received {
+            reassembler.add_chunk(chunk)?;
+        }
+        Ok(reassembler)
+    }
+}
+
+impl Reassembler {
+    /// Start a download of the data described by the manifest.
+    pub fn new(manifest: ChunkManifest) -> Result<Self, ChunkError> {
+        manifest.validate()?;
+        Ok(Reassembler {
+            manifest,
+            received: BTreeMap::new(),
+        })
+    }
+
+    /// The manifest of the download.
+    pub fn manifest(&self) -> &ChunkManifest { &self.manifest }
+
+    /// Add a chunk. The chunk is rejected if it does not match its hash in the
+    /// manifest. Adding a chunk that was already received has no effect.
+    pub fn add_chunk(&mut self, chunk: Chunk) -> Result<(), ChunkError> {
+        let expected = self
+            .manifest
+            .chunk_hashes
+            .get(chunk.index as usize)
+            .ok_or(ChunkError::UnknownChunk(chunk.index))?;
+        if chunk.data.len() as u64 != self.manifest.chunk_len(chunk.index)
+            || ChunkHash::of(&chunk.data) != *expected
+        {
+            return Err(ChunkError::InvalidChunk(chunk.index));
+        }
+        self.received.insert(chunk.index, chunk.data);
+        Ok(())
+    }
+
+    /// Indices of the chunks that have not been received yet, in increasing
+    /// order.
+    pub fn missing_chunks(&self) -> Vec<u32> {
+        (0..self.manifest.chunk_hashes.len() as u32)
+            .filter(|index| !self.received.contains_key(index))
+            .collect()
+    }
+
+    /// Whether all chunks have been received.
+    pub fn is_complete(&self) -> bool { self.received.len() == self.manifest.chunk_hashes.len() }
+
+    /// Concatenate the chunks, and check the result against the hash of the
+    /// whole data.
+    pub fn finish(self) -> Result<Vec<u8>, ChunkError> {
+        if !self.is_complete() {
+            return Err(ChunkError::Incomplete(self.missing_chunks().len()));
+        }
+        let mut data = Vec::with_capacity(self.manifest.total_size as usize);
+        for chunk in self.received.values() {
+            data.extend_from_slice(chunk);
+        }
+        if ChunkHash::of(&data) != self.manifest.hash {
+            return Err(ChunkError::InvalidData);
+        }
+        Ok(data)
+    }
+}
+
+/// Serialize the received chunks as a list of [Chunk]s.
+fn serialize_received_chunks<S: Serializer>(
+    received: &BTreeMap<u32, Vec<u8>>,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    received
+        .iter()
+        .map(|(&index, data)| Chunk {
+            index,
+            data: data.clone(),
+        })
+        .collect::<Vec<_>>()
+        .serialize(ser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::*;
+
+    #[test]
+    fn test_chunked_download() {
+        let mut csprng = thread_rng();
+        let data: Vec<u8> = (0..1000).map(|_| csprng.gen()).collect();
+        let (manifest, chunks) = split_into_chunks(&data, 64);
+        assert_eq!(chunks.len(), 16);
+        assert_eq!(chunks[15].data.len(), 1000 - 15 * 64);
+
+        let mut reassembler = Reassembler::new(manifest.clone()).expect("Manifest is valid.");
+        for chunk in chunks.iter().step_by(2) {
+            reassembler
+                .add_chunk(chunk.clone())
+                .expect("Chunk should be valid.");
+        }
+        assert_eq!(
+            reassembler.missing_chunks(),
+            (1..16).step_by(2).collect::<Vec<_>>()
+        );
+        assert_eq!(reassembler.clone().finish(), Err(ChunkError::Incomplete(8)));
+
+        // Store the state and resume the download.
+        let json = serde_json::to_string(&reassembler).expect("State should serialize.");
+        let mut reassembler: Reassembler =
+            serde_json::from_str(&json).expect("State should parse.");
+        let mut corrupted = chunks[1].clone();
+        corrupted.data[0] ^= 1;
+        assert_eq!(
+            reassembler.add_chunk(corrupted),
+            Err(ChunkError::InvalidChunk(1))
+        );
+        assert_eq!(
+            reassembler.add_chunk(Chunk {
+                index: 16,
+                data:  Vec::new(),
+            }),
+            Err(ChunkError::UnknownChunk(16))
+        );
+        for index in reassembler.missing_chunks() {
+            reassembler
+                .add_chunk(chunks[index as usize].clone())
+                .expect("Chunk should be valid.");
+        }
+        assert!(reassembler.is_complete());
+        assert_eq!(reassembler.finish(), Ok(data));
+
+        // Hashes are hex strings in JSON.
+        let hash_json = serde_json::to_value(&manifest.hash).expect("Hash should serialize.");
+        assert_eq!(
+            hash_json,
+            serde_json::Value::String(hex::encode(&manifest.hash.0))
+        );
+
+        let mut invalid = manifest.clone();
+        invalid.chunk_hashes.pop();
+        assert!(Reassembler::new(invalid).is_err());
+
+        // A stored state is checked against the manifest when it is parsed.
+        let mut reassembler = Reassembler::new(manifest).expect("Manifest is valid.");
+        reassembler
+            .add_chunk(chunks[0].clone())
+            .expect("Chunk should be valid.");
+        let mut state = serde_json::to_value(&reassembler).expect("State should serialize.");
+        state["received"][0]["data"] = serde_json::Value::String(hex::encode(&chunks[1].data));
+        assert!(serde_json::from_value::<Reassembler>(state.clone()).is_err());
+        state["received"] = serde_json::Value::Array(Vec::new());
+        state["manifest"]["chunkSize"] = serde_json::Value::from(0);
+        assert!(serde_json::from_value::<Reassembler>(state).is_err());
+    }
+}
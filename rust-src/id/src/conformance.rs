@@ -0,0 +1,236 @@
+//! Fixtures shared with the Haskell implementation.
+//!
+//! Credentials are created by Rust and verified by the node, which is written
+//! in Haskell, so the two implementations must agree on the serialization of
+//! credentials and on which credentials are valid. A fixture is a serialized
+//! object together with the checks it must pass, see [Fixture]. The
+//! [check_fixture] harness parses a fixture, checks that it serializes back to
+//! exactly the same bytes, and runs its checks.
+//!
+//! The only fixture at the moment is [CredentialFixture], in the format that
+//! the Haskell test `ConcordiumTests.Crypto.FFIVerify` reads. It is written by
+//! `generate_testdata` in rust-bins, so `testdata/testdata.bin` is produced by
+//! Rust and consumed by Haskell. There are no fixtures produced by the Haskell
+//! implementation in the repository yet. When they are added, they are checked
+//! with [check_fixture] in the same way.
+use crate::{
+    chain::{verify_cdi, verify_initial_cdi, CdiVerificationError},
+    constants::{ArCurve, AttributeKind, IpPairing},
+    types::*,
+};
+use anyhow::{bail, ensure};
+use crypto_common::{types::TransactionTime, *};
+use either::Either::{Left, Right};
+use random_oracle::ChallengeContext;
+use std::{collections::BTreeMap, io::Cursor};
+
+/// The expiry of the credentials in the fixtures. This must match `maxExpiry`
+/// in the Haskell test.
+pub const FIXTURE_EXPIRY: TransactionTime = TransactionTime { seconds: u64::MAX };
+
+/// The contents of a credential fixture, e.g., `testdata/testdata.bin`.
+pub struct CredentialFixture {
+    pub global_context: GlobalContext<ArCurve>,
+    pub ip_info:        IpInfo<IpPairing>,
+    pub ars_infos:      BTreeMap<ArIdentity, ArInfo<ArCurve>>,
+    /// A credential that creates a new account, with expiry
+    /// [FIXTURE_EXPIRY].
+    pub new_account:    CredentialDeploymentInfo<IpPairing, ArCurve, AttributeKind>,
+    /// The account created by `new_account`.
+    pub address:        AccountAddress,
+    /// A credential that is deployed to `address`.
+    pub existing:       CredentialDeploymentInfo<IpPairing, ArCurve, AttributeKind>,
+    /// An account that `existing` is not valid for.
+    pub other_address:  AccountAddress,
+    /// An initial credential with expiry [FIXTURE_EXPIRY].
+    pub initial:        InitialCredentialDeploymentInfo<ArCurve, AttributeKind>,
+}
+
+/// Write a value prefixed by the length of its serialization in 4 bytes.
+fn put_with_length<T: Serial, B: Buffer>(out: &mut B, value: &T) {
+    let bytes = to_bytes(value);
+    out.put(&(bytes.len() as u32));
+    out.write_all(&bytes)
+        .expect("Writing to buffer should succeed.");
+}
+
+/// Read a value prefixed by the length of its serialization in 4 bytes. The
+/// value must use exactly the given number of bytes.
+fn get_with_length<T: Deserial, R: ReadBytesExt>(source: &mut R) -> ParseResult<T> {
+    let len: u32 = source.get()?;
+    let bytes = deserial_bytes(source, len as usize)?;
+    let mut inner = Cursor::new(&bytes[..]);
+    let value = inner.get()?;
+    ensure!(
+        inner.position() == u64::from(len),
+        "Value uses {} of {} bytes.",
+        inner.position(),
+        len
+    );
+    Ok(value)
+}
+
+/// An object produced by one implementation and checked by the other. The
+/// harness [check_fixture] checks that the serialization round trips exactly
+/// and then calls [Fixture::verify].
+pub trait Fixture: Serial + Deserial {
+    /// Check the contents of the fixture, e.g., that the credentials in it
+    /// verify in their intended context and are rejected in others.
+    fn verify(&self) -> anyhow::Result<()>;
+}
+
+/// Parse a serialized value, and check that all of the input is used and that
+/// the value serializes back to exactly the same bytes.
+pub fn check_roundtrip<T: Serial + Deserial>(bytes: &[u8]) -> anyhow::Result<T> {
+    let mut source = Cursor::new(bytes);
+    let value: T = source.get()?;
+    ensure!(
+        source.position() as usize == bytes.len(),
+        "Trailing bytes after the fixture."
+    );
+    let reserialized = to_bytes(&value);
+    if let Some(pos) = reserialized.iter().zip(bytes).position(|(a, b)| a != b) {
+        bail!("Serialization differs from the fixture at byte {}.", pos);
+    }
+    ensure!(
+        reserialized.len() == bytes.len(),
+        "Serialization differs in length from the fixture."
+    );
+    Ok(value)
+}
+
+/// Check a fixture: it must parse, serialize back to exactly the same bytes,
+/// and pass the checks of [Fixture::verify].
+pub fn check_fixture<T: Fixture>(bytes: &[u8]) -> anyhow::Result<T> {
+    let fixture: T = check_roundtrip(bytes)?;
+    fixture.verify()?;
+    Ok(fixture)
+}
+
+impl Deserial for CredentialFixture {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let global_context = get_with_length(source)?;
+        let ip_info = get_with_length(source)?;
+        let num_ars: u64 = source.get()?;
+        let mut ars_infos = BTreeMap::new();
+        for _ in 0..num_ars {
+            let ar: ArInfo<ArCurve> = get_with_length(source)?;
+            if ars_infos.insert(ar.ar_identity, ar).is_some() {
+                bail!("Duplicate anonymity revoker.");
+            }
+        }
+        let new_account = get_with_length(source)?;
+        let address = source.get()?;
+        let existing = get_with_length(source)?;
+        let other_address = source.get()?;
+        let initial = get_with_length(source)?;
+        Ok(CredentialFixture {
+            global_context,
+            ip_info,
+            ars_infos,
+            new_account,
+            address,
+            existing,
+            other_address,
+            initial,
+        })
+    }
+}
+
+/// The format that the Haskell tests read.
+impl Serial for CredentialFixture {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        put_with_length(out, &self.global_context);
+        put_with_length(out, &self.ip_info);
+        // The length of the list is expected big-endian in Haskell.
+        out.put(&(self.ars_infos.len() as u64));
+        for ar in self.ars_infos.values() {
+            put_with_length(out, ar);
+        }
+        put_with_length(out, &self.new_account);
+        out.put(&self.address);
+        put_with_length(out, &self.existing);
+        out.put(&self.other_address);
+        put_with_length(out, &self.initial);
+    }
+}
+
+impl Fixture for CredentialFixture {
+    /// Check the credentials of the fixture in the same way as the Haskell
+    /// test. The credentials must verify in their intended context and be
+    /// rejected in others.
+    fn verify(&self) -> anyhow::Result<()> {
+        let verify = |cdi, new_or_existing| {
+            verify_cdi::<IpPairing, ArCurve, AttributeKind, ArInfo<ArCurve>>(
+                &ChallengeContext::Legacy,
+                &self.global_context,
+                &self.ip_info,
+                &self.ars_infos,
+                cdi,
+                &new_or_existing,
+            )
+        };
+        let ok = |r: Result<(), CdiVerificationError>| r.is_ok();
+        ensure!(
+            ok(verify(&self.new_account, Left(FIXTURE_EXPIRY))),
+            "Verification of the new account credential failed."
+        );
+        ensure!(
+            !ok(verify(
+                &self.new_account,
+                Left(TransactionTime { seconds: 0 })
+            )),
+            "The new account credential verifies with the wrong expiry."
+        );
+        ensure!(
+            !ok(verify(&self.new_account, Right(self.address))),
+            "The new account credential verifies for an existing account."
+        );
+        ensure!(
+            ok(verify(&self.existing, Right(self.address))),
+            "Verification of the credential for the existing account failed."
+        );
+        ensure!(
+            !ok(verify(&self.existing, Right(self.other_address))),
+            "The credential for the existing account verifies for another account."
+        );
+        ensure!(
+            ok(verify_initial_cdi(
+                &self.ip_info,
+                &self.initial,
+                FIXTURE_EXPIRY
+            )),
+            "Verification of the initial credential failed."
+        );
+        ensure!(
+            !ok(verify_initial_cdi(
+                &self.ip_info,
+                &self.initial,
+                TransactionTime { seconds: 0 }
+            )),
+            "The initial credential verifies with the wrong expiry."
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::read_golden_bytes;
+
+    #[test]
+    fn test_credential_fixture() {
+        let bytes = read_golden_bytes("testdata/testdata.bin");
+        let fixture: CredentialFixture =
+            check_fixture(&bytes).expect("The fixture should be valid.");
+
+        // Tampering with the credentials is detected.
+        let mut tampered = fixture;
+        tampered.other_address = tampered.address;
+        assert!(tampered.verify().is_err());
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(check_fixture::<CredentialFixture>(&trailing).is_err());
+    }
+}
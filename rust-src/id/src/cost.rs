@@ -0,0 +1,305 @@
+//! Estimates of the size and cost of credential deployments and account
+//! transactions.
+//!
+//! Generating the proofs of a credential is expensive, so wallets can use the
+//! functions in this module to check, before generating them, that a
+//! credential would not exceed the limits of the chain. The estimates are
+//! exact for the current serialization of credentials.
+//!
+//! The energy of an account transaction is the cost of checking the header and
+//! the signatures, which depends on the size of the transaction and the number
+//! of signatures, plus the cost of executing the payload. The
+//! [transaction_energy] function computes it the same way as the chain, so
+//! that wallets do not have to.
+use crate::{secret_sharing::Threshold, types::*};
+use crypto_common::to_bytes;
+use curve_arithmetic::{Curve, Pairing};
+
+/// Base cost of verifying an initial credential deployment.
+pub const INITIAL_CREDENTIAL_BASE_ENERGY: u64 = 1000;
+
+/// Base cost of verifying a normal credential deployment.
+pub const NORMAL_CREDENTIAL_BASE_ENERGY: u64 = 54000;
+
+/// Cost of each key of a credential, in addition to the base cost.
+pub const CREDENTIAL_KEY_ENERGY: u64 = 100;
+
+/// The maximum size of a transaction payload accepted by the node.
+pub const MAX_PAYLOAD_SIZE: usize = 100 * 1024;
+
+/// Size of the header of an account transaction, i.e., sender, nonce,
+/// energy, payload size and expiry.
+pub const TRANSACTION_HEADER_SIZE: u64 = 32 + 8 + 8 + 4 + 8;
+
+/// Cost of checking each signature on an account transaction.
+pub const SIGNATURE_ENERGY: u64 = 100;
+
+/// Cost of checking each byte of an account transaction, including the
+/// header.
+pub const TRANSACTION_BYTE_ENERGY: u64 = 1;
+
+/// Cost of executing a transfer of a public amount, with or without a memo.
+pub const SIMPLE_TRANSFER_ENERGY: u64 = 300;
+
+/// Cost of executing each release of a transfer with schedule, with or
+/// without a memo.
+pub const SCHEDULED_TRANSFER_RELEASE_ENERGY: u64 = 300 + 64;
+
+/// Cost of each credential of the account when updating the keys of one of
+/// them.
+pub const CREDENTIAL_UPDATE_ENERGY: u64 = 500;
+
+/// Cost of executing an encrypted transfer, with or without a memo.
+pub const ENCRYPTED_TRANSFER_ENERGY: u64 = 27000;
+
+/// Cost of executing a transfer from the public to the shielded balance.
+pub const TRANSFER_TO_ENCRYPTED_ENERGY: u64 = 600;
+
+/// Cost of executing a transfer from the shielded to the public balance.
+pub const TRANSFER_TO_PUBLIC_ENERGY: u64 = 14850;
+
+/// Cost of executing a configure baker transaction that sets the keys of the
+/// baker, which includes checking the proofs of knowledge of the keys.
+pub const CONFIGURE_BAKER_WITH_KEYS_ENERGY: u64 = 4050;
+
+/// Cost of executing a configure baker transaction that does not set the
+/// keys of the baker.
+pub const CONFIGURE_BAKER_WITHOUT_KEYS_ENERGY: u64 = 300;
+
+/// Cost of executing a configure delegation transaction.
+pub const CONFIGURE_DELEGATION_ENERGY: u64 = 300;
+
+/// Number of bits in the range proof that the credential counter is at most the
+/// maximum number of accounts.
+const CRED_COUNTER_BITS: usize = 8;
+
+/// Size of an ed25519 public key or signature, without the scheme tag.
+const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+
+/// Predicted size and verification cost of a credential deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CredentialDeploymentEstimate {
+    /// The length of the [submission
+    /// bytes](AccountCredentialMessage::submission_bytes) of the deployment.
+    pub size:   usize,
+    /// The energy charged by the chain for verifying the credential.
+    pub energy: u64,
+}
+
+impl CredentialDeploymentEstimate {
+    /// Whether the deployment is larger than the node accepts.
+    pub fn exceeds_size_limit(&self) -> bool { self.size > MAX_PAYLOAD_SIZE }
+}
+
+/// The parts of a normal credential that determine its size. These are all
+/// known before the proofs of the credential are generated.
+pub struct NormalCredentialShape<'a, C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// Number of keys of the credential.
+    pub num_keys:       u8,
+    /// Number of signatures in the proof of ownership of the account. When the
+    /// credential creates a new account this is the number of keys of the
+    /// credential. Otherwise it is the number of keys of the existing account
+    /// that sign the deployment.
+    pub num_signatures: u8,
+    /// Number of attributes of the identity object the credential is derived
+    /// from. This includes the revealed attributes.
+    pub num_attributes: usize,
+    /// Policy of the credential, with the revealed attributes.
+    pub policy:         &'a Policy<C, AttributeType>,
+    /// Number of anonymity revokers of the credential.
+    pub num_ars:        usize,
+    /// Anonymity revocation threshold.
+    pub threshold:      Threshold,
+}
+
+/// Energy charged for verifying a credential deployment with the given number
+/// of keys. This matches the cost that the chain charges.
+pub fn credential_deployment_energy(initial: bool, num_keys: u8) -> u64 {
+    let base = if initial {
+        INITIAL_CREDENTIAL_BASE_ENERGY
+    } else {
+        NORMAL_CREDENTIAL_BASE_ENERGY
+    };
+    base + CREDENTIAL_KEY_ENERGY * u64::from(num_keys)
+}
+
+/// Energy charged for checking the header and the signatures of an account
+/// transaction with the given number of signatures and payload size. This
+/// matches the base cost that the chain charges.
+pub fn transaction_base_energy(num_signatures: u32, payload_size: u64) -> u64 {
+    SIGNATURE_ENERGY * u64::from(num_signatures)
+        + TRANSACTION_BYTE_ENERGY * (TRANSACTION_HEADER_SIZE + payload_size)
+}
+
+/// The energy to put in the header of an account transaction, i.e., the base
+/// cost of the transaction plus the given cost of executing its payload.
+pub fn transaction_energy(num_signatures: u32, payload_size: u64, execution_energy: u64) -> u64 {
+    transaction_base_energy(num_signatures, payload_size) + execution_energy
+}
+
+/// Cost of executing a transfer with schedule with the given number of
+/// releases.
+pub fn scheduled_transfer_energy(num_releases: usize) -> u64 {
+    SCHEDULED_TRANSFER_RELEASE_ENERGY * num_releases as u64
+}
+
+/// Cost of executing an update of the keys of a credential, given the number
+/// of credentials of the account and the number of new keys.
+pub fn update_credential_keys_energy(num_credentials: usize, num_keys: usize) -> u64 {
+    CREDENTIAL_UPDATE_ENERGY * num_credentials as u64 + CREDENTIAL_KEY_ENERGY * num_keys as u64
+}
+
+/// Estimate the size and cost of deploying a normal credential with the given
+/// shape.
+pub fn estimate_normal_credential_deployment<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    shape: &NormalCredentialShape<C, AttributeType>,
+) -> CredentialDeploymentEstimate {
+    let group = C::GROUP_ELEMENT_LENGTH;
+    let scalar = C::SCALAR_LENGTH;
+    let num_revealed = shape.policy.policy_vec.len();
+    let num_ars = shape.num_ars;
+    let values = public_keys_size(shape.num_keys)
+        + group // cred_id
+        + 4 // ip_identity
+        + 1 // threshold
+        + 2 + num_ars * (4 + 2 * group) // ar_data
+        + policy_size(shape.policy);
+    let commitments = 3 * group
+        + 2
+        + shape.num_attributes.saturating_sub(num_revealed) * (1 + group)
+        + 8
+        + usize::from(shape.threshold) * group;
+    // The signature of the identity provider is on the identity credential
+    // secret, the PRF key, the public values, the anonymity revokers, the
+    // attribute tags, the maximum number of accounts and the attributes.
+    let num_signed_values = shape.num_attributes + num_ars + 5;
+    let rounds = (2 * CRED_COUNTER_BITS).trailing_zeros() as usize;
+    let range_proof = 4 * group + 3 * scalar + 4 + rounds * 2 * group + 2 * scalar;
+    let proofs = 2 * P::G1::GROUP_ELEMENT_LENGTH // blinded signature
+        + commitments
+        + 32 // challenge
+        + 4 + num_ars * (4 + 3 * scalar) // proofs of the encrypted shares
+        + scalar + 4 + num_signed_values * 2 * scalar // proof of the signature
+        + 5 * scalar // proof of the registration id
+        + ownership_proof_size(shape.num_signatures)
+        + range_proof;
+    CredentialDeploymentEstimate {
+        size:   message_overhead() + values + 4 + proofs,
+        energy: credential_deployment_energy(false, shape.num_keys),
+    }
+}
+
+/// Estimate the size and cost of deploying an initial credential with the given
+/// number of keys and policy.
+pub fn estimate_initial_credential_deployment<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    num_keys: u8,
+    policy: &Policy<C, AttributeType>,
+) -> CredentialDeploymentEstimate {
+    let values = public_keys_size(num_keys)
+        + C::GROUP_ELEMENT_LENGTH // reg_id
+        + 4 // ip_identity
+        + policy_size(policy);
+    CredentialDeploymentEstimate {
+        size:   message_overhead() + values + ED25519_SIGNATURE_LENGTH,
+        energy: credential_deployment_energy(true, num_keys),
+    }
+}
+
+/// Version, block item tag, message expiry, and credential tag.
+fn message_overhead() -> usize { 1 + 1 + 8 + 1 }
+
+fn public_keys_size(num_keys: u8) -> usize {
+    1 + usize::from(num_keys) * (1 + 1 + ED25519_PUBLIC_KEY_LENGTH) + 1
+}
+
+fn ownership_proof_size(num_signatures: u8) -> usize {
+    1 + usize::from(num_signatures) * (1 + ED25519_SIGNATURE_LENGTH)
+}
+
+fn policy_size<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    policy: &Policy<C, AttributeType>,
+) -> usize {
+    // valid_to, created_at, and the number of revealed attributes
+    let header = 3 + 3 + 2;
+    header
+        + policy
+            .policy_vec
+            .values()
+            .map(|v| 1 + to_bytes(v).len())
+            .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::*, test::*};
+    use rand::*;
+    use random_oracle::ChallengeContext;
+    use std::collections::btree_map::BTreeMap;
+
+    #[test]
+    fn test_transaction_energy() {
+        // A simple transfer has a payload of 41 bytes, and with a single
+        // signature costs 501 energy, as charged by the chain.
+        assert_eq!(transaction_energy(1, 41, SIMPLE_TRANSFER_ENERGY), 501);
+        assert_eq!(transaction_base_energy(3, 0), 360);
+    }
+
+    #[test]
+    fn test_estimate_matches_credentials() {
+        let mut csprng = thread_rng();
+
+        let initial_acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 2),
+            threshold: SignatureThreshold(1),
+        };
+        let identity = test_create_identity(&mut csprng, 5, 10, &initial_acc_data);
+        let icdi = identity.icdi.clone();
+        let estimate = estimate_initial_credential_deployment(2, &icdi.values.policy);
+        let message = AccountCredentialMessage::<IpPairing, ArCurve, _> {
+            message_expiry: EXPIRY,
+            credential:     AccountCredential::Initial { icdi },
+        };
+        assert_eq!(estimate.size, message.submission_bytes().len());
+        assert_eq!(estimate.energy, 1200);
+
+        let policy = Policy {
+            valid_to:   YearMonth::new(2022, 5).unwrap(),
+            created_at: YearMonth::new(2020, 5).unwrap(),
+            policy_vec: {
+                let mut tree = BTreeMap::new();
+                tree.insert(AttributeTag::from(8u8), AttributeKind::from(31));
+                tree
+            },
+            _phantom:   Default::default(),
+        };
+        let cred_data = CredentialData {
+            keys:      test_create_keys(&mut csprng, 3),
+            threshold: SignatureThreshold(2),
+        };
+        let cdi =
+            identity.create_credential(&ChallengeContext::Legacy, 0, policy.clone(), &cred_data);
+        let estimate = estimate_normal_credential_deployment::<IpPairing, ArCurve, _>(
+            &NormalCredentialShape {
+                num_keys:       3,
+                num_signatures: 3,
+                num_attributes: identity.id_object.alist.alist.len(),
+                policy:         &policy,
+                num_ars:        cdi.values.ar_data.len(),
+                threshold:      cdi.values.threshold,
+            },
+        );
+        let message = AccountCredentialMessage::<IpPairing, ArCurve, _> {
+            message_expiry: EXPIRY,
+            credential:     AccountCredential::Normal { cdi },
+        };
+        assert_eq!(estimate.size, message.submission_bytes().len());
+        assert_eq!(estimate.energy, 54300);
+        assert!(!estimate.exceeds_size_limit());
+    }
+}
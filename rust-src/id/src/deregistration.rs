@@ -0,0 +1,166 @@
+//! Signed statements by which the owner of an account requests to be
+//! deregistered from a service.
+//!
+//! A service that offboards a user can ask for a statement "I control account
+//! X and request deregistration from service S at time T", signed with the
+//! keys of the account in the same way as a transaction. The service checks the
+//! signatures against the keys of the account on the chain, and can later
+//! prove that the owner of the account requested the deregistration.
+use crate::{types::*, utils::*};
+use crypto_common::{
+    types::{CredentialIndex, Timestamp, TransactionSignature},
+    *,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Prefix of the hash that is signed. It makes sure that a signature on a
+/// statement cannot be a signature on a transaction or any other message.
+const DEREGISTRATION_DOMAIN: &[u8] = b"concordium-account-deregistration";
+
+/// A request of the owner of an account to be deregistered from a service.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeregistrationStatement {
+    /// The account that is deregistered.
+    pub account:   AccountAddress,
+    /// Name of the service the account is deregistered from.
+    pub service:   String,
+    /// Time of the request.
+    pub timestamp: Timestamp,
+}
+
+impl Serial for DeregistrationStatement {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.account);
+        out.put(&(self.service.len() as u32));
+        serial_string(&self.service, out);
+        out.put(&self.timestamp);
+    }
+}
+
+impl DeregistrationStatement {
+    /// The hash that is signed by the keys of the account. It is the SHA256
+    /// hash of [DEREGISTRATION_DOMAIN] followed by the serialization of the
+    /// statement.
+    pub fn hash_to_sign(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(DEREGISTRATION_DOMAIN);
+        hasher.update(&to_bytes(self));
+        hasher.finalize().to_vec()
+    }
+}
+
+/// A deregistration statement together with signatures by the keys of the
+/// account.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedDeregistrationStatement {
+    pub statement: DeregistrationStatement,
+    pub signature: TransactionSignature,
+}
+
+/// Sign the statement with all the given keys of the account. The keys must be
+/// valid, see [AccountKeys::validate], and must include enough keys to satisfy
+/// the thresholds of the account.
+pub fn sign_deregistration_statement(
+    keys: &AccountKeys,
+    statement: DeregistrationStatement,
+) -> Result<SignedDeregistrationStatement, AccountKeysError> {
+    keys.validate()?;
+    let hash = statement.hash_to_sign();
+    Ok(SignedDeregistrationStatement {
+        statement,
+        signature: keys.sign_hash(&hash),
+    })
+}
+
+/// Verify a signed statement against the keys and the threshold of the
+/// account, as found in the account info returned by the node. The signatures
+/// must satisfy the same conditions as the signatures of a transaction, see
+/// [verify_transaction_signatures_batch].
+pub fn verify_deregistration_statement(
+    keys: &BTreeMap<CredentialIndex, CredentialPublicKeys>,
+    threshold: SignatureThreshold,
+    signed: &SignedDeregistrationStatement,
+) -> bool {
+    let hash = signed.statement.hash_to_sign();
+    verify_transaction_signatures_batch(&[TransactionToVerify {
+        keys,
+        threshold,
+        hash: &hash,
+        signature: &signed.signature,
+    }])[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::{KeyIndex, KeyPair};
+    use curve_arithmetic::Curve;
+    use pairing::bls12_381::G1;
+    use rand::*;
+
+    #[test]
+    fn test_deregistration_statement() {
+        let mut csprng = thread_rng();
+        let mut keys = AccountKeys::from(CredentialData::from(KeyPair::generate(&mut csprng)));
+        keys.add_key(
+            CredentialIndex { index: 0 },
+            KeyIndex(1),
+            KeyPair::generate(&mut csprng),
+        )
+        .expect("Key should be added.");
+        let public_keys: BTreeMap<_, _> = keys
+            .keys
+            .iter()
+            .map(|(&idx, cred)| (idx, cred.get_cred_key_info()))
+            .collect();
+        let statement = DeregistrationStatement {
+            account:   AccountAddress::new(&G1::one_point()),
+            service:   String::from("example.com"),
+            timestamp: Timestamp::from(1_600_000_000_000),
+        };
+
+        let signed =
+            sign_deregistration_statement(&keys, statement).expect("Keys should be valid.");
+        assert!(verify_deregistration_statement(
+            &public_keys,
+            keys.threshold,
+            &signed
+        ));
+        let json = serde_json::to_string(&signed).expect("Statement should serialize.");
+        let parsed: SignedDeregistrationStatement =
+            serde_json::from_str(&json).expect("Statement should parse.");
+        assert_eq!(parsed, signed);
+
+        // The signatures are only valid for the statement that was signed.
+        let mut other = signed.clone();
+        other.statement.timestamp = Timestamp::from(1_600_000_000_001);
+        assert!(!verify_deregistration_statement(
+            &public_keys,
+            keys.threshold,
+            &other
+        ));
+        let mut other = signed.clone();
+        other.statement.service = String::from("example.org");
+        assert!(!verify_deregistration_statement(
+            &public_keys,
+            keys.threshold,
+            &other
+        ));
+
+        // The statement is not valid with keys of another account.
+        let other_keys = AccountKeys::from(CredentialData::from(KeyPair::generate(&mut csprng)));
+        let other_public_keys: BTreeMap<_, _> = other_keys
+            .keys
+            .iter()
+            .map(|(&idx, cred)| (idx, cred.get_cred_key_info()))
+            .collect();
+        assert!(!verify_deregistration_statement(
+            &other_public_keys,
+            other_keys.threshold,
+            &signed
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! Semantic comparison of identity objects and credentials.
+//!
+//! When a user reports a problem with an identity or an account, support needs
+//! to compare what the user holds with what was issued or deployed. Comparing
+//! the JSON or the serialized bytes directly only shows that something differs.
+//! The functions in this module instead report each differing attribute, key
+//! and commitment separately, and report proofs and signatures by whether
+//! they are present and a digest of them, since the proofs themselves are not
+//! meaningful to read.
+use crate::{secret_sharing::Threshold, types::*};
+use crypto_common::*;
+use curve_arithmetic::{Curve, Pairing};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// A field that differs between the two compared objects.
+#[derive(SerdeSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct Difference {
+    /// Path of the field, with components separated by dots, e.g.,
+    /// `attributeList.chosenAttributes.dob`.
+    #[serde(rename = "field")]
+    pub field: String,
+    /// The value in the first object, or `None` if the field is absent there.
+    #[serde(rename = "left")]
+    pub left:  Option<String>,
+    /// The value in the second object, or `None` if the field is absent there.
+    #[serde(rename = "right")]
+    pub right: Option<String>,
+}
+
+/// The result of comparing two objects. The differences are ordered by the
+/// path of the field.
+#[derive(SerdeSerialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct DiffReport {
+    #[serde(rename = "differences")]
+    pub differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    /// Whether the two objects are the same.
+    pub fn is_empty(&self) -> bool { self.differences.is_empty() }
+
+    /// Compare the fields of two flattened objects.
+    fn from_fields(left: BTreeMap<String, String>, right: BTreeMap<String, String>) -> Self {
+        let mut right = right;
+        let mut differences = Vec::new();
+        for (field, l) in left {
+            let r = right.remove(&field);
+            if r.as_ref() != Some(&l) {
+                differences.push(Difference {
+                    field,
+                    left: Some(l),
+                    right: r,
+                });
+            }
+        }
+        for (field, r) in right {
+            differences.push(Difference {
+                field,
+                left: None,
+                right: Some(r),
+            });
+        }
+        differences.sort_by(|a, b| a.field.cmp(&b.field));
+        DiffReport { differences }
+    }
+}
+
+/// Compare two identity objects. The attributes are compared one by one, as
+/// are the account keys and the anonymity revocation data. The proofs of the
+/// pre-identity object and the signature of the identity provider are
+/// compared by digest.
+pub fn diff_identity_objects<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    left: &IdentityObject<P, C, AttributeType>,
+    right: &IdentityObject<P, C, AttributeType>,
+) -> DiffReport {
+    DiffReport::from_fields(identity_object_fields(left), identity_object_fields(right))
+}
+
+/// Compare two credentials. The keys, revealed attributes and anonymity
+/// revocation data are compared one by one. The proofs of normal credentials
+/// and the signature of the identity provider on initial credentials are
+/// compared by digest, so that a credential without proofs differs from one
+/// with proofs in the field `proofs`.
+pub fn diff_credentials<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    left: &AccountCredential<P, C, AttributeType>,
+    right: &AccountCredential<P, C, AttributeType>,
+) -> DiffReport {
+    DiffReport::from_fields(credential_fields(left), credential_fields(right))
+}
+
+fn digest<T: Serial>(x: &T) -> String { hex::encode(Sha256::digest(&to_bytes(x))) }
+
+fn year_month(ym: &YearMonth) -> String { format!("{:04}{:02}", ym.year, ym.month) }
+
+fn threshold(t: &Threshold) -> String { t.0.to_string() }
+
+fn key_fields(out: &mut BTreeMap<String, String>, prefix: &str, keys: &CredentialPublicKeys) {
+    for (idx, key) in keys.keys.iter() {
+        out.insert(
+            format!("{}.keys.{}", prefix, idx.0),
+            base16_encode_string(key),
+        );
+    }
+    out.insert(
+        format!("{}.threshold", prefix),
+        keys.threshold.0.to_string(),
+    );
+}
+
+fn attribute_fields<F: ff::Field, AttributeType: Attribute<F>>(
+    out: &mut BTreeMap<String, String>,
+    prefix: &str,
+    attributes: &BTreeMap<AttributeTag, AttributeType>,
+) {
+    for (tag, value) in attributes.iter() {
+        out.insert(format!("{}.{}", prefix, tag), value.to_string());
+    }
+}
+
+fn identity_object_fields<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    id: &IdentityObject<P, C, AttributeType>,
+) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    let alist = &id.alist;
+    out.insert("attributeList.validTo".into(), year_month(&alist.valid_to));
+    out.insert(
+        "attributeList.createdAt".into(),
+        year_month(&alist.created_at),
+    );
+    out.insert(
+        "attributeList.maxAccounts".into(),
+        alist.max_accounts.to_string(),
+    );
+    attribute_fields(&mut out, "attributeList.chosenAttributes", &alist.alist);
+
+    let pio = &id.pre_identity_object;
+    out.insert(
+        "preIdentityObject.pubInfoForIp.idCredPub".into(),
+        base16_encode_string(&pio.pub_info_for_ip.id_cred_pub),
+    );
+    out.insert(
+        "preIdentityObject.pubInfoForIp.regId".into(),
+        base16_encode_string(&pio.pub_info_for_ip.reg_id),
+    );
+    key_fields(
+        &mut out,
+        "preIdentityObject.pubInfoForIp.publicKeys",
+        &pio.pub_info_for_ip.vk_acc,
+    );
+    for (ar, data) in pio.ip_ar_data.iter() {
+        out.insert(
+            format!("preIdentityObject.ipArData.{}", ar),
+            base16_encode_string(data),
+        );
+    }
+    let ars = &pio.choice_ar_parameters;
+    out.insert(
+        "preIdentityObject.choiceArData.arIdentities".into(),
+        ars.ar_identities
+            .iter()
+            .map(|ar| ar.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    out.insert(
+        "preIdentityObject.choiceArData.threshold".into(),
+        threshold(&ars.threshold),
+    );
+    out.insert(
+        "preIdentityObject.idCredSecCommitment".into(),
+        base16_encode_string(&pio.cmm_sc),
+    );
+    out.insert(
+        "preIdentityObject.prfKeyCommitmentWithIP".into(),
+        base16_encode_string(&pio.cmm_prf),
+    );
+    for (i, cmm) in pio.cmm_prf_sharing_coeff.iter().enumerate() {
+        out.insert(
+            format!("preIdentityObject.prfKeySharingCoeffCommitments.{}", i),
+            base16_encode_string(cmm),
+        );
+    }
+    out.insert(
+        "preIdentityObject.proofsOfKnowledge".into(),
+        digest(&pio.poks),
+    );
+    out.insert("signature".into(), digest(&id.signature));
+    out
+}
+
+fn policy_fields<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    out: &mut BTreeMap<String, String>,
+    policy: &Policy<C, AttributeType>,
+) {
+    out.insert("policy.validTo".into(), year_month(&policy.valid_to));
+    out.insert("policy.createdAt".into(), year_month(&policy.created_at));
+    attribute_fields(out, "policy.revealedAttributes", &policy.policy_vec);
+}
+
+fn credential_fields<
+    P: Pairing,
+    C: Curve<Scalar = P::ScalarField>,
+    AttributeType: Attribute<C::Scalar>,
+>(
+    cred: &AccountCredential<P, C, AttributeType>,
+) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    match cred {
+        AccountCredential::Initial { icdi } => {
+            let values = &icdi.values;
+            out.insert("type".into(), "initial".into());
+            out.insert("credId".into(), base16_encode_string(&values.reg_id));
+            out.insert("ipIdentity".into(), values.ip_identity.to_string());
+            key_fields(&mut out, "credentialPublicKeys", &values.cred_account);
+            policy_fields(&mut out, &values.policy);
+            out.insert("sig".into(), digest(&icdi.sig));
+        }
+        AccountCredential::Normal { cdi } => {
+            let values = &cdi.values;
+            out.insert("type".into(), "normal".into());
+            out.insert("credId".into(), base16_encode_string(&values.cred_id));
+            out.insert("ipIdentity".into(), values.ip_identity.to_string());
+            key_fields(&mut out, "credentialPublicKeys", &values.cred_key_info);
+            policy_fields(&mut out, &values.policy);
+            out.insert("revocationThreshold".into(), threshold(&values.threshold));
+            for (ar, data) in values.ar_data.iter() {
+                out.insert(format!("arData.{}", ar), base16_encode_string(data));
+            }
+            out.insert("proofs".into(), digest(&cdi.proofs));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::*, test::*};
+    use std::str::FromStr;
+
+    type Credential = AccountCredential<IpPairing, ArCurve, AttributeKind>;
+
+    #[test]
+    fn test_diff_credentials() {
+        let cdi: Versioned<CredentialDeploymentInfo<IpPairing, ArCurve, AttributeKind>> =
+            read_golden_json("testdata/cdi.json");
+        let icdi: Versioned<InitialCredentialDeploymentInfo<ArCurve, AttributeKind>> =
+            read_golden_json("testdata/icdi.json");
+        let normal: Credential = AccountCredential::Normal { cdi: cdi.value };
+        let initial: Credential = AccountCredential::Initial { icdi: icdi.value };
+
+        assert!(diff_credentials(&normal, &normal).is_empty());
+
+        let mut changed = normal.clone();
+        if let AccountCredential::Normal { cdi } = &mut changed {
+            cdi.values.cred_key_info.threshold = SignatureThreshold(2);
+            cdi.values.policy.policy_vec.insert(
+                AttributeTag::from_str("countryOfResidence").unwrap(),
+                AttributeKind("XX".into()),
+            );
+        }
+        let report = diff_credentials(&normal, &changed);
+        let fields: Vec<&str> = report
+            .differences
+            .iter()
+            .map(|d| d.field.as_str())
+            .collect();
+        assert_eq!(fields, vec![
+            "credentialPublicKeys.threshold",
+            "policy.revealedAttributes.countryOfResidence"
+        ]);
+        assert_eq!(report.differences[1].right.as_deref(), Some("XX"));
+
+        // Proofs are only present on normal credentials, and the signature only
+        // on initial ones.
+        let report = diff_credentials(&normal, &initial);
+        let proofs = report
+            .differences
+            .iter()
+            .find(|d| d.field == "proofs")
+            .expect("Proofs should differ.");
+        assert!(proofs.left.is_some() && proofs.right.is_none());
+        let sig = report
+            .differences
+            .iter()
+            .find(|d| d.field == "sig")
+            .expect("Signatures should differ.");
+        assert!(sig.left.is_none() && sig.right.is_some());
+    }
+}
@@ -9,6 +9,7 @@ use ffi_helpers::*;
 use pairing::bls12_381::{Bls12, G1};
 use pedersen_scheme::CommitmentKey as PedersenKey;
 use rand::thread_rng;
+use random_oracle::ChallengeContext;
 use std::{collections::BTreeMap, convert::TryInto, io::Cursor};
 
 #[no_mangle]
@@ -88,6 +89,7 @@ extern "C" fn verify_cdi_ffi(
                 }
             }
             match chain::verify_cdi::<Bls12, G1, AttributeKind, ArPublicKey<G1>>(
+                &ChallengeContext::Legacy,
                 from_ptr!(gc_ptr),
                 from_ptr!(ip_info_ptr),
                 &ars_infos,
@@ -103,6 +105,8 @@ extern "C" fn verify_cdi_ffi(
                 Err(CdiVerificationError::Ar) => -6,
                 Err(CdiVerificationError::AccountOwnership) => -7,
                 Err(CdiVerificationError::Proof) => -8,
+                Err(CdiVerificationError::Expired) => -9,
+                Err(CdiVerificationError::IpIdentity) => -15,
             }
         }
     }
@@ -227,13 +231,7 @@ mod test {
             prf_key,
         };
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
 
@@ -257,8 +255,14 @@ mod test {
 
         let context = IpContext::new(&ip_info, &ars_infos, &global_ctx);
         let threshold = Threshold(num_ars - 1);
-        let (pio, randomness) = generate_pio(&context, threshold, &aci, &acc_data)
-            .expect("Creating the credential should succeed.");
+        let (pio, randomness) = generate_pio(
+            &ChallengeContext::Legacy,
+            &context,
+            threshold,
+            &aci,
+            &acc_data,
+        )
+        .expect("Creating the credential should succeed.");
 
         let ver_ok = verify_credentials(
             &pio,
@@ -326,6 +330,7 @@ mod test {
         };
 
         let (cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_use_data,
@@ -337,6 +342,7 @@ mod test {
         .expect("Should generate the credential successfully.");
 
         let (wrong_cdi, _) = create_credential(
+            &ChallengeContext::Legacy,
             context,
             &id_object,
             &id_use_data,
@@ -1,11 +1,18 @@
-use crate::types::*;
+use crate::{
+    account_ownership::*,
+    sigma_protocols::{common::prove, dlog},
+    statement::*,
+    types::*,
+    web3id::*,
+};
 use bulletproofs::range_proof::{prove_given_scalars, Generators, RangeProof};
 use curve_arithmetic::Curve;
 use ed25519_dalek as ed25519;
 use ff::Field;
-use pedersen_scheme::{CommitmentKey as PedersenKey, Randomness as PedersenRandomness};
-use random_oracle::RandomOracle;
+use pedersen_scheme::{CommitmentKey as PedersenKey, Randomness as PedersenRandomness, Value};
+use random_oracle::{domains, ChallengeContext, RandomOracle};
 use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, rc::Rc};
 
 /// Function for proving ownership of an account. The parameters are
 /// - data - the CredentialData containing the private keys of the prover
@@ -46,8 +53,25 @@ pub fn prove_ownership_of_account(
     AccountOwnershipProof { sigs }
 }
 
+/// Prove ownership of an account to a third party by signing its challenge
+/// with the keys of all the credentials of the account that the wallet holds.
+/// Each credential signs as in [prove_ownership_of_account], with the
+/// [signed bytes](OwnershipChallenge::to_signed_bytes) of the challenge.
+pub fn prove_account_ownership_challenge(
+    keys: &AccountKeys,
+    account: AccountAddress,
+    challenge: &OwnershipChallenge,
+) -> AccountOwnershipProofs {
+    let to_sign = challenge.to_signed_bytes();
+    keys.keys
+        .iter()
+        .map(|(&index, data)| (index, prove_ownership_of_account(data, account, &to_sign)))
+        .collect()
+}
+
 /// Function for proving that an attribute inside a commitment is in a range of
 /// the form [a,b). The parameters are
+/// - context - the context of the proof, which the verifier must use as well
 /// - gens - the bulletproof generators needed for range proofs
 /// - keys - the commitments keys used to commit to the attribute
 /// - attribute - the attribute inside the commitment
@@ -59,6 +83,30 @@ pub fn prove_ownership_of_account(
 /// This is done by proving that attribute-upper+2^n and attribute-lower lie in
 /// [0, 2^n). For further details about this technique, see page 15 in https://arxiv.org/pdf/1907.06381.pdf.
 pub fn prove_attribute_in_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    context: &ChallengeContext,
+    gens: &Generators<C>,
+    keys: &PedersenKey<C>,
+    attribute: &AttributeType,
+    lower: &AttributeType,
+    upper: &AttributeType,
+    r: &PedersenRandomness<C>,
+) -> Option<RangeProof<C>> {
+    let mut transcript = RandomOracle::domain_in_context(context, domains::ATTRIBUTE_RANGE_PROOF);
+    prove_attribute_in_range_with_transcript(
+        &mut transcript,
+        gens,
+        keys,
+        attribute,
+        lower,
+        upper,
+        r,
+    )
+}
+
+/// Same as [prove_attribute_in_range], but the proof is added to the given
+/// transcript.
+fn prove_attribute_in_range_with_transcript<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    transcript: &mut RandomOracle,
     gens: &Generators<C>,
     keys: &PedersenKey<C>,
     attribute: &AttributeType,
@@ -66,7 +114,6 @@ pub fn prove_attribute_in_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
     upper: &AttributeType,
     r: &PedersenRandomness<C>,
 ) -> Option<RangeProof<C>> {
-    let mut transcript = RandomOracle::domain("attribute_range_proof");
     let mut csprng = rand::thread_rng();
     let delta = attribute.to_field_element();
     let a = lower.to_field_element();
@@ -81,7 +128,7 @@ pub fn prove_attribute_in_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
     let rand1 = r.clone();
     let rand2 = r.clone();
     prove_given_scalars(
-        &mut transcript,
+        transcript,
         &mut csprng,
         64,
         2,
@@ -91,3 +138,174 @@ pub fn prove_attribute_in_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
         &[rand1, rand2],
     )
 }
+
+/// Prove a statement about the attributes of a credential. The parameters are
+/// - context - the context of the proof, which the verifier must use as well
+/// - challenge - a challenge produced by the verifier
+/// - global - the global context the credential was created with
+/// - statement - the statement to prove
+/// - attributes - the attributes of the identity the credential is derived from
+/// - randomness - the randomness of the commitments of the credential, as
+///   returned when the credential was created
+///
+/// The randomness contains no entry for the attributes revealed in the policy
+/// of the credential, and the statements about those are proved with
+/// [AtomicProof::InPolicy]. The function fails if the attributes do not
+/// satisfy the statement.
+pub fn prove_statement<C: Curve, AttributeType: Attribute<C::Scalar>, R: rand::Rng>(
+    context: &ChallengeContext,
+    challenge: &[u8],
+    global: &GlobalContext<C>,
+    statement: &Statement<C, AttributeType>,
+    attributes: &BTreeMap<AttributeTag, AttributeType>,
+    randomness: &CommitmentsRandomness<C>,
+    csprng: &mut R,
+) -> Result<StatementProof<C, AttributeType>, StatementError> {
+    statement.check_attributes(attributes)?;
+    let keys = &global.on_chain_commitment_key;
+    let mut transcript = statement_transcript(context, challenge);
+    let mut proofs = Vec::with_capacity(statement.statements().len());
+    for atomic in statement.statements() {
+        let tag = atomic.attribute_tag();
+        transcript.append_message("attribute_tag", &tag);
+        let attribute = attributes
+            .get(&tag)
+            .ok_or(StatementError::MissingAttribute(tag))?;
+        let r = match randomness.attributes_rand.get(&tag) {
+            Some(r) => r,
+            None => {
+                proofs.push(AtomicProof::InPolicy);
+                continue;
+            }
+        };
+        let value = Value::<C>::new(attribute.to_field_element());
+        let commitment = keys.hide(&value, r);
+        transcript.append_message("commitment", &commitment);
+        let proof = match atomic {
+            AtomicStatement::RevealAttribute { .. } => {
+                let prover = dlog::Dlog {
+                    public: keys.h.mul_by_scalar(r),
+                    coeff:  keys.h,
+                };
+                let secret = dlog::DlogSecret {
+                    secret: r.as_value(),
+                };
+                let proof = prove(&mut transcript, &prover, secret, csprng)
+                    .ok_or(StatementError::ProofFailed(tag))?;
+                AtomicProof::RevealAttribute {
+                    attribute: attribute.clone(),
+                    proof,
+                }
+            }
+            AtomicStatement::AttributeInRange { lower, upper, .. } => {
+                let proof = prove_attribute_in_range_with_transcript(
+                    &mut transcript,
+                    global.bulletproof_generators(),
+                    keys,
+                    attribute,
+                    lower,
+                    upper,
+                    r,
+                )
+                .ok_or(StatementError::ProofFailed(tag))?;
+                AtomicProof::AttributeInRange { proof }
+            }
+            AtomicStatement::AttributeInSet { set, .. } => {
+                let known = set
+                    .iter()
+                    .position(|x| x.to_field_element() == *value)
+                    .ok_or(StatementError::NotSatisfied(tag))?;
+                let prover = set_membership_protocol(keys, &commitment, set, known);
+                let secret = dlog::DlogSecret {
+                    secret: r.as_value(),
+                };
+                let proof = prove(&mut transcript, &prover, secret, csprng)
+                    .ok_or(StatementError::ProofFailed(tag))?;
+                AtomicProof::AttributeInSet { proof }
+            }
+            AtomicStatement::AttributeNotInSet { set, .. } => {
+                let mut set_proofs = Vec::with_capacity(set.len());
+                for x in set.iter() {
+                    let mut diff = *value;
+                    diff.sub_assign(&x.to_field_element());
+                    let inverse = diff.inverse().ok_or(StatementError::NotSatisfied(tag))?;
+                    let mut y = *r.as_ref();
+                    y.mul_assign(&inverse);
+                    y.negate();
+                    let prover = non_membership_protocol(keys, &commitment, x);
+                    let secret = vec![Rc::new(inverse), Rc::new(y)];
+                    let proof = prove(&mut transcript, &prover, secret, csprng)
+                        .ok_or(StatementError::ProofFailed(tag))?;
+                    set_proofs.push(proof);
+                }
+                AtomicProof::AttributeNotInSet { proofs: set_proofs }
+            }
+        };
+        proofs.push(proof);
+    }
+    Ok(StatementProof { proofs })
+}
+
+/// Produce the presentation that answers a request of a verifier. The
+/// parameters are
+/// - context - the context of the proofs, which the verifier must use as well
+/// - global - the global context of the chain
+/// - request - the request of the verifier
+/// - credentials - for each statement of the request, in order, the attributes
+///   of the identity the credential is derived from and the randomness of the
+///   commitments of the credential
+/// - accounts - for each account of the request, in order, the keys of the
+///   credentials of the account that the wallet holds
+///
+/// The function fails if the number of credentials or accounts does not match
+/// the request, or if the attributes do not satisfy a statement.
+pub fn prove_presentation<C: Curve, AttributeType: Attribute<C::Scalar>, R: rand::Rng>(
+    context: &ChallengeContext,
+    global: &GlobalContext<C>,
+    request: &Request<C, AttributeType>,
+    credentials: &[(
+        &BTreeMap<AttributeTag, AttributeType>,
+        &CommitmentsRandomness<C>,
+    )],
+    accounts: &[&AccountKeys],
+    csprng: &mut R,
+) -> Result<Presentation<C, AttributeType>, PresentationError> {
+    if credentials.len() != request.credential_statements.len() {
+        return Err(PresentationError::CredentialCountMismatch);
+    }
+    if accounts.len() != request.accounts.len() {
+        return Err(PresentationError::AccountCountMismatch);
+    }
+    let challenge = request.proof_challenge(context);
+    let mut credential_proofs = Vec::with_capacity(credentials.len());
+    for (cs, (attributes, randomness)) in request.credential_statements.iter().zip(credentials) {
+        let proof = prove_statement(
+            context,
+            &challenge,
+            global,
+            &cs.statement,
+            attributes,
+            randomness,
+            csprng,
+        )?;
+        credential_proofs.push(CredentialProof {
+            cred_id: cs.cred_id,
+            proof,
+        });
+    }
+    let ownership_challenge = request.ownership_challenge(context);
+    let account_proofs = request
+        .accounts
+        .iter()
+        .zip(accounts)
+        .map(|(&account, keys)| AccountProof {
+            account,
+            proof: prove_account_ownership_challenge(keys, account, &ownership_challenge),
+        })
+        .collect();
+    Ok(Presentation {
+        challenge: request.challenge.clone(),
+        credential_proofs,
+        account_proofs,
+    })
+}
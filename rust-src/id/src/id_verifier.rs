@@ -1,11 +1,19 @@
-use crate::{types::*, utils};
+use crate::{
+    account_info::AccountCredentials,
+    account_ownership::*,
+    sigma_protocols::{common::verify, dlog},
+    statement::*,
+    types::*,
+    utils,
+    web3id::*,
+};
 use bulletproofs::range_proof::{verify_efficient, Generators, RangeProof, VerificationError};
 use curve_arithmetic::Curve;
 use ff::Field;
 use pedersen_scheme::{
     Commitment, CommitmentKey as PedersenKey, Randomness as PedersenRandomness, Value,
 };
-use random_oracle::RandomOracle;
+use random_oracle::{domains, ChallengeContext, RandomOracle};
 use sha2::{Digest, Sha256};
 
 /// Function for opening an attribute inside a commitment. The arguments are
@@ -28,6 +36,7 @@ pub fn verify_attribute<C: Curve, AttributeType: Attribute<C::Scalar>>(
 
 /// Function for verifying a range proof about an attribute inside a commitment.
 /// The arguments are
+/// - context - the context the proof was produced in
 /// - keys - the commitments keys used to commit to the attribute
 /// - gens - the bulletproof generators needed for range proofs
 /// - lower - the lower bound of the range
@@ -40,7 +49,24 @@ pub fn verify_attribute<C: Curve, AttributeType: Attribute<C::Scalar>>(
 /// This is done by verifying that the attribute inside the commitment satisfies
 /// that attribute-upper+2^n and attribute-lower lie in [0, 2^n).
 /// For further details about this technique, see page 15 in https://arxiv.org/pdf/1907.06381.pdf.
+/// The proof only verifies if it was produced in the same context.
 pub fn verify_attribute_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    context: &ChallengeContext,
+    keys: &PedersenKey<C>,
+    gens: &Generators<C>,
+    lower: &AttributeType,
+    upper: &AttributeType,
+    c: &Commitment<C>,
+    proof: &RangeProof<C>,
+) -> Result<(), VerificationError> {
+    let mut transcript = RandomOracle::domain_in_context(context, domains::ATTRIBUTE_RANGE_PROOF);
+    verify_attribute_range_with_transcript(&mut transcript, keys, gens, lower, upper, c, proof)
+}
+
+/// Same as [verify_attribute_range], but the proof is checked as part of the
+/// given transcript.
+fn verify_attribute_range_with_transcript<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    transcript: &mut RandomOracle,
     keys: &PedersenKey<C>,
     gens: &Generators<C>,
     lower: &AttributeType,
@@ -48,7 +74,6 @@ pub fn verify_attribute_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
     c: &Commitment<C>,
     proof: &RangeProof<C>,
 ) -> Result<(), VerificationError> {
-    let mut transcript = RandomOracle::domain("attribute_range_proof");
     let a = lower.to_field_element();
     let b = upper.to_field_element();
     let zero_randomness = PedersenRandomness::<C>::zero();
@@ -61,7 +86,7 @@ pub fn verify_attribute_range<C: Curve, AttributeType: Attribute<C::Scalar>>(
     let com_delta_minus_a = Commitment(c.0.minus_point(&com_a.0));
 
     verify_efficient(
-        &mut transcript,
+        transcript,
         64,
         &[com_delta_minus_b_plus_2n, com_delta_minus_a],
         proof,
@@ -96,27 +121,229 @@ pub fn verify_account_ownership(
     utils::verify_account_ownership_proof(&public_data.keys, public_data.threshold, &proof, to_sign)
 }
 
+/// Verify a proof of ownership of an account produced by
+/// [prove_account_ownership_challenge](crate::id_prover::prove_account_ownership_challenge).
+/// The parameters are
+/// - credentials - the credentials of the account on the chain, e.g., as parsed
+///   with [parse_account_credentials](crate::account_info::parse_account_credentials)
+///   from the account info of the address
+/// - threshold - the account threshold, i.e., how many credentials must sign
+/// - account - the address of the account
+/// - challenge - the challenge of the verifier
+/// - proofs - the signatures of the credentials
+///
+/// The proof is valid if the signatures of every credential in it are valid,
+/// and at least `threshold` credentials have signed.
+pub fn verify_account_ownership_challenge<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    credentials: &AccountCredentials<C, AttributeType>,
+    threshold: SignatureThreshold,
+    account: AccountAddress,
+    challenge: &OwnershipChallenge,
+    proofs: &AccountOwnershipProofs,
+) -> bool {
+    let to_sign = challenge.to_signed_bytes();
+    let all_valid = proofs
+        .iter()
+        .all(|(index, proof)| match credentials.get(index) {
+            Some(credential) => {
+                verify_account_ownership(credential.public_keys(), account, &to_sign, proof)
+            }
+            None => false,
+        });
+    all_valid && proofs.len() >= usize::from(threshold.0)
+}
+
+/// Function for verifying the proof of a statement about the attributes of a
+/// credential. The arguments are
+/// - context - the context of the proof
+/// - challenge - the challenge that the verifier gave to the prover
+/// - global - the global context of the chain
+/// - statement - the statement that is proved
+/// - credential - the credential the statement is about, as it is on the chain
+/// - proof - the prover's proof
+///
+/// The statements about attributes revealed in the policy of the credential
+/// are checked against the values in the policy, and the others against the
+/// commitments of the credential. The function outputs a bool, indicating
+/// whether the proof is correct or not.
+pub fn verify_statement<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    context: &ChallengeContext,
+    challenge: &[u8],
+    global: &GlobalContext<C>,
+    statement: &Statement<C, AttributeType>,
+    credential: &AccountCredentialWithoutProofs<C, AttributeType>,
+    proof: &StatementProof<C, AttributeType>,
+) -> bool {
+    if proof.proofs.len() != statement.statements().len() {
+        return false;
+    }
+    let keys = &global.on_chain_commitment_key;
+    let mut transcript = statement_transcript(context, challenge);
+    for (atomic, atomic_proof) in statement.statements().iter().zip(proof.proofs.iter()) {
+        let tag = atomic.attribute_tag();
+        transcript.append_message("attribute_tag", &tag);
+        if let AtomicProof::InPolicy = atomic_proof {
+            match credential.policy().policy_vec.get(&tag) {
+                Some(value) if is_satisfied::<C, _>(atomic, value) => continue,
+                _ => return false,
+            }
+        }
+        let commitment = match credential.attribute_commitment(tag) {
+            Some(commitment) => commitment,
+            None => return false,
+        };
+        transcript.append_message("commitment", commitment);
+        let valid = match (atomic, atomic_proof) {
+            (
+                AtomicStatement::RevealAttribute { .. },
+                AtomicProof::RevealAttribute { attribute, proof },
+            ) => {
+                let verifier = dlog::Dlog {
+                    public: commitment
+                        .minus_point(&keys.g.mul_by_scalar(&attribute.to_field_element())),
+                    coeff:  keys.h,
+                };
+                verify(&mut transcript, &verifier, proof)
+            }
+            (
+                AtomicStatement::AttributeInRange { lower, upper, .. },
+                AtomicProof::AttributeInRange { proof },
+            ) => verify_attribute_range_with_transcript(
+                &mut transcript,
+                keys,
+                global.bulletproof_generators(),
+                lower,
+                upper,
+                commitment,
+                proof,
+            )
+            .is_ok(),
+            (
+                AtomicStatement::AttributeInSet { set, .. },
+                AtomicProof::AttributeInSet { proof },
+            ) => {
+                let verifier = set_membership_protocol(keys, commitment, set, 0);
+                verify(&mut transcript, &verifier, proof)
+            }
+            (
+                AtomicStatement::AttributeNotInSet { set, .. },
+                AtomicProof::AttributeNotInSet { proofs },
+            ) => {
+                proofs.len() == set.len()
+                    && set.iter().zip(proofs.iter()).all(|(x, proof)| {
+                        let verifier = non_membership_protocol(keys, commitment, x);
+                        verify(&mut transcript, &verifier, proof)
+                    })
+            }
+            _ => false,
+        };
+        if !valid {
+            return false;
+        }
+    }
+    true
+}
+
+/// Verify a presentation produced by
+/// [prove_presentation](crate::id_prover::prove_presentation). The parameters
+/// are
+/// - context - the context of the proofs
+/// - global - the global context of the chain
+/// - request - the request that the presentation answers
+/// - credentials - the credentials of the statements of the request, in order,
+///   as they are on the chain
+/// - accounts - for each account of the request, in order, the credentials of
+///   the account as they are on the chain, and the account threshold
+/// - presentation - the presentation of the wallet
+///
+/// If the presentation is not valid, the function returns the first reason it
+/// finds.
+pub fn verify_presentation<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    context: &ChallengeContext,
+    global: &GlobalContext<C>,
+    request: &Request<C, AttributeType>,
+    credentials: &[AccountCredentialWithoutProofs<C, AttributeType>],
+    accounts: &[(&AccountCredentials<C, AttributeType>, SignatureThreshold)],
+    presentation: &Presentation<C, AttributeType>,
+) -> Result<(), PresentationError> {
+    if presentation.challenge != request.challenge {
+        return Err(PresentationError::ChallengeMismatch);
+    }
+    let num_credentials = request.credential_statements.len();
+    if credentials.len() != num_credentials
+        || presentation.credential_proofs.len() != num_credentials
+    {
+        return Err(PresentationError::CredentialCountMismatch);
+    }
+    let num_accounts = request.accounts.len();
+    if accounts.len() != num_accounts || presentation.account_proofs.len() != num_accounts {
+        return Err(PresentationError::AccountCountMismatch);
+    }
+    let challenge = request.proof_challenge(context);
+    for (i, (cs, credential, cp)) in izip!(
+        request.credential_statements.iter(),
+        credentials.iter(),
+        presentation.credential_proofs.iter()
+    )
+    .enumerate()
+    {
+        if cp.cred_id != cs.cred_id || *credential.cred_id() != cs.cred_id {
+            return Err(PresentationError::CredentialMismatch(i));
+        }
+        if !verify_statement(
+            context,
+            &challenge,
+            global,
+            &cs.statement,
+            credential,
+            &cp.proof,
+        ) {
+            return Err(PresentationError::InvalidCredentialProof(i));
+        }
+    }
+    let ownership_challenge = request.ownership_challenge(context);
+    for (&account, (credentials, threshold), ap) in izip!(
+        request.accounts.iter(),
+        accounts.iter(),
+        presentation.account_proofs.iter()
+    ) {
+        if ap.account != account {
+            return Err(PresentationError::AccountMismatch(ap.account));
+        }
+        if !verify_account_ownership_challenge(
+            credentials,
+            *threshold,
+            account,
+            &ownership_challenge,
+            &ap.proof,
+        ) {
+            return Err(PresentationError::InvalidAccountProof(account));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{constants::AttributeKind, id_prover::*};
-    use crypto_common::types::{KeyIndex, KeyPair};
+    use crate::{
+        constants::AttributeKind, id_prover::*, secret_sharing::Threshold, test::test_create_keys,
+    };
+    use crypto_common::{
+        serialize_deserialize, to_bytes,
+        types::{CredentialIndex, KeyIndex, KeyPair},
+        Version, Versioned,
+    };
     use pairing::bls12_381::G1;
     use rand::*;
-    use std::collections::btree_map::BTreeMap;
+    use std::collections::{btree_map::BTreeMap, HashMap};
 
     #[test]
     fn test_verify_account_ownership() {
         let mut csprng = thread_rng();
 
         let cred_data = CredentialData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
 
@@ -161,11 +388,26 @@ mod tests {
         let upper = AttributeKind("20000103".to_string());
         let value = Value::<G1>::new(attribute.to_field_element());
         let (commitment, randomness) = keys.commit(&value, &mut csprng);
-        let maybe_proof =
-            prove_attribute_in_range(&gens, &keys, &attribute, &lower, &upper, &randomness);
+        let maybe_proof = prove_attribute_in_range(
+            &ChallengeContext::Legacy,
+            &gens,
+            &keys,
+            &attribute,
+            &lower,
+            &upper,
+            &randomness,
+        );
         if let Some(proof) = maybe_proof {
             assert_eq!(
-                verify_attribute_range(&keys, &gens, &lower, &upper, &commitment, &proof),
+                verify_attribute_range(
+                    &ChallengeContext::Legacy,
+                    &keys,
+                    &gens,
+                    &lower,
+                    &upper,
+                    &commitment,
+                    &proof
+                ),
                 Ok(()),
                 "Incorrect range proof."
             );
@@ -173,4 +415,479 @@ mod tests {
             assert!(false, "Failed to produce proof.");
         };
     }
+
+    #[test]
+    fn test_verify_attribute_in_range_in_context() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let keys = global.on_chain_commitment_key;
+        let gens = global.bulletproof_generators();
+        let lower = AttributeKind("20000102".to_string());
+        let attribute = AttributeKind("20000102".to_string());
+        let upper = AttributeKind("20000103".to_string());
+        let value = Value::<G1>::new(attribute.to_field_element());
+        let (commitment, randomness) = keys.commit(&value, &mut csprng);
+        let context = random_oracle::ProofContext {
+            network_id:       b"testnet".to_vec(),
+            protocol_version: 1,
+            statement_type:   "AttributeInRange".to_owned(),
+        };
+        let in_context = ChallengeContext::Versioned(context.clone());
+        let proof = prove_attribute_in_range(
+            &in_context,
+            &gens,
+            &keys,
+            &attribute,
+            &lower,
+            &upper,
+            &randomness,
+        )
+        .expect("Failed to produce proof.");
+        let verify = |ctx: &ChallengeContext| {
+            verify_attribute_range(ctx, &keys, &gens, &lower, &upper, &commitment, &proof)
+        };
+        assert_eq!(verify(&in_context), Ok(()));
+        assert!(
+            verify(&ChallengeContext::Legacy).is_err(),
+            "Proof should not verify without the context."
+        );
+        let other_network = ChallengeContext::Versioned(random_oracle::ProofContext {
+            network_id: b"mainnet".to_vec(),
+            ..context
+        });
+        assert!(
+            verify(&other_network).is_err(),
+            "Proof should not verify on another network."
+        );
+    }
+
+    /// A normal credential with attributes `firstName` and `dob` committed to,
+    /// and `nationality` revealed in the policy, together with the attributes,
+    /// the randomness of the commitments and the keys of the credential.
+    fn statement_test_credential<R: Rng + CryptoRng>(
+        global: &GlobalContext<G1>,
+        csprng: &mut R,
+    ) -> (
+        BTreeMap<AttributeTag, AttributeKind>,
+        AccountCredentialWithoutProofs<G1, AttributeKind>,
+        CommitmentsRandomness<G1>,
+        CredentialData,
+    ) {
+        let keys = global.on_chain_commitment_key;
+        let kind = |s: &str| AttributeKind(s.to_string());
+        let mut attributes = BTreeMap::new();
+        attributes.insert(AttributeTag(0), kind("John"));
+        attributes.insert(AttributeTag(3), kind("19800229"));
+        attributes.insert(AttributeTag(5), kind("DK"));
+
+        // The first two attributes are committed to, and the last is revealed in
+        // the policy.
+        let mut cmm_attributes = BTreeMap::new();
+        let mut attributes_rand = HashMap::new();
+        for tag in [AttributeTag(0), AttributeTag(3)].iter() {
+            let value = Value::<G1>::new(attributes[tag].to_field_element());
+            let (cmm, rand) = keys.commit(&value, csprng);
+            cmm_attributes.insert(*tag, cmm);
+            attributes_rand.insert(*tag, rand);
+        }
+        let mut policy_vec = BTreeMap::new();
+        policy_vec.insert(AttributeTag(5), kind("DK"));
+        let (cmm, rand) = keys.commit(&Value::<G1>::new(G1::scalar_from_u64(0)), csprng);
+        let cred_data = CredentialData {
+            keys:      {
+                let mut keys = BTreeMap::new();
+                keys.insert(KeyIndex(0), KeyPair::generate(csprng));
+                keys
+            },
+            threshold: SignatureThreshold(1),
+        };
+        let credential = AccountCredentialWithoutProofs::Normal {
+            cdv:         CredentialDeploymentValues {
+                cred_key_info: cred_data.get_cred_key_info(),
+                cred_id:       G1::hash_to_group(b"some_bytes"),
+                ip_identity:   IpIdentity(0),
+                threshold:     Threshold(1),
+                ar_data:       BTreeMap::new(),
+                policy:        Policy {
+                    valid_to: YearMonth::new(2022, 5).unwrap(),
+                    created_at: YearMonth::new(2020, 5).unwrap(),
+                    policy_vec,
+                    _phantom: Default::default(),
+                },
+            },
+            commitments: CredentialDeploymentCommitments {
+                cmm_prf: cmm,
+                cmm_cred_counter: cmm,
+                cmm_max_accounts: cmm,
+                cmm_attributes,
+                cmm_id_cred_sec_sharing_coeff: Vec::new(),
+            },
+        };
+        let randomness = CommitmentsRandomness {
+            id_cred_sec_rand: rand.clone(),
+            prf_rand: rand.clone(),
+            cred_counter_rand: rand.clone(),
+            max_accounts_rand: rand,
+            attributes_rand,
+        };
+        (attributes, credential, randomness, cred_data)
+    }
+
+    #[test]
+    fn test_verify_statement() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let kind = |s: &str| AttributeKind(s.to_string());
+        let (attributes, credential, randomness, _) =
+            statement_test_credential(&global, &mut csprng);
+
+        let statement = StatementBuilder::<G1, AttributeKind>::new()
+            .reveal(AttributeTag(0))
+            .in_range(AttributeTag(3), kind("19000101"), kind("20030101"))
+            .in_set(AttributeTag(5), vec![kind("DK"), kind("DE")])
+            .build()
+            .expect("Statement should be well-formed.");
+        let context = ChallengeContext::Legacy;
+        let challenge = b"13549686546546546854651357687354";
+        let proof = prove_statement(
+            &context,
+            challenge,
+            &global,
+            &statement,
+            &attributes,
+            &randomness,
+            &mut csprng,
+        )
+        .expect("Statement should be proved.");
+        assert!(matches!(proof.proofs[2], AtomicProof::InPolicy));
+        let proof = serialize_deserialize(&proof).expect("Proof should deserialize.");
+        assert!(verify_statement(
+            &context,
+            challenge,
+            &global,
+            &statement,
+            &credential,
+            &proof
+        ));
+        assert!(
+            !verify_statement(
+                &context,
+                b"another challenge",
+                &global,
+                &statement,
+                &credential,
+                &proof
+            ),
+            "Proof should not verify for another challenge."
+        );
+
+        // Revealing another value than the committed one is rejected.
+        let mut wrong = proof.clone();
+        if let AtomicProof::RevealAttribute { attribute, .. } = &mut wrong.proofs[0] {
+            *attribute = kind("Jane");
+        }
+        assert!(!verify_statement(
+            &context,
+            challenge,
+            &global,
+            &statement,
+            &credential,
+            &wrong
+        ));
+
+        // Statements the attributes do not satisfy cannot be proved.
+        let too_young = StatementBuilder::<G1, AttributeKind>::new()
+            .in_range(AttributeTag(3), kind("19900101"), kind("20030101"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            prove_statement(
+                &context,
+                challenge,
+                &global,
+                &too_young,
+                &attributes,
+                &randomness,
+                &mut csprng
+            )
+            .unwrap_err(),
+            StatementError::NotSatisfied(AttributeTag(3))
+        );
+
+        // Set statements about committed attributes.
+        let sets = StatementBuilder::<G1, AttributeKind>::new()
+            .in_set(AttributeTag(0), vec![
+                kind("Jane"),
+                kind("John"),
+                kind("Joe"),
+            ])
+            .not_in_set(AttributeTag(3), vec![kind("19800228"), kind("19800301")])
+            .build()
+            .unwrap();
+        let proof = prove_statement(
+            &context,
+            challenge,
+            &global,
+            &sets,
+            &attributes,
+            &randomness,
+            &mut csprng,
+        )
+        .expect("Set statements should be proved.");
+        let proof = serialize_deserialize(&proof).expect("Proof should deserialize.");
+        assert!(verify_statement(
+            &context,
+            challenge,
+            &global,
+            &sets,
+            &credential,
+            &proof
+        ));
+        // The proofs are not valid for other sets.
+        let other_sets = StatementBuilder::<G1, AttributeKind>::new()
+            .in_set(AttributeTag(0), vec![
+                kind("Jane"),
+                kind("Jim"),
+                kind("Joe"),
+            ])
+            .not_in_set(AttributeTag(3), vec![kind("19800228"), kind("19800301")])
+            .build()
+            .unwrap();
+        assert!(!verify_statement(
+            &context,
+            challenge,
+            &global,
+            &other_sets,
+            &credential,
+            &proof
+        ));
+        let other_sets = StatementBuilder::<G1, AttributeKind>::new()
+            .in_set(AttributeTag(0), vec![
+                kind("Jane"),
+                kind("John"),
+                kind("Joe"),
+            ])
+            .not_in_set(AttributeTag(3), vec![kind("19800228"), kind("19800229")])
+            .build()
+            .unwrap();
+        assert!(!verify_statement(
+            &context,
+            challenge,
+            &global,
+            &other_sets,
+            &credential,
+            &proof
+        ));
+        assert_eq!(
+            prove_statement(
+                &context,
+                challenge,
+                &global,
+                &other_sets,
+                &attributes,
+                &randomness,
+                &mut csprng
+            )
+            .unwrap_err(),
+            StatementError::NotSatisfied(AttributeTag(3))
+        );
+    }
+
+    #[test]
+    fn test_verify_account_ownership_challenge() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let mut keys = AccountKeys::new();
+        let mut credentials = BTreeMap::new();
+        for i in 0..2 {
+            let (_, credential, _, cred_data) = statement_test_credential(&global, &mut csprng);
+            keys.keys.insert(CredentialIndex { index: i }, cred_data);
+            credentials.insert(CredentialIndex { index: i }, credential);
+        }
+        keys.threshold = SignatureThreshold(2);
+        let account = AccountAddress::new(credentials[&CredentialIndex { index: 0 }].cred_id());
+        let challenge = OwnershipChallenge {
+            verifier: String::from("exchange.example"),
+            nonce:    b"13549686546546546854651357687354".to_vec(),
+        };
+
+        let proofs = prove_account_ownership_challenge(&keys, account, &challenge);
+        assert!(verify_account_ownership_challenge(
+            &credentials,
+            keys.threshold,
+            account,
+            &challenge,
+            &proofs
+        ));
+
+        // The proof is not valid for another verifier or nonce.
+        let mut other = challenge.clone();
+        other.verifier = String::from("another.example");
+        assert!(!verify_account_ownership_challenge(
+            &credentials,
+            keys.threshold,
+            account,
+            &other,
+            &proofs
+        ));
+        let mut other = challenge.clone();
+        other.nonce = b"another nonce".to_vec();
+        assert!(!verify_account_ownership_challenge(
+            &credentials,
+            keys.threshold,
+            account,
+            &other,
+            &proofs
+        ));
+        // The signature of a single credential does not meet the threshold.
+        let mut one = proofs.clone();
+        one.remove(&CredentialIndex { index: 1 });
+        assert!(!verify_account_ownership_challenge(
+            &credentials,
+            keys.threshold,
+            account,
+            &challenge,
+            &one
+        ));
+        assert!(verify_account_ownership_challenge(
+            &credentials,
+            SignatureThreshold(1),
+            account,
+            &challenge,
+            &one
+        ));
+        // Signatures on the nonce alone are not accepted.
+        let mut plain = proofs.clone();
+        plain.insert(
+            CredentialIndex { index: 1 },
+            prove_ownership_of_account(
+                &keys.keys[&CredentialIndex { index: 1 }],
+                account,
+                &challenge.nonce,
+            ),
+        );
+        assert!(!verify_account_ownership_challenge(
+            &credentials,
+            keys.threshold,
+            account,
+            &challenge,
+            &plain
+        ));
+    }
+
+    #[test]
+    fn test_verify_presentation() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let kind = |s: &str| AttributeKind(s.to_string());
+        let (attributes, credential, randomness, _) =
+            statement_test_credential(&global, &mut csprng);
+        let cred_id = *credential.cred_id();
+        let account = AccountAddress::new(&cred_id);
+        let statement = StatementBuilder::<G1, AttributeKind>::new()
+            .in_range(AttributeTag(3), kind("19000101"), kind("20030101"))
+            .in_set(AttributeTag(5), vec![kind("DK"), kind("DE")])
+            .build()
+            .unwrap();
+        let request = Request {
+            challenge:             b"13549686546546546854651357687354".to_vec(),
+            credential_statements: vec![CredentialStatement { cred_id, statement }],
+            accounts:              vec![account],
+        };
+        // Requests are exchanged serialized or as JSON.
+        let parsed = serialize_deserialize(&request).expect("Request should deserialize.");
+        assert_eq!(to_bytes(&parsed), to_bytes(&request));
+        let json = serde_json::to_value(&request).expect("Request should serialize.");
+        assert_eq!(json["challenge"], hex::encode(&request.challenge));
+        let parsed: Request<G1, AttributeKind> =
+            serde_json::from_value(json).expect("Request should parse.");
+        assert_eq!(to_bytes(&parsed), to_bytes(&request));
+
+        let context = ChallengeContext::Legacy;
+        let (_, account_credential, _, account_data) =
+            statement_test_credential(&global, &mut csprng);
+        let account_credentials: AccountCredentials<G1, AttributeKind> =
+            std::iter::once((CredentialIndex { index: 0 }, account_credential)).collect();
+        let account_keys = AccountKeys::from(account_data);
+        let presentation = prove_presentation(
+            &context,
+            &global,
+            &request,
+            &[(&attributes, &randomness)],
+            &[&account_keys],
+            &mut csprng,
+        )
+        .expect("Presentation should be produced.");
+        let versioned = serialize_deserialize(&presentation.to_versioned())
+            .expect("Presentation should deserialize.");
+        let presentation = Presentation::from_versioned(versioned).unwrap();
+        let credentials = [credential];
+        let keys = [(&account_credentials, account_keys.threshold)];
+        assert_eq!(
+            verify_presentation(
+                &context,
+                &global,
+                &request,
+                &credentials,
+                &keys,
+                &presentation
+            ),
+            Ok(())
+        );
+
+        // The proofs are not valid for another challenge.
+        let mut other = request.clone();
+        other.challenge = b"another challenge".to_vec();
+        assert_eq!(
+            verify_presentation(
+                &context,
+                &global,
+                &other,
+                &credentials,
+                &keys,
+                &presentation
+            ),
+            Err(PresentationError::ChallengeMismatch)
+        );
+        let mut replayed = presentation.clone();
+        replayed.challenge = other.challenge.clone();
+        assert_eq!(
+            verify_presentation(&context, &global, &other, &credentials, &keys, &replayed),
+            Err(PresentationError::InvalidCredentialProof(0))
+        );
+
+        // Ownership is checked against the credentials of the account.
+        let (_, other_credential, _, _) = statement_test_credential(&global, &mut csprng);
+        let other_credentials: AccountCredentials<G1, AttributeKind> =
+            std::iter::once((CredentialIndex { index: 0 }, other_credential)).collect();
+        let other_keys = [(&other_credentials, SignatureThreshold(1))];
+        assert_eq!(
+            verify_presentation(
+                &context,
+                &global,
+                &request,
+                &credentials,
+                &other_keys,
+                &presentation
+            ),
+            Err(PresentationError::InvalidAccountProof(account))
+        );
+        assert_eq!(
+            verify_presentation(
+                &context,
+                &global,
+                &request,
+                &credentials,
+                &[],
+                &presentation
+            ),
+            Err(PresentationError::AccountCountMismatch)
+        );
+
+        assert_eq!(
+            Presentation::from_versioned(Versioned::new(Version::from(1), presentation))
+                .unwrap_err(),
+            PresentationError::UnsupportedVersion(Version::from(1))
+        );
+    }
 }
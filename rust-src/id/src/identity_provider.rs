@@ -13,7 +13,7 @@ use elgamal::multicombine;
 use ff::Field;
 use pedersen_scheme::{Commitment, CommitmentKey};
 use rand::*;
-use random_oracle::RandomOracle;
+use random_oracle::{domains, ChallengeContext, RandomOracle};
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -48,10 +48,12 @@ impl std::fmt::Display for Reason {
     }
 }
 
-/// FIXME: This function does not check that the anonymity revocation
-/// parameters make sense.
-/// Validate all the proofs in an identity object request.
+/// Validate all the proofs in an identity object request, and that the
+/// anonymity revocation parameters are consistent with the anonymity revokers
+/// of the context. The proofs only verify if they were produced in the given
+/// challenge context.
 pub fn validate_request<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
+    challenge_context: &ChallengeContext,
     pre_id_obj: &PreIdentityObject<P, C>,
     context: IpContext<P, C>,
 ) -> Result<(), Reason> {
@@ -84,7 +86,8 @@ pub fn validate_request<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         h: ip_info.ip_verify_key.g,
     };
 
-    let mut transcript = RandomOracle::domain("PreIdentityProof");
+    let mut transcript =
+        RandomOracle::domain_in_context(challenge_context, domains::PRE_IDENTITY_PROOF);
     transcript.append_message(b"ctx", &context.global_context);
     transcript.append_message(b"choice_ar_parameters", &pre_id_obj.choice_ar_parameters);
     transcript.append_message(b"cmm_sc", &pre_id_obj.cmm_sc);
@@ -214,6 +217,11 @@ pub fn validate_request<P: Pairing, C: Curve<Scalar = P::ScalarField>>(
         witness,
     };
     let bulletproofs = &pre_id_obj.poks.bulletproofs;
+    // There must be a range proof for the encrypted share of each anonymity
+    // revoker, otherwise the shares without one would not be checked below.
+    if bulletproofs.len() != pre_id_obj.ip_ar_data.len() {
+        return Err(Reason::IncorrectProof);
+    }
     for ((ar_identity, ar_data), proof) in pre_id_obj
         .ip_ar_data
         .iter()
@@ -336,7 +344,7 @@ pub fn verify_credentials<
     ),
     Reason,
 > {
-    validate_request(pre_id_obj, context)?;
+    validate_request(&ChallengeContext::Legacy, pre_id_obj, context)?;
     let sig = sign_identity_object(pre_id_obj, &context.ip_info, alist, ip_secret_key)?;
     let initial_cdi = create_initial_cdi(
         &context.ip_info,
@@ -402,6 +410,24 @@ fn sign_initial_cred_values<
         .into()
 }
 
+/// Sign the metadata of the identity provider with the key
+/// [IpInfo::ip_cdi_verify_key], so that wallets can verify it with
+/// [SignedIpMetadata::verify].
+pub fn sign_ip_metadata<P: Pairing>(
+    metadata: IpMetadata,
+    ip_info: &IpInfo<P>,
+    ip_cdi_secret_key: &ed25519_dalek::SecretKey,
+) -> SignedIpMetadata {
+    let to_sign = metadata.to_sign(ip_info.ip_identity);
+    let expanded_sk = ed25519_dalek::ExpandedSecretKey::from(ip_cdi_secret_key);
+    let signature = expanded_sk.sign(&to_sign, &ip_info.ip_cdi_verify_key);
+    SignedIpMetadata {
+        ip_identity: ip_info.ip_identity,
+        metadata,
+        signature,
+    }
+}
+
 pub fn compute_message<P: Pairing, AttributeType: Attribute<P::ScalarField>>(
     cmm_prf: &Commitment<P::G1>,
     cmm_sc: &Commitment<P::G1>,
@@ -483,10 +509,8 @@ pub fn compute_message<P: Pairing, AttributeType: Attribute<P::ScalarField>>(
 mod tests {
     use super::*;
     use crate::{constants::ArCurve, test::*};
-    use crypto_common::types::{KeyIndex, KeyPair};
     use ff::Field;
     use pedersen_scheme::{CommitmentKey, Value as PedersenValue};
-    use std::collections::btree_map::BTreeMap;
 
     const EXPIRY: TransactionTime = TransactionTime {
         seconds: 111111111111111111,
@@ -535,6 +559,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_signed_ip_metadata() {
+        let mut csprng = thread_rng();
+        let IpData {
+            public_ip_info: ip_info,
+            ip_cdi_secret_key,
+            ..
+        } = test_create_ip_info(&mut csprng, 2, 10);
+        let metadata = IpMetadata {
+            display_name: "Identity provider".to_owned(),
+            icon_url:     "https://example.com/icon.png".to_owned(),
+            support_url:  "https://example.com/support".to_owned(),
+            issuance_url: "https://example.com/issue".to_owned(),
+        };
+        let signed = sign_ip_metadata(metadata.clone(), &ip_info, &ip_cdi_secret_key);
+        assert_eq!(signed.verify(&ip_info), Ok(&metadata));
+
+        let json = serde_json::to_string(&signed).expect("Serialization should succeed.");
+        let parsed: SignedIpMetadata = serde_json::from_str(&json).expect("Should parse.");
+        assert_eq!(parsed.verify(&ip_info), Ok(&metadata));
+
+        let mut tampered = signed.clone();
+        tampered.metadata.issuance_url = "https://example.org/issue".to_owned();
+        assert_eq!(tampered.verify(&ip_info), Err(IpMetadataError::Signature));
+
+        let mut other_ip = ip_info.clone();
+        other_ip.ip_identity = IpIdentity(ip_info.ip_identity.0 + 1);
+        assert_eq!(
+            signed.verify(&other_ip),
+            Err(IpMetadataError::WrongIdentityProvider {
+                expected: other_ip.ip_identity,
+                actual:   ip_info.ip_identity,
+            })
+        );
+    }
+
     /// Check IP's verify_credentials succeeds for well-formed data.
     #[test]
     fn test_verify_credentials_success() {
@@ -553,13 +613,7 @@ mod tests {
 
         let aci = test_create_aci(&mut csprng);
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
         let (context, pio, _) =
@@ -641,13 +695,7 @@ mod tests {
             test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
         let aci = test_create_aci(&mut csprng);
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
         let (ctx, mut pio, _) =
@@ -662,7 +710,7 @@ mod tests {
         let id_cred_sec = aci.cred_holder_info.id_cred.id_cred_sec;
         let (cmm_sc, _) = sc_ck.commit(&id_cred_sec, &mut csprng);
         pio.cmm_sc = cmm_sc;
-        let ver_ok = validate_request(&pio, ctx);
+        let ver_ok = validate_request(&ChallengeContext::Legacy, &pio, ctx);
 
         // Assert
         assert_eq!(
@@ -672,6 +720,91 @@ mod tests {
         );
     }
 
+    /// Test that requests without a range proof for each encrypted share of
+    /// the PRF key are rejected.
+    #[test]
+    fn test_validate_request_fail_missing_range_proof() {
+        let max_attrs = 10;
+        let num_ars = 4;
+        let mut csprng = thread_rng();
+        let IpData {
+            public_ip_info: ip_info,
+            ..
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        let global_ctx = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
+        };
+        let (context, mut pio, _) =
+            test_create_pio(&aci, &ip_info, &ars_infos, &global_ctx, num_ars, &acc_data);
+        assert_eq!(
+            validate_request(&ChallengeContext::Legacy, &pio, context),
+            Ok(())
+        );
+
+        pio.poks.bulletproofs.pop();
+        assert_eq!(
+            validate_request(&ChallengeContext::Legacy, &pio, context),
+            Err(Reason::IncorrectProof),
+            "Request without all range proofs should be rejected."
+        );
+    }
+
+    /// Test that requests produced in one context are rejected in another.
+    #[test]
+    fn test_validate_request_in_context() {
+        let max_attrs = 10;
+        let num_ars = 4;
+        let mut csprng = thread_rng();
+        let IpData {
+            public_ip_info: ip_info,
+            ..
+        } = test_create_ip_info(&mut csprng, num_ars, max_attrs);
+        let global_ctx = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let (ars_infos, _) =
+            test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
+        let aci = test_create_aci(&mut csprng);
+        let acc_data = InitialAccountData {
+            keys:      test_create_keys(&mut csprng, 1),
+            threshold: SignatureThreshold(1),
+        };
+        let context = IpContext::new(&ip_info, &ars_infos, &global_ctx);
+        let proof_context = random_oracle::ProofContext {
+            network_id:       b"testnet".to_vec(),
+            protocol_version: 1,
+            statement_type:   "PreIdentityObject".to_owned(),
+        };
+        let in_context = ChallengeContext::Versioned(proof_context.clone());
+        let (pio, _) = crate::account_holder::generate_pio_with_rng(
+            &in_context,
+            &context,
+            Threshold(2),
+            &aci,
+            &acc_data,
+            &mut csprng,
+        )
+        .expect("Generating the pre-identity object should succeed.");
+        assert_eq!(validate_request(&in_context, &pio, context), Ok(()));
+        assert_eq!(
+            validate_request(&ChallengeContext::Legacy, &pio, context),
+            Err(Reason::IncorrectProof),
+            "Request should not be valid without the context."
+        );
+        let other_network = ChallengeContext::Versioned(random_oracle::ProofContext {
+            network_id: b"mainnet".to_vec(),
+            ..proof_context
+        });
+        assert_eq!(
+            validate_request(&other_network, &pio, context),
+            Err(Reason::IncorrectProof),
+            "Request should not be valid on another network."
+        );
+    }
+
     /// Test IP's verify_credentials fails if the PRF key check fail.
     #[test]
     fn test_verify_credentials_fail_prf_data() {
@@ -688,13 +821,7 @@ mod tests {
             test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, &mut csprng);
         let aci = test_create_aci(&mut csprng);
         let acc_data = InitialAccountData {
-            keys:      {
-                let mut keys = BTreeMap::new();
-                keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-                keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-                keys
-            },
+            keys:      test_create_keys(&mut csprng, 3),
             threshold: SignatureThreshold(2),
         };
         let (context, mut pio, _) =
@@ -708,7 +835,7 @@ mod tests {
             .on_chain_commitment_key
             .commit(&val, &mut csprng);
         pio.cmm_prf = cmm_prf;
-        let ver_ok = validate_request(&pio, context);
+        let ver_ok = validate_request(&ChallengeContext::Legacy, &pio, context);
 
         // Assert
         assert_eq!(
@@ -2,18 +2,35 @@
 extern crate itertools;
 
 pub mod account_holder;
+pub mod account_info;
+pub mod account_ownership;
 pub mod anonymity_revoker;
+#[cfg(feature = "ffi")]
+mod ar_ffi;
+pub mod canonical_json;
 pub mod chain;
+pub mod chunked;
+pub mod conformance;
 pub mod constants;
+pub mod cost;
+pub mod deregistration;
+pub mod diff;
 #[cfg(feature = "ffi")]
 mod ffi;
 pub mod id_prover;
 pub mod id_verifier;
 pub mod identity_provider;
+pub mod message_signing;
+pub mod rerandomize;
+pub mod revocation;
 pub mod secret_sharing;
 pub mod sigma_protocols;
+pub mod statement;
+pub mod statistics;
+pub mod threshold_decryption;
 pub mod types;
 pub mod utils;
+pub mod web3id;
 
 /// Re-export of Pedersen commitments functionality.
 pub use pedersen_scheme as pedersen_commitment;
@@ -27,6 +44,9 @@ pub use elgamal;
 /// Re-export of bulletproofs.
 pub use bulletproofs::range_proof;
 
+/// Re-export of the random oracle, e.g., for the challenge contexts of proofs.
+pub use random_oracle;
+
 #[macro_use]
 extern crate crypto_common_derive;
 
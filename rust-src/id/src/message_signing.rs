@@ -0,0 +1,233 @@
+//! Signatures on arbitrary messages with the keys of an account.
+//!
+//! dApps ask the owner of an account to sign messages, e.g., to log in. The
+//! signature must not be usable for anything else, so the signed hash binds a
+//! [SigningContext] naming the dApp, the chain, and a nonce chosen by the dApp.
+//! A signature for one dApp is then not accepted by another, a signature on a
+//! testnet is not accepted on mainnet, and the dApp rejects a signature that
+//! is replayed to it, by issuing a fresh nonce for every request.
+//!
+//! The signed data starts with the account address followed by 8 zero bytes.
+//! In a transaction these bytes are the nonce of the account, which is never
+//! zero, so a message signature can never be a signature on a transaction.
+use crate::{
+    chunked::{hex_decode, hex_encode},
+    types::*,
+    utils::*,
+};
+use crypto_common::{
+    types::{CredentialIndex, TransactionSignature},
+    *,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Separates message signatures from any other data signed with account keys
+/// that starts with an address followed by a zero nonce.
+const MESSAGE_SIGNING_DOMAIN: &[u8] = b"concordium-message-signing";
+
+/// The context a message is signed in. It is serialized as part of the signed
+/// data, so a signature is only valid in the context it was made in.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningContext {
+    /// Identifier of the dApp that requests the signature, e.g., its domain.
+    pub dapp_id:  String,
+    /// Identifier of the chain, e.g., the hash of its genesis block.
+    pub chain_id: String,
+    /// A nonce chosen by the dApp. The dApp must not accept two signatures
+    /// with the same nonce.
+    pub nonce:    u64,
+}
+
+impl Serial for SigningContext {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&(self.dapp_id.len() as u32));
+        serial_string(&self.dapp_id, out);
+        out.put(&(self.chain_id.len() as u32));
+        serial_string(&self.chain_id, out);
+        out.put(&self.nonce);
+    }
+}
+
+/// A message together with the account and the context it is signed in.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageToSign {
+    /// The account whose keys sign the message.
+    pub account: AccountAddress,
+    pub context: SigningContext,
+    /// The message, hex encoded in JSON.
+    #[serde(serialize_with = "hex_encode", deserialize_with = "hex_decode")]
+    pub message: Vec<u8>,
+}
+
+impl MessageToSign {
+    /// The hash that is signed by the keys of the account. It is the SHA256
+    /// hash of the account address, 8 zero bytes, [MESSAGE_SIGNING_DOMAIN],
+    /// the serialized context, and the length-prefixed message.
+    pub fn hash_to_sign(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(&to_bytes(&self.account));
+        hasher.update(&[0u8; 8]);
+        hasher.update(MESSAGE_SIGNING_DOMAIN);
+        hasher.update(&to_bytes(&self.context));
+        hasher.update(&(self.message.len() as u32).to_be_bytes());
+        hasher.update(&self.message);
+        hasher.finalize().to_vec()
+    }
+}
+
+/// A message together with signatures by the keys of the account.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedMessage {
+    pub message:   MessageToSign,
+    pub signature: TransactionSignature,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+/// Reasons why a signed message is rejected by the verifier.
+pub enum MessageVerificationError {
+    #[error("The message is signed for dApp {0}.")]
+    WrongDapp(String),
+    #[error("The message is signed for chain {0}.")]
+    WrongChain(String),
+    #[error("The message is signed with nonce {0}, which was not issued or is already used.")]
+    WrongNonce(u64),
+    #[error("The message is signed by a different account.")]
+    WrongAccount,
+    #[error("The signatures are not valid for the keys of the account.")]
+    InvalidSignature,
+}
+
+/// Sign the message with all the given keys of the account. The keys must be
+/// valid, see [AccountKeys::validate], and must include enough keys to satisfy
+/// the thresholds of the account.
+pub fn sign_message(
+    keys: &AccountKeys,
+    message: MessageToSign,
+) -> Result<SignedMessage, AccountKeysError> {
+    keys.validate()?;
+    let hash = message.hash_to_sign();
+    Ok(SignedMessage {
+        message,
+        signature: keys.sign_hash(&hash),
+    })
+}
+
+/// Verify a signed message. The verifier gives the context it expects, i.e.,
+/// its own dApp identifier, the chain, and the nonce it issued for this
+/// request, as well as the account it expects the message from, and the keys
+/// and threshold of that account as found in the account info returned by the
+/// node. The signatures must satisfy the same conditions as the signatures of
+/// a transaction, see [verify_transaction_signatures_batch].
+pub fn verify_signed_message(
+    expected_context: &SigningContext,
+    account: &AccountAddress,
+    keys: &BTreeMap<CredentialIndex, CredentialPublicKeys>,
+    threshold: SignatureThreshold,
+    signed: &SignedMessage,
+) -> Result<(), MessageVerificationError> {
+    let context = &signed.message.context;
+    if context.dapp_id != expected_context.dapp_id {
+        return Err(MessageVerificationError::WrongDapp(context.dapp_id.clone()));
+    }
+    if context.chain_id != expected_context.chain_id {
+        return Err(MessageVerificationError::WrongChain(
+            context.chain_id.clone(),
+        ));
+    }
+    if context.nonce != expected_context.nonce {
+        return Err(MessageVerificationError::WrongNonce(context.nonce));
+    }
+    if &signed.message.account != account {
+        return Err(MessageVerificationError::WrongAccount);
+    }
+    let hash = signed.message.hash_to_sign();
+    let valid = verify_transaction_signatures_batch(&[TransactionToVerify {
+        keys,
+        threshold,
+        hash: &hash,
+        signature: &signed.signature,
+    }])[0];
+    if valid {
+        Ok(())
+    } else {
+        Err(MessageVerificationError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::KeyPair;
+    use curve_arithmetic::Curve;
+    use pairing::bls12_381::G1;
+    use rand::*;
+
+    #[test]
+    fn test_signed_message() {
+        let mut csprng = thread_rng();
+        let keys = AccountKeys::from(CredentialData::from(KeyPair::generate(&mut csprng)));
+        let public_keys: BTreeMap<_, _> = keys
+            .keys
+            .iter()
+            .map(|(&idx, cred)| (idx, cred.get_cred_key_info()))
+            .collect();
+        let account = AccountAddress::new(&G1::one_point());
+        let context = SigningContext {
+            dapp_id:  String::from("example.com"),
+            chain_id: String::from("testnet"),
+            nonce:    17,
+        };
+        let message = MessageToSign {
+            account,
+            context: context.clone(),
+            message: b"Log in to example.com".to_vec(),
+        };
+        let signed = sign_message(&keys, message).expect("Keys should be valid.");
+        let verify = |expected: &SigningContext, signed: &SignedMessage| {
+            verify_signed_message(expected, &account, &public_keys, keys.threshold, signed)
+        };
+        assert_eq!(verify(&context, &signed), Ok(()));
+        let json = serde_json::to_string(&signed).expect("Message should serialize.");
+        let parsed: SignedMessage = serde_json::from_str(&json).expect("Message should parse.");
+        assert_eq!(parsed, signed);
+
+        // The context must be the one the verifier expects.
+        let mut other = context.clone();
+        other.dapp_id = String::from("example.org");
+        assert!(matches!(
+            verify(&other, &signed),
+            Err(MessageVerificationError::WrongDapp(_))
+        ));
+        let mut other = context.clone();
+        other.chain_id = String::from("mainnet");
+        assert!(matches!(
+            verify(&other, &signed),
+            Err(MessageVerificationError::WrongChain(_))
+        ));
+        let mut other = context.clone();
+        other.nonce = 18;
+        assert_eq!(
+            verify(&other, &signed),
+            Err(MessageVerificationError::WrongNonce(17))
+        );
+
+        // The context is bound into the signature.
+        let mut tampered = signed.clone();
+        tampered.message.context.nonce = 18;
+        assert_eq!(
+            verify(&tampered.message.context, &tampered),
+            Err(MessageVerificationError::InvalidSignature)
+        );
+        let mut tampered = signed;
+        tampered.message.message = b"Transfer everything".to_vec();
+        assert_eq!(
+            verify(&context, &tampered),
+            Err(MessageVerificationError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Re-randomization of attribute commitments.
+//!
+//! The commitments to the attributes of a credential are on the chain, so
+//! proofs about the same attribute made directly against the on-chain
+//! commitment all contain the same commitment value. Before making a
+//! presentation, the prover can instead compute a fresh commitment to the same
+//! attribute by adding `h^delta` for a random `delta` to the on-chain
+//! commitment, together with a proof of knowledge of `delta`. The proofs of the
+//! presentation, e.g., range proofs, are then made against the fresh commitment
+//! with the randomness returned by [rerandomize_commitment], and each
+//! presentation uses a different commitment.
+use crate::sigma_protocols::{common::*, dlog};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::Curve;
+use ff::Field;
+use pedersen_scheme::{Commitment, CommitmentKey as PedersenKey, Randomness as PedersenRandomness};
+use random_oracle::{domains, ChallengeContext, RandomOracle};
+
+/// A fresh commitment to the same value as an on-chain commitment, together
+/// with a proof that it differs from the on-chain commitment only in the
+/// randomness.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeBase16Serialize)]
+pub struct RerandomizedCommitment<C: Curve> {
+    /// The fresh commitment.
+    pub commitment: Commitment<C>,
+    /// Proof of knowledge of the difference of the randomness of the two
+    /// commitments.
+    pub proof:      dlog::Proof<C>,
+}
+
+fn transcript<C: Curve>(
+    context: &ChallengeContext,
+    on_chain: &Commitment<C>,
+    rerandomized: &Commitment<C>,
+) -> RandomOracle {
+    let mut ro = RandomOracle::domain_in_context(context, domains::COMMITMENT_RERANDOMIZATION);
+    ro.append_message("on_chain_commitment", on_chain);
+    ro.append_message("rerandomized_commitment", rerandomized);
+    ro
+}
+
+/// Re-randomize a commitment with the given commitment key and randomness.
+/// Returns the fresh commitment with the proof of the re-randomization, and
+/// the randomness of the fresh commitment, which must be used for the proofs
+/// about the committed value.
+pub fn rerandomize_commitment<C: Curve, R: rand::Rng>(
+    context: &ChallengeContext,
+    keys: &PedersenKey<C>,
+    on_chain: &Commitment<C>,
+    randomness: &PedersenRandomness<C>,
+    csprng: &mut R,
+) -> Option<(RerandomizedCommitment<C>, PedersenRandomness<C>)> {
+    let delta = PedersenRandomness::<C>::generate_non_zero(csprng);
+    let shift = keys.h.mul_by_scalar(&delta);
+    let commitment = Commitment(on_chain.plus_point(&shift));
+    let prover = dlog::Dlog {
+        public: shift,
+        coeff:  keys.h,
+    };
+    let secret = dlog::DlogSecret {
+        secret: delta.as_value(),
+    };
+    let mut ro = transcript(context, on_chain, &commitment);
+    let proof = prove(&mut ro, &prover, secret, csprng)?;
+    let mut new_randomness = *randomness.as_ref();
+    new_randomness.add_assign(&delta);
+    Some((
+        RerandomizedCommitment { commitment, proof },
+        PedersenRandomness::new(new_randomness),
+    ))
+}
+
+/// Check that the re-randomized commitment commits to the same value as the
+/// on-chain commitment. Proofs about the committed value are then checked
+/// against `rerandomized.commitment`.
+pub fn verify_rerandomization<C: Curve>(
+    context: &ChallengeContext,
+    keys: &PedersenKey<C>,
+    on_chain: &Commitment<C>,
+    rerandomized: &RerandomizedCommitment<C>,
+) -> bool {
+    let verifier = dlog::Dlog {
+        public: rerandomized.commitment.minus_point(on_chain),
+        coeff:  keys.h,
+    };
+    let mut ro = transcript(context, on_chain, &rerandomized.commitment);
+    verify(&mut ro, &verifier, &rerandomized.proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::AttributeKind, id_prover::*, id_verifier::*, types::*};
+    use pairing::bls12_381::G1;
+    use pedersen_scheme::Value;
+    use rand::*;
+
+    #[test]
+    fn test_rerandomize_commitment() {
+        let mut csprng = thread_rng();
+        let global = GlobalContext::<G1>::generate(String::from("genesis_string"));
+        let keys = global.on_chain_commitment_key;
+        let gens = global.bulletproof_generators();
+        let attribute = AttributeKind("20000102".to_string());
+        let lower = AttributeKind("20000101".to_string());
+        let upper = AttributeKind("20000103".to_string());
+        let value = Value::<G1>::new(attribute.to_field_element());
+        let (on_chain, randomness) = keys.commit(&value, &mut csprng);
+        let context = ChallengeContext::Legacy;
+
+        let (first, first_randomness) =
+            rerandomize_commitment(&context, &keys, &on_chain, &randomness, &mut csprng)
+                .expect("Re-randomization should succeed.");
+        let (second, second_randomness) =
+            rerandomize_commitment(&context, &keys, &on_chain, &randomness, &mut csprng)
+                .expect("Re-randomization should succeed.");
+        assert_ne!(first.commitment, on_chain);
+        assert_ne!(first.commitment, second.commitment);
+        assert!(verify_rerandomization(&context, &keys, &on_chain, &first));
+        assert!(verify_rerandomization(&context, &keys, &on_chain, &second));
+        assert!(verify_attribute(
+            &keys,
+            &attribute,
+            &first_randomness,
+            &first.commitment
+        ));
+
+        // Proofs about the attribute are made against the fresh commitment.
+        let proof = prove_attribute_in_range(
+            &ChallengeContext::Legacy,
+            &gens,
+            &keys,
+            &attribute,
+            &lower,
+            &upper,
+            &second_randomness,
+        )
+        .expect("Failed to produce proof.");
+        assert_eq!(
+            verify_attribute_range(
+                &ChallengeContext::Legacy,
+                &keys,
+                &gens,
+                &lower,
+                &upper,
+                &second.commitment,
+                &proof
+            ),
+            Ok(())
+        );
+
+        // A fresh commitment to another value is rejected.
+        let other = Value::<G1>::new(AttributeKind("20000103".to_string()).to_field_element());
+        let (other_commitment, _) = keys.commit(&other, &mut csprng);
+        assert!(!verify_rerandomization(
+            &context,
+            &keys,
+            &other_commitment,
+            &first
+        ));
+        let forged = RerandomizedCommitment {
+            commitment: other_commitment,
+            proof:      first.proof.clone(),
+        };
+        assert!(!verify_rerandomization(&context, &keys, &on_chain, &forged));
+    }
+}
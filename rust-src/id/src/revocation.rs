@@ -0,0 +1,229 @@
+//! Credential revocation lists.
+//!
+//! An identity provider can revoke credentials it has issued, e.g., because
+//! the account keys were compromised. Since the chain has no notion of revoked
+//! credentials, off-chain verifiers of attribute proofs need a way to consult
+//! the revocation status of the credential the proof is about. This module
+//! defines a revocation list, signed by the identity provider with the same
+//! key that is used for signing initial credentials.
+use crate::types::*;
+use anyhow::bail;
+use byteorder::ReadBytesExt;
+use crypto_common::{types::Timestamp, *};
+use curve_arithmetic::{Curve, Pairing};
+use ed25519_dalek as ed25519;
+use ed25519_dalek::Verifier;
+use sha2::{Digest, Sha256};
+
+/// Domain separation string for the signature on a revocation list.
+const REVOCATION_LIST_DOMAIN: &[u8] = b"CredentialRevocationList";
+
+/// Reason why a credential was revoked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RevocationReason {
+    /// No specific reason given.
+    Unspecified,
+    /// The account keys of the credential have been compromised.
+    KeyCompromise,
+    /// The identity the credential was derived from has been revoked.
+    IdentityRevoked,
+    /// The credential holder's attributes have changed, and the credential no
+    /// longer reflects them.
+    AttributesChanged,
+}
+
+impl Serial for RevocationReason {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let tag: u8 = match self {
+            RevocationReason::Unspecified => 0,
+            RevocationReason::KeyCompromise => 1,
+            RevocationReason::IdentityRevoked => 2,
+            RevocationReason::AttributesChanged => 3,
+        };
+        out.put(&tag)
+    }
+}
+
+impl Deserial for RevocationReason {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get::<u8>()? {
+            0 => Ok(RevocationReason::Unspecified),
+            1 => Ok(RevocationReason::KeyCompromise),
+            2 => Ok(RevocationReason::IdentityRevoked),
+            3 => Ok(RevocationReason::AttributesChanged),
+            n => bail!("Unknown revocation reason {}.", n),
+        }
+    }
+}
+
+/// A single revoked credential.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+pub struct RevokedCredential<C: Curve> {
+    /// Registration id of the revoked credential.
+    #[serde(
+        rename = "credId",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    pub cred_id:    C,
+    #[serde(rename = "reason")]
+    pub reason:     RevocationReason,
+    /// Time from which the credential is considered revoked.
+    #[serde(rename = "revokedAt")]
+    pub revoked_at: Timestamp,
+}
+
+/// A list of credentials revoked by an identity provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+pub struct RevocationList<C: Curve> {
+    /// Identity provider that issued the revoked credentials.
+    #[serde(rename = "ipIdentity")]
+    pub ip_identity: IpIdentity,
+    /// Time at which this list was produced. Verifiers can use this to decide
+    /// whether the list is recent enough.
+    #[serde(rename = "issuedAt")]
+    pub issued_at:   Timestamp,
+    #[serde(rename = "revoked")]
+    #[size_length = 4]
+    pub revoked:     Vec<RevokedCredential<C>>,
+}
+
+impl<C: Curve> RevocationList<C> {
+    /// Look up the revocation entry of the given credential, if it is revoked.
+    pub fn lookup(&self, cred_id: &C) -> Option<&RevokedCredential<C>> {
+        self.revoked.iter().find(|r| r.cred_id == *cred_id)
+    }
+
+    /// Check whether the credential is revoked at the given time.
+    pub fn is_revoked(&self, cred_id: &C, time: Timestamp) -> bool {
+        self.lookup(cred_id).map_or(false, |r| r.revoked_at <= time)
+    }
+
+    /// The hash of the list that is signed by the identity provider.
+    fn hash_to_sign(&self) -> impl AsRef<[u8]> {
+        let mut hasher = Sha256::new();
+        hasher.update(REVOCATION_LIST_DOMAIN);
+        hasher.update(&to_bytes(self));
+        hasher.finalize()
+    }
+
+    /// Sign the list with the identity provider's key. This is the key whose
+    /// public part is `ip_cdi_verify_key` in the identity provider's public
+    /// information.
+    pub fn sign<P: Pairing>(
+        self,
+        ip_info: &IpInfo<P>,
+        ip_cdi_secret_key: &ed25519::SecretKey,
+    ) -> SignedRevocationList<C> {
+        let expanded_sk = ed25519::ExpandedSecretKey::from(ip_cdi_secret_key);
+        let signature = expanded_sk.sign(self.hash_to_sign().as_ref(), &ip_info.ip_cdi_verify_key);
+        SignedRevocationList {
+            list: self,
+            signature,
+        }
+    }
+}
+
+/// A revocation list together with the signature of the identity provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+pub struct SignedRevocationList<C: Curve> {
+    #[serde(rename = "list")]
+    pub list:      RevocationList<C>,
+    #[serde(
+        rename = "signature",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    pub signature: ed25519::Signature,
+}
+
+impl<C: Curve> SignedRevocationList<C> {
+    /// Check that the list was signed by the given identity provider.
+    pub fn verify<P: Pairing>(&self, ip_info: &IpInfo<P>) -> bool {
+        self.list.ip_identity == ip_info.ip_identity
+            && ip_info
+                .ip_cdi_verify_key
+                .verify(self.list.hash_to_sign().as_ref(), &self.signature)
+                .is_ok()
+    }
+
+    /// Check the revocation status of a credential. This returns `None` if the
+    /// list is not correctly signed by the given identity provider, since the
+    /// status cannot be determined in that case.
+    pub fn check<P: Pairing>(
+        &self,
+        ip_info: &IpInfo<P>,
+        cred_id: &C,
+        time: Timestamp,
+    ) -> Option<bool> {
+        if self.verify(ip_info) {
+            Some(self.list.is_revoked(cred_id, time))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::*;
+    use pairing::bls12_381::G1;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_revocation_list() {
+        let mut csprng = thread_rng();
+        let IpData {
+            public_ip_info: ip_info,
+            ip_cdi_secret_key,
+            ..
+        } = test_create_ip_info(&mut csprng, 3, 10);
+        let revoked_id = G1::generate(&mut csprng);
+        let other_id = G1::generate(&mut csprng);
+        let list = RevocationList {
+            ip_identity: ip_info.ip_identity,
+            issued_at:   Timestamp::from(2000),
+            revoked:     vec![RevokedCredential {
+                cred_id:    revoked_id,
+                reason:     RevocationReason::KeyCompromise,
+                revoked_at: Timestamp::from(1000),
+            }],
+        };
+        let signed = list.sign(&ip_info, &ip_cdi_secret_key);
+        assert!(signed.verify(&ip_info));
+        assert_eq!(
+            signed.check(&ip_info, &revoked_id, Timestamp::from(1500)),
+            Some(true)
+        );
+        assert_eq!(
+            signed.check(&ip_info, &revoked_id, Timestamp::from(500)),
+            Some(false)
+        );
+        assert_eq!(
+            signed.check(&ip_info, &other_id, Timestamp::from(1500)),
+            Some(false)
+        );
+
+        let bytes = to_bytes(&signed);
+        let parsed: SignedRevocationList<G1> =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).expect("Deserialization should succeed.");
+        assert_eq!(parsed, signed);
+        let json = serde_json::to_string(&signed).expect("JSON serialization should succeed.");
+        let parsed: SignedRevocationList<G1> =
+            serde_json::from_str(&json).expect("JSON deserialization should succeed.");
+        assert_eq!(parsed, signed);
+
+        let mut tampered = signed;
+        tampered.list.revoked[0].reason = RevocationReason::Unspecified;
+        assert!(!tampered.verify(&ip_info));
+        assert_eq!(
+            tampered.check(&ip_info, &revoked_id, Timestamp::from(1500)),
+            None
+        );
+    }
+}
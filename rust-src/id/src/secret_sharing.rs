@@ -194,6 +194,98 @@ pub fn reveal_in_group<P: Into<u64> + Copy, C: Curve>(shares: &[(P, C)]) -> C {
     })
 }
 
+/// Data of one participant in a round of share refresh.
+///
+/// To refresh the shares of a secret without changing it, each participant
+/// shares zero with the same threshold and points as the original sharing,
+/// and sends the share for each point to its holder, together with the
+/// commitments to the coefficients. Each holder checks the shares it received
+/// with [verify_refresh_share] and adds them to its share with
+/// [apply_refresh]. Since the shared values sum to zero, the refreshed shares
+/// still reveal the original secret, but shares from before the refresh can
+/// no longer be combined with shares from after it.
+pub struct RefreshData<C: Curve> {
+    /// Commitments `g^a_i` to the coefficients of the sharing polynomial of
+    /// zero, except the zeroth, which is zero.
+    pub coefficient_commitments: Vec<C>,
+    /// Shares of zero, i.e., evaluations of the polynomial at the specified
+    /// points.
+    pub shares:                  Vec<PedersenValue<C>>,
+}
+
+/// Generate the data of one participant in a round of share refresh. The
+/// points and the threshold must be the ones of the original sharing. The
+/// generator is the one used to check the shares, and is typically the
+/// generator the secret is used with. The preconditions of [share] apply.
+pub fn share_refresh<
+    C: Curve,
+    P: Into<u64>,
+    I: IntoIterator<Item = P> + ExactSizeIterator,
+    R: Rng,
+>(
+    generator: &C,
+    points: I,
+    revealing_threshold: Threshold,
+    csprng: &mut R,
+) -> RefreshData<C> {
+    let SharingData {
+        coefficients,
+        shares,
+    } = share::<C, _, _, _>(&C::Scalar::zero(), points, revealing_threshold, csprng);
+    let coefficient_commitments = coefficients
+        .iter()
+        .map(|coeff| generator.mul_by_scalar(coeff))
+        .collect();
+    RefreshData {
+        coefficient_commitments,
+        shares,
+    }
+}
+
+/// Check that a share received in a round of share refresh is the evaluation
+/// at the given point of a polynomial with zero constant term and the
+/// committed coefficients, i.e., that `g^share = prod_i A_i^(point^i)`.
+/// Checking this prevents a participant from changing the shared secret.
+/// The number of commitments must be one less than the threshold of the
+/// sharing, otherwise the participant could change the threshold, and the
+/// share is rejected if it is not.
+pub fn verify_refresh_share<C: Curve, P: Into<u64>>(
+    generator: &C,
+    point: P,
+    revealing_threshold: Threshold,
+    share: &PedersenValue<C>,
+    coefficient_commitments: &[C],
+) -> bool {
+    if coefficient_commitments.len() + 1 != usize::from(revealing_threshold) {
+        return false;
+    }
+    let x = C::scalar_from_u64(point.into());
+    // Evaluate the polynomial in the exponent by Horner's method, with the
+    // final multiplication by x accounting for the zero constant term.
+    let expected = coefficient_commitments
+        .iter()
+        .rev()
+        .fold(C::zero_point(), |accum, commitment| {
+            accum.mul_by_scalar(&x).plus_point(commitment)
+        })
+        .mul_by_scalar(&x);
+    generator.mul_by_scalar(share) == expected
+}
+
+/// Add the shares of zero received in a round of share refresh to a share,
+/// giving the refreshed share. The received shares should be checked with
+/// [verify_refresh_share] first.
+pub fn apply_refresh<C: Curve>(
+    share: &PedersenValue<C>,
+    updates: &[PedersenValue<C>],
+) -> PedersenValue<C> {
+    let refreshed = updates.iter().fold(**share, |mut accum, update| {
+        accum.add_assign(update);
+        accum
+    });
+    PedersenValue::new(refreshed)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -334,4 +426,92 @@ mod test {
             assert_ne!(revealed_data_point, secret_point);
         }
     }
+
+    /// Test share refresh:
+    ///   - The refreshed shares reveal the same secret
+    ///   - The shares of zero verify against the commitments, and a modified
+    ///     share does not
+    ///   - Mixing old and refreshed shares does not reveal the secret
+    #[test]
+    pub fn test_share_refresh() {
+        let mut csprng = thread_rng();
+        let generator =
+            G1::one_point().mul_by_scalar(&<G1 as Curve>::generate_non_zero_scalar(&mut csprng));
+        for n in 2u8..8 {
+            let secret = <G1 as Curve>::generate_scalar(&mut csprng);
+            let threshold = Threshold(csprng.gen_range(1, n + 1));
+            let xs = (1..=n).collect::<Vec<_>>();
+            let sharing_data =
+                share::<G1, _, _, _>(&secret, xs.iter().copied(), threshold, &mut csprng);
+
+            // Every holder of a share takes part in the refresh.
+            let rounds = xs
+                .iter()
+                .map(|_| share_refresh(&generator, xs.iter().copied(), threshold, &mut csprng))
+                .collect::<Vec<_>>();
+            let mut refreshed = Vec::with_capacity(xs.len());
+            for (i, (&x, old)) in xs.iter().zip(sharing_data.shares.iter()).enumerate() {
+                let updates = rounds
+                    .iter()
+                    .map(|round| {
+                        assert_eq!(
+                            round.coefficient_commitments.len() + 1,
+                            usize::from(threshold)
+                        );
+                        assert!(verify_refresh_share(
+                            &generator,
+                            x,
+                            threshold,
+                            &round.shares[i],
+                            &round.coefficient_commitments
+                        ));
+                        round.shares[i].clone()
+                    })
+                    .collect::<Vec<_>>();
+                refreshed.push((x, apply_refresh(old, &updates)));
+            }
+
+            let sample = &refreshed[(n - threshold.0) as usize..];
+            assert_eq!(reveal::<_, G1>(sample), secret);
+
+            let bad_share = PedersenValue::generate(&mut csprng);
+            assert!(!verify_refresh_share(
+                &generator,
+                xs[0],
+                threshold,
+                &bad_share,
+                &rounds[0].coefficient_commitments
+            ));
+
+            // A participant cannot raise the threshold by committing to more
+            // coefficients, nor lower it by committing to fewer.
+            let mut longer = rounds[0].coefficient_commitments.clone();
+            longer.push(G1::zero_point());
+            assert!(!verify_refresh_share(
+                &generator,
+                xs[0],
+                threshold,
+                &rounds[0].shares[0],
+                &longer
+            ));
+            if threshold.0 > 1 {
+                let shorter = &rounds[0].coefficient_commitments[1..];
+                assert!(!verify_refresh_share(
+                    &generator,
+                    xs[0],
+                    threshold,
+                    &rounds[0].shares[0],
+                    shorter
+                ));
+            }
+
+            // With threshold 1 the shares of zero are all zero and refresh does not
+            // change the shares, so mixing only makes a difference for larger thresholds.
+            if threshold.0 > 1 {
+                let mut mixed = refreshed[..usize::from(threshold)].to_vec();
+                mixed[0] = (xs[0], sharing_data.shares[0].clone());
+                assert_ne!(reveal::<_, G1>(&mixed), secret);
+            }
+        }
+    }
 }
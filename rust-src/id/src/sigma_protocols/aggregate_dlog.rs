@@ -18,12 +18,42 @@ pub struct AggregateDlog<C: Curve> {
 }
 
 /// Aggregate dlog witness. We deliberately make it opaque.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Witness<C: Curve> {
+    /// One per coefficient. The protocols of the library use only a few.
     #[size_length = 4]
+    #[max_size = 256]
     witness: Vec<C::Scalar>,
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_vec",
+        deserialize_with = "base16_decode_vec"
+    )]
+    witness: Vec<C::Scalar>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for Witness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 /// Convenient alias for aggregate dlog proof
 pub type Proof<C> = SigmaProof<Witness<C>>;
 
@@ -86,11 +116,13 @@ impl<C: Curve> SigmaProtocol for AggregateDlog<C> {
         if witness.witness.len() != self.coeff.len() {
             return None;
         }
-        let mut point = self.public.mul_by_scalar(challenge);
-        for (ref w, ref g) in izip!(witness.witness.iter(), self.coeff.iter()) {
-            point = point.plus_point(&g.mul_by_scalar(w));
-        }
-        Some(point)
+        let mut bases = Vec::with_capacity(self.coeff.len() + 1);
+        let mut exps = Vec::with_capacity(self.coeff.len() + 1);
+        bases.push(self.public);
+        exps.push(*challenge);
+        bases.extend_from_slice(&self.coeff);
+        exps.extend_from_slice(&witness.witness);
+        Some(multiexp(&bases, &exps))
     }
 
     #[cfg(test)]
@@ -114,6 +146,15 @@ impl<C: Curve> SigmaProtocol for AggregateDlog<C> {
     }
 }
 
+impl<C: Curve> SimulatableSigmaProtocol for AggregateDlog<C> {
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        let witness = (0..self.coeff.len())
+            .map(|_| C::generate_scalar(csprng))
+            .collect();
+        Some(Witness { witness })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,4 +218,28 @@ mod tests {
             })
         }
     }
+
+    #[cfg(feature = "json-proofs")]
+    #[test]
+    pub fn test_aggregate_dlog_json_roundtrip() {
+        let mut csprng = thread_rng();
+        AggregateDlog::with_valid_data(5, &mut csprng, |agg: AggregateDlog<G1>, secret, csprng| {
+            let mut ro = RandomOracle::domain(generate_challenge_prefix(csprng));
+            let proof = prove(&mut ro.split(), &agg, secret, csprng).expect("Input data is valid.");
+            // The serde instance is still the opaque hex string.
+            assert!(serde_json::to_value(&proof).map_or(false, |v| v.is_string()));
+            let json = proof
+                .serialize_structured(serde_json::value::Serializer)
+                .expect("Proofs can be serialized.");
+            let witnesses = json["witness"]["witness"]
+                .as_array()
+                .expect("The witness should be a list of scalars.");
+            assert_eq!(witnesses.len(), 5);
+            assert!(witnesses.iter().all(|w| w.is_string()));
+            let parsed: Proof<G1> =
+                StructuredSerde::deserialize_structured(json).expect("Proofs can be parsed.");
+            assert_eq!(to_bytes(&parsed), to_bytes(&proof));
+            assert!(verify(&mut ro, &agg, &parsed));
+        })
+    }
 }
@@ -35,6 +35,34 @@ pub struct Witness<T: Curve> {
     witness: (T::Scalar, T::Scalar, T::Scalar),
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "T: Curve", deserialize = "T: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<T: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_triple",
+        deserialize_with = "base16_decode_triple"
+    )]
+    witness: (T::Scalar, T::Scalar, T::Scalar),
+}
+
+#[cfg(feature = "json-proofs")]
+impl<T: Curve> StructuredSerde for Witness<T> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 pub struct ComEncEq<C: Curve> {
     /// The encryption $e$ of the secret value.
     pub cipher: ElGamalCipher<C>,
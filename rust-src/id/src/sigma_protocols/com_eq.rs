@@ -24,6 +24,34 @@ pub struct Witness<T: Curve> {
     pub witness: (T::Scalar, T::Scalar),
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "T: Curve", deserialize = "T: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<T: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_pair",
+        deserialize_with = "base16_decode_pair"
+    )]
+    witness: (T::Scalar, T::Scalar),
+}
+
+#[cfg(feature = "json-proofs")]
+impl<T: Curve> StructuredSerde for Witness<T> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct CommittedPoints<C: Curve, D: Curve> {
     pub u: C,
@@ -111,11 +139,11 @@ impl<C: Curve, D: Curve<Scalar = C::Scalar>> SigmaProtocol for ComEq<C, D> {
 
         let u = multiexp(&[self.y, self.g], &[*challenge, witness.witness.0]);
 
-        let v = self.commitment.mul_by_scalar(challenge).plus_point(
-            &self
-                .cmm_key
-                .hide_worker(&witness.witness.0, &witness.witness.1),
-        );
+        let v = multiexp(&[self.commitment.0, self.cmm_key.g, self.cmm_key.h], &[
+            *challenge,
+            witness.witness.0,
+            witness.witness.1,
+        ]);
         Some(CommittedPoints {
             u,
             v: Commitment(v),
@@ -212,4 +240,29 @@ mod test {
             })
         }
     }
+
+    #[cfg(feature = "json-proofs")]
+    #[test]
+    pub fn test_com_eq_json_roundtrip() {
+        let mut csprng = rand::thread_rng();
+        ComEq::<G1, G2>::with_valid_data(0, &mut csprng, |com_eq, secret, csprng| {
+            let mut ro = RandomOracle::domain(generate_challenge_prefix(csprng));
+            let proof =
+                prove(&mut ro.split(), &com_eq, secret, csprng).expect("Proving should succeed.");
+            // The serde instance is still the opaque hex string.
+            assert!(serde_json::to_value(&proof).map_or(false, |v| v.is_string()));
+            let json = proof
+                .serialize_structured(serde_json::value::Serializer)
+                .expect("Proofs can be serialized.");
+            let components = json["witness"]["witness"]
+                .as_array()
+                .expect("The witness should be a list of its components.");
+            assert_eq!(components.len(), 2);
+            assert!(components.iter().all(|c| c.is_string()));
+            let parsed: SigmaProof<Witness<G1>> =
+                StructuredSerde::deserialize_structured(json).expect("Proofs can be parsed.");
+            assert_eq!(parsed, proof);
+            assert!(verify(&mut ro, &com_eq, &parsed));
+        })
+    }
 }
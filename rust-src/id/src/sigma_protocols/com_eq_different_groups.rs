@@ -24,6 +24,37 @@ pub struct Witness<C1: Curve, C2: Curve<Scalar = C1::Scalar>> {
     witness: (C1::Scalar, C1::Scalar, C2::Scalar),
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(
+        serialize = "C1: Curve, C2: Curve<Scalar = C1::Scalar>",
+        deserialize = "C1: Curve, C2: Curve<Scalar = C1::Scalar>"
+    ),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C1: Curve, C2: Curve<Scalar = C1::Scalar>> {
+    #[serde(
+        serialize_with = "base16_encode_triple",
+        deserialize_with = "base16_decode_triple"
+    )]
+    witness: (C1::Scalar, C1::Scalar, C2::Scalar),
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C1: Curve, C2: Curve<Scalar = C1::Scalar>> StructuredSerde for Witness<C1, C2> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 pub struct ComEqDiffGroups<C1: Curve, C2: Curve> {
     /// A pair of commitments to the same value in different
     ///   groups.
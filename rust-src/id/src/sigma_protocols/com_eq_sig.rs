@@ -20,10 +20,46 @@ pub struct Witness<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
     /// List of witnesses $(w_i, R_i)$ that the user knows the messages m_i and
     /// randomness R_i that combine to commitments and the public randomized
     /// signature.
+    /// There is one per signed message, i.e., per attribute and the few fixed
+    /// values of the identity, so 1024 leaves ample room.
     #[size_length = 4]
+    #[max_size = 1024]
     witness_commit: Vec<(P::ScalarField, C::Scalar)>,
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(
+        serialize = "P: Pairing, C: Curve<Scalar = P::ScalarField>",
+        deserialize = "P: Pairing, C: Curve<Scalar = P::ScalarField>"
+    ),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    witness_rho:    P::ScalarField,
+    #[serde(
+        serialize_with = "base16_encode_vec_pair",
+        deserialize_with = "base16_decode_vec_pair"
+    )]
+    witness_commit: Vec<(P::ScalarField, C::Scalar)>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> StructuredSerde for Witness<P, C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 pub struct ComEqSig<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
     /// The blinded signature
     pub blinded_sig: BlindedSignature<P>,
@@ -46,6 +46,41 @@ pub struct Witness<C: Curve> {
     s:  C::Scalar,
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_vec",
+        deserialize_with = "base16_decode_vec"
+    )]
+    zs: Vec<C::Scalar>,
+    #[serde(
+        serialize_with = "base16_encode_vec",
+        deserialize_with = "base16_decode_vec"
+    )]
+    ss: Vec<C::Scalar>,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    s:  C::Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for Witness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 impl<C: Curve> SigmaProtocol for ComLin<C> {
     type CommitMessage = (Vec<Commitment<C>>, Commitment<C>);
     type ProtocolChallenge = C::Scalar;
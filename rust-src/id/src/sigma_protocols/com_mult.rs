@@ -32,6 +32,41 @@ pub struct Witness<C: Curve> {
     t:  C::Scalar,
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C: Curve> {
+    #[serde(
+        serialize_with = "base16_encode_array",
+        deserialize_with = "base16_decode_array"
+    )]
+    ss: [C::Scalar; 2],
+    #[serde(
+        serialize_with = "base16_encode_array",
+        deserialize_with = "base16_decode_array"
+    )]
+    ts: [C::Scalar; 2],
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    t:  C::Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for Witness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 #[allow(non_snake_case)]
 impl<'a, C: Curve> SigmaProtocol for ComMult<C> {
     type CommitMessage = ([Commitment<C>; 2], Commitment<C>);
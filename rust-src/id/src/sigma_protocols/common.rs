@@ -78,12 +78,80 @@ pub struct SigmaProof<W: Serialize> {
     pub witness:   W,
 }
 
+/// The structured JSON representation of [SigmaProof], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "SigmaProof",
+    bound(serialize = "W: StructuredSerde", deserialize = "W: StructuredSerde"),
+    rename_all = "camelCase"
+)]
+struct SigmaProofJson<W: Serialize> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    challenge: Challenge,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    witness:   W,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<W: Serialize + StructuredSerde> StructuredSerde for SigmaProof<W> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        SigmaProofJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        SigmaProofJson::deserialize(des)
+    }
+}
+
 #[derive(Serialize)]
 pub struct AndWitness<W1: Serialize, W2: Serialize> {
     pub w1: W1,
     pub w2: W2,
 }
 
+/// The structured JSON representation of [AndWitness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "AndWitness",
+    bound(
+        serialize = "W1: StructuredSerde, W2: StructuredSerde",
+        deserialize = "W1: StructuredSerde, W2: StructuredSerde"
+    ),
+    rename_all = "camelCase"
+)]
+struct AndWitnessJson<W1: Serialize, W2: Serialize> {
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    w1: W1,
+    #[serde(
+        serialize_with = "structured_encode",
+        deserialize_with = "structured_decode"
+    )]
+    w2: W2,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<W1: Serialize + StructuredSerde, W2: Serialize + StructuredSerde> StructuredSerde
+    for AndWitness<W1, W2>
+{
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        AndWitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        AndWitnessJson::deserialize(des)
+    }
+}
+
 /// An adapter to combine multiple provers or multiple verifiers.
 /// The marker type C is for convenience in use with the
 /// SigmaProtocolProver/Verifier traits below.
@@ -183,6 +251,34 @@ pub struct ReplicateWitness<W: Serialize> {
     pub witnesses: Vec<W>,
 }
 
+/// The structured JSON representation of [ReplicateWitness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "ReplicateWitness",
+    bound(serialize = "W: StructuredSerde", deserialize = "W: StructuredSerde"),
+    rename_all = "camelCase"
+)]
+struct ReplicateWitnessJson<W: Serialize> {
+    #[serde(
+        serialize_with = "structured_encode_vec",
+        deserialize_with = "structured_decode_vec"
+    )]
+    witnesses: Vec<W>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<W: Serialize + StructuredSerde> StructuredSerde for ReplicateWitness<W> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ReplicateWitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        ReplicateWitnessJson::deserialize(des)
+    }
+}
+
 /// An adapter to combine multiple provers or multiple verifiers.
 /// The marker type C is for convenience in use with the
 /// SigmaProtocolProver/Verifier traits below.
@@ -286,6 +382,235 @@ impl<P: SigmaProtocol> ReplicateAdapter<P> {
     }
 }
 
+/// Sigma protocols whose transcripts can be simulated without knowing the
+/// secret, for any given challenge. For all the protocols in this module the
+/// responses of an honest prover are uniformly random, so a random response
+/// together with the commit message extracted from it with
+/// [extract_point](SigmaProtocol::extract_point) is distributed like a real
+/// transcript. This is what [OrAdapter] needs for the statements the prover
+/// does not know the secrets of.
+pub trait SimulatableSigmaProtocol: SigmaProtocol {
+    /// A uniformly random response. This is allowed to return `None` if the
+    /// public data is malformed.
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness>;
+}
+
+impl<P1: SimulatableSigmaProtocol, P2: SimulatableSigmaProtocol> SimulatableSigmaProtocol
+    for AndAdapter<P1, P2>
+{
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        let w1 = self.first.random_witness(csprng)?;
+        let w2 = self.second.random_witness(csprng)?;
+        Some(AndWitness { w1, w2 })
+    }
+}
+
+impl<P: SimulatableSigmaProtocol> SimulatableSigmaProtocol for ReplicateAdapter<P> {
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        let mut witnesses = Vec::with_capacity(self.protocols.len());
+        for p in self.protocols.iter() {
+            witnesses.push(p.random_witness(csprng)?);
+        }
+        Some(ReplicateWitness { witnesses })
+    }
+}
+
+/// The response of a [OrAdapter]: the challenges and responses of all the
+/// statements.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrWitness<W: Serialize> {
+    /// The challenges of all the statements but the last. The challenge of
+    /// the last is the XOR of the challenge of the proof with them.
+    #[size_length = 4]
+    pub challenges: Vec<Challenge>,
+    #[size_length = 4]
+    pub witnesses:  Vec<W>,
+}
+
+/// The structured JSON representation of [OrWitness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "OrWitness",
+    bound(serialize = "W: StructuredSerde", deserialize = "W: StructuredSerde"),
+    rename_all = "camelCase"
+)]
+struct OrWitnessJson<W: Serialize> {
+    #[serde(
+        serialize_with = "base16_encode_vec",
+        deserialize_with = "base16_decode_vec"
+    )]
+    challenges: Vec<Challenge>,
+    #[serde(
+        serialize_with = "structured_encode_vec",
+        deserialize_with = "structured_decode_vec"
+    )]
+    witnesses:  Vec<W>,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<W: Serialize + StructuredSerde> StructuredSerde for OrWitness<W> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        OrWitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        OrWitnessJson::deserialize(des)
+    }
+}
+
+/// An adapter to prove that the prover knows the secret of one of several
+/// statements of the same kind. The prover simulates the transcripts of the
+/// statements it does not know the secrets of, with challenges chosen at
+/// random before committing, and answers the challenge that is left for the
+/// other statement honestly. The verifier cannot tell which of the transcripts
+/// are simulated.
+pub struct OrAdapter<P> {
+    /// The statements. This vector is assumed to be non-empty.
+    pub protocols: Vec<P>,
+    /// The index of the statement the prover knows the secret of. This is
+    /// ignored when verifying.
+    pub known:     usize,
+}
+
+/// State of the prover of a [OrAdapter]: the state of the statement it
+/// knows the secret of, and the challenges and responses of the simulated
+/// ones, in order.
+pub type OrState<P> = (
+    <P as SigmaProtocol>::ProverState,
+    Vec<(Challenge, <P as SigmaProtocol>::ProverWitness)>,
+);
+
+impl<P: SimulatableSigmaProtocol> SigmaProtocol for OrAdapter<P> {
+    type CommitMessage = ReplicatePoints<P::CommitMessage>;
+    // The challenge is split between the statements, so it is kept as bytes.
+    type ProtocolChallenge = Challenge;
+    type ProverState = OrState<P>;
+    type ProverWitness = OrWitness<P::ProverWitness>;
+    type SecretData = P::SecretData;
+
+    fn public(&self, ro: &mut RandomOracle) { self.protocols.iter().for_each(|p| p.public(ro)) }
+
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge { *challenge }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let mut points = Vec::with_capacity(self.protocols.len());
+        let mut state = None;
+        let mut simulated = Vec::with_capacity(self.protocols.len());
+        for (i, p) in self.protocols.iter().enumerate() {
+            if i == self.known {
+                let (m, s) = p.commit_point(csprng)?;
+                points.push(m);
+                state = Some(s);
+            } else {
+                let c = Challenge::from(csprng.gen::<[u8; 32]>());
+                let w = p.random_witness(csprng)?;
+                points.push(p.extract_point(&p.get_challenge(&c), &w)?);
+                simulated.push((c, w));
+            }
+        }
+        Some((ReplicatePoints { points }, (state?, simulated)))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        let (state, simulated) = state;
+        if simulated.len() + 1 != self.protocols.len() {
+            return None;
+        }
+        let known = self.protocols.get(self.known)?;
+        let c = simulated.iter().fold(*challenge, |c, (ci, _)| c ^ *ci);
+        let mut w = Some(known.generate_witness(secret, state, &known.get_challenge(&c))?);
+        let mut simulated = simulated.into_iter();
+        let mut challenges = Vec::with_capacity(self.protocols.len());
+        let mut witnesses = Vec::with_capacity(self.protocols.len());
+        for i in 0..self.protocols.len() {
+            if i == self.known {
+                challenges.push(c);
+                witnesses.push(w.take()?);
+            } else {
+                let (ci, wi) = simulated.next()?;
+                challenges.push(ci);
+                witnesses.push(wi);
+            }
+        }
+        // The last challenge is determined by the others.
+        challenges.pop();
+        Some(OrWitness {
+            challenges,
+            witnesses,
+        })
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        let n = self.protocols.len();
+        if n == 0 || witness.challenges.len() + 1 != n || witness.witnesses.len() != n {
+            return None;
+        }
+        let last = witness.challenges.iter().fold(*challenge, |c, ci| c ^ *ci);
+        let mut points = Vec::with_capacity(n);
+        for (p, c, w) in izip!(
+            self.protocols.iter(),
+            witness.challenges.iter().chain(std::iter::once(&last)),
+            witness.witnesses.iter()
+        ) {
+            points.push(p.extract_point(&p.get_challenge(c), w)?);
+        }
+        Some(ReplicatePoints { points })
+    }
+
+    #[cfg(test)]
+    fn with_valid_data<R: rand::Rng>(
+        data_size: usize,
+        csprng: &mut R,
+        f: impl FnOnce(Self, Self::SecretData, &mut R) -> (),
+    ) {
+        // The other statements are unrelated valid instances, whose secrets the
+        // prover does not use.
+        P::with_valid_data(data_size, csprng, |first, _, csprng| {
+            P::with_valid_data(data_size, csprng, |second, s2, csprng| {
+                P::with_valid_data(data_size, csprng, |third, _, csprng| {
+                    let or = OrAdapter {
+                        protocols: vec![first, second, third],
+                        known:     1,
+                    };
+                    f(or, s2, csprng)
+                })
+            })
+        })
+    }
+}
+
+impl<P: SimulatableSigmaProtocol> SimulatableSigmaProtocol for OrAdapter<P> {
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        let n = self.protocols.len();
+        let mut challenges = Vec::with_capacity(n);
+        let mut witnesses = Vec::with_capacity(n);
+        for (i, p) in self.protocols.iter().enumerate() {
+            if i + 1 < n {
+                challenges.push(Challenge::from(csprng.gen::<[u8; 32]>()));
+            }
+            witnesses.push(p.random_witness(csprng)?);
+        }
+        Some(OrWitness {
+            challenges,
+            witnesses,
+        })
+    }
+}
+
 /// Given a sigma protocol prover and a context (in the form of the random
 /// oracle), produce a sigma proof and update the context. This function can
 /// return 'None' if the input data is inconsistent.
@@ -4,7 +4,7 @@
 use crate::sigma_protocols::common::*;
 use crypto_common::*;
 use crypto_common_derive::*;
-use curve_arithmetic::{Curve, Value};
+use curve_arithmetic::{multiexp, Curve, Value};
 use ff::Field;
 use random_oracle::{Challenge, RandomOracle};
 
@@ -26,6 +26,31 @@ pub struct Witness<C: Curve> {
     witness: C::Scalar,
 }
 
+/// The structured JSON representation of [Witness], see
+/// [StructuredSerde].
+#[cfg(feature = "json-proofs")]
+#[derive(SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    remote = "Witness",
+    bound(serialize = "C: Curve", deserialize = "C: Curve"),
+    rename_all = "camelCase"
+)]
+struct WitnessJson<C: Curve> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    witness: C::Scalar,
+}
+
+#[cfg(feature = "json-proofs")]
+impl<C: Curve> StructuredSerde for Witness<C> {
+    fn serialize_structured<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        WitnessJson::serialize(self, ser)
+    }
+
+    fn deserialize_structured<'de, D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        WitnessJson::deserialize(des)
+    }
+}
+
 /// Convenient alias for aggregate dlog proof
 pub type Proof<C> = SigmaProof<Witness<C>>;
 
@@ -73,10 +98,12 @@ impl<C: Curve> SigmaProtocol for Dlog<C> {
         challenge: &Self::ProtocolChallenge,
         witness: &Self::ProverWitness,
     ) -> Option<Self::CommitMessage> {
-        let randomised_point = self
-            .coeff
-            .mul_by_scalar(&witness.witness)
-            .minus_point(&self.public.mul_by_scalar(challenge));
+        let mut minus_challenge = *challenge;
+        minus_challenge.negate();
+        let randomised_point = multiexp(&[self.coeff, self.public], &[
+            witness.witness,
+            minus_challenge,
+        ]);
         Some(randomised_point)
     }
 
@@ -97,6 +124,14 @@ impl<C: Curve> SigmaProtocol for Dlog<C> {
     }
 }
 
+impl<C: Curve> SimulatableSigmaProtocol for Dlog<C> {
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        Some(Witness {
+            witness: C::generate_scalar(csprng),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,4 +198,32 @@ mod tests {
             })
         }
     }
+
+    #[cfg(feature = "json-proofs")]
+    #[test]
+    pub fn test_dlog_json_roundtrip() {
+        let mut csprng = rand::thread_rng();
+        Dlog::with_valid_data(0, &mut csprng, |dlog: Dlog<G1>, secret, csprng| {
+            let mut ro = RandomOracle::empty();
+            let proof =
+                prove(&mut ro.split(), &dlog, secret, csprng).expect("Proving should succeed.");
+            // The serde instance is still the opaque hex string.
+            assert!(serde_json::to_value(&proof).map_or(false, |v| v.is_string()));
+            let json = proof
+                .serialize_structured(serde_json::value::Serializer)
+                .expect("Proofs can be serialized.");
+            assert!(
+                json["challenge"].is_string(),
+                "Challenge should be a hex string."
+            );
+            assert!(
+                json["witness"]["witness"].is_string(),
+                "Witness should be structured."
+            );
+            let parsed: Proof<G1> =
+                StructuredSerde::deserialize_structured(json).expect("Proofs can be parsed.");
+            assert_eq!(parsed, proof);
+            assert!(verify(&mut ro, &dlog, &parsed));
+        })
+    }
 }
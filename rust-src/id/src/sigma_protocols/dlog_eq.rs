@@ -0,0 +1,128 @@
+//! This module provides the implementation of the sigma protocol for equality
+//! of discrete logarithms. It enables one to prove knowledge of `x` such that
+//! `y_1 = g_1^x` and `y_2 = g_2^x`, without revealing `x`. The anonymity
+//! revokers use it to prove that they decrypted correctly, see
+//! [crate::anonymity_revoker::VerifiableDecryption].
+use crate::sigma_protocols::{
+    common::*,
+    dlog::{Dlog, DlogSecret, Witness},
+};
+use curve_arithmetic::Curve;
+#[cfg(test)]
+use curve_arithmetic::Value;
+use random_oracle::{Challenge, RandomOracle};
+
+pub struct DlogEqual<C: Curve> {
+    pub dlog1: Dlog<C>,
+    pub dlog2: Dlog<C>,
+}
+
+/// Convenient alias for the proof of equality of discrete logarithms.
+pub type Proof<C> = SigmaProof<Witness<C>>;
+
+impl<C: Curve> SigmaProtocol for DlogEqual<C> {
+    type CommitMessage = (C, C);
+    type ProtocolChallenge = C::Scalar;
+    type ProverState = C::Scalar;
+    type ProverWitness = Witness<C>;
+    type SecretData = DlogSecret<C>;
+
+    fn public(&self, ro: &mut RandomOracle) {
+        self.dlog1.public(ro);
+        self.dlog2.public(ro)
+    }
+
+    fn get_challenge(&self, challenge: &Challenge) -> Self::ProtocolChallenge {
+        C::scalar_from_bytes(challenge)
+    }
+
+    fn commit_point<R: rand::Rng>(
+        &self,
+        csprng: &mut R,
+    ) -> Option<(Self::CommitMessage, Self::ProverState)> {
+        let rand_scalar = C::generate_non_zero_scalar(csprng);
+        let randomised_point_1 = self.dlog1.coeff.mul_by_scalar(&rand_scalar);
+        let randomised_point_2 = self.dlog2.coeff.mul_by_scalar(&rand_scalar);
+        Some(((randomised_point_1, randomised_point_2), rand_scalar))
+    }
+
+    fn generate_witness(
+        &self,
+        secret: Self::SecretData,
+        state: Self::ProverState,
+        challenge: &Self::ProtocolChallenge,
+    ) -> Option<Self::ProverWitness> {
+        // Both discrete logarithms are the same, so the witness for the first
+        // one is also a witness for the second.
+        self.dlog1.generate_witness(secret, state, challenge)
+    }
+
+    fn extract_point(
+        &self,
+        challenge: &Self::ProtocolChallenge,
+        witness: &Self::ProverWitness,
+    ) -> Option<Self::CommitMessage> {
+        let p1 = self.dlog1.extract_point(challenge, witness)?;
+        let p2 = self.dlog2.extract_point(challenge, witness)?;
+        Some((p1, p2))
+    }
+
+    #[cfg(test)]
+    fn with_valid_data<R: rand::Rng>(
+        _data_size: usize,
+        csprng: &mut R,
+        f: impl FnOnce(DlogEqual<C>, Self::SecretData, &mut R) -> (),
+    ) {
+        let secret = Value::generate(csprng);
+        let coeff1 = C::generate(csprng);
+        let coeff2 = C::generate(csprng);
+        let dlog_equal = DlogEqual {
+            dlog1: Dlog {
+                public: coeff1.mul_by_scalar(&secret),
+                coeff:  coeff1,
+            },
+            dlog2: Dlog {
+                public: coeff2.mul_by_scalar(&secret),
+                coeff:  coeff2,
+            },
+        };
+        f(dlog_equal, DlogSecret { secret }, csprng);
+    }
+}
+
+impl<C: Curve> SimulatableSigmaProtocol for DlogEqual<C> {
+    fn random_witness<R: rand::Rng>(&self, csprng: &mut R) -> Option<Self::ProverWitness> {
+        // The response is a single scalar, as for the first logarithm.
+        self.dlog1.random_witness(csprng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+
+    #[test]
+    pub fn test_dlog_eq_correctness_and_soundness() {
+        let mut csprng = rand::thread_rng();
+        for _ in 0..100 {
+            DlogEqual::with_valid_data(0, &mut csprng, |dlog_eq: DlogEqual<G1>, secret, csprng| {
+                let challenge_prefix = generate_challenge_prefix(csprng);
+                let mut ro = RandomOracle::domain(&challenge_prefix);
+                let proof = prove(&mut ro.split(), &dlog_eq, secret, csprng)
+                    .expect("Proving should succeed.");
+                assert!(verify(&mut ro.split(), &dlog_eq, &proof));
+
+                // The proof does not verify if the second logarithm differs.
+                let wrong = DlogEqual {
+                    dlog1: dlog_eq.dlog1,
+                    dlog2: Dlog {
+                        public: G1::generate(csprng),
+                        coeff:  dlog_eq.dlog2.coeff,
+                    },
+                };
+                assert!(!verify(&mut ro, &wrong, &proof));
+            })
+        }
+    }
+}
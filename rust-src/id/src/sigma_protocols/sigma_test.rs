@@ -1,4 +1,7 @@
-use crate::sigma_protocols::{com_enc_eq, com_eq_sig, common::*, dlog};
+use crate::sigma_protocols::{com_enc_eq, com_eq_sig, common::*, dlog, dlog_eq};
+#[cfg(feature = "json-proofs")]
+use crypto_common::StructuredSerde;
+use curve_arithmetic::Curve;
 use pairing::bls12_381::{Bls12, G1, G2};
 use random_oracle::RandomOracle;
 
@@ -14,3 +17,150 @@ pub fn test_and() {
         assert!(verify(&mut RandomOracle::domain("test"), &prover, &proof))
     })
 }
+
+#[cfg(feature = "json-proofs")]
+#[test]
+pub fn test_and_json_roundtrip() {
+    let mut csprng = rand::thread_rng();
+    AndAdapter::<
+        AndAdapter<dlog::Dlog<G1>, com_eq_sig::ComEqSig<Bls12, G1>>,
+        com_enc_eq::ComEncEq<G2>,
+    >::with_valid_data(5, &mut csprng, |prover, secret, csprng| {
+        let proof = prove(&mut RandomOracle::domain("test"), &prover, secret, csprng)
+            .expect("Proving should succeed.");
+        // The serde instance is still the opaque hex string.
+        assert!(serde_json::to_value(&proof).map_or(false, |v| v.is_string()));
+        let json = proof
+            .serialize_structured(serde_json::value::Serializer)
+            .expect("Proofs can be serialized.");
+        // Each component of the witnesses is encoded on its own.
+        let com_eq_sig = &json["witness"]["w1"]["w2"];
+        assert!(com_eq_sig["witnessRho"].is_string());
+        let commit = com_eq_sig["witnessCommit"]
+            .as_array()
+            .expect("The witnesses of the commitments should be a list.");
+        assert!(commit
+            .iter()
+            .all(|pair| pair.as_array().map_or(false, |p| p.len() == 2)));
+        let com_enc_eq = json["witness"]["w2"]["witness"]
+            .as_array()
+            .expect("The witness should be a list of its components.");
+        assert_eq!(com_enc_eq.len(), 3);
+        let parsed: SigmaProof<_> =
+            StructuredSerde::deserialize_structured(json).expect("Proofs can be parsed.");
+        assert_eq!(
+            crypto_common::to_bytes(&parsed),
+            crypto_common::to_bytes(&proof)
+        );
+        assert!(verify(&mut RandomOracle::domain("test"), &prover, &parsed))
+    })
+}
+
+#[cfg(feature = "json-proofs")]
+#[test]
+pub fn test_replicate_json_roundtrip() {
+    let mut csprng = rand::thread_rng();
+    let mut protocols = Vec::new();
+    let mut secrets = Vec::new();
+    for _ in 0..3 {
+        com_enc_eq::ComEncEq::<G1>::with_valid_data(0, &mut csprng, |protocol, secret, _| {
+            protocols.push(protocol);
+            secrets.push(secret);
+        })
+    }
+    let prover = ReplicateAdapter { protocols };
+    let proof = prove(
+        &mut RandomOracle::domain("test"),
+        &prover,
+        secrets,
+        &mut csprng,
+    )
+    .expect("Proving should succeed.");
+    // The serde instance is still the opaque hex string.
+    assert!(serde_json::to_value(&proof).map_or(false, |v| v.is_string()));
+    let json = proof
+        .serialize_structured(serde_json::value::Serializer)
+        .expect("Proofs can be serialized.");
+    let witnesses = json["witness"]["witnesses"]
+        .as_array()
+        .expect("The witnesses should be a list.");
+    assert_eq!(witnesses.len(), 3);
+    assert!(witnesses
+        .iter()
+        .all(|w| w["witness"].as_array().map_or(false, |c| c.len() == 3)));
+    let parsed: SigmaProof<_> =
+        StructuredSerde::deserialize_structured(json).expect("Proofs can be parsed.");
+    assert_eq!(
+        crypto_common::to_bytes(&parsed),
+        crypto_common::to_bytes(&proof)
+    );
+    assert!(verify(&mut RandomOracle::domain("test"), &prover, &parsed))
+}
+
+#[test]
+pub fn test_or() {
+    let mut csprng = rand::thread_rng();
+    OrAdapter::<dlog::Dlog<G1>>::with_valid_data(0, &mut csprng, |prover, secret, csprng| {
+        let proof = prove(&mut RandomOracle::domain("test"), &prover, secret, csprng)
+            .expect("Proving should succeed.");
+        assert!(verify(&mut RandomOracle::domain("test"), &prover, &proof));
+        let proof =
+            crypto_common::serialize_deserialize(&proof).expect("Proof should deserialize.");
+        assert!(verify(&mut RandomOracle::domain("test"), &prover, &proof));
+
+        // The proof does not verify if one of the statements is changed.
+        let mut protocols = prover.protocols;
+        protocols[0].public = G1::generate(csprng);
+        let wrong = OrAdapter {
+            protocols,
+            known: prover.known,
+        };
+        assert!(!verify(&mut RandomOracle::domain("test"), &wrong, &proof));
+    })
+}
+
+#[test]
+pub fn test_or_each_known() {
+    let mut csprng = rand::thread_rng();
+    for known in 0..2 {
+        dlog::Dlog::<G1>::with_valid_data(0, &mut csprng, |first, s1, csprng| {
+            dlog::Dlog::<G1>::with_valid_data(0, csprng, |second, s2, csprng| {
+                let secret = if known == 0 { s1 } else { s2 };
+                let or = OrAdapter {
+                    protocols: vec![first, second],
+                    known,
+                };
+                let proof = prove(&mut RandomOracle::domain("test"), &or, secret, csprng)
+                    .expect("Proving should succeed.");
+                assert!(verify(&mut RandomOracle::domain("test"), &or, &proof));
+            })
+        })
+    }
+}
+
+#[test]
+pub fn test_or_wrong_secret() {
+    let mut csprng = rand::thread_rng();
+    OrAdapter::<dlog::Dlog<G1>>::with_valid_data(0, &mut csprng, |prover, secret, csprng| {
+        // The secret is for the second statement, so claiming to know the
+        // first fails.
+        let prover = OrAdapter { known: 0, ..prover };
+        let proof = prove(&mut RandomOracle::domain("test"), &prover, secret, csprng)
+            .expect("Proving should succeed.");
+        assert!(!verify(&mut RandomOracle::domain("test"), &prover, &proof));
+    })
+}
+
+#[test]
+pub fn test_and_in_or() {
+    let mut csprng = rand::thread_rng();
+    OrAdapter::<AndAdapter<dlog::Dlog<G1>, dlog_eq::DlogEqual<G1>>>::with_valid_data(
+        5,
+        &mut csprng,
+        |prover, secret, csprng| {
+            let proof = prove(&mut RandomOracle::domain("test"), &prover, secret, csprng)
+                .expect("Proving should succeed.");
+            assert!(verify(&mut RandomOracle::domain("test"), &prover, &proof))
+        },
+    )
+}
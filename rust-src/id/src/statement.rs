@@ -0,0 +1,607 @@
+//! Statements about the attributes of a credential, and a builder that checks
+//! that they are well-formed.
+//!
+//! A dApp asks the owner of a credential to prove a statement about the
+//! attributes of the credential, e.g., that the owner is at least 18 years old
+//! and a resident of one of a list of countries. A malformed statement, e.g.,
+//! one with an empty range, can never be proved, and the user would only find
+//! out after the wallet fails to produce the proof. The [StatementBuilder]
+//! instead rejects such statements when they are built, with errors that
+//! describe the problem, so that they can be shown to the developer of the
+//! dApp or the user.
+//!
+//! A statement is proved about a credential with
+//! [prove_statement](crate::id_prover::prove_statement), and the
+//! [StatementProof] is checked against the credential on the chain with
+//! [verify_statement](crate::id_verifier::verify_statement).
+use crate::{
+    sigma_protocols::{
+        aggregate_dlog,
+        common::{OrAdapter, OrWitness, SigmaProof},
+        dlog,
+    },
+    types::*,
+};
+use anyhow::bail;
+use bulletproofs::range_proof::RangeProof;
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::Curve;
+use pedersen_scheme::{Commitment, CommitmentKey as PedersenKey};
+use random_oracle::{domains, ChallengeContext, RandomOracle};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+};
+use thiserror::Error;
+
+/// A statement about a single attribute.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "AttributeType: SerdeSerialize",
+    deserialize = "AttributeType: SerdeDeserialize<'de>"
+))]
+#[serde(tag = "type")]
+pub enum AtomicStatement<AttributeType> {
+    /// The value of the attribute is revealed.
+    RevealAttribute {
+        #[serde(rename = "attributeTag")]
+        attribute_tag: AttributeTag,
+    },
+    /// The value of the attribute is in the range `[lower, upper)`.
+    AttributeInRange {
+        #[serde(rename = "attributeTag")]
+        attribute_tag: AttributeTag,
+        #[serde(rename = "lower")]
+        lower:         AttributeType,
+        #[serde(rename = "upper")]
+        upper:         AttributeType,
+    },
+    /// The value of the attribute is one of the given values.
+    AttributeInSet {
+        #[serde(rename = "attributeTag")]
+        attribute_tag: AttributeTag,
+        #[serde(rename = "set")]
+        set:           Vec<AttributeType>,
+    },
+    /// The value of the attribute is none of the given values.
+    AttributeNotInSet {
+        #[serde(rename = "attributeTag")]
+        attribute_tag: AttributeTag,
+        #[serde(rename = "set")]
+        set:           Vec<AttributeType>,
+    },
+}
+
+impl<AttributeType: Serial> Serial for AtomicStatement<AttributeType> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            AtomicStatement::RevealAttribute { attribute_tag } => {
+                0u8.serial(out);
+                attribute_tag.serial(out)
+            }
+            AtomicStatement::AttributeInRange {
+                attribute_tag,
+                lower,
+                upper,
+            } => {
+                1u8.serial(out);
+                attribute_tag.serial(out);
+                lower.serial(out);
+                upper.serial(out)
+            }
+            AtomicStatement::AttributeInSet { attribute_tag, set } => {
+                2u8.serial(out);
+                attribute_tag.serial(out);
+                set.serial(out)
+            }
+            AtomicStatement::AttributeNotInSet { attribute_tag, set } => {
+                3u8.serial(out);
+                attribute_tag.serial(out);
+                set.serial(out)
+            }
+        }
+    }
+}
+
+impl<AttributeType: Deserial> Deserial for AtomicStatement<AttributeType> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get()? {
+            0u8 => {
+                let attribute_tag = source.get()?;
+                Ok(AtomicStatement::RevealAttribute { attribute_tag })
+            }
+            1u8 => {
+                let attribute_tag = source.get()?;
+                let lower = source.get()?;
+                let upper = source.get()?;
+                Ok(AtomicStatement::AttributeInRange {
+                    attribute_tag,
+                    lower,
+                    upper,
+                })
+            }
+            2u8 => {
+                let attribute_tag = source.get()?;
+                let set = source.get()?;
+                Ok(AtomicStatement::AttributeInSet { attribute_tag, set })
+            }
+            3u8 => {
+                let attribute_tag = source.get()?;
+                let set = source.get()?;
+                Ok(AtomicStatement::AttributeNotInSet { attribute_tag, set })
+            }
+            n => bail!("AtomicStatement::deserial: Unsupported tag {}.", n),
+        }
+    }
+}
+
+impl<AttributeType> AtomicStatement<AttributeType> {
+    /// The attribute the statement is about.
+    pub fn attribute_tag(&self) -> AttributeTag {
+        match self {
+            AtomicStatement::RevealAttribute { attribute_tag } => *attribute_tag,
+            AtomicStatement::AttributeInRange { attribute_tag, .. } => *attribute_tag,
+            AtomicStatement::AttributeInSet { attribute_tag, .. } => *attribute_tag,
+            AtomicStatement::AttributeNotInSet { attribute_tag, .. } => *attribute_tag,
+        }
+    }
+}
+
+/// A well-formed statement, i.e., a list of statements about distinct,
+/// existing attributes, each of which can be satisfied. It can only be
+/// constructed with a [StatementBuilder].
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize)]
+#[serde(bound(serialize = "AttributeType: SerdeSerialize"))]
+#[serde(transparent)]
+pub struct Statement<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    statements: Vec<AtomicStatement<AttributeType>>,
+    #[serde(skip)]
+    _phantom:   PhantomData<C>,
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial for Statement<C, AttributeType> {
+    fn serial<B: Buffer>(&self, out: &mut B) { self.statements.serial(out) }
+}
+
+/// Deserialization checks that the statement is well-formed, as
+/// [StatementBuilder::build] does.
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Deserial for Statement<C, AttributeType> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let statements: Vec<AtomicStatement<AttributeType>> = source.get()?;
+        let builder = statements
+            .into_iter()
+            .fold(StatementBuilder::new(), StatementBuilder::add);
+        Ok(builder.build()?)
+    }
+}
+
+/// Parsing from JSON checks that the statement is well-formed, as
+/// [StatementBuilder::build] does.
+impl<'de, C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>>
+    SerdeDeserialize<'de> for Statement<C, AttributeType>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        let statements = Vec::<AtomicStatement<AttributeType>>::deserialize(des)?;
+        statements
+            .into_iter()
+            .fold(StatementBuilder::new(), StatementBuilder::add)
+            .build()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The name of an attribute for error messages. Unknown tags are shown by
+/// their number.
+fn tag_name(tag: &AttributeTag) -> String {
+    if usize::from(tag.0) < ATTRIBUTE_NAMES.len() {
+        tag.to_string()
+    } else {
+        format!("with tag {}", tag.0)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+/// Reasons why a statement is not well-formed, or is not satisfied by the
+/// attributes of a credential.
+pub enum StatementError {
+    #[error("The statement is empty.")]
+    Empty,
+    #[error("Attribute {} does not exist.", tag_name(.0))]
+    UnknownAttribute(AttributeTag),
+    #[error("The range of attribute {} is empty, since the lower bound is not below the upper bound.", tag_name(.0))]
+    EmptyRange(AttributeTag),
+    #[error("The set of values of attribute {} is empty.", tag_name(.0))]
+    EmptySet(AttributeTag),
+    #[error("There are several statements about attribute {}. Combine them into one.", tag_name(.0))]
+    ConflictingStatements(AttributeTag),
+    #[error("The credential does not have attribute {}.", tag_name(.0))]
+    MissingAttribute(AttributeTag),
+    #[error("The value of attribute {} does not satisfy the statement.", tag_name(.0))]
+    NotSatisfied(AttributeTag),
+    #[error("Could not produce the proof about attribute {}.", tag_name(.0))]
+    ProofFailed(AttributeTag),
+}
+
+/// Attributes are ordered as the field elements they are encoded as, since
+/// that is the order the range proofs are about. The scalars are serialized
+/// in big-endian, so comparing the serializations compares the numbers.
+fn attribute_key<C: Curve, AttributeType: Attribute<C::Scalar>>(a: &AttributeType) -> Vec<u8> {
+    to_bytes(&a.to_field_element())
+}
+
+/// A builder of statements. The statements about individual attributes are
+/// added with the methods of the builder, and checked together by
+/// [StatementBuilder::build].
+#[derive(Debug, Clone)]
+pub struct StatementBuilder<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    statements: Vec<AtomicStatement<AttributeType>>,
+    _phantom:   PhantomData<C>,
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Default for StatementBuilder<C, AttributeType> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> StatementBuilder<C, AttributeType> {
+    /// A builder without any statements.
+    pub fn new() -> Self {
+        StatementBuilder {
+            statements: Vec::new(),
+            _phantom:   PhantomData,
+        }
+    }
+
+    /// Reveal the value of the attribute.
+    pub fn reveal(mut self, attribute_tag: AttributeTag) -> Self {
+        self.statements
+            .push(AtomicStatement::RevealAttribute { attribute_tag });
+        self
+    }
+
+    /// The value of the attribute is at least `lower` and less than `upper`.
+    pub fn in_range(
+        mut self,
+        attribute_tag: AttributeTag,
+        lower: AttributeType,
+        upper: AttributeType,
+    ) -> Self {
+        self.statements.push(AtomicStatement::AttributeInRange {
+            attribute_tag,
+            lower,
+            upper,
+        });
+        self
+    }
+
+    /// The value of the attribute is one of the given values.
+    pub fn in_set(mut self, attribute_tag: AttributeTag, set: Vec<AttributeType>) -> Self {
+        self.statements
+            .push(AtomicStatement::AttributeInSet { attribute_tag, set });
+        self
+    }
+
+    /// The value of the attribute is none of the given values.
+    pub fn not_in_set(mut self, attribute_tag: AttributeTag, set: Vec<AttributeType>) -> Self {
+        self.statements
+            .push(AtomicStatement::AttributeNotInSet { attribute_tag, set });
+        self
+    }
+
+    /// Add a statement about an attribute, e.g., one that was parsed from
+    /// JSON.
+    pub fn add(mut self, statement: AtomicStatement<AttributeType>) -> Self {
+        self.statements.push(statement);
+        self
+    }
+
+    /// Check that the statement is well-formed. The statement must not be
+    /// empty, every attribute must exist and be the subject of at most one
+    /// statement, ranges must be non-empty, and the set of an
+    /// [AtomicStatement::AttributeInSet] must be non-empty. The first problem
+    /// found is returned.
+    pub fn build(self) -> Result<Statement<C, AttributeType>, StatementError> {
+        if self.statements.is_empty() {
+            return Err(StatementError::Empty);
+        }
+        let mut seen = BTreeSet::new();
+        for statement in self.statements.iter() {
+            let tag = statement.attribute_tag();
+            if usize::from(tag.0) >= ATTRIBUTE_NAMES.len() {
+                return Err(StatementError::UnknownAttribute(tag));
+            }
+            if !seen.insert(tag) {
+                return Err(StatementError::ConflictingStatements(tag));
+            }
+            match statement {
+                AtomicStatement::RevealAttribute { .. } => {}
+                AtomicStatement::AttributeInRange { lower, upper, .. } => {
+                    if attribute_key::<C, _>(lower) >= attribute_key::<C, _>(upper) {
+                        return Err(StatementError::EmptyRange(tag));
+                    }
+                }
+                AtomicStatement::AttributeInSet { set, .. } => {
+                    if set.is_empty() {
+                        return Err(StatementError::EmptySet(tag));
+                    }
+                }
+                AtomicStatement::AttributeNotInSet { .. } => {}
+            }
+        }
+        Ok(Statement {
+            statements: self.statements,
+            _phantom:   PhantomData,
+        })
+    }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Statement<C, AttributeType> {
+    /// The statements about the individual attributes.
+    pub fn statements(&self) -> &[AtomicStatement<AttributeType>] { &self.statements }
+
+    /// Check that the attributes of a credential satisfy the statement, so
+    /// that the wallet can tell the user before trying to produce a proof.
+    pub fn check_attributes(
+        &self,
+        attributes: &BTreeMap<AttributeTag, AttributeType>,
+    ) -> Result<(), StatementError> {
+        for statement in self.statements.iter() {
+            let tag = statement.attribute_tag();
+            match attributes.get(&tag) {
+                Some(value) if is_satisfied::<C, _>(statement, value) => {}
+                Some(_) => return Err(StatementError::NotSatisfied(tag)),
+                None => return Err(StatementError::MissingAttribute(tag)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the value of the attribute satisfies the statement about it.
+pub(crate) fn is_satisfied<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    statement: &AtomicStatement<AttributeType>,
+    value: &AttributeType,
+) -> bool {
+    let value = attribute_key::<C, _>(value);
+    match statement {
+        AtomicStatement::RevealAttribute { .. } => true,
+        AtomicStatement::AttributeInRange { lower, upper, .. } => {
+            attribute_key::<C, _>(lower) <= value && value < attribute_key::<C, _>(upper)
+        }
+        AtomicStatement::AttributeInSet { set, .. } => {
+            set.iter().any(|x| attribute_key::<C, _>(x) == value)
+        }
+        AtomicStatement::AttributeNotInSet { set, .. } => {
+            set.iter().all(|x| attribute_key::<C, _>(x) != value)
+        }
+    }
+}
+
+/// The transcript of the proof of a statement. The proofs of the individual
+/// statements are added to it in order, each preceded by the tag of its
+/// attribute, and the commitment to the attribute unless it is revealed in
+/// the policy.
+pub(crate) fn statement_transcript(context: &ChallengeContext, challenge: &[u8]) -> RandomOracle {
+    let mut ro = RandomOracle::domain_in_context(context, domains::ATTRIBUTE_STATEMENT_PROOF);
+    ro.append_message("challenge", &challenge.to_vec());
+    ro
+}
+
+/// The protocol proving that a commitment is to one of the values of the set,
+/// i.e., that the commitment divided by `g^v` is a power of `h` for one of the
+/// values `v`. The prover knows the exponent for the value with index `known`.
+pub(crate) fn set_membership_protocol<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    keys: &PedersenKey<C>,
+    commitment: &Commitment<C>,
+    set: &[AttributeType],
+    known: usize,
+) -> OrAdapter<dlog::Dlog<C>> {
+    let protocols = set
+        .iter()
+        .map(|v| dlog::Dlog {
+            public: commitment.minus_point(&keys.g.mul_by_scalar(&v.to_field_element())),
+            coeff:  keys.h,
+        })
+        .collect();
+    OrAdapter { protocols, known }
+}
+
+/// The protocol proving that a commitment `C = g^a h^r` is not to the value
+/// `v`. With `D = C g^(-v)` it proves knowledge of a representation
+/// `g = D^x h^y`, for which the prover uses `x = 1/(a - v)` and `y = -r x`.
+/// If `a = v` then `D` is a power of `h`, and such a representation would give
+/// the discrete logarithm of `g` with respect to `h`.
+pub(crate) fn non_membership_protocol<C: Curve, AttributeType: Attribute<C::Scalar>>(
+    keys: &PedersenKey<C>,
+    commitment: &Commitment<C>,
+    value: &AttributeType,
+) -> aggregate_dlog::AggregateDlog<C> {
+    aggregate_dlog::AggregateDlog {
+        public: keys.g,
+        coeff:  vec![
+            commitment.minus_point(&keys.g.mul_by_scalar(&value.to_field_element())),
+            keys.h,
+        ],
+    }
+}
+
+/// The proof of an [AtomicStatement] about an attribute of a credential.
+#[derive(Debug, Clone)]
+pub enum AtomicProof<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// The attribute is revealed in the policy of the credential, so the
+    /// verifier checks the statement against the value on the chain.
+    InPolicy,
+    /// The value of the attribute, with a proof of knowledge of the randomness
+    /// of the commitment to the attribute, which shows that the commitment is
+    /// to this value.
+    RevealAttribute {
+        attribute: AttributeType,
+        proof:     dlog::Proof<C>,
+    },
+    /// A proof that the value of the commitment to the attribute is in the
+    /// range of the statement.
+    AttributeInRange { proof: RangeProof<C> },
+    /// A proof that the value of the commitment to the attribute is one of
+    /// the values of the set. The size of the proof is linear in the size of
+    /// the set.
+    AttributeInSet {
+        proof: SigmaProof<OrWitness<dlog::Witness<C>>>,
+    },
+    /// Proofs that the value of the commitment to the attribute differs from
+    /// each of the values of the set, in the order of the set.
+    AttributeNotInSet {
+        proofs: Vec<aggregate_dlog::Proof<C>>,
+    },
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial for AtomicProof<C, AttributeType> {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            AtomicProof::InPolicy => 0u8.serial(out),
+            AtomicProof::RevealAttribute { attribute, proof } => {
+                1u8.serial(out);
+                attribute.serial(out);
+                proof.serial(out)
+            }
+            AtomicProof::AttributeInRange { proof } => {
+                2u8.serial(out);
+                proof.serial(out)
+            }
+            AtomicProof::AttributeInSet { proof } => {
+                3u8.serial(out);
+                proof.serial(out)
+            }
+            AtomicProof::AttributeNotInSet { proofs } => {
+                4u8.serial(out);
+                proofs.serial(out)
+            }
+        }
+    }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Deserial for AtomicProof<C, AttributeType> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get()? {
+            0u8 => Ok(AtomicProof::InPolicy),
+            1u8 => {
+                let attribute = source.get()?;
+                let proof = source.get()?;
+                Ok(AtomicProof::RevealAttribute { attribute, proof })
+            }
+            2u8 => {
+                let proof = source.get()?;
+                Ok(AtomicProof::AttributeInRange { proof })
+            }
+            3u8 => {
+                let proof = source.get()?;
+                Ok(AtomicProof::AttributeInSet { proof })
+            }
+            4u8 => {
+                let proofs = source.get()?;
+                Ok(AtomicProof::AttributeNotInSet { proofs })
+            }
+            n => bail!("AtomicProof::deserial: Unsupported tag {}.", n),
+        }
+    }
+}
+
+/// The proof of a [Statement], with the proofs of the individual statements in
+/// the same order. It is produced with
+/// [prove_statement](crate::id_prover::prove_statement) and checked with
+/// [verify_statement](crate::id_verifier::verify_statement).
+#[derive(Debug, Clone, Serialize, SerdeBase16Serialize)]
+pub struct StatementProof<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    pub proofs: Vec<AtomicProof<C, AttributeType>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{ArCurve, AttributeKind};
+    use std::str::FromStr;
+
+    type Builder = StatementBuilder<ArCurve, AttributeKind>;
+
+    fn tag(name: &str) -> AttributeTag { AttributeTag::from_str(name).unwrap() }
+
+    fn kind(s: &str) -> AttributeKind { AttributeKind(s.to_owned()) }
+
+    #[test]
+    fn test_statement_builder() {
+        let statement = Builder::new()
+            .reveal(tag("firstName"))
+            .in_range(tag("dob"), kind("19000101"), kind("20030101"))
+            .in_set(tag("countryOfResidence"), vec![kind("DK"), kind("DE")])
+            .build()
+            .expect("Statement should be well-formed.");
+        let mut attributes = BTreeMap::new();
+        attributes.insert(tag("firstName"), kind("John"));
+        attributes.insert(tag("dob"), kind("19800229"));
+        attributes.insert(tag("countryOfResidence"), kind("DK"));
+        assert_eq!(statement.check_attributes(&attributes), Ok(()));
+        attributes.insert(tag("dob"), kind("20050101"));
+        assert_eq!(
+            statement.check_attributes(&attributes),
+            Err(StatementError::NotSatisfied(tag("dob")))
+        );
+        attributes.remove(&tag("firstName"));
+        assert_eq!(
+            statement.check_attributes(&attributes),
+            Err(StatementError::MissingAttribute(tag("firstName")))
+        );
+
+        assert_eq!(Builder::new().build(), Err(StatementError::Empty));
+        assert_eq!(
+            Builder::new().reveal(AttributeTag(200)).build(),
+            Err(StatementError::UnknownAttribute(AttributeTag(200)))
+        );
+        assert_eq!(
+            Builder::new()
+                .in_range(tag("dob"), kind("20030101"), kind("19000101"))
+                .build(),
+            Err(StatementError::EmptyRange(tag("dob")))
+        );
+        assert_eq!(
+            Builder::new()
+                .in_set(tag("countryOfResidence"), Vec::new())
+                .build(),
+            Err(StatementError::EmptySet(tag("countryOfResidence")))
+        );
+        let err = Builder::new()
+            .reveal(tag("dob"))
+            .in_range(tag("dob"), kind("19000101"), kind("20030101"))
+            .build()
+            .unwrap_err();
+        assert_eq!(err, StatementError::ConflictingStatements(tag("dob")));
+        assert_eq!(
+            err.to_string(),
+            "There are several statements about attribute dob. Combine them into one."
+        );
+    }
+
+    #[test]
+    fn test_statement_serialization() {
+        let statement = Builder::new()
+            .reveal(tag("firstName"))
+            .in_range(tag("dob"), kind("19000101"), kind("20030101"))
+            .in_set(tag("countryOfResidence"), vec![kind("DK"), kind("DE")])
+            .not_in_set(tag("nationality"), vec![kind("US")])
+            .build()
+            .expect("Statement should be well-formed.");
+        let parsed = serialize_deserialize(&statement).expect("Statement should deserialize.");
+        assert_eq!(parsed, statement);
+        let json = serde_json::to_value(&statement).expect("Statement should serialize.");
+        let parsed: Statement<ArCurve, AttributeKind> =
+            serde_json::from_value(json).expect("Statement should parse.");
+        assert_eq!(parsed, statement);
+
+        // Statements that are not well-formed are rejected.
+        let empty: Vec<AtomicStatement<AttributeKind>> = Vec::new();
+        let json = serde_json::to_value(&empty).expect("Statements should serialize.");
+        assert!(serde_json::from_value::<Statement<ArCurve, AttributeKind>>(json).is_err());
+        let bytes = to_bytes(&empty);
+        let parsed: ParseResult<Statement<ArCurve, AttributeKind>> =
+            (&mut std::io::Cursor::new(&bytes)).get();
+        assert!(parsed.is_err());
+    }
+}
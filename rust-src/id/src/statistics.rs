@@ -0,0 +1,171 @@
+//! Aggregate statistics about issued identities.
+//!
+//! Identity providers are required to report how many identities they have
+//! issued with certain properties, e.g., per country of residence, but they
+//! should not have to keep the attribute values themselves for that. The
+//! types in this module allow the identity provider to map each attribute to a
+//! coarse bucket at issuance time and only keep a count per bucket.
+//!
+//! In addition to the plain count each bucket keeps a Pedersen commitment to
+//! the count. The commitments can be published when the statistics are
+//! collected, and the count together with the randomness later revealed to an
+//! auditor, who can then check that the reported numbers were not changed
+//! after the fact.
+use crate::types::*;
+use curve_arithmetic::{Curve, Value};
+use ff::Field;
+use pedersen_scheme::{Commitment, CommitmentKey, Randomness};
+use rand::*;
+use std::collections::BTreeMap;
+
+/// A label of a bucket of attribute values, e.g., an age range.
+pub type Bucket = String;
+
+/// The count of identities in a single bucket, together with a commitment to
+/// the count and the randomness needed to open it.
+#[derive(Debug, Clone)]
+pub struct BucketCount<C: Curve> {
+    pub count:      u64,
+    pub commitment: Commitment<C>,
+    pub randomness: Randomness<C>,
+}
+
+/// Counts of identities per attribute and bucket.
+pub struct AttributeStatistics<C: Curve> {
+    commitment_key: CommitmentKey<C>,
+    buckets:        BTreeMap<(AttributeTag, Bucket), BucketCount<C>>,
+}
+
+impl<C: Curve> AttributeStatistics<C> {
+    /// Construct empty statistics. The commitment key is used for committing
+    /// to the counts.
+    pub fn new(commitment_key: CommitmentKey<C>) -> Self {
+        AttributeStatistics {
+            commitment_key,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Record the attributes of a newly issued identity. The given function
+    /// determines the bucket of each attribute, and attributes for which it
+    /// returns `None` are not counted. The attribute values themselves are not
+    /// retained.
+    pub fn record<AttributeType: Attribute<C::Scalar>, R: Rng>(
+        &mut self,
+        alist: &AttributeList<C::Scalar, AttributeType>,
+        bucket_of: impl Fn(AttributeTag, &AttributeType) -> Option<Bucket>,
+        csprng: &mut R,
+    ) {
+        for (tag, value) in alist.alist.iter() {
+            if let Some(bucket) = bucket_of(*tag, value) {
+                let (cmm, r) = self.commitment_key.commit(&Value::<C>::from(1u64), csprng);
+                let entry = self
+                    .buckets
+                    .entry((*tag, bucket))
+                    .or_insert_with(|| BucketCount {
+                        count:      0,
+                        commitment: Commitment(C::zero_point()),
+                        randomness: Randomness::zero(),
+                    });
+                let mut randomness = *entry.randomness;
+                randomness.add_assign(&r);
+                entry.count += 1;
+                entry.commitment = entry.commitment.combine(&cmm);
+                entry.randomness = Randomness::new(randomness);
+            }
+        }
+    }
+
+    /// Get the count of the given bucket, if there are any identities in it.
+    pub fn get(&self, tag: AttributeTag, bucket: &str) -> Option<&BucketCount<C>> {
+        self.buckets.get(&(tag, bucket.into()))
+    }
+
+    /// Get the counts of all buckets that contain at least `min_count`
+    /// identities. Buckets with fewer identities are left out, since
+    /// reporting them could make it possible to single out individual users.
+    pub fn counts(&self, min_count: u64) -> BTreeMap<(AttributeTag, Bucket), u64> {
+        self.buckets
+            .iter()
+            .filter(|(_, v)| v.count >= min_count)
+            .map(|(k, v)| (k.clone(), v.count))
+            .collect()
+    }
+
+    /// Get the commitments to the counts of all buckets.
+    pub fn commitments(&self) -> BTreeMap<(AttributeTag, Bucket), Commitment<C>> {
+        self.buckets
+            .iter()
+            .map(|(k, v)| (k.clone(), v.commitment))
+            .collect()
+    }
+}
+
+/// Check that the commitment to a bucket count opens to the given count.
+pub fn verify_bucket_count<C: Curve>(
+    commitment_key: &CommitmentKey<C>,
+    commitment: &Commitment<C>,
+    count: u64,
+    randomness: &Randomness<C>,
+) -> bool {
+    commitment_key.open(&Value::from(count), randomness, commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use pairing::bls12_381::G1;
+    use std::convert::TryFrom;
+
+    fn make_alist(country: &str, age: u64) -> AttributeList<BaseField, AttributeKind> {
+        let mut alist = BTreeMap::new();
+        alist.insert(AttributeTag::from(4u8), AttributeKind(country.into()));
+        alist.insert(AttributeTag::from(3u8), AttributeKind::from(age));
+        AttributeList {
+            valid_to: YearMonth::try_from(2022 << 8 | 5).unwrap(),
+            created_at: YearMonth::try_from(2020 << 8 | 5).unwrap(),
+            max_accounts: 200,
+            alist,
+            _phantom: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_attribute_statistics() {
+        let mut csprng = thread_rng();
+        let key = CommitmentKey::<G1>::generate(&mut csprng);
+        let mut stats = AttributeStatistics::new(key);
+        // Only count the country, which is attribute 4.
+        let bucket_of = |tag: AttributeTag, value: &AttributeKind| {
+            if tag == AttributeTag::from(4u8) {
+                Some(value.to_string())
+            } else {
+                None
+            }
+        };
+        for (country, age) in &[("DK", 30), ("DK", 40), ("DE", 50)] {
+            stats.record(&make_alist(country, *age), bucket_of, &mut csprng);
+        }
+        let counts = stats.counts(0);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&(AttributeTag::from(4u8), "DK".into())], 2);
+        assert_eq!(counts[&(AttributeTag::from(4u8), "DE".into())], 1);
+        assert_eq!(
+            stats.counts(2).len(),
+            1,
+            "Small buckets should be suppressed."
+        );
+
+        let dk = stats
+            .get(AttributeTag::from(4u8), "DK")
+            .expect("Bucket should exist.");
+        assert!(verify_bucket_count(&key, &dk.commitment, 2, &dk.randomness));
+        assert!(!verify_bucket_count(
+            &key,
+            &dk.commitment,
+            3,
+            &dk.randomness
+        ));
+    }
+}
@@ -17,7 +17,12 @@ use ed25519_dalek as ed25519;
 use either::Either::Left;
 use elgamal::{PublicKey, SecretKey};
 use rand::*;
-use std::{collections::BTreeMap, convert::TryFrom};
+use random_oracle::ChallengeContext;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
 
 type ExampleAttribute = AttributeKind;
 
@@ -27,6 +32,104 @@ pub const EXPIRY: TransactionTime = TransactionTime {
     seconds: 111111111111111111,
 };
 
+/// Environment variable which, when set, makes [assert_golden_serialization]
+/// record the golden files instead of checking against them.
+pub const UPDATE_GOLDEN_FILES_VAR: &str = "UPDATE_GOLDEN_FILES";
+
+/// Get the path of a file given relative to the root of the repository, e.g.,
+/// `testdata/cdi.bin`. Golden files are committed to the repository and are
+/// shared with the Haskell tests, so they live outside of the Rust crates.
+pub fn repository_file(path: impl AsRef<Path>) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path)
+}
+
+/// Read a golden file, given relative to the root of the repository.
+pub fn read_golden_bytes(path: impl AsRef<Path>) -> Vec<u8> {
+    let path = repository_file(path);
+    std::fs::read(&path)
+        .unwrap_or_else(|e| panic!("Could not read golden file {}: {}", path.display(), e))
+}
+
+/// Read and parse a JSON golden file, given relative to the root of the
+/// repository.
+pub fn read_golden_json<T: serde::de::DeserializeOwned>(path: impl AsRef<Path>) -> T {
+    let bytes = read_golden_bytes(path.as_ref());
+    serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        panic!(
+            "Could not parse golden file {}: {}",
+            path.as_ref().display(),
+            e
+        )
+    })
+}
+
+/// Read and parse a JSON golden file, and check that serializing the parsed
+/// value to JSON gives back the contents of the file, up to formatting. The
+/// parsed value is returned so that its binary serialization can be checked
+/// with [assert_golden_serialization].
+pub fn assert_golden_json<T: SerdeSerialize + serde::de::DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> T {
+    let value: T = read_golden_json(path.as_ref());
+    let expected: serde_json::Value = read_golden_json(path.as_ref());
+    let actual = serde_json::to_value(&value).expect("JSON serialization should succeed.");
+    assert_eq!(
+        actual,
+        expected,
+        "JSON serialization differs from golden file {}.",
+        path.as_ref().display()
+    );
+    value
+}
+
+/// Check that the binary serialization of the value is exactly the contents
+/// of the given golden file, and that the contents of the file deserialize to
+/// a value with the same serialization.
+///
+/// If the environment variable [UPDATE_GOLDEN_FILES_VAR] is set the golden
+/// file is instead overwritten with the current serialization. This should
+/// only be done when the change of format is intentional, since the
+/// serialization must match the one in Haskell.
+pub fn assert_golden_serialization<T: Serial + Deserial>(path: impl AsRef<Path>, value: &T) {
+    let path = repository_file(path);
+    let actual = to_bytes(value);
+    if std::env::var_os(UPDATE_GOLDEN_FILES_VAR).is_some() {
+        std::fs::write(&path, &actual)
+            .unwrap_or_else(|e| panic!("Could not write golden file {}: {}", path.display(), e));
+        return;
+    }
+    let expected = std::fs::read(&path)
+        .unwrap_or_else(|e| panic!("Could not read golden file {}: {}", path.display(), e));
+    if let Some(pos) = actual.iter().zip(expected.iter()).position(|(a, e)| a != e) {
+        panic!(
+            "Serialization differs from golden file {} at byte {}.",
+            path.display(),
+            pos
+        );
+    }
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "Serialization differs in length from golden file {}.",
+        path.display()
+    );
+    let parsed: T = from_bytes(&mut std::io::Cursor::new(&expected)).unwrap_or_else(|e| {
+        panic!(
+            "Could not deserialize golden file {}: {}",
+            path.display(),
+            e
+        )
+    });
+    assert_eq!(
+        to_bytes(&parsed),
+        expected,
+        "Deserialization of golden file {} does not round trip.",
+        path.display()
+    );
+}
+
 /// Create #num_ars anonymity revokers to be used by test
 pub fn test_create_ars<T: Rng>(
     ar_base: &ArCurve,
@@ -102,6 +205,17 @@ pub fn test_create_aci<T: Rng>(csprng: &mut T) -> AccCredentialInfo<ArCurve> {
     }
 }
 
+/// Create #num_keys fresh key pairs with indices 0, 1, ... to be used as the
+/// keys of an account in tests.
+pub fn test_create_keys<T: Rng + rand_core::CryptoRng>(
+    csprng: &mut T,
+    num_keys: u8,
+) -> BTreeMap<KeyIndex, KeyPair> {
+    (0..num_keys)
+        .map(|i| (KeyIndex(i), KeyPair::generate(csprng)))
+        .collect()
+}
+
 /// Create PreIdentityObject for an account holder to be used by tests,
 /// with the anonymity revocation using all the given ars_infos.
 pub fn test_create_pio<'a>(
@@ -123,8 +237,14 @@ pub fn test_create_pio<'a>(
     let threshold = Threshold::try_from(num_ars - 1).unwrap_or(Threshold(1));
 
     // Create and return PIO
-    let (pio, randomness) = generate_pio(&context, threshold, &aci, initial_account_data)
-        .expect("Generating the pre-identity object should succeed.");
+    let (pio, randomness) = generate_pio(
+        &ChallengeContext::Legacy,
+        &context,
+        threshold,
+        &aci,
+        initial_account_data,
+    )
+    .expect("Generating the pre-identity object should succeed.");
     (context, pio, randomness)
 }
 
@@ -146,6 +266,95 @@ pub fn test_create_attributes() -> ExampleAttributeList {
     }
 }
 
+/// An identity object issued by a test identity provider, together with the
+/// data needed to create credentials from it. See [test_create_identity].
+pub struct TestIdentity {
+    pub ip_info:     IpInfo<IpPairing>,
+    pub ars_infos:   BTreeMap<ArIdentity, ArInfo<ArCurve>>,
+    pub global_ctx:  GlobalContext<ArCurve>,
+    pub id_object:   IdentityObject<IpPairing, ArCurve, ExampleAttribute>,
+    pub id_use_data: IdObjectUseData<IpPairing, ArCurve>,
+    /// The initial credential that the identity provider creates together
+    /// with the identity object.
+    pub icdi:        InitialCredentialDeploymentInfo<ArCurve, ExampleAttribute>,
+}
+
+/// Create an identity object with the attributes of
+/// [test_create_attributes], issued by an identity provider with #num_ars
+/// anonymity revokers, and the initial credential of its account with the
+/// given keys, expiring at [EXPIRY].
+pub fn test_create_identity<T: Rng + rand_core::CryptoRng>(
+    csprng: &mut T,
+    num_ars: u8,
+    max_attrs: u8,
+    initial_account_data: &InitialAccountData,
+) -> TestIdentity {
+    let IpData {
+        public_ip_info: ip_info,
+        ip_secret_key,
+        ip_cdi_secret_key,
+    } = test_create_ip_info(csprng, num_ars, max_attrs);
+    let global_ctx = GlobalContext::generate(String::from("genesis_string"));
+    let (ars_infos, _) = test_create_ars(&global_ctx.on_chain_commitment_key.g, num_ars, csprng);
+    let aci = test_create_aci(csprng);
+    let (context, pio, randomness) = test_create_pio(
+        &aci,
+        &ip_info,
+        &ars_infos,
+        &global_ctx,
+        num_ars,
+        initial_account_data,
+    );
+    let alist = test_create_attributes();
+    let (signature, icdi) = verify_credentials(
+        &pio,
+        context,
+        &alist,
+        EXPIRY,
+        &ip_secret_key,
+        &ip_cdi_secret_key,
+    )
+    .expect("Credentials should be valid.");
+    TestIdentity {
+        ip_info,
+        ars_infos,
+        global_ctx,
+        id_object: IdentityObject {
+            pre_identity_object: pio,
+            alist,
+            signature,
+        },
+        id_use_data: IdObjectUseData { aci, randomness },
+        icdi,
+    }
+}
+
+impl TestIdentity {
+    /// Create the credential with the given index of a new account that
+    /// expires at [EXPIRY], with proofs in the given challenge context.
+    pub fn create_credential(
+        &self,
+        challenge_context: &ChallengeContext,
+        cred_counter: u8,
+        policy: Policy<ArCurve, ExampleAttribute>,
+        cred_data: &CredentialData,
+    ) -> CredentialDeploymentInfo<IpPairing, ArCurve, ExampleAttribute> {
+        let context = IpContext::new(&self.ip_info, &self.ars_infos, &self.global_ctx);
+        let (cdi, _) = create_credential(
+            challenge_context,
+            context,
+            &self.id_object,
+            &self.id_use_data,
+            cred_counter,
+            policy,
+            cred_data,
+            &Left(EXPIRY),
+        )
+        .expect("Should generate the credential successfully.");
+        cdi
+    }
+}
+
 pub fn test_pipeline() {
     let mut csprng = thread_rng();
 
@@ -165,13 +374,7 @@ pub fn test_pipeline() {
 
     let aci = test_create_aci(&mut csprng);
     let acc_data = InitialAccountData {
-        keys:      {
-            let mut keys = BTreeMap::new();
-            keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-            keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-            keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-            keys
-        },
+        keys:      test_create_keys(&mut csprng, 3),
         threshold: SignatureThreshold(2),
     };
     let (context, pio, randomness) =
@@ -223,16 +426,11 @@ pub fn test_pipeline() {
         _phantom: Default::default(),
     };
     let acc_data = CredentialData {
-        keys:      {
-            let mut keys = BTreeMap::new();
-            keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
-            keys.insert(KeyIndex(1), KeyPair::generate(&mut csprng));
-            keys.insert(KeyIndex(2), KeyPair::generate(&mut csprng));
-            keys
-        },
+        keys:      test_create_keys(&mut csprng, 3),
         threshold: SignatureThreshold(2),
     };
     let (cdi, _) = create_credential(
+        &ChallengeContext::Legacy,
         context,
         &id_object,
         &id_use_data,
@@ -242,7 +440,14 @@ pub fn test_pipeline() {
         &Left(EXPIRY),
     )
     .expect("Should generate the credential successfully.");
-    let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &cdi, &Left(EXPIRY));
+    let cdi_check = verify_cdi(
+        &ChallengeContext::Legacy,
+        &global_ctx,
+        &ip_info,
+        &ars_infos,
+        &cdi,
+        &Left(EXPIRY),
+    );
     assert_eq!(cdi_check, Ok(()));
 
     // Verify serialization
@@ -300,6 +505,7 @@ pub fn test_pipeline() {
     // two anonymity revokers. Verification of this credential should fail the
     // signature at the very least.
     let (mut cdi, _) = create_credential(
+        &ChallengeContext::Legacy,
         context,
         &id_object,
         &id_use_data,
@@ -331,7 +537,14 @@ pub fn test_pipeline() {
         .get_mut(&ArIdentity::new(3))
         .expect("AR 2 exists") = x_2;
     // Verification should now fail.
-    let cdi_check = verify_cdi(&global_ctx, &ip_info, &ars_infos, &cdi, &Left(EXPIRY));
+    let cdi_check = verify_cdi(
+        &ChallengeContext::Legacy,
+        &global_ctx,
+        &ip_info,
+        &ars_infos,
+        &cdi,
+        &Left(EXPIRY),
+    );
     assert_ne!(cdi_check, Ok(()));
 }
 
@@ -0,0 +1,247 @@
+//! Threshold decryption of ElGamal ciphertexts.
+//!
+//! The secret key of an anonymity revoker can be shared among several parties
+//! with Shamir secret sharing, so that any `threshold` of them can decrypt
+//! together, but fewer learn nothing about the key or the plaintext. Each
+//! party decrypts with its key share and proves that it did so correctly with
+//! respect to the public key of its share. The partial decryptions are then
+//! combined by interpolation.
+//!
+//! A partial decryption of `(c_1, c_2)` with key share `s_i` is
+//! `c_2 - s_i * c_1`, i.e., the decryption with the key share as the secret
+//! key. Since the Lagrange coefficients sum to one, interpolating the partial
+//! decryptions at zero gives `c_2 - s * c_1`, the decryption with the shared
+//! key.
+use crate::{
+    anonymity_revoker::{verify_decryption, DecryptionProof, VerifiableDecryption},
+    secret_sharing::{reveal_in_group, share, Threshold},
+    types::ArIdentity,
+};
+use anyhow::{bail, ensure};
+use crypto_common::*;
+use curve_arithmetic::Curve;
+use elgamal::{Cipher, Message, PublicKey, SecretKey};
+use rand::Rng;
+use random_oracle::ChallengeContext;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The public data of a shared key.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdPublicKey<C: Curve> {
+    /// The public key that ciphertexts are encrypted with.
+    pub public_key:        PublicKey<C>,
+    /// The number of partial decryptions needed to decrypt.
+    pub threshold:         Threshold,
+    /// The public keys of the key shares, used to check partial decryptions.
+    pub verification_keys: BTreeMap<ArIdentity, PublicKey<C>>,
+}
+
+/// The share of a secret key held by one party.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct KeyShare<C: Curve> {
+    /// The point the share is the evaluation of the sharing polynomial at.
+    pub ar_identity: ArIdentity,
+    /// The share as a secret key with the generator of the shared key.
+    pub secret_key:  SecretKey<C>,
+}
+
+/// The decryption of a ciphertext with a key share, together with the proof
+/// that it is correct.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(rename_all = "camelCase")]
+pub struct PartialDecryption<C: Curve> {
+    pub ar_identity: ArIdentity,
+    pub decryption:  Message<C>,
+    pub proof:       DecryptionProof<C>,
+}
+
+/// Share the secret key among the given parties, so that any `threshold` of
+/// them can decrypt. This is done by a trusted dealer, who must delete the key
+/// afterwards, e.g., to distribute the key of an existing anonymity revoker.
+///
+/// Returns `None` if the threshold is 0 or exceeds the number of parties.
+pub fn share_secret_key<C: Curve, R: Rng>(
+    secret_key: &SecretKey<C>,
+    threshold: Threshold,
+    ar_identities: &BTreeSet<ArIdentity>,
+    csprng: &mut R,
+) -> Option<(ThresholdPublicKey<C>, Vec<KeyShare<C>>)> {
+    if threshold.0 == 0 || usize::from(threshold) > ar_identities.len() {
+        return None;
+    }
+    let sharing_data = share::<C, _, _, _>(
+        &secret_key.scalar,
+        ar_identities.iter().copied(),
+        threshold,
+        csprng,
+    );
+    let mut verification_keys = BTreeMap::new();
+    let mut key_shares = Vec::with_capacity(ar_identities.len());
+    for (&ar_identity, share) in ar_identities.iter().zip(sharing_data.shares) {
+        let secret_key = SecretKey {
+            generator: secret_key.generator,
+            scalar:    *share,
+        };
+        verification_keys.insert(ar_identity, PublicKey::from(&secret_key));
+        key_shares.push(KeyShare {
+            ar_identity,
+            secret_key,
+        });
+    }
+    let public_key = ThresholdPublicKey {
+        public_key: PublicKey::from(secret_key),
+        threshold,
+        verification_keys,
+    };
+    Some((public_key, key_shares))
+}
+
+/// Generate a fresh key with the given generator and share it, as
+/// [share_secret_key]. The key itself is never returned.
+pub fn generate_key_shares<C: Curve, R: Rng>(
+    generator: &C,
+    threshold: Threshold,
+    ar_identities: &BTreeSet<ArIdentity>,
+    csprng: &mut R,
+) -> Option<(ThresholdPublicKey<C>, Vec<KeyShare<C>>)> {
+    let secret_key = SecretKey::generate(generator, csprng);
+    share_secret_key(&secret_key, threshold, ar_identities, csprng)
+}
+
+impl<C: Curve> KeyShare<C> {
+    /// Decrypt the ciphertext with the key share, and prove that it is done
+    /// correctly.
+    pub fn partial_decrypt<R: Rng>(
+        &self,
+        cipher: &Cipher<C>,
+        csprng: &mut R,
+    ) -> PartialDecryption<C> {
+        let (decryption, proof) =
+            self.secret_key
+                .decrypt_with_proof(&ChallengeContext::Legacy, cipher, csprng);
+        PartialDecryption {
+            ar_identity: self.ar_identity,
+            decryption,
+            proof,
+        }
+    }
+}
+
+impl<C: Curve> ThresholdPublicKey<C> {
+    /// Check that the partial decryption is by one of the parties, and that it
+    /// is done correctly.
+    pub fn verify_partial_decryption(
+        &self,
+        cipher: &Cipher<C>,
+        partial: &PartialDecryption<C>,
+    ) -> bool {
+        match self.verification_keys.get(&partial.ar_identity) {
+            Some(key) => verify_decryption(
+                &ChallengeContext::Legacy,
+                key,
+                cipher,
+                &partial.decryption,
+                &partial.proof,
+            ),
+            None => false,
+        }
+    }
+
+    /// Combine the partial decryptions of the ciphertext into its decryption.
+    /// This fails if there are fewer than `threshold` partial decryptions,
+    /// if two are by the same party, or if any of them is not correct.
+    pub fn combine(
+        &self,
+        cipher: &Cipher<C>,
+        partials: &[PartialDecryption<C>],
+    ) -> anyhow::Result<Message<C>> {
+        ensure!(
+            partials.len() >= usize::from(self.threshold),
+            "{} partial decryptions are given, but {} are needed.",
+            partials.len(),
+            self.threshold
+        );
+        let mut shares = Vec::with_capacity(partials.len());
+        let mut seen = BTreeSet::new();
+        for partial in partials {
+            ensure!(
+                seen.insert(partial.ar_identity),
+                "Duplicate partial decryption by {}.",
+                partial.ar_identity
+            );
+            if !self.verify_partial_decryption(cipher, partial) {
+                bail!(
+                    "Partial decryption by {} is not correct.",
+                    partial.ar_identity
+                );
+            }
+            shares.push((partial.ar_identity, partial.decryption.value));
+        }
+        Ok(Message {
+            value: reveal_in_group(&shares),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pairing::bls12_381::G1;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_threshold_decryption() {
+        let mut csprng = thread_rng();
+        let generator = G1::generate(&mut csprng);
+        let ar_identities = (1..=5).map(ArIdentity::new).collect::<BTreeSet<_>>();
+        assert!(
+            generate_key_shares(&generator, Threshold(6), &ar_identities, &mut csprng).is_none()
+        );
+        let secret_key = SecretKey::generate(&generator, &mut csprng);
+        assert!(share_secret_key(&secret_key, Threshold(0), &ar_identities, &mut csprng).is_none());
+        let (public_key, key_shares) =
+            generate_key_shares(&generator, Threshold(3), &ar_identities, &mut csprng)
+                .expect("Threshold is valid.");
+
+        let message = Message::<G1>::generate(&mut csprng);
+        let cipher = public_key.public_key.encrypt(&mut csprng, &message);
+        let partials: Vec<_> = key_shares
+            .iter()
+            .map(|share| share.partial_decrypt(&cipher, &mut csprng))
+            .collect();
+        for partial in partials.iter() {
+            assert!(public_key.verify_partial_decryption(&cipher, partial));
+        }
+        // Any three partial decryptions suffice.
+        let decrypted = public_key
+            .combine(&cipher, &partials[..3])
+            .expect("Combining should succeed.");
+        assert_eq!(decrypted, message);
+        let decrypted = public_key
+            .combine(&cipher, &[
+                partials[4].clone(),
+                partials[1].clone(),
+                partials[2].clone(),
+            ])
+            .expect("Combining should succeed.");
+        assert_eq!(decrypted, message);
+
+        // Too few, duplicate, or incorrect partial decryptions are rejected.
+        assert!(public_key.combine(&cipher, &partials[..2]).is_err());
+        assert!(public_key
+            .combine(&cipher, &[
+                partials[0].clone(),
+                partials[0].clone(),
+                partials[1].clone()
+            ])
+            .is_err());
+        let mut wrong = partials[..3].to_vec();
+        wrong[1].decryption = Message::generate(&mut csprng);
+        assert!(public_key.combine(&cipher, &wrong).is_err());
+    }
+}
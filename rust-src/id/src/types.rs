@@ -5,15 +5,15 @@ use crate::{
     sigma_protocols::{
         com_enc_eq, com_eq, com_eq_different_groups, com_eq_sig, com_mult,
         common::{ReplicateAdapter, ReplicateWitness},
-        dlog,
+        dlog, dlog_eq,
     },
 };
 use anyhow::{anyhow, bail};
 use base58check::*; // only for account addresses
-use bulletproofs::range_proof::{Generators, RangeProof};
+use bulletproofs::range_proof::{Generators, LazyGenerators, RangeProof};
 use byteorder::ReadBytesExt;
 use crypto_common::{
-    types::{CredentialIndex, KeyIndex, KeyPair},
+    types::{CredentialIndex, KeyIndex, KeyPair, TransactionSignature},
     *,
 };
 use crypto_common_derive::*;
@@ -27,7 +27,7 @@ use elgamal::{ChunkSize, Cipher, Message, SecretKey as ElgamalSecretKey};
 use ff::Field;
 use hex::{decode, encode};
 use pedersen_scheme::{
-    Commitment as PedersenCommitment, CommitmentKey as PedersenKey,
+    Commitment as PedersenCommitment, CommitmentKey as PedersenKey, PrecomputedCommitmentKey,
     Randomness as PedersenRandomness, Value as PedersenValue,
 };
 use random_oracle::Challenge;
@@ -37,6 +37,7 @@ use serde::{
 };
 use sha2::{Digest, Sha256};
 use std::{
+    borrow::Cow,
     cmp::Ordering,
     collections::{btree_map::BTreeMap, hash_map::HashMap, BTreeSet},
     convert::{TryFrom, TryInto},
@@ -230,7 +231,7 @@ impl From<ed25519::Signature> for AccountOwnershipSignature {
 /// The list should be non-empty and at most 255 elements long, and have no
 /// duplicates. The current choice of data structure disallows duplicates by
 /// design.
-#[derive(SerdeSerialize, SerdeDeserialize, Clone)]
+#[derive(Debug, SerdeSerialize, SerdeDeserialize, Clone)]
 #[serde(transparent)]
 pub struct AccountOwnershipProof {
     pub sigs: BTreeMap<KeyIndex, AccountOwnershipSignature>,
@@ -654,6 +655,10 @@ pub struct IdCredentials<C: Curve> {
     pub id_cred_sec: PedersenValue<C>,
 }
 
+impl<C: Curve> Zeroize for IdCredentials<C> {
+    fn zeroize(&mut self) { self.id_cred_sec.zeroize() }
+}
+
 impl<C: Curve> IdCredentials<C> {
     /// Use a cryptographically secure random number generator to
     /// generate a fresh secret credential.
@@ -676,6 +681,10 @@ pub struct CredentialHolderInfo<C: Curve> {
     pub id_cred: IdCredentials<C>,
 }
 
+impl<C: Curve> Zeroize for CredentialHolderInfo<C> {
+    fn zeroize(&mut self) { self.id_cred.zeroize() }
+}
+
 /// Private and public data chosen by the credential holder before the
 /// interaction with the identity provider. The credential holder chooses a prf
 /// key and an attribute list.
@@ -689,6 +698,13 @@ pub struct AccCredentialInfo<C: Curve> {
     pub prf_key:          prf::SecretKey<C>,
 }
 
+impl<C: Curve> Zeroize for AccCredentialInfo<C> {
+    fn zeroize(&mut self) {
+        self.cred_holder_info.zeroize();
+        self.prf_key.zeroize();
+    }
+}
+
 /// The data relating to a single anonymity revoker
 /// sent by the account holder to the identity provider
 /// typically the account holder will send a vector of these
@@ -742,7 +758,7 @@ pub struct ChainArData<C: Curve> {
 /// need the context for that. In the other cases the data is always in the
 /// context of a credential or pre-identity object, and as a result part of the
 /// map.
-#[derive(Debug, PartialEq, Eq, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
 pub struct ChainArDecryptedData<C: Curve> {
     /// identity of the anonymity revoker
@@ -751,6 +767,14 @@ pub struct ChainArDecryptedData<C: Curve> {
     /// share of id cred pub
     #[serde(rename = "idCredPubShare")]
     pub id_cred_pub_share: Message<C>,
+    /// proof that the share is the decryption of the encrypted share, see
+    /// [crate::anonymity_revoker::VerifiableDecryption]
+    #[serde(
+        rename = "decryptionProof",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub decryption_proof:  Option<dlog_eq::Proof<C>>,
 }
 
 // NOTE: This struct is redundant, but we will
@@ -846,7 +870,7 @@ pub struct PreIdentityObject<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
 }
 
 /// The data we get back from the identity provider.
-#[derive(SerdeSerialize, SerdeDeserialize)]
+#[derive(Serialize, SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(
     serialize = "P: Pairing, C: Curve<Scalar=P::ScalarField>, AttributeType: Attribute<C::Scalar> \
                  + SerdeSerialize",
@@ -945,6 +969,94 @@ pub struct IpInfos<P: Pairing> {
     pub identity_providers: BTreeMap<IpIdentity, IpInfo<P>>,
 }
 
+/// Metadata about an identity provider that wallets display to users when they
+/// create identities. The metadata is distributed alongside the [IpInfo], and
+/// is signed by the identity provider, see [SignedIpMetadata].
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+pub struct IpMetadata {
+    /// Name of the identity provider as displayed to users.
+    #[string_size_length = 4]
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    /// URL of the icon of the identity provider.
+    #[string_size_length = 4]
+    #[serde(rename = "iconUrl")]
+    pub icon_url:     String,
+    /// URL of the support page of the identity provider.
+    #[string_size_length = 4]
+    #[serde(rename = "supportUrl")]
+    pub support_url:  String,
+    /// URL at which identities are issued.
+    #[string_size_length = 4]
+    #[serde(rename = "issuanceUrl")]
+    pub issuance_url: String,
+}
+
+/// Domain separator of the message that is signed in [SignedIpMetadata].
+const IP_METADATA_DOMAIN: &[u8] = b"IpMetadata";
+
+impl IpMetadata {
+    /// The message signed by the identity provider. It includes the identity
+    /// of the identity provider, so that metadata signed by one identity
+    /// provider cannot be presented as that of another one with the same key.
+    pub fn to_sign(&self, ip_identity: IpIdentity) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(IP_METADATA_DOMAIN);
+        hasher.update(&to_bytes(&ip_identity));
+        hasher.update(&to_bytes(self));
+        hasher.finalize().to_vec()
+    }
+}
+
+/// [IpMetadata] together with a signature by the identity provider, made with
+/// the key [IpInfo::ip_cdi_verify_key].
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+pub struct SignedIpMetadata {
+    /// The identity provider the metadata is about.
+    #[serde(rename = "ipIdentity")]
+    pub ip_identity: IpIdentity,
+    #[serde(rename = "metadata")]
+    pub metadata:    IpMetadata,
+    #[serde(
+        rename = "signature",
+        serialize_with = "base16_encode",
+        deserialize_with = "base16_decode"
+    )]
+    pub signature:   ed25519::Signature,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+/// Reasons why signed identity provider metadata is not valid.
+pub enum IpMetadataError {
+    #[error("The metadata is for identity provider {actual}, not {expected}.")]
+    WrongIdentityProvider {
+        expected: IpIdentity,
+        actual:   IpIdentity,
+    },
+    #[error("The signature on the metadata is not valid.")]
+    Signature,
+}
+
+impl SignedIpMetadata {
+    /// Check that the metadata is about the given identity provider and that
+    /// it is signed by it. Only metadata that is verified should be displayed
+    /// to users.
+    pub fn verify<P: Pairing>(&self, ip_info: &IpInfo<P>) -> Result<&IpMetadata, IpMetadataError> {
+        if self.ip_identity != ip_info.ip_identity {
+            return Err(IpMetadataError::WrongIdentityProvider {
+                expected: ip_info.ip_identity,
+                actual:   self.ip_identity,
+            });
+        }
+        let to_sign = self.metadata.to_sign(self.ip_identity);
+        ip_info
+            .ip_cdi_verify_key
+            .verify(&to_sign, &self.signature)
+            .map_err(|_| IpMetadataError::Signature)?;
+        Ok(&self.metadata)
+    }
+}
+
 /// Public key of an anonymity revoker.
 pub type ArPublicKey<C> = elgamal::PublicKey<C>;
 
@@ -1180,7 +1292,7 @@ pub struct IdOwnershipProofs<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
     pub cred_counter_less_than_max_accounts: RangeProof<C>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, SerdeSerialize, SerdeDeserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(
     serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
     deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
@@ -1195,35 +1307,13 @@ pub struct Policy<C: Curve, AttributeType: Attribute<C::Scalar>> {
     /// Revealed attributes for now. In the future we might have
     /// additional items with (Tag, Property, Proof).
     #[serde(rename = "revealedAttributes")]
+    #[map_size_length = 2]
     pub policy_vec: BTreeMap<AttributeTag, AttributeType>,
     #[serde(skip)]
+    #[serial_skip]
     pub _phantom:   std::marker::PhantomData<C>,
 }
 
-impl<C: Curve, AttributeType: Attribute<C::Scalar>> Serial for Policy<C, AttributeType> {
-    fn serial<B: Buffer>(&self, out: &mut B) {
-        out.put(&self.valid_to);
-        out.put(&self.created_at);
-        out.put(&(self.policy_vec.len() as u16));
-        serial_map_no_length(&self.policy_vec, out)
-    }
-}
-
-impl<C: Curve, AttributeType: Attribute<C::Scalar>> Deserial for Policy<C, AttributeType> {
-    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
-        let valid_to = source.get()?;
-        let created_at = source.get()?;
-        let len: u16 = source.get()?;
-        let policy_vec = deserial_map_no_length(source, usize::from(len))?;
-        Ok(Policy {
-            valid_to,
-            created_at,
-            policy_vec,
-            _phantom: Default::default(),
-        })
-    }
-}
-
 #[derive(Debug, PartialEq, Eq)]
 /// Which signature scheme is being used. Currently only one is supported.
 pub enum SchemeId {
@@ -1516,6 +1606,60 @@ pub enum AccountCredentialWithoutProofs<C: Curve, AttributeType: Attribute<C::Sc
     },
 }
 
+impl<C: Curve, AttributeType: Attribute<C::Scalar>>
+    AccountCredentialWithoutProofs<C, AttributeType>
+{
+    /// The registration id of the credential.
+    pub fn cred_id(&self) -> &C {
+        match self {
+            AccountCredentialWithoutProofs::Initial { icdv } => &icdv.reg_id,
+            AccountCredentialWithoutProofs::Normal { cdv, .. } => &cdv.cred_id,
+        }
+    }
+
+    /// The identity provider that issued the identity the credential is
+    /// derived from.
+    pub fn ip_identity(&self) -> IpIdentity {
+        match self {
+            AccountCredentialWithoutProofs::Initial { icdv } => icdv.ip_identity,
+            AccountCredentialWithoutProofs::Normal { cdv, .. } => cdv.ip_identity,
+        }
+    }
+
+    /// The policy of the credential, with the revealed attributes.
+    pub fn policy(&self) -> &Policy<C, AttributeType> {
+        match self {
+            AccountCredentialWithoutProofs::Initial { icdv } => &icdv.policy,
+            AccountCredentialWithoutProofs::Normal { cdv, .. } => &cdv.policy,
+        }
+    }
+
+    /// The public keys of the credential.
+    pub fn public_keys(&self) -> &CredentialPublicKeys {
+        match self {
+            AccountCredentialWithoutProofs::Initial { icdv } => &icdv.cred_account,
+            AccountCredentialWithoutProofs::Normal { cdv, .. } => &cdv.cred_key_info,
+        }
+    }
+
+    /// The commitments of the credential. Initial credentials have no
+    /// commitments.
+    pub fn commitments(&self) -> Option<&CredentialDeploymentCommitments<C>> {
+        match self {
+            AccountCredentialWithoutProofs::Initial { .. } => None,
+            AccountCredentialWithoutProofs::Normal { commitments, .. } => Some(commitments),
+        }
+    }
+
+    /// The commitment to the given attribute, which is what proofs about the
+    /// attribute are verified against. This is `None` if the credential has
+    /// no commitment to the attribute, e.g., because the attribute is revealed
+    /// in the policy.
+    pub fn attribute_commitment(&self, tag: AttributeTag) -> Option<&PedersenCommitment<C>> {
+        self.commitments()?.cmm_attributes.get(&tag)
+    }
+}
+
 /// This is the CredentialDeploymentInfo structure, that instead of containing
 /// CredDeploymentProofs, it contains UnsignedCredDeploymentProofs, and
 /// the reg_id that also has to be signed.
@@ -1595,10 +1739,14 @@ pub struct IpContext<'a, P: Pairing, C: Curve<Scalar = P::ScalarField>> {
 
 impl<'a, P: Pairing, C: Curve<Scalar = P::ScalarField>> Copy for IpContext<'a, P, C> {}
 
-#[derive(Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[derive(Clone, Serial, SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(serialize = "C: Curve", deserialize = "C: Curve"))]
+#[serde(try_from = "UncheckedGlobalContext<C>")]
 /// A set of cryptographic parameters that are particular to the chain and
 /// shared by everybody that interacts with the chain.
+///
+/// The context is validated with [GlobalContext::validate] when it is
+/// deserialized, both from binary and from JSON.
 pub struct GlobalContext<C: Curve> {
     /// A shared commitment key known to the chain and the account holder (and
     /// therefore it is public). The account holder uses this commitment key to
@@ -1607,14 +1755,20 @@ pub struct GlobalContext<C: Curve> {
     pub on_chain_commitment_key: PedersenKey<C>,
     /// Generators for the bulletproofs.
     /// It is unclear what length we will require here, or whether we'll allow
-    /// dynamic generation.
+    /// dynamic generation. Only a prefix of them is decoded if the context is
+    /// deserialized with [GlobalContext::deserial_lazy].
     #[serde(rename = "bulletproofGenerators")]
-    bulletproof_generators:      Generators<C>,
+    bulletproof_generators:      LazyGenerators<C>,
     #[string_size_length = 4]
     #[serde(rename = "genesisString")]
     /// A free-form string used to distinguish between different chains even if
     /// they share other parameters.
     pub genesis_string:          String,
+    /// A table for committing with the on-chain commitment key, see
+    /// [GlobalContext::precompute]. It is not part of the serialization.
+    #[serial_skip]
+    #[serde(skip)]
+    precomputed_commitment_key:  Option<PrecomputedCommitmentKey<C>>,
 }
 
 impl<C: Curve> GlobalContext<C> {
@@ -1650,8 +1804,9 @@ impl<C: Curve> GlobalContext<C> {
 
         GlobalContext {
             on_chain_commitment_key: cmm_key,
-            bulletproof_generators: Generators { G_H: generators },
+            bulletproof_generators: Generators { G_H: generators }.into(),
             genesis_string,
+            precomputed_commitment_key: None,
         }
     }
 
@@ -1672,9 +1827,229 @@ impl<C: Curve> GlobalContext<C> {
     /// The generator used as the base for elgamal public keys.
     pub fn elgamal_generator(&self) -> &C { &self.on_chain_commitment_key.g }
 
+    /// Precompute a table for committing with the on-chain commitment key.
+    /// This speeds up [GlobalContext::precomputed_commitment_key], and is
+    /// worthwhile if the context is used for several credentials, e.g., when it
+    /// is kept by a wallet.
+    pub fn precompute(&mut self) {
+        self.precomputed_commitment_key = Some(PrecomputedCommitmentKey::from(
+            &self.on_chain_commitment_key,
+        ));
+    }
+
+    /// The on-chain commitment key with a table for committing with it. This is
+    /// the table computed by [GlobalContext::precompute] if there is one for
+    /// the current key, and a freshly computed table otherwise.
+    pub fn precomputed_commitment_key(&self) -> Cow<'_, PrecomputedCommitmentKey<C>> {
+        match &self.precomputed_commitment_key {
+            Some(precomputed) if *precomputed.key() == self.on_chain_commitment_key => {
+                Cow::Borrowed(precomputed)
+            }
+            _ => Cow::Owned(PrecomputedCommitmentKey::from(
+                &self.on_chain_commitment_key,
+            )),
+        }
+    }
+
     /// A wrapper function to support changes in internal structure of the
     /// context in the future, e.g., lazy generation of generators.
-    pub fn bulletproof_generators(&self) -> &Generators<C> { &self.bulletproof_generators }
+    ///
+    /// If the context was deserialized with [GlobalContext::deserial_lazy]
+    /// these are only the generators that have been decoded so far, see
+    /// [GlobalContext::ensure_bulletproof_generators], which must be used to
+    /// decode enough of them before range proofs that need more generators
+    /// are produced or verified with the context.
+    pub fn bulletproof_generators(&self) -> &Generators<C> { self.bulletproof_generators.decoded() }
+
+    /// Deserialize a context in the same format as [Deserial], but only decode
+    /// the first `n` bulletproof generators. This is intended for wallets that
+    /// only need short range proofs, e.g., for credential deployments, and
+    /// should not pay for decoding all of the generators. Further generators
+    /// can be decoded on demand with
+    /// [GlobalContext::ensure_bulletproof_generators].
+    ///
+    /// The serialization of the context is the same regardless of how many
+    /// generators are decoded.
+    pub fn deserial_lazy<R: ReadBytesExt>(source: &mut R, n: usize) -> ParseResult<Self> {
+        let on_chain_commitment_key = source.get()?;
+        let bulletproof_generators = LazyGenerators::deserial_prefix(source, n)?;
+        let len: u32 = source.get()?;
+        let genesis_string = deserial_string(source, usize::try_from(len)?)?;
+        let context = GlobalContext {
+            on_chain_commitment_key,
+            bulletproof_generators,
+            genesis_string,
+            precomputed_commitment_key: None,
+        };
+        context.validate()?;
+        Ok(context)
+    }
+
+    /// Deserialize a context from its JSON representation, but only decode the
+    /// first `n` bulletproof generators, as [GlobalContext::deserial_lazy]
+    /// does for the binary serialization. The context is validated.
+    pub fn deserialize_lazy<'de, D: Deserializer<'de>>(des: D, n: usize) -> Result<Self, D::Error> {
+        let value = LazyGlobalContext::<C>::deserialize(des)?;
+        let bytes = decode(&value.bulletproof_generators).map_err(de::Error::custom)?;
+        let bulletproof_generators = LazyGenerators::deserial_prefix(&mut Cursor::new(&bytes), n)
+            .map_err(de::Error::custom)?;
+        let context = GlobalContext {
+            on_chain_commitment_key: value.on_chain_commitment_key,
+            bulletproof_generators,
+            genesis_string: value.genesis_string,
+            precomputed_commitment_key: None,
+        };
+        context.validate().map_err(de::Error::custom)?;
+        Ok(context)
+    }
+
+    /// Make sure that at least the first `n` bulletproof generators are
+    /// decoded, so that [GlobalContext::bulletproof_generators] has at least
+    /// `n` elements. Range proofs for encrypted transfers need
+    /// [MIN_BULLETPROOF_GENERATORS] generators.
+    pub fn ensure_bulletproof_generators(&mut self, n: usize) -> ParseResult<()> {
+        self.bulletproof_generators.ensure(n)?;
+        self.validate()?;
+        Ok(())
+    }
+
+    /// Check that the context can be used for producing and verifying proofs.
+    /// This checks that
+    /// - none of the generators is the identity element,
+    /// - the two generators of the commitment key are distinct, as are the two
+    ///   generators in each pair of bulletproof generators,
+    /// - there are at least [MIN_BULLETPROOF_GENERATORS] bulletproof
+    ///   generators.
+    ///
+    /// The bulletproof generators that have not been decoded are checked on
+    /// their serialization, which is canonical. Whether they are valid group
+    /// elements is only checked when they are decoded.
+    pub fn validate(&self) -> Result<(), GlobalContextError> {
+        let cmm_key = &self.on_chain_commitment_key;
+        if cmm_key.g.is_zero_point() || cmm_key.h.is_zero_point() {
+            return Err(GlobalContextError::DegenerateCommitmentKey);
+        }
+        if cmm_key.g == cmm_key.h {
+            return Err(GlobalContextError::DependentCommitmentKey);
+        }
+        let num_generators = self.bulletproof_generators.len();
+        if num_generators < MIN_BULLETPROOF_GENERATORS {
+            return Err(GlobalContextError::TooFewBulletproofGenerators {
+                actual:   num_generators,
+                required: MIN_BULLETPROOF_GENERATORS,
+            });
+        }
+        let decoded = &self.bulletproof_generators().G_H;
+        for (i, (g, h)) in decoded.iter().enumerate() {
+            if g.is_zero_point() || h.is_zero_point() || g == h {
+                return Err(GlobalContextError::DegenerateBulletproofGenerator(i));
+            }
+        }
+        let zero = to_bytes(&C::zero_point());
+        for (i, (g, h)) in self.bulletproof_generators.undecoded().enumerate() {
+            if g == &zero[..] || h == &zero[..] || g == h {
+                return Err(GlobalContextError::DegenerateBulletproofGenerator(
+                    decoded.len() + i,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Extract a global context from its versioned form, migrating it to
+    /// [GLOBAL_CONTEXT_VERSION] if it has an older version, and validate it.
+    /// Currently version 0 is the only version, so there is nothing to
+    /// migrate, but callers should use this function so that future versions
+    /// are handled in one place.
+    pub fn from_versioned(versioned: Versioned<Self>) -> Result<Self, GlobalContextError> {
+        if versioned.version != GLOBAL_CONTEXT_VERSION {
+            return Err(GlobalContextError::UnsupportedVersion(versioned.version));
+        }
+        let context = versioned.value;
+        context.validate()?;
+        Ok(context)
+    }
+}
+
+/// The version of the global context that this library produces. See
+/// [GlobalContext::from_versioned].
+pub const GLOBAL_CONTEXT_VERSION: Version = VERSION_0;
+
+/// The minimum number of bulletproof generators in a valid global context.
+/// Encrypted transfers use range proofs for 64-bit amounts, which need 64
+/// generators.
+pub const MIN_BULLETPROOF_GENERATORS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+/// Reasons why a global context is not valid.
+pub enum GlobalContextError {
+    #[error("Unsupported global context version {0}.")]
+    UnsupportedVersion(Version),
+    #[error("A generator of the commitment key is the identity element.")]
+    DegenerateCommitmentKey,
+    #[error("The generators of the commitment key are equal.")]
+    DependentCommitmentKey,
+    #[error("There are {actual} bulletproof generators, but at least {required} are needed.")]
+    TooFewBulletproofGenerators { actual: usize, required: usize },
+    #[error("The bulletproof generators at index {0} are degenerate.")]
+    DegenerateBulletproofGenerator(usize),
+}
+
+impl<C: Curve> Deserial for GlobalContext<C> {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let on_chain_commitment_key = source.get()?;
+        let bulletproof_generators = source.get()?;
+        let len: u32 = source.get()?;
+        let genesis_string = deserial_string(source, usize::try_from(len)?)?;
+        let context = GlobalContext {
+            on_chain_commitment_key,
+            bulletproof_generators,
+            genesis_string,
+            precomputed_commitment_key: None,
+        };
+        context.validate()?;
+        Ok(context)
+    }
+}
+
+/// The JSON representation of a global context, before it is validated.
+#[derive(SerdeDeserialize)]
+#[serde(bound(deserialize = "C: Curve"))]
+struct UncheckedGlobalContext<C: Curve> {
+    #[serde(rename = "onChainCommitmentKey")]
+    on_chain_commitment_key: PedersenKey<C>,
+    #[serde(rename = "bulletproofGenerators")]
+    bulletproof_generators:  Generators<C>,
+    #[serde(rename = "genesisString")]
+    genesis_string:          String,
+}
+
+/// The JSON representation of a global context whose bulletproof generators
+/// are kept in serialized form, see [GlobalContext::deserialize_lazy].
+#[derive(SerdeDeserialize)]
+#[serde(bound(deserialize = "C: Curve"))]
+struct LazyGlobalContext<C: Curve> {
+    #[serde(rename = "onChainCommitmentKey")]
+    on_chain_commitment_key: PedersenKey<C>,
+    #[serde(rename = "bulletproofGenerators")]
+    bulletproof_generators:  String,
+    #[serde(rename = "genesisString")]
+    genesis_string:          String,
+}
+
+impl<C: Curve> TryFrom<UncheckedGlobalContext<C>> for GlobalContext<C> {
+    type Error = GlobalContextError;
+
+    fn try_from(value: UncheckedGlobalContext<C>) -> Result<Self, Self::Error> {
+        let context = GlobalContext {
+            on_chain_commitment_key:    value.on_chain_commitment_key,
+            bulletproof_generators:     value.bulletproof_generators.into(),
+            genesis_string:             value.genesis_string,
+            precomputed_commitment_key: None,
+        };
+        context.validate()?;
+        Ok(context)
+    }
 }
 
 /// Make a context in which the account holder can produce a pre-identity object
@@ -1804,6 +2179,198 @@ impl From<InitialAccountData> for AccountKeys {
     }
 }
 
+/// The maximum number of keys of a credential, and the maximum number of
+/// credentials of an account. The number is serialized in a single byte.
+pub const MAX_KEYS: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+/// Reasons why account keys are not valid.
+pub enum AccountKeysError {
+    #[error("The account has no credentials.")]
+    NoCredentials,
+    #[error("The account has more than {} credentials.", MAX_KEYS)]
+    TooManyCredentials,
+    #[error("Credential {0} already exists.")]
+    DuplicateCredential(u8),
+    #[error("Credential {0} does not exist.")]
+    UnknownCredential(u8),
+    #[error("Credential {0} has no keys.")]
+    NoKeys(u8),
+    #[error("Credential {0} has more than {} keys.", MAX_KEYS)]
+    TooManyKeys(u8),
+    #[error("Key {key} already exists on credential {credential}.")]
+    DuplicateKey { credential: u8, key: u8 },
+    #[error(
+        "Threshold {threshold} of credential {credential} must be between 1 and the number of \
+         keys {num_keys}."
+    )]
+    CredentialThreshold {
+        credential: u8,
+        threshold:  u8,
+        num_keys:   usize,
+    },
+    #[error(
+        "Account threshold {threshold} must be between 1 and the number of credentials \
+         {num_credentials}."
+    )]
+    AccountThreshold {
+        threshold:       u8,
+        num_credentials: usize,
+    },
+}
+
+impl AccountKeys {
+    /// Account keys with no credentials and account threshold 1. Credentials
+    /// are added with [AccountKeys::add_credential] and
+    /// [AccountKeys::add_key].
+    pub fn new() -> Self {
+        AccountKeys {
+            keys:      BTreeMap::new(),
+            threshold: SignatureThreshold(1),
+        }
+    }
+
+    /// Add a credential at the given index. The credential must be valid, see
+    /// [CredentialData::validate], and the index must not be in use.
+    pub fn add_credential(
+        &mut self,
+        index: CredentialIndex,
+        credential: CredentialData,
+    ) -> Result<&mut Self, AccountKeysError> {
+        if self.keys.contains_key(&index) {
+            return Err(AccountKeysError::DuplicateCredential(index.index));
+        }
+        if self.keys.len() >= MAX_KEYS {
+            return Err(AccountKeysError::TooManyCredentials);
+        }
+        credential.validate(index)?;
+        self.keys.insert(index, credential);
+        Ok(self)
+    }
+
+    /// Add a key to an existing credential. The threshold of the credential is
+    /// not changed.
+    pub fn add_key(
+        &mut self,
+        credential: CredentialIndex,
+        index: KeyIndex,
+        key: KeyPair,
+    ) -> Result<&mut Self, AccountKeysError> {
+        let cred = self
+            .keys
+            .get_mut(&credential)
+            .ok_or(AccountKeysError::UnknownCredential(credential.index))?;
+        if cred.keys.contains_key(&index) {
+            return Err(AccountKeysError::DuplicateKey {
+                credential: credential.index,
+                key:        index.0,
+            });
+        }
+        if cred.keys.len() >= MAX_KEYS {
+            return Err(AccountKeysError::TooManyKeys(credential.index));
+        }
+        cred.keys.insert(index, key);
+        Ok(self)
+    }
+
+    /// Set the threshold of an existing credential. It must be at most the
+    /// number of keys of the credential.
+    pub fn set_credential_threshold(
+        &mut self,
+        credential: CredentialIndex,
+        threshold: SignatureThreshold,
+    ) -> Result<&mut Self, AccountKeysError> {
+        let cred = self
+            .keys
+            .get_mut(&credential)
+            .ok_or(AccountKeysError::UnknownCredential(credential.index))?;
+        check_credential_threshold(credential, threshold, cred.keys.len())?;
+        cred.threshold = threshold;
+        Ok(self)
+    }
+
+    /// Set the account threshold. It must be at most the number of
+    /// credentials.
+    pub fn set_threshold(
+        &mut self,
+        threshold: SignatureThreshold,
+    ) -> Result<&mut Self, AccountKeysError> {
+        check_account_threshold(threshold, self.keys.len())?;
+        self.threshold = threshold;
+        Ok(self)
+    }
+
+    /// Check that the account has at least one credential, that all the
+    /// credentials are valid, and that the account threshold is at most the
+    /// number of credentials. Keys that are constructed directly or parsed
+    /// from JSON should be checked with this function before they are used.
+    pub fn validate(&self) -> Result<(), AccountKeysError> {
+        if self.keys.is_empty() {
+            return Err(AccountKeysError::NoCredentials);
+        }
+        if self.keys.len() > MAX_KEYS {
+            return Err(AccountKeysError::TooManyCredentials);
+        }
+        for (&index, cred) in self.keys.iter() {
+            cred.validate(index)?;
+        }
+        check_account_threshold(self.threshold, self.keys.len())
+    }
+
+    /// Sign the hash with all the keys of the account, regardless of the
+    /// thresholds. This is how transactions and any other data signed by the
+    /// account, e.g., messages, are signed.
+    pub fn sign_hash(&self, hash: &[u8]) -> TransactionSignature {
+        let signatures = self
+            .keys
+            .iter()
+            .map(|(&cred_index, cred)| {
+                let sigs = cred
+                    .keys
+                    .iter()
+                    .map(|(&key_index, kp)| (key_index, kp.sign(hash)))
+                    .collect();
+                (cred_index, sigs)
+            })
+            .collect();
+        TransactionSignature { signatures }
+    }
+}
+
+impl Default for AccountKeys {
+    fn default() -> Self { Self::new() }
+}
+
+fn check_credential_threshold(
+    credential: CredentialIndex,
+    threshold: SignatureThreshold,
+    num_keys: usize,
+) -> Result<(), AccountKeysError> {
+    if threshold.0 == 0 || usize::from(threshold.0) > num_keys {
+        Err(AccountKeysError::CredentialThreshold {
+            credential: credential.index,
+            threshold: threshold.0,
+            num_keys,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_account_threshold(
+    threshold: SignatureThreshold,
+    num_credentials: usize,
+) -> Result<(), AccountKeysError> {
+    if threshold.0 == 0 || usize::from(threshold.0) > num_credentials {
+        Err(AccountKeysError::AccountThreshold {
+            threshold: threshold.0,
+            num_credentials,
+        })
+    } else {
+        Ok(())
+    }
+}
+
 /// Credential data needed by the account holder to generate proofs to deploy
 /// the credential object. This contains all the keys on the credential at the
 /// moment of its deployment. If this creates the account then the account
@@ -1816,6 +2383,41 @@ pub struct CredentialData {
     pub threshold: SignatureThreshold,
 }
 
+impl Zeroize for CredentialData {
+    fn zeroize(&mut self) {
+        for kp in self.keys.values_mut() {
+            kp.zeroize();
+        }
+    }
+}
+
+/// Credential data with a single key at index 0 and threshold 1.
+impl From<KeyPair> for CredentialData {
+    fn from(kp: KeyPair) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(KeyIndex(0), kp);
+        CredentialData {
+            keys,
+            threshold: SignatureThreshold(1),
+        }
+    }
+}
+
+impl CredentialData {
+    /// Check that the credential has at least one and at most [MAX_KEYS]
+    /// keys, and that the threshold is at most the number of keys. The index
+    /// is only used in the error.
+    pub fn validate(&self, index: CredentialIndex) -> Result<(), AccountKeysError> {
+        if self.keys.is_empty() {
+            return Err(AccountKeysError::NoKeys(index.index));
+        }
+        if self.keys.len() > MAX_KEYS {
+            return Err(AccountKeysError::TooManyKeys(index.index));
+        }
+        check_credential_threshold(index, self.threshold, self.keys.len())
+    }
+}
+
 impl PublicCredentialData for CredentialData {
     fn get_threshold(&self) -> SignatureThreshold { self.threshold }
 
@@ -1970,6 +2572,13 @@ pub struct IpData<P: Pairing> {
     pub ip_cdi_secret_key: ed25519::SecretKey,
 }
 
+impl<P: Pairing> Zeroize for IpData<P> {
+    fn zeroize(&mut self) {
+        self.ip_secret_key.zeroize();
+        self.ip_cdi_secret_key.zeroize();
+    }
+}
+
 /// Private and public data on an anonymity revoker.
 /// This is used purely off-chain.
 #[derive(SerdeSerialize, SerdeDeserialize, Serialize)]
@@ -1985,6 +2594,10 @@ pub struct ArData<C: Curve> {
     pub ar_secret_key:  ElgamalSecretKey<C>,
 }
 
+impl<C: Curve> Zeroize for ArData<C> {
+    fn zeroize(&mut self) { self.ar_secret_key.zeroize() }
+}
+
 /// Data needed to use the retrieved identity object to generate credentials.
 #[derive(SerdeSerialize, SerdeDeserialize)]
 #[serde(bound(
@@ -2003,6 +2616,13 @@ pub struct IdObjectUseData<P: Pairing, C: Curve<Scalar = P::ScalarField>> {
     pub randomness: ps_sig::SigRetrievalRandomness<P>,
 }
 
+impl<P: Pairing, C: Curve<Scalar = P::ScalarField>> Zeroize for IdObjectUseData<P, C> {
+    fn zeroize(&mut self) {
+        self.aci.zeroize();
+        self.randomness.zeroize();
+    }
+}
+
 /// Data that needs to be stored by the identity provider to support anonymity
 /// revocation.
 #[derive(SerdeSerialize, SerdeDeserialize)]
@@ -2108,6 +2728,63 @@ pub struct AccountCredentialMessage<
     pub credential:     AccountCredential<P, C, AttributeType>,
 }
 
+/// Tag of credential deployments in the node's encoding of block items.
+pub const CREDENTIAL_DEPLOYMENT_TAG: u8 = 1;
+
+impl<P: Pairing, C: Curve<Scalar = P::ScalarField>, AttributeType: Attribute<C::Scalar>>
+    AccountCredentialMessage<P, C, AttributeType>
+{
+    /// The encoding of the message as a block item, exactly as the node
+    /// serializes it. The [hash](Self::hash) of the deployment is computed over
+    /// these bytes.
+    pub fn block_item_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.put(&CREDENTIAL_DEPLOYMENT_TAG);
+        out.put(self);
+        out
+    }
+
+    /// The bytes that are submitted to the node, i.e., the block item bytes
+    /// prefixed with the version of the block item encoding.
+    pub fn submission_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.put(&VERSION_0);
+        out.extend_from_slice(&self.block_item_bytes());
+        out
+    }
+
+    /// Parse the output of [submission_bytes](Self::submission_bytes). This
+    /// fails if the version or the tag are not those of a credential
+    /// deployment, or if there are any trailing bytes.
+    pub fn from_submission_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        let mut source = std::io::Cursor::new(bytes);
+        let version: Version = source.get()?;
+        if version != VERSION_0 {
+            bail!("Unsupported block item version {}.", version);
+        }
+        let tag: u8 = source.get()?;
+        if tag != CREDENTIAL_DEPLOYMENT_TAG {
+            bail!(
+                "Block item with tag {} is not a credential deployment.",
+                tag
+            );
+        }
+        let message = source.get()?;
+        if source.position() != bytes.len() as u64 {
+            bail!("Trailing bytes after the credential deployment.");
+        }
+        Ok(message)
+    }
+
+    /// The hash of the credential deployment. This is the transaction hash
+    /// under which the node reports the status of the deployment.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&Sha256::digest(&self.block_item_bytes()));
+        hash
+    }
+}
+
 /// A type encapsulating both types of credential values, analogous to
 /// AccountCredential.
 /// Serialization must match the one in Haskell.
@@ -2151,6 +2828,182 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_account_keys_builder() {
+        use rand::thread_rng;
+
+        let mut csprng = thread_rng();
+        let cred = |index| CredentialIndex { index };
+        let mut keys = AccountKeys::new();
+        assert_eq!(keys.validate(), Err(AccountKeysError::NoCredentials));
+        keys.add_credential(cred(0), KeyPair::generate(&mut csprng).into())
+            .and_then(|k| k.add_key(cred(0), KeyIndex(5), KeyPair::generate(&mut csprng)))
+            .and_then(|k| k.set_credential_threshold(cred(0), SignatureThreshold(2)))
+            .and_then(|k| k.add_credential(cred(3), KeyPair::generate(&mut csprng).into()))
+            .and_then(|k| k.set_threshold(SignatureThreshold(2)))
+            .expect("Keys should be valid.");
+        assert_eq!(keys.validate(), Ok(()));
+        assert_eq!(keys.keys[&cred(0)].keys.len(), 2);
+
+        assert_eq!(
+            keys.add_credential(cred(3), KeyPair::generate(&mut csprng).into())
+                .err(),
+            Some(AccountKeysError::DuplicateCredential(3))
+        );
+        assert_eq!(
+            keys.add_key(cred(1), KeyIndex(0), KeyPair::generate(&mut csprng))
+                .err(),
+            Some(AccountKeysError::UnknownCredential(1))
+        );
+        assert_eq!(
+            keys.add_key(cred(0), KeyIndex(5), KeyPair::generate(&mut csprng))
+                .err(),
+            Some(AccountKeysError::DuplicateKey {
+                credential: 0,
+                key:        5,
+            })
+        );
+        assert!(keys
+            .set_credential_threshold(cred(3), SignatureThreshold(2))
+            .is_err());
+        assert!(keys.set_threshold(SignatureThreshold(3)).is_err());
+        assert!(keys.set_threshold(SignatureThreshold(0)).is_err());
+
+        // Keys constructed directly are only checked by validate.
+        keys.threshold = SignatureThreshold(3);
+        assert_eq!(
+            keys.validate(),
+            Err(AccountKeysError::AccountThreshold {
+                threshold:       3,
+                num_credentials: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_global_context_validation() {
+        type ExampleCurve = pairing::bls12_381::G1;
+        let context = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        assert_eq!(context.validate(), Ok(()));
+
+        // Valid contexts survive a round trip in both serialization formats.
+        let bytes = to_bytes(&context);
+        assert!(GlobalContext::<ExampleCurve>::deserial(&mut Cursor::new(&bytes)).is_ok());
+        let json = serde_json::to_string(&context).unwrap();
+        assert!(serde_json::from_str::<GlobalContext<ExampleCurve>>(&json).is_ok());
+
+        // Too few bulletproof generators are rejected when deserializing.
+        let small = GlobalContext::<ExampleCurve>::generate_size(String::from("genesis_string"), 8);
+        assert_eq!(
+            small.validate(),
+            Err(GlobalContextError::TooFewBulletproofGenerators {
+                actual:   8,
+                required: MIN_BULLETPROOF_GENERATORS,
+            })
+        );
+        let bytes = to_bytes(&small);
+        assert!(GlobalContext::<ExampleCurve>::deserial(&mut Cursor::new(&bytes)).is_err());
+        let json = serde_json::to_string(&small).unwrap();
+        assert!(serde_json::from_str::<GlobalContext<ExampleCurve>>(&json).is_err());
+
+        // A degenerate commitment key is rejected.
+        let mut degenerate = context.clone();
+        degenerate.on_chain_commitment_key.h = degenerate.on_chain_commitment_key.g;
+        assert_eq!(
+            degenerate.validate(),
+            Err(GlobalContextError::DependentCommitmentKey)
+        );
+        degenerate.on_chain_commitment_key.h = ExampleCurve::zero_point();
+        assert_eq!(
+            degenerate.validate(),
+            Err(GlobalContextError::DegenerateCommitmentKey)
+        );
+
+        // Unknown versions are rejected.
+        assert!(GlobalContext::from_versioned(Versioned::new(VERSION_0, context.clone())).is_ok());
+        assert_eq!(
+            GlobalContext::from_versioned(Versioned::new(Version::from(1), context)).map(|_| ()),
+            Err(GlobalContextError::UnsupportedVersion(Version::from(1)))
+        );
+    }
+
+    #[test]
+    fn test_global_context_lazy_generators() {
+        type ExampleCurve = pairing::bls12_381::G1;
+        let context = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        let bytes = to_bytes(&context);
+
+        let mut lazy = GlobalContext::<ExampleCurve>::deserial_lazy(&mut Cursor::new(&bytes), 8)
+            .expect("Context should deserialize.");
+        assert_eq!(lazy.bulletproof_generators().G_H.len(), 8);
+        // The serialization is unaffected by how many generators are decoded.
+        assert_eq!(to_bytes(&lazy), bytes);
+
+        lazy.ensure_bulletproof_generators(MIN_BULLETPROOF_GENERATORS)
+            .expect("There are enough generators.");
+        assert_eq!(
+            lazy.bulletproof_generators().G_H,
+            context.bulletproof_generators().G_H[..MIN_BULLETPROOF_GENERATORS]
+        );
+        assert_eq!(to_bytes(&lazy), bytes);
+        assert!(lazy
+            .ensure_bulletproof_generators(NUM_BULLETPROOF_GENERATORS + 1)
+            .is_err());
+
+        // The same from JSON.
+        let json = serde_json::to_value(&context).expect("Context can be serialized.");
+        let lazy = GlobalContext::<ExampleCurve>::deserialize_lazy(&json, 0)
+            .expect("Context should deserialize.");
+        assert!(lazy.bulletproof_generators().G_H.is_empty());
+        assert_eq!(to_bytes(&lazy), bytes);
+
+        // Generators that are not decoded are still checked for degeneracy.
+        let mut degenerate = context.bulletproof_generators().clone();
+        degenerate.G_H[100].1 = degenerate.G_H[100].0;
+        let degenerate = GlobalContext {
+            bulletproof_generators: degenerate.into(),
+            ..context
+        };
+        let bytes = to_bytes(&degenerate);
+        let err = GlobalContext::<ExampleCurve>::deserial_lazy(&mut Cursor::new(&bytes), 8)
+            .err()
+            .expect("Degenerate generators are rejected.");
+        assert_eq!(
+            err.downcast_ref::<GlobalContextError>(),
+            Some(&GlobalContextError::DegenerateBulletproofGenerator(100))
+        );
+    }
+
+    #[test]
+    fn test_global_context_precompute() {
+        type ExampleCurve = pairing::bls12_381::G1;
+        let mut csprng = rand::thread_rng();
+        let mut context = GlobalContext::<ExampleCurve>::generate(String::from("genesis_string"));
+        let bytes = to_bytes(&context);
+        assert!(matches!(
+            context.precomputed_commitment_key(),
+            Cow::Owned(_)
+        ));
+
+        context.precompute();
+        // The table is not part of the serialization.
+        assert_eq!(to_bytes(&context), bytes);
+        let precomputed = context.precomputed_commitment_key();
+        assert!(matches!(precomputed, Cow::Borrowed(_)));
+        let value = PedersenValue::<ExampleCurve>::generate(&mut csprng);
+        let randomness = PedersenRandomness::<ExampleCurve>::generate(&mut csprng);
+        assert_eq!(
+            precomputed.hide(&value, &randomness),
+            context.on_chain_commitment_key.hide(&value, &randomness)
+        );
+
+        // A table for a key that has since been replaced is not used.
+        context.on_chain_commitment_key = PedersenKey::generate(&mut csprng);
+        let precomputed = context.precomputed_commitment_key();
+        assert!(matches!(precomputed, Cow::Owned(_)));
+        assert_eq!(precomputed.key(), &context.on_chain_commitment_key);
+    }
+
     #[test]
     fn test_yearmonth_serialization() {
         // Test equality
@@ -2178,4 +3031,64 @@ mod tests {
         let ym1_parsed = YearMonth::try_from(num).unwrap();
         assert_eq!(ym1, ym1_parsed);
     }
+
+    #[test]
+    fn test_policy_serialization() {
+        use crate::constants::*;
+        let mut policy_vec = BTreeMap::new();
+        policy_vec.insert(AttributeTag(1), AttributeKind("ab".into()));
+        let policy: Policy<ArCurve, AttributeKind> = Policy {
+            valid_to: YearMonth::new(2022, 1).unwrap(),
+            created_at: YearMonth::new(2020, 2).unwrap(),
+            policy_vec,
+            _phantom: Default::default(),
+        };
+        let bytes = to_bytes(&policy);
+        // The phantom data is not serialized, and the map is prefixed by a u16 length.
+        assert_eq!(bytes, vec![7, 230, 1, 7, 228, 2, 0, 1, 1, 2, b'a', b'b']);
+        let parsed: Policy<ArCurve, AttributeKind> =
+            from_bytes(&mut std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(parsed, policy);
+    }
+
+    #[test]
+    fn test_golden_cdi() {
+        use crate::{constants::*, test::*};
+        let cdi: Versioned<CredentialDeploymentInfo<IpPairing, ArCurve, AttributeKind>> =
+            read_golden_json("testdata/cdi.json");
+        assert_golden_serialization("testdata/cdi.bin", &cdi);
+        assert_golden_serialization(
+            "testdata/cdi-coms.bin",
+            &cdi.value.proofs.id_proofs.commitments,
+        );
+    }
+
+    #[test]
+    fn test_golden_initial_cdi() {
+        use crate::{constants::*, test::*};
+        let icdi: Versioned<InitialCredentialDeploymentInfo<ArCurve, AttributeKind>> =
+            read_golden_json("testdata/icdi.json");
+        assert_golden_serialization("testdata/icdi.bin", &icdi);
+    }
+
+    #[test]
+    fn test_golden_pio() {
+        use crate::{constants::*, test::*};
+        let pio: Versioned<PreIdentityObject<IpPairing, ArCurve>> =
+            assert_golden_json("testdata/pio.json");
+        assert_golden_serialization("testdata/pio.bin", &pio);
+    }
+
+    #[test]
+    fn test_golden_identity_object() {
+        use crate::{constants::*, test::*};
+        let id_object: Versioned<IdentityObject<IpPairing, ArCurve, AttributeKind>> =
+            assert_golden_json("testdata/identity-object.json");
+        assert_golden_serialization("testdata/identity-object.bin", &id_object);
+        // The identity object starts with the pre-identity object.
+        assert_eq!(
+            to_bytes(&id_object.value.pre_identity_object),
+            &read_golden_bytes("testdata/pio.bin")[1..]
+        );
+    }
 }
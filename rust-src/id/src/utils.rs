@@ -4,7 +4,7 @@ use crate::{secret_sharing::Threshold, types::*};
 use anyhow::bail;
 use crypto_common::{
     to_bytes,
-    types::{KeyIndex, TransactionTime},
+    types::{CredentialIndex, KeyIndex, TransactionSignature, TransactionTime},
     ParseResult,
 };
 use curve_arithmetic::{multiexp, Curve, Pairing, Value};
@@ -15,7 +15,10 @@ use ff::{Field, PrimeField};
 use pedersen_scheme::Commitment;
 use rand::*;
 use sha2::{Digest, Sha256};
-use std::collections::{btree_map::BTreeMap, BTreeSet};
+use std::{
+    collections::{btree_map::BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
 
 /// Given a list of commitments g^{a_i}h^{r_i}
 /// and a point x (the share number), compute
@@ -72,10 +75,10 @@ pub fn encrypt_prf_share<C: Curve, R: Rng>(
     // The generator for encryption in the exponent is the second component of the
     // commitment key, the 'h'.
     let h = context.encryption_in_exponent_generator();
-    // let mut ciphers = encrypt_in_chunks_given_generator(pk, share, CHUNK_SIZE, h,
-    // csprng);
     let chunks = value_to_chunks::<C>(share, CHUNK_SIZE);
-    let mut ciphers = pk.encrypt_exponent_vec_given_generator(&chunks, h, csprng);
+    // All the chunks are encrypted with the same key and generator, so the
+    // tables for them are computed once.
+    let mut ciphers = PrecomputedPublicKey::new(pk, h).encrypt_exponent_vec(&chunks, csprng);
     // these are guaranteed to exist because we used `ChunkSize::ThirtyTwo`. The
     // encryptions are in little-endian limbs, so the last one is the encryption
     // of the high bits.
@@ -231,6 +234,24 @@ pub fn verify_account_ownership_proof(
     proof_acc_sk: &AccountOwnershipProof,
     msg: &[u8],
 ) -> bool {
+    match account_ownership_signatures(keys, threshold, proof_acc_sk) {
+        Some(sigs) => sigs
+            .into_iter()
+            .all(|(key, sig)| key.verify(msg, sig).is_ok()),
+        None => false,
+    }
+}
+
+/// Pair the signatures inside the AccountOwnershipProof with the keys that
+/// must verify them. This does all the checks of
+/// [verify_account_ownership_proof] except checking the signatures themselves,
+/// so that they can be checked in a batch with other signatures. It returns
+/// `None` if any of the checks fail.
+pub fn account_ownership_signatures<'a>(
+    keys: &'a BTreeMap<KeyIndex, VerifyKey>,
+    threshold: SignatureThreshold,
+    proof_acc_sk: &'a AccountOwnershipProof,
+) -> Option<Vec<(&'a ed25519_dalek::PublicKey, &'a ed25519_dalek::Signature)>> {
     // we check all the keys that were provided, and check enough were provided
     // compared to the threshold
     // We also make sure that no more than 255 keys are provided, as well as
@@ -242,25 +263,108 @@ pub fn verify_account_ownership_proof(
         || keys.is_empty()
         || proof_acc_sk.num_proofs() != SignatureThreshold(keys.len() as u8)
     {
-        return false;
+        return None;
     }
     // set of processed keys already
     let mut processed = BTreeSet::new();
+    let mut sigs = Vec::with_capacity(keys.len());
     for (idx, key) in keys.iter() {
         // insert returns true if key was __not__ present
         if !processed.insert(key) {
-            return false;
+            return None;
+        }
+        let sig = proof_acc_sk.sigs.get(&idx)?;
+        let VerifyKey::Ed25519VerifyKey(ref key) = key;
+        sigs.push((key, &**sig));
+    }
+    Some(sigs)
+}
+
+/// A transaction whose signatures are to be checked, together with the keys of
+/// the sender account.
+#[derive(Clone, Copy)]
+pub struct TransactionToVerify<'a> {
+    /// Keys of the sender account, per credential.
+    pub keys:      &'a BTreeMap<CredentialIndex, CredentialPublicKeys>,
+    /// Number of credentials that must sign the transaction.
+    pub threshold: SignatureThreshold,
+    /// The hash of the transaction that is signed.
+    pub hash:      &'a [u8],
+    /// Signatures on the transaction.
+    pub signature: &'a TransactionSignature,
+}
+
+/// Collect the individual signatures that must be valid for the transaction
+/// to be accepted, together with their keys. Returns `None` if the
+/// transaction cannot be valid irrespective of the validity of individual
+/// signatures, e.g., if there are not enough signatures, or signatures for
+/// keys that do not exist.
+fn transaction_signature_checks(
+    tx: &TransactionToVerify,
+) -> Option<Vec<(ed25519_dalek::Signature, ed25519_dalek::PublicKey)>> {
+    let sigs = &tx.signature.signatures;
+    if sigs.len() < usize::from(tx.threshold.0) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(tx.signature.num_signatures() as usize);
+    for (cred_index, cred_sigs) in sigs.iter() {
+        let cred_keys = tx.keys.get(cred_index)?;
+        if cred_sigs.len() < usize::from(cred_keys.threshold.0) {
+            return None;
+        }
+        for (key_index, sig) in cred_sigs.iter() {
+            let VerifyKey::Ed25519VerifyKey(key) = cred_keys.keys.get(key_index)?;
+            let sig = ed25519_dalek::Signature::try_from(sig.as_ref()).ok()?;
+            out.push((sig, *key));
         }
-        if let Some(sig) = proof_acc_sk.sigs.get(&idx) {
-            let VerifyKey::Ed25519VerifyKey(ref key) = key;
-            if key.verify(msg, &sig).is_err() {
-                return false;
+    }
+    Some(out)
+}
+
+/// Verify the signatures on many transactions at once, using ed25519 batch
+/// verification which is considerably faster than checking each signature
+/// individually. A transaction is valid if
+/// - the number of credentials that signed it is at least the account
+///   threshold,
+/// - for each such credential the number of signatures is at least the
+///   credential threshold, and
+/// - all the signatures are valid signatures on the transaction hash by
+///   existing keys.
+///
+/// The return value contains the validity of each transaction, in the order
+/// they were given.
+pub fn verify_transaction_signatures_batch(transactions: &[TransactionToVerify]) -> Vec<bool> {
+    let checks = transactions
+        .iter()
+        .map(transaction_signature_checks)
+        .collect::<Vec<_>>();
+    let mut msgs: Vec<&[u8]> = Vec::new();
+    let mut sigs = Vec::new();
+    let mut keys = Vec::new();
+    for (tx, check) in transactions.iter().zip(checks.iter()) {
+        if let Some(check) = check {
+            for (sig, key) in check.iter() {
+                msgs.push(tx.hash);
+                sigs.push(*sig);
+                keys.push(*key);
             }
-        } else {
-            return false;
         }
     }
-    true
+    if msgs.is_empty() || ed25519_dalek::verify_batch(&msgs, &sigs, &keys).is_ok() {
+        return checks.iter().map(Option::is_some).collect();
+    }
+    // Some signature is invalid. Batch verification does not tell us which, so
+    // we check each transaction separately.
+    transactions
+        .iter()
+        .zip(checks.iter())
+        .map(|(tx, check)| match check {
+            Some(check) => check
+                .iter()
+                .all(|(sig, key)| key.verify(tx.hash, sig).is_ok()),
+            None => false,
+        })
+        .collect()
 }
 
 /// Compute the hash of the credential deployment that should be signed by the
@@ -359,4 +463,58 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    pub fn test_verify_transaction_signatures_batch() {
+        use crypto_common::types::KeyPair;
+        let mut csprng = thread_rng();
+        let kps = (0..3)
+            .map(|_| KeyPair::generate(&mut csprng))
+            .collect::<Vec<_>>();
+        let mut keys = BTreeMap::new();
+        keys.insert(CredentialIndex { index: 0 }, CredentialPublicKeys {
+            keys:      kps
+                .iter()
+                .enumerate()
+                .map(|(i, kp)| (KeyIndex(i as u8), VerifyKey::Ed25519VerifyKey(kp.public)))
+                .collect(),
+            threshold: SignatureThreshold(2),
+        });
+        let hashes = (0..4).map(|_| csprng.gen::<[u8; 32]>()).collect::<Vec<_>>();
+        // sign each hash with the given number of keys
+        let sign = |hash: &[u8], n: usize| {
+            let mut cred_sigs = BTreeMap::new();
+            for (i, kp) in kps.iter().enumerate().take(n) {
+                cred_sigs.insert(KeyIndex(i as u8), kp.sign(hash));
+            }
+            let mut signatures = BTreeMap::new();
+            signatures.insert(CredentialIndex { index: 0 }, cred_sigs);
+            TransactionSignature { signatures }
+        };
+        let sigs = vec![
+            sign(&hashes[0], 3),
+            // not enough signatures for the credential
+            sign(&hashes[1], 1),
+            // signature on the wrong hash
+            sign(&hashes[0], 2),
+            sign(&hashes[3], 2),
+        ];
+        let transactions = hashes
+            .iter()
+            .zip(sigs.iter())
+            .map(|(hash, signature)| TransactionToVerify {
+                keys: &keys,
+                threshold: SignatureThreshold(1),
+                hash,
+                signature,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(verify_transaction_signatures_batch(&transactions), vec![
+            true, false, false, true
+        ]);
+        assert_eq!(
+            verify_transaction_signatures_batch(&[transactions[3], transactions[0]]),
+            vec![true, true]
+        );
+    }
 }
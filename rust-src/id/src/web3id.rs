@@ -0,0 +1,165 @@
+//! Presentations of credentials in the style of Web3 ID, for identity
+//! verification by dApps that happens off chain.
+//!
+//! The verifier sends a [Request] to the wallet: a challenge, statements about
+//! the attributes of some of the credentials of the user, and accounts the user
+//! must prove to own. The wallet answers with a single [Presentation] that
+//! contains the proofs of the statements and the proofs of ownership of the
+//! accounts. It is produced with
+//! [prove_presentation](crate::id_prover::prove_presentation), and checked
+//! against the credentials and account keys on the chain with
+//! [verify_presentation](crate::id_verifier::verify_presentation).
+//!
+//! All the proofs of a presentation are made for the same challenge, which is
+//! derived from the challenge of the verifier, the context of the proofs, and
+//! the credentials and accounts of the request. Proofs can therefore not be
+//! taken out of one presentation and put into another.
+use crate::{
+    account_ownership::{AccountOwnershipProofs, OwnershipChallenge},
+    chunked::{hex_decode, hex_encode},
+    statement::*,
+    types::*,
+};
+use crypto_common::*;
+use crypto_common_derive::*;
+use curve_arithmetic::Curve;
+use random_oracle::{domains, ChallengeContext, RandomOracle};
+use thiserror::Error;
+
+/// The version of presentations that this library produces. See
+/// [Presentation::from_versioned].
+pub const PRESENTATION_VERSION: Version = VERSION_0;
+
+/// A statement about the attributes of a credential, which is identified by
+/// its registration id.
+#[derive(Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialStatement<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub cred_id:   C,
+    pub statement: Statement<C, AttributeType>,
+}
+
+/// What a verifier asks the wallet to prove. The wallet proves the statements
+/// and the ownership of the accounts in the order they are given. Requests are
+/// sent to the wallet either serialized or as JSON, in which the challenge is
+/// hex encoded.
+#[derive(Debug, Clone, Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(bound(
+    serialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeSerialize",
+    deserialize = "C: Curve, AttributeType: Attribute<C::Scalar> + SerdeDeserialize<'de>"
+))]
+#[serde(rename_all = "camelCase")]
+pub struct Request<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// The challenge of the verifier. It should be fresh for every request, so
+    /// that presentations cannot be replayed.
+    #[size_length = 4]
+    #[max_size = 1024]
+    #[serde(serialize_with = "hex_encode", deserialize_with = "hex_decode")]
+    pub challenge:             Vec<u8>,
+    #[size_length = 2]
+    pub credential_statements: Vec<CredentialStatement<C, AttributeType>>,
+    #[size_length = 2]
+    pub accounts:              Vec<AccountAddress>,
+}
+
+/// The proof of the statement about a credential.
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialProof<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    pub cred_id: C,
+    pub proof:   StatementProof<C, AttributeType>,
+}
+
+/// The proof of ownership of an account, with the signatures of the
+/// credentials of the account that the wallet holds keys for.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountProof {
+    pub account: AccountAddress,
+    #[map_size_length = 1]
+    pub proof:   AccountOwnershipProofs,
+}
+
+/// The answer of the wallet to a [Request]. It is exchanged as a
+/// [Versioned] value, see [Presentation::to_versioned].
+#[derive(Debug, Clone, Serialize, SerdeBase16Serialize)]
+pub struct Presentation<C: Curve, AttributeType: Attribute<C::Scalar>> {
+    /// The challenge of the request the presentation answers.
+    #[size_length = 4]
+    #[max_size = 1024]
+    pub challenge:         Vec<u8>,
+    /// The proofs of the statements, in the order of the request.
+    #[size_length = 2]
+    pub credential_proofs: Vec<CredentialProof<C, AttributeType>>,
+    /// The proofs of ownership of the accounts, in the order of the request.
+    #[size_length = 2]
+    pub account_proofs:    Vec<AccountProof>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+/// Reasons why a presentation cannot be produced, or is not valid.
+pub enum PresentationError {
+    #[error("Unsupported presentation version {0}.")]
+    UnsupportedVersion(Version),
+    #[error("The presentation is for another challenge.")]
+    ChallengeMismatch,
+    #[error("The number of credentials does not match the request.")]
+    CredentialCountMismatch,
+    #[error("The credential with index {0} is not the one of the request.")]
+    CredentialMismatch(usize),
+    #[error("The proof of the statement about the credential with index {0} is not valid.")]
+    InvalidCredentialProof(usize),
+    #[error("The number of accounts does not match the request.")]
+    AccountCountMismatch,
+    #[error("The account {0} is not the one of the request.")]
+    AccountMismatch(AccountAddress),
+    #[error("The proof of ownership of account {0} is not valid.")]
+    InvalidAccountProof(AccountAddress),
+    #[error("{0}")]
+    Statement(#[from] StatementError),
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Presentation<C, AttributeType> {
+    /// Wrap the presentation with the current [PRESENTATION_VERSION].
+    pub fn to_versioned(self) -> Versioned<Self> { Versioned::new(PRESENTATION_VERSION, self) }
+
+    /// Unwrap a versioned presentation, e.g., one received from a wallet.
+    /// This fails if the version is not supported.
+    pub fn from_versioned(versioned: Versioned<Self>) -> Result<Self, PresentationError> {
+        if versioned.version != PRESENTATION_VERSION {
+            return Err(PresentationError::UnsupportedVersion(versioned.version));
+        }
+        Ok(versioned.value)
+    }
+}
+
+impl<C: Curve, AttributeType: Attribute<C::Scalar>> Request<C, AttributeType> {
+    /// The challenge that all the proofs of the presentation are made for. It
+    /// is the challenge of the verifier together with the context, the
+    /// registration ids of the credentials and the accounts of the request.
+    pub(crate) fn proof_challenge(&self, context: &ChallengeContext) -> Vec<u8> {
+        let mut ro = RandomOracle::domain_in_context(context, domains::WEB3ID_PRESENTATION);
+        ro.append_message("challenge", &self.challenge);
+        let cred_ids: Vec<C> = self
+            .credential_statements
+            .iter()
+            .map(|s| s.cred_id)
+            .collect();
+        ro.append_message("cred_ids", &cred_ids);
+        ro.append_message("accounts", &self.accounts);
+        ro.get_challenge().as_ref().to_vec()
+    }
+
+    /// The challenge that the keys of the accounts sign to prove ownership. Its
+    /// nonce is the [proof challenge](Request::proof_challenge), so that the
+    /// signatures are only valid for this request.
+    pub(crate) fn ownership_challenge(&self, context: &ChallengeContext) -> OwnershipChallenge {
+        OwnershipChallenge {
+            verifier: domains::WEB3ID_PRESENTATION.to_owned(),
+            nonce:    self.proof_challenge(context),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Conversion between entropy and mnemonic phrases as specified by
+//! [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki).
+//!
+//! A phrase encodes the entropy followed by a checksum of `entropy_bits / 32`
+//! bits, which are the first bits of the SHA-256 hash of the entropy. Every
+//! word encodes 11 bits as its index in a list of 2048 words. The seed that
+//! keys are derived from is computed from the phrase with
+//! [seed_from_mnemonic](crate::seed_from_mnemonic).
+use crypto_common::{volatile_overwrite, Zeroize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// The English word list of BIP-39, one word per line.
+pub const ENGLISH: &str = include_str!("../data/BIP39English.txt");
+
+/// The number of words in a word list.
+pub const WORD_LIST_SIZE: usize = 2048;
+
+/// The number of bits encoded by a word.
+const BITS_PER_WORD: usize = 11;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum Bip39Error {
+    #[error(
+        "The word list must contain {} distinct words, but {0}.",
+        WORD_LIST_SIZE
+    )]
+    InvalidWordList(String),
+    #[error("The entropy must be 16, 20, 24, 28 or 32 bytes, but is {0} bytes.")]
+    InvalidEntropyLength(usize),
+    #[error("A phrase must have 12, 15, 18, 21 or 24 words, but has {0} words.")]
+    InvalidWordCount(usize),
+    #[error("The word '{0}' is not in the word list.")]
+    UnknownWord(String),
+    #[error("The checksum of the phrase is incorrect.")]
+    InvalidChecksum,
+}
+
+/// A list of 2048 distinct words, such as one of the lists of BIP-39.
+#[derive(Debug, Clone)]
+pub struct WordList {
+    words: Vec<String>,
+    index: HashMap<String, u16>,
+}
+
+impl WordList {
+    /// Construct a word list from the given words, in order.
+    pub fn new(words: Vec<String>) -> Result<Self, Bip39Error> {
+        if words.len() != WORD_LIST_SIZE {
+            return Err(Bip39Error::InvalidWordList(format!(
+                "it contains {} words",
+                words.len()
+            )));
+        }
+        let mut index = HashMap::with_capacity(WORD_LIST_SIZE);
+        for (i, word) in words.iter().enumerate() {
+            if word.is_empty() || word.chars().any(char::is_whitespace) {
+                return Err(Bip39Error::InvalidWordList(format!(
+                    "'{}' is not a word",
+                    word
+                )));
+            }
+            if index.insert(word.clone(), i as u16).is_some() {
+                return Err(Bip39Error::InvalidWordList(format!(
+                    "'{}' appears more than once",
+                    word
+                )));
+            }
+        }
+        Ok(WordList { words, index })
+    }
+
+    /// Parse a word list with the words separated by whitespace, e.g., one
+    /// word per line.
+    pub fn parse(list: &str) -> Result<Self, Bip39Error> {
+        Self::new(list.split_whitespace().map(String::from).collect())
+    }
+
+    /// The English word list of BIP-39.
+    pub fn english() -> Self { Self::parse(ENGLISH).expect("The English word list is valid.") }
+
+    /// The words of the list, in order.
+    pub fn words(&self) -> &[String] { &self.words }
+}
+
+/// The checksum of the entropy, i.e., the first `entropy.len() / 4` bits of
+/// its hash.
+fn checksum_bits(entropy: &[u8]) -> impl Iterator<Item = bool> {
+    let hash = Sha256::digest(entropy);
+    let cs_len = entropy.len() / 4;
+    (0..cs_len).map(move |i| hash[i / 8] & (0x80 >> (i % 8)) != 0)
+}
+
+/// Encode the entropy as a phrase with words from the given list. The words
+/// are returned in order, and form the phrase when joined by spaces.
+pub fn entropy_to_mnemonic(entropy: &[u8], words: &WordList) -> Result<Vec<String>, Bip39Error> {
+    match entropy.len() {
+        16 | 20 | 24 | 28 | 32 => (),
+        n => return Err(Bip39Error::InvalidEntropyLength(n)),
+    }
+    let bits = entropy
+        .iter()
+        .flat_map(|b| (0..8).map(move |i| b & (0x80 >> i) != 0))
+        .chain(checksum_bits(entropy))
+        .collect::<Vec<_>>();
+    Ok(bits
+        .chunks(BITS_PER_WORD)
+        .map(|chunk| {
+            let i = chunk
+                .iter()
+                .fold(0usize, |acc, &bit| (acc << 1) | usize::from(bit));
+            words.words[i].clone()
+        })
+        .collect())
+}
+
+/// Decode a phrase to the entropy it encodes, checking that all words are in
+/// the given list and that the checksum is correct. The words may be
+/// separated by any whitespace. The intermediate bits are overwritten, and
+/// the caller should overwrite the returned entropy when it is no longer
+/// needed.
+pub fn mnemonic_to_entropy(mnemonic: &str, words: &WordList) -> Result<Vec<u8>, Bip39Error> {
+    let phrase = mnemonic.split_whitespace().collect::<Vec<_>>();
+    match phrase.len() {
+        12 | 15 | 18 | 21 | 24 => (),
+        n => return Err(Bip39Error::InvalidWordCount(n)),
+    }
+    let mut bits = Vec::with_capacity(phrase.len() * BITS_PER_WORD);
+    for word in phrase {
+        let i = match words.index.get(word) {
+            Some(i) => *i,
+            None => return Err(Bip39Error::UnknownWord(word.to_owned())),
+        };
+        bits.extend((0..BITS_PER_WORD).rev().map(|j| i & (1 << j) != 0));
+    }
+    // The phrase encodes `ent_len` bits of entropy and `ent_len / 32` bits of
+    // checksum.
+    let ent_len = 32 * bits.len() / 33;
+    let mut entropy = bits[..ent_len]
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))
+        })
+        .collect::<Vec<_>>();
+    let valid = checksum_bits(&entropy).eq(bits[ent_len..].iter().copied());
+    for bit in bits.iter_mut() {
+        volatile_overwrite(bit, false);
+    }
+    if !valid {
+        entropy.zeroize();
+        return Err(Bip39Error::InvalidChecksum);
+    }
+    Ok(entropy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vectors from the reference implementation of BIP-39.
+    const VECTORS: &[(&str, &str)] = &[
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        ),
+        (
+            "8080808080808080808080808080808080808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+             amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when",
+        ),
+    ];
+
+    #[test]
+    fn test_bip39_vectors() {
+        let words = WordList::english();
+        for (entropy, phrase) in VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            assert_eq!(
+                entropy_to_mnemonic(&entropy, &words).unwrap().join(" "),
+                *phrase
+            );
+            assert_eq!(mnemonic_to_entropy(phrase, &words), Ok(entropy));
+        }
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        let words = WordList::english();
+        assert_eq!(
+            mnemonic_to_entropy("abandon abandon about", &words),
+            Err(Bip39Error::InvalidWordCount(3))
+        );
+        assert_eq!(
+            mnemonic_to_entropy(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                 abandon abandon",
+                &words
+            ),
+            Err(Bip39Error::InvalidChecksum)
+        );
+        assert_eq!(
+            mnemonic_to_entropy(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                 abandon concordium",
+                &words
+            ),
+            Err(Bip39Error::UnknownWord("concordium".into()))
+        );
+        assert_eq!(
+            entropy_to_mnemonic(&[0u8; 15], &words),
+            Err(Bip39Error::InvalidEntropyLength(15))
+        );
+        assert!(WordList::parse("abandon ability").is_err());
+    }
+}
@@ -0,0 +1,517 @@
+//! Hierarchical deterministic derivation of wallet keys.
+//!
+//! Keys are derived from a seed following
+//! [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md)
+//! for the ed25519 curve. Only hardened derivation is defined for ed25519, so
+//! every index in a [DerivationPath] is hardened.
+//!
+//! Concordium wallets derive all of their keys below the path
+//! `m/44'/coin_type'`, where the coin type is determined by the [Net]. Wallets
+//! that use the same seed and the same paths derive the same keys, which is
+//! what makes funds recoverable across wallet implementations.
+pub mod bip39;
+
+use crypto_common::Zeroize;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+/// Offset that is added to an index to mark it as hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// The purpose field of the path, as defined by BIP-44.
+pub const PURPOSE: u32 = 44;
+
+/// The coin type used on mainnet, as registered in SLIP-0044.
+pub const MAINNET_COIN_TYPE: u32 = 919;
+
+/// The coin type used on testnet. This is the coin type shared by all
+/// testnets.
+pub const TESTNET_COIN_TYPE: u32 = 1;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DerivationError {
+    #[error("Invalid derivation path '{0}'.")]
+    InvalidPath(String),
+    #[error("Index {0} is out of range. Indices must be less than 2^31.")]
+    IndexOutOfRange(u32),
+    #[error("Index {0} is not hardened. Only hardened derivation is supported for ed25519.")]
+    NotHardened(u32),
+    #[error("Unknown network '{0}'.")]
+    UnknownNet(String),
+}
+
+/// The network that keys are derived for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Net {
+    Mainnet,
+    Testnet,
+}
+
+impl Net {
+    /// The coin type of the network, i.e., the second index of every
+    /// derivation path.
+    pub fn coin_type(self) -> u32 {
+        match self {
+            Net::Mainnet => MAINNET_COIN_TYPE,
+            Net::Testnet => TESTNET_COIN_TYPE,
+        }
+    }
+}
+
+impl FromStr for Net {
+    type Err = DerivationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Net::Mainnet),
+            "testnet" => Ok(Net::Testnet),
+            _ => Err(DerivationError::UnknownNet(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Net {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Net::Mainnet => write!(f, "Mainnet"),
+            Net::Testnet => write!(f, "Testnet"),
+        }
+    }
+}
+
+/// A derivation path, such as `m/44'/919'/0'`. The indices are stored without
+/// the [HARDENED_OFFSET], and are all less than 2^31.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Construct a path from the given indices, which are interpreted as
+    /// hardened.
+    pub fn new(indices: Vec<u32>) -> Result<Self, DerivationError> {
+        if let Some(&i) = indices.iter().find(|&&i| i >= HARDENED_OFFSET) {
+            return Err(DerivationError::IndexOutOfRange(i));
+        }
+        Ok(DerivationPath { indices })
+    }
+
+    /// Construct the path `m/44'/coin_type'/rest...` used by Concordium
+    /// wallets on the given network.
+    pub fn concordium(net: Net, rest: &[u32]) -> Result<Self, DerivationError> {
+        let mut indices = Vec::with_capacity(rest.len() + 2);
+        indices.push(PURPOSE);
+        indices.push(net.coin_type());
+        indices.extend_from_slice(rest);
+        Self::new(indices)
+    }
+
+    /// The indices of the path, without the hardened offset.
+    pub fn indices(&self) -> &[u32] { &self.indices }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationError;
+
+    /// Parse a path of the form `m/i_1'/i_2'/...`. Hardened indices can be
+    /// marked either with `'` or with `H`. Non-hardened indices are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(DerivationError::InvalidPath(s.to_owned()));
+        }
+        let mut indices = Vec::new();
+        for part in parts {
+            let (index, hardened) = match part.strip_suffix('\'').or_else(|| part.strip_suffix('H'))
+            {
+                Some(index) => (index, true),
+                None => (part, false),
+            };
+            let index: u32 = index
+                .parse()
+                .map_err(|_| DerivationError::InvalidPath(s.to_owned()))?;
+            if !hardened {
+                return Err(DerivationError::NotHardened(index));
+            }
+            indices.push(index);
+        }
+        Self::new(indices)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for i in self.indices.iter() {
+            write!(f, "/{}'", i)?;
+        }
+        Ok(())
+    }
+}
+
+/// A private key together with its chain code. The private key is an ed25519
+/// secret key.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    pub private_key: [u8; 32],
+    pub chain_code:  [u8; 32],
+}
+
+impl Zeroize for ExtendedKey {
+    fn zeroize(&mut self) {
+        self.private_key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+/// Overwrite the key and the chain code when the key goes out of scope. The
+/// keys of the intermediate nodes of a path are secret too, since the keys of
+/// all their descendants can be derived from them.
+impl Drop for ExtendedKey {
+    fn drop(&mut self) { self.zeroize() }
+}
+
+impl ExtendedKey {
+    /// Derive the master key from the seed.
+    pub fn master(seed: &[u8]) -> Self { Self::from_hmac(b"ed25519 seed", &[seed]) }
+
+    /// Derive the hardened child with the given index. The index must be less
+    /// than 2^31, the hardened offset is added by this function.
+    pub fn derive_child(&self, index: u32) -> Result<Self, DerivationError> {
+        if index >= HARDENED_OFFSET {
+            return Err(DerivationError::IndexOutOfRange(index));
+        }
+        let index_bytes = (index | HARDENED_OFFSET).to_be_bytes();
+        Ok(Self::from_hmac(&self.chain_code, &[
+            &[0u8],
+            &self.private_key,
+            &index_bytes,
+        ]))
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Self {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC can take key of any size");
+        for d in data {
+            mac.update(d);
+        }
+        let mut bytes = mac.finalize().into_bytes();
+        let mut private_key = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        private_key.copy_from_slice(&bytes[..32]);
+        chain_code.copy_from_slice(&bytes[32..]);
+        bytes[..].zeroize();
+        ExtendedKey {
+            private_key,
+            chain_code,
+        }
+    }
+}
+
+/// Derive the key at the given path from the seed.
+pub fn derive_from_path(seed: &[u8], path: &DerivationPath) -> ExtendedKey {
+    path.indices
+        .iter()
+        .fold(ExtendedKey::master(seed), |key, &i| {
+            key.derive_child(i)
+                .expect("Indices of a derivation path are in range.")
+        })
+}
+
+/// The number of iterations of PBKDF2 used to derive the seed from a
+/// mnemonic, as specified by BIP-39.
+const BIP39_ITERATIONS: u32 = 2048;
+
+/// Derive the 64-byte seed from a BIP-39 mnemonic phrase and an optional
+/// passphrase, which is the empty string if not used. The seed is computed
+/// with PBKDF2-HMAC-SHA512 as specified by
+/// [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki).
+///
+/// Words may be separated by any whitespace. The phrase and the passphrase
+/// are converted to Unicode normalization form NFKD, as required by BIP-39, so
+/// that the same seed is computed however the input is encoded. This function
+/// does not check that the words are in a word list or that the checksum is
+/// correct, see [bip39::mnemonic_to_entropy] for that.
+pub fn seed_from_mnemonic(mnemonic: &str, passphrase: &str) -> [u8; 64] {
+    let mut joined = mnemonic.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut phrase = joined.nfkd().collect::<String>();
+    joined.zeroize();
+    let mut salt = "mnemonic"
+        .chars()
+        .chain(passphrase.nfkd())
+        .collect::<String>();
+    let mut seed = [0u8; 64];
+    pbkdf2::pbkdf2::<Hmac<Sha512>>(
+        phrase.as_bytes(),
+        salt.as_bytes(),
+        BIP39_ITERATIONS,
+        &mut seed,
+    );
+    phrase.zeroize();
+    salt.zeroize();
+    seed
+}
+
+/// The key derivation scheme of Concordium wallets. All keys of an identity
+/// are derived below the path `m/44'/coin_type'/ip'/identity'`, where `ip` is
+/// the index of the identity provider that issued the identity, and
+/// `identity` is the index of the identity among those that the wallet has
+/// created with that identity provider. Below that path
+///
+/// - `0'/credential'` is the signing key of the account that is created with
+///   credential number `credential` of the identity,
+/// - `2'` is the seed of `id_cred_sec` of the identity,
+/// - `3'` is the seed of the PRF key of the identity,
+/// - `4'` is the seed of the randomness used to blind the signature of the
+///   identity provider.
+///
+/// The account signing keys are ed25519 keys. The remaining values are
+/// scalars of the BLS12-381 curve, which are obtained by using the derived
+/// key as input keying material for `keygen_bls`. The encryption keys of the
+/// accounts are not derived separately, since they are determined by the PRF
+/// key and the credential number.
+pub struct ConcordiumHdWallet {
+    pub seed: [u8; 64],
+    pub net:  Net,
+}
+
+impl Zeroize for ConcordiumHdWallet {
+    fn zeroize(&mut self) { self.seed.zeroize() }
+}
+
+/// Overwrite the seed when the wallet goes out of scope, so that it is cleared
+/// on every path out of the function that uses it, including errors.
+impl Drop for ConcordiumHdWallet {
+    fn drop(&mut self) { self.zeroize() }
+}
+
+/// Index below the identity path of the account signing keys.
+const ACCOUNT_SIGNING_KEY_INDEX: u32 = 0;
+/// Index below the identity path of the seed of `id_cred_sec`.
+const ID_CRED_SEC_INDEX: u32 = 2;
+/// Index below the identity path of the seed of the PRF key.
+const PRF_KEY_INDEX: u32 = 3;
+/// Index below the identity path of the seed of the blinding randomness.
+const BLINDING_RANDOMNESS_INDEX: u32 = 4;
+
+impl ConcordiumHdWallet {
+    /// Construct a wallet from a BIP-39 mnemonic and passphrase, see
+    /// [seed_from_mnemonic].
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str, net: Net) -> Self {
+        ConcordiumHdWallet {
+            seed: seed_from_mnemonic(mnemonic, passphrase),
+            net,
+        }
+    }
+
+    fn identity_path(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+        rest: &[u32],
+    ) -> Result<DerivationPath, DerivationError> {
+        let mut indices = vec![identity_provider_index, identity_index];
+        indices.extend_from_slice(rest);
+        DerivationPath::concordium(self.net, &indices)
+    }
+
+    fn derive(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+        rest: &[u32],
+    ) -> Result<[u8; 32], DerivationError> {
+        let path = self.identity_path(identity_provider_index, identity_index, rest)?;
+        Ok(derive_from_path(&self.seed, &path).private_key)
+    }
+
+    /// The path of the signing key of the account created with the given
+    /// credential of the identity.
+    pub fn account_signing_key_path(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+        credential_counter: u32,
+    ) -> Result<DerivationPath, DerivationError> {
+        self.identity_path(identity_provider_index, identity_index, &[
+            ACCOUNT_SIGNING_KEY_INDEX,
+            credential_counter,
+        ])
+    }
+
+    /// The ed25519 secret key of the account created with the given
+    /// credential of the identity.
+    pub fn get_account_signing_key(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+        credential_counter: u32,
+    ) -> Result<[u8; 32], DerivationError> {
+        self.derive(identity_provider_index, identity_index, &[
+            ACCOUNT_SIGNING_KEY_INDEX,
+            credential_counter,
+        ])
+    }
+
+    /// The key material from which `id_cred_sec` of the identity is
+    /// generated.
+    pub fn get_id_cred_sec_seed(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+    ) -> Result<[u8; 32], DerivationError> {
+        self.derive(identity_provider_index, identity_index, &[
+            ID_CRED_SEC_INDEX,
+        ])
+    }
+
+    /// The key material from which the PRF key of the identity is generated.
+    pub fn get_prf_key_seed(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+    ) -> Result<[u8; 32], DerivationError> {
+        self.derive(identity_provider_index, identity_index, &[PRF_KEY_INDEX])
+    }
+
+    /// The key material from which the randomness that blinds the signature
+    /// of the identity provider is generated.
+    pub fn get_blinding_randomness_seed(
+        &self,
+        identity_provider_index: u32,
+        identity_index: u32,
+    ) -> Result<[u8; 32], DerivationError> {
+        self.derive(identity_provider_index, identity_index, &[
+            BLINDING_RANDOMNESS_INDEX,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Test vectors for ed25519. The `slip10` vectors are test vectors 1 and 2
+    /// of the SLIP-0010 specification. The `concordium` vectors are for paths
+    /// of the Concordium scheme on mainnet and testnet.
+    const VECTORS: &str = include_str!("../test-vectors/ed25519.json");
+
+    fn check_vector(v: &Value) {
+        let seed = hex::decode(v["seed"].as_str().unwrap()).unwrap();
+        let path: DerivationPath = v["path"].as_str().unwrap().parse().expect("Valid path.");
+        assert_eq!(path.to_string(), v["path"].as_str().unwrap());
+        let key = derive_from_path(&seed, &path);
+        assert_eq!(
+            hex::encode(key.private_key),
+            v["privateKey"].as_str().unwrap(),
+            "{}",
+            path
+        );
+        assert_eq!(
+            hex::encode(key.chain_code),
+            v["chainCode"].as_str().unwrap(),
+            "{}",
+            path
+        );
+    }
+
+    #[test]
+    fn test_slip10_vectors() {
+        let vectors: Value = serde_json::from_str(VECTORS).unwrap();
+        for v in vectors["slip10"].as_array().unwrap() {
+            check_vector(v);
+        }
+    }
+
+    #[test]
+    fn test_concordium_vectors() {
+        let vectors: Value = serde_json::from_str(VECTORS).unwrap();
+        for v in vectors["concordium"].as_array().unwrap() {
+            check_vector(v);
+            let net: Net = v["net"].as_str().unwrap().parse().unwrap();
+            let path: DerivationPath = v["path"].as_str().unwrap().parse().unwrap();
+            let rest = &path.indices()[2..];
+            assert_eq!(DerivationPath::concordium(net, rest), Ok(path));
+        }
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!("m".parse::<DerivationPath>(), Ok(DerivationPath::default()));
+        assert_eq!(
+            "m/44H/1H".parse::<DerivationPath>(),
+            DerivationPath::concordium(Net::Testnet, &[])
+        );
+        assert_eq!(
+            "m/44'/0".parse::<DerivationPath>(),
+            Err(DerivationError::NotHardened(0))
+        );
+        assert_eq!(
+            "m/2147483648'".parse::<DerivationPath>(),
+            Err(DerivationError::IndexOutOfRange(HARDENED_OFFSET))
+        );
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/x'".parse::<DerivationPath>().is_err());
+    }
+
+    #[test]
+    fn test_seed_from_mnemonic() {
+        // Test vector from the reference implementation of BIP-39.
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        assert_eq!(
+            hex::encode(&seed_from_mnemonic(mnemonic, "TREZOR")[..]),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+        // The passphrase is normalized, so the composed and the decomposed form
+        // of "café" give the same seed. The expected seed is computed with the
+        // NFKD form.
+        let expected = "af8bbd2566df7b69d926f2b09dfdbd75db6c994a3399b2cc65f928d63e3fd4e61218ee0d15f8c810be4d45e66d47b43c15a5cc753976b1666912377ff7ae9818";
+        assert_eq!(
+            hex::encode(&seed_from_mnemonic(mnemonic, "caf\u{e9}")[..]),
+            expected
+        );
+        assert_eq!(
+            hex::encode(&seed_from_mnemonic(mnemonic, "cafe\u{301}")[..]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_zeroize_extended_key() {
+        let mut key = ExtendedKey::master(&[1u8; 64]);
+        assert_ne!(key.private_key, [0u8; 32]);
+        key.zeroize();
+        assert_eq!(key.private_key, [0u8; 32]);
+        assert_eq!(key.chain_code, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_concordium_scheme() {
+        let wallet = ConcordiumHdWallet {
+            seed: [7u8; 64],
+            net:  Net::Testnet,
+        };
+        let path = wallet.account_signing_key_path(1, 2, 3).unwrap();
+        assert_eq!(path.to_string(), "m/44'/1'/1'/2'/0'/3'");
+        assert_eq!(
+            wallet.get_account_signing_key(1, 2, 3).unwrap(),
+            derive_from_path(&wallet.seed, &path).private_key
+        );
+        let id_cred_sec = wallet.get_id_cred_sec_seed(1, 2).unwrap();
+        let prf_key = wallet.get_prf_key_seed(1, 2).unwrap();
+        assert_ne!(id_cred_sec, prf_key);
+        assert_ne!(prf_key, wallet.get_prf_key_seed(1, 3).unwrap());
+        assert_eq!(
+            wallet.get_id_cred_sec_seed(HARDENED_OFFSET, 0),
+            Err(DerivationError::IndexOutOfRange(HARDENED_OFFSET))
+        );
+
+        let mut wallet = wallet;
+        wallet.zeroize();
+        assert_eq!(wallet.seed, [0u8; 64]);
+    }
+}
@@ -67,6 +67,50 @@ impl<C: Curve> CommitmentKey<C> {
     }
 }
 
+/// A commitment key together with a precomputed table for its bases, for
+/// committing to many values with the same key.
+#[derive(Debug, Clone)]
+pub struct PrecomputedCommitmentKey<C: Curve> {
+    key:   CommitmentKey<C>,
+    table: MultiExpTable<C>,
+}
+
+impl<C: Curve> From<&CommitmentKey<C>> for PrecomputedCommitmentKey<C> {
+    fn from(key: &CommitmentKey<C>) -> Self {
+        PrecomputedCommitmentKey {
+            key:   *key,
+            table: MultiExpTable::new(&[key.g, key.h]),
+        }
+    }
+}
+
+impl<C: Curve> PrecomputedCommitmentKey<C> {
+    /// The commitment key the table is for.
+    pub fn key(&self) -> &CommitmentKey<C> { &self.key }
+
+    /// Same as [CommitmentKey::hide_worker], using the precomputed table.
+    pub fn hide_worker(&self, value: &C::Scalar, randomness: &C::Scalar) -> Commitment<C> {
+        Commitment(self.table.multiexp(&[*value, *randomness]))
+    }
+
+    /// Same as [CommitmentKey::hide], using the precomputed table.
+    pub fn hide<V: AsRef<C::Scalar>>(&self, s: &V, r: &Randomness<C>) -> Commitment<C> {
+        self.hide_worker(s.as_ref(), r.as_ref())
+    }
+
+    /// Same as [CommitmentKey::commit], using the precomputed table.
+    pub fn commit<T, V: AsRef<C::Scalar>>(
+        &self,
+        s: &V,
+        csprng: &mut T,
+    ) -> (Commitment<C>, Randomness<C>)
+    where
+        T: Rng, {
+        let r = Randomness::<C>::generate(csprng);
+        (self.hide(s, &r), r)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,4 +158,16 @@ mod tests {
 
     macro_test_commit_open!(commit_open_bls12_381_g2_affine, G2Affine);
     macro_test_commit_open!(commit_open_bls12_381_g2_projective, G2);
+
+    #[test]
+    pub fn test_precomputed_key() {
+        let mut csprng = thread_rng();
+        let key = CommitmentKey::<G1>::generate(&mut csprng);
+        let precomputed = PrecomputedCommitmentKey::from(&key);
+        for _i in 1..100 {
+            let v = Value::<G1>::generate(&mut csprng);
+            let r = Randomness::<G1>::generate(&mut csprng);
+            assert_eq!(precomputed.hide(&v, &r), key.hide(&v, &r));
+        }
+    }
 }
@@ -53,13 +53,7 @@ impl<C: Pairing> PublicKey<C> {
         if sig.0.is_zero_point() || ms.len() > ys.len() {
             return false;
         }
-        let h = ys
-            .iter()
-            .zip(ms.iter())
-            .fold(C::G2::zero_point(), |acc, (y, m)| {
-                let ym = y.mul_by_scalar(&m);
-                acc.plus_point(&ym)
-            });
+        let h = multiexp(&ys[..ms.len()], ms);
         let hx = h.plus_point(&x);
         C::check_pairing_eq(&sig.0, &hx, &sig.1, &self.g_tilda)
     }
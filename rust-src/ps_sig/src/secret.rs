@@ -35,6 +35,21 @@ impl<C: Pairing> PartialEq for SecretKey<C> {
 
 impl<C: Pairing> Eq for SecretKey<C> {}
 
+/// Only the scalars are overwritten, the generators are not secret.
+impl<C: Pairing> Zeroize for SecretKey<C> {
+    fn zeroize(&mut self) {
+        for y in self.ys.iter_mut() {
+            volatile_overwrite(y, C::ScalarField::zero());
+        }
+        volatile_overwrite(&mut self.x, C::ScalarField::zero());
+    }
+}
+
+/// Overwrite secret key material with zeros when it goes out of scope.
+impl<C: Pairing> Drop for SecretKey<C> {
+    fn drop(&mut self) { self.zeroize() }
+}
+
 impl<C: Pairing> SecretKey<C> {
     /// Generate a secret key from a `csprng`. NB: This fixes the generators to
     /// be those defined by the library.
@@ -1,5 +1,6 @@
 use crate::unknown_message::SigRetrievalRandomness;
 use curve_arithmetic::*;
+use ff::Field;
 use rand::*;
 
 use crypto_common::*;
@@ -55,7 +56,10 @@ impl<C: Pairing> Signature<C> {
         let t = C::generate_non_zero_scalar(csprng);
         let Signature(a, b) = self;
         let a_hid = a.mul_by_scalar(&r);
-        let b_hid = b.plus_point(&a.mul_by_scalar(&t)).mul_by_scalar(&r);
+        // (b + t * a) * r
+        let mut tr = t;
+        tr.mul_assign(&r);
+        let b_hid = multiexp(&[*b, *a], &[r, tr]);
         let sig = Signature(a_hid, b_hid);
         let randomness = BlindingRandomness(Secret::new(r), Secret::new(t));
         (BlindedSignature { sig }, randomness)
@@ -1,5 +1,6 @@
 use crypto_common::*;
 use curve_arithmetic::*;
+use ff::Field;
 use pedersen_scheme::Commitment;
 
 use rand::*;
@@ -40,6 +41,13 @@ pub struct SigRetrievalRandomness<P: Pairing> {
     pub randomness: Rc<Secret<P::ScalarField>>,
 }
 
+/// The randomness is replaced by fresh zero randomness. The old value is
+/// overwritten in memory when the last reference to it is dropped, see
+/// [Secret].
+impl<C: Pairing> Zeroize for SigRetrievalRandomness<C> {
+    fn zeroize(&mut self) { self.randomness = Rc::new(Secret::new(C::ScalarField::zero())) }
+}
+
 /// This trait allows automatic conversion of &SigRetrievalRandomness<C> to
 /// &C::Scalar.
 impl<C: Pairing> std::ops::Deref for SigRetrievalRandomness<C> {
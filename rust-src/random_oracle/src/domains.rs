@@ -0,0 +1,70 @@
+//! Domain separation strings of the proofs that are produced and verified by
+//! the node and the wallets. Every proof starts its random oracle with
+//! [RandomOracle::domain](crate::RandomOracle::domain) or
+//! [RandomOracle::domain_in_context](crate::RandomOracle::domain_in_context)
+//! with one of these strings, so that a challenge computed for one type of
+//! proof is never valid for another.
+//!
+//! The strings are part of the proof format and must not be changed. Since the
+//! domain is added without a length prefix, a new domain must not be a prefix
+//! of an existing one or the other way around, which is checked by the tests
+//! for the strings listed in [ALL].
+
+/// Proofs of a credential deployment.
+pub const CREDENTIAL: &str = "credential";
+
+/// Proofs of the pre-identity object sent to the identity provider.
+pub const PRE_IDENTITY_PROOF: &str = "PreIdentityProof";
+
+/// Proofs of correct decryption by an anonymity revoker.
+pub const ELGAMAL_DECRYPTION_PROOF: &str = "ElGamalDecryptionProof";
+
+/// Proofs of encrypted transfers.
+pub const ENCRYPTED_TRANSFER: &str = "EncryptedTransfer";
+
+/// Proofs of transfers from the encrypted to the public balance.
+pub const SEC_TO_PUB_TRANSFER: &str = "SecToPubTransfer";
+
+/// Range proofs of attributes of a credential.
+pub const ATTRIBUTE_RANGE_PROOF: &str = "attribute_range_proof";
+
+/// Proofs of rerandomization of attribute commitments.
+pub const COMMITMENT_RERANDOMIZATION: &str = "commitment_rerandomization";
+
+/// Proofs of statements about the attributes of a credential.
+pub const ATTRIBUTE_STATEMENT_PROOF: &str = "AttributeStatementProof";
+
+/// Presentations of credentials and accounts to a verifier.
+pub const WEB3ID_PRESENTATION: &str = "Web3IdPresentation";
+
+/// All the domains above.
+pub const ALL: &[&str] = &[
+    CREDENTIAL,
+    PRE_IDENTITY_PROOF,
+    ELGAMAL_DECRYPTION_PROOF,
+    ENCRYPTED_TRANSFER,
+    SEC_TO_PUB_TRANSFER,
+    ATTRIBUTE_RANGE_PROOF,
+    COMMITMENT_RERANDOMIZATION,
+    ATTRIBUTE_STATEMENT_PROOF,
+    WEB3ID_PRESENTATION,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_domains_prefix_free() {
+        for (i, d1) in ALL.iter().enumerate() {
+            for d2 in ALL[i + 1..].iter() {
+                assert!(
+                    !d1.starts_with(d2) && !d2.starts_with(d1),
+                    "Domains {} and {} overlap.",
+                    d1,
+                    d2
+                );
+            }
+        }
+    }
+}
@@ -1,12 +1,28 @@
 //! This module provides the random oracle replacement function needed in the
 //! sigma protocols, bulletproofs, and any other constructions. It is based on
 //! SHA3.
+//!
+//! A [RandomOracle] is the transcript of a proof: the prover and the verifier
+//! both add the same data to it in the same order, and challenges are derived
+//! from its state. The transcript of a proof is
+//! - the domain of the proof, one of the strings in [domains], optionally
+//!   preceded by a [ProofContext], see [RandomOracle::domain_in_context],
+//! - followed by the messages of the proof, each added with
+//!   [RandomOracle::append_message] or [RandomOracle::extend_from] as its label
+//!   followed by its serialization, see [Serial]. Sigma protocols add their
+//!   public data and then their commit message under the label `point`.
+//!
+//! Challenges are taken from a [split](RandomOracle::split) of the transcript,
+//! so the transcript can be continued with further proofs, which then depend
+//! on all the preceding ones.
 use crypto_common::*;
 use crypto_common_derive::Serialize;
 use curve_arithmetic::Curve;
 use sha3::{Digest, Sha3_256};
 use std::io::Write;
 
+pub mod domains;
+
 /// State of the random oracle, used to incrementally build up the output.
 #[repr(transparent)]
 #[derive(Debug)]
@@ -19,10 +35,56 @@ pub struct Challenge {
     challenge: [u8; 32],
 }
 
+/// Version of the encoding of a [ProofContext] in the random oracle.
+pub const PROOF_CONTEXT_VERSION: u8 = 1;
+
+/// Label that precedes a [ProofContext] in the random oracle.
+const PROOF_CONTEXT_LABEL: &[u8] = b"ConcordiumProofContext";
+
+/// Context of a proof that is included in its Fiat-Shamir challenge. A proof
+/// that is produced in one context does not verify in any other context, so
+/// proofs cannot be replayed on another network, in another version of the
+/// protocol, or as a proof of another type of statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofContext {
+    /// Identifier of the network, e.g., the hash of the genesis block.
+    pub network_id:       Vec<u8>,
+    /// Version of the protocol the proof is produced for.
+    pub protocol_version: u32,
+    /// The type of the statement that is proved.
+    pub statement_type:   String,
+}
+
+/// Whether a [ProofContext] is included in challenges. Proofs that were
+/// produced before contexts were introduced do not include one, and must be
+/// verified with [ChallengeContext::Legacy].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeContext {
+    Legacy,
+    Versioned(ProofContext),
+}
+
 impl AsRef<[u8]> for Challenge {
     fn as_ref(&self) -> &[u8] { &self.challenge }
 }
 
+impl From<[u8; 32]> for Challenge {
+    fn from(challenge: [u8; 32]) -> Self { Challenge { challenge } }
+}
+
+/// Challenges are combined bytewise with XOR. OR-proofs use this to split the
+/// challenge of a proof between its two statements.
+impl std::ops::BitXor for Challenge {
+    type Output = Challenge;
+
+    fn bitxor(mut self, rhs: Challenge) -> Challenge {
+        for (byte, other) in self.challenge.iter_mut().zip(rhs.challenge.iter()) {
+            *byte ^= other;
+        }
+        self
+    }
+}
+
 /// This implementation allows the use of a random oracle without intermediate
 /// allocations of byte buffers.
 impl Write for RandomOracle {
@@ -70,6 +132,29 @@ impl RandomOracle {
     /// Start with the initial domain string.
     pub fn domain<B: AsRef<[u8]>>(data: B) -> Self { RandomOracle(Sha3_256::new().chain(data)) }
 
+    /// Start with the given context, followed by the domain string. With
+    /// [ChallengeContext::Legacy] this is the same as
+    /// [RandomOracle::domain].
+    ///
+    /// A versioned context is added as the label `ConcordiumProofContext`,
+    /// followed by [PROOF_CONTEXT_VERSION], and then the network identifier
+    /// and the statement type each prefixed by their length as a `u32`, with
+    /// the protocol version in between.
+    pub fn domain_in_context<B: AsRef<[u8]>>(context: &ChallengeContext, data: B) -> Self {
+        let mut ro = RandomOracle::empty();
+        if let ChallengeContext::Versioned(ctx) = context {
+            ro.add_bytes(PROOF_CONTEXT_LABEL);
+            ro.add(&PROOF_CONTEXT_VERSION);
+            ro.add(&(ctx.network_id.len() as u32));
+            ro.add_bytes(&ctx.network_id);
+            ro.add(&ctx.protocol_version);
+            ro.add(&(ctx.statement_type.len() as u32));
+            ro.add_bytes(&ctx.statement_type);
+        }
+        ro.add_bytes(data);
+        ro
+    }
+
     /// Duplicate the random oracle, creating a fresh copy of it.
     /// Further updates are independent.
     pub fn split(&self) -> Self { RandomOracle(self.0.clone()) }
@@ -147,6 +232,44 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_domain_in_context() {
+        let legacy = RandomOracle::domain_in_context(&ChallengeContext::Legacy, b"domain");
+        assert_eq!(legacy, RandomOracle::domain(b"domain"));
+
+        let context = ProofContext {
+            network_id:       b"network".to_vec(),
+            protocol_version: 1,
+            statement_type:   "statement".to_owned(),
+        };
+        let versioned = |ctx: &ProofContext| {
+            RandomOracle::domain_in_context(&ChallengeContext::Versioned(ctx.clone()), b"domain")
+        };
+        assert_ne!(versioned(&context), legacy);
+        assert_eq!(versioned(&context), versioned(&context.clone()));
+        assert_ne!(
+            versioned(&context),
+            versioned(&ProofContext {
+                network_id: b"other network".to_vec(),
+                ..context.clone()
+            })
+        );
+        assert_ne!(
+            versioned(&context),
+            versioned(&ProofContext {
+                protocol_version: 2,
+                ..context.clone()
+            })
+        );
+        assert_ne!(
+            versioned(&context),
+            versioned(&ProofContext {
+                statement_type: "other statement".to_owned(),
+                ..context.clone()
+            })
+        );
+    }
+
     #[test]
     pub fn test_split() {
         let mut v1 = vec![0u8; 50];
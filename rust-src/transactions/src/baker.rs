@@ -0,0 +1,334 @@
+//! The payload of the configure baker transaction, which both registers an
+//! account as a baker and updates the baker of an account.
+//!
+//! Each field of the payload is optional, and a bitmap at the start of the
+//! payload records which fields are present. Registering a baker requires all
+//! the fields, whereas an update only sets the given ones. When the keys of the
+//! baker are set the payload contains proofs of knowledge of the secret keys,
+//! which are bound to the account sending the transaction.
+use anyhow::{bail, ensure};
+use crypto_common::{types::Amount, *};
+use ed25519_dalek as ed25519;
+use id::types::AccountAddress;
+use pairing::bls12_381::Bls12;
+use random_oracle::RandomOracle;
+use serde::{de, Deserializer, Serializer};
+use std::io::Write;
+
+/// Maximum length in bytes of the metadata URL of a baker.
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// Commission rates are given in parts per hundred thousand.
+const FRACTION_RESOLUTION: u32 = 100_000;
+
+/// Whether a baker pool accepts delegators.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OpenStatus {
+    OpenForAll,
+    ClosedForNew,
+    ClosedForAll,
+}
+
+impl Serial for OpenStatus {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        let tag: u8 = match self {
+            OpenStatus::OpenForAll => 0,
+            OpenStatus::ClosedForNew => 1,
+            OpenStatus::ClosedForAll => 2,
+        };
+        out.put(&tag)
+    }
+}
+
+impl Deserial for OpenStatus {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get::<u8>()? {
+            0 => Ok(OpenStatus::OpenForAll),
+            1 => Ok(OpenStatus::ClosedForNew),
+            2 => Ok(OpenStatus::ClosedForAll),
+            tag => bail!("Unknown open status {}.", tag),
+        }
+    }
+}
+
+/// A commission rate, i.e., a fraction between 0 and 1 with a resolution of
+/// 1/100000. In JSON it is a decimal number, which must be exactly
+/// representable with this resolution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmountFraction {
+    parts_per_hundred_thousand: u32,
+}
+
+impl AmountFraction {
+    /// Construct a fraction from parts per hundred thousand, if it is at most
+    /// 1.
+    pub fn new(parts_per_hundred_thousand: u32) -> Option<Self> {
+        if parts_per_hundred_thousand <= FRACTION_RESOLUTION {
+            Some(AmountFraction {
+                parts_per_hundred_thousand,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn parts_per_hundred_thousand(self) -> u32 { self.parts_per_hundred_thousand }
+}
+
+impl Serial for AmountFraction {
+    fn serial<B: Buffer>(&self, out: &mut B) { out.put(&self.parts_per_hundred_thousand) }
+}
+
+impl Deserial for AmountFraction {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let parts: u32 = source.get()?;
+        match AmountFraction::new(parts) {
+            Some(fraction) => Ok(fraction),
+            None => bail!("Fraction of {}/{} exceeds 1.", parts, FRACTION_RESOLUTION),
+        }
+    }
+}
+
+impl SerdeSerialize for AmountFraction {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_f64(
+            f64::from(self.parts_per_hundred_thousand) / f64::from(FRACTION_RESOLUTION),
+        )
+    }
+}
+
+impl<'de> SerdeDeserialize<'de> for AmountFraction {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        let x = f64::deserialize(des)?;
+        if !(0.0..=1.0).contains(&x) {
+            return Err(de::Error::custom(format!(
+                "Fraction {} is not between 0 and 1.",
+                x
+            )));
+        }
+        let parts = (x * f64::from(FRACTION_RESOLUTION)).round() as u32;
+        // Both are the closest float to a multiple of the resolution, so they are
+        // equal exactly if `x` is such a multiple.
+        if f64::from(parts) / f64::from(FRACTION_RESOLUTION) != x {
+            return Err(de::Error::custom(format!(
+                "Fraction {} has more than 5 decimals.",
+                x
+            )));
+        }
+        Ok(AmountFraction {
+            parts_per_hundred_thousand: parts,
+        })
+    }
+}
+
+/// The public keys of a baker together with the proofs of knowledge of the
+/// secret keys. In JSON the keys are hex encoded, which is the format returned
+/// by `generate_baker_keys`.
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BakerKeysWithProofs {
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub election_verify_key:    ecvrf::PublicKey,
+    pub proof_election:         eddsa_ed25519::Ed25519DlogProof,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub signature_verify_key:   ed25519::PublicKey,
+    pub proof_sig:              eddsa_ed25519::Ed25519DlogProof,
+    #[serde(serialize_with = "base16_encode", deserialize_with = "base16_decode")]
+    pub aggregation_verify_key: aggregate_sig::PublicKey<Bls12>,
+    pub proof_aggregation:      aggregate_sig::Proof<Bls12>,
+}
+
+impl Serial for BakerKeysWithProofs {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.election_verify_key);
+        out.put(&self.proof_election);
+        out.put(&self.signature_verify_key);
+        out.put(&self.proof_sig);
+        out.put(&self.aggregation_verify_key);
+        out.put(&self.proof_aggregation);
+    }
+}
+
+impl Deserial for BakerKeysWithProofs {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        Ok(BakerKeysWithProofs {
+            election_verify_key:    source.get()?,
+            proof_election:         source.get()?,
+            signature_verify_key:   source.get()?,
+            proof_sig:              source.get()?,
+            aggregation_verify_key: source.get()?,
+            proof_aggregation:      source.get()?,
+        })
+    }
+}
+
+/// The challenge of the proofs of knowledge of the baker keys. The proofs are
+/// bound to the account and all the public keys, so that they cannot be reused
+/// with other keys or by other accounts.
+pub fn key_challenge(
+    sender: &AccountAddress,
+    election_verify_key: &ecvrf::PublicKey,
+    signature_verify_key: &ed25519::PublicKey,
+    aggregation_verify_key: &aggregate_sig::PublicKey<Bls12>,
+) -> Vec<u8> {
+    let mut challenge = b"configureBaker".to_vec();
+    challenge.put(sender);
+    challenge.put(election_verify_key);
+    challenge.put(signature_verify_key);
+    challenge.put(aggregation_verify_key);
+    challenge
+}
+
+impl BakerKeysWithProofs {
+    /// Check the proofs of knowledge of the secret keys for the given sender.
+    pub fn verify(&self, sender: &AccountAddress) -> bool {
+        let challenge = key_challenge(
+            sender,
+            &self.election_verify_key,
+            &self.signature_verify_key,
+            &self.aggregation_verify_key,
+        );
+        // The VRF keys are ed25519 points, with stricter checks.
+        let election_as_ed25519 =
+            match ed25519::PublicKey::from_bytes(self.election_verify_key.as_bytes()) {
+                Ok(key) => key,
+                Err(_) => return false,
+            };
+        eddsa_ed25519::verify_dlog_ed25519(
+            &mut RandomOracle::domain(&challenge),
+            &election_as_ed25519,
+            &self.proof_election,
+        ) && eddsa_ed25519::verify_dlog_ed25519(
+            &mut RandomOracle::domain(&challenge),
+            &self.signature_verify_key,
+            &self.proof_sig,
+        ) && self.aggregation_verify_key.check_proof(
+            &mut RandomOracle::domain(&challenge),
+            &self.proof_aggregation,
+        )
+    }
+}
+
+/// The payload of a configure baker transaction, without the tag. Absent
+/// fields are left unchanged by the transaction.
+#[derive(SerdeSerialize, SerdeDeserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigureBakerPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capital: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restake_earnings: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_for_delegation: Option<OpenStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys_with_proofs: Option<BakerKeysWithProofs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_fee_commission: Option<AmountFraction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baking_reward_commission: Option<AmountFraction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalization_reward_commission: Option<AmountFraction>,
+}
+
+impl ConfigureBakerPayload {
+    /// The bitmap of the fields that are present, in the order they are
+    /// serialized.
+    pub fn bitmap(&self) -> u16 {
+        [
+            self.capital.is_some(),
+            self.restake_earnings.is_some(),
+            self.open_for_delegation.is_some(),
+            self.keys_with_proofs.is_some(),
+            self.metadata_url.is_some(),
+            self.transaction_fee_commission.is_some(),
+            self.baking_reward_commission.is_some(),
+            self.finalization_reward_commission.is_some(),
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bitmap, (i, &present)| {
+            bitmap | (u16::from(present) << i)
+        })
+    }
+}
+
+impl Serial for ConfigureBakerPayload {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.bitmap());
+        if let Some(capital) = &self.capital {
+            out.put(capital);
+        }
+        if let Some(restake_earnings) = &self.restake_earnings {
+            out.put(restake_earnings);
+        }
+        if let Some(open_for_delegation) = &self.open_for_delegation {
+            out.put(open_for_delegation);
+        }
+        if let Some(keys_with_proofs) = &self.keys_with_proofs {
+            out.put(keys_with_proofs);
+        }
+        if let Some(url) = &self.metadata_url {
+            out.put(&(url.len() as u16));
+            out.write_all(url.as_bytes())
+                .expect("Writing to buffer should succeed.");
+        }
+        if let Some(commission) = &self.transaction_fee_commission {
+            out.put(commission);
+        }
+        if let Some(commission) = &self.baking_reward_commission {
+            out.put(commission);
+        }
+        if let Some(commission) = &self.finalization_reward_commission {
+            out.put(commission);
+        }
+    }
+}
+
+impl Deserial for ConfigureBakerPayload {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let bitmap: u16 = source.get()?;
+        ensure!(
+            bitmap >> 8 == 0,
+            "Unknown fields in bitmap {:#06x} of configure baker.",
+            bitmap
+        );
+        let present = |i: u16| bitmap & (1 << i) != 0;
+        let mut payload = ConfigureBakerPayload::default();
+        if present(0) {
+            payload.capital = Some(source.get()?);
+        }
+        if present(1) {
+            payload.restake_earnings = Some(source.get()?);
+        }
+        if present(2) {
+            payload.open_for_delegation = Some(source.get()?);
+        }
+        if present(3) {
+            payload.keys_with_proofs = Some(source.get()?);
+        }
+        if present(4) {
+            let len: u16 = source.get()?;
+            ensure!(
+                usize::from(len) <= MAX_URL_LENGTH,
+                "Metadata URL of {} bytes exceeds the maximum of {}.",
+                len,
+                MAX_URL_LENGTH
+            );
+            let bytes = deserial_bytes(source, usize::from(len))?;
+            payload.metadata_url = Some(String::from_utf8(bytes)?);
+        }
+        if present(5) {
+            payload.transaction_fee_commission = Some(source.get()?);
+        }
+        if present(6) {
+            payload.baking_reward_commission = Some(source.get()?);
+        }
+        if present(7) {
+            payload.finalization_reward_commission = Some(source.get()?);
+        }
+        Ok(payload)
+    }
+}
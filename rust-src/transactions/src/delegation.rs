@@ -0,0 +1,113 @@
+//! The payload of the configure delegation transaction, which both starts
+//! delegation from an account and updates it.
+//!
+//! As for configure baker, each field of the payload is optional, and a bitmap
+//! at the start of the payload records which fields are present. Starting
+//! delegation requires all the fields.
+use anyhow::{bail, ensure};
+use crypto_common::{types::Amount, *};
+
+/// Tag of [DelegationTarget::Passive] in the serialization of delegation
+/// targets.
+const PASSIVE_DELEGATION_TAG: u8 = 0;
+
+/// Tag of [DelegationTarget::Baker] in the serialization of delegation
+/// targets.
+const BAKER_DELEGATION_TAG: u8 = 1;
+
+/// Where stake is delegated to.
+#[derive(SerdeSerialize, SerdeDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "delegateType")]
+pub enum DelegationTarget {
+    /// Passive delegation, which is not tied to a specific baker.
+    Passive,
+    /// Delegation to the pool of the given baker.
+    Baker {
+        #[serde(rename = "bakerId")]
+        baker_id: u64,
+    },
+}
+
+impl Serial for DelegationTarget {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            DelegationTarget::Passive => out.put(&PASSIVE_DELEGATION_TAG),
+            DelegationTarget::Baker { baker_id } => {
+                out.put(&BAKER_DELEGATION_TAG);
+                out.put(baker_id);
+            }
+        }
+    }
+}
+
+impl Deserial for DelegationTarget {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match source.get::<u8>()? {
+            PASSIVE_DELEGATION_TAG => Ok(DelegationTarget::Passive),
+            BAKER_DELEGATION_TAG => Ok(DelegationTarget::Baker {
+                baker_id: source.get()?,
+            }),
+            tag => bail!("Unknown delegation target {}.", tag),
+        }
+    }
+}
+
+/// The payload of a configure delegation transaction, without the tag. Absent
+/// fields are left unchanged by the transaction.
+#[derive(SerdeSerialize, SerdeDeserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigureDelegationPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capital:           Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restake_earnings:  Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation_target: Option<DelegationTarget>,
+}
+
+impl ConfigureDelegationPayload {
+    /// The bitmap of the fields that are present, in the order they are
+    /// serialized.
+    pub fn bitmap(&self) -> u16 {
+        u16::from(self.capital.is_some())
+            | u16::from(self.restake_earnings.is_some()) << 1
+            | u16::from(self.delegation_target.is_some()) << 2
+    }
+}
+
+impl Serial for ConfigureDelegationPayload {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.bitmap());
+        if let Some(capital) = &self.capital {
+            out.put(capital);
+        }
+        if let Some(restake_earnings) = &self.restake_earnings {
+            out.put(restake_earnings);
+        }
+        if let Some(target) = &self.delegation_target {
+            out.put(target);
+        }
+    }
+}
+
+impl Deserial for ConfigureDelegationPayload {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let bitmap: u16 = source.get()?;
+        ensure!(
+            bitmap >> 3 == 0,
+            "Unknown fields in bitmap {:#06x} of configure delegation.",
+            bitmap
+        );
+        let mut payload = ConfigureDelegationPayload::default();
+        if bitmap & 1 != 0 {
+            payload.capital = Some(source.get()?);
+        }
+        if bitmap & 2 != 0 {
+            payload.restake_earnings = Some(source.get()?);
+        }
+        if bitmap & 4 != 0 {
+            payload.delegation_target = Some(source.get()?);
+        }
+        Ok(payload)
+    }
+}
@@ -0,0 +1,802 @@
+//! Account transactions and their serialization.
+//!
+//! An account transaction consists of the signatures, a [TransactionHeader],
+//! and the payload. The payload starts with a tag that determines its type,
+//! and the header records the size of the payload. What is signed is the
+//! SHA-256 hash of the header followed by the payload, see
+//! [transaction_sign_hash]. The serialization matches Transactions.hs in
+//! haskell-src.
+//!
+//! The payloads of the common transaction types, including contract
+//! initializations and updates and the configuration of bakers and
+//! delegation, are described by [Payload].
+//! Other payloads can be used by implementing [PayloadLike], or directly as an
+//! [EncodedPayload].
+pub mod baker;
+pub mod delegation;
+
+use baker::ConfigureBakerPayload;
+use crypto_common::{
+    derive::Serialize,
+    types::{
+        Amount, InitName, Memo, ModuleReference, ReceiveName, Timestamp, TransactionSignature,
+        TransactionTime,
+    },
+    *,
+};
+use delegation::ConfigureDelegationPayload;
+use encrypted_transfers::types::{EncryptedAmountTransferData, SecToPubAmountTransferData};
+use id::{
+    constants::ArCurve,
+    types::{AccountAddress, AccountKeys, CredentialPublicKeys},
+};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// Tags of the payloads of account transactions.
+pub mod tags {
+    pub const INIT_CONTRACT: u8 = 1;
+    pub const UPDATE: u8 = 2;
+    pub const TRANSFER: u8 = 3;
+    pub const UPDATE_CREDENTIAL_KEYS: u8 = 13;
+    pub const ENCRYPTED_AMOUNT_TRANSFER: u8 = 16;
+    pub const TRANSFER_TO_ENCRYPTED: u8 = 17;
+    pub const TRANSFER_TO_PUBLIC: u8 = 18;
+    pub const TRANSFER_WITH_SCHEDULE: u8 = 19;
+    pub const TRANSFER_WITH_MEMO: u8 = 22;
+    pub const ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO: u8 = 23;
+    pub const TRANSFER_WITH_SCHEDULE_AND_MEMO: u8 = 24;
+    pub const CONFIGURE_BAKER: u8 = 25;
+    pub const CONFIGURE_DELEGATION: u8 = 26;
+
+    /// The names of the payload types, e.g., in the JSON description of a
+    /// transaction, paired with their tags.
+    const NAMES: [(u8, &str); 13] = [
+        (INIT_CONTRACT, "initContract"),
+        (UPDATE, "update"),
+        (TRANSFER, "transfer"),
+        (UPDATE_CREDENTIAL_KEYS, "updateCredentialKeys"),
+        (ENCRYPTED_AMOUNT_TRANSFER, "encryptedAmountTransfer"),
+        (TRANSFER_TO_ENCRYPTED, "transferToEncrypted"),
+        (TRANSFER_TO_PUBLIC, "transferToPublic"),
+        (TRANSFER_WITH_SCHEDULE, "transferWithSchedule"),
+        (TRANSFER_WITH_MEMO, "transferWithMemo"),
+        (
+            ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO,
+            "encryptedAmountTransferWithMemo",
+        ),
+        (
+            TRANSFER_WITH_SCHEDULE_AND_MEMO,
+            "transferWithScheduleAndMemo",
+        ),
+        (CONFIGURE_BAKER, "configureBaker"),
+        (CONFIGURE_DELEGATION, "configureDelegation"),
+    ];
+
+    /// The name of the payload type with the given tag, if it is one of the
+    /// tags above.
+    pub fn name(tag: u8) -> Option<&'static str> {
+        NAMES.iter().find(|(t, _)| *t == tag).map(|(_, name)| *name)
+    }
+
+    /// The tag of the payload type with the given name, see [name].
+    pub fn from_name(name: &str) -> Option<u8> {
+        NAMES.iter().find(|(_, n)| *n == name).map(|(tag, _)| *tag)
+    }
+}
+
+/// The maximum number of releases of a transfer with schedule. The number is
+/// serialized in a single byte.
+pub const MAX_RELEASES: usize = 255;
+
+/// The releases of a transfer with schedule, i.e., the times at which the
+/// amounts become available to the receiver. A schedule is constructed with
+/// [Schedule::try_from], which rejects more than [MAX_RELEASES] releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    releases: Vec<(Timestamp, Amount)>,
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "The schedule has {0} releases, but at most {} are allowed.",
+    MAX_RELEASES
+)]
+/// The error of constructing a [Schedule] with too many releases.
+pub struct TooManyReleases(pub usize);
+
+impl TryFrom<Vec<(Timestamp, Amount)>> for Schedule {
+    type Error = TooManyReleases;
+
+    fn try_from(releases: Vec<(Timestamp, Amount)>) -> Result<Self, Self::Error> {
+        if releases.len() > MAX_RELEASES {
+            return Err(TooManyReleases(releases.len()));
+        }
+        Ok(Schedule { releases })
+    }
+}
+
+impl AsRef<[(Timestamp, Amount)]> for Schedule {
+    fn as_ref(&self) -> &[(Timestamp, Amount)] { &self.releases }
+}
+
+impl Serial for Schedule {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        // The length fits in a byte since there are at most MAX_RELEASES
+        // releases.
+        out.put(&(self.releases.len() as u8));
+        for (timestamp, amount) in self.releases.iter() {
+            out.put(timestamp);
+            out.put(amount);
+        }
+    }
+}
+
+impl Deserial for Schedule {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let len: u8 = source.get()?;
+        let mut releases = Vec::with_capacity(len.into());
+        for _ in 0..len {
+            releases.push((source.get()?, source.get()?));
+        }
+        Ok(Schedule { releases })
+    }
+}
+
+/// The maximum size of the parameter of a contract init or receive function.
+/// This must match the limit of the node.
+pub const MAX_PARAMETER_SIZE: usize = 1024;
+
+/// The parameter of a contract init or receive function. It is constructed
+/// with [Parameter::try_from], which rejects more than [MAX_PARAMETER_SIZE]
+/// bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Parameter {
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "The parameter is {0} bytes, but at most {} are allowed.",
+    MAX_PARAMETER_SIZE
+)]
+/// The error of constructing a [Parameter] that is too large.
+pub struct ParameterTooLarge(pub usize);
+
+impl TryFrom<Vec<u8>> for Parameter {
+    type Error = ParameterTooLarge;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        if bytes.len() > MAX_PARAMETER_SIZE {
+            return Err(ParameterTooLarge(bytes.len()));
+        }
+        Ok(Parameter { bytes })
+    }
+}
+
+impl AsRef<[u8]> for Parameter {
+    fn as_ref(&self) -> &[u8] { &self.bytes }
+}
+
+impl Serial for Parameter {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        // The length fits in 2 bytes since it is at most MAX_PARAMETER_SIZE.
+        out.put(&(self.bytes.len() as u16));
+        out.write_all(&self.bytes)
+            .expect("Writing to buffer should succeed.");
+    }
+}
+
+impl Deserial for Parameter {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let len: u16 = source.get()?;
+        let len = usize::from(len);
+        anyhow::ensure!(
+            len <= MAX_PARAMETER_SIZE,
+            "Parameter of {} bytes exceeds the maximum of {}.",
+            len,
+            MAX_PARAMETER_SIZE
+        );
+        Ok(Parameter {
+            bytes: deserial_bytes(source, len)?,
+        })
+    }
+}
+
+/// The address of a smart contract instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ContractAddress {
+    pub index:    u64,
+    pub subindex: u64,
+}
+
+/// The part of an account transaction that precedes the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TransactionHeader {
+    /// The account sending the transaction.
+    pub sender:       AccountAddress,
+    /// The nonce of the transaction, which must be the next nonce of the
+    /// sender.
+    pub nonce:        u64,
+    /// The maximum energy the transaction may use.
+    pub energy:       u64,
+    /// The size of the serialized payload in bytes.
+    pub payload_size: u32,
+    /// The time after which the transaction is rejected.
+    pub expiry:       TransactionTime,
+}
+
+impl TransactionHeader {
+    /// The header of a transaction with the given payload.
+    pub fn new(
+        sender: AccountAddress,
+        nonce: u64,
+        energy: u64,
+        expiry: TransactionTime,
+        payload: &EncodedPayload,
+    ) -> Self {
+        TransactionHeader {
+            sender,
+            nonce,
+            energy,
+            payload_size: payload.size(),
+            expiry,
+        }
+    }
+}
+
+/// A serialized payload, including the tag of its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedPayload {
+    bytes: Vec<u8>,
+}
+
+impl EncodedPayload {
+    /// Wrap payload bytes that are already serialized. The bytes are not
+    /// checked.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self { EncodedPayload { bytes } }
+
+    /// The size of the payload in bytes.
+    pub fn size(&self) -> u32 { self.bytes.len() as u32 }
+
+    /// The tag of the payload type, if the payload is not empty.
+    pub fn tag(&self) -> Option<u8> { self.bytes.first().copied() }
+
+    /// Decode the payload. This fails if the payload is not one of the types
+    /// of [Payload], or there are trailing bytes.
+    pub fn decode(&self) -> ParseResult<Payload> {
+        let mut source = std::io::Cursor::new(&self.bytes);
+        let payload = source.get()?;
+        anyhow::ensure!(
+            source.position() == self.bytes.len() as u64,
+            "The payload has trailing bytes."
+        );
+        Ok(payload)
+    }
+}
+
+impl AsRef<[u8]> for EncodedPayload {
+    fn as_ref(&self) -> &[u8] { &self.bytes }
+}
+
+/// Something that can be serialized as the payload of an account transaction.
+pub trait PayloadLike {
+    /// Write the payload, starting with its tag.
+    fn encode_to_buffer<B: Buffer>(&self, out: &mut B);
+
+    fn encode(&self) -> EncodedPayload {
+        let mut bytes = Vec::new();
+        self.encode_to_buffer(&mut bytes);
+        EncodedPayload { bytes }
+    }
+}
+
+impl PayloadLike for EncodedPayload {
+    fn encode_to_buffer<B: Buffer>(&self, out: &mut B) {
+        out.write_all(&self.bytes)
+            .expect("Writing to buffer should succeed.")
+    }
+
+    fn encode(&self) -> EncodedPayload { self.clone() }
+}
+
+/// The payloads of the account transactions created by wallets.
+#[derive(Debug, Clone)]
+pub enum Payload {
+    /// Create an instance of a contract of a deployed module.
+    InitContract {
+        amount:    Amount,
+        mod_ref:   ModuleReference,
+        init_name: InitName,
+        param:     Parameter,
+    },
+    /// Call a receive function of a contract instance.
+    Update {
+        amount:       Amount,
+        address:      ContractAddress,
+        receive_name: ReceiveName,
+        message:      Parameter,
+    },
+    /// Transfer a public amount to the given account.
+    Transfer {
+        to_address: AccountAddress,
+        amount:     Amount,
+    },
+    /// Same as [Payload::Transfer], with a memo.
+    TransferWithMemo {
+        to_address: AccountAddress,
+        memo:       Memo,
+        amount:     Amount,
+    },
+    /// Transfer amounts that are released to the receiver at the given times.
+    /// The timestamps must be strictly increasing.
+    TransferWithSchedule {
+        to_address: AccountAddress,
+        schedule:   Schedule,
+    },
+    /// Same as [Payload::TransferWithSchedule], with a memo.
+    TransferWithScheduleAndMemo {
+        to_address: AccountAddress,
+        memo:       Memo,
+        schedule:   Schedule,
+    },
+    /// Replace the keys of the credential with the given registration id.
+    UpdateCredentialKeys {
+        cred_id: ArCurve,
+        keys:    CredentialPublicKeys,
+    },
+    /// Transfer an amount from the shielded balance of the sender to the
+    /// shielded balance of the receiver.
+    EncryptedAmountTransfer {
+        to_address: AccountAddress,
+        data:       Box<EncryptedAmountTransferData<ArCurve>>,
+    },
+    /// Same as [Payload::EncryptedAmountTransfer], with a memo.
+    EncryptedAmountTransferWithMemo {
+        to_address: AccountAddress,
+        memo:       Memo,
+        data:       Box<EncryptedAmountTransferData<ArCurve>>,
+    },
+    /// Transfer an amount from the public to the shielded balance of the
+    /// sender.
+    TransferToEncrypted { amount: Amount },
+    /// Transfer an amount from the shielded to the public balance of the
+    /// sender.
+    TransferToPublic {
+        data: Box<SecToPubAmountTransferData<ArCurve>>,
+    },
+    /// Register the sender as a baker, or update its baker.
+    ConfigureBaker { data: Box<ConfigureBakerPayload> },
+    /// Start delegating stake from the sender, or update its delegation.
+    ConfigureDelegation { data: ConfigureDelegationPayload },
+}
+
+impl Payload {
+    /// The tag of the payload type.
+    pub fn tag(&self) -> u8 {
+        match self {
+            Payload::InitContract { .. } => tags::INIT_CONTRACT,
+            Payload::Update { .. } => tags::UPDATE,
+            Payload::Transfer { .. } => tags::TRANSFER,
+            Payload::TransferWithMemo { .. } => tags::TRANSFER_WITH_MEMO,
+            Payload::TransferWithSchedule { .. } => tags::TRANSFER_WITH_SCHEDULE,
+            Payload::TransferWithScheduleAndMemo { .. } => tags::TRANSFER_WITH_SCHEDULE_AND_MEMO,
+            Payload::UpdateCredentialKeys { .. } => tags::UPDATE_CREDENTIAL_KEYS,
+            Payload::EncryptedAmountTransfer { .. } => tags::ENCRYPTED_AMOUNT_TRANSFER,
+            Payload::EncryptedAmountTransferWithMemo { .. } => {
+                tags::ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO
+            }
+            Payload::TransferToEncrypted { .. } => tags::TRANSFER_TO_ENCRYPTED,
+            Payload::TransferToPublic { .. } => tags::TRANSFER_TO_PUBLIC,
+            Payload::ConfigureBaker { .. } => tags::CONFIGURE_BAKER,
+            Payload::ConfigureDelegation { .. } => tags::CONFIGURE_DELEGATION,
+        }
+    }
+}
+
+impl Serial for Payload {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.tag());
+        match self {
+            Payload::InitContract {
+                amount,
+                mod_ref,
+                init_name,
+                param,
+            } => {
+                out.put(amount);
+                out.put(mod_ref);
+                out.put(init_name);
+                out.put(param);
+            }
+            Payload::Update {
+                amount,
+                address,
+                receive_name,
+                message,
+            } => {
+                out.put(amount);
+                out.put(address);
+                out.put(receive_name);
+                out.put(message);
+            }
+            Payload::Transfer { to_address, amount } => {
+                out.put(to_address);
+                out.put(amount);
+            }
+            Payload::TransferWithMemo {
+                to_address,
+                memo,
+                amount,
+            } => {
+                out.put(to_address);
+                out.put(memo);
+                out.put(amount);
+            }
+            Payload::TransferWithSchedule {
+                to_address,
+                schedule,
+            } => {
+                out.put(to_address);
+                out.put(schedule);
+            }
+            Payload::TransferWithScheduleAndMemo {
+                to_address,
+                memo,
+                schedule,
+            } => {
+                out.put(to_address);
+                out.put(memo);
+                out.put(schedule);
+            }
+            Payload::UpdateCredentialKeys { cred_id, keys } => {
+                out.put(cred_id);
+                out.put(keys);
+            }
+            Payload::EncryptedAmountTransfer { to_address, data } => {
+                out.put(to_address);
+                out.put(data.as_ref());
+            }
+            Payload::EncryptedAmountTransferWithMemo {
+                to_address,
+                memo,
+                data,
+            } => {
+                out.put(to_address);
+                out.put(memo);
+                out.put(data.as_ref());
+            }
+            Payload::TransferToEncrypted { amount } => out.put(amount),
+            Payload::TransferToPublic { data } => out.put(data.as_ref()),
+            Payload::ConfigureBaker { data } => out.put(data.as_ref()),
+            Payload::ConfigureDelegation { data } => out.put(data),
+        }
+    }
+}
+
+impl Deserial for Payload {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let tag: u8 = source.get()?;
+        let payload = match tag {
+            tags::INIT_CONTRACT => Payload::InitContract {
+                amount:    source.get()?,
+                mod_ref:   source.get()?,
+                init_name: source.get()?,
+                param:     source.get()?,
+            },
+            tags::UPDATE => Payload::Update {
+                amount:       source.get()?,
+                address:      source.get()?,
+                receive_name: source.get()?,
+                message:      source.get()?,
+            },
+            tags::TRANSFER => Payload::Transfer {
+                to_address: source.get()?,
+                amount:     source.get()?,
+            },
+            tags::TRANSFER_WITH_MEMO => Payload::TransferWithMemo {
+                to_address: source.get()?,
+                memo:       source.get()?,
+                amount:     source.get()?,
+            },
+            tags::TRANSFER_WITH_SCHEDULE => Payload::TransferWithSchedule {
+                to_address: source.get()?,
+                schedule:   source.get()?,
+            },
+            tags::TRANSFER_WITH_SCHEDULE_AND_MEMO => Payload::TransferWithScheduleAndMemo {
+                to_address: source.get()?,
+                memo:       source.get()?,
+                schedule:   source.get()?,
+            },
+            tags::UPDATE_CREDENTIAL_KEYS => Payload::UpdateCredentialKeys {
+                cred_id: source.get()?,
+                keys:    source.get()?,
+            },
+            tags::ENCRYPTED_AMOUNT_TRANSFER => Payload::EncryptedAmountTransfer {
+                to_address: source.get()?,
+                data:       Box::new(source.get()?),
+            },
+            tags::ENCRYPTED_AMOUNT_TRANSFER_WITH_MEMO => Payload::EncryptedAmountTransferWithMemo {
+                to_address: source.get()?,
+                memo:       source.get()?,
+                data:       Box::new(source.get()?),
+            },
+            tags::TRANSFER_TO_ENCRYPTED => Payload::TransferToEncrypted {
+                amount: source.get()?,
+            },
+            tags::TRANSFER_TO_PUBLIC => Payload::TransferToPublic {
+                data: Box::new(source.get()?),
+            },
+            tags::CONFIGURE_BAKER => Payload::ConfigureBaker {
+                data: Box::new(source.get()?),
+            },
+            tags::CONFIGURE_DELEGATION => Payload::ConfigureDelegation {
+                data: source.get()?,
+            },
+            _ => anyhow::bail!("Unknown payload tag {}.", tag),
+        };
+        Ok(payload)
+    }
+}
+
+impl PayloadLike for Payload {
+    fn encode_to_buffer<B: Buffer>(&self, out: &mut B) { out.put(self) }
+}
+
+/// The header followed by the payload, i.e., the transaction without the
+/// signatures. This is what the signatures are on.
+pub fn transaction_body(header: &TransactionHeader, payload: &EncodedPayload) -> Vec<u8> {
+    let mut body = to_bytes(header);
+    body.extend_from_slice(payload.as_ref());
+    body
+}
+
+/// The hash that the keys of the sender sign, i.e., the SHA-256 hash of the
+/// [transaction_body].
+pub fn transaction_sign_hash(header: &TransactionHeader, payload: &EncodedPayload) -> [u8; 32] {
+    Sha256::digest(&transaction_body(header, payload)).into()
+}
+
+/// A signed account transaction, ready to be sent to a node.
+#[derive(Debug, Clone)]
+pub struct AccountTransaction {
+    pub signature: TransactionSignature,
+    pub header:    TransactionHeader,
+    pub payload:   EncodedPayload,
+}
+
+impl AccountTransaction {
+    /// Create a transaction with the given payload and sign it with all the
+    /// keys of the sender, regardless of the thresholds.
+    pub fn sign<P: PayloadLike + ?Sized>(
+        keys: &AccountKeys,
+        sender: AccountAddress,
+        nonce: u64,
+        energy: u64,
+        expiry: TransactionTime,
+        payload: &P,
+    ) -> Self {
+        let payload = payload.encode();
+        let header = TransactionHeader::new(sender, nonce, energy, expiry, &payload);
+        let signature = keys.sign_hash(&transaction_sign_hash(&header, &payload));
+        AccountTransaction {
+            signature,
+            header,
+            payload,
+        }
+    }
+
+    /// The hash that the signatures are on.
+    pub fn hash_to_sign(&self) -> [u8; 32] { transaction_sign_hash(&self.header, &self.payload) }
+}
+
+impl Serial for AccountTransaction {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        out.put(&self.signature);
+        out.put(&self.header);
+        self.payload.encode_to_buffer(out);
+    }
+}
+
+impl Deserial for AccountTransaction {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        let signature = source.get()?;
+        let header: TransactionHeader = source.get()?;
+        let len = usize::try_from(header.payload_size)?;
+        let payload = EncodedPayload::from_bytes(deserial_bytes(source, len)?);
+        Ok(AccountTransaction {
+            signature,
+            header,
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_common::types::{CredentialIndex, KeyIndex, KeyPair};
+    use id::types::{CredentialData, SignatureThreshold};
+    use rand::thread_rng;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_tag_names() {
+        assert_eq!(
+            tags::name(tags::TRANSFER_WITH_MEMO),
+            Some("transferWithMemo")
+        );
+        assert_eq!(
+            tags::from_name("configureBaker"),
+            Some(tags::CONFIGURE_BAKER)
+        );
+        assert_eq!(tags::name(0), None);
+        assert_eq!(tags::from_name("deployModule"), None);
+        for tag in 0..=u8::MAX {
+            if let Some(name) = tags::name(tag) {
+                assert_eq!(tags::from_name(name), Some(tag));
+            }
+        }
+    }
+
+    fn address(byte: u8) -> AccountAddress {
+        from_bytes(&mut std::io::Cursor::new([byte; 32])).expect("Addresses are 32 bytes.")
+    }
+
+    #[test]
+    fn test_transfer_roundtrip() {
+        let mut csprng = thread_rng();
+        let mut keys = BTreeMap::new();
+        keys.insert(KeyIndex(0), KeyPair::generate(&mut csprng));
+        let account_keys = AccountKeys::from((CredentialIndex { index: 0 }, CredentialData {
+            keys,
+            threshold: SignatureThreshold(1),
+        }));
+        let payload = Payload::TransferWithSchedule {
+            to_address: address(1),
+            schedule:   Schedule::try_from(vec![
+                (Timestamp { millis: 1 }, Amount::from(10)),
+                (Timestamp { millis: 2 }, Amount::from(20)),
+            ])
+            .expect("The schedule is short enough."),
+        };
+        let encoded = payload.encode();
+        assert_eq!(encoded.tag(), Some(tags::TRANSFER_WITH_SCHEDULE));
+        assert_eq!(encoded.size(), 1 + 32 + 1 + 2 * 16);
+
+        let tx = AccountTransaction::sign(
+            &account_keys,
+            address(2),
+            7,
+            1000,
+            TransactionTime { seconds: 100 },
+            &payload,
+        );
+        assert_eq!(
+            transaction_body(&tx.header, &tx.payload).len(),
+            60 + encoded.size() as usize
+        );
+        let parsed: AccountTransaction = from_bytes(&mut std::io::Cursor::new(to_bytes(&tx)))
+            .expect("The transaction should parse.");
+        assert_eq!(parsed.header, tx.header);
+        assert_eq!(parsed.payload, encoded);
+        assert_eq!(parsed.signature, tx.signature);
+        match parsed.payload.decode().expect("The payload should decode.") {
+            Payload::TransferWithSchedule { schedule, .. } => {
+                assert_eq!(schedule.as_ref().len(), 2)
+            }
+            _ => panic!("Wrong payload type."),
+        }
+    }
+
+    #[test]
+    fn test_schedule_too_long() {
+        let releases = |n: u64| {
+            (1..=n)
+                .map(|i| (Timestamp { millis: i }, Amount::from(1)))
+                .collect::<Vec<_>>()
+        };
+        let schedule =
+            Schedule::try_from(releases(MAX_RELEASES as u64)).expect("The schedule is allowed.");
+        let parsed: Schedule = from_bytes(&mut std::io::Cursor::new(to_bytes(&schedule)))
+            .expect("The schedule should parse.");
+        assert_eq!(parsed, schedule);
+        assert_eq!(
+            Schedule::try_from(releases(MAX_RELEASES as u64 + 1)),
+            Err(TooManyReleases(MAX_RELEASES + 1))
+        );
+    }
+
+    #[test]
+    fn test_contract_payloads() {
+        let update = Payload::Update {
+            amount:       Amount::from(5),
+            address:      ContractAddress {
+                index:    7,
+                subindex: 0,
+            },
+            receive_name: ReceiveName::new("counter.increment".into()).expect("The name is valid."),
+            message:      Parameter::try_from(vec![0xab, 0xcd]).expect("The parameter is small."),
+        };
+        let encoded = update.encode();
+        assert_eq!(encoded.tag(), Some(tags::UPDATE));
+        assert_eq!(encoded.size(), 1 + 8 + 16 + 2 + 17 + 2 + 2);
+        match encoded.decode().expect("The update should decode.") {
+            Payload::Update {
+                address,
+                receive_name,
+                message,
+                ..
+            } => {
+                assert_eq!(address.index, 7);
+                assert_eq!(receive_name.as_str(), "counter.increment");
+                assert_eq!(message.as_ref(), &[0xab, 0xcd]);
+            }
+            _ => panic!("Wrong payload type."),
+        }
+
+        let init = Payload::InitContract {
+            amount:    Amount::from(0),
+            mod_ref:   ModuleReference { bytes: [3u8; 32] },
+            init_name: InitName::new("init_counter".into()).expect("The name is valid."),
+            param:     Parameter::default(),
+        };
+        let mut bytes = to_bytes(&init);
+        assert!(EncodedPayload::from_bytes(bytes.clone()).decode().is_ok());
+        // Replace the name with one that is not an init name.
+        let name_start = 1 + 8 + 32 + 2;
+        bytes[name_start..name_start + 5].copy_from_slice(b"xnit_");
+        assert!(EncodedPayload::from_bytes(bytes).decode().is_err());
+
+        assert_eq!(
+            Parameter::try_from(vec![0; MAX_PARAMETER_SIZE + 1]),
+            Err(ParameterTooLarge(MAX_PARAMETER_SIZE + 1))
+        );
+    }
+
+    #[test]
+    fn test_configure_payloads() {
+        let delegation = Payload::ConfigureDelegation {
+            data: ConfigureDelegationPayload {
+                capital:           Some(Amount::from(1000)),
+                restake_earnings:  None,
+                delegation_target: Some(delegation::DelegationTarget::Passive),
+            },
+        };
+        let encoded = delegation.encode();
+        // The bitmap, the capital, and the tag of passive delegation.
+        let mut expected = vec![tags::CONFIGURE_DELEGATION, 0, 5];
+        expected.extend_from_slice(&1000u64.to_be_bytes());
+        expected.push(0);
+        assert_eq!(encoded.as_ref(), expected.as_slice());
+        match encoded.decode().expect("The delegation should decode.") {
+            Payload::ConfigureDelegation { data } => assert_eq!(data, ConfigureDelegationPayload {
+                capital:           Some(Amount::from(1000)),
+                restake_earnings:  None,
+                delegation_target: Some(delegation::DelegationTarget::Passive),
+            }),
+            _ => panic!("Wrong payload type."),
+        }
+
+        let baker = Payload::ConfigureBaker {
+            data: Box::new(ConfigureBakerPayload {
+                open_for_delegation: Some(baker::OpenStatus::ClosedForAll),
+                metadata_url: Some("https://example.com".into()),
+                ..ConfigureBakerPayload::default()
+            }),
+        };
+        let encoded = baker.encode();
+        assert_eq!(encoded.size(), 1 + 2 + 1 + 2 + 19);
+        match encoded.decode().expect("The baker should decode.") {
+            Payload::ConfigureBaker { data } => {
+                assert_eq!(data.bitmap(), 0b10100);
+                assert_eq!(data.metadata_url.as_deref(), Some("https://example.com"));
+            }
+            _ => panic!("Wrong payload type."),
+        }
+        // Bits of unknown fields are rejected.
+        let unknown = EncodedPayload::from_bytes(vec![tags::CONFIGURE_BAKER, 1, 0]);
+        assert!(unknown.decode().is_err());
+    }
+}